@@ -2,10 +2,12 @@
 
 use crate::func::native::locked_write;
 use crate::parser::{ParseResult, ParseState};
+use crate::tokenizer::{RecordedToken, Token};
 use crate::types::StringsInterner;
-use crate::{Engine, OptimizationLevel, Scope, AST};
+use crate::{Engine, OptimizationLevel, ParseErrorType, Scope, SmartString, AST};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
+use std::{cell::RefCell, rc::Rc};
 
 impl Engine {
     /// Compile a string into an [`AST`], which can be used later for evaluation.
@@ -72,6 +74,51 @@ impl Engine {
     pub fn compile_with_scope(&self, scope: &Scope, script: impl AsRef<str>) -> ParseResult<AST> {
         self.compile_scripts_with_scope(scope, &[script])
     }
+    /// Compile a string into an [`AST`] using own scope, or return `None` if the script is
+    /// incomplete.
+    ///
+    /// A script is incomplete when it ends prematurely such that more text may complete it, i.e.
+    /// within an unterminated block, arguments list, multi-line string or block comment, or in the
+    /// middle of an expression or statement. This is useful when reading a script line by line,
+    /// e.g. in a REPL.
+    ///
+    /// Other syntax errors are returned as usual.
+    ///
+    /// Under `no_position`, only multi-line strings, block comments and expressions that are
+    /// unterminated are detected, because syntax errors do not carry positions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_position"))]
+    /// # {
+    /// use rhai::{Engine, Scope};
+    ///
+    /// let engine = Engine::new();
+    /// let scope = Scope::new();
+    ///
+    /// assert!(engine.compile_with_scope_if_complete(&scope, "if x > 0 {")?.is_none());
+    /// assert!(engine.compile_with_scope_if_complete(&scope, "let s = `hello")?.is_none());
+    /// assert!(engine.compile_with_scope_if_complete(&scope, "if x > 0 { 42 }")?.is_some());
+    ///
+    /// // A syntax error before the end of the script
+    /// assert!(engine.compile_with_scope_if_complete(&scope, "if x > 0 } {").is_err());
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn compile_with_scope_if_complete(
+        &self,
+        scope: &Scope,
+        script: impl AsRef<str>,
+    ) -> ParseResult<Option<AST>> {
+        match self.compile_scripts_with_status(Some(scope), &[script], self.optimization_level) {
+            (.., true) => Ok(None),
+            (result, false) => result.map(Some),
+        }
+    }
     /// Compile a string into an [`AST`] using own scope, which can be used later for evaluation,
     /// embedding all imported modules.
     ///
@@ -218,6 +265,20 @@ impl Engine {
         scripts: impl AsRef<[S]>,
         optimization_level: OptimizationLevel,
     ) -> ParseResult<AST> {
+        self.compile_scripts_with_status(scope, scripts, optimization_level)
+            .0
+    }
+    /// Join a list of strings and compile into an [`AST`] using own scope at a specific optimization level,
+    /// also returning whether the scripts are incomplete.
+    ///
+    /// The scripts are incomplete if they end within a multi-line string or a block comment, or if
+    /// parsing fails upon reaching the end of the scripts.
+    fn compile_scripts_with_status<S: AsRef<str>>(
+        &self,
+        scope: Option<&Scope>,
+        scripts: impl AsRef<[S]>,
+        optimization_level: OptimizationLevel,
+    ) -> (ParseResult<AST>, bool) {
         let (stream, tc) = self.lex(scripts.as_ref());
 
         let mut interner;
@@ -233,13 +294,62 @@ impl Engine {
         let input = &mut stream.peekable();
         let lib = &mut <_>::default();
         let state = &mut ParseState::new(scope, interned_strings, input, tc, lib);
-        let mut _ast = self.parse(state, optimization_level)?;
+        let result = self.parse(state, optimization_level);
         #[cfg(feature = "metadata")]
-        {
+        let mut result = result;
+        #[cfg(feature = "metadata")]
+        if let Ok(ref mut ast) = result {
             let global_comments = &state.tokenizer_control.borrow().global_comments;
-            _ast.doc = global_comments.into();
+            ast.doc = global_comments.into();
         }
-        Ok(_ast)
+
+        let is_incomplete = state.tokenizer_control.borrow().is_incomplete
+            || match (&result, state.input.peek()) {
+                (Err(err), Some((Token::EOF, ..))) if err.position().is_none() => {
+                    *err.err_type() == ParseErrorType::UnexpectedEOF
+                }
+                // Error upon reaching EOF
+                (Err(err), Some((Token::EOF, ..))) => {
+                    // EOF may have already been consumed by the parser, so find its position
+                    let (mut stream, ..) = self.lex(scripts.as_ref());
+                    let eof = stream.find(|(token, ..)| *token == Token::EOF);
+                    eof.map_or(false, |(.., pos)| err.position() >= pos)
+                }
+                _ => false,
+            };
+
+        (result, is_incomplete)
+    }
+    /// Compile a script into an [`AST`] without optimization, also returning all the tokens of the
+    /// script (including comments) with their original text.
+    ///
+    /// This is used by tools that need the layout of the script, such as the formatter and the linter.
+    pub(crate) fn compile_with_tokens(
+        &self,
+        script: impl AsRef<str>,
+    ) -> ParseResult<(Vec<RecordedToken>, AST)> {
+        let scripts = [script];
+        let (mut stream, tc) = self.lex(&scripts);
+
+        let recorded_tokens = Rc::new(RefCell::new(Vec::new()));
+        stream.recorded_tokens = Some(recorded_tokens.clone());
+        stream.state.include_comments = true;
+        stream.state.last_token = Some(SmartString::new_const());
+        let mut interner = StringsInterner::new();
+
+        let input = &mut stream.peekable();
+        let lib = &mut <_>::default();
+        let mut state = ParseState::new(None, &mut interner, input, tc, lib);
+
+        let ast = self.parse(
+            &mut state,
+            #[cfg(not(feature = "no_optimize"))]
+            OptimizationLevel::None,
+            #[cfg(feature = "no_optimize")]
+            (),
+        )?;
+
+        Ok((recorded_tokens.take(), ast))
     }
     /// Compile a string containing an expression into an [`AST`],
     /// which can be used later for evaluation.
@@ -0,0 +1,502 @@
+//! Module that defines context-aware completion of scripts for the [`Engine`].
+#![cfg(feature = "metadata")]
+#![cfg(not(feature = "no_position"))]
+
+use crate::api::docs::format_params;
+use crate::api::formatting::def_type_name;
+use crate::module::FuncMetadata;
+use crate::tokenizer::{is_valid_identifier, Token};
+use crate::{Engine, FnAccess, FnNamespace, Scope, AST};
+use std::collections::BTreeMap;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Keywords offered for completion.
+const KEYWORDS: &[&str] = &[
+    "let",
+    "const",
+    "if",
+    "else",
+    "switch",
+    "do",
+    "while",
+    "until",
+    "loop",
+    "for",
+    "in",
+    "continue",
+    "break",
+    "return",
+    "throw",
+    "try",
+    "catch",
+    "true",
+    "false",
+    #[cfg(not(feature = "no_function"))]
+    "fn",
+    #[cfg(not(feature = "no_function"))]
+    "private",
+    #[cfg(not(feature = "no_function"))]
+    crate::engine::KEYWORD_THIS,
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_module"))]
+    crate::engine::KEYWORD_GLOBAL,
+    #[cfg(not(feature = "no_module"))]
+    "import",
+    #[cfg(not(feature = "no_module"))]
+    "export",
+    #[cfg(not(feature = "no_module"))]
+    "as",
+];
+
+/// Kind of a [`Completion`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum CompletionKind {
+    /// A keyword (or the first keyword of a custom syntax).
+    Keyword,
+    /// A variable in the [`Scope`].
+    Variable,
+    /// A constant in the [`Scope`] or in a module.
+    Constant,
+    /// A function.
+    Function,
+    /// A method callable on the value before the `.`.
+    Method,
+    /// A property of the value before the `.` (a getter, or a key of an object map).
+    Property,
+    /// A module.
+    Module,
+}
+
+/// _(metadata)_ A completion candidate for a position in a script.
+/// Exported under the `metadata` feature only.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Completion {
+    /// Text to insert in place of the word being completed.
+    pub text: String,
+    /// Kind of the completion.
+    pub kind: CompletionKind,
+    /// Signature of the first function overload, or type of the variable or constant, if any.
+    pub detail: Option<String>,
+}
+
+impl Completion {
+    /// Create a new [`Completion`].
+    fn new(text: &str, kind: CompletionKind, detail: Option<String>) -> Self {
+        Self {
+            text: text.into(),
+            kind,
+            detail,
+        }
+    }
+}
+
+impl Engine {
+    /// _(metadata)_ Get the completion candidates at a byte position in a script.
+    /// Exported under the `metadata` feature only.
+    ///
+    /// Returns the byte position where the word being completed starts, together with the
+    /// candidates starting with that word, sorted by text.
+    ///
+    /// * After `.`, the candidates are the methods and properties (getters) whose first parameter
+    ///   matches the type of the value before the `.` (if it is a variable in the [`Scope`] or a
+    ///   literal), plus the keys of an object map.
+    /// * After `::`, the candidates are the functions, constants and sub-modules of the static
+    ///   module at that path.
+    /// * Otherwise, the candidates are keywords, variables in the [`Scope`], functions (including
+    ///   those defined in the [`AST`]) and static modules.
+    ///
+    /// There are no candidates within strings or comments.
+    ///
+    /// Not available under `no_position`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{CompletionKind, Engine, Scope, AST};
+    ///
+    /// let engine = Engine::new();
+    /// let mut scope = Scope::new();
+    /// scope.push("text", "hello");
+    ///
+    /// let (start, candidates) = engine.complete(&scope, &AST::empty(), "text.to_up", 10);
+    ///
+    /// assert_eq!(start, 5);
+    /// assert_eq!(candidates[0].text, "to_upper");
+    /// assert_eq!(candidates[0].kind, CompletionKind::Method);
+    /// ```
+    #[must_use]
+    pub fn complete(
+        &self,
+        scope: &Scope,
+        ast: &AST,
+        script: &str,
+        pos: usize,
+    ) -> (usize, Vec<Completion>) {
+        let before = &script[..pos];
+        let mut tokens = self.tokenize_with_ranges(before);
+
+        // No completion within strings and comments
+        match tokens.last() {
+            Some((Token::LexError(..), ..)) => return (pos, Vec::new()),
+            Some((Token::Comment(..), range))
+                if range.end == pos
+                    || (before[range.clone()].starts_with("//")
+                        && !before[range.start..].contains('\n')) =>
+            {
+                return (pos, Vec::new())
+            }
+            Some((Token::StringConstant(..) | Token::CharConstant(..), range))
+                if range.end == pos =>
+            {
+                return (pos, Vec::new())
+            }
+            _ => (),
+        }
+
+        // The word being completed
+        let start = match tokens.last() {
+            Some((.., range))
+                if range.end == pos && is_valid_identifier(&before[range.clone()]) =>
+            {
+                let start = range.start;
+                tokens.pop();
+                start
+            }
+            _ => pos,
+        };
+        let prefix = &before[start..];
+
+        tokens.retain(|(token, ..)| !matches!(token, Token::Comment(..)));
+
+        let candidates = match tokens.last() {
+            Some((Token::Period, ..)) => {
+                let receiver = tokens.len().checked_sub(2).map(|i| &tokens[i].0);
+                self.member_completions(scope, ast, receiver)
+            }
+            #[cfg(not(feature = "no_object"))]
+            Some((Token::Elvis, ..)) => {
+                let receiver = tokens.len().checked_sub(2).map(|i| &tokens[i].0);
+                self.member_completions(scope, ast, receiver)
+            }
+            Some((Token::DoubleColon, ..)) => {
+                let mut path = Vec::new();
+                let mut i = tokens.len() - 1;
+
+                while i > 0 {
+                    match tokens[i - 1].0 {
+                        Token::Identifier(ref name) => path.push(name.as_str()),
+                        _ => break,
+                    }
+                    if i < 2 || tokens[i - 2].0 != Token::DoubleColon {
+                        break;
+                    }
+                    i -= 2;
+                }
+                path.reverse();
+
+                self.module_completions(&path)
+            }
+            _ => self.global_completions(scope, ast),
+        };
+
+        // Filter by the word being completed, keeping the first candidate of each text
+        let mut completions = BTreeMap::new();
+
+        for completion in candidates {
+            if completion.text.starts_with(prefix) {
+                completions
+                    .entry(completion.text.clone())
+                    .or_insert(completion);
+            }
+        }
+
+        (start, completions.into_values().collect())
+    }
+
+    /// _(metadata)_ Get the signatures of the function called at a byte position in a script.
+    /// Exported under the `metadata` feature only.
+    ///
+    /// The function is the one whose argument list (i.e. the innermost unclosed `(`) contains the
+    /// position. Only the overloads that can take the number of arguments already entered are
+    /// returned, as in `name(x: int, y: int) -> int`.
+    ///
+    /// Not available under `no_position`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, AST};
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.register_fn("area", |w: i64, h: i64| w * h);
+    ///
+    /// let signatures = engine.signature_help(&AST::empty(), "let z = area(1, ", 16);
+    ///
+    /// assert_eq!(signatures, ["area(_: int, _: int) -> int"]);
+    /// ```
+    #[must_use]
+    pub fn signature_help(&self, ast: &AST, script: &str, pos: usize) -> Vec<String> {
+        let tokens = self.tokenize_with_ranges(&script[..pos]);
+
+        // Find the innermost unclosed `(`, counting the arguments entered
+        let mut level = 0_usize;
+        let mut args = 0;
+        let mut index = None;
+
+        for (i, (token, ..)) in tokens.iter().enumerate().rev() {
+            match token {
+                Token::RightParen | Token::RightBracket | Token::RightBrace => level += 1,
+                Token::LeftParen | Token::LeftBracket | Token::LeftBrace | Token::MapStart
+                    if level > 0 =>
+                {
+                    level -= 1
+                }
+                Token::LeftParen => {
+                    index = Some(i);
+                    break;
+                }
+                // Commas within an unclosed array or object map are not argument separators
+                Token::LeftBracket | Token::MapStart => args = 0,
+                Token::LeftBrace => break,
+                Token::Comma if level == 0 => args += 1,
+                _ => (),
+            }
+        }
+
+        let (name, is_method) = match index {
+            Some(i) if i > 0 => match tokens[i - 1].0 {
+                Token::Identifier(ref name) => {
+                    let is_method = i > 1 && tokens[i - 2].0 == Token::Period;
+                    (name.as_str(), is_method)
+                }
+                _ => return Vec::new(),
+            },
+            _ => return Vec::new(),
+        };
+        let min_params = args + 1 + usize::from(is_method);
+
+        let mut signatures = Vec::new();
+
+        self.for_each_function(ast, |m| {
+            if m.name == name && m.num_params >= min_params {
+                let signature = format!("{name}{}", format_params(self, m));
+                if !signatures.contains(&signature) {
+                    signatures.push(signature);
+                }
+            }
+        });
+
+        signatures
+    }
+
+    /// Call a function on the metadata of all non-private functions callable without a
+    /// namespace, including script-defined functions in an [`AST`].
+    fn for_each_function(&self, ast: &AST, mut f: impl FnMut(&FuncMetadata)) {
+        let _ = ast;
+
+        #[cfg(not(feature = "no_function"))]
+        let script_modules = Some(&**ast.shared_lib());
+        #[cfg(feature = "no_function")]
+        let script_modules = None;
+
+        #[cfg(not(feature = "no_module"))]
+        let sub_modules = self.global_sub_modules.values().map(|m| &**m);
+        #[cfg(feature = "no_module")]
+        let sub_modules = std::iter::empty::<&crate::Module>();
+
+        for m in self
+            .global_modules
+            .iter()
+            .map(|m| &**m)
+            .chain(script_modules)
+        {
+            for (.., info) in m.iter_fn() {
+                if info.access != FnAccess::Private {
+                    f(info);
+                }
+            }
+        }
+        for m in sub_modules {
+            for (.., info) in m.iter_fn() {
+                if info.access != FnAccess::Private && info.namespace == FnNamespace::Global {
+                    f(info);
+                }
+            }
+        }
+    }
+
+    /// Completions with no context.
+    fn global_completions(&self, scope: &Scope, ast: &AST) -> Vec<Completion> {
+        let mut completions = Vec::new();
+
+        for (name, constant, value) in scope.iter() {
+            let kind = if constant {
+                CompletionKind::Constant
+            } else {
+                CompletionKind::Variable
+            };
+            let typ = self.map_type_name(value.type_name()).to_string();
+            completions.push(Completion::new(name, kind, Some(typ)));
+        }
+
+        for &keyword in KEYWORDS {
+            if !self.is_symbol_disabled(keyword) {
+                completions.push(Completion::new(keyword, CompletionKind::Keyword, None));
+            }
+        }
+        #[cfg(not(feature = "no_custom_syntax"))]
+        for keyword in self.custom_syntax.keys() {
+            if is_valid_identifier(keyword) {
+                completions.push(Completion::new(keyword, CompletionKind::Keyword, None));
+            }
+        }
+
+        self.for_each_function(ast, |m| {
+            if is_valid_identifier(&m.name) {
+                let signature = format!("{}{}", m.name, format_params(self, m));
+                completions.push(Completion::new(
+                    &m.name,
+                    CompletionKind::Function,
+                    Some(signature),
+                ));
+            }
+        });
+
+        #[cfg(not(feature = "no_module"))]
+        for name in self.global_sub_modules.keys() {
+            completions.push(Completion::new(name, CompletionKind::Module, None));
+        }
+
+        completions
+    }
+
+    /// Completions of methods and properties after `.`.
+    fn member_completions(
+        &self,
+        scope: &Scope,
+        ast: &AST,
+        receiver: Option<&Token>,
+    ) -> Vec<Completion> {
+        let mut completions = Vec::new();
+
+        // Type of the receiver, if known
+        let typ = match receiver {
+            Some(Token::Identifier(name)) => scope.get(name).map(|value| {
+                #[cfg(not(feature = "no_object"))]
+                if let Some(map) = value.read_lock::<crate::Map>() {
+                    for key in map.keys() {
+                        let typ = self.map_type_name(map[key].type_name()).to_string();
+                        completions.push(Completion::new(key, CompletionKind::Property, Some(typ)));
+                    }
+                }
+                self.map_type_name(value.type_name())
+            }),
+            Some(Token::IntegerConstant(..)) => Some("int"),
+            #[cfg(not(feature = "no_float"))]
+            Some(Token::FloatConstant(..)) => Some("float"),
+            Some(Token::StringConstant(..)) => Some("string"),
+            Some(Token::CharConstant(..)) => Some("char"),
+            Some(Token::True | Token::False) => Some("bool"),
+            _ => None,
+        };
+        let typ = typ.map(|typ| self.type_key(typ));
+
+        let matches = |m: &FuncMetadata| {
+            let param_type = m.params_info.first().and_then(|p| p.split_once(':'));
+
+            match (param_type, &typ) {
+                (Some((.., param_type)), Some(typ)) => {
+                    let param_type = self.type_key(param_type);
+                    param_type == "?" || param_type == *typ
+                }
+                _ => true,
+            }
+        };
+
+        self.for_each_function(ast, |m| {
+            if m.num_params == 0 {
+                // Script-defined functions can be called as methods with `this`
+                #[cfg(not(feature = "no_function"))]
+                if is_valid_identifier(&m.name) {
+                    let signature = format!("{}{}", m.name, format_params(self, m));
+                    completions.push(Completion::new(
+                        &m.name,
+                        CompletionKind::Method,
+                        Some(signature),
+                    ));
+                }
+            } else if let Some(name) = m.name.strip_prefix("get$") {
+                if matches(m) {
+                    let typ = def_type_name(&m.return_type, self).into_owned();
+                    completions.push(Completion::new(name, CompletionKind::Property, Some(typ)));
+                }
+            } else if is_valid_identifier(&m.name) && matches(m) {
+                let signature = format!("{}{}", m.name, format_params(self, m));
+                completions.push(Completion::new(
+                    &m.name,
+                    CompletionKind::Method,
+                    Some(signature),
+                ));
+            }
+        });
+
+        completions
+    }
+
+    /// Completions of the members of a static module after `::`.
+    fn module_completions(&self, path: &[&str]) -> Vec<Completion> {
+        let mut completions = Vec::new();
+
+        #[cfg(not(feature = "no_module"))]
+        let module = path.split_first().and_then(|(root, rest)| {
+            rest.iter()
+                .try_fold(&**self.global_sub_modules.get(*root)?, |m, name| {
+                    m.get_sub_module(name)
+                })
+        });
+        #[cfg(feature = "no_module")]
+        let module: Option<&crate::Module> = {
+            let _ = path;
+            None
+        };
+
+        if let Some(module) = module {
+            for (name, ..) in module.iter_sub_modules() {
+                completions.push(Completion::new(name, CompletionKind::Module, None));
+            }
+            for (name, value) in module.iter_var() {
+                let typ = self.map_type_name(value.type_name()).to_string();
+                completions.push(Completion::new(name, CompletionKind::Constant, Some(typ)));
+            }
+            for (.., m) in module.iter_fn() {
+                if m.access != FnAccess::Private && is_valid_identifier(&m.name) {
+                    let signature = format!("{}{}", m.name, format_params(self, m));
+                    completions.push(Completion::new(
+                        &m.name,
+                        CompletionKind::Function,
+                        Some(signature),
+                    ));
+                }
+            }
+        }
+
+        completions
+    }
+
+    /// Normalize a type name (of a value or a parameter) for matching a method to its receiver.
+    ///
+    /// Returns `?` for [`Dynamic`][crate::Dynamic].
+    fn type_key(&self, typ: &str) -> String {
+        let typ = def_type_name(typ.trim(), self).to_lowercase();
+
+        match typ.as_str() {
+            "dynamic" => "?".into(),
+            "str" | "immutablestring" => "string".into(),
+            "fnptr" => "fn".into(),
+            "instant" => "timestamp".into(),
+            _ => typ,
+        }
+    }
+}
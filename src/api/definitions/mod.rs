@@ -2,13 +2,14 @@
 #![cfg(feature = "internals")]
 #![cfg(feature = "metadata")]
 
+use crate::api::formatting::def_type_name;
 use crate::module::FuncMetadata;
 use crate::tokenizer::{is_valid_function_name, Token};
-use crate::{Engine, FnAccess, FnPtr, Module, Scope, INT};
+use crate::{Engine, FnAccess, Module, Scope};
 
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
-use std::{any::type_name, borrow::Cow, cmp::Ordering, fmt};
+use std::{borrow::Cow, cmp::Ordering, fmt};
 
 impl Engine {
     /// _(metadata, internals)_ Return [`Definitions`] that can be used to generate definition files
@@ -508,63 +509,15 @@ impl FuncMetadata {
             }
         }
 
-        write!(
-            writer,
-            ") -> {};",
-            def_type_name(&self.return_type, def.engine)
-        )?;
+        match def_type_name(&self.return_type, def.engine) {
+            ty if ty.is_empty() => writer.write_str(") -> ?;")?,
+            ty => write!(writer, ") -> {ty};")?,
+        }
 
         Ok(())
     }
 }
 
-/// We have to transform some of the types.
-///
-/// This is highly inefficient and is currently based on trial and error with the core packages.
-///
-/// It tries to flatten types, removing `&` and `&mut`, and paths, while keeping generics.
-///
-/// Associated generic types are also rewritten into regular generic type parameters.
-#[must_use]
-fn def_type_name<'a>(ty: &'a str, engine: &'a Engine) -> Cow<'a, str> {
-    let ty = engine.format_param_type(ty).replace("crate::", "");
-    let ty = ty.strip_prefix("&mut").unwrap_or(&*ty).trim();
-    let ty = ty.split("::").last().unwrap();
-
-    let ty = ty
-        .strip_prefix("RhaiResultOf<")
-        .and_then(|s| s.strip_suffix('>'))
-        .map_or(ty, str::trim);
-
-    let ty = ty
-        .replace("Iterator<Item=", "Iterator<")
-        .replace("Dynamic", "?")
-        .replace("INT", "int")
-        .replace(type_name::<INT>(), "int")
-        .replace("FLOAT", "float")
-        .replace("&str", "String")
-        .replace("ImmutableString", "String");
-
-    #[cfg(not(feature = "no_float"))]
-    let ty = ty.replace(type_name::<crate::FLOAT>(), "float");
-
-    #[cfg(not(feature = "no_index"))]
-    let ty = ty.replace(type_name::<crate::Array>(), "Array");
-
-    #[cfg(not(feature = "no_index"))]
-    let ty = ty.replace(type_name::<crate::Blob>(), "Blob");
-
-    #[cfg(not(feature = "no_object"))]
-    let ty = ty.replace(type_name::<crate::Map>(), "Map");
-
-    #[cfg(not(feature = "no_time"))]
-    let ty = ty.replace(type_name::<crate::Instant>(), "Instant");
-
-    let ty = ty.replace(type_name::<FnPtr>(), "FnPtr");
-
-    ty.into()
-}
-
 impl Scope<'_> {
     /// _(metadata, internals)_ Return definitions for all items inside the [`Scope`].
     fn write_definition(&self, writer: &mut dyn fmt::Write, def: &Definitions) -> fmt::Result {
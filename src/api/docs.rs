@@ -0,0 +1,1084 @@
+//! Module that generates API documentation in HTML and Markdown for an [`Engine`].
+#![cfg(feature = "metadata")]
+
+use crate::api::formatting::def_type_name;
+use crate::func::RhaiFunc;
+use crate::module::FuncMetadata;
+use crate::tokenizer::is_valid_function_name;
+use crate::{Engine, FnAccess, Module, AST};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{collections::BTreeMap, fmt::Write};
+
+/// Style sheet embedded into each HTML page.
+const HTML_STYLE: &str = "
+body { margin: 0; display: flex; font-family: sans-serif; line-height: 1.5; color: #222; }
+nav { flex: 0 0 16rem; min-height: 100vh; padding: 1rem; background: #f4f4f4; box-sizing: border-box; }
+nav .title { display: block; font-size: 1.25rem; font-weight: bold; margin-bottom: 1rem; }
+nav h2 { font-size: 0.9rem; text-transform: uppercase; color: #666; margin: 1rem 0 0.25rem; }
+nav ul { list-style: none; padding: 0; margin: 0; }
+main { flex: 1; max-width: 60rem; padding: 1rem 2rem; }
+a { color: #2a6ebb; text-decoration: none; }
+a:hover { text-decoration: underline; }
+h1, h2, h3 { border-bottom: 1px solid #ddd; padding-bottom: 0.25rem; }
+code { font-family: monospace; background: #f4f4f4; padding: 0 0.2rem; }
+pre { background: #f4f4f4; padding: 0.75rem; overflow-x: auto; }
+pre code { padding: 0; }
+pre.signature { border-left: 3px solid #2a6ebb; }
+ul.contents { columns: 3; }
+";
+
+impl Engine {
+    /// _(metadata)_ Return [`Documentation`] that generates API documentation in HTML or
+    /// Markdown for the [`Engine`].
+    /// Exported under the `metadata` feature only.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use rhai::Engine;
+    /// # fn main() -> std::io::Result<()> {
+    /// let engine = Engine::new();
+    ///
+    /// engine
+    ///     .documentation()
+    ///     .with_title("My API")
+    ///     .write_html_to_dir("doc")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    #[must_use]
+    pub fn documentation(&self) -> Documentation<'_> {
+        Documentation {
+            engine: self,
+            scripts: Vec::new(),
+            title: "Rhai API".into(),
+            include_standard_packages: true,
+        }
+    }
+}
+
+/// _(metadata)_ Documentation helper type to generate API documentation, as a static HTML site or
+/// Markdown pages, based on the contents of an [`Engine`].
+/// Exported under the `metadata` feature only.
+///
+/// There is one page for each group of functions:
+///
+/// * the global namespace
+/// * the standard packages (optional)
+/// * each registered global package
+/// * each static module (and sub-module)
+/// * each script module, i.e. the functions defined in an [`AST`]
+/// * each custom type, with the functions taking it as the first parameter (or `this`)
+///
+/// Functions in each page are grouped into operators, properties (getters and setters), indexers
+/// and other functions, with their signatures and doc-comments. Doc-comments are rendered as
+/// Markdown.
+///
+/// The generated pages do not refer to any external resources.
+#[derive(Debug, Clone)]
+pub struct Documentation<'e> {
+    /// The [`Engine`].
+    engine: &'e Engine,
+    /// Script modules, with their names.
+    scripts: Vec<(String, &'e AST)>,
+    /// Title of the documentation.
+    title: String,
+    /// Include standard packages (default `true`).
+    include_standard_packages: bool,
+}
+
+/// Section of a page holding a function.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+enum Section {
+    Operators,
+    Properties,
+    Indexers,
+    Functions,
+}
+
+impl Section {
+    /// Title of the section.
+    #[must_use]
+    const fn title(self) -> &'static str {
+        match self {
+            Self::Operators => "Operators",
+            Self::Properties => "Properties",
+            Self::Indexers => "Indexers",
+            Self::Functions => "Functions",
+        }
+    }
+}
+
+/// Documentation of a function.
+#[derive(Debug, Clone)]
+struct FnDoc {
+    /// Section holding the function.
+    section: Section,
+    /// Name of the function (the property for getters and setters, `[]` for indexers).
+    name: String,
+    /// Signature of the function.
+    signature: String,
+    /// Type of the first parameter (or `this`), if any.
+    first_type: String,
+    /// Text of the doc-comments.
+    doc: String,
+}
+
+/// Kind of a documentation page.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+enum PageKind {
+    Package,
+    Module,
+    Type,
+}
+
+/// A documentation page.
+#[derive(Debug, Clone)]
+struct Page {
+    /// Kind of the page.
+    kind: PageKind,
+    /// File name of the page, without extension.
+    file: String,
+    /// Label of the page (e.g. `Module`).
+    label: String,
+    /// Name of the module or type, if any.
+    name: Option<String>,
+    /// Text of the documentation of the module or type.
+    doc: String,
+    /// Sub-modules, with their page file names.
+    modules: Vec<(String, String)>,
+    /// Constants, with their types.
+    constants: Vec<(String, String)>,
+    /// Functions.
+    functions: Vec<FnDoc>,
+}
+
+impl Page {
+    /// Create a new empty [`Page`].
+    fn new(kind: PageKind, file: String, label: &str, name: Option<String>) -> Self {
+        Self {
+            kind,
+            file,
+            label: label.into(),
+            name,
+            doc: String::new(),
+            modules: Vec::new(),
+            constants: Vec::new(),
+            functions: Vec::new(),
+        }
+    }
+    /// Is the page empty?
+    fn is_empty(&self) -> bool {
+        self.doc.is_empty()
+            && self.modules.is_empty()
+            && self.constants.is_empty()
+            && self.functions.is_empty()
+    }
+    /// Title of the page in Markdown.
+    fn title_markdown(&self) -> String {
+        match self.name {
+            Some(ref name) => format!("{} `{name}`", self.label),
+            None => self.label.clone(),
+        }
+    }
+    /// Title of the page in HTML.
+    fn title_html(&self) -> String {
+        match self.name {
+            Some(ref name) => format!("{} <code>{}</code>", self.label, escape_html(name)),
+            None => escape_html(&self.label),
+        }
+    }
+}
+
+impl<'e> Documentation<'e> {
+    /// Set the title of the documentation (default `Rhai API`).
+    #[inline(always)]
+    #[must_use]
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+    /// Include standard packages (default `true`).
+    #[inline(always)]
+    #[must_use]
+    pub const fn include_standard_packages(mut self, include_standard_packages: bool) -> Self {
+        self.include_standard_packages = include_standard_packages;
+        self
+    }
+    /// Document the functions defined in an [`AST`] as a script module with a name (e.g. the
+    /// name of the script file).
+    ///
+    /// Private functions are not included.
+    #[inline(always)]
+    #[must_use]
+    pub fn with_script_module(mut self, name: impl Into<String>, ast: &'e AST) -> Self {
+        self.scripts.push((name.into(), ast));
+        self
+    }
+    /// Get the [`Engine`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn engine(&self) -> &Engine {
+        self.engine
+    }
+
+    /// Build the documentation pages (excluding the index).
+    fn pages(&self) -> Vec<Page> {
+        let mut pages = Vec::new();
+        let mut types = BTreeMap::new();
+
+        // Packages
+        let mut std_page = Page::new(PageKind::Package, "std".into(), "Standard library", None);
+
+        for (i, m) in self.engine.global_modules.iter().enumerate() {
+            if m.is_standard_lib() {
+                if self.include_standard_packages {
+                    self.fill_page(&mut std_page, m, &mut types);
+                }
+                continue;
+            }
+
+            let mut page = if i == 0 {
+                Page::new(PageKind::Package, "global".into(), "Global namespace", None)
+            } else {
+                let name = m.id().map(Into::into);
+                Page::new(PageKind::Package, format!("package-{i}"), "Package", name)
+            };
+            if page.name.is_none() && i > 0 {
+                page.label = format!("Package {i}");
+            }
+            self.fill_page(&mut page, m, &mut types);
+            pages.push(page);
+        }
+
+        pages.push(std_page);
+
+        // Static modules
+        #[cfg(not(feature = "no_module"))]
+        for (name, m) in &self.engine.global_sub_modules {
+            self.add_module_pages(&mut pages, name, m, &mut types);
+        }
+
+        // Script modules
+        for (name, ast) in &self.scripts {
+            let file = format!("script-{}", slug(name));
+            let mut page = Page::new(PageKind::Module, file, "Module", Some(name.clone()));
+            page.doc = doc_text([ast.doc()]);
+            #[cfg(not(feature = "no_function"))]
+            self.fill_page(&mut page, ast.shared_lib(), &mut types);
+            pages.push(page);
+        }
+
+        pages.retain(|page| !page.is_empty());
+
+        // Custom types, with the functions taking them as the first parameter
+        for (display_name, doc) in types {
+            let file = format!("type-{}", slug(&display_name));
+            let mut page = Page::new(PageKind::Type, file, "Type", Some(display_name.clone()));
+            page.doc = doc;
+            page.functions = pages
+                .iter()
+                .flat_map(|page| &page.functions)
+                .filter(|f| f.first_type == display_name)
+                .cloned()
+                .collect();
+            sort_functions(&mut page.functions);
+            pages.push(page);
+        }
+
+        pages
+    }
+    /// Add the pages for a static module and its sub-modules.
+    #[cfg(not(feature = "no_module"))]
+    fn add_module_pages(
+        &self,
+        pages: &mut Vec<Page>,
+        path: &str,
+        module: &Module,
+        types: &mut BTreeMap<String, String>,
+    ) {
+        let file = format!(
+            "module-{}",
+            path.split("::").map(slug).collect::<Vec<_>>().join(".")
+        );
+        let mut page = Page::new(PageKind::Module, file, "Module", Some(path.into()));
+        page.doc = doc_text([module.doc()]);
+        self.fill_page(&mut page, module, types);
+
+        let mut sub_modules: Vec<_> = module.iter_sub_modules().collect();
+        sub_modules.sort_by_key(|(name, ..)| *name);
+
+        let index = pages.len();
+        pages.push(page);
+
+        for (name, m) in sub_modules {
+            let path = format!("{path}::{name}");
+            let file = pages.len();
+            self.add_module_pages(pages, &path, m, types);
+            let file = pages[file].file.clone();
+            pages[index].modules.push((path, file));
+        }
+    }
+    /// Fill a page with the constants, functions and custom types in a [`Module`].
+    fn fill_page(&self, page: &mut Page, module: &Module, types: &mut BTreeMap<String, String>) {
+        let mut constants: Vec<_> = module
+            .iter_var()
+            .map(|(name, value)| {
+                let typ = def_type_name(value.type_name(), self.engine);
+                (name.to_string(), typ.into())
+            })
+            .collect();
+        constants.sort();
+        page.constants.extend(constants);
+
+        page.functions
+            .extend(module.iter_fn().filter_map(|(f, m)| self.fn_doc(f, m)));
+        sort_functions(&mut page.functions);
+
+        for (.., info) in module.iter_custom_types() {
+            let doc = doc_text(info.comments.iter());
+            let entry = types.entry(info.display_name.to_string()).or_default();
+            if entry.is_empty() {
+                *entry = doc;
+            }
+        }
+    }
+    /// Document a function.
+    fn fn_doc(&self, f: &RhaiFunc, m: &FuncMetadata) -> Option<FnDoc> {
+        if m.access == FnAccess::Private {
+            return None;
+        }
+
+        #[allow(unused_mut)]
+        let mut this_type = None;
+        #[allow(unused_mut)]
+        let mut doc = doc_text(m.comments.iter());
+
+        #[cfg(not(feature = "no_function"))]
+        if let Some(fn_def) = f.get_script_fn_def() {
+            if crate::parser::is_anonymous_fn(&m.name) {
+                return None;
+            }
+            doc = doc_text(fn_def.comments.iter());
+            #[cfg(not(feature = "no_object"))]
+            {
+                this_type = fn_def.this_type.as_ref().map(|t| t.to_string());
+            }
+        }
+        #[cfg(feature = "no_function")]
+        let _ = f;
+
+        let params = format_params(self.engine, m);
+
+        let (section, name, signature) = if m.name.starts_with("index$get$") {
+            (Section::Indexers, "[]".into(), format!("get []{params}"))
+        } else if m.name.starts_with("index$set$") {
+            (Section::Indexers, "[]".into(), format!("set []{params}"))
+        } else if let Some(name) = m.name.strip_prefix("get$") {
+            (
+                Section::Properties,
+                name.into(),
+                format!("get {name}{params}"),
+            )
+        } else if let Some(name) = m.name.strip_prefix("set$") {
+            (
+                Section::Properties,
+                name.into(),
+                format!("set {name}{params}"),
+            )
+        } else if self.is_operator(&m.name) {
+            (
+                Section::Operators,
+                m.name.to_string(),
+                format!("op {}{params}", m.name),
+            )
+        } else if let Some(ref typ) = this_type {
+            (
+                Section::Functions,
+                m.name.to_string(),
+                format!("fn {typ}.{}{params}", m.name),
+            )
+        } else {
+            (
+                Section::Functions,
+                m.name.to_string(),
+                format!("fn {}{params}", m.name),
+            )
+        };
+
+        let first_type = this_type.unwrap_or_else(|| {
+            m.params_info
+                .first()
+                .and_then(|param| param.split_once(':'))
+                .map_or_else(String::new, |(.., typ)| {
+                    def_type_name(typ, self.engine).into()
+                })
+        });
+
+        Some(FnDoc {
+            section,
+            name,
+            signature,
+            first_type,
+            doc,
+        })
+    }
+    /// Is a function name an operator?
+    fn is_operator(&self, name: &str) -> bool {
+        let operator = !name.contains('$')
+            && !is_valid_function_name(name)
+            && !name.starts_with(|c: char| c.is_alphanumeric() || c == '_');
+
+        #[cfg(not(feature = "no_custom_syntax"))]
+        let operator = operator || self.engine.custom_keywords.contains_key(name);
+
+        operator
+    }
+
+    /// Return the documentation as Markdown pages, as (file name, content) pairs.
+    ///
+    /// The first page is the index, named `index.md`.
+    #[must_use]
+    pub fn markdown_files(&self) -> Vec<(String, String)> {
+        let pages = self.pages();
+        let mut files = Vec::with_capacity(pages.len() + 1);
+
+        let mut index = format!("# {}\n\n", self.title);
+        let doc = self.global_doc();
+        if !doc.is_empty() {
+            index += &demote_headings(&doc);
+            index += "\n\n";
+        }
+        for (kind, title) in [
+            (PageKind::Package, "Packages"),
+            (PageKind::Module, "Modules"),
+            (PageKind::Type, "Types"),
+        ] {
+            let mut links = pages.iter().filter(|page| page.kind == kind).peekable();
+            if links.peek().is_some() {
+                writeln!(index, "## {title}\n").unwrap();
+                for page in links {
+                    writeln!(index, "* [{}]({}.md)", page.title_markdown(), page.file).unwrap();
+                }
+                index.push('\n');
+            }
+        }
+        files.push(("index.md".to_string(), index));
+
+        for page in &pages {
+            let mut md = format!(
+                "[{}](index.md)\n\n# {}\n\n",
+                self.title,
+                page.title_markdown()
+            );
+
+            if !page.doc.is_empty() {
+                md += &demote_headings(&page.doc);
+                md += "\n\n";
+            }
+            if !page.modules.is_empty() {
+                md += "## Modules\n\n";
+                for (name, file) in &page.modules {
+                    writeln!(md, "* [`{name}`]({file}.md)").unwrap();
+                }
+                md.push('\n');
+            }
+            if !page.constants.is_empty() {
+                md += "## Constants\n\n```rhai\n";
+                for (name, typ) in &page.constants {
+                    writeln!(md, "const {name}: {typ};").unwrap();
+                }
+                md += "```\n\n";
+            }
+            for (section, functions) in group_sections(&page.functions) {
+                writeln!(md, "## {}\n", section.title()).unwrap();
+
+                for (name, overloads) in group_names(functions) {
+                    writeln!(md, "### `{name}`\n").unwrap();
+
+                    for (doc, signatures) in group_docs(overloads) {
+                        md += "```rhai\n";
+                        for signature in signatures {
+                            md += signature;
+                            md.push('\n');
+                        }
+                        md += "```\n\n";
+                        if !doc.is_empty() {
+                            md += &demote_headings(doc);
+                            md += "\n\n";
+                        }
+                    }
+                }
+            }
+
+            files.push((
+                format!("{}.md", page.file),
+                md.trim_end().to_string() + "\n",
+            ));
+        }
+
+        files
+    }
+
+    /// Return the documentation as a static HTML site, as (file name, content) pairs.
+    ///
+    /// The first page is the index, named `index.html`. Each page contains a navigation bar
+    /// linking to all pages, and an embedded style sheet.
+    #[must_use]
+    pub fn html_files(&self) -> Vec<(String, String)> {
+        let pages = self.pages();
+        let mut files = Vec::with_capacity(pages.len() + 1);
+
+        let mut nav = format!(
+            "<nav>\n<a class=\"title\" href=\"index.html\">{}</a>\n",
+            escape_html(&self.title)
+        );
+        for (kind, title) in [
+            (PageKind::Package, "Packages"),
+            (PageKind::Module, "Modules"),
+            (PageKind::Type, "Types"),
+        ] {
+            let mut links = pages.iter().filter(|page| page.kind == kind).peekable();
+            if links.peek().is_some() {
+                writeln!(nav, "<h2>{title}</h2>\n<ul>").unwrap();
+                for page in links {
+                    let name = page.name.as_ref().unwrap_or(&page.label);
+                    writeln!(
+                        nav,
+                        "<li><a href=\"{}.html\">{}</a></li>",
+                        page.file,
+                        escape_html(name)
+                    )
+                    .unwrap();
+                }
+                nav += "</ul>\n";
+            }
+        }
+        nav += "</nav>\n";
+
+        let mut index = String::new();
+        let doc = self.global_doc();
+        if !doc.is_empty() {
+            index += &markdown_to_html(&doc, 1);
+        }
+        files.push((
+            "index.html".to_string(),
+            self.html_page(&self.title, &nav, &escape_html(&self.title), &index),
+        ));
+
+        for page in &pages {
+            let mut html = String::new();
+
+            if !page.doc.is_empty() {
+                html += &markdown_to_html(&page.doc, 1);
+            }
+
+            // Table of contents
+            let sections = group_sections(&page.functions);
+            if !sections.is_empty() {
+                html += "<ul class=\"contents\">\n";
+                for (section, functions) in &sections {
+                    for (name, ..) in group_names(functions) {
+                        writeln!(
+                            html,
+                            "<li><a href=\"#{}\"><code>{}</code></a></li>",
+                            anchor(*section, name),
+                            escape_html(name)
+                        )
+                        .unwrap();
+                    }
+                }
+                html += "</ul>\n";
+            }
+
+            if !page.modules.is_empty() {
+                html += "<h2>Modules</h2>\n<ul>\n";
+                for (name, file) in &page.modules {
+                    writeln!(
+                        html,
+                        "<li><a href=\"{file}.html\"><code>{}</code></a></li>",
+                        escape_html(name)
+                    )
+                    .unwrap();
+                }
+                html += "</ul>\n";
+            }
+            if !page.constants.is_empty() {
+                html += "<h2>Constants</h2>\n<pre class=\"signature\"><code>";
+                for (name, typ) in &page.constants {
+                    writeln!(html, "const {}: {};", escape_html(name), escape_html(typ)).unwrap();
+                }
+                html += "</code></pre>\n";
+            }
+            for (section, functions) in sections {
+                writeln!(html, "<h2>{}</h2>", section.title()).unwrap();
+
+                for (name, overloads) in group_names(functions) {
+                    writeln!(
+                        html,
+                        "<h3 id=\"{}\"><code>{}</code></h3>",
+                        anchor(section, name),
+                        escape_html(name)
+                    )
+                    .unwrap();
+
+                    for (doc, signatures) in group_docs(overloads) {
+                        html += "<pre class=\"signature\"><code>";
+                        for signature in signatures {
+                            html += &escape_html(signature);
+                            html.push('\n');
+                        }
+                        html += "</code></pre>\n";
+                        if !doc.is_empty() {
+                            html += &markdown_to_html(doc, 3);
+                        }
+                    }
+                }
+            }
+
+            let title = match page.name {
+                Some(ref name) => format!("{} {name}", page.label),
+                None => page.label.clone(),
+            };
+            let content = self.html_page(&title, &nav, &page.title_html(), &html);
+            files.push((format!("{}.html", page.file), content));
+        }
+
+        files
+    }
+    /// Wrap the content of a page into an HTML document.
+    fn html_page(&self, title: &str, nav: &str, heading: &str, content: &str) -> String {
+        let title = if title == self.title {
+            escape_html(title)
+        } else {
+            format!("{} - {}", escape_html(title), escape_html(&self.title))
+        };
+
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+             <title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n{nav}\
+             <main>\n<h1>{heading}</h1>\n{content}</main>\n</body>\n</html>\n"
+        )
+    }
+    /// Documentation of the global modules.
+    fn global_doc(&self) -> String {
+        doc_text(
+            self.engine
+                .global_modules
+                .iter()
+                .filter(|m| self.include_standard_packages || !m.is_standard_lib())
+                .map(|m| m.doc()),
+        )
+    }
+
+    /// Write the documentation as Markdown pages into a directory.
+    ///
+    /// This function creates the directory if needed and overrides any existing files.
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(any(not(target_family = "wasm"), not(target_os = "unknown")))]
+    #[inline]
+    pub fn write_markdown_to_dir(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        Self::write_files(path.as_ref(), self.markdown_files())
+    }
+    /// Write the documentation as a static HTML site into a directory.
+    ///
+    /// This function creates the directory if needed and overrides any existing files.
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(any(not(target_family = "wasm"), not(target_os = "unknown")))]
+    #[inline]
+    pub fn write_html_to_dir(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        Self::write_files(path.as_ref(), self.html_files())
+    }
+    /// Write files into a directory.
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(any(not(target_family = "wasm"), not(target_os = "unknown")))]
+    fn write_files(path: &std::path::Path, files: Vec<(String, String)>) -> std::io::Result<()> {
+        std::fs::create_dir_all(path)?;
+
+        for (file_name, content) in files {
+            std::fs::write(path.join(file_name), content)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Format the parameters and return type of a function, e.g. `(x: int, y: int) -> int`.
+///
+/// Types are flattened as in definition files. Parameters without types (e.g. for script-defined
+/// functions) are shown by name only.
+pub(crate) fn format_params(engine: &Engine, m: &FuncMetadata) -> String {
+    let params = m
+        .params_info
+        .iter()
+        .map(|param| {
+            let mut segment = param.splitn(2, ':');
+            let name = match segment.next().unwrap().trim() {
+                "" => "_",
+                s => s.rsplit(' ').next().unwrap(),
+            };
+            segment.next().map_or_else(
+                || name.to_string(),
+                |typ| format!("{name}: {}", def_type_name(typ, engine)),
+            )
+        })
+        .collect::<Vec<_>>();
+    let params = if params.is_empty() && m.num_params > 0 {
+        vec!["_"; m.num_params].join(", ")
+    } else {
+        params.join(", ")
+    };
+
+    match def_type_name(&m.return_type, engine) {
+        ty if ty.is_empty() || ty == "()" => format!("({params})"),
+        ty => format!("({params}) -> {ty}"),
+    }
+}
+
+/// Sort functions by section, name and signature.
+fn sort_functions(functions: &mut [FnDoc]) {
+    functions.sort_by(|a, b| {
+        (a.section, &a.name, &a.signature).cmp(&(b.section, &b.name, &b.signature))
+    });
+}
+
+/// Group sorted functions by section.
+fn group_sections(functions: &[FnDoc]) -> Vec<(Section, &[FnDoc])> {
+    let mut groups = Vec::new();
+    let mut rest = functions;
+
+    while let Some(first) = rest.first() {
+        let n = rest
+            .iter()
+            .take_while(|f| f.section == first.section)
+            .count();
+        groups.push((first.section, &rest[..n]));
+        rest = &rest[n..];
+    }
+
+    groups
+}
+
+/// Group sorted functions by name.
+fn group_names(functions: &[FnDoc]) -> Vec<(&str, &[FnDoc])> {
+    let mut groups = Vec::new();
+    let mut rest = functions;
+
+    while let Some(first) = rest.first() {
+        let n = rest.iter().take_while(|f| f.name == first.name).count();
+        groups.push((first.name.as_str(), &rest[..n]));
+        rest = &rest[n..];
+    }
+
+    groups
+}
+
+/// Group the overloads of a function by documentation, in order of first appearance.
+fn group_docs(functions: &[FnDoc]) -> Vec<(&str, Vec<&str>)> {
+    let mut groups: Vec<(&str, Vec<&str>)> = Vec::new();
+
+    for f in functions {
+        match groups.iter_mut().find(|(doc, ..)| *doc == f.doc) {
+            Some((.., signatures)) => signatures.push(&f.signature),
+            None => groups.push((&f.doc, vec![&f.signature])),
+        }
+    }
+
+    groups
+}
+
+/// Strip the leaders (`///`, `//!`, `/**`, `*` and `*/`) from the lines of a doc-comment.
+pub(crate) fn doc_comment_lines(comment: &str) -> impl Iterator<Item = &str> {
+    comment.lines().map(|line| {
+        let text = line.trim_start();
+        let text = text.strip_suffix("*/").unwrap_or(text);
+        let text = text
+            .strip_prefix("///")
+            .or_else(|| text.strip_prefix("//!"))
+            .or_else(|| text.strip_prefix("/**"))
+            .or_else(|| text.strip_prefix('*'))
+            .unwrap_or(text);
+        text.strip_prefix(' ').unwrap_or(text).trim_end()
+    })
+}
+
+/// Get the text of doc-comments, separating blocks with blank lines.
+fn doc_text<S: AsRef<str>>(comments: impl IntoIterator<Item = S>) -> String {
+    let mut text = String::new();
+
+    for comment in comments {
+        let block = doc_comment_lines(comment.as_ref())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let block = block.trim_matches('\n');
+
+        if !block.is_empty() {
+            if !text.is_empty() {
+                text += "\n\n";
+            }
+            text += block;
+        }
+    }
+
+    text
+}
+
+/// Demote the Markdown headings in a text by three levels, outside code blocks.
+fn demote_headings(text: &str) -> String {
+    let mut in_code = false;
+
+    text.lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_code = !in_code;
+            }
+            if !in_code && line.starts_with('#') {
+                format!("###{line}")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Make a name safe for file names and HTML anchors.
+fn slug(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+
+    for ch in name.chars() {
+        if ch.is_ascii_alphanumeric() || ch == '_' {
+            slug.push(ch);
+        } else {
+            write!(slug, "-{:x}", ch as u32).unwrap();
+        }
+    }
+
+    slug
+}
+
+/// HTML anchor of a function.
+fn anchor(section: Section, name: &str) -> String {
+    let prefix = match section {
+        Section::Operators => "op",
+        Section::Properties => "prop",
+        Section::Indexers => "index",
+        Section::Functions => "fn",
+    };
+    format!("{prefix}.{}", slug(name))
+}
+
+/// Escape text for use in HTML content and attribute values.
+fn escape_html(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            _ => result.push(ch),
+        }
+    }
+
+    result
+}
+
+/// Render Markdown text as HTML, demoting headings by a number of levels.
+///
+/// Only a subset of Markdown commonly used in doc-comments is supported: headings, paragraphs,
+/// fenced code blocks, bullet and numbered lists, and inline code, emphasis and links.
+fn markdown_to_html(text: &str, demote: usize) -> String {
+    let mut html = String::new();
+    let mut paragraph = Vec::new();
+    let mut list: Option<(&str, Vec<String>)> = None;
+    let mut code: Option<(&str, String)> = None;
+
+    let flush =
+        |html: &mut String, paragraph: &mut Vec<&str>, list: &mut Option<(&str, Vec<String>)>| {
+            if !paragraph.is_empty() {
+                writeln!(html, "<p>{}</p>", inline_html(&paragraph.join("\n"))).unwrap();
+                paragraph.clear();
+            }
+            if let Some((tag, items)) = list.take() {
+                writeln!(html, "<{tag}>").unwrap();
+                for item in items {
+                    writeln!(html, "<li>{}</li>", inline_html(&item)).unwrap();
+                }
+                writeln!(html, "</{tag}>").unwrap();
+            }
+        };
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+
+        // Code blocks
+        if let Some((marker, ref mut content)) = code {
+            if trimmed.starts_with(marker) {
+                writeln!(html, "{content}</code></pre>").unwrap();
+                code = None;
+            } else {
+                content.push_str(&escape_html(line));
+                content.push('\n');
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            flush(&mut html, &mut paragraph, &mut list);
+
+            let lang = trimmed[3..].split([',', ' ']).next().unwrap_or("");
+            let content = if lang.is_empty() {
+                "<pre><code>".to_string()
+            } else {
+                format!("<pre><code class=\"language-{}\">", escape_html(lang))
+            };
+            code = Some((&trimmed[..3], content));
+            continue;
+        }
+
+        // Blank lines
+        if trimmed.is_empty() {
+            flush(&mut html, &mut paragraph, &mut list);
+            continue;
+        }
+
+        // Headings
+        let level = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            flush(&mut html, &mut paragraph, &mut list);
+            let level = (level + demote).min(6);
+            let text = inline_html(trimmed.trim_start_matches('#').trim());
+            writeln!(html, "<h{level}>{text}</h{level}>").unwrap();
+            continue;
+        }
+
+        // List items
+        let bullet = ["* ", "- ", "+ "]
+            .iter()
+            .find_map(|b| trimmed.strip_prefix(b))
+            .map(|item| ("ul", item));
+        let numbered = trimmed
+            .split_once(". ")
+            .filter(|(n, ..)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+            .map(|(.., item)| ("ol", item));
+
+        if let Some((tag, item)) = bullet.or(numbered) {
+            if !paragraph.is_empty() || list.as_ref().map_or(false, |(t, ..)| *t != tag) {
+                flush(&mut html, &mut paragraph, &mut list);
+            }
+            list.get_or_insert_with(|| (tag, Vec::new()))
+                .1
+                .push(item.to_string());
+            continue;
+        }
+
+        // Continuation of a list item
+        if let Some((.., ref mut items)) = list {
+            if line.starts_with(' ') {
+                let item = items.last_mut().unwrap();
+                item.push('\n');
+                item.push_str(trimmed);
+                continue;
+            }
+            flush(&mut html, &mut paragraph, &mut list);
+        }
+
+        paragraph.push(line);
+    }
+
+    if let Some((.., content)) = code {
+        writeln!(html, "{content}</code></pre>").unwrap();
+    }
+    flush(&mut html, &mut paragraph, &mut list);
+
+    html
+}
+
+/// Render inline Markdown (code, emphasis and links) as HTML.
+fn inline_html(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(ch) = rest.chars().next() {
+        let after = &rest[ch.len_utf8()..];
+
+        // Inline code
+        if ch == '`' {
+            if let Some(end) = after.find('`') {
+                write!(html, "<code>{}</code>", escape_html(&after[..end])).unwrap();
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+
+        // Strong emphasis
+        if rest.starts_with("**") || rest.starts_with("__") {
+            let marker = &rest[..2];
+            if let Some(end) = rest[2..].find(marker).filter(|&end| end > 0) {
+                write!(html, "<strong>{}</strong>", inline_html(&rest[2..2 + end])).unwrap();
+                rest = &rest[end + 4..];
+                continue;
+            }
+        }
+
+        // Emphasis, not within words
+        if (ch == '*' || ch == '_')
+            && !html.chars().last().map_or(false, char::is_alphanumeric)
+            && after.chars().next().map_or(false, |c| !c.is_whitespace())
+        {
+            let end = after.char_indices().find(|&(i, c)| {
+                c == ch
+                    && !after[i + 1..]
+                        .chars()
+                        .next()
+                        .map_or(false, char::is_alphanumeric)
+            });
+            if let Some((end, ..)) = end {
+                write!(html, "<em>{}</em>", inline_html(&after[..end])).unwrap();
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+
+        // Links
+        if ch == '[' {
+            let link = after.find("](").and_then(|mid| {
+                let url_len = after[mid + 2..].find(')')?;
+                Some((&after[..mid], &after[mid + 2..mid + 2 + url_len]))
+            });
+            if let Some((label, url)) = link {
+                write!(
+                    html,
+                    "<a href=\"{}\">{}</a>",
+                    escape_html(url),
+                    inline_html(label)
+                )
+                .unwrap();
+                rest = &after[label.len() + url.len() + 3..];
+                continue;
+            }
+        }
+
+        // Keep HTML entities (e.g. `&ndash;`)
+        if ch == '&' {
+            let entity = after
+                .find(';')
+                .filter(|&end| end > 0 && after[..end].chars().all(|c| c.is_ascii_alphanumeric()));
+            if let Some(end) = entity {
+                html.push_str(&rest[..end + 2]);
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+
+        html.push_str(&escape_html(&rest[..ch.len_utf8()]));
+        rest = after;
+    }
+
+    html
+}
@@ -0,0 +1,272 @@
+//! Module that defines the public asynchronous evaluation API of [`Engine`].
+
+use crate::eval::{Caches, GlobalRuntimeState, Resumable, ResumeMode, Suspension};
+use crate::types::dynamic::Variant;
+use crate::{Dynamic, Engine, Position, RhaiResult, RhaiResultOf, Scope, AST, ERR};
+use std::any::{type_name, TypeId};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+impl Engine {
+    /// Evaluate a string as a script asynchronously, returning the result value or an error.
+    ///
+    /// Calls to asynchronous functions registered via
+    /// [`register_async_fn`][Engine::register_async_fn] suspend the evaluation, which is resumed
+    /// when the future of the call completes.
+    ///
+    /// Evaluation can only be suspended at calls that make up a whole statement, i.e.
+    /// `foo(x);`, `let y = foo(x);`, `y = foo(x);` or `return foo(x);`, including inside blocks,
+    /// loops, `try` statements and script-defined functions called in the same way.
+    /// Calls to asynchronous functions in other positions are rejected when the script is compiled.
+    /// A script-defined function that is itself called elsewhere (e.g. within an expression) cannot
+    /// be suspended, so any asynchronous call within it that is not immediately ready raises an error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    /// #     struct NoopWaker;
+    /// #     impl std::task::Wake for NoopWaker { fn wake(self: std::sync::Arc<Self>) {} }
+    /// #     let waker = std::task::Waker::from(std::sync::Arc::new(NoopWaker));
+    /// #     let mut future = Box::pin(future);
+    /// #     loop {
+    /// #         let cx = &mut std::task::Context::from_waker(&waker);
+    /// #         if let std::task::Poll::Ready(value) = future.as_mut().poll(cx) { return value; }
+    /// #     }
+    /// # }
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_async_fn("fetch", |x: i64| async move { Ok(x * 2) });
+    ///
+    /// let result = block_on(engine.eval_async::<i64>("let y = fetch(20); y + 2"))?;
+    ///
+    /// assert_eq!(result, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub async fn eval_async<T: Variant + Clone>(&self, script: &str) -> RhaiResultOf<T> {
+        self.eval_with_scope_async(&mut Scope::new(), script).await
+    }
+    /// Evaluate a string as a script asynchronously with own scope, returning the result value or
+    /// an error.
+    ///
+    /// See [`eval_async`][Engine::eval_async] for details.
+    #[inline]
+    pub async fn eval_with_scope_async<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope<'_>,
+        script: &str,
+    ) -> RhaiResultOf<T> {
+        let ast =
+            self.compile_scripts_with_scope_raw(Some(scope), [script], self.optimization_level)?;
+        self.eval_ast_with_scope_async(scope, &ast).await
+    }
+    /// Evaluate an [`AST`] asynchronously, returning the result value or an error.
+    ///
+    /// See [`eval_async`][Engine::eval_async] for details.
+    #[inline(always)]
+    pub async fn eval_ast_async<T: Variant + Clone>(&self, ast: &AST) -> RhaiResultOf<T> {
+        self.eval_ast_with_scope_async(&mut Scope::new(), ast).await
+    }
+    /// Evaluate an [`AST`] asynchronously with own scope, returning the result value or an error.
+    ///
+    /// See [`eval_async`][Engine::eval_async] for details.
+    #[inline]
+    pub async fn eval_ast_with_scope_async<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope<'_>,
+        ast: &AST,
+    ) -> RhaiResultOf<T> {
+        let global = &mut self.new_global_runtime_state();
+        let caches = &mut Caches::new();
+
+        self.start_resumable_eval(global, ast);
+
+        let state = &mut Resumable::default();
+        let result = self
+            .run_async(global, caches, scope, ast.statements(), state)
+            .await?;
+
+        self.finish_resumable_eval(global, caches, scope)?;
+
+        self.cast_async_result(result)
+    }
+    /// Call a script function defined in an [`AST`] asynchronously with multiple arguments.
+    ///
+    /// The statements in the [`AST`] are evaluated first, with new variables rewound afterwards.
+    ///
+    /// See [`eval_async`][Engine::eval_async] for details on calls to asynchronous functions.
+    ///
+    /// Not available under `no_function`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    /// #     struct NoopWaker;
+    /// #     impl std::task::Wake for NoopWaker { fn wake(self: std::sync::Arc<Self>) {} }
+    /// #     let waker = std::task::Waker::from(std::sync::Arc::new(NoopWaker));
+    /// #     let mut future = Box::pin(future);
+    /// #     loop {
+    /// #         let cx = &mut std::task::Context::from_waker(&waker);
+    /// #         if let std::task::Poll::Ready(value) = future.as_mut().poll(cx) { return value; }
+    /// #     }
+    /// # }
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Scope};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_async_fn("fetch", |x: i64| async move { Ok(x * 2) });
+    ///
+    /// let ast = engine.compile("fn add(x, y) { let z = fetch(y); x + z }")?;
+    ///
+    /// let mut scope = Scope::new();
+    ///
+    /// let result = block_on(engine.call_fn_async::<i64>(&mut scope, &ast, "add", (2_i64, 20_i64)))?;
+    ///
+    /// assert_eq!(result, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_function"))]
+    pub async fn call_fn_async<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope<'_>,
+        ast: &AST,
+        name: impl AsRef<str>,
+        args: impl crate::FuncArgs,
+    ) -> RhaiResultOf<T> {
+        let name = name.as_ref();
+        let mut arg_values = crate::StaticVec::new_const();
+        args.parse(&mut arg_values);
+
+        let global = &mut self.new_global_runtime_state();
+        let caches = &mut Caches::new();
+
+        self.start_resumable_eval(global, ast);
+
+        // Evaluate the statements first
+        let orig_scope_len = scope.len();
+        let state = &mut Resumable::default();
+        let result = self
+            .run_async(global, caches, scope, ast.statements(), state)
+            .await;
+        scope.rewind(orig_scope_len);
+        let _ = result?;
+
+        // Check for data race.
+        #[cfg(not(feature = "no_closure"))]
+        crate::func::ensure_no_data_race(
+            name,
+            &arg_values.iter_mut().collect::<crate::FnArgsVec<_>>(),
+            false,
+        )?;
+
+        let fn_def = ast
+            .shared_lib()
+            .get_script_fn(name, arg_values.len())
+            .ok_or_else(|| ERR::ErrorFunctionNotFound(name.into(), Position::NONE))?
+            .clone();
+
+        let state = &mut Resumable::default();
+        let source = global.source.clone();
+        let args = arg_values.into_iter().collect();
+        let pos = Position::NONE;
+
+        self.push_fn_call_frame(
+            global, caches, scope, state, fn_def, None, source, args, None, pos,
+        )?;
+
+        let result = self.run_async(global, caches, scope, &[], state).await?;
+
+        self.finish_resumable_eval(global, caches, scope)?;
+
+        self.cast_async_result(result)
+    }
+    /// Set up the global runtime state for a resumable evaluation of an [`AST`].
+    pub(crate) fn start_resumable_eval(&self, global: &mut GlobalRuntimeState, ast: &AST) {
+        global.source = ast.source_raw().cloned();
+
+        #[cfg(not(feature = "no_function"))]
+        global.lib.push(ast.shared_lib().clone());
+
+        #[cfg(not(feature = "no_module"))]
+        {
+            global.embedded_module_resolver = ast.resolver.clone();
+        }
+    }
+    /// Run statements asynchronously to completion, awaiting calls to asynchronous functions.
+    async fn run_async(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope<'_>,
+        statements: &[crate::ast::Stmt],
+        state: &mut Resumable,
+    ) -> RhaiResult {
+        let this_ptr = &mut None;
+
+        loop {
+            match self.run_resumable(
+                global,
+                caches,
+                scope,
+                this_ptr,
+                statements,
+                state,
+                ResumeMode::Async,
+            )? {
+                Suspension::Done(value) => return Ok(value),
+                Suspension::Async(future) => state.complete_async_call(future.await),
+                #[cfg(not(feature = "no_function"))]
+                Suspension::Yield(..) => unreachable!("yield outside of a generator"),
+                Suspension::Suspended => unreachable!("no suspender"),
+            }
+        }
+    }
+    /// Finish a resumable evaluation.
+    #[inline]
+    pub(crate) fn finish_resumable_eval(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+    ) -> RhaiResultOf<()> {
+        #[cfg(feature = "debugging")]
+        if self.is_debugger_registered() {
+            global.debugger_mut().status = crate::eval::DebuggerStatus::Terminate;
+            let node = &crate::ast::Stmt::Noop(Position::NONE);
+            self.run_debugger(global, caches, scope, None, node)?;
+        }
+        #[cfg(not(feature = "debugging"))]
+        let _ = (global, caches, scope);
+
+        Ok(())
+    }
+    /// Cast the result of an asynchronous evaluation to the required type.
+    fn cast_async_result<T: Variant + Clone>(&self, result: Dynamic) -> RhaiResultOf<T> {
+        // Bail out early if the return type needs no cast
+        if TypeId::of::<T>() == TypeId::of::<Dynamic>() {
+            return Ok(reify! { result => T });
+        }
+
+        result.try_cast_raw::<T>().map_err(|v| {
+            let typename = match type_name::<T>() {
+                typ if typ.contains("::") => self.map_type_name(typ),
+                typ => typ,
+            };
+
+            ERR::ErrorMismatchOutputType(
+                typename.into(),
+                self.map_type_name(v.type_name()).into(),
+                Position::NONE,
+            )
+            .into()
+        })
+    }
+}
@@ -0,0 +1,138 @@
+//! Module that defines the public API for evaluations that can be suspended and resumed.
+
+use crate::eval::{Caches, GlobalRuntimeState, Resumable, ResumeMode, Suspension};
+use crate::{Continuation, Engine, EvalOutcome, RhaiResultOf, Scope, Suspender, AST};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+impl Engine {
+    /// Evaluate an [`AST`] with own scope, suspending it when requested by a [`Suspender`].
+    ///
+    /// When the [`Suspender`] requests it, the evaluation stops before the next statement and
+    /// returns a [`Continuation`] to be resumed later by [`resume_ast`][Engine::resume_ast].
+    /// The scope then holds the variables of the root statements so far.
+    ///
+    /// Under the `serde` feature, the [`Continuation`] can be serialized, so that the evaluation
+    /// can be resumed after the host restarts.
+    ///
+    /// The evaluation cannot be suspended inside a `for` loop over anything other than a range of
+    /// integers (e.g. `1..=10` or `range(0, 100, 5)`), inside a function from a module, or while
+    /// modules are imported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, EvalOutcome, Scope, Suspender};
+    ///
+    /// let mut engine = Engine::new();
+    /// let suspender = Suspender::new();
+    ///
+    /// let s = suspender.clone();
+    /// engine.register_fn("checkpoint", move || s.suspend());
+    ///
+    /// let ast = engine.compile("
+    ///     let sum = 0;
+    ///     for x in 1..=10 {
+    ///         sum += x;
+    ///         if x % 5 == 0 { checkpoint(); }
+    ///     }
+    ///     sum
+    /// ")?;
+    ///
+    /// let mut scope = Scope::new();
+    /// let mut outcome = engine.eval_ast_suspendable(&mut scope, &ast, &suspender)?;
+    /// let mut suspensions = 0;
+    ///
+    /// while let EvalOutcome::Suspended(continuation) = outcome {
+    ///     suspensions += 1;
+    ///     outcome = engine.resume_ast(&mut scope, &ast, continuation, &suspender)?;
+    /// }
+    ///
+    /// let EvalOutcome::Done(sum) = outcome else { unreachable!() };
+    ///
+    /// assert_eq!(sum.as_int().unwrap(), 55);
+    /// assert_eq!(suspensions, 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn eval_ast_suspendable(
+        &self,
+        scope: &mut Scope,
+        ast: &AST,
+        suspender: &Suspender,
+    ) -> RhaiResultOf<EvalOutcome> {
+        let global = &mut self.new_global_runtime_state();
+        let caches = &mut Caches::new();
+
+        self.start_resumable_eval(global, ast);
+
+        let state = &mut Resumable::with_suspender(suspender.clone());
+        self.run_suspendable(global, caches, scope, ast, state)
+    }
+    /// Resume an evaluation of an [`AST`] suspended by
+    /// [`eval_ast_suspendable`][Engine::eval_ast_suspendable].
+    ///
+    /// The [`AST`] must be the same as the one originally evaluated, otherwise an error is
+    /// returned. The scope is replaced by the variables held in the [`Continuation`].
+    ///
+    /// The evaluation can be suspended again by the [`Suspender`].
+    pub fn resume_ast(
+        &self,
+        scope: &mut Scope,
+        ast: &AST,
+        continuation: Continuation,
+        suspender: &Suspender,
+    ) -> RhaiResultOf<EvalOutcome> {
+        let global = &mut self.new_global_runtime_state();
+        let caches = &mut Caches::new();
+
+        self.start_resumable_eval(global, ast);
+
+        let statements = ast.statements();
+        let state = &mut self.restore_resumable(global, caches, scope, statements, continuation)?;
+        state.suspender = Some(suspender.clone());
+
+        self.run_suspendable(global, caches, scope, ast, state)
+    }
+    /// Run an evaluation that can be suspended, up to its end or its next suspension.
+    fn run_suspendable(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        ast: &AST,
+        state: &mut Resumable,
+    ) -> RhaiResultOf<EvalOutcome> {
+        let this_ptr = &mut None;
+        let statements = ast.statements();
+
+        match self.run_resumable(
+            global,
+            caches,
+            scope,
+            this_ptr,
+            statements,
+            state,
+            ResumeMode::Suspendable,
+        )? {
+            Suspension::Done(value) => {
+                // Requests while finishing are discarded
+                if let Some(ref suspender) = state.suspender {
+                    suspender.take_request();
+                }
+
+                self.finish_resumable_eval(global, caches, scope)?;
+
+                Ok(EvalOutcome::Done(value))
+            }
+            Suspension::Suspended => {
+                let continuation = self.snapshot_resumable(global, caches, scope, state)?;
+                Ok(EvalOutcome::Suspended(continuation))
+            }
+            Suspension::Async(..) => unreachable!("asynchronous calls cannot suspend"),
+            #[cfg(not(feature = "no_function"))]
+            Suspension::Yield(..) => unreachable!("yield outside of a generator"),
+        }
+    }
+}
@@ -0,0 +1,575 @@
+//! Module that defines the script formatter of the [`Engine`].
+
+use crate::parser::ParseResult;
+use crate::tokenizer::{RecordedToken, Token};
+use crate::Engine;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Number of spaces per level of indentation.
+const INDENT_SIZE: usize = 4;
+
+/// Type of a nesting group opened by a bracket.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+enum GroupType {
+    /// `{` ... `}` statements block.
+    Block,
+    /// `{` ... `}` body of a `switch` statement.
+    Switch,
+    /// `${` ... `}` interpolation within a string.
+    Interpolation,
+    /// `(` ... `)`
+    Paren,
+    /// `[` ... `]`
+    Bracket,
+    /// `#{` ... `}`
+    Map,
+}
+
+/// A nesting group opened by a bracket.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+struct Group {
+    /// Type of the group.
+    typ: GroupType,
+    /// Indentation level of the line where the group is opened.
+    base: usize,
+    /// Is this a block or `switch` body kept on a single line?
+    inline: bool,
+    /// Is this the body of a `do` loop?
+    is_do: bool,
+}
+
+impl Group {
+    /// Are statements within this group laid out one per line?
+    #[inline(always)]
+    #[must_use]
+    const fn is_multi_line_block(&self) -> bool {
+        matches!(self.typ, GroupType::Block | GroupType::Switch) && !self.inline
+    }
+}
+
+/// Separator to insert before a token.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+enum Separator {
+    /// No separator.
+    None,
+    /// A single space.
+    Space,
+    /// A line break followed by indentation, optionally preceded by a blank line.
+    NewLine(usize, bool),
+}
+
+/// Formatter that re-emits a recorded stream of tokens in canonical form.
+struct Formatter<'a> {
+    /// Reference to the scripting [`Engine`].
+    #[cfg_attr(feature = "no_custom_syntax", allow(dead_code))]
+    engine: &'a Engine,
+    /// Recorded tokens, with original text and position.
+    tokens: &'a [RecordedToken],
+    /// Formatted output.
+    output: String,
+    /// Stack of nesting groups.
+    groups: Vec<Group>,
+    /// Indentation level of the current line.
+    line_indent: usize,
+}
+
+/// Is this character part of an operator symbol?
+#[inline(always)]
+#[must_use]
+const fn is_symbol_char(ch: char) -> bool {
+    matches!(
+        ch,
+        '+' | '-'
+            | '*'
+            | '/'
+            | '%'
+            | '='
+            | '<'
+            | '>'
+            | '!'
+            | '&'
+            | '|'
+            | '^'
+            | '~'
+            | '?'
+            | '.'
+            | ':'
+            | '#'
+            | '@'
+            | '$'
+    )
+}
+
+/// Is this character part of an identifier or number?
+#[inline(always)]
+#[must_use]
+fn is_word_char(ch: char) -> bool {
+    ch == '_' || ch.is_alphanumeric()
+}
+
+/// Can this token end an operand (so that a following `|` is a binary operator)?
+#[must_use]
+fn ends_operand(token: &Token) -> bool {
+    #[allow(clippy::enum_glob_use)]
+    use Token::*;
+
+    match token {
+        IntegerConstant(..) | CharConstant(..) | StringConstant(..) | Identifier(..)
+        | Reserved(..) | RightBrace | RightParen | RightBracket | Unit | True | False => true,
+        #[cfg(not(feature = "no_float"))]
+        FloatConstant(..) => true,
+        #[cfg(feature = "decimal")]
+        DecimalConstant(..) => true,
+        _ => false,
+    }
+}
+
+/// Does this token continue the current statement after the closing `}` of a block?
+#[must_use]
+fn continues_after_block(token: &Token, is_do: bool) -> bool {
+    #[allow(clippy::enum_glob_use)]
+    use Token::*;
+
+    match token {
+        Else | Catch | SemiColon | Comma | Period | RightParen | RightBracket | RightBrace
+        | DoubleQuestion | DoubleArrow | Equals => true,
+        While | Until => is_do,
+        #[cfg(not(feature = "no_object"))]
+        Elvis => true,
+        #[cfg(not(feature = "no_index"))]
+        QuestionBracket => true,
+        _ if token.is_op_assignment() => true,
+        _ => token.precedence().is_some(),
+    }
+}
+
+/// Should there be a space between two adjacent tokens on the same line?
+#[must_use]
+fn space_between(prev: &Token, token: &Token) -> bool {
+    #[allow(clippy::enum_glob_use)]
+    use Token::*;
+
+    match (prev, token) {
+        (_, RightParen | RightBracket | Comma | SemiColon | Period | DoubleColon | Colon) => false,
+        (LeftParen | LeftBracket | Period | DoubleColon | UnaryMinus | UnaryPlus | Bang, _) => {
+            false
+        }
+        #[cfg(not(feature = "no_object"))]
+        (Elvis, _) | (_, Elvis) => false,
+        #[cfg(not(feature = "no_index"))]
+        (QuestionBracket, _) | (_, QuestionBracket) => false,
+        // `[a, ..rest]` and `#{ .. }` in patterns
+        (Comma | MapStart, ExclusiveRange) => true,
+        (ExclusiveRange | InclusiveRange, _) | (_, ExclusiveRange | InclusiveRange) => false,
+        (Identifier(..) | Reserved(..) | RightParen | RightBracket, LeftParen | Unit) => false,
+        (
+            Identifier(..) | Reserved(..) | RightParen | RightBracket | StringConstant(..),
+            LeftBracket,
+        ) => false,
+        (LeftBrace | MapStart, RightBrace) => false,
+        _ => true,
+    }
+}
+
+impl<'a> Formatter<'a> {
+    /// Create a new [`Formatter`] over a stream of recorded tokens.
+    #[inline(always)]
+    #[must_use]
+    const fn new(engine: &'a Engine, tokens: &'a [RecordedToken]) -> Self {
+        Self {
+            engine,
+            tokens,
+            output: String::new(),
+            groups: Vec::new(),
+            line_indent: 0,
+        }
+    }
+
+    /// Is the block opened at the specified token index to be kept on a single line?
+    ///
+    /// A block stays on a single line if it is empty, or if it contains a single statement
+    /// written on a single line without any comments.
+    #[must_use]
+    fn is_inline_block(&self, index: usize) -> bool {
+        let mut level = 0_usize;
+        let start_line = self.tokens[index].2.line();
+        // Has a statement within the block ended with a `;`?
+        let mut after_semicolon = false;
+        // Has a statement within the block ended with a nested block (and is it a `do` body)?
+        let mut after_block: Option<bool> = None;
+        // Is the nested group currently open a block (and is it the body of a `do` loop)?
+        let mut nested: Option<bool> = None;
+
+        for (i, (token, text, pos)) in self.tokens.iter().enumerate().skip(index) {
+            // Another statement follows within the block
+            if level == 1 && *token != Token::RightBrace {
+                if after_semicolon
+                    || after_block.map_or(false, |is_do| !continues_after_block(token, is_do))
+                {
+                    return false;
+                }
+                after_semicolon = false;
+                after_block = None;
+            }
+
+            match token {
+                Token::LeftBrace | Token::MapStart => {
+                    if level == 1 {
+                        nested = match self.tokens[i - 1].0 {
+                            _ if *token == Token::MapStart => None,
+                            Token::InterpolatedString(..) => None,
+                            ref t => Some(*t == Token::Do),
+                        };
+                    }
+                    level += 1;
+                }
+                Token::RightBrace => {
+                    level -= 1;
+
+                    if level == 0 {
+                        return i == index + 1
+                            || (start_line.is_some() && pos.line() == start_line);
+                    }
+                    if level == 1 {
+                        after_block = nested;
+                    }
+                }
+                Token::SemiColon if level == 1 => after_semicolon = true,
+                Token::Comment(..) => return false,
+                _ => (),
+            }
+
+            if i > index && (text.contains('\n') || pos.line() != start_line) {
+                return false;
+            }
+        }
+
+        false
+    }
+
+    /// Is this token an identifier that starts a custom syntax?
+    #[inline]
+    #[must_use]
+    fn is_custom_syntax_keyword(&self, token: &Token) -> bool {
+        match token {
+            #[cfg(not(feature = "no_custom_syntax"))]
+            Token::Identifier(name) => self.engine.custom_syntax.contains_key(name.as_str()),
+            _ => false,
+        }
+    }
+
+    /// Indentation level of a new line within the current group.
+    #[inline]
+    #[must_use]
+    fn inner_indent(&self) -> usize {
+        match self.groups.last() {
+            Some(g) if g.typ == GroupType::Interpolation => self.line_indent,
+            Some(g) => g.base + 1,
+            None => 0,
+        }
+    }
+
+    /// Is the current position within an interpolated string?
+    #[inline]
+    #[must_use]
+    fn within_interpolation(&self) -> bool {
+        self.groups
+            .iter()
+            .any(|g| g.typ == GroupType::Interpolation)
+    }
+
+    /// Emit a token's text after a separator.
+    fn emit(&mut self, separator: Separator, text: &str) {
+        match separator {
+            Separator::None => (),
+            Separator::Space => self.output.push(' '),
+            Separator::NewLine(indent, blank) => {
+                self.output.truncate(self.output.trim_end().len());
+
+                if !self.output.is_empty() {
+                    self.output.push('\n');
+
+                    if blank {
+                        self.output.push('\n');
+                    }
+                }
+
+                self.line_indent = indent;
+                self.output
+                    .extend(std::iter::repeat(' ').take(indent * INDENT_SIZE));
+            }
+        }
+
+        self.output.push_str(text);
+    }
+
+    /// Format the tokens stream.
+    #[must_use]
+    fn format(mut self) -> String {
+        // Last non-comment token
+        let mut prev: Option<&Token> = None;
+        // Group closed by the last non-comment token, if any
+        let mut closed: Option<Group> = None;
+        // Line number where the last token ends
+        let mut last_line: Option<usize> = None;
+        // Must the next token start on a new line (e.g. after a line comment)?
+        let mut force_new_line = false;
+        // Nesting level of a pending `switch` body
+        let mut pending_switch: Option<usize> = None;
+        // Is the tokenizer within the parameters list of a closure?
+        let mut within_closure_params = false;
+        // Was the last token the closing pipe of a closure's parameters list?
+        let mut closure_params_closed = false;
+        // Was the last token the opening pipe of a closure's parameters list?
+        let mut closure_params_opened = false;
+        // Was the last item a comment?
+        let mut after_comment = false;
+
+        let tokens = self.tokens;
+
+        for (index, (token, text, pos)) in tokens.iter().enumerate() {
+            let line = pos.line();
+            let gap = match (last_line, line) {
+                (Some(last), Some(line)) if line > last => line - last,
+                _ => 0,
+            };
+            last_line = line.map(|n| n + text.matches('\n').count());
+
+            // Comments
+            if let Token::Comment(..) = token {
+                let separator = if self.output.is_empty() {
+                    Separator::None
+                } else if gap > 0 || force_new_line {
+                    Separator::NewLine(self.inner_indent(), gap > 1)
+                } else {
+                    Separator::Space
+                };
+
+                self.emit(separator, text);
+                force_new_line = text.starts_with("//");
+                after_comment = true;
+                continue;
+            }
+
+            let top = self.groups.last().copied();
+            let opened = prev.map_or(false, |t| {
+                matches!(
+                    t,
+                    Token::LeftBrace | Token::MapStart | Token::LeftParen | Token::LeftBracket
+                )
+            });
+
+            // Closing brackets
+            let closing = match token {
+                Token::RightBrace | Token::RightParen | Token::RightBracket => self.groups.pop(),
+                _ => None,
+            };
+
+            // Indentation of the next line, which may continue a statement broken across lines
+            let at_boundary = opened
+                || matches!(prev, None | Some(Token::SemiColon | Token::Comma))
+                || closed.map_or(false, |g| {
+                    matches!(g.typ, GroupType::Block | GroupType::Switch)
+                });
+            let continued_indent = match top {
+                Some(g) if !at_boundary && g.is_multi_line_block() => g.base + 2,
+                None if !at_boundary => 1,
+                _ => self.inner_indent(),
+            };
+
+            #[cfg(not(feature = "no_custom_syntax"))]
+            let is_custom = token.is_custom();
+            #[cfg(feature = "no_custom_syntax")]
+            let is_custom = false;
+
+            // Does the previous token end a statement (or a `switch` case)?
+            let ends_statement = (matches!(prev, Some(Token::SemiColon))
+                && top.map_or(true, |g| g.is_multi_line_block()))
+                || (matches!(prev, Some(Token::Comma))
+                    && top.map_or(false, |g| g.typ == GroupType::Switch && !g.inline))
+                || closed.map_or(false, |g| {
+                    matches!(g.typ, GroupType::Block | GroupType::Switch)
+                        && top.map_or(true, |g| g.is_multi_line_block())
+                        && !continues_after_block(token, g.is_do)
+                        && (gap > 0 || !is_custom)
+                });
+
+            let separator = if self.output.is_empty() {
+                Separator::None
+            } else if let Some(g) = closing {
+                match g.typ {
+                    _ if force_new_line => Separator::NewLine(g.base, false),
+                    GroupType::Interpolation => Separator::None,
+                    _ if g.is_multi_line_block() => Separator::NewLine(g.base, false),
+                    GroupType::Paren | GroupType::Bracket | GroupType::Map if gap > 0 => {
+                        Separator::NewLine(g.base, false)
+                    }
+                    GroupType::Paren | GroupType::Bracket => Separator::None,
+                    _ if opened => Separator::None,
+                    _ => Separator::Space,
+                }
+            } else if force_new_line {
+                Separator::NewLine(continued_indent, gap > 1 && !opened)
+            } else if after_comment && gap == 0 {
+                Separator::Space
+            } else if opened && top.map_or(false, |g| g.is_multi_line_block()) {
+                Separator::NewLine(self.inner_indent(), false)
+            } else if opened && top.map_or(false, |g| g.typ == GroupType::Interpolation) {
+                Separator::None
+            } else if opened && gap > 0 && !self.within_interpolation() {
+                Separator::NewLine(self.inner_indent(), false)
+            } else if ends_statement {
+                Separator::NewLine(self.inner_indent(), gap > 1)
+            } else if closed.map_or(false, |g| g.typ == GroupType::Interpolation)
+                || matches!(prev, Some(Token::InterpolatedString(..)))
+            {
+                Separator::None
+            } else if gap > 0
+                && !self.within_interpolation()
+                && !closed.map_or(false, |g| {
+                    matches!(g.typ, GroupType::Block | GroupType::Switch)
+                })
+                && !matches!(token, Token::LeftBrace)
+            {
+                Separator::NewLine(continued_indent, gap > 1 && at_boundary)
+            } else if closure_params_opened
+                || (within_closure_params && matches!(token, Token::Pipe))
+            {
+                Separator::None
+            } else if closure_params_closed
+                || prev.map_or(false, |t| {
+                    self.is_custom_syntax_keyword(t) || space_between(t, token)
+                })
+            {
+                Separator::Space
+            } else {
+                Separator::None
+            };
+
+            // Never join two tokens that would be lexed differently when put together
+            let separator = match separator {
+                Separator::None => match (self.output.chars().last(), text.chars().next()) {
+                    (Some(a), Some(b))
+                        if (is_word_char(a) && is_word_char(b))
+                            || (is_symbol_char(a) && is_symbol_char(b)) =>
+                    {
+                        Separator::Space
+                    }
+                    _ => Separator::None,
+                },
+                sep => sep,
+            };
+
+            self.emit(separator, text);
+
+            // Track closures
+            closure_params_opened = false;
+            closure_params_closed = false;
+
+            match token {
+                Token::Pipe if within_closure_params => {
+                    within_closure_params = false;
+                    closure_params_closed = true;
+                }
+                Token::Pipe if !prev.map_or(false, ends_operand) => {
+                    within_closure_params = true;
+                    closure_params_opened = true;
+                }
+                Token::Or if !prev.map_or(false, ends_operand) => closure_params_closed = true,
+                _ => (),
+            }
+
+            // Open groups
+            let base = self.line_indent;
+            let group = |typ, inline| Group {
+                typ,
+                base,
+                inline,
+                is_do: false,
+            };
+
+            match token {
+                Token::LeftBrace if matches!(prev, Some(Token::InterpolatedString(..))) => {
+                    let g = group(GroupType::Interpolation, true);
+                    self.groups.push(g);
+                }
+                Token::LeftBrace if pending_switch == Some(self.groups.len()) => {
+                    pending_switch = None;
+                    let g = group(GroupType::Switch, self.is_inline_block(index));
+                    self.groups.push(g);
+                }
+                Token::LeftBrace => {
+                    let mut g = group(GroupType::Block, self.is_inline_block(index));
+                    g.is_do = matches!(prev, Some(Token::Do));
+                    self.groups.push(g);
+                }
+                Token::MapStart => {
+                    let g = group(GroupType::Map, true);
+                    self.groups.push(g);
+                }
+                Token::LeftParen => {
+                    let g = group(GroupType::Paren, true);
+                    self.groups.push(g);
+                }
+                Token::LeftBracket => {
+                    let g = group(GroupType::Bracket, true);
+                    self.groups.push(g);
+                }
+                #[cfg(not(feature = "no_index"))]
+                Token::QuestionBracket => {
+                    let g = group(GroupType::Bracket, true);
+                    self.groups.push(g);
+                }
+                Token::Switch => pending_switch = Some(self.groups.len()),
+                _ => (),
+            }
+
+            prev = Some(token);
+            closed = closing;
+            force_new_line = false;
+            after_comment = false;
+        }
+
+        self.output.truncate(self.output.trim_end().len());
+
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+
+        self.output
+    }
+}
+
+impl Engine {
+    /// Format a script in canonical form.
+    ///
+    /// Indentation, spacing and line breaks are normalized, while all comments (including
+    /// doc-comments) are kept in place.
+    ///
+    /// The script is fully parsed, so custom syntax registered on this [`Engine`] and string
+    /// interpolations are supported.
+    /// A syntax error in the script is returned as a [`ParseError`][crate::ParseError].
+    ///
+    /// The output script is semantically identical to the input script.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), rhai::ParseError> {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let script = engine.format_script("let x=40+2;// the answer\nif x>0{print(x)}")?;
+    ///
+    /// assert_eq!(script, "let x = 40 + 2; // the answer\nif x > 0 { print(x) }\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn format_script(&self, script: impl AsRef<str>) -> ParseResult<String> {
+        let (tokens, ..) = self.compile_with_tokens(script)?;
+
+        Ok(Formatter::new(self, &tokens).format())
+    }
+}
@@ -43,6 +43,10 @@ pub fn map_std_type_name(name: &str, shorthands: bool) -> &str {
     if name == type_name::<FnPtr>() || name == "FnPtr" {
         return if shorthands { "Fn" } else { "FnPtr" };
     }
+    #[cfg(not(feature = "no_function"))]
+    if name == type_name::<crate::Generator>() || name == "Generator" {
+        return if shorthands { "generator" } else { "Generator" };
+    }
     #[cfg(not(feature = "no_index"))]
     if name == type_name::<crate::Array>() || name == "Array" {
         return if shorthands { "array" } else { "Array" };
@@ -196,6 +200,54 @@ pub fn format_param_type_for_display(typ: &str, is_return_type: bool) -> std::bo
     }
 }
 
+/// We have to transform some of the types.
+///
+/// This is highly inefficient and is currently based on trial and error with the core packages.
+///
+/// It tries to flatten types, removing `&` and `&mut`, and paths, while keeping generics.
+///
+/// Associated generic types are also rewritten into regular generic type parameters.
+#[cfg(feature = "metadata")]
+#[must_use]
+pub(crate) fn def_type_name<'a>(ty: &'a str, engine: &'a Engine) -> std::borrow::Cow<'a, str> {
+    let ty = engine.format_param_type(ty).replace("crate::", "");
+    let ty = ty.strip_prefix("&mut").unwrap_or(&*ty).trim();
+    let ty = ty.split("::").last().unwrap();
+
+    let ty = ty
+        .strip_prefix("RhaiResultOf<")
+        .and_then(|s| s.strip_suffix('>'))
+        .map_or(ty, str::trim);
+
+    let ty = ty
+        .replace("Iterator<Item=", "Iterator<")
+        .replace("Dynamic", "?")
+        .replace("INT", "int")
+        .replace(type_name::<crate::INT>(), "int")
+        .replace("FLOAT", "float")
+        .replace("&str", "String")
+        .replace("ImmutableString", "String");
+
+    #[cfg(not(feature = "no_float"))]
+    let ty = ty.replace(type_name::<crate::FLOAT>(), "float");
+
+    #[cfg(not(feature = "no_index"))]
+    let ty = ty.replace(type_name::<crate::Array>(), "Array");
+
+    #[cfg(not(feature = "no_index"))]
+    let ty = ty.replace(type_name::<crate::Blob>(), "Blob");
+
+    #[cfg(not(feature = "no_object"))]
+    let ty = ty.replace(type_name::<crate::Map>(), "Map");
+
+    #[cfg(not(feature = "no_time"))]
+    let ty = ty.replace(type_name::<crate::Instant>(), "Instant");
+
+    let ty = ty.replace(type_name::<FnPtr>(), "FnPtr");
+
+    ty.into()
+}
+
 impl Engine {
     /// Pretty-print a type name.
     ///
@@ -0,0 +1,205 @@
+//! Module that defines token-based syntax highlighting of scripts for the [`Engine`].
+#![cfg(not(feature = "no_position"))]
+
+use crate::tokenizer::Token;
+use crate::{Engine, Position};
+use std::ops::Range;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Kind of a highlighted span in a script.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum HighlightKind {
+    /// A keyword (including `true`, `false` and reserved keywords).
+    Keyword,
+    /// A custom keyword or symbol, or the first keyword of a custom syntax.
+    Custom,
+    /// A variable, property or other identifier.
+    Identifier,
+    /// The name of a function in a function call or definition.
+    Function,
+    /// A number literal.
+    Number,
+    /// A string or character literal (including interpolated strings).
+    String,
+    /// A comment.
+    Comment,
+    /// An operator or punctuation.
+    Symbol,
+    /// Text that cannot be tokenized (e.g. an unterminated string).
+    Error,
+}
+
+/// A highlighted span in a script.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct HighlightSpan {
+    /// Kind of the span.
+    pub kind: HighlightKind,
+    /// Byte range of the span within the script.
+    pub range: Range<usize>,
+}
+
+impl Engine {
+    /// Split a script into highlighted spans, based on its tokens.
+    ///
+    /// The script is tokenized but not parsed, so incomplete scripts are supported.
+    /// Custom keywords and symbols registered on this [`Engine`], as well as the first keywords of
+    /// custom syntax, are highlighted as [`HighlightKind::Custom`].
+    ///
+    /// White-spaces between tokens are not included in any span.
+    ///
+    /// Not available under `no_position`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, HighlightKind};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let script = "let x = 42; // the answer";
+    /// let spans = engine.highlight_script(script);
+    ///
+    /// assert_eq!(spans[0].kind, HighlightKind::Keyword);
+    /// assert_eq!(&script[spans[0].range.clone()], "let");
+    /// assert_eq!(spans[3].kind, HighlightKind::Number);
+    /// assert_eq!(&script[spans[5].range.clone()], "// the answer");
+    /// ```
+    #[must_use]
+    pub fn highlight_script(&self, script: &str) -> Vec<HighlightSpan> {
+        let tokens = self.tokenize_with_ranges(script);
+
+        tokens
+            .iter()
+            .enumerate()
+            .map(|(i, (token, range))| {
+                let next = tokens[i + 1..]
+                    .iter()
+                    .map(|(t, ..)| t)
+                    .find(|t| !matches!(t, Token::Comment(..)));
+                HighlightSpan {
+                    kind: self.highlight_kind(token, next),
+                    range: range.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Tokenize a script (including comments) into tokens with their byte ranges.
+    ///
+    /// Interpolated strings are tracked so that the text following an interpolation is tokenized
+    /// as a string. The ending [`Token::EOF`] is not included.
+    #[must_use]
+    pub(crate) fn tokenize_with_ranges(&self, script: &str) -> Vec<(Token, Range<usize>)> {
+        let scripts = [script];
+        let (mut stream, control) = self.lex(&scripts);
+        stream.state.include_comments = true;
+
+        let mut tokens = Vec::new();
+        let mut level = 0_usize;
+        let mut interpolations = Vec::new();
+
+        for (token, pos) in stream {
+            match token {
+                Token::EOF => break,
+                Token::InterpolatedString(..) => interpolations.push(level),
+                Token::LeftBrace | Token::MapStart => level += 1,
+                Token::RightBrace => {
+                    level = level.saturating_sub(1);
+
+                    // Continue the interpolated string after the closing brace
+                    if interpolations.last() == Some(&level) {
+                        interpolations.pop();
+                        control.borrow_mut().is_within_text = true;
+                    }
+                }
+                _ => (),
+            }
+
+            tokens.push((token, pos));
+        }
+
+        // Each token extends to the start of the next one, less any white-spaces
+        let starts: Vec<_> = tokens
+            .iter()
+            .map(|(.., pos)| byte_offset(script, *pos))
+            .chain(Some(script.len()))
+            .collect();
+
+        tokens
+            .into_iter()
+            .enumerate()
+            .map(|(i, (token, ..))| {
+                let start = starts[i];
+                let end = start + script[start..starts[i + 1]].trim_end().len();
+                (token, start..end)
+            })
+            .collect()
+    }
+
+    /// Get the [`HighlightKind`] of a token, given the following token (if any).
+    fn highlight_kind(&self, token: &Token, next: Option<&Token>) -> HighlightKind {
+        match token {
+            Token::IntegerConstant(..) => HighlightKind::Number,
+            #[cfg(not(feature = "no_float"))]
+            Token::FloatConstant(..) => HighlightKind::Number,
+            #[cfg(feature = "decimal")]
+            Token::DecimalConstant(..) => HighlightKind::Number,
+            Token::StringConstant(..) | Token::InterpolatedString(..) | Token::CharConstant(..) => {
+                HighlightKind::String
+            }
+            Token::Comment(..) => HighlightKind::Comment,
+            Token::LexError(..) => HighlightKind::Error,
+            #[cfg(not(feature = "no_custom_syntax"))]
+            Token::Custom(..) => HighlightKind::Custom,
+            #[cfg(not(feature = "no_custom_syntax"))]
+            Token::Identifier(name) if self.custom_syntax.contains_key(name.as_str()) => {
+                HighlightKind::Custom
+            }
+            #[cfg(not(feature = "no_function"))]
+            Token::Identifier(name) if name.as_str() == crate::engine::KEYWORD_THIS => {
+                HighlightKind::Keyword
+            }
+            #[cfg(not(feature = "no_function"))]
+            #[cfg(not(feature = "no_module"))]
+            Token::Identifier(name) if name.as_str() == crate::engine::KEYWORD_GLOBAL => {
+                HighlightKind::Keyword
+            }
+            Token::Identifier(..) if matches!(next, Some(Token::LeftParen | Token::Bang)) => {
+                HighlightKind::Function
+            }
+            Token::Identifier(..) => HighlightKind::Identifier,
+            Token::Reserved(s) if s.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                HighlightKind::Keyword
+            }
+            token if token.is_standard_keyword() => HighlightKind::Keyword,
+            token
+                if token.is_literal()
+                    && token
+                        .literal_syntax()
+                        .starts_with(|c: char| c.is_ascii_alphabetic()) =>
+            {
+                HighlightKind::Keyword
+            }
+            _ => HighlightKind::Symbol,
+        }
+    }
+}
+
+/// Convert a [`Position`] into a byte offset within a script.
+fn byte_offset(script: &str, pos: Position) -> usize {
+    let line = pos.line().unwrap_or(1);
+    let column = pos.position().unwrap_or(1);
+
+    let line_start = script
+        .match_indices('\n')
+        .nth(line.saturating_sub(2))
+        .filter(|_| line > 1)
+        .map_or(0, |(i, ..)| i + 1);
+
+    script[line_start..]
+        .char_indices()
+        .nth(column - 1)
+        .map_or(script.len(), |(i, ..)| line_start + i)
+}
@@ -0,0 +1,517 @@
+//! Module that defines the script linter of the [`Engine`].
+
+use crate::ast::{ASTFlags, ASTNode, Expr, FlowControl, FnCallExpr, Ident, Stmt};
+use crate::parser::ParseResult;
+use crate::tokenizer::{is_valid_identifier, RecordedToken, Token};
+use crate::{Engine, ImmutableString, Position, AST};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{collections::BTreeSet, fmt};
+
+/// Marker starting a comment that suppresses lint warnings.
+const LINT_COMMENT_MARKER: &str = "rhai-lint:";
+
+/// Code of a lint reported by the linter.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum LintCode {
+    /// A variable is defined but never used.
+    UnusedVariable,
+    /// A variable definition shadows a previous variable (or parameter) of the same name.
+    ShadowedVariable,
+    /// A statement that can never be reached because it follows a `return`, `throw`, `break` or
+    /// `continue` statement.
+    UnreachableCode,
+    /// A function that is neither defined in the script nor registered with the [`Engine`].
+    UnknownFunction,
+}
+
+impl fmt::Display for LintCode {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl LintCode {
+    /// All lint codes.
+    pub const ALL: [Self; 4] = [
+        Self::UnusedVariable,
+        Self::ShadowedVariable,
+        Self::UnreachableCode,
+        Self::UnknownFunction,
+    ];
+
+    /// Get the name of this lint code, as used in suppression comments.
+    #[inline]
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::UnusedVariable => "unused_variable",
+            Self::ShadowedVariable => "shadowed_variable",
+            Self::UnreachableCode => "unreachable_code",
+            Self::UnknownFunction => "unknown_function",
+        }
+    }
+    /// Look up a lint code by name.
+    #[must_use]
+    pub fn lookup(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|code| code.as_str() == name)
+    }
+}
+
+/// A warning reported by the linter.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct LintWarning {
+    /// Lint code.
+    pub code: LintCode,
+    /// Description of the problem.
+    pub message: String,
+    /// Position of the problem in the script.
+    pub position: Position,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.message, self.code)?;
+
+        // Do not write any position if None
+        if !self.position.is_none() {
+            write!(f, " ({})", self.position)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Create a callback for walking the [`AST`] that returns `false` when a variable is used.
+///
+/// A call to `eval` counts as a use because it can access any variable.
+fn find_variable(name: &str) -> impl FnMut(&[ASTNode]) -> bool + '_ {
+    move |path| match path.last() {
+        Some(ASTNode::Expr(Expr::Variable(x, ..))) => !(x.1.is_empty() && x.3 == name),
+        Some(ASTNode::Expr(Expr::FnCall(x, ..)) | ASTNode::Stmt(Stmt::FnCall(x, ..))) => {
+            x.name != crate::engine::KEYWORD_EVAL
+        }
+        #[cfg(not(feature = "no_module"))]
+        Some(ASTNode::Stmt(Stmt::Export(x, ..))) => x.0.name != name,
+        _ => true,
+    }
+}
+
+/// Get the variables defined by a statement.
+fn defined_vars(stmt: &Stmt) -> Vec<&Ident> {
+    match stmt {
+        Stmt::Var(x, ..) => match x.4 {
+            Some(ref pattern) => pattern.variables(),
+            None => vec![&x.0],
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Is a variable used within a list of statements?
+fn is_used_in(name: &str, stmts: &[Stmt]) -> bool {
+    let path = &mut Vec::new();
+    !stmts
+        .iter()
+        .all(|stmt| stmt.walk(path, &mut find_variable(name)))
+}
+
+/// Is `child` one of the statements in a block?
+fn is_in_block(block: &[Stmt], child: &ASTNode) -> bool {
+    block.iter().any(|stmt| ASTNode::from(stmt) == *child)
+}
+
+/// Find the nearest definition of a variable among the statements preceding `child` in a block.
+fn find_in_block(block: &[Stmt], child: &ASTNode, name: &str) -> Option<Position> {
+    let index = block
+        .iter()
+        .position(|stmt| ASTNode::from(stmt) == *child)?;
+
+    block[..index].iter().rev().find_map(|stmt| {
+        defined_vars(stmt)
+            .into_iter()
+            .find(|v| v.name == name)
+            .map(|v| v.pos)
+    })
+}
+
+/// Find the nearest definition of a variable within `parent` that is visible to `child`.
+fn find_definition(parent: &ASTNode, child: &ASTNode, name: &str) -> Option<Position> {
+    match parent {
+        ASTNode::Stmt(Stmt::Block(x)) | ASTNode::Expr(Expr::Stmt(x)) => {
+            find_in_block(x.statements(), child, name)
+        }
+        ASTNode::Stmt(Stmt::If(x, ..) | Stmt::While(x, ..) | Stmt::Do(x, ..)) => {
+            find_in_block(x.body.statements(), child, name)
+                .or_else(|| find_in_block(x.branch.statements(), child, name))
+        }
+        ASTNode::Stmt(Stmt::TryCatch(x, ..)) => find_in_block(x.body.statements(), child, name)
+            .or_else(|| find_in_block(x.branch.statements(), child, name))
+            .or_else(|| match x.expr {
+                Expr::Variable(ref v, .., pos)
+                    if v.3 == name && is_in_block(x.branch.statements(), child) =>
+                {
+                    Some(pos)
+                }
+                _ => None,
+            }),
+        ASTNode::Stmt(Stmt::For(x, ..)) => {
+            let (var, counter, FlowControl { body, .. }) = &**x;
+
+            find_in_block(body.statements(), child, name).or_else(|| {
+                [Some(var), counter.as_ref()]
+                    .iter()
+                    .flatten()
+                    .find(|v| v.name == name && is_in_block(body.statements(), child))
+                    .map(|v| v.pos)
+            })
+        }
+        _ => None,
+    }
+}
+
+/// State of the linter.
+struct Linter<'a> {
+    /// Names and numbers of parameters of all functions that can be called.
+    functions: BTreeSet<(&'a str, usize)>,
+    /// Names of variables accessed via a namespace (e.g. `global::x`).
+    qualified_vars: BTreeSet<ImmutableString>,
+    /// Warnings found so far.
+    warnings: Vec<LintWarning>,
+}
+
+impl<'a> Linter<'a> {
+    /// Create a new [`Linter`].
+    fn new(engine: &'a Engine, ast: &'a AST) -> Self {
+        let mut functions = BTreeSet::new();
+
+        for m in &engine.global_modules {
+            functions.extend(m.iter_fn().map(|(.., f)| (f.name.as_str(), f.num_params)));
+        }
+        #[cfg(not(feature = "no_module"))]
+        for m in engine.global_sub_modules.values() {
+            functions.extend(
+                m.iter_fn()
+                    .filter(|(.., f)| f.namespace == crate::FnNamespace::Global)
+                    .map(|(.., f)| (f.name.as_str(), f.num_params)),
+            );
+        }
+        #[cfg(not(feature = "no_function"))]
+        functions.extend(
+            ast.shared_lib()
+                .iter_script_fn()
+                .map(|(.., name, num_params, _)| (name, num_params)),
+        );
+
+        let mut qualified_vars = BTreeSet::new();
+
+        ast._walk(&mut |path: &[ASTNode]| {
+            if let Some(ASTNode::Expr(Expr::Variable(x, ..))) = path.last() {
+                if !x.1.is_empty() {
+                    qualified_vars.insert(x.3.clone());
+                }
+            }
+            true
+        });
+
+        Self {
+            functions,
+            qualified_vars,
+            warnings: Vec::new(),
+        }
+    }
+    /// Record a warning.
+    fn warn(&mut self, code: LintCode, message: String, position: Position) {
+        self.warnings.push(LintWarning {
+            code,
+            message,
+            position,
+        });
+    }
+    /// Lint the body of the main script or a function.
+    fn lint_body(&mut self, params: &[ImmutableString], body: &[Stmt]) {
+        self.lint_block(body);
+
+        let path = &mut Vec::new();
+
+        for stmt in body {
+            stmt.walk(path, &mut |path: &[ASTNode]| {
+                self.lint_node(path, params, body);
+                true
+            });
+        }
+    }
+    /// Lint an [`AST`] node, given the path leading to it.
+    fn lint_node(&mut self, path: &[ASTNode], params: &[ImmutableString], body: &[Stmt]) {
+        match path.last() {
+            Some(ASTNode::Stmt(stmt)) => match stmt {
+                Stmt::Var(x, ..) => {
+                    for var in defined_vars(stmt) {
+                        self.check_shadowing(path, params, body, var, &x.1);
+                    }
+                }
+                Stmt::FnCall(x, pos) => self.check_fn_call(x, *pos),
+                Stmt::Block(x) => self.lint_block(x.statements()),
+                Stmt::If(x, ..) | Stmt::While(x, ..) | Stmt::Do(x, ..) | Stmt::TryCatch(x, ..) => {
+                    self.lint_block(x.body.statements());
+                    self.lint_block(x.branch.statements());
+                }
+                Stmt::For(x, ..) => {
+                    let (var, counter, FlowControl { body, .. }) = &**x;
+
+                    for v in [Some(var), counter.as_ref()].iter().flatten() {
+                        self.check_unused(v, body.statements());
+                    }
+                    self.lint_block(body.statements());
+                }
+                _ => (),
+            },
+            Some(ASTNode::Expr(expr)) => match expr {
+                Expr::FnCall(x, pos) => self.check_fn_call(x, *pos),
+                Expr::Stmt(x) => self.lint_block(x.statements()),
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+    /// Lint the statements of a block.
+    fn lint_block(&mut self, stmts: &[Stmt]) {
+        if let Some(index) = stmts
+            .iter()
+            .position(|stmt| matches!(stmt, Stmt::Return(..) | Stmt::BreakLoop(..)))
+        {
+            if let Some(stmt) = stmts[index + 1..].iter().find(|stmt| !stmt.is_noop()) {
+                let message = "Unreachable code".into();
+                self.warn(LintCode::UnreachableCode, message, stmt.position());
+            }
+        }
+
+        for (index, stmt) in stmts.iter().enumerate() {
+            match stmt {
+                Stmt::Var(_, options, ..) if !options.contains(ASTFlags::EXPORTED) => {
+                    for var in defined_vars(stmt) {
+                        self.check_unused(var, &stmts[index + 1..]);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    /// Check whether a variable is used within the statements in its scope.
+    fn check_unused(&mut self, var: &Ident, scope: &[Stmt]) {
+        let name = var.name.as_str();
+
+        // The scope ends at a new definition of the same variable
+        let scope = match scope
+            .iter()
+            .position(|stmt| defined_vars(stmt).iter().any(|v| v.name == name))
+        {
+            Some(index) => &scope[..=index],
+            None => scope,
+        };
+
+        if name.starts_with('_') || self.qualified_vars.contains(name) || is_used_in(name, scope) {
+            return;
+        }
+
+        let message = format!("Unused variable: {name}");
+        self.warn(LintCode::UnusedVariable, message, var.pos);
+    }
+    /// Check whether a variable definition shadows a previous definition.
+    ///
+    /// A definition that uses the previous variable in its initial value (e.g. `let x = x + 1;`)
+    /// is considered intentional.
+    fn check_shadowing(
+        &mut self,
+        path: &[ASTNode],
+        params: &[ImmutableString],
+        body: &[Stmt],
+        var: &Ident,
+        expr: &Expr,
+    ) {
+        let name = var.name.as_str();
+
+        if name.starts_with('_') || !expr.walk(&mut Vec::new(), &mut find_variable(name)) {
+            return;
+        }
+
+        let message = match (0..path.len() - 1)
+            .rev()
+            .find_map(|i| find_definition(&path[i], &path[i + 1], name))
+            .or_else(|| find_in_block(body, &path[0], name))
+        {
+            Some(pos) if pos.is_none() => format!("Variable {name} shadows a previous definition"),
+            Some(pos) => format!("Variable {name} shadows a previous definition at {pos}"),
+            None if params.iter().any(|p| p.as_str() == name) => {
+                format!("Variable {name} shadows a function parameter")
+            }
+            None => return,
+        };
+
+        self.warn(LintCode::ShadowedVariable, message, var.pos);
+    }
+    /// Check whether a called function exists.
+    fn check_fn_call(&mut self, x: &FnCallExpr, pos: Position) {
+        let name = x.name.as_str();
+        let num_params = x.args.len();
+
+        // Skip operators, qualified calls and keywords
+        if x.op_token.is_some()
+            || !x.namespace.is_empty()
+            || !is_valid_identifier(name)
+            || self.functions.contains(&(name, num_params))
+        {
+            return;
+        }
+
+        match name {
+            crate::engine::KEYWORD_PRINT
+            | crate::engine::KEYWORD_DEBUG
+            | crate::engine::KEYWORD_TYPE_OF
+            | crate::engine::KEYWORD_EVAL
+            | crate::engine::KEYWORD_FN_PTR
+            | crate::engine::KEYWORD_FN_PTR_CALL
+            | crate::engine::KEYWORD_FN_PTR_CURRY
+            | crate::engine::KEYWORD_IS_DEF_VAR => return,
+            #[cfg(not(feature = "no_closure"))]
+            crate::engine::KEYWORD_IS_SHARED => return,
+            #[cfg(not(feature = "no_function"))]
+            crate::engine::KEYWORD_IS_DEF_FN => return,
+            _ => (),
+        }
+
+        let message = if self.functions.iter().any(|&(f, _)| f == name) {
+            match num_params {
+                0 => format!("Function not found: {name} with no parameters"),
+                1 => format!("Function not found: {name} with 1 parameter"),
+                _ => format!("Function not found: {name} with {num_params} parameters"),
+            }
+        } else {
+            format!("Function not found: {name}")
+        };
+
+        self.warn(LintCode::UnknownFunction, message, pos);
+    }
+    /// Lint an [`AST`], returning all warnings sorted by position.
+    fn lint(mut self, ast: &AST) -> Vec<LintWarning> {
+        self.lint_body(&[], ast.statements());
+
+        #[cfg(not(feature = "no_function"))]
+        for (.., f) in ast.shared_lib().iter_script_fn() {
+            self.lint_body(&f.params, f.body.statements());
+        }
+
+        self.warnings.sort_by_key(|w| w.position);
+        self.warnings
+    }
+}
+
+/// Parse the suppression comments in a list of tokens.
+///
+/// Returns the line number and list of suppressed lint codes (empty for all) for each comment.
+///
+/// A comment after code on the same line applies to that line; a comment on its own line applies
+/// to the next line of code.
+fn parse_suppressions(tokens: &[RecordedToken]) -> Vec<(usize, Vec<&str>)> {
+    let mut suppressions = Vec::new();
+
+    for (index, (token, text, pos)) in tokens.iter().enumerate() {
+        if !matches!(token, Token::Comment(..)) {
+            continue;
+        }
+
+        let text = text.trim_start_matches('/').trim_start_matches('*');
+        let text = text.trim_end_matches('/').trim_end_matches('*').trim();
+
+        let rest = match text.strip_prefix(LINT_COMMENT_MARKER) {
+            Some(rest) => rest.trim(),
+            None => continue,
+        };
+        let codes = match rest.strip_prefix("allow") {
+            Some(codes) => codes.trim(),
+            None => continue,
+        };
+        let codes = match codes.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+            Some(codes) => codes.split(',').map(str::trim).collect(),
+            None if codes.is_empty() => Vec::new(),
+            None => continue,
+        };
+
+        let trailing = index > 0 && tokens[index - 1].2.line() == pos.line();
+
+        let line = if trailing {
+            pos.line()
+        } else {
+            tokens[index + 1..]
+                .iter()
+                .find(|(t, ..)| !matches!(t, Token::Comment(..)))
+                .and_then(|(.., p)| p.line())
+        };
+
+        if let Some(line) = line {
+            suppressions.push((line, codes));
+        }
+    }
+
+    suppressions
+}
+
+impl Engine {
+    /// Lint a script, returning warnings for common mistakes.
+    ///
+    /// The script is compiled without optimization, so that no code is eliminated before linting.
+    ///
+    /// Warnings on a line can be suppressed with a comment of the form
+    /// `// rhai-lint: allow(unused_variable, unknown_function)` (or `// rhai-lint: allow` to
+    /// suppress all warnings). A comment after code on the same line applies to that line, while
+    /// a comment on its own line applies to the next line.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, LintCode};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let script = "
+    ///     let x = 42;
+    ///     foo(x);
+    ///     let y = 0;      // rhai-lint: allow(unused_variable)
+    ///     let z = 1;
+    /// ";
+    ///
+    /// let warnings = engine.lint(script)?;
+    ///
+    /// assert_eq!(warnings.len(), 2);
+    /// assert_eq!(warnings[0].code, LintCode::UnknownFunction);
+    /// assert_eq!(warnings[1].code, LintCode::UnusedVariable);
+    /// assert_eq!(warnings[1].to_string(), "Unused variable: z [unused_variable] (line 5, position 9)");
+    /// # Ok::<_, rhai::ParseError>(())
+    /// ```
+    pub fn lint(&self, script: impl AsRef<str>) -> ParseResult<Vec<LintWarning>> {
+        let (tokens, ast) = self.compile_with_tokens(script)?;
+
+        let suppressions = parse_suppressions(&tokens);
+
+        let mut warnings = self.lint_ast(&ast);
+
+        warnings.retain(|w| {
+            !suppressions.iter().any(|(line, codes)| {
+                w.position.line() == Some(*line)
+                    && (codes.is_empty() || codes.contains(&w.code.as_str()))
+            })
+        });
+
+        Ok(warnings)
+    }
+    /// Lint an [`AST`], returning warnings for common mistakes.
+    ///
+    /// Suppression comments are not available because the [`AST`] does not keep comments.
+    /// Compile the [`AST`] without optimization to avoid code being eliminated before linting.
+    #[must_use]
+    pub fn lint_ast(&self, ast: &AST) -> Vec<LintWarning> {
+        Linter::new(self, ast).lint(ast)
+    }
+}
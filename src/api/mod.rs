@@ -2,6 +2,10 @@
 
 pub mod eval;
 
+pub mod eval_async;
+
+pub mod eval_suspendable;
+
 pub mod run;
 
 pub mod compile;
@@ -24,6 +28,12 @@ pub mod events;
 
 pub mod formatting;
 
+pub mod formatter;
+
+pub mod lint;
+
+pub mod testing;
+
 pub mod custom_syntax;
 
 pub mod build_type;
@@ -31,6 +41,14 @@ pub mod build_type;
 #[cfg(feature = "metadata")]
 pub mod definitions;
 
+#[cfg(feature = "metadata")]
+pub mod docs;
+
+pub mod highlight;
+
+#[cfg(feature = "metadata")]
+pub mod completion;
+
 pub mod deprecated;
 
 use crate::{Dynamic, Engine, Identifier};
@@ -32,6 +32,15 @@ bitflags! {
         const FAIL_ON_INVALID_MAP_PROPERTY = 0b_0001_0000_0000;
         /// Fast operators mode?
         const FAST_OPS = 0b_0010_0000_0000;
+        /// Are tail calls of script-defined functions optimized?
+        #[cfg(not(feature = "no_function"))]
+        const TAIL_CALLS = 0b_0100_0000_0000;
+        /// Are call stack frames of functions replaced by tail calls kept for the debugger?
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(feature = "debugging")]
+        const KEEP_TAIL_CALL_FRAMES = 0b_1000_0000_0000;
+        /// Are function calls bound to native Rust functions at compile time?
+        const BIND_CALLS = 0b_0001_0000_0000_0000;
     }
 }
 
@@ -57,6 +66,17 @@ impl LangOptions {
                     {
                         Self::empty().bits()
                     }
+                }
+                | {
+                    #[cfg(not(feature = "no_function"))]
+                    #[cfg(feature = "debugging")]
+                    {
+                        Self::KEEP_TAIL_CALL_FRAMES.bits()
+                    }
+                    #[cfg(any(feature = "no_function", not(feature = "debugging")))]
+                    {
+                        Self::empty().bits()
+                    }
                 },
         )
     }
@@ -207,4 +227,81 @@ impl Engine {
         self.options.set(LangOptions::FAST_OPS, enable);
         self
     }
+    /// Are tail calls of script-defined functions optimized?
+    /// Default is `false`.
+    ///
+    /// A call to a script-defined function in tail position (i.e. in a `return` statement or
+    /// producing the value of the function body) then reuses the call frame of the calling
+    /// function, so recursion in tail position is not limited by the
+    /// [maximum call levels][Engine::max_call_levels].
+    ///
+    /// As the calling functions are replaced, an error raised within a tail call is wrapped in
+    /// [`ErrorInFunctionCall`][crate::EvalAltResult::ErrorInFunctionCall] only for the outermost
+    /// function call, instead of once for every function called in tail position.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    #[must_use]
+    pub const fn tail_call_optimization(&self) -> bool {
+        self.options.intersects(LangOptions::TAIL_CALLS)
+    }
+    /// Set whether tail calls of script-defined functions are optimized.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub fn set_tail_call_optimization(&mut self, enable: bool) -> &mut Self {
+        self.options.set(LangOptions::TAIL_CALLS, enable);
+        self
+    }
+    /// Are call stack frames of functions replaced by tail calls kept for the debugger?
+    /// Default is `true`.
+    ///
+    /// If `true`, the debugger's call stack shows every function called in tail position, at the
+    /// cost of memory growing with the depth of the recursion.
+    /// If `false`, the call stack frame of a function is replaced by that of its tail call.
+    ///
+    /// Not available under `no_function` or without `debugging`.
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(feature = "debugging")]
+    #[inline(always)]
+    #[must_use]
+    pub const fn keep_tail_call_frames(&self) -> bool {
+        self.options.intersects(LangOptions::KEEP_TAIL_CALL_FRAMES)
+    }
+    /// Set whether call stack frames of functions replaced by tail calls are kept for the debugger.
+    ///
+    /// Not available under `no_function` or without `debugging`.
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(feature = "debugging")]
+    #[inline(always)]
+    pub fn set_keep_tail_call_frames(&mut self, enable: bool) -> &mut Self {
+        self.options.set(LangOptions::KEEP_TAIL_CALL_FRAMES, enable);
+        self
+    }
+    /// Are function calls bound to native Rust functions at compile time?
+    /// Default is `false`.
+    ///
+    /// When a script is compiled, each function call is bound to the native Rust functions (in the
+    /// global namespace of the [`Engine`]) with the same name and number of parameters.  At runtime,
+    /// a bound function matching the types of the arguments is then called directly without
+    /// looking up the function resolution cache, falling back to normal resolution otherwise (e.g.
+    /// for functions with [`Dynamic`][crate::Dynamic] parameters).
+    ///
+    /// Script-defined functions still take precedence.  Bindings are ignored after functions are
+    /// registered into the [`Engine`], so scripts should be compiled again to benefit from them.
+    ///
+    /// This is useful for scripts compiled once and run many times.
+    #[inline(always)]
+    #[must_use]
+    pub const fn bind_native_calls(&self) -> bool {
+        self.options.intersects(LangOptions::BIND_CALLS)
+    }
+    /// Set whether function calls are bound to native Rust functions at compile time.
+    #[inline(always)]
+    pub fn set_bind_native_calls(&mut self, enable: bool) -> &mut Self {
+        self.options.set(LangOptions::BIND_CALLS, enable);
+        self
+    }
 }
@@ -1,10 +1,11 @@
 //! Module that defines the public function/module registration API of [`Engine`].
 
-use crate::func::{FnCallArgs, RhaiFunc, RhaiNativeFunc, SendSync};
+use crate::func::{FnCallArgs, RhaiAsyncFunc, RhaiFunc, RhaiNativeFunc, SendSync};
 use crate::module::FuncRegistration;
 use crate::types::dynamic::Variant;
 use crate::{
-    Dynamic, Engine, Identifier, Module, NativeCallContext, RhaiResultOf, Shared, SharedModule,
+    calc_fn_hash, Dynamic, Engine, Identifier, Module, NativeCallContext, RhaiResultOf, Shared,
+    SharedModule,
 };
 use std::any::{type_name, TypeId};
 #[cfg(feature = "no_std")]
@@ -25,6 +26,9 @@ impl Engine {
             self.global_modules.push(global_namespace.into());
         }
 
+        // Function calls bound to the registered functions must be resolved again
+        self.fn_version = crate::engine::next_fn_version();
+
         Shared::get_mut(self.global_modules.first_mut().unwrap()).unwrap()
     }
     /// Register a custom function with the [`Engine`].
@@ -99,6 +103,86 @@ impl Engine {
 
         self
     }
+    /// Register an asynchronous custom function with the [`Engine`].
+    ///
+    /// The function takes all its parameters by value and returns a [`Future`][std::future::Future]
+    /// that resolves to `Result<T, Box<EvalAltResult>>`.
+    ///
+    /// Under [`eval_async`][Engine::eval_async] and related methods, evaluation is suspended
+    /// (without blocking) while the future is pending, as long as the function is called by itself
+    /// in a statement, e.g. `foo(x);`, `let y = foo(x);`, `y = foo(x);` or `return foo(x);`,
+    /// either in the script or in any script-defined function called the same way.
+    ///
+    /// Calls in any other position (e.g. within an expression or a closure) are rejected when the
+    /// script is compiled.  Under normal synchronous evaluation, the future must be ready when it
+    /// is first polled, otherwise an error is raised.
+    ///
+    /// Functions must be registered before compiling the scripts that call them.
+    ///
+    /// # Assumptions
+    ///
+    /// * **Accessibility**: The function namespace is [`FnNamespace::Global`][`crate::FnNamespace::Global`].
+    ///
+    /// * **Volatility**: The function is _volatile_ -- i.e. it is never evaluated by the optimizer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_async_fn("fetch", |x: i64| async move { Ok(x + 1) });
+    ///
+    /// // Futures that are immediately ready also work under synchronous evaluation
+    /// assert_eq!(engine.eval::<i64>("fetch(41)")?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn register_async_fn<
+        A: 'static,
+        const N: usize,
+        R: Variant + Clone,
+        FUNC: RhaiAsyncFunc<A, N, R> + SendSync + 'static,
+    >(
+        &mut self,
+        name: impl AsRef<str> + Into<Identifier>,
+        func: FUNC,
+    ) -> &mut Self {
+        // Calls to the function are checked when scripts are compiled
+        self.async_fns.insert(calc_fn_hash(None, name.as_ref(), N));
+
+        let reg = FuncRegistration::new(name.into()).in_global_namespace();
+
+        #[cfg(feature = "metadata")]
+        let reg = {
+            let mut param_type_names = FUNC::param_names()
+                .iter()
+                .map(|ty| format!("_: {}", self.format_param_type(ty)))
+                .collect::<crate::FnArgsVec<_>>();
+
+            if TypeId::of::<R>() != TypeId::of::<()>() {
+                param_type_names.push(self.format_param_type(FUNC::return_type_name()).into());
+            }
+
+            let param_type_names = param_type_names
+                .iter()
+                .map(String::as_str)
+                .collect::<crate::FnArgsVec<_>>();
+
+            reg.with_params_info(param_type_names)
+        };
+
+        reg.set_into_module_raw(
+            self.global_namespace_mut(),
+            FUNC::param_types(),
+            func.into_rhai_function(),
+        );
+
+        self
+    }
     /// Register a function of the [`Engine`].
     ///
     /// # WARNING - Low Level API
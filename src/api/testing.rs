@@ -0,0 +1,1057 @@
+//! Module that defines the test runner for tests written in Rhai scripts.
+#![cfg(not(feature = "no_function"))]
+#![cfg(not(feature = "no_std"))]
+#![cfg(any(not(target_family = "wasm"), not(target_os = "unknown")))]
+
+#[cfg(feature = "metadata")]
+use crate::api::docs::doc_comment_lines;
+use crate::{
+    CallFnOptions, Dynamic, Engine, FnPtr, ImmutableString, NativeCallContext, Position,
+    RhaiResultOf, Scope, AST, ERR,
+};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fmt::Write,
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Doc-comment attribute marking a function as a test.
+const TEST_ATTRIBUTE: &str = "#[test]";
+/// Doc-comment attribute marking a test as ignored.
+const IGNORE_ATTRIBUTE: &str = "#[ignore]";
+/// Prefix of the names of functions that are tests.
+const TEST_PREFIX: &str = "test_";
+
+/// Callback function to create an [`Engine`] for running tests.
+pub type OnCreateTestEngine = dyn Fn() -> Engine + Send + Sync;
+
+/// Source name of doc-tests on native Rust functions.
+#[cfg(feature = "metadata")]
+const NATIVE_SOURCE: &str = "(native)";
+
+/// Kind of a [`TestCase`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum TestKind {
+    /// A test function.
+    Function,
+    /// A code example in the doc-comments of a function.
+    DocTest {
+        /// Code of the example.
+        code: String,
+        /// Number of the example among the examples of the function, starting from 1.
+        index: usize,
+        /// Is the example only compiled but not run (marked `no_run`)?
+        no_run: bool,
+    },
+}
+
+/// A test discovered in a script.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TestCase {
+    /// Name of the script (usually its file path).
+    ///
+    /// Doc-tests on native Rust functions have the source `(native)`.
+    pub source: String,
+    /// Name of the test function, or of the function holding the doc-test.
+    pub name: String,
+    /// [Position] of the test function, or of the first line of code of the doc-test
+    /// ([`Position::NONE`] for doc-tests on native Rust functions).
+    pub pos: Position,
+    /// Is the test marked `#[ignore]` (or `ignore` for doc-tests)?
+    pub ignored: bool,
+    /// Kind of the test.
+    pub kind: TestKind,
+}
+
+impl TestCase {
+    /// Name of the test within its script.
+    fn test_name(&self) -> String {
+        match self.kind {
+            TestKind::Function => self.name.clone(),
+            TestKind::DocTest { index, .. } => match self.pos.line() {
+                Some(line) => format!("{} (line {line})", self.name),
+                None => format!("{} (example {index})", self.name),
+            },
+        }
+    }
+}
+
+impl fmt::Display for TestCase {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}", self.source, self.test_name())
+    }
+}
+
+/// Outcome of running a [`TestCase`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum TestOutcome {
+    /// The test passed.
+    Passed,
+    /// The test failed, with the error message.
+    Failed(String),
+    /// The test is ignored and not run.
+    Ignored,
+}
+
+/// Result of running a [`TestCase`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TestResult {
+    /// The test.
+    pub case: TestCase,
+    /// Outcome of the test.
+    pub outcome: TestOutcome,
+    /// Time taken to run the test.
+    pub duration: Duration,
+}
+
+/// Results of running tests with a [`TestRunner`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub struct TestReport {
+    /// Results of the tests run, in order of script and position.
+    pub results: Vec<TestResult>,
+    /// Scripts that fail to compile, with the error messages.
+    pub errors: Vec<(String, String)>,
+    /// Total time taken.
+    pub duration: Duration,
+}
+
+impl TestReport {
+    /// Number of tests that pass.
+    #[inline]
+    #[must_use]
+    pub fn passed(&self) -> usize {
+        self.count(|outcome| matches!(outcome, TestOutcome::Passed))
+    }
+    /// Number of tests that fail.
+    #[inline]
+    #[must_use]
+    pub fn failed(&self) -> usize {
+        self.count(|outcome| matches!(outcome, TestOutcome::Failed(..)))
+    }
+    /// Number of tests that are ignored.
+    #[inline]
+    #[must_use]
+    pub fn ignored(&self) -> usize {
+        self.count(|outcome| matches!(outcome, TestOutcome::Ignored))
+    }
+    /// Count the tests with an outcome.
+    fn count(&self, filter: impl Fn(&TestOutcome) -> bool) -> usize {
+        self.results.iter().filter(|r| filter(&r.outcome)).count()
+    }
+    /// Do all scripts compile, with no test failing?
+    #[inline]
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty() && self.failed() == 0
+    }
+    /// Export the results in JUnit XML format.
+    ///
+    /// There is one `testsuite` per script. A script that fails to compile has a single `testcase`
+    /// named `(compile)` with an `error`.
+    #[must_use]
+    pub fn to_junit_xml(&self) -> String {
+        let mut suites: BTreeMap<&str, Vec<&TestResult>> = BTreeMap::new();
+
+        for result in &self.results {
+            suites.entry(&result.case.source).or_default().push(result);
+        }
+        for (source, ..) in &self.errors {
+            suites.entry(source).or_default();
+        }
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+        writeln!(
+            xml,
+            "<testsuites tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">",
+            self.results.len(),
+            self.failed(),
+            self.errors.len(),
+            self.ignored(),
+            self.duration.as_secs_f64()
+        )
+        .unwrap();
+
+        for (source, results) in suites {
+            let error = self.errors.iter().find(|(s, ..)| s == source);
+            let failed = results
+                .iter()
+                .filter(|r| matches!(r.outcome, TestOutcome::Failed(..)))
+                .count();
+            let ignored = results
+                .iter()
+                .filter(|r| r.outcome == TestOutcome::Ignored)
+                .count();
+            let time: Duration = results.iter().map(|r| r.duration).sum();
+
+            writeln!(
+                xml,
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failed}\" errors=\"{}\" skipped=\"{ignored}\" time=\"{:.3}\">",
+                escape_xml(source),
+                results.len(),
+                usize::from(error.is_some()),
+                time.as_secs_f64()
+            )
+            .unwrap();
+
+            if let Some((.., message)) = error {
+                writeln!(
+                    xml,
+                    "    <testcase name=\"(compile)\" classname=\"{0}\" time=\"0.000\">\n      <error message=\"{1}\">{1}</error>\n    </testcase>",
+                    escape_xml(source),
+                    escape_xml(message)
+                )
+                .unwrap();
+            }
+
+            for result in results {
+                write!(
+                    xml,
+                    "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                    escape_xml(&result.case.test_name()),
+                    escape_xml(source),
+                    result.duration.as_secs_f64()
+                )
+                .unwrap();
+
+                match result.outcome {
+                    TestOutcome::Passed => xml.push_str("/>\n"),
+                    TestOutcome::Failed(ref message) => {
+                        let summary = message.lines().next().unwrap_or("");
+                        writeln!(
+                            xml,
+                            ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>",
+                            escape_xml(summary),
+                            escape_xml(message)
+                        )
+                        .unwrap();
+                    }
+                    TestOutcome::Ignored => {
+                        xml.push_str(">\n      <skipped/>\n    </testcase>\n");
+                    }
+                }
+            }
+
+            xml.push_str("  </testsuite>\n");
+        }
+
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+}
+
+/// Escape text for use in XML content and attribute values.
+fn escape_xml(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            _ => result.push(ch),
+        }
+    }
+
+    result
+}
+
+/// A runner of tests written in Rhai scripts.
+///
+/// Not available under `no_function` or `no_std`.
+///
+/// Tests are script-defined functions with no parameters that are either named `test_*` or have a
+/// `#[test]` line in their doc-comments (requires the `metadata` feature). A test with an
+/// `#[ignore]` line in its doc-comments is not run.
+///
+/// Each test is run in a new [`Scope`], after running the statements at global level of its
+/// script. A test passes if it returns without error. The assertion functions registered by
+/// [`Engine::register_assertions`] are available.
+///
+/// Under the `metadata` feature, the code examples in doc-comments of script-defined functions and
+/// native Rust functions can also be run as tests (see [`set_doc_tests`][TestRunner::set_doc_tests]
+/// and [`set_native_doc_tests`][TestRunner::set_native_doc_tests]).
+///
+/// Tests are run in parallel on multiple threads, each with its own [`Engine`].
+///
+/// # Example
+///
+/// ```
+/// use rhai::testing::{TestOutcome, TestRunner};
+///
+/// let mut runner = TestRunner::new();
+///
+/// runner.add_script(
+///     "math",
+///     "
+///         fn test_add() { assert_eq(1 + 2, 3); }
+///         fn test_sub() { assert_eq(3 - 1, 1); }
+///         fn helper() { 42 }
+///     ",
+/// );
+///
+/// let report = runner.run();
+///
+/// assert_eq!(report.passed(), 1);
+/// assert_eq!(report.failed(), 1);
+/// assert!(!report.is_success());
+///
+/// let result = &report.results[1];
+/// assert_eq!(result.case.to_string(), "math::test_sub");
+/// assert!(matches!(result.outcome, TestOutcome::Failed(..)));
+/// ```
+pub struct TestRunner {
+    /// Scripts, with their names.
+    scripts: Vec<(String, String)>,
+    /// Only run tests whose full names contain one of these (if any).
+    filters: Vec<String>,
+    /// Number of threads to run tests on.
+    threads: usize,
+    /// Callback to create an [`Engine`].
+    create_engine: Box<OnCreateTestEngine>,
+    /// Run the code examples in the doc-comments of script-defined functions?
+    #[cfg(feature = "metadata")]
+    doc_tests: bool,
+    /// Run the code examples in the doc-comments of native Rust functions?
+    #[cfg(feature = "metadata")]
+    native_doc_tests: bool,
+}
+
+impl fmt::Debug for TestRunner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestRunner")
+            .field("scripts", &self.scripts.len())
+            .field("filters", &self.filters)
+            .field("threads", &self.threads)
+            .finish()
+    }
+}
+
+impl Default for TestRunner {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestRunner {
+    /// Create a new [`TestRunner`] that runs tests with [`Engine::new`], on as many threads as
+    /// the available parallelism.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            scripts: Vec::new(),
+            filters: Vec::new(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            create_engine: Box::new(Engine::new),
+            #[cfg(feature = "metadata")]
+            doc_tests: false,
+            #[cfg(feature = "metadata")]
+            native_doc_tests: false,
+        }
+    }
+    /// Set the callback to create an [`Engine`] for running tests, e.g. to register custom
+    /// functions and types.
+    ///
+    /// The assertion functions are registered into each [`Engine`] created.
+    #[inline(always)]
+    pub fn set_engine_factory(
+        &mut self,
+        callback: impl Fn() -> Engine + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.create_engine = Box::new(callback);
+        self
+    }
+    /// Set the number of threads to run tests on. Zero is the same as one.
+    #[inline(always)]
+    pub fn set_threads(&mut self, threads: usize) -> &mut Self {
+        self.threads = threads.max(1);
+        self
+    }
+    /// Only run tests whose full names (`script::name`) contain the text.
+    ///
+    /// If there are multiple filters, tests matching any of them are run.
+    #[inline(always)]
+    pub fn add_filter(&mut self, filter: impl Into<String>) -> &mut Self {
+        self.filters.push(filter.into());
+        self
+    }
+    /// _(metadata)_ Also run the code examples in the doc-comments of script-defined functions as
+    /// tests (default `false`). Exported under the `metadata` feature only.
+    ///
+    /// Code examples are fenced code blocks marked `rhai` (e.g. ` ```rhai `). They are run with the
+    /// functions defined in the same script. Code blocks marked `rhai,ignore` are not run, and
+    /// those marked `rhai,no_run` are only compiled.
+    #[cfg(feature = "metadata")]
+    #[inline(always)]
+    pub fn set_doc_tests(&mut self, enable: bool) -> &mut Self {
+        self.doc_tests = enable;
+        self
+    }
+    /// _(metadata)_ Also run the code examples in the doc-comments of native Rust functions
+    /// registered into the [`Engine`] as tests (default `false`). Exported under the `metadata`
+    /// feature only.
+    ///
+    /// This includes functions in the global namespace, in static modules and in packages
+    /// (including plugin modules defined via `#[export_module]`), but not those in the standard
+    /// packages.
+    #[cfg(feature = "metadata")]
+    #[inline(always)]
+    pub fn set_native_doc_tests(&mut self, enable: bool) -> &mut Self {
+        self.native_doc_tests = enable;
+        self
+    }
+    /// Add a script containing tests.
+    #[inline(always)]
+    pub fn add_script(&mut self, name: impl Into<String>, script: impl Into<String>) -> &mut Self {
+        self.scripts.push((name.into(), script.into()));
+        self
+    }
+    /// Add a script file containing tests, named by its path.
+    pub fn add_file(&mut self, path: impl AsRef<Path>) -> RhaiResultOf<&mut Self> {
+        let path = path.as_ref();
+
+        let mut script = fs::read_to_string(path).map_err(|err| {
+            ERR::ErrorSystem(
+                format!("Cannot open script file '{}'", path.to_string_lossy()),
+                err.into(),
+            )
+        })?;
+
+        // Blank out any shebang line, keeping line numbers
+        if script.starts_with("#!") {
+            let end = script.find('\n').unwrap_or(script.len());
+            script.replace_range(..end, "");
+        }
+
+        Ok(self.add_script(path.to_string_lossy(), script))
+    }
+    /// Create an [`Engine`] for running tests.
+    fn create_engine(&self) -> Engine {
+        let mut engine = (self.create_engine)();
+        engine.register_assertions();
+        engine
+    }
+    /// Discover the tests in all scripts, in order of script and position, with the scripts that
+    /// fail to compile.
+    ///
+    /// Filters are applied.
+    #[must_use]
+    pub fn discover(&self) -> (Vec<TestCase>, Vec<(String, String)>) {
+        let engine = self.create_engine();
+        let mut tests = Vec::new();
+        let mut errors = Vec::new();
+
+        for (name, script) in &self.scripts {
+            let ast = match engine.compile(script) {
+                Ok(ast) => ast,
+                Err(err) => {
+                    errors.push((name.clone(), err.to_string()));
+                    continue;
+                }
+            };
+
+            #[allow(unused_mut)]
+            let mut script_tests = discover_tests(name, &ast);
+
+            #[cfg(feature = "metadata")]
+            if self.doc_tests {
+                script_tests.extend(discover_script_doc_tests(name, script, &ast));
+            }
+
+            script_tests.sort_by_key(|t| (t.pos.line(), t.pos.position()));
+            tests.extend(script_tests);
+        }
+
+        #[cfg(feature = "metadata")]
+        if self.native_doc_tests {
+            tests.extend(discover_native_doc_tests(&engine));
+        }
+
+        tests.retain(|t| self.is_selected(t));
+
+        (tests, errors)
+    }
+    /// Is a test selected by the filters?
+    fn is_selected(&self, test: &TestCase) -> bool {
+        if self.filters.is_empty() {
+            return true;
+        }
+        let name = test.to_string();
+        self.filters.iter().any(|f| name.contains(f.as_str()))
+    }
+    /// Run all tests.
+    #[must_use]
+    pub fn run(&self) -> TestReport {
+        let start = Instant::now();
+        let (tests, errors) = self.discover();
+
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(vec![None; tests.len()]);
+
+        let worker = || {
+            let engine = self.create_engine();
+            let mut asts = BTreeMap::<&str, AST>::new();
+
+            loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+
+                let test = match tests.get(index) {
+                    Some(test) => test,
+                    None => break,
+                };
+
+                let result = self.run_test(&engine, &mut asts, test);
+                results.lock().unwrap()[index] = Some(result);
+            }
+        };
+
+        let threads = self.threads.min(tests.len());
+
+        if threads <= 1 {
+            worker();
+        } else {
+            thread::scope(|s| {
+                for _ in 0..threads {
+                    s.spawn(worker);
+                }
+            });
+        }
+
+        TestReport {
+            results: results
+                .into_inner()
+                .unwrap()
+                .into_iter()
+                .flatten()
+                .collect(),
+            errors,
+            duration: start.elapsed(),
+        }
+    }
+    /// Run a test, compiling its script if not yet compiled.
+    fn run_test<'s>(
+        &'s self,
+        engine: &Engine,
+        asts: &mut BTreeMap<&'s str, AST>,
+        test: &TestCase,
+    ) -> TestResult {
+        let start = Instant::now();
+
+        let outcome = if test.ignored {
+            TestOutcome::Ignored
+        } else {
+            let script = self.scripts.iter().find(|(name, ..)| *name == test.source);
+
+            if let Some((name, script)) = script {
+                if !asts.contains_key(name.as_str()) {
+                    if let Ok(ast) = engine.compile(script) {
+                        asts.insert(name, ast);
+                    }
+                }
+            }
+
+            let ast = asts.get(test.source.as_str());
+
+            let result = match test.kind {
+                TestKind::Function => match ast {
+                    Some(ast) => {
+                        let scope = &mut Scope::new();
+                        let options = CallFnOptions::new();
+                        engine
+                            .call_fn_with_options::<Dynamic>(options, scope, ast, &test.name, ())
+                            .map(|_| ())
+                    }
+                    None => {
+                        Err(ERR::ErrorRuntime("Script does not compile".into(), test.pos).into())
+                    }
+                },
+                TestKind::DocTest {
+                    ref code, no_run, ..
+                } => run_doc_test(engine, ast, code, no_run).map_err(|mut err| {
+                    // Map the position within the code example to the position in the script
+                    if let (Some(start), Some(line)) = (test.pos.line(), err.position().line()) {
+                        let pos = err.position().position().unwrap_or(0);
+                        err.set_position(Position::new((start + line - 1) as u16, pos as u16));
+                    }
+                    err
+                }),
+            };
+
+            match result {
+                Ok(..) => TestOutcome::Passed,
+                Err(err) => TestOutcome::Failed(err.to_string()),
+            }
+        };
+
+        TestResult {
+            case: test.clone(),
+            outcome,
+            duration: start.elapsed(),
+        }
+    }
+}
+
+/// Run a code example, with the functions defined in the script (if any).
+fn run_doc_test(
+    engine: &Engine,
+    script: Option<&AST>,
+    code: &str,
+    no_run: bool,
+) -> RhaiResultOf<()> {
+    let mut ast = engine.compile(code)?;
+
+    if no_run {
+        return Ok(());
+    }
+
+    if let Some(script) = script {
+        ast = script.clone_functions_only().merge(&ast);
+    }
+
+    engine.run_ast_with_scope(&mut Scope::new(), &ast)
+}
+
+/// Discover the tests in an [`AST`], in order of position.
+fn discover_tests(source: &str, ast: &AST) -> Vec<TestCase> {
+    let mut tests: Vec<_> = ast
+        .shared_lib()
+        .iter_script_fn()
+        .filter(|(.., num_params, _fn_def)| {
+            #[cfg(not(feature = "no_object"))]
+            if _fn_def.this_type.is_some() {
+                return false;
+            }
+            *num_params == 0
+        })
+        .filter_map(|(.., fn_def)| {
+            #[cfg(feature = "metadata")]
+            let has_attribute = |attr: &str| {
+                fn_def.comments.iter().any(|comment| {
+                    comment
+                        .lines()
+                        .any(|line| line.trim_start_matches(['/', '*']).trim().starts_with(attr))
+                })
+            };
+            #[cfg(not(feature = "metadata"))]
+            let has_attribute = |_: &str| false;
+
+            if !fn_def.name.starts_with(TEST_PREFIX) && !has_attribute(TEST_ATTRIBUTE) {
+                return None;
+            }
+
+            Some(TestCase {
+                source: source.to_string(),
+                name: fn_def.name.to_string(),
+                pos: fn_def.body.position(),
+                ignored: has_attribute(IGNORE_ATTRIBUTE),
+                kind: TestKind::Function,
+            })
+        })
+        .collect();
+
+    tests.sort_by_key(|t| (t.pos.line(), t.pos.position()));
+    tests
+}
+
+/// A code example in a doc-comment.
+#[cfg(feature = "metadata")]
+struct Example {
+    /// Line of the first line of code within the doc-comment (0-based).
+    line: usize,
+    /// Code of the example.
+    code: String,
+    /// Is the example marked `ignore`?
+    ignored: bool,
+    /// Is the example marked `no_run`?
+    no_run: bool,
+}
+
+/// Extract the code examples (fenced code blocks marked `rhai`) in a doc-comment.
+#[cfg(feature = "metadata")]
+fn extract_examples(comment: &str) -> Vec<Example> {
+    let mut examples = Vec::new();
+    let mut fence: Option<(&str, Option<Example>)> = None;
+
+    for (index, text) in doc_comment_lines(comment).enumerate() {
+        let trimmed = text.trim_start();
+
+        match fence {
+            // End of code block
+            Some((marker, ref mut example)) if trimmed.starts_with(marker) => {
+                examples.extend(example.take());
+                fence = None;
+            }
+            Some((.., Some(ref mut example))) => {
+                example.code.push_str(text);
+                example.code.push('\n');
+            }
+            Some((.., None)) => (),
+            // Start of code block
+            None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                let marker = &trimmed[..3];
+                let attributes: Vec<_> = trimmed[3..]
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+
+                let example = (attributes.first() == Some(&"rhai")).then(|| Example {
+                    line: index + 1,
+                    code: String::new(),
+                    ignored: attributes.contains(&"ignore"),
+                    no_run: attributes.contains(&"no_run"),
+                });
+
+                fence = Some((marker, example));
+            }
+            None => (),
+        }
+    }
+
+    examples
+}
+
+/// Find the line (1-based) of a doc-comment in a script, as the last match before a line.
+#[cfg(feature = "metadata")]
+fn find_comment_line(script: &str, comment: &str, before: usize) -> Option<usize> {
+    let lines: Vec<_> = script.lines().map(str::trim).collect();
+    let comment: Vec<_> = comment.lines().map(str::trim).collect();
+    let end = before.saturating_sub(1).min(lines.len());
+
+    (0..=end.checked_sub(comment.len())?)
+        .rev()
+        .find(|&start| lines[start..start + comment.len()] == comment[..])
+        .map(|start| start + 1)
+}
+
+/// Discover the code examples in the doc-comments of the functions defined in a script.
+#[cfg(feature = "metadata")]
+fn discover_script_doc_tests(source: &str, script: &str, ast: &AST) -> Vec<TestCase> {
+    let mut tests = Vec::new();
+
+    for (.., fn_def) in ast.shared_lib().iter_script_fn() {
+        let fn_line = fn_def.body.position().line().unwrap_or(usize::MAX);
+        let mut index = 0;
+
+        for comment in &fn_def.comments {
+            let start = find_comment_line(script, comment, fn_line);
+
+            for example in extract_examples(comment) {
+                index += 1;
+
+                let pos = match start {
+                    Some(start) => Position::new((start + example.line) as u16, 0),
+                    None => Position::NONE,
+                };
+
+                tests.push(TestCase {
+                    source: source.to_string(),
+                    name: fn_def.name.to_string(),
+                    pos,
+                    ignored: example.ignored,
+                    kind: TestKind::DocTest {
+                        code: example.code,
+                        index,
+                        no_run: example.no_run,
+                    },
+                });
+            }
+        }
+    }
+
+    tests
+}
+
+/// Discover the code examples in the doc-comments of native Rust functions registered into an
+/// [`Engine`], excluding the standard packages.
+#[cfg(feature = "metadata")]
+fn discover_native_doc_tests(engine: &Engine) -> Vec<TestCase> {
+    fn collect<'a>(
+        namespace: &str,
+        module: &'a crate::Module,
+        functions: &mut Vec<(String, &'a crate::module::FuncMetadata)>,
+    ) {
+        functions.extend(
+            module
+                .iter_fn()
+                .filter(|(f, m)| !f.is_script() && !m.comments.is_empty())
+                .map(|(.., m)| (format!("{namespace}{}", m.name), m)),
+        );
+
+        #[cfg(not(feature = "no_module"))]
+        for (name, m) in module.iter_sub_modules() {
+            collect(&format!("{namespace}{name}::"), m, functions);
+        }
+    }
+
+    let mut functions = Vec::new();
+
+    if let Some(global_namespace) = engine.global_modules.first() {
+        collect("", global_namespace, &mut functions);
+    }
+
+    for m in engine
+        .global_modules
+        .iter()
+        .skip(1)
+        .filter(|m| !m.is_internal() && !m.is_standard_lib())
+    {
+        collect("", m, &mut functions);
+    }
+
+    #[cfg(not(feature = "no_module"))]
+    for (name, m) in &engine.global_sub_modules {
+        collect(&format!("{name}::"), m, &mut functions);
+    }
+
+    // Sort by name and signature, as functions are not kept in order
+    functions.sort_by_cached_key(|(name, m)| (name.clone(), m.gen_signature(Into::into)));
+
+    let mut tests = Vec::new();
+
+    for (name, metadata) in functions {
+        let examples = metadata.comments.iter().flat_map(|c| extract_examples(c));
+
+        for (index, example) in examples.enumerate() {
+            tests.push(TestCase {
+                source: NATIVE_SOURCE.to_string(),
+                name: name.clone(),
+                pos: Position::NONE,
+                ignored: example.ignored,
+                kind: TestKind::DocTest {
+                    code: example.code,
+                    index: index + 1,
+                    no_run: example.no_run,
+                },
+            });
+        }
+    }
+
+    tests
+}
+
+/// Are two values equal, according to the `==` operator?
+///
+/// Values of types without the `==` operator are compared by type and debug format.
+fn values_equal(context: &NativeCallContext, left: &Dynamic, right: &Dynamic) -> bool {
+    match context.call_native_fn::<bool>(crate::OP_EQUALS, (left.clone(), right.clone())) {
+        Ok(result) => result,
+        Err(..) => {
+            left.type_name() == right.type_name() && format!("{left:?}") == format!("{right:?}")
+        }
+    }
+}
+
+/// Describe the differences between two values, recursing into arrays and object maps.
+#[allow(unused_variables)]
+fn diff_values(
+    context: &NativeCallContext,
+    path: &str,
+    left: &Dynamic,
+    right: &Dynamic,
+    diff: &mut Vec<String>,
+) {
+    #[cfg(not(feature = "no_index"))]
+    if let (Some(l), Some(r)) = (
+        left.read_lock::<crate::Array>(),
+        right.read_lock::<crate::Array>(),
+    ) {
+        for i in 0..l.len().max(r.len()) {
+            let path = format!("{path}[{i}]");
+
+            match (l.get(i), r.get(i)) {
+                (Some(x), Some(y)) if !values_equal(context, x, y) => {
+                    diff_values(context, &path, x, y, diff)
+                }
+                (Some(x), None) => diff.push(format!("{path}: {x:?} (left only)")),
+                (None, Some(y)) => diff.push(format!("{path}: {y:?} (right only)")),
+                _ => (),
+            }
+        }
+        return;
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    if let (Some(l), Some(r)) = (
+        left.read_lock::<crate::Map>(),
+        right.read_lock::<crate::Map>(),
+    ) {
+        for key in l.keys().chain(r.keys().filter(|k| !l.contains_key(*k))) {
+            let path = format!("{path}.{key}");
+
+            match (l.get(key), r.get(key)) {
+                (Some(x), Some(y)) if !values_equal(context, x, y) => {
+                    diff_values(context, &path, x, y, diff)
+                }
+                (Some(x), None) => diff.push(format!("{path}: {x:?} (left only)")),
+                (None, Some(y)) => diff.push(format!("{path}: {y:?} (right only)")),
+                _ => (),
+            }
+        }
+        return;
+    }
+
+    let path = if path.is_empty() { "value" } else { path };
+    diff.push(format!("{path}: {left:?} != {right:?}"));
+}
+
+/// Create the error of a failed assertion.
+fn assertion_failed(context: &NativeCallContext, message: impl Into<String>) -> Box<ERR> {
+    ERR::ErrorRuntime(message.into().into(), context.position()).into()
+}
+
+/// Check that two values are equal, describing the differences if not.
+fn check_eq(
+    context: &NativeCallContext,
+    left: &Dynamic,
+    right: &Dynamic,
+    message: Option<&str>,
+) -> RhaiResultOf<()> {
+    if values_equal(context, left, right) {
+        return Ok(());
+    }
+
+    let mut text = match message {
+        Some(message) => format!("assertion failed: {message}"),
+        None => "assertion failed: `left == right`".to_string(),
+    };
+    write!(text, "\n  left: {left:?}\n right: {right:?}").unwrap();
+
+    let mut diff = Vec::new();
+    diff_values(context, "", left, right, &mut diff);
+
+    // Only show the differences within arrays and object maps
+    if diff.len() > 1 || diff.first().map_or(false, |d| !d.starts_with("value:")) {
+        text.push_str("\n  diff:");
+        for d in diff {
+            write!(text, "\n    {d}").unwrap();
+        }
+    }
+
+    Err(assertion_failed(context, text))
+}
+
+/// Check that a function call throws an error, optionally containing a text.
+fn check_throws(context: &NativeCallContext, f: &FnPtr, text: Option<&str>) -> RhaiResultOf<()> {
+    match f.call_raw(context, None, []) {
+        Ok(value) => Err(assertion_failed(
+            context,
+            format!("assertion failed: expected an error, but the call returns {value:?}"),
+        )),
+        Err(err) => match text {
+            Some(text) if !err.to_string().contains(text) => Err(assertion_failed(
+                context,
+                format!("assertion failed: expected an error containing {text:?}\n error: {err}"),
+            )),
+            _ => Ok(()),
+        },
+    }
+}
+
+impl Engine {
+    /// Register the assertion functions for tests written in Rhai scripts:
+    ///
+    /// * `assert(condition)` and `assert(condition, message)`
+    /// * `assert_eq(left, right)` and `assert_eq(left, right, message)`, which describe the
+    ///   differences between arrays and object maps element by element
+    /// * `assert_ne(left, right)` and `assert_ne(left, right, message)`
+    /// * `assert_throws(fn_ptr)` and `assert_throws(fn_ptr, text)`, which check that calling a
+    ///   function pointer throws an error (containing a text)
+    ///
+    /// A failed assertion throws an error with a description of the failure.
+    ///
+    /// Not available under `no_function` or `no_std`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.register_assertions();
+    ///
+    /// engine.run("assert_eq([1, 2, 3], [1, 2, 3]); assert_throws(|| 1 / 0);")?;
+    ///
+    /// let err = engine.run("assert_eq([1, 2, 3], [1, 5, 3])").unwrap_err();
+    /// assert!(err.to_string().contains("[1]: 2 != 5"));
+    /// # Ok::<_, Box<rhai::EvalAltResult>>(())
+    /// ```
+    pub fn register_assertions(&mut self) -> &mut Self {
+        self.register_fn("assert", |ctx: NativeCallContext, condition: bool| {
+            if condition {
+                Ok(())
+            } else {
+                Err(assertion_failed(&ctx, "assertion failed"))
+            }
+        })
+        .register_fn(
+            "assert",
+            |ctx: NativeCallContext, condition: bool, message: ImmutableString| {
+                if condition {
+                    Ok(())
+                } else {
+                    Err(assertion_failed(
+                        &ctx,
+                        format!("assertion failed: {message}"),
+                    ))
+                }
+            },
+        )
+        .register_fn(
+            "assert_eq",
+            |ctx: NativeCallContext, left: Dynamic, right: Dynamic| {
+                check_eq(&ctx, &left, &right, None)
+            },
+        )
+        .register_fn(
+            "assert_eq",
+            |ctx: NativeCallContext, left: Dynamic, right: Dynamic, message: ImmutableString| {
+                check_eq(&ctx, &left, &right, Some(&message))
+            },
+        )
+        .register_fn(
+            "assert_ne",
+            |ctx: NativeCallContext, left: Dynamic, right: Dynamic| {
+                if values_equal(&ctx, &left, &right) {
+                    let message = format!("assertion failed: `left != right`\n  both: {left:?}");
+                    Err(assertion_failed(&ctx, message))
+                } else {
+                    Ok(())
+                }
+            },
+        )
+        .register_fn(
+            "assert_ne",
+            |ctx: NativeCallContext, left: Dynamic, right: Dynamic, message: ImmutableString| {
+                if values_equal(&ctx, &left, &right) {
+                    let message = format!("assertion failed: {message}\n  both: {left:?}");
+                    Err(assertion_failed(&ctx, message))
+                } else {
+                    Ok(())
+                }
+            },
+        )
+        .register_fn("assert_throws", |ctx: NativeCallContext, f: FnPtr| {
+            check_throws(&ctx, &f, None)
+        })
+        .register_fn(
+            "assert_throws",
+            |ctx: NativeCallContext, f: FnPtr, text: ImmutableString| {
+                check_throws(&ctx, &f, Some(&text))
+            },
+        )
+    }
+}
@@ -745,8 +745,9 @@ impl AST {
     ) -> impl Iterator<Item = (&str, bool, Dynamic)> {
         self.statements().iter().filter_map(move |stmt| match stmt {
             Stmt::Var(x, options, ..)
-                if options.intersects(ASTFlags::CONSTANT) && include_constants
-                    || !options.intersects(ASTFlags::CONSTANT) && include_variables =>
+                if x.4.is_none()
+                    && (options.intersects(ASTFlags::CONSTANT) && include_constants
+                        || !options.intersects(ASTFlags::CONSTANT) && include_variables) =>
             {
                 let (name, expr, ..) = &**x;
                 expr.get_literal_value()
@@ -2,15 +2,17 @@
 
 use super::{ASTFlags, ASTNode, Ident, Namespace, Stmt, StmtBlock};
 use crate::engine::KEYWORD_FN_PTR;
+use crate::eval::FnResolutionCacheEntry;
 use crate::tokenizer::Token;
 use crate::types::dynamic::Union;
 use crate::{
-    calc_fn_hash, Dynamic, FnArgsVec, FnPtr, Identifier, ImmutableString, Position, SmartString,
-    StaticVec, ThinVec, INT,
+    calc_fn_hash, Dynamic, FnArgsVec, FnPtr, Identifier, ImmutableString, Position, Shared,
+    SmartString, StaticVec, ThinVec, INT,
 };
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{
+    any::TypeId,
     collections::BTreeMap,
     fmt,
     fmt::Write,
@@ -179,6 +181,47 @@ impl FnCallHashes {
     }
 }
 
+/// _(internals)_ Native Rust functions that a function call is bound to at compile time.
+/// Exported under the `internals` feature only.
+///
+/// A function call is bound to all native Rust functions in the global namespace of the
+/// [`Engine`][crate::Engine] with the same name and number of parameters, in resolution order.
+///
+/// At runtime, the first function with parameter types matching the types of the arguments exactly
+/// is called without looking up the function resolution cache.  Otherwise (e.g. for functions with
+/// [`Dynamic`] parameters), the function call is resolved normally.
+///
+/// The binding is ignored when functions are later registered into the [`Engine`][crate::Engine].
+#[derive(Debug, Clone)]
+pub struct FnCallBinding {
+    /// Version of the functions registered into the [`Engine`][crate::Engine] when bound.
+    pub version: usize,
+    /// Native hash of the function call when bound.
+    pub hash: u64,
+    /// Bound native Rust functions with their parameter types.
+    pub funcs: StaticVec<(FnArgsVec<TypeId>, FnResolutionCacheEntry)>,
+}
+
+impl Hash for FnCallBinding {
+    #[inline(always)]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        // A binding does not change the meaning of a function call
+        let _ = state;
+    }
+}
+
+impl FnCallBinding {
+    /// Get the bound native Rust function matching the types of the arguments exactly, if any.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, args: &[&mut Dynamic]) -> Option<&FnResolutionCacheEntry> {
+        self.funcs.iter().find_map(|(types, entry)| {
+            (types.len() == args.len() && types.iter().zip(args).all(|(t, a)| *t == a.type_id()))
+                .then_some(entry)
+        })
+    }
+}
+
 /// _(internals)_ A function call.
 /// Exported under the `internals` feature only.
 #[derive(Clone, Hash)]
@@ -195,6 +238,12 @@ pub struct FnCallExpr {
     pub capture_parent_scope: bool,
     /// Is this function call a native operator?
     pub op_token: Option<Token>,
+    /// Is this function call in tail position within the body of a script-defined function?
+    ///
+    /// Set by the optimizer.
+    pub is_tail_call: bool,
+    /// Native Rust functions bound to this function call at compile time, if any.
+    pub binding: Option<Shared<FnCallBinding>>,
 }
 
 impl fmt::Debug for FnCallExpr {
@@ -214,6 +263,9 @@ impl fmt::Debug for FnCallExpr {
         if self.capture_parent_scope {
             ff.field("capture_parent_scope", &self.capture_parent_scope);
         }
+        if self.is_tail_call {
+            ff.field("is_tail_call", &self.is_tail_call);
+        }
         ff.finish()
     }
 }
@@ -565,6 +617,8 @@ impl Expr {
                     args: once(Self::StringConstant(f.fn_name().into(), pos)).collect(),
                     capture_parent_scope: false,
                     op_token: None,
+                    is_tail_call: false,
+                    binding: None,
                 }
                 .into(),
                 pos,
@@ -865,7 +919,7 @@ impl Expr {
                     return false;
                 }
             }
-            Self::FnCall(x, ..) => {
+            Self::FnCall(x, ..) | Self::MethodCall(x, ..) => {
                 for e in &*x.args {
                     if !e.walk(path, on_node) {
                         return false;
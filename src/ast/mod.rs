@@ -7,19 +7,21 @@ pub mod flags;
 pub mod ident;
 pub mod namespace;
 pub mod namespace_none;
+pub mod pattern;
 pub mod script_fn;
 pub mod stmt;
 
 pub use ast::{ASTNode, EncapsulatedEnviron, AST};
 #[cfg(not(feature = "no_custom_syntax"))]
 pub use expr::CustomExpr;
-pub use expr::{BinaryExpr, Expr, FnCallExpr, FnCallHashes};
+pub use expr::{BinaryExpr, Expr, FnCallBinding, FnCallExpr, FnCallHashes};
 pub use flags::{ASTFlags, FnAccess};
 pub use ident::Ident;
 #[cfg(not(feature = "no_module"))]
 pub use namespace::Namespace;
 #[cfg(feature = "no_module")]
 pub use namespace_none::Namespace;
+pub use pattern::Pattern;
 #[cfg(not(feature = "no_function"))]
 pub use script_fn::{ScriptFnMetadata, ScriptFuncDef};
 pub use stmt::{
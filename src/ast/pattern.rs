@@ -0,0 +1,125 @@
+//! Module defining destructuring patterns.
+
+use super::Ident;
+use crate::{Dynamic, ImmutableString, Position};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{fmt, hash::Hash};
+
+/// _(internals)_ A destructuring pattern in a variable definition or a `switch` case.
+/// Exported under the `internals` feature only.
+///
+/// Literal values and type names can only be matched in `switch` cases.
+#[derive(Debug, Clone, Hash)]
+#[non_exhaustive]
+pub enum Pattern {
+    /// `_`
+    Ignore(Position),
+    /// Variable name.
+    Var(Ident),
+    /// Literal value.
+    Value(Box<Dynamic>, Position),
+    /// Type name (e.g. `int`).
+    Type(ImmutableString, Position),
+    /// `[` pattern `,` ... \[`,` `..` \[rest\]\] `]`
+    ///
+    /// The second item is the pattern for the rest of the array, if any: [`Ignore`][Pattern::Ignore]
+    /// for `..` or [`Var`][Pattern::Var] for `..rest`.
+    #[cfg(not(feature = "no_index"))]
+    Array(Box<(crate::StaticVec<Pattern>, Option<Pattern>)>, Position),
+    /// `#{` key \[`:` pattern\] `,` ... \[`,` `..`\] `}`
+    ///
+    /// The second item is `true` if there is a `..` rest, which allows other properties in a
+    /// `switch` case.
+    #[cfg(not(feature = "no_object"))]
+    Map(Box<(crate::StaticVec<(Ident, Pattern)>, bool)>, Position),
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ignore(..) => f.write_str("_"),
+            Self::Var(x) => f.write_str(x.as_str()),
+            Self::Value(x, ..) => write!(f, "{x:?}"),
+            Self::Type(x, ..) => f.write_str(x),
+            #[cfg(not(feature = "no_index"))]
+            Self::Array(x, ..) => {
+                f.write_str("[")?;
+                for (i, pattern) in x.0.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{pattern}")?;
+                }
+                if let Some(ref rest) = x.1 {
+                    if !x.0.is_empty() {
+                        f.write_str(", ")?;
+                    }
+                    match rest {
+                        Self::Ignore(..) => f.write_str("..")?,
+                        _ => write!(f, "..{rest}")?,
+                    }
+                }
+                f.write_str("]")
+            }
+            #[cfg(not(feature = "no_object"))]
+            Self::Map(x, ..) => {
+                f.write_str("#{")?;
+                for (i, (key, pattern)) in x.0.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    match pattern {
+                        Self::Var(v) if v.name == key.name => f.write_str(key.as_str())?,
+                        _ if crate::tokenizer::is_valid_identifier(key.as_str()) => {
+                            write!(f, "{}: {pattern}", key.as_str())?;
+                        }
+                        _ => write!(f, "{:?}: {pattern}", key.as_str())?,
+                    }
+                }
+                if x.1 {
+                    f.write_str(if x.0.is_empty() { ".." } else { ", .." })?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+impl Pattern {
+    /// Get the [position][Position] of this pattern.
+    #[inline]
+    #[must_use]
+    pub fn position(&self) -> Position {
+        match self {
+            Self::Ignore(pos) | Self::Value(.., pos) | Self::Type(.., pos) => *pos,
+            Self::Var(x) => x.pos,
+            #[cfg(not(feature = "no_index"))]
+            Self::Array(.., pos) => *pos,
+            #[cfg(not(feature = "no_object"))]
+            Self::Map(.., pos) => *pos,
+        }
+    }
+    /// Get all the variables defined by this pattern, in order.
+    #[must_use]
+    pub fn variables(&self) -> Vec<&Ident> {
+        let mut vars = Vec::new();
+        self.collect_variables(&mut vars);
+        vars
+    }
+    /// Collect all the variables defined by this pattern into a list.
+    fn collect_variables<'a>(&'a self, vars: &mut Vec<&'a Ident>) {
+        match self {
+            Self::Ignore(..) | Self::Value(..) | Self::Type(..) => (),
+            Self::Var(x) => vars.push(x),
+            #[cfg(not(feature = "no_index"))]
+            Self::Array(x, ..) => {
+                x.0.iter()
+                    .chain(x.1.iter())
+                    .for_each(|p| p.collect_variables(vars))
+            }
+            #[cfg(not(feature = "no_object"))]
+            Self::Map(x, ..) => x.0.iter().for_each(|(_, p)| p.collect_variables(vars)),
+        }
+    }
+}
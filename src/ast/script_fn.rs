@@ -23,6 +23,17 @@ pub struct ScriptFuncDef {
     pub this_type: Option<ImmutableString>,
     /// Names of function parameters.
     pub params: FnArgsVec<ImmutableString>,
+    /// Type annotations of function parameters.
+    ///
+    /// Empty if no parameter has a type annotation, otherwise of the same length as `params`.
+    pub param_types: FnArgsVec<Option<ImmutableString>>,
+    /// Type annotation of the return value, if any.
+    pub return_type: Option<ImmutableString>,
+    /// Is this a generator function (i.e. does its body contain `yield` statements)?
+    ///
+    /// Calling a generator function returns a [`Generator`][crate::Generator] instead of running
+    /// the function body.
+    pub is_generator: bool,
     /// _(metadata)_ Function doc-comments (if any). Exported under the `metadata` feature only.
     ///
     /// Doc-comments are comment lines beginning with `///` or comment blocks beginning with `/**`,
@@ -53,6 +64,9 @@ impl ScriptFuncDef {
             #[cfg(not(feature = "no_object"))]
             this_type: self.this_type.clone(),
             params: self.params.clone(),
+            param_types: self.param_types.clone(),
+            return_type: self.return_type.clone(),
+            is_generator: self.is_generator,
             #[cfg(feature = "metadata")]
             comments: <_>::default(),
         }
@@ -81,10 +95,20 @@ impl fmt::Display for ScriptFuncDef {
             self.name,
             self.params
                 .iter()
-                .map(ImmutableString::as_str)
+                .enumerate()
+                .map(|(i, p)| match self.param_types.get(i) {
+                    Some(Some(typ)) => format!("{p}: {typ}"),
+                    _ => p.to_string(),
+                })
                 .collect::<FnArgsVec<_>>()
                 .join(", ")
-        )
+        )?;
+
+        if let Some(ref typ) = self.return_type {
+            write!(f, " -> {typ}")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -109,6 +133,20 @@ pub struct ScriptFnMetadata<'a> {
         serde(default, skip_serializing_if = "Vec::is_empty")
     )]
     pub params: Vec<&'a str>,
+    /// Type annotations of function parameters.
+    ///
+    /// Empty if no parameter has a type annotation, otherwise of the same length as `params`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub param_types: Vec<Option<&'a str>>,
+    /// Type annotation of the return value, if any.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub return_type: Option<&'a str>,
     /// Function access mode.
     pub access: FnAccess,
     /// Type of `this` pointer, if any.
@@ -160,10 +198,20 @@ impl fmt::Display for ScriptFnMetadata<'_> {
             self.name,
             self.params
                 .iter()
-                .copied()
+                .enumerate()
+                .map(|(i, p)| match self.param_types.get(i) {
+                    Some(Some(typ)) => format!("{p}: {typ}"),
+                    _ => (*p).to_string(),
+                })
                 .collect::<FnArgsVec<_>>()
                 .join(", ")
-        )
+        )?;
+
+        if let Some(typ) = self.return_type {
+            write!(f, " -> {typ}")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -173,6 +221,8 @@ impl<'a> From<&'a ScriptFuncDef> for ScriptFnMetadata<'a> {
         Self {
             name: &value.name,
             params: value.params.iter().map(ImmutableString::as_str).collect(),
+            param_types: value.param_types.iter().map(Option::as_deref).collect(),
+            return_type: value.return_type.as_deref(),
             access: value.access,
             #[cfg(not(feature = "no_object"))]
             this_type: value.this_type.as_deref(),
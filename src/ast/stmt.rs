@@ -1,12 +1,12 @@
 //! Module defining script statements.
 
-use super::{ASTFlags, ASTNode, BinaryExpr, Expr, FnCallExpr, Ident};
+use super::{ASTFlags, ASTNode, BinaryExpr, Expr, FnCallExpr, Ident, Pattern};
 use crate::engine::{KEYWORD_EVAL, OP_EQUALS};
 use crate::func::StraightHashMap;
 use crate::tokenizer::Token;
 use crate::types::dynamic::Union;
 use crate::types::Span;
-use crate::{calc_fn_hash, Dynamic, FnArgsVec, Position, StaticVec, INT};
+use crate::{calc_fn_hash, Dynamic, FnArgsVec, ImmutableString, Position, StaticVec, INT};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{
@@ -337,6 +337,9 @@ pub struct SwitchCasesCollection {
     pub cases: StraightHashMap<CaseBlocksList>,
     /// List of range cases.
     pub ranges: StaticVec<RangeCase>,
+    /// List of structural pattern cases, each with the index of its conditional expression,
+    /// in order.
+    pub patterns: StaticVec<(Pattern, usize)>,
     /// Statements block for the default case (there can be no condition for the default case).
     pub def_case: Option<usize>,
 }
@@ -350,6 +353,7 @@ impl Hash for SwitchCasesCollection {
         self.cases.iter().for_each(|kv| kv.hash(state));
 
         self.ranges.hash(state);
+        self.patterns.hash(state);
         self.def_case.hash(state);
     }
 }
@@ -613,13 +617,28 @@ pub enum Stmt {
     Do(Box<FlowControl>, ASTFlags, Position),
     /// `for` `(` id `,` counter `)` `in` expr `{` stmt `}`
     For(Box<(Ident, Option<Ident>, FlowControl)>, Position),
-    /// \[`export`\] `let`|`const` id `=` expr
+    /// \[`export`\] `let`|`const` id|pattern \[`:` type\] `=` expr
+    ///
+    /// The fourth item is the type annotation of the variable, if any.
+    ///
+    /// The last item is the destructuring pattern, if any, in which case the [`Ident`] holds the
+    /// text of the pattern instead of a variable name.
     ///
     /// ### Flags
     ///
     /// * [`EXPORTED`][ASTFlags::EXPORTED] = `export`  
     /// * [`CONSTANT`][ASTFlags::CONSTANT] = `const`
-    Var(Box<(Ident, Expr, Option<NonZeroUsize>)>, ASTFlags, Position),
+    Var(
+        Box<(
+            Ident,
+            Expr,
+            Option<NonZeroUsize>,
+            Option<ImmutableString>,
+            Option<Box<Pattern>>,
+        )>,
+        ASTFlags,
+        Position,
+    ),
     /// expr op`=` expr
     Assignment(Box<(OpAssignment, BinaryExpr)>),
     /// func `(` expr `,` ... `)`
@@ -647,6 +666,13 @@ pub enum Stmt {
     /// * [`NONE`][ASTFlags::NONE] = `return`
     /// * [`BREAK`][ASTFlags::BREAK] = `throw`
     Return(Option<Box<Expr>>, ASTFlags, Position),
+    /// `yield` expr
+    ///
+    /// Only allowed as a statement inside generator functions.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    Yield(Option<Box<Expr>>, Position),
     /// `import` expr `as` alias
     ///
     /// Not available under `no_module`.
@@ -705,6 +731,9 @@ impl Stmt {
             | Self::TryCatch(..)
             | Self::Assignment(..) => ASTFlags::empty(),
 
+            #[cfg(not(feature = "no_function"))]
+            Self::Yield(..) => ASTFlags::empty(),
+
             #[cfg(not(feature = "no_module"))]
             Self::Import(..) | Self::Export(..) => ASTFlags::empty(),
 
@@ -734,6 +763,9 @@ impl Stmt {
 
             Self::Expr(x) => x.start_position(),
 
+            #[cfg(not(feature = "no_function"))]
+            Self::Yield(.., pos) => *pos,
+
             #[cfg(not(feature = "no_module"))]
             Self::Import(.., pos) => *pos,
             #[cfg(not(feature = "no_module"))]
@@ -766,6 +798,9 @@ impl Stmt {
                 x.set_position(new_pos);
             }
 
+            #[cfg(not(feature = "no_function"))]
+            Self::Yield(.., pos) => *pos = new_pos,
+
             #[cfg(not(feature = "no_module"))]
             Self::Import(.., pos) => *pos = new_pos,
             #[cfg(not(feature = "no_module"))]
@@ -795,6 +830,9 @@ impl Stmt {
 
             Self::Var(..) | Self::Assignment(..) | Self::BreakLoop(..) | Self::Return(..) => false,
 
+            #[cfg(not(feature = "no_function"))]
+            Self::Yield(..) => false,
+
             #[cfg(not(feature = "no_module"))]
             Self::Import(..) | Self::Export(..) => false,
 
@@ -829,6 +867,9 @@ impl Stmt {
             | Self::BreakLoop(..)
             | Self::Return(..) => false,
 
+            #[cfg(not(feature = "no_function"))]
+            Self::Yield(..) => false,
+
             #[cfg(not(feature = "no_module"))]
             Self::Import(..) | Self::Export(..) => false,
 
@@ -860,6 +901,10 @@ impl Stmt {
                         let block = &sw.expressions[r.index()];
                         block.lhs.is_pure() && block.rhs.is_pure()
                     })
+                    && sw.patterns.iter().all(|&(_, index)| {
+                        let block = &sw.expressions[index];
+                        block.lhs.is_pure() && block.rhs.is_pure()
+                    })
                     && sw.def_case.is_some()
                     && sw.expressions[sw.def_case.unwrap()].rhs.is_pure()
             }
@@ -883,6 +928,8 @@ impl Stmt {
             Self::Var(..) | Self::Assignment(..) | Self::FnCall(..) => false,
             Self::Block(block, ..) => block.iter().all(Self::is_pure),
             Self::BreakLoop(..) | Self::Return(..) => false,
+            #[cfg(not(feature = "no_function"))]
+            Self::Yield(..) => false,
             Self::TryCatch(x, ..) => {
                 x.expr.is_pure()
                     && x.body.iter().all(Self::is_pure)
@@ -935,7 +982,8 @@ impl Stmt {
     #[must_use]
     pub fn is_internally_pure(&self) -> bool {
         match self {
-            Self::Var(x, ..) => x.1.is_pure(),
+            // Destructuring may fail
+            Self::Var(x, ..) => x.4.is_none() && x.1.is_pure(),
 
             Self::Expr(e) => match &**e {
                 Expr::Stmt(s) => s.iter().all(Self::is_internally_pure),
@@ -960,6 +1008,16 @@ impl Stmt {
     pub const fn is_control_flow_break(&self) -> bool {
         matches!(self, Self::Return(..) | Self::BreakLoop(..))
     }
+    /// Does this statement contain a `yield` statement?
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[must_use]
+    pub fn contains_yield(&self) -> bool {
+        !self.walk(&mut Vec::new(), &mut |path| {
+            !matches!(path.last(), Some(ASTNode::Stmt(Self::Yield(..))))
+        })
+    }
     /// Return this [`Stmt`], replacing it with [`Stmt::Noop`].
     #[inline(always)]
     #[must_use]
@@ -981,11 +1039,7 @@ impl Stmt {
         }
 
         match self {
-            Self::Var(x, ..) => {
-                if !x.1.walk(path, on_node) {
-                    return false;
-                }
-            }
+            Self::Var(x, ..) if !x.1.walk(path, on_node) => return false,
             Self::If(x, ..) => {
                 if !x.expr.walk(path, on_node) {
                     return false;
@@ -1029,8 +1083,23 @@ impl Stmt {
                         return false;
                     }
                 }
+                for &(_, index) in &sw.patterns {
+                    let block = &sw.expressions[index];
+
+                    if !block.lhs.walk(path, on_node) {
+                        return false;
+                    }
+                    if !block.rhs.walk(path, on_node) {
+                        return false;
+                    }
+                }
                 if let Some(index) = sw.def_case {
-                    if !sw.expressions[index].lhs.walk(path, on_node) {
+                    let block = &sw.expressions[index];
+
+                    if !block.lhs.walk(path, on_node) {
+                        return false;
+                    }
+                    if !block.rhs.walk(path, on_node) {
                         return false;
                     }
                 }
@@ -1099,6 +1168,8 @@ impl Stmt {
                     return false;
                 }
             }
+            #[cfg(not(feature = "no_function"))]
+            Self::Yield(Some(e), ..) if !e.walk(path, on_node) => return false,
             #[cfg(not(feature = "no_module"))]
             Self::Import(x, ..) => {
                 if !x.0.walk(path, on_node) {
@@ -0,0 +1,919 @@
+//! Debug Adapter Protocol (DAP) server for Rhai scripts, communicating over stdin/stdout.
+
+#[cfg(not(any(feature = "no_object", feature = "no_index")))]
+mod dap {
+    use rhai::debugger::{BreakPoint, BreakPointOptions, DebuggerCommand, DebuggerEvent};
+    use rhai::{
+        ASTNode, Array, Dynamic, Engine, EvalAltResult, EvalContext, ImmutableString, Locked, Map,
+        Position, Scope, Shared, Stmt, AST, INT,
+    };
+
+    use std::{
+        io::{stdin, stdout, BufRead, Read, Write},
+        ops::DerefMut,
+        path::Path,
+        process::exit,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    /// The only thread reported to the client.
+    const THREAD_ID: INT = 1;
+
+    /// Sequence number of the next message sent to the client.
+    static SEQ: AtomicUsize = AtomicUsize::new(1);
+
+    /// Build an object map from a list of fields.
+    fn object<'a>(fields: impl IntoIterator<Item = (&'a str, Dynamic)>) -> Map {
+        fields.into_iter().map(|(k, v)| (k.into(), v)).collect()
+    }
+
+    /// Get the options of a break-point from its `condition`, `hitCondition` and `logMessage`.
+    fn break_point_options(bp: &Map) -> Result<BreakPointOptions, String> {
+        let text = |key| get_str(bp, key).filter(|s| !s.trim().is_empty());
+
+        Ok(BreakPointOptions {
+            condition: text("condition").map(Into::into),
+            hit_condition: text("hitCondition").map(|s| s.parse()).transpose()?,
+            log_message: text("logMessage").map(Into::into),
+            ..Default::default()
+        })
+    }
+
+    /// Get a string field of an object map.
+    fn get_str(map: &Map, key: &str) -> Option<String> {
+        map.get(key).and_then(|v| v.clone().into_string().ok())
+    }
+
+    /// Get an integer field of an object map.
+    fn get_int(map: &Map, key: &str) -> Option<INT> {
+        map.get(key).and_then(|v| v.as_int().ok())
+    }
+
+    /// Get an object map field of an object map, or an empty map.
+    fn get_map(map: &Map, key: &str) -> Map {
+        map.get(key)
+            .and_then(|v| v.clone().try_cast::<Map>())
+            .unwrap_or_default()
+    }
+
+    /// Get an array field of an object map, or an empty array.
+    fn get_array(map: &Map, key: &str) -> Array {
+        map.get(key)
+            .and_then(|v| v.clone().try_cast::<Array>())
+            .unwrap_or_default()
+    }
+
+    /// Send a message to the client.
+    fn send(kind: &str, mut message: Map) {
+        let seq = SEQ.fetch_add(1, Ordering::Relaxed) as INT;
+        message.insert("seq".into(), seq.into());
+        message.insert("type".into(), kind.into());
+
+        let json = rhai::format_map_as_json(&message);
+        let mut out = stdout().lock();
+        write!(out, "Content-Length: {}\r\n\r\n{json}", json.len()).expect("couldn't write stdout");
+        out.flush().expect("couldn't flush stdout");
+    }
+
+    /// Send an event to the client.
+    fn send_event(event: &str, body: Map) {
+        send(
+            "event",
+            object([("event", event.into()), ("body", body.into())]),
+        );
+    }
+
+    /// Send the response to a request to the client.
+    fn respond(request: &Map, result: Result<Map, String>) {
+        let mut response = object([
+            ("request_seq", get_int(request, "seq").unwrap_or(0).into()),
+            (
+                "command",
+                get_str(request, "command").unwrap_or_default().into(),
+            ),
+            ("success", result.is_ok().into()),
+        ]);
+        match result {
+            Ok(body) => response.insert("body".into(), body.into()),
+            Err(message) => response.insert("message".into(), message.into()),
+        };
+        send("response", response);
+    }
+
+    /// Send text to the debug console of the client.
+    fn send_output(category: &str, text: &str) {
+        send_event(
+            "output",
+            object([
+                ("category", category.into()),
+                ("output", format!("{text}\n").into()),
+            ]),
+        );
+    }
+
+    /// Normalize a file path so that break-points and sources refer to the same script.
+    fn normalize(path: &str) -> String {
+        match Path::new(path).canonicalize() {
+            Ok(path) => path.to_string_lossy().into_owned(),
+            Err(..) => path.into(),
+        }
+    }
+
+    /// How to resume evaluation after the script is stopped.
+    #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    enum Resume {
+        /// Run until the next break-point.
+        Continue,
+        /// Run to the next line, diving into functions.
+        StepIn,
+        /// Run to the next line, stepping over functions.
+        Next,
+    }
+
+    impl Resume {
+        /// Get the [`DebuggerCommand`] stepping through the current line.
+        const fn command(self) -> DebuggerCommand {
+            match self {
+                Self::Continue | Self::StepIn => DebuggerCommand::StepInto,
+                Self::Next => DebuggerCommand::StepOver,
+            }
+        }
+    }
+
+    /// Get an identifier for an [`AST` Node][ASTNode] that is unique within the [`AST`].
+    fn node_id(node: ASTNode) -> usize {
+        match node {
+            ASTNode::Stmt(stmt) => stmt as *const Stmt as usize,
+            ASTNode::Expr(expr) => expr as *const rhai::Expr as usize,
+            _ => unreachable!(),
+        }
+    }
+
+    /// The line the script is stopped at, which is skipped when resuming.
+    #[derive(Debug, Clone)]
+    struct Skip {
+        /// How to resume evaluation.
+        mode: Resume,
+        /// Identifier of the node the script is stopped at.
+        node: usize,
+        /// Source of the line.
+        source: Option<String>,
+        /// Line number.
+        line: Option<usize>,
+        /// Depth of the function call stack.
+        depth: usize,
+    }
+
+    /// Why the script is stopped.
+    #[derive(Debug, Clone, Copy)]
+    enum Stop {
+        /// Script evaluation starts.
+        Entry,
+        /// Stopped after a step.
+        Step,
+        /// Stopped at a break-point.
+        BreakPoint(usize),
+    }
+
+    /// Container of variables shown to the client, identified by a variables reference.
+    #[derive(Debug, Clone)]
+    enum Handle {
+        /// Variables in the current scope.
+        Locals,
+        /// Arguments of a function call in the call stack.
+        Arguments(usize),
+        /// Items of an array or properties of an object map.
+        Value(Dynamic),
+    }
+
+    /// State of a debugging session.
+    struct Session {
+        /// Engine parsing JSON messages, without the debugger.
+        json: Engine,
+        /// Normalized path of the script, used as its source.
+        path: String,
+        /// Stop at the beginning of the script?
+        stop_on_entry: bool,
+        /// Break-points with their IDs and options.
+        break_points: Vec<(INT, BreakPoint, BreakPointOptions)>,
+        /// ID of the next break-point.
+        next_id: INT,
+        /// Are the break-points changed since they are last copied into the debugger?
+        dirty: bool,
+        /// The line being stepped over, if any.
+        skip: Option<Skip>,
+        /// Containers of variables shown to the client, indexed by variables reference minus one.
+        handles: Vec<Handle>,
+        /// Has the client disconnected?
+        disconnected: bool,
+    }
+
+    /// Lock a [`Session`], returning `None` if it is already locked.
+    fn try_lock(session: &Locked<Session>) -> Option<impl DerefMut<Target = Session> + '_> {
+        #[cfg(not(feature = "sync"))]
+        return session.try_borrow_mut().ok();
+        #[cfg(feature = "sync")]
+        return session.try_write().ok();
+    }
+
+    impl Session {
+        /// Create a new [`Session`].
+        fn new() -> Self {
+            Self {
+                json: Engine::new_raw(),
+                path: String::new(),
+                stop_on_entry: false,
+                break_points: Vec::new(),
+                next_id: 1,
+                dirty: true,
+                skip: None,
+                handles: Vec::new(),
+                disconnected: false,
+            }
+        }
+
+        /// Read the next message from the client, returning `None` at the end of input.
+        fn read_message(&self) -> Option<Map> {
+            let mut input = stdin().lock();
+            let mut line = String::new();
+
+            loop {
+                let mut len = None;
+
+                // Read headers
+                loop {
+                    line.clear();
+                    if input.read_line(&mut line).ok()? == 0 {
+                        return None;
+                    }
+                    match line.trim().split_once(':') {
+                        Some((name, value)) if name.eq_ignore_ascii_case("Content-Length") => {
+                            len = value.trim().parse::<usize>().ok();
+                        }
+                        None if line.trim().is_empty() && len.is_some() => break,
+                        _ => (),
+                    }
+                }
+
+                // Read content
+                let mut content = vec![0; len.unwrap()];
+                input.read_exact(&mut content).ok()?;
+
+                match self
+                    .json
+                    .parse_json(String::from_utf8_lossy(&content), true)
+                {
+                    Ok(message) => return Some(message),
+                    Err(err) => eprintln!("Invalid message: {err}"),
+                }
+            }
+        }
+
+        /// Load and compile the script to debug.
+        fn launch(&mut self, engine: &Engine, args: &Map) -> Result<AST, String> {
+            let program = get_str(args, "program").ok_or("No script file specified.")?;
+            let path = normalize(&program);
+
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|err| format!("Error reading script file: {program}\n{err}"))?;
+
+            // Skip shebang, keeping line numbers
+            let script = if contents.starts_with("#!") {
+                &contents[contents.find('\n').unwrap_or(contents.len())..]
+            } else {
+                &contents[..]
+            };
+
+            let mut ast = engine.compile(script).map_err(|err| err.to_string())?;
+            ast.set_source(path.as_str());
+
+            self.path = path;
+            self.stop_on_entry = args
+                .get("stopOnEntry")
+                .and_then(|v| v.as_bool().ok())
+                .unwrap_or(false);
+
+            Ok(ast)
+        }
+
+        /// Handle a request for break-points in a source.
+        fn set_break_points(&mut self, args: &Map) -> Result<Map, String> {
+            let path = get_str(&get_map(args, "source"), "path").ok_or("No source path.")?;
+            let _source: Option<ImmutableString> = Some(normalize(&path).into());
+
+            // Remove existing break-points in the source
+            #[cfg(not(feature = "no_position"))]
+            self.break_points.retain(|(_, bp, _)| match bp {
+                BreakPoint::AtPosition { source, .. } => *source != _source,
+                _ => true,
+            });
+            self.dirty = true;
+
+            let mut result = Array::new();
+
+            for bp in get_array(args, "breakpoints") {
+                let Some(bp) = bp.try_cast::<Map>() else {
+                    continue;
+                };
+                let line = get_int(&bp, "line").unwrap_or(0);
+
+                let _options = match break_point_options(&bp) {
+                    Ok(options) => options,
+                    Err(err) => {
+                        result.push(
+                            object([
+                                ("verified", false.into()),
+                                ("line", line.into()),
+                                ("message", err.into()),
+                            ])
+                            .into(),
+                        );
+                        continue;
+                    }
+                };
+
+                #[cfg(not(feature = "no_position"))]
+                if line > 0 && line <= u16::MAX as INT {
+                    let id = self.next_id;
+                    self.next_id += 1;
+                    self.break_points.push((
+                        id,
+                        BreakPoint::AtPosition {
+                            source: _source.clone(),
+                            pos: Position::new(line as u16, 0),
+                            enabled: true,
+                        },
+                        _options,
+                    ));
+                    result.push(
+                        object([
+                            ("id", id.into()),
+                            ("verified", true.into()),
+                            ("line", line.into()),
+                        ])
+                        .into(),
+                    );
+                    continue;
+                }
+
+                result.push(
+                    object([
+                        ("verified", false.into()),
+                        ("line", line.into()),
+                        ("message", "Invalid line number.".into()),
+                    ])
+                    .into(),
+                );
+            }
+
+            Ok(object([("breakpoints", result.into())]))
+        }
+
+        /// Handle a request for break-points at function calls.
+        fn set_function_break_points(&mut self, args: &Map) -> Map {
+            // Remove existing function break-points
+            self.break_points
+                .retain(|(_, bp, _)| !matches!(bp, BreakPoint::AtFunctionName { .. }));
+            self.dirty = true;
+
+            let mut result = Array::new();
+
+            for bp in get_array(args, "breakpoints") {
+                let bp = bp.try_cast::<Map>().unwrap_or_default();
+                let name = get_str(&bp, "name").unwrap_or_default();
+                let name = name.trim();
+
+                if name.is_empty() {
+                    result.push(object([("verified", false.into())]).into());
+                    continue;
+                }
+
+                let options = match break_point_options(&bp) {
+                    Ok(options) => options,
+                    Err(err) => {
+                        let message = ("message", err.into());
+                        result.push(object([("verified", false.into()), message]).into());
+                        continue;
+                    }
+                };
+
+                let id = self.next_id;
+                self.next_id += 1;
+                self.break_points.push((
+                    id,
+                    BreakPoint::AtFunctionName {
+                        name: name.into(),
+                        enabled: true,
+                    },
+                    options,
+                ));
+                result.push(object([("id", id.into()), ("verified", true.into())]).into());
+            }
+
+            object([("breakpoints", result.into())])
+        }
+
+        /// Handle requests that do not need a stopped script.
+        ///
+        /// Returns `false` if the request is not handled.
+        fn handle(&mut self, request: &Map) -> bool {
+            let args = get_map(request, "arguments");
+
+            match get_str(request, "command").as_deref().unwrap_or("") {
+                "threads" => {
+                    let thread = object([("id", THREAD_ID.into()), ("name", "main".into())]);
+                    let threads: Array = vec![thread.into()];
+                    respond(request, Ok(object([("threads", threads.into())])));
+                }
+                "setBreakpoints" => respond(request, self.set_break_points(&args)),
+                "setFunctionBreakpoints" => {
+                    respond(request, Ok(self.set_function_break_points(&args)))
+                }
+                "setExceptionBreakpoints" => respond(request, Ok(Map::new())),
+                "pause" => respond(request, Ok(Map::new())),
+                _ => return false,
+            }
+
+            true
+        }
+
+        /// Copy the break-points into the debugger if they are changed.
+        fn sync_break_points(&mut self, context: &mut EvalContext) {
+            if self.dirty {
+                let debugger = context.global_runtime_state_mut().debugger_mut();
+                debugger.clear_break_points();
+
+                for (_, bp, options) in &self.break_points {
+                    debugger.add_break_point_with_options(bp.clone(), options.clone());
+                }
+                self.dirty = false;
+            }
+        }
+
+        /// Main callback for debugging.
+        fn on_debugger(
+            &mut self,
+            mut context: EvalContext,
+            event: DebuggerEvent,
+            node: ASTNode,
+            source: Option<&str>,
+            pos: Position,
+        ) -> Result<DebuggerCommand, Box<EvalAltResult>> {
+            if let DebuggerEvent::Start = event {
+                // A new debugger is created for each run
+                self.dirty = true;
+                self.skip = None;
+            }
+
+            self.sync_break_points(&mut context);
+
+            let depth = context.global_runtime_state().debugger().call_stack().len();
+
+            let reason = match event {
+                DebuggerEvent::Start if self.stop_on_entry => Stop::Entry,
+                DebuggerEvent::Start => match context.trigger_break_point(node) {
+                    Some(n) => Stop::BreakPoint(n),
+                    None => return Ok(DebuggerCommand::Continue),
+                },
+                DebuggerEvent::Step | DebuggerEvent::BreakPoint(..) => {
+                    match self.skip.take() {
+                        Some(skip) => {
+                            // Keep running through the line being skipped, unless the node is
+                            // run again (e.g. in a loop)
+                            let is_same_line = matches!(node, ASTNode::Stmt(Stmt::Noop(..)))
+                                || (pos.line() == skip.line
+                                    && source == skip.source.as_deref()
+                                    && depth == skip.depth
+                                    && node_id(node) != skip.node);
+
+                            if is_same_line {
+                                let command = match event {
+                                    DebuggerEvent::BreakPoint(..) => DebuggerCommand::Continue,
+                                    _ => skip.mode.command(),
+                                };
+                                self.skip = Some(skip);
+                                return Ok(command);
+                            }
+
+                            match event {
+                                DebuggerEvent::BreakPoint(n) => Stop::BreakPoint(n),
+                                _ if skip.mode != Resume::Continue => Stop::Step,
+                                // Steps are not checked against break-points
+                                _ => match context.trigger_break_point(node) {
+                                    Some(n) => Stop::BreakPoint(n),
+                                    None => return Ok(DebuggerCommand::Continue),
+                                },
+                            }
+                        }
+                        None => match event {
+                            DebuggerEvent::BreakPoint(n) => Stop::BreakPoint(n),
+                            _ => Stop::Step,
+                        },
+                    }
+                }
+                DebuggerEvent::FunctionExitWithValue(..)
+                | DebuggerEvent::FunctionExitWithError(..) => match self.skip {
+                    Some(ref skip) => return Ok(skip.mode.command()),
+                    None => Stop::Step,
+                },
+                _ => return Ok(DebuggerCommand::Continue),
+            };
+
+            self.stop(&mut context, reason, node, source, pos, depth)
+        }
+
+        /// Notify the client that the script is stopped, then handle requests until it is resumed.
+        fn stop(
+            &mut self,
+            context: &mut EvalContext,
+            reason: Stop,
+            node: ASTNode,
+            source: Option<&str>,
+            pos: Position,
+            depth: usize,
+        ) -> Result<DebuggerCommand, Box<EvalAltResult>> {
+            let mut body = object([
+                ("threadId", THREAD_ID.into()),
+                ("allThreadsStopped", true.into()),
+            ]);
+
+            let reason = match reason {
+                Stop::Entry => "entry",
+                Stop::Step => "step",
+                Stop::BreakPoint(n) => match self.break_points.get(n) {
+                    Some((id, bp, ..)) => {
+                        let ids: Array = vec![(*id).into()];
+                        body.insert("hitBreakpointIds".into(), ids.into());
+                        match bp {
+                            BreakPoint::AtFunctionName { .. } => "function breakpoint",
+                            _ => "breakpoint",
+                        }
+                    }
+                    None => "breakpoint",
+                },
+            };
+            body.insert("reason".into(), reason.into());
+
+            send_event("stopped", body);
+
+            let resume = |session: &mut Self, mode| {
+                session.handles.clear();
+                session.skip = Some(Skip {
+                    mode,
+                    node: node_id(node),
+                    source: source.map(Into::into),
+                    line: pos.line(),
+                    depth,
+                });
+            };
+
+            loop {
+                let Some(request) = self.read_message() else {
+                    self.disconnected = true;
+                    return Err(EvalAltResult::ErrorTerminated(Dynamic::UNIT, pos).into());
+                };
+                let args = get_map(&request, "arguments");
+
+                match get_str(&request, "command").as_deref().unwrap_or("") {
+                    "continue" => {
+                        respond(&request, Ok(object([("allThreadsContinued", true.into())])));
+                        resume(self, Resume::Continue);
+                        return Ok(DebuggerCommand::StepInto);
+                    }
+                    "next" => {
+                        respond(&request, Ok(Map::new()));
+                        resume(self, Resume::Next);
+                        return Ok(DebuggerCommand::StepOver);
+                    }
+                    "stepIn" => {
+                        respond(&request, Ok(Map::new()));
+                        resume(self, Resume::StepIn);
+                        return Ok(DebuggerCommand::StepInto);
+                    }
+                    "stepOut" => {
+                        respond(&request, Ok(Map::new()));
+                        self.handles.clear();
+                        return Ok(DebuggerCommand::FunctionExit);
+                    }
+                    "disconnect" | "terminate" => {
+                        respond(&request, Ok(Map::new()));
+                        self.disconnected = true;
+                        return Err(EvalAltResult::ErrorTerminated(Dynamic::UNIT, pos).into());
+                    }
+                    "stackTrace" => respond(&request, Ok(self.stack_trace(context, source, pos))),
+                    "scopes" => {
+                        let frame = get_int(&args, "frameId").unwrap_or(0) as usize;
+                        respond(&request, Ok(self.scopes(context, frame)));
+                    }
+                    "variables" => {
+                        let reference = get_int(&args, "variablesReference").unwrap_or(0);
+                        respond(&request, self.variables(context, reference));
+                    }
+                    "evaluate" => {
+                        let frame = get_int(&args, "frameId").unwrap_or(0) as usize;
+                        let expr = get_str(&args, "expression").unwrap_or_default();
+                        respond(&request, self.evaluate(context, frame, &expr));
+                    }
+                    _ => {
+                        if !self.handle(&request) {
+                            respond(&request, Err("Unsupported request.".into()));
+                        }
+                        self.sync_break_points(context);
+                    }
+                }
+            }
+        }
+
+        /// Build a source object for the client.
+        fn source_object(&self, source: Option<&str>) -> Dynamic {
+            let path = match source {
+                Some(source) if !source.is_empty() => normalize(source),
+                _ => self.path.clone(),
+            };
+            let name = Path::new(&path)
+                .file_name()
+                .map_or_else(|| path.clone(), |name| name.to_string_lossy().into_owned());
+
+            object([("name", name.into()), ("path", path.into())]).into()
+        }
+
+        /// Handle a stack trace request.
+        ///
+        /// Frame IDs count from the innermost function call, which is always zero.
+        fn stack_trace(&self, context: &EvalContext, source: Option<&str>, pos: Position) -> Map {
+            let call_stack = context.global_runtime_state().debugger().call_stack();
+            let mut frames = Array::new();
+            let (mut source, mut pos) = (source, pos);
+
+            for id in 0..=call_stack.len() {
+                let name = match call_stack.len() - id {
+                    0 => "<main>".into(),
+                    n => call_stack[n - 1].fn_name.to_string(),
+                };
+
+                frames.push(
+                    object([
+                        ("id", (id as INT).into()),
+                        ("name", name.into()),
+                        ("source", self.source_object(source)),
+                        ("line", (pos.line().unwrap_or(0) as INT).into()),
+                        ("column", (pos.position().unwrap_or(1) as INT).into()),
+                    ])
+                    .into(),
+                );
+
+                // The caller is stopped at the function call
+                if let Some(frame) = call_stack.get(call_stack.len().wrapping_sub(id + 1)) {
+                    source = frame.source.as_deref();
+                    pos = frame.pos;
+                }
+            }
+
+            let total = frames.len() as INT;
+            object([
+                ("stackFrames", frames.into()),
+                ("totalFrames", total.into()),
+            ])
+        }
+
+        /// Add a container of variables, returning its variables reference.
+        fn add_handle(&mut self, handle: Handle) -> INT {
+            self.handles.push(handle);
+            self.handles.len() as INT
+        }
+
+        /// Handle a scopes request.
+        fn scopes(&mut self, context: &EvalContext, frame: usize) -> Map {
+            let call_stack = context.global_runtime_state().debugger().call_stack();
+
+            let (name, handle) = if frame == 0 {
+                ("Locals", Handle::Locals)
+            } else if frame < call_stack.len() {
+                ("Arguments", Handle::Arguments(call_stack.len() - 1 - frame))
+            } else {
+                let scopes: Array = Vec::new();
+                return object([("scopes", scopes.into())]);
+            };
+
+            let reference = self.add_handle(handle);
+            let scope = object([
+                ("name", name.into()),
+                ("variablesReference", reference.into()),
+                ("expensive", false.into()),
+            ]);
+            let scopes: Array = vec![scope.into()];
+
+            object([("scopes", scopes.into())])
+        }
+
+        /// Build a variable object for the client.
+        fn variable(&mut self, name: &str, value: &Dynamic) -> Dynamic {
+            let value = value.flatten_clone();
+            let reference = if value.is_map() || value.is_array() {
+                self.add_handle(Handle::Value(value.clone()))
+            } else {
+                0
+            };
+
+            object([
+                ("name", name.into()),
+                ("value", format!("{value:?}").into()),
+                ("type", value.type_name().into()),
+                ("variablesReference", reference.into()),
+            ])
+            .into()
+        }
+
+        /// Handle a variables request.
+        fn variables(&mut self, context: &EvalContext, reference: INT) -> Result<Map, String> {
+            let handle = match reference {
+                n if n > 0 && n as usize <= self.handles.len() => {
+                    self.handles[n as usize - 1].clone()
+                }
+                _ => return Err(format!("Invalid variables reference: {reference}")),
+            };
+
+            let mut variables = Array::new();
+
+            match handle {
+                Handle::Locals => {
+                    // Only show the latest of shadowed variables
+                    let mut names = Vec::new();
+
+                    for (name, .., value) in context.scope().iter_raw() {
+                        if !names.contains(&name) {
+                            names.push(name);
+                            variables.push(self.variable(name, value));
+                        }
+                    }
+                    variables.reverse();
+
+                    if let Some(this) = context.this_ptr() {
+                        variables.push(self.variable("this", this));
+                    }
+                }
+                Handle::Arguments(n) => {
+                    let call_stack = context.global_runtime_state().debugger().call_stack();
+
+                    for (i, arg) in call_stack[n].args.iter().enumerate() {
+                        variables.push(self.variable(&format!("#{}", i + 1), arg));
+                    }
+                }
+                Handle::Value(value) => {
+                    if let Some(map) = value.read_lock::<Map>() {
+                        for (name, value) in map.iter() {
+                            variables.push(self.variable(name, value));
+                        }
+                    } else if let Some(array) = value.read_lock::<Array>() {
+                        for (i, value) in array.iter().enumerate() {
+                            variables.push(self.variable(&format!("[{i}]"), value));
+                        }
+                    }
+                }
+            }
+
+            Ok(object([("variables", variables.into())]))
+        }
+
+        /// Handle an evaluate request, running the expression in a stack frame.
+        fn evaluate(
+            &mut self,
+            context: &mut EvalContext,
+            _frame: usize,
+            expr: &str,
+        ) -> Result<Map, String> {
+            #[cfg(not(feature = "no_function"))]
+            let value = context.eval_script_in_frame(_frame, expr);
+            #[cfg(feature = "no_function")]
+            let value = context.eval_script(expr);
+            let value = value.map_err(|err| err.to_string())?;
+
+            let variable = self.variable("", &value).cast::<Map>();
+
+            Ok(object([
+                ("result", variable["value"].clone()),
+                ("type", variable["type"].clone()),
+                ("variablesReference", variable["variablesReference"].clone()),
+            ]))
+        }
+    }
+
+    pub fn main() {
+        // Initialize scripting engine
+        let mut engine = Engine::new();
+
+        #[cfg(not(feature = "no_optimize"))]
+        engine.set_optimization_level(rhai::OptimizationLevel::None);
+
+        // Standard output is used for messages
+        engine.on_print(|text| send_output("stdout", text));
+        engine.on_debug(|text, source, pos| match source {
+            Some(source) => send_output("console", &format!("{source} @ {pos:?} | {text}")),
+            None => send_output("console", &format!("{pos:?} | {text}")),
+        });
+
+        let session = Shared::new(Locked::new(Session::new()));
+        let dbg_session = session.clone();
+
+        #[allow(deprecated)]
+        engine.register_debugger(
+            |_, debugger| debugger,
+            move |context, event, node, source, pos| {
+                // The session is locked while evaluating expressions for the client
+                match try_lock(&dbg_session) {
+                    Some(mut session) => session.on_debugger(context, event, node, source, pos),
+                    None => Ok(DebuggerCommand::Continue),
+                }
+            },
+        );
+
+        let mut ast = None;
+        let mut configured = false;
+
+        loop {
+            let Some(request) = try_lock(&session).unwrap().read_message() else {
+                break;
+            };
+            let args = get_map(&request, "arguments");
+
+            match get_str(&request, "command").as_deref().unwrap_or("") {
+                "initialize" => {
+                    let capabilities = object([
+                        ("supportsConfigurationDoneRequest", true.into()),
+                        ("supportsFunctionBreakpoints", true.into()),
+                        ("supportsConditionalBreakpoints", true.into()),
+                        ("supportsHitConditionalBreakpoints", true.into()),
+                        ("supportsLogPoints", true.into()),
+                        ("supportsEvaluateForHovers", true.into()),
+                        ("supportTerminateDebuggee", true.into()),
+                    ]);
+                    respond(&request, Ok(capabilities));
+                    send_event("initialized", Map::new());
+                }
+                "launch" | "attach" => {
+                    match try_lock(&session).unwrap().launch(&engine, &args) {
+                        Ok(script) => {
+                            // Set a file module resolver without caching, relative to the script
+                            #[cfg(not(feature = "no_module"))]
+                            {
+                                let path = script.source().unwrap_or("");
+                                let dir = Path::new(path).parent().unwrap_or(Path::new("."));
+                                let mut resolver =
+                                    rhai::module_resolvers::FileModuleResolver::new_with_path(dir);
+                                resolver.enable_cache(false);
+                                engine.set_module_resolver(resolver);
+                            }
+
+                            ast = Some(script);
+                            respond(&request, Ok(Map::new()));
+                        }
+                        Err(err) => respond(&request, Err(err)),
+                    }
+                }
+                "configurationDone" => {
+                    configured = true;
+                    respond(&request, Ok(Map::new()));
+                }
+                "disconnect" | "terminate" => {
+                    respond(&request, Ok(Map::new()));
+                    break;
+                }
+                _ => {
+                    if !try_lock(&session).unwrap().handle(&request) {
+                        respond(&request, Err("The script is not stopped.".into()));
+                    }
+                }
+            }
+
+            if !configured {
+                continue;
+            }
+
+            // Evaluate
+            if let Some(ast) = ast.take() {
+                let exit_code: INT = match engine.run_ast_with_scope(&mut Scope::new(), &ast) {
+                    Ok(..) => 0,
+                    Err(..) if try_lock(&session).unwrap().disconnected => exit(0),
+                    Err(err) => {
+                        send_output("stderr", &err.to_string());
+                        1
+                    }
+                };
+
+                send_event("exited", object([("exitCode", exit_code.into())]));
+                send_event("terminated", Map::new());
+            }
+        }
+    }
+}
+
+#[cfg(not(any(feature = "no_object", feature = "no_index")))]
+fn main() {
+    dap::main()
+}
+
+#[cfg(any(feature = "no_object", feature = "no_index"))]
+fn main() {
+    eprintln!("rhai-dap requires object maps and arrays for JSON messages.");
+    std::process::exit(1);
+}
@@ -1,4 +1,8 @@
-use rhai::debugger::{BreakPoint, DebuggerCommand, DebuggerEvent};
+#[cfg(not(feature = "no_position"))]
+use rhai::debugger::Trace;
+use rhai::debugger::{
+    BreakPoint, BreakPointOptions, DebuggerCommand, DebuggerEvent, HitCondition, WatchPoint,
+};
 use rhai::{Dynamic, Engine, EvalAltResult, ImmutableString, Position, Scope, INT};
 
 use std::{
@@ -126,18 +130,38 @@ fn print_debug_help() {
     println!("print, p               => print all variables de-duplicated");
     println!("print/p this           => print the `this` pointer");
     println!("print/p <variable>     => print the current value of a variable");
+    println!(
+        "eval <script...>       => evaluate a script (e.g. an expression) in the current frame"
+    );
+    println!("set <target> = <expr>  => assign to a variable, `this` property or indexed element");
     #[cfg(not(feature = "no_module"))]
     println!("imports                => print all imported modules");
     println!("node                   => print the current AST node");
     println!("list, l                => print the current source line");
     println!("list/l <line#>         => print a source line");
     println!("backtrace, bt          => print the current call-stack");
+    #[cfg(not(feature = "no_function"))]
+    println!("frame                  => print the current call-stack frame");
+    #[cfg(not(feature = "no_function"))]
+    println!("frame <#>              => select a call-stack frame for `eval` (0 = current)");
     println!("info break, i b        => print all break-points");
     println!("enable/en <bp#>        => enable a break-point");
     println!("disable/dis <bp#>      => disable a break-point");
     println!("delete, d              => delete all break-points");
     println!("delete/d <bp#>         => delete a break-point");
+    println!("cond <bp#> <expr...>   => break only when an expression is true");
+    println!("cond <bp#>             => remove the condition of a break-point");
+    println!("hits <bp#> <==|>=|%> n => break only when the hit count meets a condition");
+    println!("hits <bp#>             => remove the hit condition of a break-point");
+    println!(
+        "log <bp#> <message...> => print a message (with {{expressions}}) instead of breaking"
+    );
+    println!("log <bp#>              => remove the log message of a break-point");
     #[cfg(not(feature = "no_position"))]
+    println!("info watch, i w        => print all watch-points");
+    println!("watch <path...>        => watch a variable, `this` property or indexed element");
+    println!("unwatch                => delete all watch-points");
+    println!("unwatch <wp#>          => delete a watch-point");
     println!("break, b               => set a new break-point at the current position");
     #[cfg(not(feature = "no_position"))]
     println!("break/b <line#>        => set a new break-point at a line number");
@@ -159,9 +183,43 @@ fn print_debug_help() {
     println!();
 }
 
+/// Get the text after skipping a number of words.
+fn skip_words(input: &str, n: usize) -> &str {
+    let mut text = input.trim();
+
+    for _ in 0..n {
+        text = text.trim_start();
+        text = &text[text.find(char::is_whitespace).unwrap_or(text.len())..];
+    }
+
+    text.trim()
+}
+
+/// Get the options of a break-point by its number, printing an error if it is invalid.
+fn get_break_point_options<'a>(
+    context: &'a mut rhai::EvalContext,
+    n: &str,
+) -> Option<&'a mut BreakPointOptions> {
+    let debugger = context.global_runtime_state_mut().debugger_mut();
+
+    match n.parse::<usize>() {
+        Ok(n) if (1..=debugger.break_points().len()).contains(&n) => {
+            Some(debugger.break_point_options_mut(n - 1))
+        }
+        Ok(n) => {
+            eprintln!("\x1b[31mInvalid break-point: {n}\x1b[39m");
+            None
+        }
+        Err(..) => {
+            eprintln!("\x1b[31mInvalid break-point: '{n}'\x1b[39m");
+            None
+        }
+    }
+}
+
 // Load script to debug.
-fn load_script(engine: &Engine) -> (rhai::AST, String) {
-    if let Some(filename) = env::args().nth(1) {
+fn load_script(engine: &Engine, filename: Option<String>) -> (rhai::AST, String) {
+    if let Some(filename) = filename {
         let mut contents = String::new();
 
         let filename = match Path::new(&filename).canonicalize() {
@@ -286,6 +344,13 @@ fn debug_callback(
                 err
             )
         }
+        DebuggerEvent::WatchPoint(n, old_value, new_value) => {
+            let path = &context.global_runtime_state().debugger().watch_points()[n].path;
+            println!(
+                "! Watch-point #{} {path} changed: {old_value:?} => {new_value:?}",
+                n + 1
+            );
+        }
         _ => unreachable!(),
     }
 
@@ -295,6 +360,10 @@ fn debug_callback(
     // Read stdin for commands
     let mut input = String::new();
 
+    // Call-stack frame for evaluation
+    #[cfg(not(feature = "no_function"))]
+    let mut frame = 0;
+
     loop {
         print!("dbg> ");
 
@@ -351,6 +420,45 @@ fn debug_callback(
                     Some(value) => println!("=> {value:?}"),
                     None => eprintln!("Variable not found: {var_name}"),
                 },
+                ["eval", ..] => {
+                    let script = skip_words(&input, 1);
+
+                    #[cfg(not(feature = "no_function"))]
+                    let result = context.eval_script_in_frame(frame, script);
+                    #[cfg(feature = "no_function")]
+                    let result = context.eval_script(script);
+
+                    match result {
+                        Ok(value) => println!("=> {value:?}"),
+                        Err(err) => eprintln!("\x1b[31m{err}\x1b[39m"),
+                    }
+                }
+                ["set", ..] => {
+                    let script = skip_words(&input, 1);
+
+                    #[cfg(not(feature = "no_function"))]
+                    let is_current_frame = frame == 0;
+                    #[cfg(feature = "no_function")]
+                    let is_current_frame = true;
+
+                    match script.split_once('=') {
+                        Some(..) if !is_current_frame => {
+                            eprintln!(
+                                "\x1b[31mOnly variables in the current frame can be set.\x1b[39m"
+                            )
+                        }
+                        Some((target, ..)) => {
+                            match context
+                                .eval_script(script)
+                                .and_then(|_| context.eval_script(target))
+                            {
+                                Ok(value) => println!("{} = {value:?}", target.trim()),
+                                Err(err) => eprintln!("\x1b[31m{err}\x1b[39m"),
+                            }
+                        }
+                        None => eprintln!("\x1b[31mUsage: set <target> = <expr>\x1b[39m"),
+                    }
+                }
                 ["print" | "p"] => {
                     println!("{}", context.scope().clone_visible());
                     if let Some(value) = context.this_ptr() {
@@ -386,23 +494,63 @@ fn debug_callback(
                         println!("{frame}")
                     }
                 }
-                ["info" | "i", "break" | "b"] => Iterator::for_each(
-                    context
-                        .global_runtime_state()
-                        .debugger()
-                        .break_points()
-                        .iter()
-                        .enumerate(),
-                    |(i, bp)| match bp {
-                        #[cfg(not(feature = "no_position"))]
-                        rhai::debugger::BreakPoint::AtPosition { pos, .. } => {
-                            let line_num = format!("[{}] line ", i + 1);
-                            print!("{line_num}");
-                            print_source(lines, *pos, line_num.len(), (0, 0));
+                #[cfg(not(feature = "no_function"))]
+                ["frame", ..] => {
+                    let call_stack = context.global_runtime_state().debugger().call_stack();
+                    let n = match skip_words(&input, 1) {
+                        "" => Ok(frame),
+                        n => n.parse::<usize>(),
+                    };
+
+                    match n {
+                        Ok(0) if call_stack.is_empty() => {
+                            frame = 0;
+                            println!("#0 <main>");
                         }
-                        _ => println!("[{}] {bp}", i + 1),
-                    },
-                ),
+                        Ok(n) if n < call_stack.len() => {
+                            frame = n;
+                            println!("#{n} {}", call_stack[call_stack.len() - 1 - n]);
+                        }
+                        Ok(n) => eprintln!("\x1b[31mInvalid frame: {n}\x1b[39m"),
+                        Err(err) => eprintln!("\x1b[31mInvalid frame: {err}\x1b[39m"),
+                    }
+                }
+                ["info" | "i", "break" | "b"] => {
+                    let debugger = context.global_runtime_state().debugger();
+
+                    for (i, bp) in debugger.break_points().iter().enumerate() {
+                        let options = debugger.break_point_options(i);
+
+                        let indent = match bp {
+                            #[cfg(not(feature = "no_position"))]
+                            rhai::debugger::BreakPoint::AtPosition { pos, .. } => {
+                                let line_num = format!("[{}] line ", i + 1);
+                                print!("{line_num}");
+                                print_source(lines, *pos, line_num.len(), (0, 0));
+                                if let Some(text) = options.map(|o| o.to_string()) {
+                                    if !text.is_empty() {
+                                        println!(
+                                            "{0:>1$}{2}",
+                                            "",
+                                            line_num.len(),
+                                            text.trim_start()
+                                        );
+                                    }
+                                }
+                                line_num.len()
+                            }
+                            _ => {
+                                let text = options.map(|o| o.to_string()).unwrap_or_default();
+                                println!("[{}] {bp}{text}", i + 1);
+                                4
+                            }
+                        };
+
+                        if let Some(hits) = options.map(|o| o.hits).filter(|&n| n > 0) {
+                            println!("{0:>1$}hit {hits} time(s)", "", indent);
+                        }
+                    }
+                }
                 ["enable" | "en", n] => {
                     if let Ok(n) = n.parse::<usize>() {
                         let range = 1..=context
@@ -460,8 +608,7 @@ fn debug_callback(
                             context
                                 .global_runtime_state_mut()
                                 .debugger_mut()
-                                .break_points_mut()
-                                .remove(n - 1);
+                                .remove_break_point(n - 1);
                             println!("Break-point #{n} deleted.")
                         } else {
                             eprintln!("\x1b[31mInvalid break-point: {n}\x1b[39m");
@@ -474,10 +621,102 @@ fn debug_callback(
                     context
                         .global_runtime_state_mut()
                         .debugger_mut()
-                        .break_points_mut()
-                        .clear();
+                        .clear_break_points();
                     println!("All break-points deleted.");
                 }
+                ["info" | "i", "watch" | "w"] => context
+                    .global_runtime_state()
+                    .debugger()
+                    .watch_points()
+                    .iter()
+                    .enumerate()
+                    .for_each(|(i, wp)| println!("[{}] {wp}", i + 1)),
+                ["watch", ..] => {
+                    let path = skip_words(&input, 1);
+
+                    if path.is_empty() {
+                        eprintln!("\x1b[31mMissing watch-point path\x1b[39m");
+                    } else {
+                        let wp = WatchPoint::new(path);
+                        println!("Watch-point added for {wp}");
+                        context
+                            .global_runtime_state_mut()
+                            .debugger_mut()
+                            .watch_points_mut()
+                            .push(wp);
+                    }
+                }
+                ["unwatch", n] => {
+                    let watch_points = context
+                        .global_runtime_state_mut()
+                        .debugger_mut()
+                        .watch_points_mut();
+
+                    match n.parse::<usize>() {
+                        Ok(n) if (1..=watch_points.len()).contains(&n) => {
+                            watch_points.remove(n - 1);
+                            println!("Watch-point #{n} deleted.")
+                        }
+                        Ok(n) => eprintln!("\x1b[31mInvalid watch-point: {n}\x1b[39m"),
+                        Err(..) => eprintln!("\x1b[31mInvalid watch-point: '{n}'\x1b[39m"),
+                    }
+                }
+                ["unwatch"] => {
+                    context
+                        .global_runtime_state_mut()
+                        .debugger_mut()
+                        .watch_points_mut()
+                        .clear();
+                    println!("All watch-points deleted.");
+                }
+                ["cond", n, ..] => {
+                    let condition = skip_words(&input, 2);
+
+                    if let Some(options) = get_break_point_options(&mut context, n) {
+                        if condition.is_empty() {
+                            options.condition = None;
+                            println!("Condition removed from break-point #{n}.");
+                        } else {
+                            options.condition = Some(condition.into());
+                            println!("Break-point #{n} now breaks if {condition}");
+                        }
+                    }
+                }
+                ["hits", n, ..] => {
+                    let hit_condition = skip_words(&input, 2);
+
+                    if hit_condition.is_empty() {
+                        if let Some(options) = get_break_point_options(&mut context, n) {
+                            options.hit_condition = None;
+                            options.hits = 0;
+                            println!("Hit condition removed from break-point #{n}.");
+                        }
+                    } else {
+                        match hit_condition.parse::<HitCondition>() {
+                            Ok(value) => {
+                                if let Some(options) = get_break_point_options(&mut context, n) {
+                                    options.hit_condition = Some(value);
+                                    options.hits = 0;
+                                    println!("Break-point #{n} now breaks when hits {value}");
+                                }
+                            }
+                            Err(err) => eprintln!("\x1b[31m{err}\x1b[39m"),
+                        }
+                    }
+                }
+                ["log", n, ..] => {
+                    let message = skip_words(&input, 2);
+
+                    if let Some(options) = get_break_point_options(&mut context, n) {
+                        if message.is_empty() {
+                            options.log_message = None;
+                            println!("Log message removed from break-point #{n}.");
+                        } else {
+                            options.log_message = Some(message.into());
+                            println!("Break-point #{n} now logs: {message}");
+                        }
+                    }
+                }
                 ["break" | "b", fn_name, args] => {
                     if let Ok(args) = args.parse::<usize>() {
                         let bp = rhai::debugger::BreakPoint::AtFunctionCall {
@@ -590,6 +829,260 @@ fn debug_callback(
     }
 }
 
+/// Print replay help.
+#[cfg(not(feature = "no_position"))]
+fn print_replay_help() {
+    println!("help, h                => print this help");
+    println!("quit, q, exit          => quit");
+    println!("scope, print, p        => print the variables of the current function call");
+    println!("print/p <variable>     => print the recorded value of a variable");
+    println!("calls                  => print the native function calls made by this step");
+    println!("node                   => print the current step");
+    println!("list, l                => print the current source line");
+    println!("step, s                => step forwards");
+    println!("step/s <#steps>        => step forwards a number of steps");
+    println!("back, rs               => step backwards");
+    println!("back/rs <#steps>       => step backwards a number of steps");
+    println!("next, n                => step forwards over function calls");
+    println!("prev, rn               => step backwards over function calls");
+    println!("goto <step#>           => go to a step");
+    println!("break <line#>          => set a new break-point at a line number");
+    println!("info break, i b        => print all break-points");
+    println!("delete, d              => delete all break-points");
+    println!("continue, c            => run forwards until a break-point or the end");
+    println!("reverse, rc            => run backwards until a break-point or the start");
+    println!();
+}
+
+/// Print the current step of a replayed trace.
+#[cfg(not(feature = "no_position"))]
+fn print_step(trace: &Trace, index: usize, lines: &[String], window: (usize, usize)) {
+    let step = &trace.steps[index];
+
+    println!(
+        "\x1b[32m[{}/{}]\x1b[39m {} (level {})",
+        index + 1,
+        trace.len(),
+        step.node,
+        step.level
+    );
+
+    if !step.source.is_empty() || lines.is_empty() {
+        // Print just a line number for imported modules or without the script
+        let source = if step.source.is_empty() {
+            "main script"
+        } else {
+            &step.source
+        };
+        println!("{source} @ {:?}", step.pos);
+    } else if step.pos.line().map_or(false, |line| line <= lines.len()) {
+        print_source(lines, step.pos, 0, window);
+    } else {
+        println!();
+    }
+}
+
+/// Replay a recorded trace, stepping forwards and backwards without running anything.
+#[cfg(not(feature = "no_position"))]
+fn replay(trace: &Trace, lines: &[String]) {
+    if trace.is_empty() {
+        println!("The trace is empty.");
+        return;
+    }
+
+    let mut index = 0;
+    let mut break_points = Vec::<usize>::new();
+    let mut input = String::new();
+
+    println!("Trace of {} steps loaded.", trace.len());
+    println!("Type 'help' for commands list.");
+    println!();
+
+    print_step(trace, index, lines, (0, 0));
+
+    loop {
+        print!("replay> ");
+
+        stdout().flush().expect("couldn't flush stdout");
+
+        input.clear();
+
+        let target = match stdin().read_line(&mut input) {
+            Ok(0) => break,
+            Ok(_) => match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["help" | "h"] => {
+                    print_replay_help();
+                    continue;
+                }
+                ["exit" | "quit" | "q", ..] => break,
+                ["scope" | "print" | "p"] => {
+                    for (i, (name, value)) in trace.variables_at(index).iter().enumerate() {
+                        println!("[{}] {name} = {value}", i + 1);
+                    }
+                    continue;
+                }
+                ["print" | "p", var_name] => {
+                    match trace
+                        .variables_at(index)
+                        .into_iter()
+                        .rev()
+                        .find(|(name, ..)| name == var_name)
+                    {
+                        Some((.., value)) => println!("=> {value}"),
+                        None => eprintln!("Variable not found: {var_name}"),
+                    }
+                    continue;
+                }
+                ["calls"] => {
+                    for call in &trace.steps[index].calls {
+                        match call.result {
+                            Ok(ref value) => println!("{}(...) => {value}", call.name),
+                            Err(ref err) => println!("{}(...) => error: {err}", call.name),
+                        }
+                    }
+                    continue;
+                }
+                ["node"] => {
+                    let step = &trace.steps[index];
+                    println!("{:?}", step);
+                    continue;
+                }
+                ["list" | "l"] => {
+                    print_step(trace, index, lines, (3, 6));
+                    continue;
+                }
+                [] | ["step" | "s"] => Some(index + 1),
+                ["step" | "s", n] if n.parse::<usize>().is_ok() => {
+                    Some(index + n.parse::<usize>().unwrap())
+                }
+                ["back" | "rs"] => index.checked_sub(1),
+                ["back" | "rs", n] if n.parse::<usize>().is_ok() => {
+                    index.checked_sub(n.parse::<usize>().unwrap())
+                }
+                ["next" | "n"] => {
+                    let level = trace.steps[index].level;
+                    (index + 1..trace.len()).find(|&i| trace.steps[i].level <= level)
+                }
+                ["prev" | "rn"] => {
+                    let level = trace.steps[index].level;
+                    (0..index).rev().find(|&i| trace.steps[i].level <= level)
+                }
+                ["goto", n] if n.parse::<usize>().is_ok() => {
+                    n.parse::<usize>().unwrap().checked_sub(1)
+                }
+                ["break", n] if n.parse::<usize>().is_ok() => {
+                    let line = n.parse::<usize>().unwrap();
+                    if line == 0 || (!lines.is_empty() && line > lines.len()) {
+                        eprintln!("\x1b[31mInvalid line: {line}\x1b[39m");
+                    } else {
+                        println!("Break-point added @ {line}");
+                        break_points.push(line);
+                    }
+                    continue;
+                }
+                ["info" | "i", "break" | "b"] => {
+                    for (i, line) in break_points.iter().enumerate() {
+                        println!("[{}] @ {line}", i + 1);
+                    }
+                    continue;
+                }
+                ["delete" | "d"] => {
+                    break_points.clear();
+                    println!("All break-points deleted.");
+                    continue;
+                }
+                ["continue" | "c"] => (index + 1..trace.len())
+                    .find(|&i| {
+                        trace.steps[i]
+                            .pos
+                            .line()
+                            .map_or(false, |n| break_points.contains(&n))
+                    })
+                    .or(Some(trace.len() - 1)),
+                ["reverse" | "rc"] => (0..index)
+                    .rev()
+                    .find(|&i| {
+                        trace.steps[i]
+                            .pos
+                            .line()
+                            .map_or(false, |n| break_points.contains(&n))
+                    })
+                    .or(Some(0)),
+                _ => {
+                    eprintln!("\x1b[31mInvalid replay command: '{}'\x1b[39m", input.trim());
+                    continue;
+                }
+            },
+            Err(err) => panic!("input error: {}", err),
+        };
+
+        match target {
+            Some(n) if n < trace.len() => {
+                index = n;
+                print_step(trace, index, lines, (0, 0));
+            }
+            Some(..) => println!("\x1b[31m! End of trace\x1b[39m"),
+            None => println!("\x1b[32m! Start of trace\x1b[39m"),
+        }
+    }
+}
+
+/// Run a script, recording its execution trace to a file.
+#[cfg(not(feature = "no_position"))]
+fn record(mut engine: Engine, trace_file: &str, filename: Option<String>) {
+    let (ast, script) = load_script(&engine, filename);
+
+    let recorder = rhai::debugger::TraceRecorder::new();
+    engine.register_trace_recorder(&recorder);
+
+    if let Err(err) = engine.run_ast_with_scope(&mut Scope::new(), &ast) {
+        print_error(&script, *err);
+    }
+
+    let trace = recorder.trace();
+
+    match std::fs::write(trace_file, trace.to_string()) {
+        Ok(()) => println!("Trace of {} steps recorded to '{trace_file}'.", trace.len()),
+        Err(err) => {
+            eprintln!("\x1b[31mError writing trace file: {trace_file}\n{err}\x1b[39m");
+            exit(1);
+        }
+    }
+}
+
+/// Load a recorded trace, and the script for its source lines if specified, then replay it.
+#[cfg(not(feature = "no_position"))]
+fn load_trace(trace_file: &str, filename: Option<String>) {
+    let trace = match std::fs::read_to_string(trace_file) {
+        Ok(text) => match text.parse::<Trace>() {
+            Ok(trace) => trace,
+            Err(err) => {
+                eprintln!("\x1b[31mError loading trace file: {trace_file}\n{err}\x1b[39m");
+                exit(1);
+            }
+        },
+        Err(err) => {
+            eprintln!("\x1b[31mError reading trace file: {trace_file}\n{err}\x1b[39m");
+            exit(1);
+        }
+    };
+
+    let lines: Vec<_> = match filename {
+        Some(filename) => match std::fs::read_to_string(&filename) {
+            Ok(script) => script.trim().lines().map(|s| s.to_string()).collect(),
+            Err(err) => {
+                eprintln!("\x1b[31mError reading script file: {filename}\n{err}\x1b[39m");
+                exit(1);
+            }
+        },
+        None => Vec::new(),
+    };
+
+    replay(&trace, &lines);
+
+    println!("Replay terminated. Bye!");
+}
+
 fn main() {
     let title = format!("Rhai Debugger (version {})", env!("CARGO_PKG_VERSION"));
     println!("{title}");
@@ -601,7 +1094,30 @@ fn main() {
     #[cfg(not(feature = "no_optimize"))]
     engine.set_optimization_level(rhai::OptimizationLevel::None);
 
-    let (ast, script) = load_script(&engine);
+    let mut args = env::args().skip(1);
+    let filename = args.next();
+
+    // Record or replay a trace: --record/--replay <trace file> <script file>
+    #[cfg(not(feature = "no_position"))]
+    match filename.as_deref() {
+        Some("--record") => match args.next() {
+            Some(trace_file) => return record(engine, &trace_file, args.next()),
+            None => {
+                eprintln!("\x1b[31mNo trace file specified.\x1b[39m");
+                exit(1);
+            }
+        },
+        Some("--replay") => match args.next() {
+            Some(trace_file) => return load_trace(&trace_file, args.next()),
+            None => {
+                eprintln!("\x1b[31mNo trace file specified.\x1b[39m");
+                exit(1);
+            }
+        },
+        _ => (),
+    }
+
+    let (ast, script) = load_script(&engine, filename);
 
     // Hook up debugger
     let lines: Vec<_> = script.trim().lines().map(|s| s.to_string()).collect();
@@ -0,0 +1,100 @@
+use rhai::Engine;
+
+use std::{env, path::PathBuf, process::exit};
+
+fn print_help() {
+    println!("Usage: rhai-doc [OPTIONS] [FILE]...");
+    println!();
+    println!("Generates API documentation of the Rhai engine and script modules.");
+    println!("Each script file is documented as a script module named after the file.");
+    println!();
+    println!("Options:");
+    println!("  -o, --output <DIR>  output directory (default: doc)");
+    println!("  --markdown          generate Markdown pages instead of an HTML site");
+    println!("  --title <TEXT>      title of the documentation");
+    println!("  --no-std            exclude the standard library");
+    println!("  -h, --help          print this help message");
+}
+
+/// Get the value of an option.
+fn option_value(args: &mut impl Iterator<Item = String>, option: &str) -> String {
+    args.next().unwrap_or_else(|| {
+        eprintln!("Missing value for {option}");
+        exit(1);
+    })
+}
+
+fn main() {
+    let mut output = PathBuf::from("doc");
+    let mut markdown = false;
+    let mut title = None;
+    let mut include_std = true;
+    let mut files = Vec::new();
+
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print_help();
+                exit(0);
+            }
+            "-o" | "--output" => output = PathBuf::from(option_value(&mut args, &arg)),
+            "--markdown" => markdown = true,
+            "--title" => title = Some(option_value(&mut args, &arg)),
+            "--no-std" => include_std = false,
+            _ if arg.starts_with('-') => {
+                eprintln!("Unknown option: {arg}");
+                print_help();
+                exit(1);
+            }
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+
+    let engine = Engine::new();
+
+    let mut scripts = Vec::new();
+
+    for file in files {
+        let name = file
+            .file_stem()
+            .map_or_else(|| file.to_string_lossy(), |s| s.to_string_lossy())
+            .to_string();
+
+        match engine.compile_file(file.clone()) {
+            Ok(ast) => scripts.push((name, ast)),
+            Err(err) => {
+                eprintln!("Error compiling script: {}\n{err}", file.to_string_lossy());
+                exit(1);
+            }
+        }
+    }
+
+    let mut doc = engine
+        .documentation()
+        .include_standard_packages(include_std);
+
+    if let Some(title) = title {
+        doc = doc.with_title(title);
+    }
+    for (name, ast) in &scripts {
+        doc = doc.with_script_module(name, ast);
+    }
+
+    let result = if markdown {
+        doc.write_markdown_to_dir(&output)
+    } else {
+        doc.write_html_to_dir(&output)
+    };
+
+    if let Err(err) = result {
+        eprintln!(
+            "Error writing documentation: {}\n{err}",
+            output.to_string_lossy()
+        );
+        exit(1);
+    }
+
+    println!("Documentation written to {}", output.to_string_lossy());
+}
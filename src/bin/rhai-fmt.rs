@@ -0,0 +1,117 @@
+use rhai::Engine;
+
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    process::exit,
+};
+
+fn print_help() {
+    println!("Usage: rhai-fmt [--check] [FILE]...");
+    println!();
+    println!("Formats Rhai scripts in place.");
+    println!("If no file is given, the script is read from stdin and written to stdout.");
+    println!();
+    println!("Options:");
+    println!("  --check    do not write anything; exit with 1 if any script is not formatted");
+    println!("  -h, --help print this help message");
+}
+
+/// Split a shebang line (if any) from the script.
+fn split_shebang(contents: &str) -> (&str, &str) {
+    if contents.starts_with("#!") {
+        let pos = contents.find('\n').map_or(contents.len(), |p| p + 1);
+        contents.split_at(pos)
+    } else {
+        ("", contents)
+    }
+}
+
+/// Format a script, keeping any shebang line intact.
+fn format(engine: &Engine, name: &str, contents: &str) -> String {
+    let (shebang, script) = split_shebang(contents);
+
+    match engine.format_script(script) {
+        Ok(formatted) => format!("{shebang}{formatted}"),
+        Err(err) => {
+            eprintln!("Error formatting script: {name}\n{err}");
+            exit(1);
+        }
+    }
+}
+
+fn main() {
+    let mut check = false;
+    let mut filenames = Vec::new();
+
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--check" => check = true,
+            "-h" | "--help" => {
+                print_help();
+                exit(0);
+            }
+            _ if arg.starts_with('-') => {
+                eprintln!("Unknown option: {arg}");
+                print_help();
+                exit(1);
+            }
+            _ => filenames.push(arg),
+        }
+    }
+
+    // Initialize scripting engine
+    let engine = Engine::new();
+
+    if filenames.is_empty() {
+        let mut contents = String::new();
+
+        if let Err(err) = io::stdin().read_to_string(&mut contents) {
+            eprintln!("Error reading script from stdin\n{err}");
+            exit(1);
+        }
+
+        let formatted = format(&engine, "<stdin>", &contents);
+
+        if check {
+            if formatted != contents {
+                eprintln!("Script is not formatted: <stdin>");
+                exit(1);
+            }
+        } else {
+            io::stdout().write_all(formatted.as_bytes()).unwrap();
+        }
+
+        return;
+    }
+
+    let mut unformatted = 0;
+
+    for filename in filenames {
+        let contents = match fs::read_to_string(&filename) {
+            Ok(contents) => contents,
+            Err(err) => {
+                eprintln!("Error reading script file: {filename}\n{err}");
+                exit(1);
+            }
+        };
+
+        let formatted = format(&engine, &filename, &contents);
+
+        if formatted == contents {
+            continue;
+        }
+
+        if check {
+            eprintln!("Script is not formatted: {filename}");
+            unformatted += 1;
+        } else if let Err(err) = fs::write(&filename, formatted) {
+            eprintln!("Error writing script file: {filename}\n{err}");
+            exit(1);
+        }
+    }
+
+    if unformatted > 0 {
+        exit(1);
+    }
+}
@@ -1,15 +1,161 @@
 use rhai::packages::{streamline, Package, StreamlinePackage};
 use rhai::plugin::*;
 use rhai::{Dynamic, Engine, EvalAltResult, Module, Scope, AST, INT};
+use rustyline::completion::{Completer, Pair};
 use rustyline::config::Builder;
 use rustyline::error::ReadlineError;
-use rustyline::history::{History, SearchDirection};
-use rustyline::{Cmd, DefaultEditor, Event, EventHandler, KeyCode, KeyEvent, Modifiers, Movement};
-
+use rustyline::highlight::Highlighter;
+use rustyline::hint::{Hint, Hinter};
+use rustyline::history::{DefaultHistory, History, SearchDirection};
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{
+    Cmd, CompletionType, Editor, Event, EventHandler, Helper, KeyCode, KeyEvent, Modifiers,
+    Movement,
+};
+
+use std::{borrow::Cow, cell::RefCell, rc::Rc};
 use std::{env, fs::File, io::Read, path::Path, process::exit};
 
 const HISTORY_FILE: &str = ".rhai-repl-history";
 
+/// State of the REPL, shared with the line editor for completion and highlighting.
+struct ReplState {
+    engine: Engine,
+    scope: Scope<'static>,
+    main_ast: AST,
+}
+
+/// Line editor helper providing completion, signature hints and syntax highlighting.
+#[cfg_attr(feature = "no_position", allow(dead_code))]
+struct ReplHelper(Rc<RefCell<ReplState>>);
+
+/// Hint showing the signatures of the function being called.
+struct SignatureHint(String);
+
+impl Hint for SignatureHint {
+    fn display(&self) -> &str {
+        &self.0
+    }
+    fn completion(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    #[cfg(feature = "metadata")]
+    #[cfg(not(feature = "no_position"))]
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &rustyline::Context,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let state = self.0.borrow();
+        let (start, completions) = state
+            .engine
+            .complete(&state.scope, &state.main_ast, line, pos);
+
+        let candidates = completions
+            .into_iter()
+            .map(|c| Pair {
+                display: c.text.clone(),
+                replacement: c.text,
+            })
+            .collect();
+
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = SignatureHint;
+
+    #[cfg(feature = "metadata")]
+    #[cfg(not(feature = "no_position"))]
+    fn hint(&self, line: &str, pos: usize, _: &rustyline::Context) -> Option<SignatureHint> {
+        // Only hint at the end of the input
+        if pos < line.len() {
+            return None;
+        }
+
+        let state = self.0.borrow();
+        let signatures = state.engine.signature_help(&state.main_ast, line, pos);
+
+        match signatures.len() {
+            0 => None,
+            1 => Some(SignatureHint(format!("  {}", signatures[0]))),
+            n => Some(SignatureHint(format!(
+                "  {} (+{} more)",
+                signatures[0],
+                n - 1
+            ))),
+        }
+    }
+}
+
+impl Highlighter for ReplHelper {
+    #[cfg(not(feature = "no_position"))]
+    fn highlight<'l>(&self, line: &'l str, _: usize) -> Cow<'l, str> {
+        use rhai::HighlightKind;
+
+        let state = self.0.borrow();
+        let mut output = String::with_capacity(line.len() * 2);
+        let mut last = 0;
+
+        for span in state.engine.highlight_script(line) {
+            let color = match span.kind {
+                HighlightKind::Keyword => 35,
+                HighlightKind::Custom => 36,
+                HighlightKind::Function => 34,
+                HighlightKind::Number => 33,
+                HighlightKind::String => 32,
+                HighlightKind::Comment => 90,
+                HighlightKind::Error => 31,
+                _ => continue,
+            };
+            output += &line[last..span.range.start];
+            output += &format!("\x1b[{color}m{}\x1b[39m", &line[span.range.clone()]);
+            last = span.range.end;
+        }
+        output += &line[last..];
+
+        Cow::Owned(output)
+    }
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(format!("\x1b[90m{hint}\x1b[39m"))
+    }
+    #[cfg(not(feature = "no_position"))]
+    fn highlight_char(&self, _: &str, _: usize, _: bool) -> bool {
+        true
+    }
+}
+
+impl Validator for ReplHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let input = ctx.input();
+        let cmd = input.trim();
+
+        // Commands and lines continued with `\` are never incomplete
+        if cmd.starts_with(':') || cmd.starts_with('!') || input.ends_with('\\') {
+            return Ok(ValidationResult::Valid(None));
+        }
+
+        // Continue to the next line if the input is incomplete
+        let state = self.0.borrow();
+        match state
+            .engine
+            .compile_with_scope_if_complete(&state.scope, input)
+        {
+            Ok(None) => Ok(ValidationResult::Incomplete),
+            _ => Ok(ValidationResult::Valid(None)),
+        }
+    }
+}
+
+impl Helper for ReplHelper {}
+
 /// Pretty-print error.
 fn print_error(input: &str, mut err: EvalAltResult) {
     let lines: Vec<_> = input.lines().collect();
@@ -44,6 +190,25 @@ fn print_error(input: &str, mut err: EvalAltResult) {
     }
 }
 
+/// Join the inputs of a session into a script.
+fn session_script(session: &[String]) -> String {
+    let mut script = String::new();
+
+    for input in session {
+        let input = input.trim_end();
+        script += input;
+
+        // Terminate the last statement, which may end with a comment
+        if !input.ends_with(';') {
+            let last_line = input.lines().last().unwrap_or_default();
+            script += if last_line.contains("//") { "\n;" } else { ";" };
+        }
+        script += "\n\n";
+    }
+
+    script
+}
+
 /// Print help text.
 fn print_help() {
     println!("help       => print this help");
@@ -65,9 +230,12 @@ fn print_help() {
     println!("ast        => print the last AST (optimized)");
     #[cfg(not(feature = "no_optimize"))]
     println!("astu       => print the last raw, un-optimized AST");
+    println!(":load FILE => load and run a script file");
+    println!(":save FILE => save the session (all input run successfully) to a script file");
+    println!(":reset     => reset the session (variables and functions)");
     println!();
-    println!("press Ctrl-Enter or end a line with `\\`");
-    println!("to continue to the next line.");
+    println!("incomplete input automatically continues to the next line;");
+    println!("press Ctrl-Enter or end a line with `\\` to force a new line.");
     println!();
 }
 
@@ -104,6 +272,8 @@ fn print_keys() {
     #[cfg(target_family = "windows")]
     println!("Ctrl-Z            => undo");
     println!("Ctrl-_            => undo");
+    #[cfg(feature = "metadata")]
+    println!("Tab               => complete name of function, variable or property");
     println!("Enter             => run code");
     println!("Shift-Ctrl-Enter  => continue to next line");
     println!();
@@ -190,14 +360,16 @@ fn load_script_files(engine: &mut Engine, scope: &mut Scope) {
 }
 
 // Setup the Rustyline editor.
-fn setup_editor() -> DefaultEditor {
+fn setup_editor(helper: ReplHelper) -> Editor<ReplHelper, DefaultHistory> {
     //env_logger::init();
     let config = Builder::new()
         .tab_stop(4)
         .indent_size(4)
         .bracketed_paste(true)
+        .completion_type(CompletionType::List)
         .build();
-    let mut rl = DefaultEditor::with_config(config).unwrap();
+    let mut rl = Editor::with_config(config).unwrap();
+    rl.set_helper(Some(helper));
 
     // Bind more keys
 
@@ -282,16 +454,25 @@ fn main() {
         engine.set_module_resolver(resolver);
     }
 
+    // Keep the initial scope for resetting the session
+    let init_scope = scope.clone();
+
+    let state = Rc::new(RefCell::new(ReplState {
+        engine,
+        scope,
+        main_ast: AST::empty(),
+    }));
+
     // REPL line editor setup
-    let mut rl = setup_editor();
+    let mut rl = setup_editor(ReplHelper(state.clone()));
 
     // REPL loop
     let mut input = String::new();
     let mut replacement = None;
     let mut replacement_index = 0;
     let mut history_offset = 1;
+    let mut session = Vec::new();
 
-    let mut main_ast = AST::empty();
     #[cfg(not(feature = "no_optimize"))]
     let mut ast_u = AST::empty();
     let mut ast = AST::empty();
@@ -359,6 +540,13 @@ fn main() {
             continue;
         }
 
+        let mut state = state.borrow_mut();
+        let ReplState {
+            engine,
+            scope,
+            main_ast,
+        } = &mut *state;
+
         // Implement standard commands
         match cmd {
             "help" => {
@@ -425,6 +613,50 @@ fn main() {
                 println!("{ast:#?}\n");
                 continue;
             }
+            ":reset" => {
+                *scope = init_scope.clone();
+                *main_ast = AST::empty();
+                #[cfg(not(feature = "no_optimize"))]
+                {
+                    ast_u = AST::empty();
+                }
+                ast = AST::empty();
+                session.clear();
+                println!("Session reset.");
+                continue;
+            }
+            _ if cmd.starts_with(":save") => {
+                let path = cmd[5..].trim();
+
+                if path.is_empty() {
+                    eprintln!("Missing file name: :save FILE");
+                } else if let Err(err) = std::fs::write(path, session_script(&session)) {
+                    eprintln!("Error writing session file: {path}\n{err}");
+                } else {
+                    println!("Session saved to '{path}'.");
+                }
+                continue;
+            }
+            _ if cmd.starts_with(":load") => {
+                let path = cmd[5..].trim();
+
+                if path.is_empty() {
+                    eprintln!("Missing file name: :load FILE");
+                    continue;
+                }
+
+                // Run the script file as input
+                match std::fs::read_to_string(path) {
+                    Ok(script) => {
+                        println!("Script '{path}' loaded.");
+                        input = script;
+                    }
+                    Err(err) => {
+                        eprintln!("Error reading script file: {path}\n{err}");
+                        continue;
+                    }
+                }
+            }
             #[cfg(feature = "metadata")]
             "functions" => {
                 // print a list of all registered functions
@@ -445,7 +677,7 @@ fn main() {
                 use std::io::Write;
 
                 let json = engine
-                    .gen_fn_metadata_with_ast_to_json(&main_ast, false)
+                    .gen_fn_metadata_with_ast_to_json(main_ast, false)
                     .expect("Unable to generate JSON");
                 let mut f = std::fs::File::create("metadata.json")
                     .expect("Unable to create `metadata.json`");
@@ -515,14 +747,14 @@ fn main() {
         }
 
         match engine
-            .compile_with_scope(&scope, &input)
+            .compile_with_scope(scope, &input)
             .map_err(Into::into)
             .and_then(|r| {
                 #[cfg(not(feature = "no_optimize"))]
                 {
                     ast_u = r.clone();
 
-                    ast = engine.optimize_ast(&scope, r, optimize_level);
+                    ast = engine.optimize_ast(scope, r, optimize_level);
                 }
 
                 #[cfg(feature = "no_optimize")]
@@ -531,16 +763,18 @@ fn main() {
                 }
 
                 // Merge the AST into the main
-                main_ast += ast.clone();
+                *main_ast += ast.clone();
 
                 // Evaluate
-                engine.eval_ast_with_scope::<Dynamic>(&mut scope, &main_ast)
+                engine.eval_ast_with_scope::<Dynamic>(scope, main_ast)
             }) {
-            Ok(result) if !result.is_unit() => {
-                println!("=> {result:?}");
-                println!();
+            Ok(result) => {
+                if !result.is_unit() {
+                    println!("=> {result:?}");
+                    println!();
+                }
+                session.push(input.clone());
             }
-            Ok(_) => (),
             Err(err) => {
                 println!();
                 print_error(&input, *err);
@@ -2,24 +2,44 @@ use rhai::{Engine, EvalAltResult, Position};
 
 use std::{env, fs::File, io::Read, path::Path, process::exit};
 
-fn eprint_error(input: &str, mut err: EvalAltResult) {
-    fn eprint_line(lines: &[&str], pos: Position, err_msg: &str) {
-        let line = pos.line().unwrap();
-        let line_no = format!("{line}: ");
-
-        eprintln!("{line_no}{}", lines[line - 1]);
-
-        for (i, err_line) in err_msg.to_string().lines().enumerate() {
-            // Display position marker
-            println!(
-                "{0:>1$}{err_line}",
-                if i > 0 { "| " } else { "^ " },
-                line_no.len() + pos.position().unwrap() + 1,
-            );
+#[cfg(feature = "profiling")]
+fn write_profile(filename: &Path, profiler: &rhai::Profiler) {
+    let write = |ext: &str, text: String| {
+        let mut path = filename.as_os_str().to_owned();
+        path.push(ext);
+        let path = Path::new(&path);
+
+        match std::fs::write(path, text) {
+            Ok(()) => eprintln!("Profile written to: {}", path.to_string_lossy()),
+            Err(err) => {
+                eprintln!("Error writing profile: {}\n{}", path.to_string_lossy(), err);
+                exit(1);
+            }
         }
-        eprintln!();
+    };
+
+    write(".folded", profiler.to_folded());
+    write(".profile.json", profiler.to_json());
+}
+
+fn eprint_line(lines: &[&str], pos: Position, err_msg: &str) {
+    let line = pos.line().unwrap();
+    let line_no = format!("{line}: ");
+
+    eprintln!("{line_no}{}", lines[line - 1]);
+
+    for (i, err_line) in err_msg.to_string().lines().enumerate() {
+        // Display position marker
+        println!(
+            "{0:>1$}{err_line}",
+            if i > 0 { "| " } else { "^ " },
+            line_no.len() + pos.position().unwrap() + 1,
+        );
     }
+    eprintln!();
+}
 
+fn eprint_error(input: &str, mut err: EvalAltResult) {
     let lines: Vec<_> = input.lines().collect();
 
     // Print error
@@ -34,10 +54,41 @@ fn eprint_error(input: &str, mut err: EvalAltResult) {
     }
 }
 
+fn eprint_filename(filename: &str) {
+    eprintln!("{:=<1$}", "", filename.len());
+    eprintln!("{filename}");
+    eprintln!("{:=<1$}", "", filename.len());
+    eprintln!();
+}
+
 fn main() {
     let mut contents = String::new();
+    let mut lint = false;
+    #[cfg(feature = "profiling")]
+    let mut profile = false;
+    let mut has_warnings = false;
+
+    let (flags, filenames): (Vec<_>, Vec<_>) =
+        env::args().skip(1).partition(|arg| arg.starts_with("--"));
+
+    for flag in flags {
+        match flag.as_str() {
+            "--lint" => lint = true,
+            #[cfg(feature = "profiling")]
+            "--profile" => profile = true,
+            #[cfg(not(feature = "profiling"))]
+            "--profile" => {
+                eprintln!("Profiling requires the `profiling` feature.");
+                exit(1);
+            }
+            _ => {
+                eprintln!("Unknown option: {flag}");
+                exit(1);
+            }
+        }
+    }
 
-    for filename in env::args().skip(1) {
+    for filename in filenames {
         let filename = match Path::new(&filename).canonicalize() {
             Err(err) => {
                 eprintln!("Error script file path: {filename}\n{err}");
@@ -57,6 +108,11 @@ fn main() {
         #[cfg(not(feature = "no_optimize"))]
         engine.set_optimization_level(rhai::OptimizationLevel::Simple);
 
+        #[cfg(feature = "profiling")]
+        if profile {
+            engine.set_profiler(Some(rhai::Profiler::new()));
+        }
+
         let mut f = match File::open(&filename) {
             Err(err) => {
                 eprintln!(
@@ -87,6 +143,34 @@ fn main() {
             &contents[..]
         };
 
+        if lint {
+            match engine.lint(contents) {
+                Ok(warnings) if warnings.is_empty() => (),
+                Ok(warnings) => {
+                    let lines: Vec<_> = contents.lines().collect();
+
+                    eprint_filename(&filename.to_string_lossy());
+
+                    for warning in warnings {
+                        if warning.position.is_none() {
+                            eprintln!("{warning}");
+                        } else {
+                            let msg = format!("{} [{}]", warning.message, warning.code);
+                            eprint_line(&lines, warning.position, &msg);
+                        }
+                    }
+
+                    has_warnings = true;
+                }
+                Err(err) => {
+                    eprint_filename(&filename.to_string_lossy());
+                    eprint_error(contents, err.into());
+                    has_warnings = true;
+                }
+            }
+            continue;
+        }
+
         if let Err(err) = engine
             .compile(contents)
             .map_err(|err| err.into())
@@ -95,14 +179,17 @@ fn main() {
                 engine.run_ast(&ast)
             })
         {
-            let filename = filename.to_string_lossy();
-
-            eprintln!("{:=<1$}", "", filename.len());
-            eprintln!("{filename}");
-            eprintln!("{:=<1$}", "", filename.len());
-            eprintln!();
-
+            eprint_filename(&filename.to_string_lossy());
             eprint_error(contents, *err);
         }
+
+        #[cfg(feature = "profiling")]
+        if let Some(profiler) = engine.profiler() {
+            write_profile(&filename, profiler);
+        }
+    }
+
+    if has_warnings {
+        exit(1);
     }
 }
@@ -0,0 +1,207 @@
+#[cfg(not(feature = "no_function"))]
+use rhai::testing::{TestKind, TestOutcome, TestRunner};
+
+use std::process::exit;
+#[cfg(not(feature = "no_function"))]
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+#[cfg(not(feature = "no_function"))]
+fn print_help() {
+    println!("Usage: rhai-test [OPTIONS] [PATH]...");
+    println!();
+    println!("Runs tests written in Rhai scripts.");
+    println!("Tests are functions with no parameters named `test_*` or marked with `/// #[test]`.");
+    println!(
+        "Directories are searched recursively for `.rhai` files (default: current directory)."
+    );
+    println!();
+    println!("Options:");
+    println!("  --filter <TEXT>   only run tests whose names contain the text (may be repeated)");
+    println!("  --threads <N>     number of threads to run tests on");
+    println!("  --junit <FILE>    write the results in JUnit XML format to a file");
+    #[cfg(feature = "metadata")]
+    println!("  --doc             also run the `rhai` code examples in doc-comments");
+    println!("  --list            list the tests without running them");
+    println!("  -h, --help        print this help message");
+}
+
+/// Collect the script files under a path, in order of path.
+#[cfg(not(feature = "no_function"))]
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if !path.is_dir() {
+        files.push(path.into());
+        return;
+    }
+
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("Error reading directory: {}\n{err}", path.to_string_lossy());
+            exit(1);
+        }
+    };
+
+    let mut paths: Vec<_> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else if path.extension().map_or(false, |ext| ext == "rhai") {
+            files.push(path);
+        }
+    }
+}
+
+/// Get the value of an option.
+#[cfg(not(feature = "no_function"))]
+fn option_value(args: &mut impl Iterator<Item = String>, option: &str) -> String {
+    args.next().unwrap_or_else(|| {
+        eprintln!("Missing value for {option}");
+        exit(1);
+    })
+}
+
+#[cfg(not(feature = "no_function"))]
+fn main() {
+    let mut runner = TestRunner::new();
+    let mut paths = Vec::new();
+    let mut junit = None;
+    let mut list = false;
+
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print_help();
+                exit(0);
+            }
+            "--filter" => {
+                runner.add_filter(option_value(&mut args, &arg));
+            }
+            "--threads" => match option_value(&mut args, &arg).parse::<usize>() {
+                Ok(n) => {
+                    runner.set_threads(n);
+                }
+                Err(..) => {
+                    eprintln!("Invalid number of threads");
+                    exit(1);
+                }
+            },
+            "--junit" => junit = Some(option_value(&mut args, &arg)),
+            #[cfg(feature = "metadata")]
+            "--doc" => {
+                runner.set_doc_tests(true);
+            }
+            "--list" => list = true,
+            _ if arg.starts_with('-') => {
+                eprintln!("Unknown option: {arg}");
+                print_help();
+                exit(1);
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    if paths.is_empty() {
+        paths.push(PathBuf::from("."));
+    }
+
+    let mut files = Vec::new();
+    for path in &paths {
+        collect_files(path, &mut files);
+    }
+
+    for file in &files {
+        let file = file.strip_prefix(".").unwrap_or(file);
+
+        if let Err(err) = runner.add_file(file) {
+            eprintln!("{err}");
+            exit(1);
+        }
+    }
+
+    if list {
+        let (tests, errors) = runner.discover();
+
+        for test in &tests {
+            match test.kind {
+                TestKind::Function => println!("{test}: test"),
+                _ => println!("{test}: doc-test"),
+            }
+        }
+        for (source, err) in &errors {
+            eprintln!("Error compiling {source}: {err}");
+        }
+        println!();
+        println!("{} tests", tests.len());
+        exit(i32::from(!errors.is_empty()));
+    }
+
+    let report = runner.run();
+
+    println!();
+    println!("running {} tests", report.results.len());
+
+    for result in &report.results {
+        let status = match result.outcome {
+            TestOutcome::Passed => "\x1b[32mok\x1b[39m",
+            TestOutcome::Failed(..) => "\x1b[31mFAILED\x1b[39m",
+            _ => "\x1b[33mignored\x1b[39m",
+        };
+        println!("test {} ... {status}", result.case);
+    }
+
+    if !report.errors.is_empty() || report.failed() > 0 {
+        println!();
+        println!("failures:");
+
+        for (source, err) in &report.errors {
+            println!();
+            println!("---- {source} ----");
+            println!("Error compiling script: {err}");
+        }
+        for result in &report.results {
+            if let TestOutcome::Failed(ref message) = result.outcome {
+                println!();
+                println!("---- {} ----", result.case);
+                println!("{message}");
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "test result: {}. {} passed; {} failed; {} ignored; {} errors; finished in {:.2}s",
+        if report.is_success() {
+            "\x1b[32mok\x1b[39m"
+        } else {
+            "\x1b[31mFAILED\x1b[39m"
+        },
+        report.passed(),
+        report.failed(),
+        report.ignored(),
+        report.errors.len(),
+        report.duration.as_secs_f64()
+    );
+    println!();
+
+    if let Some(junit) = junit {
+        if let Err(err) = fs::write(&junit, report.to_junit_xml()) {
+            eprintln!("Error writing JUnit report: {junit}\n{err}");
+            exit(1);
+        }
+    }
+
+    exit(i32::from(!report.is_success()));
+}
+
+#[cfg(feature = "no_function")]
+fn main() {
+    eprintln!("rhai-test requires script-defined functions (not available under `no_function`).");
+    exit(1);
+}
@@ -11,7 +11,12 @@ use crate::types::StringsInterner;
 use crate::{Dynamic, Identifier, ImmutableString, Locked, OptimizationLevel, SharedModule};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
-use std::{collections::BTreeSet, fmt, num::NonZeroU8};
+use std::{
+    collections::BTreeSet,
+    fmt,
+    num::NonZeroU8,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 pub type Precedence = NonZeroU8;
 
@@ -87,6 +92,8 @@ pub const NAMESPACE_SEPARATOR: &str = Token::DoubleColon.literal_syntax();
 pub struct Engine {
     /// A collection of all modules loaded into the global namespace of the Engine.
     pub(crate) global_modules: Vec<SharedModule>,
+    /// Version of the functions in the global namespace, changed whenever functions are registered.
+    pub(crate) fn_version: usize,
     /// A collection of all sub-modules directly loaded into the Engine.
     #[cfg(not(feature = "no_module"))]
     pub(crate) global_sub_modules: std::collections::BTreeMap<Identifier, SharedModule>,
@@ -100,6 +107,8 @@ pub struct Engine {
 
     /// A set of symbols to disable.
     pub(crate) disabled_symbols: BTreeSet<Identifier>,
+    /// Hashes (by name and number of parameters) of asynchronous functions.
+    pub(crate) async_fns: BTreeSet<u64>,
     /// A map containing custom keywords and precedence to recognize.
     #[cfg(not(feature = "no_custom_syntax"))]
     pub(crate) custom_keywords: std::collections::BTreeMap<Identifier, Option<Precedence>>,
@@ -142,6 +151,10 @@ pub struct Engine {
         Box<crate::eval::OnDebuggingInit>,
         Box<crate::eval::OnDebuggerCallback>,
     )>,
+
+    /// Function-level profiler.
+    #[cfg(feature = "profiling")]
+    pub(crate) profiler: Option<crate::eval::Profiler>,
 }
 
 impl fmt::Debug for Engine {
@@ -156,6 +169,7 @@ impl fmt::Debug for Engine {
         f.field("global_sub_modules", &self.global_sub_modules);
 
         f.field("disabled_symbols", &self.disabled_symbols);
+        f.field("async_fns", &self.async_fns);
 
         #[cfg(not(feature = "no_custom_syntax"))]
         f.field("custom_keywords", &self.custom_keywords).field(
@@ -186,6 +200,9 @@ impl fmt::Debug for Engine {
         #[cfg(feature = "debugging")]
         f.field("debugger_interface", &self.debugger_interface.is_some());
 
+        #[cfg(feature = "profiling")]
+        f.field("profiler", &self.profiler.is_some());
+
         f.finish()
     }
 }
@@ -220,10 +237,19 @@ pub fn make_setter(id: &str) -> Identifier {
     buf
 }
 
+/// Get a new unique version of the functions in the global namespace of an [`Engine`].
+#[inline]
+#[must_use]
+pub(crate) fn next_fn_version() -> usize {
+    static NEXT_FN_VERSION: AtomicUsize = AtomicUsize::new(1);
+    NEXT_FN_VERSION.fetch_add(1, Ordering::Relaxed)
+}
+
 impl Engine {
     /// An empty raw [`Engine`].
     pub const RAW: Self = Self {
         global_modules: Vec::new(),
+        fn_version: 0,
 
         #[cfg(not(feature = "no_module"))]
         global_sub_modules: std::collections::BTreeMap::new(),
@@ -233,6 +259,7 @@ impl Engine {
 
         interned_strings: None,
         disabled_symbols: BTreeSet::new(),
+        async_fns: BTreeSet::new(),
         #[cfg(not(feature = "no_custom_syntax"))]
         custom_keywords: std::collections::BTreeMap::new(),
         #[cfg(not(feature = "no_custom_syntax"))]
@@ -262,6 +289,9 @@ impl Engine {
 
         #[cfg(feature = "debugging")]
         debugger_interface: None,
+
+        #[cfg(feature = "profiling")]
+        profiler: None,
     };
 
     /// Create a new [`Engine`].
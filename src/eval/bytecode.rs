@@ -0,0 +1,1265 @@
+//! Module implementing the bytecode backend.
+//!
+//! Statements blocks are lowered into a linear list of instructions for a stack machine, which are
+//! run by a single interpreter loop instead of by recursive tree-walking.
+//!
+//! Nodes with no dedicated instructions are delegated to the tree-walking evaluator, so the
+//! results, operations counting and error positions stay the same.
+
+use super::{loop_condition, Caches, ForSource, GlobalRuntimeState};
+use crate::ast::{
+    ASTFlags, Expr, FlowControl, FnCallBinding, FnCallExpr, FnCallHashes, Ident, Stmt,
+};
+use crate::engine::{
+    KEYWORD_EVAL, KEYWORD_FN_PTR, KEYWORD_FN_PTR_CALL, KEYWORD_FN_PTR_CURRY, KEYWORD_IS_DEF_VAR,
+};
+use crate::tokenizer::Token;
+#[cfg(not(feature = "no_function"))]
+use crate::{
+    ast::{EncapsulatedEnviron, ScriptFuncDef},
+    eval::FnResolutionCacheEntry,
+    func::RhaiFunc,
+};
+use crate::{
+    Dynamic, Engine, FnArgsVec, ImmutableString, Position, RhaiResult, RhaiResultOf, Scope, Shared,
+    StaticVec, ERR,
+};
+use std::mem;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// A function call with pre-calculated hashes, its arguments being on the stack.
+#[derive(Debug, Clone)]
+struct CallSite {
+    /// Function name.
+    name: ImmutableString,
+    /// Native operator, if any.
+    op_token: Option<Token>,
+    /// Pre-calculated hashes.
+    hashes: FnCallHashes,
+    /// Native Rust functions bound at compile time, if any.
+    binding: Option<Shared<FnCallBinding>>,
+    /// Number of arguments on the stack.
+    num_args: usize,
+    /// First argument, if it is a variable passed by reference.
+    first_var: Option<Expr>,
+    /// Index of the slot holding the resolved script-defined function, if the call can be to one.
+    #[cfg(not(feature = "no_function"))]
+    slot: Option<usize>,
+    /// [Position] of the call.
+    pos: Position,
+}
+
+/// A function call site resolved to a script-defined function or not.
+#[cfg(not(feature = "no_function"))]
+enum Slot {
+    /// Script-defined function, with its encapsulated environment and source.
+    Script(
+        Shared<ScriptFuncDef>,
+        Option<Shared<EncapsulatedEnviron>>,
+        Option<ImmutableString>,
+    ),
+    /// Not a script-defined function.
+    Other,
+}
+
+/// A `for` loop.
+#[derive(Debug, Clone)]
+struct ForLoop {
+    /// Loop variable.
+    var: Ident,
+    /// Counter variable, if any.
+    counter: Option<Ident>,
+    /// [Position] of the iterable expression.
+    expr_pos: Position,
+    /// Start [position][Position] of the iterable expression.
+    expr_start_pos: Position,
+    /// [Position] of the loop body, if it is empty.
+    empty_body: Option<Position>,
+}
+
+/// An instruction.
+///
+/// Expressions push their values onto the stack. Statements set the value of the current statement.
+#[derive(Debug, Clone)]
+enum Instr {
+    /// Count one operation.
+    Track(Position),
+    /// Count one operation and push a constant.
+    Const(Dynamic, Position),
+    /// Count one operation and push the value of a variable.
+    Load(Box<Expr>),
+    /// Count one operation and push the value of `this`.
+    LoadThis(Position),
+    /// Evaluate an expression by tree-walking and push its value.
+    Eval(Box<Expr>),
+    /// Run a statement by tree-walking. The flag is `true` if the statement is in a nested block.
+    Exec(Box<Stmt>, bool),
+    /// Call a function with the arguments on the stack and push its result.
+    Call(Box<CallSite>),
+    /// Call a binary operator with the operands on the stack, under Fast Operators mode.
+    BinaryOp(Box<CallSite>),
+    /// Call the `!` operator with the operand on the stack, under Fast Operators mode.
+    Not(Box<CallSite>),
+    /// Pop the value of the current statement, flattening it if the flag is `true`.
+    SetValue(bool),
+    /// Set the value of the current statement to `()`.
+    Unit,
+    /// Push the value of the current statement.
+    PushValue,
+    /// Enter a statements block.
+    Enter,
+    /// Leave a statements block.
+    Leave,
+    /// Jump to an instruction.
+    Jump(usize),
+    /// Pop a boolean and jump to an instruction if it equals the flag.
+    JumpIf(bool, usize, Position),
+    /// Pop a boolean and, if it equals the flag, push it back and jump to an instruction.
+    ShortCircuit(bool, usize, Position),
+    /// Pop a boolean and push it back.
+    ToBool(Position),
+    /// Jump to an instruction if the value on the stack is not `()`, otherwise pop it.
+    Coalesce(usize),
+    /// Enter a loop, with the instructions to jump to on `break` and on `continue`.
+    EnterLoop(usize, usize),
+    /// Leave a loop.
+    LeaveLoop,
+    /// Loop forever, counting one operation per iteration.
+    Spin(Position),
+    /// Check the variables about to be defined by a `let` or `const` statement.
+    CheckVar(usize),
+    /// Define the variables of a `let` or `const` statement with the value on the stack.
+    /// The flag is `true` if the statement is in a nested block.
+    DefineVar(usize, bool),
+    /// Assign the value on the stack to the left-hand side of an assignment.
+    Assign(Box<Stmt>),
+    /// Guard against too many variables for a `for` loop.
+    ForGuard(usize),
+    /// Start a `for` loop over the value on the stack, with the instructions to jump to on `break`
+    /// and on `continue`.
+    ForStart(usize, usize, usize),
+    /// Set the loop variables of a `for` loop, or jump to an instruction when there are no more
+    /// values.
+    ForNext(usize, usize),
+    /// Leave a `for` loop.
+    ForEnd,
+    /// `break` (if the first flag is `true`) or `continue`, with a value on the stack if the
+    /// second flag is `true`.
+    Break(bool, bool, Position),
+    /// `return`, with a value on the stack if the flag is `true`.
+    Return(bool, Position),
+    /// `throw`, with a value on the stack if the flag is `true`.
+    Throw(bool, Position),
+}
+
+/// A statements block compiled into bytecode.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Chunk {
+    /// Instructions.
+    code: Vec<Instr>,
+    /// `let` and `const` statements.
+    vars: Vec<Stmt>,
+    /// `for` loops.
+    fors: Vec<ForLoop>,
+    /// Number of function call sites with slots.
+    #[cfg(not(feature = "no_function"))]
+    slots: usize,
+}
+
+/// Compiler of statements into a [`Chunk`].
+struct Compiler<'e> {
+    /// The [`Engine`].
+    engine: &'e Engine,
+    /// The [`Chunk`] being compiled.
+    chunk: Chunk,
+}
+
+impl Compiler<'_> {
+    /// Add an instruction, returning its index.
+    #[inline]
+    fn emit(&mut self, instr: Instr) -> usize {
+        self.chunk.code.push(instr);
+        self.chunk.code.len() - 1
+    }
+    /// Index of the next instruction.
+    #[inline(always)]
+    fn next(&self) -> usize {
+        self.chunk.code.len()
+    }
+    /// Set the target of a jump instruction to the next instruction.
+    fn patch(&mut self, index: usize) {
+        let target = self.next();
+
+        match self.chunk.code[index] {
+            Instr::Jump(ref mut t)
+            | Instr::JumpIf(_, ref mut t, ..)
+            | Instr::ShortCircuit(_, ref mut t, ..)
+            | Instr::Coalesce(ref mut t)
+            | Instr::ForNext(_, ref mut t) => *t = target,
+            ref instr => unreachable!("jump instruction expected but gets {:?}", instr),
+        }
+    }
+    /// Compile a statements block into instructions setting the value of the block.
+    fn compile_block(&mut self, statements: &[Stmt]) {
+        if statements.is_empty() {
+            self.emit(Instr::Unit);
+            return;
+        }
+
+        self.emit(Instr::Enter);
+        for stmt in statements {
+            self.compile_stmt(stmt, true);
+        }
+        self.emit(Instr::Leave);
+    }
+    /// Compile a loop body.
+    fn compile_body(&mut self, statements: &[Stmt]) {
+        if !statements.is_empty() {
+            self.compile_block(statements);
+        }
+    }
+    /// Compile a statement into instructions setting the value of the statement.
+    fn compile_stmt(&mut self, stmt: &Stmt, nested: bool) {
+        match stmt {
+            Stmt::Noop(pos) => {
+                self.emit(Instr::Track(*pos));
+                self.emit(Instr::Unit);
+            }
+
+            Stmt::Expr(expr) => {
+                self.emit(Instr::Track(stmt.position()));
+                self.compile_expr(expr);
+                self.emit(Instr::SetValue(true));
+            }
+
+            Stmt::Block(stmts, ..) => {
+                self.emit(Instr::Track(stmt.position()));
+                self.compile_block(stmts.statements());
+            }
+
+            Stmt::FnCall(x, pos) if self.is_compiled_call(x) => {
+                self.emit(Instr::Track(*pos));
+                self.compile_call(x, *pos);
+                self.emit(Instr::SetValue(false));
+            }
+
+            Stmt::Assignment(x) if !matches!(x.1.lhs, Expr::ThisPtr(..)) => {
+                self.emit(Instr::Track(stmt.position()));
+                self.compile_expr(&x.1.rhs);
+                self.emit(Instr::Assign(stmt.clone().into()));
+            }
+
+            Stmt::Var(x, .., pos) => {
+                let index = self.chunk.vars.len();
+                self.chunk.vars.push(stmt.clone());
+
+                self.emit(Instr::Track(*pos));
+                self.emit(Instr::CheckVar(index));
+                self.compile_expr(&x.1);
+                self.emit(Instr::DefineVar(index, nested));
+            }
+
+            Stmt::If(x, pos) => {
+                let FlowControl { expr, body, branch } = &**x;
+
+                self.emit(Instr::Track(*pos));
+                self.compile_expr(expr);
+                let jump_else = self.emit(Instr::JumpIf(false, 0, expr.position()));
+                self.compile_block(body.statements());
+                let jump_end = self.emit(Instr::Jump(0));
+                self.patch(jump_else);
+                self.compile_block(branch.statements());
+                self.patch(jump_end);
+            }
+
+            Stmt::While(x, pos)
+                if matches!(x.expr, Expr::Unit(..) | Expr::BoolConstant(true, ..)) =>
+            {
+                let body = &x.body;
+
+                self.emit(Instr::Track(*pos));
+
+                if body.is_empty() {
+                    self.emit(Instr::Spin(body.position()));
+                    return;
+                }
+
+                let enter = self.emit(Instr::EnterLoop(0, 0));
+                let start = self.next();
+                self.compile_block(body.statements());
+                self.emit(Instr::Jump(start));
+                let exit = self.emit(Instr::LeaveLoop);
+                self.chunk.code[enter] = Instr::EnterLoop(exit, start);
+            }
+
+            Stmt::While(x, pos) => {
+                let FlowControl { expr, body, .. } = &**x;
+
+                self.emit(Instr::Track(*pos));
+                let enter = self.emit(Instr::EnterLoop(0, 0));
+                let start = self.next();
+                self.compile_expr(expr);
+                let jump_done = self.emit(Instr::JumpIf(false, 0, expr.position()));
+                self.compile_body(body.statements());
+                self.emit(Instr::Jump(start));
+                self.patch(jump_done);
+                self.emit(Instr::Unit);
+                let exit = self.emit(Instr::LeaveLoop);
+                self.chunk.code[enter] = Instr::EnterLoop(exit, start);
+            }
+
+            Stmt::Do(x, .., pos) => {
+                let body = &x.body;
+                let (expr, is_while) = loop_condition(stmt);
+
+                self.emit(Instr::Track(*pos));
+                let enter = self.emit(Instr::EnterLoop(0, 0));
+                let start = self.next();
+                self.compile_body(body.statements());
+                self.compile_expr(expr);
+                self.emit(Instr::JumpIf(is_while, start, expr.position()));
+                self.emit(Instr::Unit);
+                let exit = self.emit(Instr::LeaveLoop);
+                self.chunk.code[enter] = Instr::EnterLoop(exit, start);
+            }
+
+            Stmt::For(x, pos) => {
+                let (var, counter, FlowControl { expr, body, .. }) = &**x;
+
+                let index = self.chunk.fors.len();
+                self.chunk.fors.push(ForLoop {
+                    var: var.clone(),
+                    counter: counter.clone(),
+                    expr_pos: expr.position(),
+                    expr_start_pos: expr.start_position(),
+                    empty_body: Some(body.position()).filter(|_| body.is_empty()),
+                });
+
+                self.emit(Instr::Track(*pos));
+                self.emit(Instr::ForGuard(index));
+                self.compile_expr(expr);
+                let start = self.emit(Instr::ForStart(index, 0, 0));
+                let next = self.emit(Instr::ForNext(index, 0));
+                self.compile_body(body.statements());
+                self.emit(Instr::Jump(next));
+                self.patch(next);
+                self.emit(Instr::Unit);
+                let exit = self.emit(Instr::ForEnd);
+                self.chunk.code[start] = Instr::ForStart(index, exit, next);
+            }
+
+            Stmt::BreakLoop(expr, options, pos) => {
+                self.emit(Instr::Track(*pos));
+                if let Some(expr) = expr {
+                    self.compile_expr(expr);
+                }
+                let is_break = options.intersects(ASTFlags::BREAK);
+                self.emit(Instr::Break(is_break, expr.is_some(), *pos));
+            }
+
+            Stmt::Return(expr, options, pos) => {
+                self.emit(Instr::Track(*pos));
+                if let Some(expr) = expr {
+                    self.compile_expr(expr);
+                }
+                self.emit(if options.intersects(ASTFlags::BREAK) {
+                    Instr::Throw(expr.is_some(), *pos)
+                } else {
+                    Instr::Return(expr.is_some(), *pos)
+                });
+            }
+
+            _ => {
+                self.emit(Instr::Exec(stmt.clone().into(), nested));
+            }
+        }
+    }
+    /// Compile an expression into instructions pushing its value.
+    fn compile_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::DynamicConstant(..)
+            | Expr::IntegerConstant(..)
+            | Expr::StringConstant(..)
+            | Expr::CharConstant(..)
+            | Expr::BoolConstant(..)
+            | Expr::Unit(..) => {
+                let value = expr.get_literal_value().unwrap();
+                self.emit(Instr::Const(value, expr.position()));
+            }
+            #[cfg(not(feature = "no_float"))]
+            Expr::FloatConstant(..) => {
+                let value = expr.get_literal_value().unwrap();
+                self.emit(Instr::Const(value, expr.position()));
+            }
+
+            Expr::Variable(..) => {
+                self.emit(Instr::Load(expr.clone().into()));
+            }
+
+            Expr::ThisPtr(pos) => {
+                self.emit(Instr::LoadThis(*pos));
+            }
+
+            Expr::FnCall(x, pos) if self.is_compiled_call(x) => {
+                self.emit(Instr::Track(*pos));
+                self.compile_call(x, *pos);
+            }
+
+            Expr::And(x, pos) | Expr::Or(x, pos) => {
+                let when = matches!(expr, Expr::Or(..));
+
+                self.emit(Instr::Track(*pos));
+                self.compile_expr(&x.lhs);
+                let jump = self.emit(Instr::ShortCircuit(when, 0, x.lhs.position()));
+                self.compile_expr(&x.rhs);
+                self.emit(Instr::ToBool(x.rhs.position()));
+                self.patch(jump);
+            }
+
+            Expr::Coalesce(x, pos) => {
+                self.emit(Instr::Track(*pos));
+                self.compile_expr(&x.lhs);
+                let jump = self.emit(Instr::Coalesce(0));
+                self.compile_expr(&x.rhs);
+                self.patch(jump);
+            }
+
+            Expr::Stmt(x) => {
+                self.emit(Instr::Track(expr.position()));
+                self.compile_block(x.statements());
+                self.emit(Instr::PushValue);
+            }
+
+            _ => {
+                self.emit(Instr::Eval(expr.clone().into()));
+            }
+        }
+    }
+    /// Can a function call be compiled?
+    ///
+    /// Namespace-qualified calls, calls capturing the parent scope, tail calls, calls with `this`
+    /// as the first argument and calls to special functions are left to the tree-walking evaluator.
+    fn is_compiled_call(&self, x: &FnCallExpr) -> bool {
+        #[cfg(not(feature = "no_module"))]
+        if !x.namespace.is_empty() {
+            return false;
+        }
+
+        if x.capture_parent_scope || x.is_tail_call {
+            return false;
+        }
+
+        if x.op_token.is_some() {
+            return self.is_fast_op(x) || !matches!(x.args.first(), Some(Expr::ThisPtr(..)));
+        }
+
+        match x.name.as_str() {
+            KEYWORD_FN_PTR_CALL | KEYWORD_FN_PTR | KEYWORD_FN_PTR_CURRY | KEYWORD_IS_DEF_VAR
+            | KEYWORD_EVAL => return false,
+            #[cfg(not(feature = "no_closure"))]
+            crate::engine::KEYWORD_IS_SHARED => return false,
+            #[cfg(not(feature = "no_function"))]
+            crate::engine::KEYWORD_IS_DEF_FN => return false,
+            _ => (),
+        }
+
+        !matches!(x.args.first(), Some(Expr::ThisPtr(..)))
+    }
+    /// Is a function call a native operator short-circuited under Fast Operators mode?
+    fn is_fast_op(&self, x: &FnCallExpr) -> bool {
+        self.engine.fast_operators()
+            && match x.op_token {
+                Some(Token::Bang) if x.args.len() == 1 => true,
+                Some(_) => x.args.len() == 2,
+                None => false,
+            }
+    }
+    /// Compile a function call argument into instructions pushing its value.
+    fn compile_arg(&mut self, expr: &Expr) {
+        match expr.get_literal_value() {
+            Some(value) => {
+                self.emit(Instr::Const(value, expr.start_position()));
+            }
+            None => self.compile_expr(expr),
+        }
+    }
+    /// Compile a function call into instructions pushing its result.
+    fn compile_call(&mut self, x: &FnCallExpr, pos: Position) {
+        let mut site = CallSite {
+            name: x.name.clone(),
+            op_token: x.op_token.clone(),
+            hashes: x.hashes,
+            binding: x.binding.clone(),
+            num_args: x.args.len(),
+            first_var: None,
+            #[cfg(not(feature = "no_function"))]
+            slot: None,
+            pos,
+        };
+
+        if self.is_fast_op(x) {
+            x.args.iter().for_each(|arg| self.compile_arg(arg));
+
+            self.emit(if x.args.len() == 1 {
+                Instr::Not(site.into())
+            } else {
+                Instr::BinaryOp(site.into())
+            });
+            return;
+        }
+
+        // Pass the first argument by reference if it is a variable
+        let args = match x.args.split_first() {
+            Some((first @ Expr::Variable(..), rest)) => {
+                self.emit(Instr::Track(first.position()));
+                site.num_args -= 1;
+                site.first_var = Some(first.clone());
+                rest
+            }
+            _ => &x.args[..],
+        };
+
+        args.iter().for_each(|arg| self.compile_arg(arg));
+
+        #[cfg(not(feature = "no_function"))]
+        if !x.hashes.is_native_only() {
+            site.slot = Some(self.chunk.slots);
+            self.chunk.slots += 1;
+        }
+
+        self.emit(Instr::Call(site.into()));
+    }
+}
+
+/// State of a statements block being run.
+struct BlockState {
+    /// Number of variables in the scope.
+    scope_len: usize,
+    /// Always search the scope for variables?
+    always_search_scope: bool,
+    /// Number of imported modules.
+    #[cfg(not(feature = "no_module"))]
+    imports_len: usize,
+    /// Number of function resolution caches.
+    caches_len: usize,
+}
+
+/// State of a loop being run.
+#[derive(Clone, Copy)]
+struct LoopState {
+    /// Instruction to jump to on `break`.
+    brk: usize,
+    /// Instruction to jump to on `continue`.
+    cont: usize,
+    /// Number of blocks being run.
+    blocks: usize,
+    /// Number of values on the stack.
+    stack: usize,
+}
+
+/// State of a `for` loop being run.
+struct ForState {
+    /// Source of values.
+    source: ForSource,
+    /// Number of variables in the scope before the loop.
+    scope_len: usize,
+    /// Index of the counter variable, if any.
+    counter_index: Option<usize>,
+    /// Index of the loop variable.
+    index: usize,
+    /// Number of iterations.
+    count: usize,
+}
+
+/// State of the bytecode interpreter.
+struct Vm {
+    /// Index of the next instruction.
+    pc: usize,
+    /// Value of the current statement.
+    value: Dynamic,
+    /// Stack of values.
+    stack: StaticVec<Dynamic>,
+    /// Statements blocks being run.
+    blocks: StaticVec<BlockState>,
+    /// Loops being run.
+    loops: StaticVec<LoopState>,
+    /// `for` loops being run.
+    fors: StaticVec<ForState>,
+    /// Number of function resolution caches at the start.
+    #[cfg(not(feature = "no_module"))]
+    caches_len: usize,
+    /// Resolved function call sites.
+    #[cfg(not(feature = "no_function"))]
+    slots: StaticVec<Option<Slot>>,
+    /// Number of imported modules when the function call sites were resolved.
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_module"))]
+    slots_imports_len: usize,
+}
+
+impl Vm {
+    /// Pop a value from the stack.
+    #[inline(always)]
+    fn pop(&mut self) -> Dynamic {
+        self.stack.pop().unwrap()
+    }
+}
+
+impl Engine {
+    /// Compile a list of statements into bytecode.
+    fn compile_bytecode(&self, statements: &[Stmt]) -> Chunk {
+        let mut compiler = Compiler {
+            engine: self,
+            chunk: Chunk::default(),
+        };
+
+        for stmt in statements {
+            compiler.compile_stmt(stmt, false);
+        }
+
+        compiler.chunk
+    }
+
+    /// Evaluate a list of statements via bytecode, in the same manner as
+    /// [`eval_stmt_block`][Engine::eval_stmt_block].
+    ///
+    /// The bytecode of a function body is compiled once and cached.
+    /// Top-level statements are compiled only when they contain loops.
+    pub(crate) fn eval_bytecode(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: Option<&mut Dynamic>,
+        statements: &[Stmt],
+        #[cfg(not(feature = "no_function"))] fn_def: Option<&Shared<ScriptFuncDef>>,
+        restore_orig_state: bool,
+    ) -> RhaiResult {
+        if statements.is_empty() {
+            return Ok(Dynamic::UNIT);
+        }
+
+        // Debugging hooks are run by the tree-walking evaluator
+        #[cfg(feature = "debugging")]
+        if self.is_debugger_registered() {
+            return self.eval_stmt_block(
+                global,
+                caches,
+                scope,
+                this_ptr,
+                statements,
+                restore_orig_state,
+            );
+        }
+
+        #[cfg(not(feature = "no_function"))]
+        let cached = fn_def.map(|fn_def| match caches.bytecode(fn_def) {
+            Some(chunk) => chunk,
+            None => {
+                let chunk = Shared::new(self.compile_bytecode(statements));
+                caches.cache_bytecode(fn_def, chunk.clone());
+                chunk
+            }
+        });
+        #[cfg(feature = "no_function")]
+        let cached: Option<crate::Shared<Chunk>> = None;
+
+        let compiled;
+
+        let chunk = match cached {
+            Some(ref chunk) => &**chunk,
+            None if statements.iter().any(has_loop) => {
+                compiled = self.compile_bytecode(statements);
+                &compiled
+            }
+            None => {
+                return self.eval_stmt_block(
+                    global,
+                    caches,
+                    scope,
+                    this_ptr,
+                    statements,
+                    restore_orig_state,
+                )
+            }
+        };
+
+        // Restore scope at end of block if necessary
+        defer! { scope if restore_orig_state => rewind; let orig_scope_len = scope.len(); }
+
+        // Restore global state at end of block if necessary
+        let orig_always_search_scope = global.always_search_scope;
+        #[cfg(not(feature = "no_module"))]
+        let orig_imports_len = global.num_imports();
+
+        if restore_orig_state {
+            global.scope_level += 1;
+        }
+
+        defer! { global if restore_orig_state => move |g| {
+            g.scope_level -= 1;
+
+            #[cfg(not(feature = "no_module"))]
+            g.truncate_imports(orig_imports_len);
+
+            // The impact of new local variables goes away at the end of a block
+            // because any new variables introduced will go out of scope
+            g.always_search_scope = orig_always_search_scope;
+        }}
+
+        // Pop new function resolution caches at end of block
+        defer! {
+            caches => rewind_fn_resolution_caches;
+            let orig_fn_resolution_caches_len = caches.fn_resolution_caches_len();
+        }
+
+        self.run_bytecode(
+            global,
+            caches,
+            scope,
+            this_ptr,
+            chunk,
+            orig_fn_resolution_caches_len,
+            restore_orig_state,
+        )
+    }
+
+    /// Run a [`Chunk`], catching `break` and `continue` for the loops inside it.
+    fn run_bytecode(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        mut this_ptr: Option<&mut Dynamic>,
+        chunk: &Chunk,
+        caches_len: usize,
+        restore_orig_state: bool,
+    ) -> RhaiResult {
+        #[cfg(feature = "no_module")]
+        let _ = caches_len;
+
+        let vm = &mut Vm {
+            pc: 0,
+            value: Dynamic::UNIT,
+            stack: StaticVec::new_const(),
+            blocks: StaticVec::new_const(),
+            loops: StaticVec::new_const(),
+            fors: StaticVec::new_const(),
+            #[cfg(not(feature = "no_module"))]
+            caches_len,
+            #[cfg(not(feature = "no_function"))]
+            slots: StaticVec::new_const(),
+            #[cfg(not(feature = "no_function"))]
+            #[cfg(not(feature = "no_module"))]
+            slots_imports_len: global.num_imports(),
+        };
+
+        loop {
+            let this_ptr = this_ptr.as_deref_mut();
+
+            let mut err = match self.exec_bytecode(
+                global,
+                caches,
+                scope,
+                this_ptr,
+                chunk,
+                vm,
+                restore_orig_state,
+            ) {
+                Ok(()) => return Ok(mem::take(&mut vm.value)),
+                Err(err) => err,
+            };
+
+            if let Some(&l) = vm.loops.last() {
+                if let ERR::LoopBreak(is_break, ref mut value, ..) = *err {
+                    self.leave_blocks(global, caches, scope, vm, l.blocks);
+                    vm.stack.truncate(l.stack);
+
+                    if is_break {
+                        vm.value = mem::take(value);
+                        vm.pc = l.brk;
+                    } else {
+                        vm.pc = l.cont;
+                    }
+                    continue;
+                }
+            }
+
+            for f in vm.fors.iter().rev() {
+                scope.rewind(f.scope_len);
+            }
+            self.leave_blocks(global, caches, scope, vm, 0);
+
+            return Err(err);
+        }
+    }
+
+    /// Leave statements blocks being run, down to a certain number of blocks.
+    fn leave_blocks(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        vm: &mut Vm,
+        len: usize,
+    ) {
+        while vm.blocks.len() > len {
+            let block = vm.blocks.pop().unwrap();
+
+            // Function call sites resolved with the imported modules must be resolved again
+            #[cfg(not(feature = "no_function"))]
+            #[cfg(not(feature = "no_module"))]
+            if global.num_imports() > block.imports_len {
+                vm.slots.clear();
+            }
+
+            caches.rewind_fn_resolution_caches(block.caches_len);
+            global.scope_level -= 1;
+            #[cfg(not(feature = "no_module"))]
+            global.truncate_imports(block.imports_len);
+            global.always_search_scope = block.always_search_scope;
+            scope.rewind(block.scope_len);
+        }
+    }
+
+    /// Run the instructions of a [`Chunk`] until the end or an error.
+    fn exec_bytecode(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        mut this_ptr: Option<&mut Dynamic>,
+        chunk: &Chunk,
+        vm: &mut Vm,
+        restore_orig_state: bool,
+    ) -> RhaiResultOf<()> {
+        while let Some(instr) = chunk.code.get(vm.pc) {
+            vm.pc += 1;
+
+            match instr {
+                Instr::Track(pos) => self.track_operation(global, *pos)?,
+
+                Instr::Const(value, pos) => {
+                    self.track_operation(global, *pos)?;
+                    vm.stack.push(value.clone());
+                }
+
+                Instr::Load(expr) => {
+                    self.track_operation(global, expr.position())?;
+
+                    let value = match **expr {
+                        // Short index into the scope
+                        Expr::Variable(_, Some(i), ..)
+                            if !global.always_search_scope && self.resolve_var.is_none() =>
+                        {
+                            scope
+                                .get_mut_by_index(scope.len() - i.get() as usize)
+                                .clone()
+                        }
+                        _ => self
+                            .search_namespace(global, caches, scope, this_ptr.as_deref_mut(), expr)?
+                            .take_or_clone(),
+                    };
+
+                    vm.stack.push(value);
+                }
+
+                Instr::LoadThis(pos) => {
+                    self.track_operation(global, *pos)?;
+
+                    let value = this_ptr
+                        .as_deref()
+                        .cloned()
+                        .ok_or_else(|| ERR::ErrorUnboundThis(*pos))?;
+
+                    vm.stack.push(value);
+                }
+
+                Instr::Eval(expr) => {
+                    let this_ptr = this_ptr.as_deref_mut();
+                    let value = self.eval_expr(global, caches, scope, this_ptr, expr)?;
+                    vm.stack.push(value);
+                }
+
+                Instr::Exec(stmt, nested) => {
+                    let rewind_scope = *nested || restore_orig_state;
+
+                    #[cfg(not(feature = "no_module"))]
+                    let orig_imports_len = global.num_imports();
+
+                    let this_ptr = this_ptr.as_deref_mut();
+                    vm.value =
+                        self.eval_stmt(global, caches, scope, this_ptr, stmt, rewind_scope)?;
+
+                    #[cfg(not(feature = "no_module"))]
+                    if matches!(**stmt, Stmt::Import(..)) {
+                        let caches_len = vm.blocks.last().map_or(vm.caches_len, |b| b.caches_len);
+
+                        self.update_caches_on_import(
+                            global,
+                            caches,
+                            orig_imports_len,
+                            caches_len,
+                            rewind_scope,
+                        );
+                    }
+                }
+
+                Instr::Call(site) => {
+                    let this_ptr = this_ptr.as_deref_mut();
+                    let value = self.exec_call_site(global, caches, scope, this_ptr, site, vm)?;
+                    vm.stack.push(value);
+                }
+
+                Instr::BinaryOp(site) => {
+                    let rhs = vm.pop().flatten();
+                    let lhs = vm.pop().flatten();
+                    let op_token = site.op_token.as_ref().unwrap();
+
+                    let value = self.eval_fast_binary_op(
+                        global,
+                        caches,
+                        &site.name,
+                        op_token,
+                        site.hashes,
+                        lhs,
+                        rhs,
+                        site.pos,
+                    )?;
+
+                    vm.stack.push(value);
+                }
+
+                Instr::Not(site) => {
+                    let value = vm.pop().flatten();
+                    let hashes = site.hashes;
+                    let value = self
+                        .eval_fast_unary_not(global, caches, &site.name, hashes, value, site.pos)?;
+                    vm.stack.push(value);
+                }
+
+                Instr::SetValue(flatten) => {
+                    let value = vm.pop();
+                    vm.value = if *flatten { value.flatten() } else { value };
+                }
+
+                Instr::Unit => vm.value = Dynamic::UNIT,
+
+                Instr::PushValue => {
+                    let value = mem::take(&mut vm.value);
+                    vm.stack.push(value);
+                }
+
+                Instr::Enter => {
+                    vm.blocks.push(BlockState {
+                        scope_len: scope.len(),
+                        always_search_scope: global.always_search_scope,
+                        #[cfg(not(feature = "no_module"))]
+                        imports_len: global.num_imports(),
+                        caches_len: caches.fn_resolution_caches_len(),
+                    });
+                    global.scope_level += 1;
+                }
+
+                Instr::Leave => {
+                    let len = vm.blocks.len() - 1;
+                    self.leave_blocks(global, caches, scope, vm, len);
+                }
+
+                Instr::Jump(target) => vm.pc = *target,
+
+                Instr::JumpIf(when, target, pos) => {
+                    let condition = self.condition_value(vm.pop(), *pos)?;
+
+                    if condition == *when {
+                        vm.pc = *target;
+                    }
+                }
+
+                Instr::ShortCircuit(when, target, pos) => {
+                    let value = self.condition_value(vm.pop(), *pos)?;
+
+                    if value == *when {
+                        vm.stack.push(value.into());
+                        vm.pc = *target;
+                    }
+                }
+
+                Instr::ToBool(pos) => {
+                    let value = self.condition_value(vm.pop(), *pos)?;
+
+                    vm.stack.push(value.into());
+                }
+
+                Instr::Coalesce(target) => {
+                    if vm.stack.last().unwrap().is_unit() {
+                        vm.stack.pop();
+                    } else {
+                        vm.pc = *target;
+                    }
+                }
+
+                Instr::EnterLoop(brk, cont) => vm.loops.push(LoopState {
+                    brk: *brk,
+                    cont: *cont,
+                    blocks: vm.blocks.len(),
+                    stack: vm.stack.len(),
+                }),
+
+                Instr::LeaveLoop => {
+                    vm.loops.pop();
+                }
+
+                Instr::Spin(pos) => loop {
+                    self.track_operation(global, *pos)?;
+                },
+
+                Instr::CheckVar(index) => {
+                    let stmt = &chunk.vars[*index];
+                    let this_ptr = this_ptr.as_deref_mut();
+                    self.check_var_stmt(global, caches, scope, this_ptr, stmt)?;
+                }
+
+                Instr::DefineVar(index, nested) => {
+                    let stmt = &chunk.vars[*index];
+                    let value = vm.pop().flatten();
+                    let rewind_scope = *nested || restore_orig_state;
+                    self.define_var_stmt(global, scope, stmt, value, rewind_scope)?;
+                    vm.value = Dynamic::UNIT;
+                }
+
+                Instr::Assign(stmt) => {
+                    let value = vm.pop().flatten();
+                    let this_ptr = this_ptr.as_deref_mut();
+                    self.eval_assignment(global, caches, scope, this_ptr, stmt, value)?;
+                    vm.value = Dynamic::UNIT;
+                }
+
+                Instr::ForGuard(index) => {
+                    let f = &chunk.fors[*index];
+                    self.check_for_loop_vars(scope, &f.var, f.counter.as_ref())?;
+                }
+
+                Instr::ForStart(index, brk, cont) => {
+                    let f = &chunk.fors[*index];
+                    let iter_obj = vm.pop().flatten();
+                    let source = self.make_for_source(global, iter_obj, f.expr_start_pos)?;
+                    let scope_len = scope.len();
+
+                    // Add the loop variables
+                    let (counter_index, index) =
+                        self.push_for_loop_vars(scope, &f.var, f.counter.as_ref());
+
+                    vm.fors.push(ForState {
+                        source,
+                        scope_len,
+                        counter_index,
+                        index,
+                        count: 0,
+                    });
+                    vm.loops.push(LoopState {
+                        brk: *brk,
+                        cont: *cont,
+                        blocks: vm.blocks.len(),
+                        stack: vm.stack.len(),
+                    });
+                }
+
+                Instr::ForNext(index, done) => {
+                    let f = &chunk.fors[*index];
+                    let state = vm.fors.last_mut().unwrap();
+                    let counter = f.counter.as_ref().zip(state.counter_index);
+
+                    if !self.next_for_iteration(
+                        global,
+                        caches,
+                        scope,
+                        &mut state.source,
+                        counter,
+                        state.index,
+                        state.count,
+                        f.expr_pos,
+                        f.empty_body,
+                    )? {
+                        vm.pc = *done;
+                        continue;
+                    }
+
+                    state.count += 1;
+                }
+
+                Instr::ForEnd => {
+                    let f = vm.fors.pop().unwrap();
+                    scope.rewind(f.scope_len);
+                    vm.loops.pop();
+                }
+
+                Instr::Break(is_break, has_value, pos) => {
+                    let value = if *has_value { vm.pop() } else { Dynamic::UNIT };
+                    return Err(ERR::LoopBreak(*is_break, value, *pos).into());
+                }
+
+                Instr::Return(has_value, pos) => {
+                    let value = if *has_value {
+                        vm.pop().flatten()
+                    } else {
+                        Dynamic::UNIT
+                    };
+                    return Err(ERR::Return(value, *pos).into());
+                }
+
+                Instr::Throw(has_value, pos) => {
+                    let value = if *has_value {
+                        vm.pop().flatten()
+                    } else {
+                        Dynamic::UNIT
+                    };
+                    return Err(ERR::ErrorRuntime(value, *pos).into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Call a function with its arguments on the stack.
+    fn exec_call_site(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: Option<&mut Dynamic>,
+        site: &CallSite,
+        vm: &mut Vm,
+    ) -> RhaiResult {
+        let start = vm.stack.len() - site.num_args;
+        let mut arg_values = vm
+            .stack
+            .drain(start..)
+            .map(Dynamic::flatten)
+            .collect::<FnArgsVec<_>>();
+        let mut args = FnArgsVec::with_capacity(arg_values.len() + 1);
+        let mut is_ref_mut = false;
+
+        let first = match site.first_var {
+            Some(ref first_expr) => {
+                let mut target =
+                    self.search_namespace(global, caches, scope, this_ptr, first_expr)?;
+
+                if target.as_ref().is_read_only() {
+                    target = target.into_owned();
+                }
+
+                if target.is_shared() || target.is_temp_value() {
+                    arg_values.insert(0, target.take_or_clone().flatten());
+                    None
+                } else {
+                    // Turn it into a method call only if the object is not shared and not a simple value
+                    Some(target)
+                }
+            }
+            None => None,
+        };
+
+        if let Some(target) = first {
+            is_ref_mut = true;
+            args.push(target.take_ref().unwrap());
+        }
+
+        args.extend(arg_values.iter_mut());
+
+        let name = site.name.as_str();
+        let op_token = site.op_token.as_ref();
+
+        // Call the script-defined function resolved for the call site, if any
+        #[cfg(not(feature = "no_function"))]
+        if let Some(slot) = site.slot {
+            if let Slot::Script(fn_def, environ, source) =
+                self.resolve_slot(global, caches, vm, site.hashes, slot)
+            {
+                // Check for data race.
+                #[cfg(not(feature = "no_closure"))]
+                crate::func::ensure_no_data_race(name, &args, is_ref_mut)?;
+
+                defer! { let orig_level = global.level; global.level += 1 }
+
+                return self.call_resolved_script_fn(
+                    global,
+                    caches,
+                    None,
+                    fn_def,
+                    environ.as_ref(),
+                    source.clone(),
+                    &mut args,
+                    is_ref_mut,
+                    false,
+                    site.pos,
+                );
+            }
+        }
+
+        self.exec_fn_call(
+            global,
+            caches,
+            None,
+            name,
+            op_token,
+            site.hashes,
+            site.binding.as_deref(),
+            &mut args,
+            is_ref_mut,
+            false,
+            site.pos,
+        )
+        .map(|(v, ..)| v)
+    }
+}
+
+impl Engine {
+    /// Get the resolution of a function call site, resolving it if necessary.
+    #[cfg(not(feature = "no_function"))]
+    fn resolve_slot<'v>(
+        &self,
+        global: &GlobalRuntimeState,
+        caches: &mut Caches,
+        vm: &'v mut Vm,
+        hashes: FnCallHashes,
+        slot: usize,
+    ) -> &'v Slot {
+        // Imported modules may contain global functions
+        #[cfg(not(feature = "no_module"))]
+        if global.num_imports() != vm.slots_imports_len {
+            vm.slots.clear();
+            vm.slots_imports_len = global.num_imports();
+        }
+
+        if vm.slots.len() <= slot {
+            vm.slots.resize_with(slot + 1, || None);
+        }
+
+        vm.slots[slot].get_or_insert_with(|| {
+            let local_entry = &mut None;
+            let hash = hashes.script();
+
+            match self.resolve_fn(global, caches, local_entry, None, hash, None, false) {
+                Some(FnResolutionCacheEntry {
+                    func: RhaiFunc::Script { fn_def, environ },
+                    source,
+                }) => Slot::Script(fn_def.clone(), environ.clone(), source.clone()),
+                _ => Slot::Other,
+            }
+        })
+    }
+}
+
+/// Does a statement contain a loop, not counting those inside expressions?
+fn has_loop(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::While(..) | Stmt::Do(..) | Stmt::For(..) => true,
+        Stmt::Block(stmts, ..) => stmts.iter().any(has_loop),
+        Stmt::If(x, ..) => x.body.iter().chain(x.branch.iter()).any(has_loop),
+        Stmt::TryCatch(x, ..) => x.body.iter().chain(x.branch.iter()).any(has_loop),
+        _ => false,
+    }
+}
@@ -2,6 +2,9 @@
 
 use crate::func::{RhaiFunc, StraightHashMap};
 use crate::types::BloomFilterU64;
+#[cfg(all(feature = "bytecode", not(feature = "stackless")))]
+#[cfg(not(feature = "no_function"))]
+use crate::{ast::ScriptFuncDef, eval::Chunk, Shared};
 use crate::{ImmutableString, StaticVec};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
@@ -44,9 +47,16 @@ impl FnResolutionCache {
 ///
 /// The following caches are contained inside this type:
 /// * A stack of [function resolution caches][FnResolutionCache]
+/// * The bytecode of script-defined functions (under the `bytecode` feature)
 #[derive(Debug, Clone)]
 pub struct Caches {
     fn_resolution: StaticVec<FnResolutionCache>,
+    /// Bytecode of script-defined functions, keyed by the address of the function definition.
+    ///
+    /// The function definition is kept alive so that its address is never reused.
+    #[cfg(all(feature = "bytecode", not(feature = "stackless")))]
+    #[cfg(not(feature = "no_function"))]
+    bytecode: std::collections::BTreeMap<usize, (Shared<ScriptFuncDef>, Shared<Chunk>)>,
 }
 
 impl Caches {
@@ -56,6 +66,9 @@ impl Caches {
     pub const fn new() -> Self {
         Self {
             fn_resolution: StaticVec::new_const(),
+            #[cfg(all(feature = "bytecode", not(feature = "stackless")))]
+            #[cfg(not(feature = "no_function"))]
+            bytecode: std::collections::BTreeMap::new(),
         }
     }
     /// Get the number of function resolution cache(s) in the stack.
@@ -86,4 +99,21 @@ impl Caches {
     pub fn rewind_fn_resolution_caches(&mut self, len: usize) {
         self.fn_resolution.truncate(len);
     }
+    /// Get the cached bytecode of a script-defined function, if any.
+    #[cfg(all(feature = "bytecode", not(feature = "stackless")))]
+    #[cfg(not(feature = "no_function"))]
+    #[inline]
+    #[must_use]
+    pub(crate) fn bytecode(&self, fn_def: &Shared<ScriptFuncDef>) -> Option<Shared<Chunk>> {
+        let key = Shared::as_ptr(fn_def) as usize;
+        self.bytecode.get(&key).map(|(.., chunk)| chunk.clone())
+    }
+    /// Cache the bytecode of a script-defined function.
+    #[cfg(all(feature = "bytecode", not(feature = "stackless")))]
+    #[cfg(not(feature = "no_function"))]
+    #[inline]
+    pub(crate) fn cache_bytecode(&mut self, fn_def: &Shared<ScriptFuncDef>, chunk: Shared<Chunk>) {
+        let key = Shared::as_ptr(fn_def) as usize;
+        self.bytecode.insert(key, (fn_def.clone(), chunk));
+    }
 }
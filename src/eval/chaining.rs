@@ -71,7 +71,7 @@ impl Engine {
         let args = &mut [target, idx];
 
         self.exec_native_fn_call(
-            global, caches, FN_IDX_GET, None, hash, args, true, false, pos,
+            global, caches, FN_IDX_GET, None, hash, None, args, true, false, pos,
         )
         .map(|(r, ..)| r)
     }
@@ -94,7 +94,7 @@ impl Engine {
         let args = &mut [target, idx, new_val];
 
         self.exec_native_fn_call(
-            global, caches, FN_IDX_SET, None, hash, args, is_ref_mut, false, pos,
+            global, caches, FN_IDX_SET, None, hash, None, args, is_ref_mut, false, pos,
         )
     }
 
@@ -395,7 +395,10 @@ impl Engine {
         #[cfg(not(feature = "debugging"))]
         let scope2 = ();
 
-        match (lhs, new_val) {
+        #[cfg(feature = "debugging")]
+        let mut watched = None;
+
+        let (result, ..) = match (lhs, new_val) {
             // this.??? or this[???]
             (Expr::ThisPtr(var_pos), new_val) => {
                 self.track_operation(global, *var_pos)?;
@@ -403,7 +406,13 @@ impl Engine {
                 #[cfg(feature = "debugging")]
                 self.run_debugger(global, caches, scope, this_ptr.as_deref_mut(), lhs)?;
 
-                this_ptr.map_or_else(
+                #[cfg(feature = "debugging")]
+                {
+                    watched =
+                        self.watch_values(global, caches, scope, this_ptr.as_deref_mut(), lhs);
+                }
+
+                this_ptr.as_deref_mut().map_or_else(
                     || Err(ERR::ErrorUnboundThis(*var_pos).into()),
                     |this_ptr| {
                         let target = &mut this_ptr.into();
@@ -422,7 +431,19 @@ impl Engine {
                 #[cfg(feature = "debugging")]
                 self.run_debugger(global, caches, scope, this_ptr.as_deref_mut(), lhs)?;
 
-                let target = &mut self.search_namespace(global, caches, scope, this_ptr, lhs)?;
+                #[cfg(feature = "debugging")]
+                {
+                    watched =
+                        self.watch_values(global, caches, scope, this_ptr.as_deref_mut(), lhs);
+                }
+
+                let target = &mut self.search_namespace(
+                    global,
+                    caches,
+                    scope,
+                    this_ptr.as_deref_mut(),
+                    lhs,
+                )?;
 
                 self.eval_dot_index_chain_raw(
                     global, caches, scope2, None, lhs, expr, target, rhs, idx_values, new_val,
@@ -438,12 +459,24 @@ impl Engine {
                 let item_ptr = &mut value.into();
 
                 self.eval_dot_index_chain_raw(
-                    global, caches, scope2, this_ptr, lhs_expr, expr, item_ptr, rhs, idx_values,
+                    global,
+                    caches,
+                    scope2,
+                    this_ptr.as_deref_mut(),
+                    lhs_expr,
+                    expr,
+                    item_ptr,
+                    rhs,
+                    idx_values,
                     None,
                 )
             }
-        }
-        .map(|(v, ..)| v)
+        }?;
+
+        #[cfg(feature = "debugging")]
+        self.check_watch_points(global, caches, scope, this_ptr, expr, watched)?;
+
+        Ok(result)
     }
 
     /// Evaluate a chain of indexes and store the results in a [`FnArgsVec`].
@@ -747,7 +780,11 @@ impl Engine {
                         defer! { global if Some(reset) => move |g| g.debugger_mut().reset_status(reset) }
 
                         let crate::ast::FnCallExpr {
-                            name, hashes, args, ..
+                            name,
+                            hashes,
+                            args,
+                            binding,
+                            ..
                         } = &**x;
 
                         // Truncate the index values upon exit
@@ -757,7 +794,15 @@ impl Engine {
                         let arg1_pos = args.first().map_or(Position::NONE, Expr::position);
 
                         self.make_method_call(
-                            global, caches, name, *hashes, target, call_args, arg1_pos, *pos,
+                            global,
+                            caches,
+                            name,
+                            *hashes,
+                            binding.as_deref(),
+                            target,
+                            call_args,
+                            arg1_pos,
+                            *pos,
                         )
                     }
                     // {xxx:map}.id op= ???
@@ -822,8 +867,8 @@ impl Engine {
 
                             let (mut orig_val, ..) = self
                                 .exec_native_fn_call(
-                                    global, caches, getter, None, *hash_get, args, is_ref_mut,
-                                    false, *pos,
+                                    global, caches, getter, None, *hash_get, None, args,
+                                    is_ref_mut, false, *pos,
                                 )
                                 .or_else(|err| match *err {
                                     // Try an indexer if property does not exist
@@ -858,7 +903,8 @@ impl Engine {
                         let args = &mut [target.as_mut(), &mut new_val];
 
                         self.exec_native_fn_call(
-                            global, caches, setter, None, *hash_set, args, is_ref_mut, false, *pos,
+                            global, caches, setter, None, *hash_set, None, args, is_ref_mut, false,
+                            *pos,
                         )
                         .or_else(|err| match *err {
                             // Try an indexer if property does not exist
@@ -886,7 +932,8 @@ impl Engine {
                         let args = &mut [target.as_mut()];
 
                         self.exec_native_fn_call(
-                            global, caches, getter, None, *hash_get, args, is_ref_mut, false, *pos,
+                            global, caches, getter, None, *hash_get, None, args, is_ref_mut, false,
+                            *pos,
                         )
                         .map_or_else(
                             |err| match *err {
@@ -949,7 +996,11 @@ impl Engine {
                                 defer! { global if Some(reset) => move |g| g.debugger_mut().reset_status(reset) }
 
                                 let crate::ast::FnCallExpr {
-                                    name, hashes, args, ..
+                                    name,
+                                    hashes,
+                                    args,
+                                    binding,
+                                    ..
                                 } = &**x;
 
                                 // Truncate the index values upon exit
@@ -959,7 +1010,15 @@ impl Engine {
                                 let arg1_pos = args.first().map_or(Position::NONE, Expr::position);
 
                                 self.make_method_call(
-                                    global, caches, name, *hashes, target, call_args, arg1_pos, pos,
+                                    global,
+                                    caches,
+                                    name,
+                                    *hashes,
+                                    binding.as_deref(),
+                                    target,
+                                    call_args,
+                                    arg1_pos,
+                                    pos,
                                 )?
                                 .0
                                 .into()
@@ -991,8 +1050,8 @@ impl Engine {
                                 // Assume getters are always pure
                                 let (mut val, ..) = self
                                     .exec_native_fn_call(
-                                        global, caches, getter, None, *hash_get, args, is_ref_mut,
-                                        false, pos,
+                                        global, caches, getter, None, *hash_get, None, args,
+                                        is_ref_mut, false, pos,
                                     )
                                     .or_else(|err| match *err {
                                         // Try an indexer if property does not exist
@@ -1028,7 +1087,7 @@ impl Engine {
                                     // The return value is thrown away and not used.
                                     let _ = self
                                         .exec_native_fn_call(
-                                            global, caches, setter, None, *hash_set, args,
+                                            global, caches, setter, None, *hash_set, None, args,
                                             is_ref_mut, false, pos,
                                         )
                                         .or_else(|err| match *err {
@@ -1072,7 +1131,11 @@ impl Engine {
                                     defer! { global if Some(reset) => move |g| g.debugger_mut().reset_status(reset) }
 
                                     let crate::ast::FnCallExpr {
-                                        name, hashes, args, ..
+                                        name,
+                                        hashes,
+                                        args,
+                                        binding,
+                                        ..
                                     } = &**f;
 
                                     // Truncate the index values upon exit
@@ -1082,7 +1145,15 @@ impl Engine {
                                     let pos1 = args.first().map_or(Position::NONE, Expr::position);
 
                                     self.make_method_call(
-                                        global, caches, name, *hashes, target, call_args, pos1, pos,
+                                        global,
+                                        caches,
+                                        name,
+                                        *hashes,
+                                        binding.as_deref(),
+                                        target,
+                                        call_args,
+                                        pos1,
+                                        pos,
                                     )?
                                     .0
                                 };
@@ -0,0 +1,447 @@
+//! Module implementing the collection of code coverage via the debugging interface.
+#![cfg(feature = "debugging")]
+#![cfg(not(feature = "no_position"))]
+
+use super::{DebuggerCommand, DebuggerEvent};
+use crate::ast::{ASTNode, Expr, Stmt, StmtBlock};
+use crate::func::{locked_read, locked_write};
+use crate::{Engine, Identifier, ImmutableString, Locked, Position, Shared, AST};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    fmt::Write,
+};
+
+/// _(debugging)_ Kind of branching construct recorded by [`Coverage`].
+/// Exported under the `debugging` feature only.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum BranchKind {
+    /// `if` statement, with the `then` and `else` branches.
+    If,
+    /// `switch` statement, with one branch per case in order, followed by a branch for no match
+    /// if there is no default case.
+    Switch,
+    /// `&&` operator, with branches for evaluating the right-hand side and short-circuiting.
+    And,
+    /// `||` operator, with branches for evaluating the right-hand side and short-circuiting.
+    Or,
+    /// `??` operator, with branches for evaluating the right-hand side and short-circuiting.
+    Coalesce,
+}
+
+impl fmt::Display for BranchKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::If => "if",
+            Self::Switch => "switch",
+            Self::And => "&&",
+            Self::Or => "||",
+            Self::Coalesce => "??",
+        })
+    }
+}
+
+/// _(debugging)_ Coverage of a branching construct.
+/// Exported under the `debugging` feature only.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct BranchCoverage {
+    /// Kind of branching construct.
+    pub kind: BranchKind,
+    /// [Position] of the branching construct.
+    pub pos: Position,
+    /// Number of times the branching construct is run.
+    pub hits: u64,
+    /// Number of times each branch is taken.
+    pub taken: Vec<u64>,
+}
+
+/// _(debugging)_ Coverage of a source.
+/// Exported under the `debugging` feature only.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FileCoverage {
+    /// Source (empty if not available).
+    pub source: ImmutableString,
+    /// Line numbers with the number of times each line is run.
+    pub lines: Vec<(usize, u64)>,
+    /// Branching constructs in order of position.
+    pub branches: Vec<BranchCoverage>,
+}
+
+/// Key identifying a node: its position, and whether it is a statement (0), a function call (1)
+/// or another expression (2).
+///
+/// Different nodes may share the same position, e.g. a binary operator and its left-hand side.
+type NodeKey = (Position, u8);
+
+/// Branching construct, with the first node run by each branch, if any.
+type BranchSite = (BranchKind, Vec<Option<NodeKey>>);
+
+/// Coverage data collected for a source.
+#[derive(Debug, Clone, Default)]
+struct FileData {
+    /// Number of times each node is run.
+    hits: BTreeMap<NodeKey, u64>,
+    /// Lines containing code.
+    lines: BTreeSet<usize>,
+    /// Branching constructs.
+    sites: BTreeMap<NodeKey, BranchSite>,
+}
+
+/// Coverage data collected for all sources.
+#[derive(Debug, Clone, Default)]
+struct CoverageData {
+    /// Data of each source.
+    files: BTreeMap<Identifier, FileData>,
+    /// Address and key of the last node run.
+    last: Option<(usize, NodeKey)>,
+}
+
+/// _(debugging)_ A collector of line and branch coverage of scripts, built on the debugging
+/// interface.
+/// Exported under the `debugging` feature only.
+///
+/// Not available under `no_position`.
+///
+/// Cloning a [`Coverage`] yields a handle to the same collected data, so runs with multiple
+/// [`Engine`]'s are merged.
+///
+/// # Example
+///
+/// ```
+/// use rhai::debugger::Coverage;
+/// use rhai::Engine;
+///
+/// let mut engine = Engine::new();
+/// let coverage = Coverage::new();
+///
+/// engine.register_coverage(&coverage);
+///
+/// let mut ast = engine.compile("let x = 42;\nif x > 0 {\n    x += 1;\n} else {\n    x -= 1;\n}")?;
+/// ast.set_source("test");
+///
+/// coverage.add_ast(&ast);
+/// engine.run_ast(&ast)?;
+///
+/// let file = &coverage.files()[0];
+///
+/// assert_eq!(file.source, "test");
+/// assert_eq!(file.lines, [(1, 1), (2, 1), (3, 1), (5, 0)]);
+/// assert_eq!(file.branches[0].taken, [1, 0]);
+/// # Ok::<_, Box<rhai::EvalAltResult>>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Coverage(Shared<Locked<CoverageData>>);
+
+impl Coverage {
+    /// Create a new [`Coverage`].
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Is there no coverage data?
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        locked_read(&self.0).files.is_empty()
+    }
+    /// Discard all coverage data.
+    #[inline]
+    pub fn clear(&self) {
+        *locked_write(&self.0) = CoverageData::default();
+    }
+    /// Record the lines and branching constructs of an [`AST`], under its source, so that code
+    /// that is never run is also reported.
+    pub fn add_ast(&self, ast: &AST) {
+        let files = &mut locked_write(&self.0).files;
+        let file = files.entry(ast.source().unwrap_or("").into()).or_default();
+
+        ast._walk(&mut |path| {
+            let node = *path.last().unwrap();
+
+            // Transitive nodes are not run
+            match node {
+                ASTNode::Expr(Expr::Stmt(..)) | ASTNode::Stmt(Stmt::Expr(..)) => (),
+                _ => {
+                    if let Some(line) = node.position().line() {
+                        file.lines.insert(line);
+                    }
+                }
+            }
+
+            let site = match node {
+                ASTNode::Stmt(Stmt::If(x, ..)) => {
+                    let branches = vec![first_in_block(&x.body), first_in_block(&x.branch)];
+                    Some((BranchKind::If, branches))
+                }
+                ASTNode::Stmt(Stmt::Switch(x, ..)) => {
+                    let sw = &x.1;
+                    let mut branches: Vec<_> = sw
+                        .expressions
+                        .iter()
+                        .map(|b| first_in_expr(&b.rhs))
+                        .collect();
+                    if sw.def_case.is_none() {
+                        branches.push(None);
+                    }
+                    Some((BranchKind::Switch, branches))
+                }
+                ASTNode::Expr(Expr::And(x, ..)) => {
+                    Some((BranchKind::And, vec![first_in_expr(&x.rhs), None]))
+                }
+                ASTNode::Expr(Expr::Or(x, ..)) => {
+                    Some((BranchKind::Or, vec![first_in_expr(&x.rhs), None]))
+                }
+                ASTNode::Expr(Expr::Coalesce(x, ..)) => {
+                    Some((BranchKind::Coalesce, vec![first_in_expr(&x.rhs), None]))
+                }
+                _ => None,
+            };
+
+            if let Some(site) = site {
+                if !node.position().is_none() {
+                    file.sites.insert(node_key(node), site);
+                }
+            }
+
+            true
+        });
+    }
+    /// Record a node being run.
+    fn hit(&self, source: Option<&str>, node: ASTNode) {
+        let data = &mut *locked_write(&self.0);
+        let key = node_key(node);
+        let addr = match node {
+            ASTNode::Stmt(stmt) => stmt as *const Stmt as usize,
+            ASTNode::Expr(expr) => expr as *const Expr as usize,
+        };
+
+        // A different node with the same key run right after is a child sharing the position
+        match data.last.replace((addr, key)) {
+            Some((last_addr, last_key)) if last_key == key && last_addr != addr => return,
+            _ => (),
+        }
+
+        let source = source.unwrap_or("");
+
+        let file = match data.files.get_mut(source) {
+            Some(file) => file,
+            None => data.files.entry(source.into()).or_default(),
+        };
+
+        *file.hits.entry(key).or_default() += 1;
+    }
+    /// Merge the coverage data of another [`Coverage`] into this one.
+    pub fn merge(&self, other: &Self) {
+        if Shared::ptr_eq(&self.0, &other.0) {
+            return;
+        }
+
+        let other = locked_read(&other.0).files.clone();
+        let files = &mut locked_write(&self.0).files;
+
+        for (source, data) in other {
+            let file = files.entry(source).or_default();
+
+            for (key, hits) in data.hits {
+                *file.hits.entry(key).or_default() += hits;
+            }
+            file.lines.extend(data.lines);
+            file.sites.extend(data.sites);
+        }
+    }
+    /// Get the coverage of all sources, in order of source.
+    #[must_use]
+    pub fn files(&self) -> Vec<FileCoverage> {
+        locked_read(&self.0)
+            .files
+            .iter()
+            .map(|(source, data)| {
+                // A line is run as many times as the most-run node on it
+                let mut lines: BTreeMap<_, _> = data.lines.iter().map(|&n| (n, 0)).collect();
+
+                for (&(pos, ..), &hits) in &data.hits {
+                    let count = lines.entry(pos.line().unwrap()).or_default();
+                    *count = hits.max(*count);
+                }
+
+                let branches = data
+                    .sites
+                    .iter()
+                    .map(|(key, (kind, targets))| {
+                        let hits = data.hits.get(key).copied().unwrap_or(0);
+                        let mut taken: Vec<_> = targets
+                            .iter()
+                            .map(|k| k.map_or(0, |k| data.hits.get(&k).copied().unwrap_or(0)))
+                            .collect();
+
+                        // Branches without code take the rest
+                        if let Some(index) = targets.iter().rposition(Option::is_none) {
+                            taken[index] = hits.saturating_sub(taken.iter().sum());
+                        }
+
+                        BranchCoverage {
+                            kind: *kind,
+                            pos: key.0,
+                            hits,
+                            taken,
+                        }
+                    })
+                    .collect();
+
+                FileCoverage {
+                    source: source.as_str().into(),
+                    lines: lines.into_iter().collect(),
+                    branches,
+                }
+            })
+            .collect()
+    }
+    /// Export the coverage data in LCOV format.
+    ///
+    /// Branches of branching constructs that are never run are marked `-`.
+    #[must_use]
+    pub fn to_lcov(&self) -> String {
+        let mut result = String::new();
+
+        for file in self.files() {
+            writeln!(result, "TN:\nSF:{}", file.source).unwrap();
+
+            for (block, branch) in file.branches.iter().enumerate() {
+                let line = branch.pos.line().unwrap();
+
+                for (index, &taken) in branch.taken.iter().enumerate() {
+                    if branch.hits == 0 {
+                        writeln!(result, "BRDA:{line},{block},{index},-").unwrap();
+                    } else {
+                        writeln!(result, "BRDA:{line},{block},{index},{taken}").unwrap();
+                    }
+                }
+            }
+
+            let branches = file.branches.iter().flat_map(|b| b.taken.iter());
+            let (found, hit) = branches.fold((0, 0), |(f, h), &n| (f + 1, h + usize::from(n > 0)));
+            writeln!(result, "BRF:{found}\nBRH:{hit}").unwrap();
+
+            for (line, hits) in &file.lines {
+                writeln!(result, "DA:{line},{hits}").unwrap();
+            }
+
+            let hit = file.lines.iter().filter(|(.., n)| *n > 0).count();
+            writeln!(result, "LF:{}\nLH:{hit}", file.lines.len()).unwrap();
+            result.push_str("end_of_record\n");
+        }
+
+        result
+    }
+    /// Export the coverage data as a JSON report.
+    ///
+    /// The report is an object with a `files` array holding one object per source, each with a
+    /// `lines` array and a `branches` array.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let mut result = String::from("{\"files\":[");
+
+        for (i, file) in self.files().into_iter().enumerate() {
+            if i > 0 {
+                result.push(',');
+            }
+            write!(
+                result,
+                "{{\"source\":{:?},\"lines\":[",
+                file.source.as_str()
+            )
+            .unwrap();
+
+            for (j, (line, hits)) in file.lines.iter().enumerate() {
+                if j > 0 {
+                    result.push(',');
+                }
+                write!(result, "{{\"line\":{line},\"hits\":{hits}}}").unwrap();
+            }
+
+            result.push_str("],\"branches\":[");
+
+            for (j, branch) in file.branches.iter().enumerate() {
+                if j > 0 {
+                    result.push(',');
+                }
+                write!(
+                    result,
+                    "{{\"kind\":\"{}\",\"line\":{},\"position\":{},\"hits\":{},\"taken\":{:?}}}",
+                    branch.kind,
+                    branch.pos.line().unwrap(),
+                    branch.pos.position().unwrap_or(0),
+                    branch.hits,
+                    branch.taken
+                )
+                .unwrap();
+            }
+
+            result.push_str("]}");
+        }
+
+        result.push_str("]}");
+        result
+    }
+}
+
+/// Get the [`NodeKey`] of an [`AST` Node][ASTNode].
+fn node_key(node: ASTNode) -> NodeKey {
+    match node {
+        ASTNode::Stmt(stmt) => (stmt.position(), 0),
+        ASTNode::Expr(expr @ (Expr::FnCall(..) | Expr::MethodCall(..))) => (expr.position(), 1),
+        ASTNode::Expr(expr) => (expr.position(), 2),
+    }
+}
+
+/// Get the first node run in a statements block, if any.
+fn first_in_block(block: &StmtBlock) -> Option<NodeKey> {
+    block.statements().first().and_then(first_in_stmt)
+}
+
+/// Get the first node run by a statement, skipping transitive nodes.
+fn first_in_stmt(stmt: &Stmt) -> Option<NodeKey> {
+    match stmt {
+        Stmt::Expr(expr) => first_in_expr(expr),
+        _ => Some(node_key(stmt.into())),
+    }
+}
+
+/// Get the first node run by an expression, skipping transitive nodes.
+fn first_in_expr(expr: &Expr) -> Option<NodeKey> {
+    match expr {
+        Expr::Stmt(block) => first_in_block(block),
+        _ => Some(node_key(expr.into())),
+    }
+}
+
+impl Engine {
+    /// Register a [`Coverage`] collector as the debugging interface, replacing any registered
+    /// debugger.
+    ///
+    /// Not available under `no_position`.
+    #[allow(deprecated)]
+    pub fn register_coverage(&mut self, coverage: &Coverage) -> &mut Self {
+        let coverage = coverage.clone();
+
+        self.register_debugger(
+            |_, dbg| dbg,
+            move |_, event, node, source, pos| {
+                match event {
+                    DebuggerEvent::Start | DebuggerEvent::Step | DebuggerEvent::BreakPoint(..)
+                        if !pos.is_none() =>
+                    {
+                        coverage.hit(source, node)
+                    }
+                    _ => (),
+                }
+
+                Ok(DebuggerCommand::StepInto)
+            },
+        )
+    }
+}
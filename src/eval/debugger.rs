@@ -3,12 +3,18 @@
 
 use super::{Caches, EvalContext, GlobalRuntimeState};
 use crate::ast::{ASTNode, Expr, Stmt};
+use crate::func::native::locked_write;
+#[cfg(not(feature = "no_function"))]
+use crate::parser::ParseSettingFlags;
+use crate::parser::{ParseSettings, ParseState};
+use crate::types::StringsInterner;
 use crate::{
-    Dynamic, Engine, EvalAltResult, ImmutableString, Position, RhaiResultOf, Scope, ThinVec,
+    Dynamic, Engine, EvalAltResult, ImmutableString, Position, RhaiResult, RhaiResultOf, Scope,
+    StaticVec, ThinVec,
 };
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
-use std::{fmt, iter::repeat, mem};
+use std::{collections::BTreeMap, fmt, iter::repeat, mem, str::FromStr};
 
 /// Callback function to initialize the debugger.
 #[cfg(not(feature = "sync"))]
@@ -84,10 +90,113 @@ pub enum DebuggerEvent<'a> {
     FunctionExitWithValue(&'a Dynamic),
     /// Return from a function with a value.
     FunctionExitWithError(&'a EvalAltResult),
+    /// Break on a change of the value watched by a watch-point, with the old and new values.
+    WatchPoint(usize, &'a Dynamic, &'a Dynamic),
     /// Script evaluation ends.
     End,
 }
 
+/// A condition on the number of times a [`BreakPoint`] is hit.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum HitCondition {
+    /// Trigger on the n-th hit only (`== n`).
+    Equals(usize),
+    /// Trigger on the n-th hit and all hits after it (`>= n`).
+    AtLeast(usize),
+    /// Trigger on every n-th hit (`% n`).
+    Every(usize),
+}
+
+impl fmt::Display for HitCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Equals(n) => write!(f, "== {n}"),
+            Self::AtLeast(n) => write!(f, ">= {n}"),
+            Self::Every(n) => write!(f, "% {n}"),
+        }
+    }
+}
+
+impl FromStr for HitCondition {
+    type Err = String;
+
+    /// Parse a hit condition in the form `== n`, `>= n` or `% n`.
+    /// A number alone is the same as `== n`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        let (make, n): (fn(usize) -> Self, _) = if let Some(n) = s.strip_prefix("==") {
+            (Self::Equals, n)
+        } else if let Some(n) = s.strip_prefix(">=") {
+            (Self::AtLeast, n)
+        } else if let Some(n) = s.strip_prefix('%') {
+            (Self::Every, n)
+        } else {
+            (Self::Equals, s)
+        };
+
+        match n.trim().parse::<usize>() {
+            Ok(n) if n > 0 => Ok(make(n)),
+            _ => Err(format!("Invalid hit condition: '{s}'")),
+        }
+    }
+}
+
+impl HitCondition {
+    /// Is this condition met by a particular number of hits (counting from one)?
+    #[inline]
+    #[must_use]
+    pub const fn is_met(self, hits: usize) -> bool {
+        match self {
+            Self::Equals(n) => hits == n,
+            Self::AtLeast(n) => hits >= n,
+            Self::Every(n) => n > 0 && hits % n == 0,
+        }
+    }
+}
+
+/// Options controlling when a [`BreakPoint`] is triggered.
+///
+/// Options are kept by the [`Debugger`] separately from its break-points, keyed by the index of
+/// each break-point.
+///
+/// For a break-point at the beginning of a line, the options are only checked once each time the
+/// line is run.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct BreakPointOptions {
+    /// Expression that must evaluate to `true`, in the scope where the break-point is hit, for
+    /// the break-point to trigger.
+    ///
+    /// The break-point also triggers if the expression fails to evaluate.
+    pub condition: Option<ImmutableString>,
+    /// Condition on the number of hits (counting only hits where the condition is met) for the
+    /// break-point to trigger.
+    pub hit_condition: Option<HitCondition>,
+    /// Message to print (via [`Engine::on_print`]) instead of triggering the break-point.
+    ///
+    /// Expressions within braces (e.g. `{x + 1}`) are evaluated and interpolated into the message.
+    /// Use `{{` and `}}` for literal braces.
+    pub log_message: Option<ImmutableString>,
+    /// Number of times the break-point is hit with its condition met.
+    pub hits: usize,
+}
+
+impl fmt::Display for BreakPointOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ref condition) = self.condition {
+            write!(f, " if {condition}")?;
+        }
+        if let Some(hit_condition) = self.hit_condition {
+            write!(f, " when hits {hit_condition}")?;
+        }
+        if let Some(ref message) = self.log_message {
+            write!(f, " log {message:?}")?;
+        }
+        Ok(())
+    }
+}
+
 /// A break-point for debugging.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 #[non_exhaustive]
@@ -136,51 +245,27 @@ impl fmt::Display for BreakPoint {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             #[cfg(not(feature = "no_position"))]
-            Self::AtPosition {
-                source,
-                pos,
-                enabled,
-            } => {
+            Self::AtPosition { source, pos, .. } => {
                 if let Some(ref source) = source {
                     write!(f, "{source} ")?;
                 }
                 write!(f, "@ {pos:?}")?;
-                if !*enabled {
-                    f.write_str(" (disabled)")?;
-                }
-                Ok(())
-            }
-            Self::AtFunctionName { name, enabled } => {
-                write!(f, "{name} (...)")?;
-                if !*enabled {
-                    f.write_str(" (disabled)")?;
-                }
-                Ok(())
-            }
-            Self::AtFunctionCall {
-                name,
-                args,
-                enabled,
-            } => {
-                write!(
-                    f,
-                    "{name} ({})",
-                    repeat("_").take(*args).collect::<Vec<_>>().join(", ")
-                )?;
-                if !*enabled {
-                    f.write_str(" (disabled)")?;
-                }
-                Ok(())
             }
+            Self::AtFunctionName { name, .. } => write!(f, "{name} (...)")?,
+            Self::AtFunctionCall { name, args, .. } => write!(
+                f,
+                "{name} ({})",
+                repeat("_").take(*args).collect::<Vec<_>>().join(", ")
+            )?,
             #[cfg(not(feature = "no_object"))]
-            Self::AtProperty { name, enabled } => {
-                write!(f, ".{name}")?;
-                if !*enabled {
-                    f.write_str(" (disabled)")?;
-                }
-                Ok(())
-            }
+            Self::AtProperty { name, .. } => write!(f, ".{name}")?,
+        }
+
+        if !self.is_enabled() {
+            f.write_str(" (disabled)")?;
         }
+
+        Ok(())
     }
 }
 
@@ -210,6 +295,107 @@ impl BreakPoint {
             Self::AtProperty { enabled, .. } => *enabled = value,
         }
     }
+    /// Is this [`BreakPoint`] at a particular [`AST` Node][ASTNode]?
+    ///
+    /// Whether the break-point is enabled, and its [options][BreakPointOptions], are not checked.
+    #[must_use]
+    pub fn is_at(&self, src: Option<&str>, node: ASTNode) -> bool {
+        let _src = src;
+
+        match self {
+            #[cfg(not(feature = "no_position"))]
+            Self::AtPosition { pos, .. } if pos.is_none() => false,
+            #[cfg(not(feature = "no_position"))]
+            Self::AtPosition { source, pos, .. } if pos.is_beginning_of_line() => {
+                node.position().line().unwrap_or(0) == pos.line().unwrap()
+                    && _src == source.as_deref()
+            }
+            #[cfg(not(feature = "no_position"))]
+            Self::AtPosition { source, pos, .. } => {
+                node.position() == *pos && _src == source.as_deref()
+            }
+            Self::AtFunctionName { name, .. } => match node {
+                ASTNode::Expr(Expr::FnCall(x, ..)) | ASTNode::Stmt(Stmt::FnCall(x, ..)) => {
+                    x.name == *name
+                }
+                ASTNode::Stmt(Stmt::Expr(e)) => match &**e {
+                    Expr::FnCall(x, ..) => x.name == *name,
+                    _ => false,
+                },
+                _ => false,
+            },
+            Self::AtFunctionCall { name, args, .. } => match node {
+                ASTNode::Expr(Expr::FnCall(x, ..)) | ASTNode::Stmt(Stmt::FnCall(x, ..)) => {
+                    x.args.len() == *args && x.name == *name
+                }
+                ASTNode::Stmt(Stmt::Expr(e)) => match &**e {
+                    Expr::FnCall(x, ..) => x.args.len() == *args && x.name == *name,
+                    _ => false,
+                },
+                _ => false,
+            },
+            #[cfg(not(feature = "no_object"))]
+            Self::AtProperty { name, .. } => match node {
+                ASTNode::Expr(Expr::Property(x, ..)) => x.2 == *name,
+                _ => false,
+            },
+        }
+    }
+}
+
+/// A watch-point for debugging, triggered when a write changes the value at a path.
+///
+/// The path is an expression starting with a variable or `this`, e.g. `x`, `this.items[0].name` or
+/// `list[2]`. It is evaluated in the current scope before and after each assignment to, or method
+/// call on, that variable (or `this`).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct WatchPoint {
+    /// Path of the watched value.
+    pub path: ImmutableString,
+    /// Is the watch-point enabled?
+    pub enabled: bool,
+}
+
+impl fmt::Display for WatchPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)?;
+        if !self.enabled {
+            f.write_str(" (disabled)")?;
+        }
+        Ok(())
+    }
+}
+
+impl WatchPoint {
+    /// Create a new enabled [`WatchPoint`] on a path.
+    #[inline(always)]
+    #[must_use]
+    pub fn new(path: impl Into<ImmutableString>) -> Self {
+        Self {
+            path: path.into(),
+            enabled: true,
+        }
+    }
+    /// Get the name of the variable (or `this`) at the root of the path.
+    #[must_use]
+    pub fn root(&self) -> &str {
+        let path = self.path.trim_start();
+        let len = path
+            .find(|ch: char| !ch.is_alphanumeric() && ch != '_')
+            .unwrap_or(path.len());
+        &path[..len]
+    }
+    /// Is this [`WatchPoint`] on a variable (or `this`) at the root of an [expression][Expr]?
+    #[must_use]
+    pub(crate) fn is_on(&self, root: &Expr) -> bool {
+        match root {
+            #[cfg(not(feature = "no_function"))]
+            Expr::ThisPtr(..) => self.root() == crate::engine::KEYWORD_THIS,
+            _ => root
+                .get_variable_name(true)
+                .map_or(false, |name| self.root() == name),
+        }
+    }
 }
 
 /// A function call.
@@ -253,10 +439,17 @@ pub struct Debugger {
     pub(crate) status: DebuggerStatus,
     /// The current set of break-points.
     break_points: Vec<BreakPoint>,
+    /// Options of break-points, keyed by the index of each break-point.
+    break_point_options: BTreeMap<usize, BreakPointOptions>,
+    /// The current set of watch-points.
+    watch_points: Vec<WatchPoint>,
     /// The current function call stack.
     call_stack: Vec<CallStackFrame>,
     /// The current state.
     state: Dynamic,
+    /// Call level, line and address of the first node in the current run of a line.
+    #[cfg(not(feature = "no_position"))]
+    line_run: Option<(usize, usize, usize)>,
 }
 
 impl Debugger {
@@ -267,8 +460,12 @@ impl Debugger {
         Self {
             status,
             break_points: Vec::new(),
+            break_point_options: BTreeMap::new(),
+            watch_points: Vec::new(),
             call_stack: Vec::new(),
             state: Dynamic::UNIT,
+            #[cfg(not(feature = "no_position"))]
+            line_run: None,
         }
     }
     /// Get the current call stack.
@@ -317,54 +514,14 @@ impl Debugger {
             self.status = status;
         }
     }
-    /// Returns the first break-point triggered by a particular [`AST` Node][ASTNode].
+    /// Returns the first enabled break-point at a particular [`AST` Node][ASTNode].
+    ///
+    /// The [options][BreakPointOptions] of break-points are not checked.
     #[must_use]
     pub fn is_break_point(&self, src: Option<&str>, node: ASTNode) -> Option<usize> {
-        let _src = src;
-
         self.break_points()
             .iter()
-            .enumerate()
-            .filter(|(.., bp)| bp.is_enabled())
-            .find(|(.., bp)| match bp {
-                #[cfg(not(feature = "no_position"))]
-                BreakPoint::AtPosition { pos, .. } if pos.is_none() => false,
-                #[cfg(not(feature = "no_position"))]
-                BreakPoint::AtPosition { source, pos, .. } if pos.is_beginning_of_line() => {
-                    node.position().line().unwrap_or(0) == pos.line().unwrap()
-                        && _src == source.as_deref()
-                }
-                #[cfg(not(feature = "no_position"))]
-                BreakPoint::AtPosition { source, pos, .. } => {
-                    node.position() == *pos && _src == source.as_deref()
-                }
-                BreakPoint::AtFunctionName { name, .. } => match node {
-                    ASTNode::Expr(Expr::FnCall(x, ..)) | ASTNode::Stmt(Stmt::FnCall(x, ..)) => {
-                        x.name == *name
-                    }
-                    ASTNode::Stmt(Stmt::Expr(e)) => match &**e {
-                        Expr::FnCall(x, ..) => x.name == *name,
-                        _ => false,
-                    },
-                    _ => false,
-                },
-                BreakPoint::AtFunctionCall { name, args, .. } => match node {
-                    ASTNode::Expr(Expr::FnCall(x, ..)) | ASTNode::Stmt(Stmt::FnCall(x, ..)) => {
-                        x.args.len() == *args && x.name == *name
-                    }
-                    ASTNode::Stmt(Stmt::Expr(e)) => match &**e {
-                        Expr::FnCall(x, ..) => x.args.len() == *args && x.name == *name,
-                        _ => false,
-                    },
-                    _ => false,
-                },
-                #[cfg(not(feature = "no_object"))]
-                BreakPoint::AtProperty { name, .. } => match node {
-                    ASTNode::Expr(Expr::Property(x, ..)) => x.2 == *name,
-                    _ => false,
-                },
-            })
-            .map(|(i, ..)| i)
+            .position(|bp| bp.is_enabled() && bp.is_at(src, node))
     }
     /// Get a slice of all [`BreakPoint`]'s.
     #[inline(always)]
@@ -373,11 +530,78 @@ impl Debugger {
         &self.break_points
     }
     /// Get the underlying [`Vec`] holding all [`BreakPoint`]'s.
+    ///
+    /// [Options][BreakPointOptions] are keyed by the index of each break-point, so they are not
+    /// moved when break-points are removed from the [`Vec`] directly.
+    /// Use [`remove_break_point`][Debugger::remove_break_point] and
+    /// [`clear_break_points`][Debugger::clear_break_points] to keep them in sync.
     #[inline(always)]
     #[must_use]
     pub fn break_points_mut(&mut self) -> &mut Vec<BreakPoint> {
         &mut self.break_points
     }
+    /// Add a [`BreakPoint`] with [options][BreakPointOptions], returning its index.
+    #[inline]
+    pub fn add_break_point_with_options(
+        &mut self,
+        break_point: BreakPoint,
+        options: BreakPointOptions,
+    ) -> usize {
+        let index = self.break_points.len();
+        self.break_points.push(break_point);
+        self.break_point_options.insert(index, options);
+        index
+    }
+    /// Remove the [`BreakPoint`] at a particular index, together with its
+    /// [options][BreakPointOptions].
+    ///
+    /// The options of the following break-points are moved together with them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn remove_break_point(&mut self, index: usize) -> BreakPoint {
+        let break_point = self.break_points.remove(index);
+
+        self.break_point_options = mem::take(&mut self.break_point_options)
+            .into_iter()
+            .filter(|&(i, ..)| i != index)
+            .map(|(i, options)| (if i > index { i - 1 } else { i }, options))
+            .collect();
+
+        break_point
+    }
+    /// Remove all [`BreakPoint`]'s together with their [options][BreakPointOptions].
+    #[inline]
+    pub fn clear_break_points(&mut self) {
+        self.break_points.clear();
+        self.break_point_options.clear();
+    }
+    /// Get the [options][BreakPointOptions] of the [`BreakPoint`] at a particular index, if any.
+    #[inline(always)]
+    #[must_use]
+    pub fn break_point_options(&self, index: usize) -> Option<&BreakPointOptions> {
+        self.break_point_options.get(&index)
+    }
+    /// Get a mutable reference to the [options][BreakPointOptions] of the [`BreakPoint`] at a
+    /// particular index, adding default options if there are none.
+    #[inline(always)]
+    #[must_use]
+    pub fn break_point_options_mut(&mut self, index: usize) -> &mut BreakPointOptions {
+        self.break_point_options.entry(index).or_default()
+    }
+    /// Get a slice of all [`WatchPoint`]'s.
+    #[inline(always)]
+    #[must_use]
+    pub fn watch_points(&self) -> &[WatchPoint] {
+        &self.watch_points
+    }
+    /// Get the underlying [`Vec`] holding all [`WatchPoint`]'s.
+    #[inline(always)]
+    #[must_use]
+    pub fn watch_points_mut(&mut self) -> &mut Vec<WatchPoint> {
+        &mut self.watch_points
+    }
     /// Get the custom state.
     #[inline(always)]
     pub const fn state(&self) -> &Dynamic {
@@ -449,7 +673,7 @@ impl Engine {
         global: &mut GlobalRuntimeState,
         caches: &mut Caches,
         scope: &mut Scope,
-        this_ptr: Option<&mut Dynamic>,
+        mut this_ptr: Option<&mut Dynamic>,
         node: impl Into<ASTNode<'a>>,
     ) -> RhaiResultOf<Option<DebuggerStatus>> {
         let node = node.into();
@@ -460,29 +684,360 @@ impl Engine {
             _ => (),
         }
 
-        match global.debugger {
-            Some(ref dbg) => {
-                let event = match dbg.status {
-                    DebuggerStatus::Init => Some(DebuggerEvent::Start),
-                    DebuggerStatus::NEXT if node.is_stmt() => Some(DebuggerEvent::Step),
-                    DebuggerStatus::INTO if node.is_expr() => Some(DebuggerEvent::Step),
-                    DebuggerStatus::STEP => Some(DebuggerEvent::Step),
-                    DebuggerStatus::Terminate => Some(DebuggerEvent::End),
-                    _ => None,
-                };
-
-                let event = match event {
-                    Some(e) => e,
-                    None => match dbg.is_break_point(global.source(), node) {
-                        Some(bp) => DebuggerEvent::BreakPoint(bp),
-                        None => return Ok(None),
-                    },
-                };
-
-                self.run_debugger_raw(global, caches, scope, this_ptr, node, event)
+        let event = match global.debugger {
+            Some(ref dbg) => match dbg.status {
+                DebuggerStatus::Init => Some(DebuggerEvent::Start),
+                DebuggerStatus::NEXT if node.is_stmt() => Some(DebuggerEvent::Step),
+                DebuggerStatus::INTO if node.is_expr() => Some(DebuggerEvent::Step),
+                DebuggerStatus::STEP => Some(DebuggerEvent::Step),
+                DebuggerStatus::Terminate => Some(DebuggerEvent::End),
+                _ => None,
+            },
+            None => return Ok(None),
+        };
+
+        let event = match event {
+            Some(e) => e,
+            None => {
+                match self.trigger_break_point(global, caches, scope, this_ptr.as_deref_mut(), node)
+                {
+                    Some(bp) => DebuggerEvent::BreakPoint(bp),
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        self.run_debugger_raw(global, caches, scope, this_ptr, node, event)
+    }
+    /// Find the first enabled break-point triggered by an [`AST` Node][ASTNode], checking its
+    /// [options][BreakPointOptions].
+    ///
+    /// Break-points with a log message print the message instead of triggering.
+    pub(crate) fn trigger_break_point(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        mut this_ptr: Option<&mut Dynamic>,
+        node: ASTNode,
+    ) -> Option<usize> {
+        let src = global.source_raw().cloned();
+
+        // Following nodes on the same line are part of the same run of that line,
+        // unless the first node is run again (e.g. in a loop)
+        #[cfg(not(feature = "no_position"))]
+        let same_run = {
+            let addr = match node {
+                ASTNode::Stmt(stmt) => stmt as *const _ as usize,
+                ASTNode::Expr(expr) => expr as *const _ as usize,
+            };
+            let run = (global.level, node.position().line().unwrap_or(0), addr);
+            let dbg = global.debugger_mut();
+
+            match dbg.line_run {
+                Some((level, line, addr)) if level == run.0 && line == run.1 && addr != run.2 => {
+                    true
+                }
+                _ => {
+                    dbg.line_run = Some(run);
+                    false
+                }
+            }
+        };
+
+        for index in 0..global.debugger().break_points().len() {
+            let bp = &global.debugger().break_points()[index];
+
+            if !bp.is_enabled() || !bp.is_at(src.as_deref(), node) {
+                continue;
+            }
+
+            let options = global
+                .debugger()
+                .break_point_options(index)
+                .cloned()
+                .unwrap_or_default();
+
+            // Break-points with options on a whole line are only checked once per run of the line
+            #[cfg(not(feature = "no_position"))]
+            if let BreakPoint::AtPosition { pos, .. } = bp {
+                let has_options = options.condition.is_some()
+                    || options.hit_condition.is_some()
+                    || options.log_message.is_some();
+
+                if has_options && pos.is_beginning_of_line() && same_run {
+                    continue;
+                }
+            }
+
+            // The break-point also triggers when the condition fails to evaluate
+            if let Some(ref condition) = options.condition {
+                let result = self.eval_debugger_script(
+                    global,
+                    caches,
+                    scope,
+                    this_ptr.as_deref_mut(),
+                    condition,
+                    true,
+                );
+                if let Ok(value) = result {
+                    if !value.as_bool().unwrap_or(false) {
+                        continue;
+                    }
+                }
+            }
+
+            let hits = {
+                let options = global.debugger_mut().break_point_options_mut(index);
+                options.hits += 1;
+                options.hits
+            };
+
+            if let Some(hit_condition) = options.hit_condition {
+                if !hit_condition.is_met(hits) {
+                    continue;
+                }
+            }
+
+            if let Some(ref message) = options.log_message {
+                let text = self.format_log_message(
+                    global,
+                    caches,
+                    scope,
+                    this_ptr.as_deref_mut(),
+                    message,
+                );
+                if let Some(ref print) = self.print {
+                    print(&text);
+                }
+                continue;
+            }
+
+            return Some(index);
+        }
+
+        None
+    }
+    /// Get the values watched by all enabled [watch-points][WatchPoint] on the variable (or `this`)
+    /// at the root of an [expression][Expr], before it is written.
+    ///
+    /// Values that cannot be evaluated are [`None`].
+    pub(crate) fn watch_values(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        mut this_ptr: Option<&mut Dynamic>,
+        root: &Expr,
+    ) -> Option<StaticVec<(usize, Option<Dynamic>)>> {
+        let watch_points = &global.debugger.as_ref()?.watch_points;
+
+        if !watch_points.iter().any(|w| w.enabled && w.is_on(root)) {
+            return None;
+        }
+
+        let paths = watch_points
+            .iter()
+            .enumerate()
+            .filter(|(.., w)| w.enabled && w.is_on(root))
+            .map(|(index, w)| (index, w.path.clone()))
+            .collect::<StaticVec<_>>();
+
+        let values = paths
+            .into_iter()
+            .map(|(index, path)| {
+                let value = self
+                    .eval_debugger_script(
+                        global,
+                        caches,
+                        scope,
+                        this_ptr.as_deref_mut(),
+                        &path,
+                        true,
+                    )
+                    .ok()
+                    .map(Dynamic::flatten);
+                (index, value)
+            })
+            .collect();
+
+        Some(values)
+    }
+    /// Check the values watched by [watch-points][WatchPoint] after a write, running the debugger
+    /// with [`DebuggerEvent::WatchPoint`] for each value that has changed.
+    pub(crate) fn check_watch_points<'a>(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        mut this_ptr: Option<&mut Dynamic>,
+        node: impl Into<ASTNode<'a>>,
+        watched: Option<StaticVec<(usize, Option<Dynamic>)>>,
+    ) -> RhaiResultOf<()> {
+        let watched = match watched {
+            Some(watched) => watched,
+            None => return Ok(()),
+        };
+        let node = node.into();
+
+        for (index, old_value) in watched {
+            // Values that cannot be evaluated are not watched
+            let old_value = match old_value {
+                Some(value) => value,
+                None => continue,
+            };
+            let path = match global.debugger().watch_points().get(index) {
+                Some(w) => w.path.clone(),
+                None => continue,
+            };
+            let new_value = match self.eval_debugger_script(
+                global,
+                caches,
+                scope,
+                this_ptr.as_deref_mut(),
+                &path,
+                true,
+            ) {
+                Ok(value) => value.flatten(),
+                Err(..) => continue,
+            };
+
+            if old_value.type_id() == new_value.type_id()
+                && format!("{old_value:?}") == format!("{new_value:?}")
+            {
+                continue;
+            }
+
+            let event = DebuggerEvent::WatchPoint(index, &old_value, &new_value);
+
+            if let Some(cmd) =
+                self.run_debugger_raw(global, caches, scope, this_ptr.as_deref_mut(), node, event)?
+            {
+                global.debugger_mut().status = cmd;
             }
-            None => Ok(None),
         }
+
+        Ok(())
+    }
+    /// Evaluate a script of the debugger (an expression if `is_expr` is `true`, e.g. the condition
+    /// of a break-point) in the current context, as if it runs inside a function so that `this` is
+    /// available.
+    ///
+    /// The debugger is suspended during evaluation, and variables defined by the script are
+    /// discarded afterwards.
+    pub(crate) fn eval_debugger_script(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: Option<&mut Dynamic>,
+        script: &str,
+        is_expr: bool,
+    ) -> RhaiResult {
+        let scripts = [script];
+        let statements = {
+            let mut interner;
+            let mut guard;
+            let interned_strings = if let Some(ref interner) = self.interned_strings {
+                guard = locked_write(interner);
+                &mut *guard
+            } else {
+                interner = StringsInterner::new();
+                &mut interner
+            };
+
+            let (stream, tc) = self.lex(&scripts);
+
+            let input = &mut stream.peekable();
+            let lib = &mut <_>::default();
+            let state = &mut ParseState::new(Some(scope), interned_strings, input, tc, lib);
+
+            // Allow `this` in the script, as in a function
+            let process_settings = |_settings: &mut ParseSettings| {
+                #[cfg(not(feature = "no_function"))]
+                _settings.flags.insert(ParseSettingFlags::FN_SCOPE);
+            };
+
+            if is_expr {
+                #[cfg(not(feature = "no_optimize"))]
+                let level = crate::OptimizationLevel::None;
+                #[cfg(feature = "no_optimize")]
+                let level = <_>::default();
+
+                let mut ast = self.parse_global_expr(state, process_settings, level)?;
+                mem::take(ast.statements_mut()).into_iter().collect()
+            } else {
+                self.parse_global_level(state, process_settings)?.0
+            }
+        };
+
+        // Suspend the debugger
+        let dbg = global
+            .debugger
+            .as_deref_mut()
+            .map(|dbg| mem::replace(dbg, Debugger::new(DebuggerStatus::CONTINUE)));
+
+        let result = self.eval_stmt_block(global, caches, scope, this_ptr, &statements, true);
+
+        if let Some(dbg) = dbg {
+            *global.debugger_mut() = dbg;
+        }
+
+        result
+    }
+    /// Format the log message of a break-point, interpolating expressions within braces.
+    fn format_log_message(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        mut this_ptr: Option<&mut Dynamic>,
+        message: &str,
+    ) -> String {
+        let mut text = String::new();
+        let mut chars = message.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut expr = String::new();
+                    let mut level = 0;
+
+                    for ch in chars.by_ref() {
+                        match ch {
+                            '{' => level += 1,
+                            '}' if level == 0 => break,
+                            '}' => level -= 1,
+                            _ => (),
+                        }
+                        expr.push(ch);
+                    }
+
+                    match self.eval_debugger_script(
+                        global,
+                        caches,
+                        scope,
+                        this_ptr.as_deref_mut(),
+                        &expr,
+                        true,
+                    ) {
+                        Ok(value) => text.push_str(&value.to_string()),
+                        Err(err) => text.push_str(&format!("<{err}>")),
+                    }
+                }
+                _ => text.push(ch),
+            }
+        }
+
+        text
     }
     /// Run the debugger callback unconditionally.
     ///
@@ -134,6 +134,89 @@ impl<'a, 's, 'ps, 'g, 'c, 't> EvalContext<'a, 's, 'ps, 'g, 'c, 't> {
     pub const fn call_level(&self) -> usize {
         self.global.level
     }
+    /// _(debugging)_ Find the first enabled break-point triggered by an [`AST` Node][crate::ASTNode],
+    /// checking its [options][crate::debugger::BreakPointOptions] in this context.
+    /// Exported under the `debugging` feature only.
+    ///
+    /// Break-points whose options are met are counted as hit. Those with a log message print the
+    /// message instead of triggering.
+    #[cfg(feature = "debugging")]
+    #[inline(always)]
+    pub fn trigger_break_point(&mut self, node: crate::ASTNode) -> Option<usize> {
+        let this_ptr = self.this_ptr.as_deref_mut();
+
+        self.engine
+            .trigger_break_point(self.global, self.caches, self.scope, this_ptr, node)
+    }
+    /// _(debugging)_ Evaluate a script in this context, as if it runs at the current position.
+    /// Exported under the `debugging` feature only.
+    ///
+    /// The script can use the variables in scope, `this`, imported modules and script-defined
+    /// functions, and can assign to variables (e.g. `x[1] = 42`). Variables defined by the script
+    /// are discarded afterwards.
+    ///
+    /// The debugger is suspended during evaluation, so break-points and watch-points are not
+    /// triggered.
+    #[cfg(feature = "debugging")]
+    #[inline(always)]
+    pub fn eval_script(&mut self, script: &str) -> crate::RhaiResult {
+        let this_ptr = self.this_ptr.as_deref_mut();
+
+        self.engine.eval_debugger_script(
+            self.global,
+            self.caches,
+            self.scope,
+            this_ptr,
+            script,
+            false,
+        )
+    }
+    /// _(debugging)_ Evaluate a script in a frame of the function call stack.
+    /// Exported under the `debugging` feature only.
+    ///
+    /// Frame `0` is the current frame (same as [`eval_script`][EvalContext::eval_script]).
+    ///
+    /// Frame `n` is the function call `n` levels up the call stack. Only the parameters of the
+    /// function are in scope, holding copies of the arguments of the call, so assignments to them
+    /// are lost. Variables at global level are not available when inside a function call.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(feature = "debugging")]
+    #[cfg(not(feature = "no_function"))]
+    pub fn eval_script_in_frame(&mut self, frame: usize, script: &str) -> crate::RhaiResult {
+        if frame == 0 {
+            return self.eval_script(script);
+        }
+
+        let call_stack = self.global.debugger().call_stack();
+
+        let (fn_name, args) = match call_stack.len().checked_sub(frame + 1) {
+            Some(index) => {
+                let call = &call_stack[index];
+                (call.fn_name.clone(), call.args.clone())
+            }
+            None => {
+                let msg = format!("Invalid call-stack frame: {frame}");
+                return Err(crate::ERR::ErrorRuntime(msg.into(), crate::Position::NONE).into());
+            }
+        };
+
+        let params = self
+            .global
+            .lib
+            .iter()
+            .find_map(|m| m.get_script_fn(&fn_name, args.len()))
+            .map(|fn_def| fn_def.params.clone())
+            .unwrap_or_default();
+
+        let scope = &mut Scope::new();
+        params.into_iter().zip(args).for_each(|(name, value)| {
+            scope.push(name, value);
+        });
+
+        self.engine
+            .eval_debugger_script(self.global, self.caches, scope, None, script, false)
+    }
 
     /// Evaluate an [expression tree][crate::Expression] within this [evaluation context][`EvalContext`].
     ///
@@ -0,0 +1,190 @@
+//! Module implementing the resumable evaluation of generator functions.
+#![cfg(not(feature = "no_function"))]
+
+use super::{Caches, GlobalRuntimeState, ResumeMode, Suspension};
+use crate::types::generator::GeneratorState;
+use crate::{Dynamic, Engine, Generator, Position, RhaiResultOf, ERR};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+impl Engine {
+    /// Resume a [`Generator`], running it up to its next `yield` statement.
+    ///
+    /// Returns `None` when the generator has finished.
+    pub(crate) fn resume_generator(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        generator: &Generator,
+        pos: Position,
+    ) -> RhaiResultOf<Option<Dynamic>> {
+        #[cfg(not(feature = "sync"))]
+        let guard = generator.state.try_borrow_mut().ok();
+        #[cfg(feature = "sync")]
+        let guard = generator.state.try_write().ok();
+
+        let Some(mut guard) = guard else {
+            let msg = format!("generator {} is already running", generator.name);
+            return Err(ERR::ErrorRuntime(msg.into(), pos).into());
+        };
+        let state = &mut *guard;
+
+        if state.done {
+            return Ok(None);
+        }
+
+        self.track_operation(global, pos)?;
+
+        // Check for stack overflow
+        #[cfg(not(feature = "unchecked"))]
+        if global.level > self.max_call_levels() {
+            return Err(ERR::ErrorStackOverflow(pos).into());
+        }
+
+        let orig_lib_len = global.lib.len();
+        #[cfg(not(feature = "no_module"))]
+        let orig_imports_len = global.num_imports();
+
+        #[cfg(feature = "debugging")]
+        let orig_call_stack_len = global
+            .debugger
+            .as_ref()
+            .map_or(0, |dbg| dbg.call_stack().len());
+
+        // Push a new call stack frame
+        #[cfg(feature = "debugging")]
+        if self.is_debugger_registered() {
+            let fn_name = state.fn_def.name.clone();
+            let source = global.source.clone();
+
+            global
+                .debugger_mut()
+                .push_call_stack_frame(fn_name, std::iter::empty(), source, pos);
+        }
+
+        // Record the call in the profiler
+        #[cfg(feature = "profiling")]
+        let orig_profile_len = global.profile_len();
+        #[cfg(feature = "profiling")]
+        if global.profiler.is_some() {
+            let fn_name = state.fn_def.name.clone();
+            let num_params = state.fn_def.params.len();
+            let source = global.source.clone();
+            let def_pos = state.fn_def.body.position();
+            global.enter_profile(fn_name, num_params, true, source, def_pos);
+        }
+
+        // Merge in encapsulated environment, if any
+        let orig_fn_resolution_caches_len = caches.fn_resolution_caches_len();
+
+        #[cfg(not(feature = "no_module"))]
+        let orig_constants = state.environ.clone().map(|environ| {
+            let crate::ast::EncapsulatedEnviron {
+                lib,
+                imports,
+                constants,
+            } = &*environ;
+
+            imports
+                .iter()
+                .cloned()
+                .for_each(|(n, m)| global.push_import(n, m));
+
+            global.lib.push(lib.clone());
+
+            std::mem::replace(&mut global.constants, constants.clone())
+        });
+
+        // Blocks still open in the generator are entered again
+        let orig_scope_level = global.scope_level;
+        global.scope_level += state.frames.scope_depth();
+
+        // Run the generator
+        let result = self.run_generator(global, caches, state);
+
+        global.scope_level = orig_scope_level;
+
+        #[cfg(feature = "profiling")]
+        global.rewind_profile(orig_profile_len);
+
+        #[cfg(feature = "debugging")]
+        if self.is_debugger_registered() {
+            global
+                .debugger
+                .as_mut()
+                .unwrap()
+                .rewind_call_stack(orig_call_stack_len);
+        }
+
+        // Remove all imported modules
+        global.lib.truncate(orig_lib_len);
+        #[cfg(not(feature = "no_module"))]
+        global.truncate_imports(orig_imports_len);
+
+        // Restore constants
+        #[cfg(not(feature = "no_module"))]
+        if let Some(constants) = orig_constants {
+            global.constants = constants;
+        }
+
+        // Restore state
+        caches.rewind_fn_resolution_caches(orig_fn_resolution_caches_len);
+
+        match result {
+            Ok(Some(value)) => Ok(Some(value)),
+            Ok(None) => {
+                state.finish();
+                Ok(None)
+            }
+            Err(err) => {
+                state.finish();
+
+                let environ = state.environ.as_ref();
+                Err(self.make_fn_call_error(global, &generator.name, environ, err, pos))
+            }
+        }
+    }
+
+    /// Run the statements of a generator function up to the next `yield` statement.
+    ///
+    /// Returns `None` when the function finishes.
+    fn run_generator(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        state: &mut GeneratorState,
+    ) -> RhaiResultOf<Option<Dynamic>> {
+        let fn_def = state.fn_def.clone();
+
+        let GeneratorState {
+            this_ptr,
+            scope,
+            frames,
+            ..
+        } = state;
+
+        #[cfg(feature = "debugging")]
+        if frames.frames.is_empty() && self.is_debugger_registered() {
+            let node = crate::ast::Stmt::Noop(fn_def.body.position());
+            self.run_debugger(global, caches, scope, this_ptr.as_mut(), &node)?;
+        }
+
+        let body = fn_def.body.statements();
+
+        match self.run_resumable(
+            global,
+            caches,
+            scope,
+            this_ptr,
+            body,
+            frames,
+            ResumeMode::Generator,
+        )? {
+            Suspension::Done(..) => Ok(None),
+            Suspension::Yield(value) => Ok(Some(value)),
+            Suspension::Async(..) | Suspension::Suspended => {
+                unreachable!("generators only suspend at yield statements")
+            }
+        }
+    }
+}
@@ -58,6 +58,18 @@ pub struct GlobalRuntimeState {
     ///
     /// When that happens, this flag is turned on.
     pub always_search_scope: bool,
+    /// Call site where a call to an asynchronous function can suspend evaluation, if any.
+    ///
+    /// It is the nesting level of function calls inside the call, plus the [position][crate::Position]
+    /// of the call.
+    pub(crate) async_site: Option<(usize, crate::Position)>,
+    /// Nesting level of function calls of the script-defined function body that can make tail
+    /// calls, if any.
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) tail_call_level: Option<usize>,
+    /// Pending tail call to make after the current script-defined function body is unwound, if any.
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) tail_call: Option<Box<crate::func::TailCall>>,
     /// Embedded [module][crate::Module] resolver.
     #[cfg(not(feature = "no_module"))]
     pub embedded_module_resolver:
@@ -73,6 +85,9 @@ pub struct GlobalRuntimeState {
     /// Debugging interface.
     #[cfg(feature = "debugging")]
     pub(crate) debugger: Option<Box<super::Debugger>>,
+    /// Stack of calls to functions recorded by the profiler.
+    #[cfg(feature = "profiling")]
+    pub(crate) profiler: Option<Box<super::ProfileStack>>,
 }
 
 impl Engine {
@@ -96,6 +111,11 @@ impl Engine {
             scope_level: 0,
             level: 0,
             always_search_scope: false,
+            async_site: None,
+            #[cfg(not(feature = "no_function"))]
+            tail_call_level: None,
+            #[cfg(not(feature = "no_function"))]
+            tail_call: None,
             #[cfg(not(feature = "no_module"))]
             embedded_module_resolver: None,
             #[cfg(not(feature = "no_module"))]
@@ -109,6 +129,12 @@ impl Engine {
                 let dbg = crate::eval::Debugger::new(crate::eval::DebuggerStatus::Init);
                 (x.0)(self, dbg).into()
             }),
+
+            #[cfg(feature = "profiling")]
+            profiler: self
+                .profiler
+                .as_ref()
+                .map(|p| super::ProfileStack::new(p.clone()).into()),
         }
     }
 }
@@ -343,6 +369,9 @@ impl fmt::Debug for GlobalRuntimeState {
         #[cfg(feature = "debugging")]
         f.field("debugger", &self.debugger);
 
+        #[cfg(feature = "profiling")]
+        f.field("profiler", &self.profiler);
+
         f.finish()
     }
 }
@@ -1,13 +1,23 @@
+#[cfg(all(feature = "bytecode", not(feature = "stackless")))]
+mod bytecode;
 mod cache;
 mod chaining;
+mod coverage;
 mod data_check;
 mod debugger;
 mod eval_context;
 mod expr;
+mod generator;
 mod global_state;
+mod profiler;
+mod resumable;
 mod stmt;
 mod target;
+mod trace;
 
+#[cfg(all(feature = "bytecode", not(feature = "stackless")))]
+#[cfg(not(feature = "no_function"))]
+pub(crate) use bytecode::Chunk;
 #[allow(unused_imports)]
 pub use cache::FnResolutionCache;
 pub use cache::{Caches, FnResolutionCacheEntry};
@@ -18,12 +28,15 @@ pub use data_check::calc_array_sizes;
 #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
 pub use data_check::calc_data_sizes;
 #[cfg(feature = "debugging")]
+#[cfg(not(feature = "no_position"))]
+pub use coverage::{BranchCoverage, BranchKind, Coverage, FileCoverage};
+#[cfg(feature = "debugging")]
 #[cfg(not(feature = "no_function"))]
 pub use debugger::CallStackFrame;
 #[cfg(feature = "debugging")]
 pub use debugger::{
-    BreakPoint, Debugger, DebuggerCommand, DebuggerEvent, DebuggerStatus, OnDebuggerCallback,
-    OnDebuggingInit,
+    BreakPoint, BreakPointOptions, Debugger, DebuggerCommand, DebuggerEvent, DebuggerStatus,
+    HitCondition, OnDebuggerCallback, OnDebuggingInit, WatchPoint,
 };
 pub use eval_context::EvalContext;
 
@@ -31,9 +44,18 @@ pub use global_state::GlobalRuntimeState;
 #[cfg(not(feature = "no_module"))]
 #[cfg(not(feature = "no_function"))]
 pub use global_state::SharedGlobalConstants;
+#[cfg(feature = "profiling")]
+pub(crate) use profiler::ProfileStack;
+#[cfg(feature = "profiling")]
+pub use profiler::{ProfileEntry, Profiler};
+pub(crate) use resumable::{async_call_site, Resumable, ResumeMode, Suspension};
+pub(crate) use stmt::{loop_condition, ForSource};
 #[cfg(not(feature = "no_index"))]
 pub use target::calc_offset_len;
 pub use target::{calc_index, Target};
+#[cfg(feature = "debugging")]
+#[cfg(not(feature = "no_position"))]
+pub use trace::{Trace, TraceCall, TraceRecorder, TraceStep, TraceWrite};
 
 #[cfg(feature = "unchecked")]
 mod unchecked {
@@ -0,0 +1,368 @@
+//! Module implementing the function-level profiler.
+#![cfg(feature = "profiling")]
+
+use super::GlobalRuntimeState;
+use crate::func::{locked_read, locked_write};
+use crate::{Engine, ImmutableString, Locked, Position, Shared};
+#[cfg(all(target_family = "wasm", target_os = "unknown"))]
+use instant::Instant;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+#[cfg(any(not(target_family = "wasm"), not(target_os = "unknown")))]
+use std::time::Instant;
+use std::{collections::BTreeMap, fmt, fmt::Write, time::Duration};
+
+/// _(profiling)_ Statistics of calls to a function recorded by a [`Profiler`].
+/// Exported under the `profiling` feature only.
+///
+/// Entries are identified by the function name, number of parameters, source and
+/// [position][Position], similar to a [`CallStackFrame`][crate::debugger::CallStackFrame].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ProfileEntry {
+    /// Function name.
+    pub fn_name: ImmutableString,
+    /// Number of parameters.
+    pub num_params: usize,
+    /// Is the function script-defined?
+    pub is_script: bool,
+    /// Source of the function, if any.
+    pub source: Option<ImmutableString>,
+    /// [Position][`Position`] of the body of a script-defined function.
+    ///
+    /// Always [`Position::NONE`] for native Rust functions.
+    pub pos: Position,
+    /// Number of calls.
+    pub calls: u64,
+    /// Time spent in the function, including functions called by it.
+    ///
+    /// Time spent in recursive calls is only counted once.
+    pub inclusive_time: Duration,
+    /// Time spent in the function itself, excluding functions called by it.
+    pub exclusive_time: Duration,
+    /// Number of operations performed in the function, including functions called by it.
+    ///
+    /// Operations are not counted under `unchecked`.
+    pub inclusive_operations: u64,
+    /// Number of operations performed in the function itself, excluding functions called by it.
+    ///
+    /// Operations are not counted under `unchecked`.
+    pub exclusive_operations: u64,
+}
+
+impl fmt::Display for ProfileEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.fn_name)?;
+
+        if !self.pos.is_none() {
+            if let Some(ref source) = self.source {
+                write!(f, ": {source}")?;
+            }
+            write!(f, " @ {:?}", self.pos)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Key identifying a [`ProfileEntry`].
+type ProfileKey = (
+    ImmutableString,
+    usize,
+    bool,
+    Option<ImmutableString>,
+    Position,
+);
+
+/// Data recorded by a [`Profiler`].
+#[derive(Debug, Default)]
+struct ProfileData {
+    /// Entries in the order of their first calls.
+    entries: Vec<ProfileEntry>,
+    /// Index into the entries.
+    index: BTreeMap<ProfileKey, usize>,
+    /// Exclusive time of each unique call stack, as a list of indices into the entries.
+    stacks: BTreeMap<Vec<usize>, Duration>,
+}
+
+/// _(profiling)_ A profiler recording calls to script-defined and native Rust functions.
+/// Exported under the `profiling` feature only.
+///
+/// Cloning a [`Profiler`] yields a handle to the same recorded data.
+///
+/// # Example
+///
+/// ```
+/// use rhai::{Engine, Profiler};
+///
+/// let mut engine = Engine::new();
+/// let profiler = Profiler::new();
+///
+/// engine.set_profiler(Some(profiler.clone()));
+///
+/// # #[cfg(not(feature = "no_function"))]
+/// # {
+/// engine.run("fn double(x) { x * 2 } for i in 0..10 { double(i); }")?;
+///
+/// let double = profiler.entries().into_iter().find(|e| e.fn_name == "double").unwrap();
+///
+/// assert!(double.is_script);
+/// assert_eq!(double.calls, 10);
+/// # }
+/// # Ok::<_, Box<rhai::EvalAltResult>>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Profiler(Shared<Locked<ProfileData>>);
+
+impl Profiler {
+    /// Create a new [`Profiler`].
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Are there no recorded calls?
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        locked_read(&self.0).entries.is_empty()
+    }
+    /// Discard all recorded calls.
+    ///
+    /// Evaluations in progress should not be profiled at the same time.
+    #[inline]
+    pub fn clear(&self) {
+        *locked_write(&self.0) = ProfileData::default();
+    }
+    /// Get copies of all the recorded entries, in the order of their first calls.
+    #[inline]
+    #[must_use]
+    pub fn entries(&self) -> Vec<ProfileEntry> {
+        locked_read(&self.0).entries.clone()
+    }
+    /// Export the recorded calls in the folded-stack format used by flamegraph tools.
+    ///
+    /// Each line contains a unique call stack with function names separated by `;`, followed by the
+    /// exclusive time spent in that stack in microseconds.
+    #[must_use]
+    pub fn to_folded(&self) -> String {
+        let data = &*locked_read(&self.0);
+        let mut result = String::new();
+
+        for (stack, time) in &data.stacks {
+            for (i, &index) in stack.iter().enumerate() {
+                if i > 0 {
+                    result.push(';');
+                }
+                result.push_str(&data.entries[index].to_string().replace(';', ","));
+            }
+            writeln!(result, " {}", time.as_micros()).unwrap();
+        }
+
+        result
+    }
+    /// Export the recorded entries as a JSON summary.
+    ///
+    /// The summary is an object with a `functions` array holding one object per entry, with times in
+    /// microseconds.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let data = &*locked_read(&self.0);
+        let mut result = String::from("{\"functions\":[");
+
+        for (i, entry) in data.entries.iter().enumerate() {
+            if i > 0 {
+                result.push(',');
+            }
+            write!(
+                result,
+                "{{\"name\":{:?},\"params\":{},\"script\":{}",
+                entry.fn_name.as_str(),
+                entry.num_params,
+                entry.is_script
+            )
+            .unwrap();
+            match entry.source {
+                Some(ref source) => write!(result, ",\"source\":{:?}", source.as_str()).unwrap(),
+                None => result.push_str(",\"source\":null"),
+            }
+            match (entry.pos.line(), entry.pos.position()) {
+                (Some(line), Some(pos)) => {
+                    write!(result, ",\"line\":{line},\"position\":{pos}").unwrap()
+                }
+                (Some(line), None) => write!(result, ",\"line\":{line},\"position\":null").unwrap(),
+                _ => result.push_str(",\"line\":null,\"position\":null"),
+            }
+            write!(
+                result,
+                ",\"calls\":{},\"inclusive_time\":{},\"exclusive_time\":{},\"inclusive_operations\":{},\"exclusive_operations\":{}}}",
+                entry.calls,
+                entry.inclusive_time.as_micros(),
+                entry.exclusive_time.as_micros(),
+                entry.inclusive_operations,
+                entry.exclusive_operations
+            )
+            .unwrap();
+        }
+
+        result.push_str("]}");
+        result
+    }
+}
+
+/// A call to a function in progress.
+#[derive(Debug, Clone)]
+struct ProfileFrame {
+    /// Index of the [`ProfileEntry`].
+    entry: usize,
+    /// Time the call started.
+    start: Instant,
+    /// Number of operations when the call started.
+    operations: u64,
+    /// Time spent in functions called by the function.
+    child_time: Duration,
+    /// Number of operations performed in functions called by the function.
+    child_operations: u64,
+}
+
+/// Stack of calls to functions in progress during an evaluation.
+#[derive(Debug, Clone)]
+pub(crate) struct ProfileStack {
+    /// The [`Profiler`] to record calls to.
+    profiler: Profiler,
+    /// Calls in progress.
+    frames: Vec<ProfileFrame>,
+}
+
+impl ProfileStack {
+    /// Create a new [`ProfileStack`].
+    #[inline(always)]
+    #[must_use]
+    pub fn new(profiler: Profiler) -> Self {
+        Self {
+            profiler,
+            frames: Vec::new(),
+        }
+    }
+    /// Pop the innermost call and record it.
+    fn leave(&mut self, operations: u64) {
+        let frame = self.frames.pop().unwrap();
+        let time = frame.start.elapsed();
+        let ops = operations.saturating_sub(frame.operations);
+        let exclusive_time = time.saturating_sub(frame.child_time);
+        let exclusive_ops = ops.saturating_sub(frame.child_operations);
+        let is_recursive = self.frames.iter().any(|f| f.entry == frame.entry);
+
+        let data = &mut *locked_write(&self.profiler.0);
+
+        // The profiler may have been cleared during the call
+        let Some(entry) = data.entries.get_mut(frame.entry) else {
+            return;
+        };
+
+        entry.calls += 1;
+        entry.exclusive_time += exclusive_time;
+        entry.exclusive_operations += exclusive_ops;
+
+        if !is_recursive {
+            entry.inclusive_time += time;
+            entry.inclusive_operations += ops;
+        }
+
+        let stack = self
+            .frames
+            .iter()
+            .map(|f| f.entry)
+            .chain(std::iter::once(frame.entry))
+            .collect();
+        *data.stacks.entry(stack).or_default() += exclusive_time;
+
+        if let Some(parent) = self.frames.last_mut() {
+            parent.child_time += time;
+            parent.child_operations += ops;
+        }
+    }
+}
+
+impl GlobalRuntimeState {
+    /// Get the number of calls in progress recorded by the profiler.
+    #[inline]
+    #[must_use]
+    pub(crate) fn profile_len(&self) -> usize {
+        self.profiler.as_ref().map_or(0, |p| p.frames.len())
+    }
+    /// Record the start of a call to a function, if profiling.
+    pub(crate) fn enter_profile(
+        &mut self,
+        fn_name: ImmutableString,
+        num_params: usize,
+        is_script: bool,
+        source: Option<ImmutableString>,
+        pos: Position,
+    ) {
+        let operations = self.num_operations;
+
+        let Some(ref mut stack) = self.profiler else {
+            return;
+        };
+
+        let data = &mut *locked_write(&stack.profiler.0);
+        let key = (fn_name, num_params, is_script, source, pos);
+
+        let entry = match data.index.get(&key) {
+            Some(&index) => index,
+            None => {
+                let (fn_name, num_params, is_script, source, pos) = key.clone();
+
+                data.entries.push(ProfileEntry {
+                    fn_name,
+                    num_params,
+                    is_script,
+                    source,
+                    pos,
+                    calls: 0,
+                    inclusive_time: Duration::ZERO,
+                    exclusive_time: Duration::ZERO,
+                    inclusive_operations: 0,
+                    exclusive_operations: 0,
+                });
+                data.index.insert(key, data.entries.len() - 1);
+                data.entries.len() - 1
+            }
+        };
+
+        stack.frames.push(ProfileFrame {
+            entry,
+            start: Instant::now(),
+            operations,
+            child_time: Duration::ZERO,
+            child_operations: 0,
+        });
+    }
+    /// Record the end of all calls in progress beyond a certain number, if profiling.
+    pub(crate) fn rewind_profile(&mut self, len: usize) {
+        let operations = self.num_operations;
+
+        if let Some(ref mut stack) = self.profiler {
+            while stack.frames.len() > len {
+                stack.leave(operations);
+            }
+        }
+    }
+}
+
+impl Engine {
+    /// Set the [`Profiler`] recording calls to functions, or `None` to disable profiling.
+    ///
+    /// Not available under `no_std`.
+    #[inline(always)]
+    pub fn set_profiler(&mut self, profiler: Option<Profiler>) -> &mut Self {
+        self.profiler = profiler;
+        self
+    }
+    /// Get the [`Profiler`] recording calls to functions, if any.
+    #[inline(always)]
+    #[must_use]
+    pub const fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+}
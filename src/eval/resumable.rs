@@ -0,0 +1,2212 @@
+//! Module implementing the resumable evaluation of statements.
+//!
+//! Statements are run by a stack of frames instead of by recursion, so that the evaluation can be
+//! suspended at certain statements and resumed later.
+
+use super::{loop_condition, Caches, ForSource, GlobalRuntimeState};
+use crate::ast::{ASTFlags, Expr, FlowControl, FnCallExpr, Stmt};
+use crate::func::async_fn::{AsyncCall, BoxFuture};
+use crate::types::continuation::{FrameSnapshot, FrameSnapshotKind, ScopeEntry};
+use crate::{
+    Continuation, Dynamic, Engine, ImmutableString, Position, RhaiError, RhaiResult, RhaiResultOf,
+    Scope, Suspender, ERR, INT,
+};
+use std::mem;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Iterator kept by a suspended `for` loop.
+pub(crate) enum LoopIter {
+    /// Range of integers, with the current value, the end (exclusive) and the step.
+    ///
+    /// A range is kept in a [`Continuation`] as these three numbers.
+    Range(INT, INT, INT),
+    /// Any other iterator, which cannot be kept in a [`Continuation`].
+    Iter(LoopItems),
+}
+
+/// Iterator registered for the type of the value of a `for` loop.
+#[cfg(not(feature = "sync"))]
+pub(crate) type LoopItems = Box<dyn Iterator<Item = RhaiResultOf<Dynamic>>>;
+/// Iterator registered for the type of the value of a `for` loop.
+///
+/// Under `sync`, iterators registered with the [`Engine`] are not [`Send`], so all the items are
+/// collected when the loop starts.
+#[cfg(feature = "sync")]
+pub(crate) type LoopItems = std::vec::IntoIter<RhaiResultOf<Dynamic>>;
+
+impl LoopIter {
+    /// Create a [`LoopIter::Range`] from a range of integers, if the value is one.
+    #[must_use]
+    fn from_range(value: &Dynamic) -> Option<Self> {
+        if let Some(range) = value.read_lock::<crate::ExclusiveRange>() {
+            return Some(Self::Range(range.start, range.end, 1));
+        }
+        if let Some(range) = value.read_lock::<crate::InclusiveRange>() {
+            let end = range.end().checked_add(1)?;
+            let start = if range.is_empty() {
+                end
+            } else {
+                *range.start()
+            };
+            return Some(Self::Range(start, end, 1));
+        }
+        if let Some(range) = value.read_lock::<crate::packages::iter_basic::StepRange<INT>>() {
+            let start = if range.dir == 0 { range.to } else { range.from };
+            return Some(Self::Range(start, range.to, range.step));
+        }
+        None
+    }
+}
+
+impl Iterator for LoopIter {
+    type Item = RhaiResultOf<Dynamic>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Range(current, end, step) => {
+                let value = *current;
+
+                if *step == 0 || (*step > 0 && value >= *end) || (*step < 0 && value <= *end) {
+                    return None;
+                }
+
+                *current = value.checked_add(*step).unwrap_or(*end);
+                Some(Ok(Dynamic::from_int(value)))
+            }
+            Self::Iter(iter) => iter.next(),
+        }
+    }
+}
+
+/// Script-defined function called by a function call run as a frame, with its environment and
+/// source.
+#[cfg(not(feature = "no_function"))]
+type ScriptCall = (
+    crate::Shared<crate::ast::ScriptFuncDef>,
+    Option<crate::Shared<crate::ast::EncapsulatedEnviron>>,
+    Option<ImmutableString>,
+);
+
+/// Kind of a statements block being run.
+pub(crate) enum FrameKind {
+    /// The root statements.
+    Body,
+    /// A statements block.
+    Block,
+    /// The `then` branch of an `if` statement.
+    Then,
+    /// The `else` branch of an `if` statement.
+    Else,
+    /// The body of a `while`, `loop` or `do` loop.
+    Loop,
+    /// The body of a `for` loop, with the source of values and the number of iterations so far.
+    For(Box<ForSource<LoopIter>>, usize),
+    /// The body of a `try` statement, with the name and position of the `catch` variable, if any.
+    Try(Option<(ImmutableString, Position)>),
+    /// The `catch` block of a `try` statement, with the error caught.
+    Catch(RhaiError),
+    /// The body of a script-defined function.
+    #[cfg(not(feature = "no_function"))]
+    Call(Box<CallFrame>),
+    /// An operand of the current statement of the parent frame, being reduced.
+    #[cfg(feature = "stackless")]
+    Operand(Box<Operand>),
+}
+
+impl FrameKind {
+    /// Does the frame start a new scope level?
+    #[inline]
+    #[must_use]
+    fn is_scoped(&self) -> bool {
+        match self {
+            Self::Body => false,
+            #[cfg(feature = "stackless")]
+            Self::Operand(..) => false,
+            _ => true,
+        }
+    }
+}
+
+/// A statements block being run.
+pub(crate) struct Frame {
+    /// Kind of the statements block.
+    pub kind: FrameKind,
+    /// Index of the current statement.
+    pub index: usize,
+    /// Length of the scope when the block started.
+    pub scope_len: usize,
+    /// Number of imported modules when the block started.
+    #[cfg(not(feature = "no_module"))]
+    pub imports_len: usize,
+    /// Value of `always_search_scope` when the block started.
+    pub always_search_scope: bool,
+}
+
+/// State of a call to a script-defined function run as a frame.
+#[cfg(not(feature = "no_function"))]
+pub(crate) struct CallFrame {
+    /// The function.
+    pub fn_def: crate::Shared<crate::ast::ScriptFuncDef>,
+    /// Encapsulated environment of the function, if any.
+    pub environ: Option<crate::Shared<crate::ast::EncapsulatedEnviron>>,
+    /// The calling statement, or `None` if the function is called by the host.
+    pub stmt: Option<Stmt>,
+    /// [Position][Position] of the call.
+    pub pos: Position,
+    /// Variables of the caller, if hidden from the function.
+    pub orig_scope: Option<Scope<'static>>,
+    /// Source of the caller.
+    pub orig_source: Option<ImmutableString>,
+    /// Number of namespaces of script-defined functions of the caller.
+    pub orig_lib_len: usize,
+    /// Constants of the caller, if replaced by the encapsulated environment.
+    #[cfg(not(feature = "no_module"))]
+    pub orig_constants: Option<Option<super::SharedGlobalConstants>>,
+    /// Number of function resolution caches of the caller.
+    pub orig_fn_resolution_caches_len: usize,
+    /// Length of the debugger call stack of the caller.
+    #[cfg(feature = "debugging")]
+    pub orig_call_stack_len: usize,
+    /// Number of calls in progress recorded by the profiler for the caller.
+    #[cfg(feature = "profiling")]
+    pub orig_profile_len: usize,
+    /// Return types of the functions replaced by tail calls.
+    pub return_types: crate::StaticVec<ImmutableString>,
+}
+
+/// An operand expression of a statement, reduced one call to a script-defined function at a time.
+///
+/// Each call is run as a new frame, and is then replaced by its result in the expression.
+/// Statements blocks inside the expression are also run as new frames.
+#[cfg(feature = "stackless")]
+pub(crate) struct Operand {
+    /// The expression, with the parts already run replaced by their values.
+    pub expr: Expr,
+    /// Statements block inside the expression run by the child frame, if any.
+    pub block: Option<crate::Shared<Stmt>>,
+}
+
+#[cfg(feature = "stackless")]
+impl Operand {
+    /// Create a new [`Operand`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn new(expr: Expr) -> Self {
+        Self { expr, block: None }
+    }
+}
+
+/// Next part of an operand to be run as a frame.
+#[cfg(feature = "stackless")]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Pending {
+    /// The expression itself.
+    Node,
+    /// An argument of a function call.
+    Arg(usize),
+    /// The left operand of `&&`, `||` or `??`.
+    Lhs,
+    /// The right operand of `&&`, `||` or `??`.
+    Rhs,
+}
+
+/// Kind of a resumable evaluation.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub(crate) enum ResumeMode {
+    /// Body of a generator function, suspending at `yield` statements.
+    #[cfg(not(feature = "no_function"))]
+    Generator,
+    /// Asynchronous evaluation, suspending at calls to asynchronous functions.
+    Async,
+    /// Evaluation suspending at any statement when requested by a [`Suspender`].
+    Suspendable,
+    /// Evaluation that never suspends, run as frames so as not to recurse on the native stack.
+    #[cfg(feature = "stackless")]
+    Stackless,
+}
+
+impl ResumeMode {
+    /// Can the evaluation be suspended at any statement?
+    ///
+    /// If so, all compound statements and calls to script-defined functions in statement position
+    /// are run as frames, and new variables in the root statements are kept in the scope.
+    #[inline]
+    #[must_use]
+    const fn suspends_anywhere(self) -> bool {
+        match self {
+            #[cfg(not(feature = "no_function"))]
+            Self::Generator => false,
+            _ => true,
+        }
+    }
+}
+
+/// Reason why a resumable evaluation stops.
+pub(crate) enum Suspension {
+    /// The evaluation is finished, with its result value.
+    Done(Dynamic),
+    /// A `yield` statement is run, with the value yielded.
+    #[cfg(not(feature = "no_function"))]
+    Yield(Dynamic),
+    /// A call to an asynchronous function is pending.
+    Async(BoxFuture),
+    /// The suspension of the evaluation is requested.
+    Suspended,
+}
+
+/// State of a resumable evaluation.
+#[derive(Default)]
+pub(crate) struct Resumable {
+    /// Stack of statements blocks being run.
+    pub frames: Vec<Frame>,
+    /// Value of the last statement run.
+    value: Dynamic,
+    /// Statement suspended at a pending call to an asynchronous function, if any.
+    async_stmt: Option<Stmt>,
+    /// Result of the pending call to an asynchronous function, when complete.
+    async_result: Option<RhaiResult>,
+    /// Handle to request the suspension of the evaluation, if any.
+    pub suspender: Option<Suspender>,
+    /// Do the root statements rewind the scope?
+    #[cfg(feature = "stackless")]
+    rewind_root_scope: bool,
+}
+
+impl Resumable {
+    /// Number of frames that count as a scope level.
+    #[cfg(not(feature = "no_function"))]
+    #[inline]
+    #[must_use]
+    pub fn scope_depth(&self) -> usize {
+        self.frames.iter().filter(|f| f.kind.is_scoped()).count()
+    }
+    /// Create a new [`Resumable`] that can be suspended by a [`Suspender`].
+    #[inline(always)]
+    #[must_use]
+    pub fn with_suspender(suspender: Suspender) -> Self {
+        Self {
+            suspender: Some(suspender),
+            ..Self::default()
+        }
+    }
+    /// Set the result of the pending call to an asynchronous function.
+    #[inline(always)]
+    pub fn complete_async_call(&mut self, result: RhaiResult) {
+        self.async_result = Some(result);
+    }
+}
+
+/// Get the statements run by the innermost of a stack of frames.
+///
+/// Each frame is run by the current statement of its parent frame. The outermost frame runs `body`.
+fn frame_statements<'a>(body: &'a [Stmt], frames: &[Frame]) -> &'a [Stmt] {
+    frames.windows(2).fold(body, |statements, w| {
+        match (&w[1].kind, &statements[w[0].index]) {
+            (FrameKind::Block, Stmt::Block(block)) => block.statements(),
+            (FrameKind::Then, Stmt::If(x, ..)) => x.body.statements(),
+            (FrameKind::Else, Stmt::If(x, ..)) => x.branch.statements(),
+            (FrameKind::Loop, Stmt::While(x, ..) | Stmt::Do(x, ..)) => x.body.statements(),
+            (FrameKind::For(..), Stmt::For(x, ..)) => x.2.body.statements(),
+            (FrameKind::Try(..), Stmt::TryCatch(x, ..)) => x.body.statements(),
+            (FrameKind::Catch(..), Stmt::TryCatch(x, ..)) => x.branch.statements(),
+            _ => unreachable!("frame does not match statement"),
+        }
+    })
+}
+
+/// Statements run by a frame independently of its parent frame.
+#[cfg(any(not(feature = "no_function"), feature = "stackless"))]
+enum FrameRoot {
+    /// The body of a script-defined function.
+    #[cfg(not(feature = "no_function"))]
+    Fn(crate::Shared<crate::ast::ScriptFuncDef>),
+    /// A statements block inside an operand, as a single statement.
+    #[cfg(feature = "stackless")]
+    Block(crate::Shared<Stmt>),
+}
+
+#[cfg(any(not(feature = "no_function"), feature = "stackless"))]
+impl FrameRoot {
+    /// Get the statements run.
+    #[inline]
+    #[must_use]
+    fn statements(&self) -> &[Stmt] {
+        match self {
+            #[cfg(not(feature = "no_function"))]
+            Self::Fn(fn_def) => fn_def.body.statements(),
+            #[cfg(feature = "stackless")]
+            Self::Block(stmt) => std::slice::from_ref(&**stmt),
+        }
+    }
+}
+
+/// Get the innermost frame running statements independently of its parent frame, with its index.
+///
+/// Such a frame runs the body of a script-defined function, or a statements block inside an
+/// operand.
+#[cfg(any(not(feature = "no_function"), feature = "stackless"))]
+fn innermost_root(frames: &[Frame]) -> Option<(usize, FrameRoot)> {
+    frames
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(i, f)| match f.kind {
+            #[cfg(not(feature = "no_function"))]
+            FrameKind::Call(ref call) => Some((i, FrameRoot::Fn(call.fn_def.clone()))),
+            #[cfg(feature = "stackless")]
+            FrameKind::Operand(ref operand) => operand
+                .block
+                .clone()
+                .map(|block| (i, FrameRoot::Block(block))),
+            _ => None,
+        })
+}
+
+/// Get `this` for the statements run by the innermost of a stack of frames.
+///
+/// `this` is always unbound inside the bodies of script-defined functions.
+fn frame_this_ptr<'a>(
+    _frames: &[Frame],
+    this_ptr: &'a mut Option<Dynamic>,
+    _unbound: &'a mut Option<Dynamic>,
+) -> &'a mut Option<Dynamic> {
+    #[cfg(not(feature = "no_function"))]
+    if _frames
+        .iter()
+        .any(|f| matches!(f.kind, FrameKind::Call(..)))
+    {
+        return _unbound;
+    }
+    this_ptr
+}
+
+/// Get the function call of a statement where asynchronous evaluation can be suspended, i.e.
+/// `foo(x);`, `let y = foo(x);`, `y = foo(x);` or `return foo(x);`.
+pub(crate) fn async_call_site(stmt: &Stmt) -> Option<(&FnCallExpr, Position)> {
+    match stmt {
+        Stmt::FnCall(x, pos) => Some((x, *pos)),
+        _ => match async_call_expr(stmt)? {
+            Expr::FnCall(x, pos) => Some((x, *pos)),
+            _ => unreachable!("Expr::FnCall expected"),
+        },
+    }
+}
+
+/// Get the function call expression of a statement (other than a function call statement) at
+/// which asynchronous evaluation can suspend, if any.
+fn async_call_expr(stmt: &Stmt) -> Option<&Expr> {
+    let expr = match stmt {
+        Stmt::Expr(expr) => &**expr,
+        Stmt::Var(x, ..) => &x.1,
+        Stmt::Assignment(x) => &x.1.rhs,
+        Stmt::Return(Some(expr), options, ..) if !options.intersects(ASTFlags::BREAK) => expr,
+        _ => return None,
+    };
+
+    match expr {
+        Expr::FnCall(x, ..) if x.op_token.is_none() => Some(expr),
+        _ => None,
+    }
+}
+
+impl Engine {
+    /// Run statements up to the next suspension point.
+    ///
+    /// Statements that cannot suspend are evaluated normally. Statements that may suspend (blocks,
+    /// `if` statements and loops, plus `try` statements and calls to script-defined functions under
+    /// asynchronous evaluation) push a new frame that keeps track of the position inside them, so
+    /// that they can be suspended and resumed.
+    ///
+    /// `body` is run by the outermost frame, which is pushed when there are no frames.
+    pub(crate) fn run_resumable(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: &mut Option<Dynamic>,
+        body: &[Stmt],
+        state: &mut Resumable,
+        mode: ResumeMode,
+    ) -> RhaiResultOf<Suspension> {
+        // Tail calls in frames replace the frame of the current function instead of unwinding the
+        // body of a function called natively, except under stackless evaluation
+        #[cfg(not(feature = "no_function"))]
+        let orig_tail_call_level = match mode {
+            #[cfg(feature = "stackless")]
+            ResumeMode::Stackless => global.tail_call_level,
+            _ => global.tail_call_level.take(),
+        };
+        #[cfg(not(feature = "no_function"))]
+        defer! { global => move |g| g.tail_call_level = orig_tail_call_level }
+
+        if state.frames.is_empty() {
+            self.push_frame(global, scope, state, FrameKind::Body);
+        }
+
+        // Complete the statement suspended at an asynchronous call
+        let mut result = match state.async_result.take() {
+            Some(result) => {
+                let stmt = state.async_stmt.take().unwrap();
+                let pos = async_call_site(&stmt).unwrap().1;
+                result
+                    .map_err(|err| err.fill_position(pos))
+                    .and_then(|value| {
+                        self.complete_statement(
+                            global, caches, scope, this_ptr, state, mode, stmt, value,
+                        )
+                    })
+                    .map(|_| None)
+            }
+            None => Ok(None),
+        };
+
+        loop {
+            let suspension = match result {
+                Ok(suspension) => suspension,
+                Err(err) => {
+                    self.unwind_resumable(global, caches, scope, this_ptr, state, mode, err)?
+                }
+            };
+
+            if let Some(suspension) = suspension {
+                return Ok(suspension);
+            }
+
+            if let Some(ref suspender) = state.suspender {
+                if suspender.take_request() {
+                    return Ok(Suspension::Suspended);
+                }
+            }
+
+            result = self.step_resumable(global, caches, scope, this_ptr, body, state, mode);
+        }
+    }
+
+    /// Run the next statement.
+    fn step_resumable(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: &mut Option<Dynamic>,
+        body: &[Stmt],
+        state: &mut Resumable,
+        mode: ResumeMode,
+    ) -> RhaiResultOf<Option<Suspension>> {
+        // Operands are reduced separately
+        #[cfg(feature = "stackless")]
+        if let FrameKind::Operand(..) = state.frames.last().unwrap().kind {
+            return self.step_operand(global, caches, scope, this_ptr, body, state, mode);
+        }
+
+        // Keep the function being run alive while its statements are borrowed
+        #[cfg(any(not(feature = "no_function"), feature = "stackless"))]
+        let root = innermost_root(&state.frames);
+        #[cfg(any(not(feature = "no_function"), feature = "stackless"))]
+        let (body, start) = match root {
+            Some((i, ref root)) => (root.statements(), i),
+            None => (body, 0),
+        };
+        #[cfg(all(feature = "no_function", not(feature = "stackless")))]
+        let start = 0;
+
+        let statements = frame_statements(body, &state.frames[start..]);
+        let depth = state.frames.len();
+        let index = state.frames[depth - 1].index;
+
+        // End of block
+        if index >= statements.len() {
+            match state.frames[depth - 1].kind {
+                FrameKind::Body => return Ok(Some(Suspension::Done(mem::take(&mut state.value)))),
+                #[cfg(not(feature = "no_function"))]
+                FrameKind::Call(..) => {
+                    let value = mem::take(&mut state.value);
+                    return self
+                        .finish_fn_call(global, caches, scope, this_ptr, state, mode, value);
+                }
+                _ => (),
+            }
+
+            let parent = &state.frames[depth - 2];
+            let stmt = &frame_statements(body, &state.frames[start..depth - 1])[parent.index];
+
+            // Conditions with calls to script-defined functions are run as operands
+            #[cfg(feature = "stackless")]
+            if let (FrameKind::Loop, Stmt::While(x, ..) | Stmt::Do(x, ..)) =
+                (&state.frames[depth - 1].kind, stmt)
+            {
+                if self.push_operand(global, caches, scope, state, mode, &x.expr) {
+                    return Ok(None);
+                }
+            }
+
+            let unbound = &mut None;
+            let this_ptr = frame_this_ptr(&state.frames, this_ptr, unbound);
+            let frame = state.frames.last_mut().unwrap();
+            let scope_len = frame.scope_len;
+
+            let repeat = match (&mut frame.kind, stmt) {
+                (FrameKind::Loop, Stmt::While(x, ..))
+                    if matches!(x.expr, Expr::Unit(..) | Expr::BoolConstant(true, ..)) =>
+                {
+                    // Empty loop bodies count as operations
+                    if x.body.is_empty() {
+                        self.track_operation(global, x.body.position())?;
+                    }
+                    true
+                }
+                (FrameKind::Loop, Stmt::While(..) | Stmt::Do(..)) => {
+                    let (expr, when) = loop_condition(stmt);
+                    self.eval_condition(global, caches, scope, this_ptr.as_mut(), expr)? == when
+                }
+                (FrameKind::For(source, count), Stmt::For(x, ..)) => {
+                    let (var_name, counter, FlowControl { expr, body, .. }) = &**x;
+
+                    // Loop variables are added once and then updated in place,
+                    // so closures capturing them see the same variables
+                    let index = scope_len + usize::from(counter.is_some());
+
+                    if scope.len() > index {
+                        scope.rewind(index + 1);
+                    } else {
+                        self.push_for_loop_vars(scope, var_name, counter.as_ref());
+                    }
+
+                    let counter = counter.as_ref().map(|c| (c, scope_len));
+                    let empty_body = Some(body.position()).filter(|_| body.is_empty());
+
+                    let repeat = self.next_for_iteration(
+                        global,
+                        caches,
+                        scope,
+                        source,
+                        counter,
+                        index,
+                        *count,
+                        expr.position(),
+                        empty_body,
+                    )?;
+                    *count += 1;
+                    repeat
+                }
+                _ => false,
+            };
+
+            self.repeat_or_pop(global, caches, scope, state, repeat);
+            return Ok(None);
+        }
+
+        let stmt = &statements[index];
+
+        let may_suspend = match mode {
+            #[cfg(not(feature = "no_function"))]
+            ResumeMode::Generator => stmt.contains_yield(),
+            _ => matches!(
+                stmt,
+                Stmt::Block(..)
+                    | Stmt::If(..)
+                    | Stmt::While(..)
+                    | Stmt::Do(..)
+                    | Stmt::For(..)
+                    | Stmt::TryCatch(..)
+            ),
+        };
+
+        if !may_suspend {
+            // Operands with calls to script-defined functions are run as frames
+            #[cfg(feature = "stackless")]
+            if let Some(expr) = self.stmt_operand(global, caches, mode, stmt) {
+                let kind = FrameKind::Operand(Operand::new(expr).into());
+                self.push_frame(global, scope, state, kind);
+                return Ok(None);
+            }
+
+            // Calls to asynchronous functions may suspend
+            if let Some((call, pos)) = async_call_site(stmt) {
+                if self.is_frame_call_site(global, caches, mode, call) {
+                    let expr = async_call_expr(stmt);
+                    return self.call_at_async_site(
+                        global, caches, scope, this_ptr, state, mode, stmt, expr, call, pos,
+                    );
+                }
+            }
+
+            // Other statements are evaluated normally
+            let rewind_scope = self.frame_rewinds_scope(state, mode);
+            let unbound = &mut None;
+            let this_ptr = frame_this_ptr(&state.frames, this_ptr, unbound).as_mut();
+
+            state.value = self.eval_stmt(global, caches, scope, this_ptr, stmt, rewind_scope)?;
+
+            // Newly-imported modules may contain global functions
+            #[cfg(not(feature = "no_module"))]
+            if matches!(stmt, Stmt::Import(..)) {
+                caches.fn_resolution_cache_mut().clear();
+            }
+
+            state.frames.last_mut().unwrap().index += 1;
+            return Ok(None);
+        }
+
+        let unbound = &mut None;
+        let this_ptr = frame_this_ptr(&state.frames, this_ptr, unbound);
+
+        self.track_operation(global, stmt.position())?;
+
+        #[cfg(feature = "debugging")]
+        self.run_debugger(global, caches, scope, this_ptr.as_mut(), stmt)?;
+
+        let kind = match stmt {
+            #[cfg(not(feature = "no_function"))]
+            Stmt::Yield(expr, ..) => {
+                let value = match expr {
+                    Some(expr) => self
+                        .eval_expr(global, caches, scope, this_ptr.as_mut(), expr)?
+                        .flatten(),
+                    None => Dynamic::UNIT,
+                };
+
+                state.frames.last_mut().unwrap().index += 1;
+
+                return Ok(Some(Suspension::Yield(value)));
+            }
+            Stmt::Block(..) => FrameKind::Block,
+            #[cfg(feature = "stackless")]
+            Stmt::If(x, ..) | Stmt::While(x, ..)
+                if self.push_operand(global, caches, scope, state, mode, &x.expr) =>
+            {
+                return Ok(None)
+            }
+            Stmt::If(x, ..) => {
+                if self.eval_condition(global, caches, scope, this_ptr.as_mut(), &x.expr)? {
+                    FrameKind::Then
+                } else {
+                    FrameKind::Else
+                }
+            }
+            Stmt::While(x, ..) => {
+                if matches!(x.expr, Expr::Unit(..))
+                    || self.eval_condition(global, caches, scope, this_ptr.as_mut(), &x.expr)?
+                {
+                    FrameKind::Loop
+                } else {
+                    state.value = Dynamic::UNIT;
+                    state.frames.last_mut().unwrap().index += 1;
+                    return Ok(None);
+                }
+            }
+            Stmt::Do(..) => FrameKind::Loop,
+            Stmt::TryCatch(x, ..) => match x.expr {
+                Expr::Variable(ref v, .., pos) => FrameKind::Try(Some((v.3.clone(), pos))),
+                _ => FrameKind::Try(None),
+            },
+            Stmt::For(x, ..) => {
+                let (var_name, counter, FlowControl { expr, .. }) = &**x;
+
+                self.check_for_loop_vars(scope, var_name, counter.as_ref())?;
+
+                #[cfg(feature = "stackless")]
+                if self.push_operand(global, caches, scope, state, mode, expr) {
+                    return Ok(None);
+                }
+
+                let iter_obj = self
+                    .eval_expr(global, caches, scope, this_ptr.as_mut(), expr)?
+                    .flatten();
+
+                self.start_for_loop(global, scope, state, expr, iter_obj)?;
+                return Ok(None);
+            }
+            _ => unreachable!("statement cannot suspend: {:?}", stmt),
+        };
+
+        self.push_frame(global, scope, state, kind);
+        Ok(None)
+    }
+
+    /// Start a `for` loop over the values of an iterable object, as a new frame.
+    fn start_for_loop(
+        &self,
+        global: &mut GlobalRuntimeState,
+        scope: &Scope,
+        state: &mut Resumable,
+        expr: &Expr,
+        iter_obj: Dynamic,
+    ) -> RhaiResultOf<()> {
+        let source = match LoopIter::from_range(&iter_obj) {
+            Some(range) => ForSource::Iter(range),
+            None => match self.make_for_source(global, iter_obj, expr.start_position())? {
+                #[cfg(not(feature = "sync"))]
+                ForSource::Iter(iter) => ForSource::Iter(LoopIter::Iter(iter)),
+                #[cfg(feature = "sync")]
+                ForSource::Iter(iter) => {
+                    ForSource::Iter(LoopIter::Iter(iter.collect::<Vec<_>>().into_iter()))
+                }
+                #[cfg(not(feature = "no_function"))]
+                ForSource::Generator(generator) => ForSource::Generator(generator),
+            },
+        };
+
+        // The first value is fetched at the end of the (empty) block
+        self.push_frame(global, scope, state, FrameKind::For(source.into(), 0));
+        state.frames.last_mut().unwrap().index = usize::MAX;
+        Ok(())
+    }
+
+    /// Is a function call at an asynchronous call site made by the frame machine?
+    ///
+    /// Under stackless evaluation, only calls to script-defined functions are.
+    #[inline]
+    fn is_frame_call_site(
+        &self,
+        global: &GlobalRuntimeState,
+        caches: &mut Caches,
+        mode: ResumeMode,
+        call: &FnCallExpr,
+    ) -> bool {
+        #[cfg(feature = "stackless")]
+        if mode == ResumeMode::Stackless {
+            return self.is_script_call(global, caches, call);
+        }
+        #[cfg(not(feature = "stackless"))]
+        let _ = (global, caches, call);
+
+        mode.suspends_anywhere()
+    }
+
+    /// Start running the innermost frame again if `repeat` is `true`, otherwise pop it and move on.
+    fn repeat_or_pop(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        state: &mut Resumable,
+        repeat: bool,
+    ) {
+        let frame = state.frames.last_mut().unwrap();
+
+        if repeat {
+            if !matches!(frame.kind, FrameKind::For(..)) {
+                scope.rewind(frame.scope_len);
+            }
+            frame.index = 0;
+            return;
+        }
+
+        // Loops and `catch` blocks have no value
+        if matches!(
+            frame.kind,
+            FrameKind::Loop | FrameKind::For(..) | FrameKind::Catch(..)
+        ) {
+            state.value = Dynamic::UNIT;
+        }
+        self.pop_frame(global, caches, scope, state);
+
+        // Blocks inside operands are replaced by their values
+        #[cfg(feature = "stackless")]
+        if let FrameKind::Operand(..) = state.frames.last().unwrap().kind {
+            let value = mem::take(&mut state.value);
+            self.resume_operand(global, caches, state, value);
+            return;
+        }
+
+        state.frames.last_mut().unwrap().index += 1;
+    }
+
+    /// Should statements in the innermost frame rewind the scope?
+    ///
+    /// Only the root statements of an asynchronous evaluation keep new variables in the scope,
+    /// unless a stackless evaluation runs the body of a function.
+    #[inline(always)]
+    fn frame_rewinds_scope(&self, state: &Resumable, mode: ResumeMode) -> bool {
+        #[cfg(feature = "stackless")]
+        if state.rewind_root_scope {
+            return true;
+        }
+        !mode.suspends_anywhere() || state.frames.len() > 1
+    }
+
+    /// Push a new frame.
+    fn push_frame(
+        &self,
+        global: &mut GlobalRuntimeState,
+        scope: &Scope,
+        state: &mut Resumable,
+        kind: FrameKind,
+    ) {
+        if kind.is_scoped() {
+            global.scope_level += 1;
+        }
+
+        state.frames.push(Frame {
+            kind,
+            index: 0,
+            scope_len: scope.len(),
+            #[cfg(not(feature = "no_module"))]
+            imports_len: global.num_imports(),
+            always_search_scope: global.always_search_scope,
+        });
+
+        state.value = Dynamic::UNIT;
+    }
+
+    /// Pop the innermost frame, restoring the state before it started.
+    ///
+    /// The position of its parent frame is not changed.
+    fn pop_frame(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        state: &mut Resumable,
+    ) -> Frame {
+        #[cfg_attr(feature = "no_function", allow(unused_mut))]
+        let mut frame = state.frames.pop().unwrap();
+
+        // Operands keep all changes, e.g. variables defined by `eval`
+        #[cfg(feature = "stackless")]
+        if let FrameKind::Operand(..) = frame.kind {
+            return frame;
+        }
+
+        scope.rewind(frame.scope_len);
+        #[cfg(not(feature = "no_module"))]
+        global.truncate_imports(frame.imports_len);
+        global.always_search_scope = frame.always_search_scope;
+
+        if frame.kind.is_scoped() {
+            global.scope_level -= 1;
+        }
+
+        #[cfg(not(feature = "no_function"))]
+        if let FrameKind::Call(ref mut call) = frame.kind {
+            #[cfg(feature = "debugging")]
+            if self.is_debugger_registered() {
+                global
+                    .debugger_mut()
+                    .rewind_call_stack(call.orig_call_stack_len);
+            }
+
+            #[cfg(feature = "profiling")]
+            global.rewind_profile(call.orig_profile_len);
+
+            global.lib.truncate(call.orig_lib_len);
+
+            #[cfg(not(feature = "no_module"))]
+            if let Some(constants) = call.orig_constants.take() {
+                global.constants = constants;
+            }
+
+            caches.rewind_fn_resolution_caches(call.orig_fn_resolution_caches_len);
+            global.source = call.orig_source.take();
+
+            if let Some(orig_scope) = call.orig_scope.take() {
+                scope.restore_entries(orig_scope);
+            }
+            global.level -= 1;
+        }
+        #[cfg(feature = "no_function")]
+        let _ = caches;
+
+        frame
+    }
+
+    /// Unwind the frames after an error.
+    ///
+    /// Returns an error if the error is not handled by any frame.
+    fn unwind_resumable(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: &mut Option<Dynamic>,
+        state: &mut Resumable,
+        mode: ResumeMode,
+        mut err: RhaiError,
+    ) -> RhaiResultOf<Option<Suspension>> {
+        #[cfg(feature = "no_function")]
+        let _ = this_ptr;
+
+        loop {
+            let frame = state.frames.last_mut().unwrap();
+
+            match (&frame.kind, &*err) {
+                // Continue loop
+                (FrameKind::Loop | FrameKind::For(..), ERR::LoopBreak(false, ..)) => {
+                    frame.index = usize::MAX;
+                    return Ok(None);
+                }
+                // Break out of loop
+                (FrameKind::Loop | FrameKind::For(..), ERR::LoopBreak(true, ..)) => {
+                    self.pop_frame(global, caches, scope, state);
+                    state.frames.last_mut().unwrap().index += 1;
+
+                    if let ERR::LoopBreak(.., value, _) = *err {
+                        state.value = value;
+                    }
+                    return Ok(None);
+                }
+                // Return from the root statements
+                (FrameKind::Body, ERR::Return(..)) => {
+                    let ERR::Return(value, ..) = *err else {
+                        unreachable!()
+                    };
+                    return Ok(Some(Suspension::Done(value)));
+                }
+                // Exit from the root statements of an asynchronous evaluation
+                (FrameKind::Body, ERR::Exit(..))
+                    if matches!(mode, ResumeMode::Async | ResumeMode::Suspendable) =>
+                {
+                    let ERR::Exit(value, ..) = *err else {
+                        unreachable!()
+                    };
+                    return Ok(Some(Suspension::Done(value)));
+                }
+                // Error not handled
+                (FrameKind::Body, ..) => return Err(err),
+                // Return from function
+                #[cfg(not(feature = "no_function"))]
+                (FrameKind::Call(..), ERR::Return(..)) => {
+                    let ERR::Return(value, ..) = *err else {
+                        unreachable!()
+                    };
+
+                    match self.finish_fn_call(global, caches, scope, this_ptr, state, mode, value) {
+                        Ok(suspension) => return Ok(suspension),
+                        Err(e) => err = e,
+                    }
+                }
+                // Error in function
+                #[cfg(not(feature = "no_function"))]
+                (FrameKind::Call(call), ..) => {
+                    let (fn_def, environ, pos) =
+                        (call.fn_def.clone(), call.environ.clone(), call.pos);
+
+                    #[cfg(feature = "debugging")]
+                    if let Err(e) = self.run_debugger_on_fn_exit(
+                        global,
+                        caches,
+                        scope,
+                        None,
+                        &fn_def,
+                        Err(&err),
+                        pos,
+                    ) {
+                        err = e;
+                    }
+
+                    err = self.make_fn_call_error(global, &fn_def.name, environ.as_ref(), err, pos);
+                    self.pop_frame(global, caches, scope, state);
+                }
+                // Catch error
+                (FrameKind::Try(..), ..) if err.is_catchable() => {
+                    let FrameKind::Try(catch_var) =
+                        self.pop_frame(global, caches, scope, state).kind
+                    else {
+                        unreachable!()
+                    };
+
+                    self.push_frame(global, scope, state, FrameKind::Catch(err));
+
+                    let FrameKind::Catch(ref mut caught) = state.frames.last_mut().unwrap().kind
+                    else {
+                        unreachable!()
+                    };
+
+                    if let Err(e) = self.push_catch_var(global, scope, caught, catch_var) {
+                        err = e;
+                        continue;
+                    }
+
+                    return Ok(None);
+                }
+                // Error in `catch` block
+                (FrameKind::Catch(..), ..) => {
+                    let FrameKind::Catch(caught) =
+                        self.pop_frame(global, caches, scope, state).kind
+                    else {
+                        unreachable!()
+                    };
+
+                    err = self.make_catch_block_error(caught, err);
+                }
+                _ => {
+                    self.pop_frame(global, caches, scope, state);
+                }
+            }
+        }
+    }
+
+    /// Call a function at a statement where asynchronous evaluation can suspend.
+    ///
+    /// Script-defined functions are run as new frames, so that the evaluation can be suspended
+    /// inside them as well.
+    ///
+    /// `expr` is the function call expression, unless the statement is a function call itself.
+    fn call_at_async_site(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: &mut Option<Dynamic>,
+        state: &mut Resumable,
+        mode: ResumeMode,
+        stmt: &Stmt,
+        expr: Option<&Expr>,
+        call: &FnCallExpr,
+        pos: Position,
+    ) -> RhaiResultOf<Option<Suspension>> {
+        let unbound = &mut None;
+        let frame_this_ptr = frame_this_ptr(&state.frames, this_ptr, unbound);
+
+        // The statement (unless the call is an operand), then the call expression (if any), are
+        // run up to the call in the same manner as `eval_stmt`
+        if !matches!(stmt, Stmt::Noop(..)) {
+            self.track_operation(global, stmt.position())?;
+
+            #[cfg(feature = "debugging")]
+            self.run_debugger(global, caches, scope, frame_this_ptr.as_mut(), stmt)?;
+        }
+        if let Stmt::Var(..) = stmt {
+            self.check_var_stmt(global, caches, scope, frame_this_ptr.as_mut(), stmt)?;
+        }
+        if let Some(_expr) = expr {
+            self.track_operation(global, pos)?;
+
+            #[cfg(feature = "debugging")]
+            self.run_debugger(global, caches, scope, frame_this_ptr.as_mut(), _expr)?;
+        }
+
+        #[cfg(not(feature = "no_function"))]
+        if let Some((fn_def, environ, source)) = self.resolve_script_call(global, caches, call) {
+            // Tail calls in the body of a function not run as a frame unwind the body instead
+            if !call.is_tail_call || global.tail_call_level != Some(global.level) {
+                let args = call
+                    .args
+                    .iter()
+                    .map(|expr| {
+                        self.get_arg_value(global, caches, scope, frame_this_ptr.as_mut(), expr)
+                            .map(|(value, ..)| value.flatten())
+                    })
+                    .collect::<RhaiResultOf<crate::FnArgsVec<_>>>()?;
+
+                // Tail calls replace the frame of the current function
+                if call.is_tail_call
+                    && self.tail_call_optimization()
+                    && environ.is_none()
+                    && source == global.source
+                    && state
+                        .frames
+                        .iter()
+                        .any(|f| matches!(f.kind, FrameKind::Call(..)))
+                {
+                    self.replace_fn_call_frame(global, caches, scope, state, fn_def, args, pos)?;
+                    return Ok(None);
+                }
+
+                let stmt = Some(stmt.clone());
+                self.push_fn_call_frame(
+                    global, caches, scope, state, fn_def, environ, source, args, stmt, pos,
+                )?;
+                return Ok(None);
+            }
+        }
+
+        // Only the function called here can suspend, under asynchronous evaluation
+        let orig_async_site = match mode {
+            ResumeMode::Async => global.async_site.replace((global.level + 1, pos)),
+            _ => global.async_site.take(),
+        };
+
+        let result =
+            self.eval_fn_call_expr(global, caches, scope, frame_this_ptr.as_mut(), call, pos);
+
+        global.async_site = orig_async_site;
+
+        let value = result?;
+
+        if value.is::<AsyncCall>() {
+            if let Some(future) = value.cast::<AsyncCall>().take_future() {
+                state.async_stmt = Some(stmt.clone());
+                return Ok(Some(Suspension::Async(future)));
+            }
+            unreachable!("future already taken");
+        }
+
+        let stmt = stmt.clone();
+        self.complete_statement(global, caches, scope, this_ptr, state, mode, stmt, value)?;
+
+        Ok(None)
+    }
+
+    /// Get the script-defined function called by a function call, if it can be run as a frame.
+    #[cfg(not(feature = "no_function"))]
+    fn resolve_script_call(
+        &self,
+        global: &GlobalRuntimeState,
+        caches: &mut Caches,
+        call: &FnCallExpr,
+    ) -> Option<ScriptCall> {
+        if call.op_token.is_some()
+            || call.is_qualified()
+            || call.hashes.is_native_only()
+            || call.capture_parent_scope
+        {
+            return None;
+        }
+
+        let local_entry = &mut None;
+        let hash = call.hashes.script();
+
+        match self.resolve_fn(global, caches, local_entry, None, hash, None, false)? {
+            super::FnResolutionCacheEntry {
+                func: crate::func::RhaiFunc::Script { fn_def, environ },
+                source,
+            } if !fn_def.is_generator => Some((fn_def.clone(), environ.clone(), source.clone())),
+            _ => None,
+        }
+    }
+
+    /// Complete a statement with the result of its function call, moving on to the next statement.
+    fn complete_statement(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: &mut Option<Dynamic>,
+        state: &mut Resumable,
+        mode: ResumeMode,
+        stmt: Stmt,
+        value: Dynamic,
+    ) -> RhaiResultOf<()> {
+        // Calls inside operands are replaced by their results
+        #[cfg(feature = "stackless")]
+        if let FrameKind::Operand(..) = state.frames.last().unwrap().kind {
+            self.resume_operand(global, caches, state, value);
+            return Ok(());
+        }
+
+        let rewind_scope = self.frame_rewinds_scope(state, mode);
+        let unbound = &mut None;
+        let this_ptr = frame_this_ptr(&state.frames, this_ptr, unbound).as_mut();
+
+        // Complete the statement with the result of the function call
+        let value = match stmt {
+            Stmt::FnCall(..) | Stmt::Expr(..) => value,
+            Stmt::Var(..) => {
+                self.define_var_stmt(global, scope, &stmt, value.flatten(), rewind_scope)?;
+                Dynamic::UNIT
+            }
+            Stmt::Assignment(..) => {
+                self.eval_assignment(global, caches, scope, this_ptr, &stmt, value.flatten())?;
+                Dynamic::UNIT
+            }
+            Stmt::Return(_, options, pos) if options.intersects(ASTFlags::BREAK) => {
+                return Err(ERR::ErrorRuntime(value, pos).into())
+            }
+            Stmt::Return(.., pos) => return Err(ERR::Return(value, pos).into()),
+            _ => unreachable!("statement has no function call: {:?}", stmt),
+        };
+
+        state.value = value;
+        state.frames.last_mut().unwrap().index += 1;
+        Ok(())
+    }
+
+    /// Start running a script-defined function as a new frame.
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) fn push_fn_call_frame(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        state: &mut Resumable,
+        fn_def: crate::Shared<crate::ast::ScriptFuncDef>,
+        environ: Option<crate::Shared<crate::ast::EncapsulatedEnviron>>,
+        source: Option<ImmutableString>,
+        args: crate::FnArgsVec<Dynamic>,
+        stmt: Option<Stmt>,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        debug_assert_eq!(fn_def.params.len(), args.len());
+
+        // The function is called by the host
+        if state.frames.is_empty() {
+            self.push_frame(global, scope, state, FrameKind::Body);
+        }
+
+        let level = global.level + 1;
+        self.check_script_fn_call(global, &fn_def, args.iter(), level, pos)?;
+
+        // Functions called by scripts do not see the variables of the caller
+        let orig_scope = stmt.as_ref().map(|_| scope.take_entries());
+
+        if let Err(err) = self.check_fn_params_len(scope.len(), &fn_def, pos) {
+            if let Some(orig_scope) = orig_scope {
+                scope.restore_entries(orig_scope);
+            }
+            return Err(err);
+        }
+
+        let scope_len = scope.len();
+        #[cfg(not(feature = "no_module"))]
+        let imports_len = global.num_imports();
+        let orig_lib_len = global.lib.len();
+        let orig_source = mem::replace(&mut global.source, source);
+
+        global.level += 1;
+        global.scope_level += 1;
+
+        // Put arguments into scope as variables
+        crate::func::script::push_fn_params(scope, &fn_def, args);
+
+        // Push a new call stack frame
+        #[cfg(feature = "debugging")]
+        let orig_call_stack_len =
+            self.push_fn_call_stack_frame(global, &fn_def, scope, scope_len, pos);
+
+        // Record the call in the profiler
+        #[cfg(feature = "profiling")]
+        let orig_profile_len = self.enter_fn_profile(global, &fn_def);
+
+        // Merge in encapsulated environment, if any
+        let orig_fn_resolution_caches_len = caches.fn_resolution_caches_len();
+
+        #[cfg(not(feature = "no_module"))]
+        let orig_constants = self.enter_fn_environ(global, environ.as_ref());
+
+        let body_pos = fn_def.body.position();
+
+        let call = CallFrame {
+            fn_def,
+            environ,
+            stmt,
+            pos,
+            orig_scope,
+            orig_source,
+            orig_lib_len,
+            #[cfg(not(feature = "no_module"))]
+            orig_constants,
+            orig_fn_resolution_caches_len,
+            #[cfg(feature = "debugging")]
+            orig_call_stack_len,
+            #[cfg(feature = "profiling")]
+            orig_profile_len,
+            return_types: crate::StaticVec::new_const(),
+        };
+
+        state.frames.push(Frame {
+            kind: FrameKind::Call(call.into()),
+            index: 0,
+            scope_len,
+            #[cfg(not(feature = "no_module"))]
+            imports_len,
+            always_search_scope: global.always_search_scope,
+        });
+        state.value = Dynamic::UNIT;
+
+        #[cfg(feature = "debugging")]
+        if self.is_debugger_registered() {
+            let node = Stmt::Noop(body_pos);
+            self.run_debugger(global, caches, scope, None, &node)?;
+        }
+        #[cfg(not(feature = "debugging"))]
+        let _ = body_pos;
+
+        Ok(())
+    }
+
+    /// Finish running a script-defined function with its return value.
+    #[cfg(not(feature = "no_function"))]
+    fn finish_fn_call(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: &mut Option<Dynamic>,
+        state: &mut Resumable,
+        mode: ResumeMode,
+        value: Dynamic,
+    ) -> RhaiResultOf<Option<Suspension>> {
+        let FrameKind::Call(ref call) = state.frames.last().unwrap().kind else {
+            unreachable!("function call frame expected")
+        };
+
+        // Check return type
+        let result: RhaiResult = self
+            .check_return_types(&call.fn_def, &call.return_types, &value, call.pos)
+            .map(|()| value);
+
+        #[cfg(feature = "debugging")]
+        let result = {
+            let (fn_def, pos) = (call.fn_def.clone(), call.pos);
+            let exit = result.as_ref();
+
+            match self.run_debugger_on_fn_exit(global, caches, scope, None, &fn_def, exit, pos) {
+                Ok(()) => result,
+                Err(err) => Err(err),
+            }
+        };
+
+        let FrameKind::Call(call) = self.pop_frame(global, caches, scope, state).kind else {
+            unreachable!()
+        };
+        let value = result?;
+
+        match call.stmt {
+            Some(stmt) => {
+                self.complete_statement(global, caches, scope, this_ptr, state, mode, stmt, value)?;
+                Ok(None)
+            }
+            None => Ok(Some(Suspension::Done(value))),
+        }
+    }
+
+    /// Replace the frame of the script-defined function being run by a tail call to another
+    /// script-defined function.
+    #[cfg(not(feature = "no_function"))]
+    fn replace_fn_call_frame(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        state: &mut Resumable,
+        fn_def: crate::Shared<crate::ast::ScriptFuncDef>,
+        args: crate::FnArgsVec<Dynamic>,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        // Unwind the body of the current function
+        while !matches!(state.frames.last().unwrap().kind, FrameKind::Call(..)) {
+            self.pop_frame(global, caches, scope, state);
+        }
+
+        // Keep the call stack frame of the current function
+        #[cfg(feature = "debugging")]
+        let orig_call_stack_len = match state.frames.last_mut().unwrap().kind {
+            FrameKind::Call(ref mut call) if self.keep_tail_call_frames() => {
+                let len = global
+                    .debugger
+                    .as_ref()
+                    .map_or(0, |dbg| dbg.call_stack().len());
+                Some(mem::replace(&mut call.orig_call_stack_len, len))
+            }
+            _ => None,
+        };
+
+        let FrameKind::Call(call) = self.pop_frame(global, caches, scope, state).kind else {
+            unreachable!()
+        };
+        let CallFrame {
+            fn_def: orig_fn_def,
+            stmt,
+            pos: orig_pos,
+            mut return_types,
+            ..
+        } = *call;
+
+        crate::func::script::add_tail_call_return_type(&mut return_types, &orig_fn_def, &fn_def);
+
+        let source = global.source.clone();
+        self.push_fn_call_frame(
+            global, caches, scope, state, fn_def, None, source, args, stmt, pos,
+        )?;
+
+        let FrameKind::Call(ref mut call) = state.frames.last_mut().unwrap().kind else {
+            unreachable!()
+        };
+        call.pos = orig_pos;
+        call.return_types = return_types;
+        #[cfg(feature = "debugging")]
+        if let Some(len) = orig_call_stack_len {
+            call.orig_call_stack_len = len;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "stackless")]
+impl Engine {
+    /// Run statements as frames, in the same manner as [`eval_stmt_block`][Engine::eval_stmt_block].
+    ///
+    /// Blocks, `if` statements, loops, `try` statements and calls to script-defined functions do
+    /// not recurse on the native stack, as long as the calls are statements or are nested only
+    /// within function call arguments and the operands of `&&`, `||` and `??`.
+    ///
+    /// Calls made by native functions (e.g. function pointers, methods and closures), switch
+    /// cases, custom syntax and calls nested inside other expressions still recurse natively.
+    pub(crate) fn run_stackless(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: Option<&mut Dynamic>,
+        statements: &[Stmt],
+        restore_orig_state: bool,
+    ) -> RhaiResult {
+        if statements.is_empty() {
+            return Ok(Dynamic::UNIT);
+        }
+
+        // Restore scope at end of block if necessary
+        defer! { scope if restore_orig_state => rewind; let orig_scope_len = scope.len(); }
+
+        // Restore global state at end of block if necessary
+        let orig_always_search_scope = global.always_search_scope;
+        #[cfg(not(feature = "no_module"))]
+        let orig_imports_len = global.num_imports();
+
+        if restore_orig_state {
+            global.scope_level += 1;
+        }
+
+        defer! { global if restore_orig_state => move |g| {
+            g.scope_level -= 1;
+
+            #[cfg(not(feature = "no_module"))]
+            g.truncate_imports(orig_imports_len);
+
+            g.always_search_scope = orig_always_search_scope;
+        }}
+
+        // Pop new function resolution caches at end of block
+        defer! {
+            caches => rewind_fn_resolution_caches;
+            let orig_fn_resolution_caches_len = caches.fn_resolution_caches_len();
+        }
+
+        // Move `this` into the evaluation and back
+        let mut this = this_ptr.map(|v| (mem::take(v), v));
+        let mut value = this.as_mut().map(|(value, ..)| mem::take(value));
+
+        let state = &mut Resumable {
+            rewind_root_scope: restore_orig_state,
+            ..Resumable::default()
+        };
+        let result = self.run_resumable(
+            global,
+            caches,
+            scope,
+            &mut value,
+            statements,
+            state,
+            ResumeMode::Stackless,
+        );
+
+        if let (Some((.., this_ptr)), Some(value)) = (this, value) {
+            *this_ptr = value;
+        }
+
+        match result? {
+            Suspension::Done(value) => Ok(value),
+            _ => unreachable!("stackless evaluation cannot suspend"),
+        }
+    }
+
+    /// Get the next part of an expression to be run as a frame, if any.
+    ///
+    /// Calls to script-defined functions and statements blocks are run as frames. Only the
+    /// arguments of function calls and the operands of `&&`, `||` and `??` are searched; other
+    /// expressions are evaluated normally.
+    fn next_pending(
+        &self,
+        global: &GlobalRuntimeState,
+        caches: &mut Caches,
+        expr: &Expr,
+    ) -> Option<Pending> {
+        match expr {
+            Expr::FnCall(x, ..) => {
+                match x
+                    .args
+                    .iter()
+                    .position(|arg| self.has_pending(global, caches, arg))
+                {
+                    Some(i) => Some(Pending::Arg(i)),
+                    None if self.is_script_call(global, caches, x) => Some(Pending::Node),
+                    None => None,
+                }
+            }
+            Expr::And(x, ..) | Expr::Or(x, ..) | Expr::Coalesce(x, ..) => {
+                if self.has_pending(global, caches, &x.lhs) {
+                    Some(Pending::Lhs)
+                } else if self.has_pending(global, caches, &x.rhs) {
+                    Some(Pending::Rhs)
+                } else {
+                    None
+                }
+            }
+            Expr::Stmt(x) if !x.is_empty() => Some(Pending::Node),
+            _ => None,
+        }
+    }
+
+    /// Is a function call a call to a script-defined function to be run as a frame?
+    #[inline(always)]
+    fn is_script_call(
+        &self,
+        global: &GlobalRuntimeState,
+        caches: &mut Caches,
+        call: &FnCallExpr,
+    ) -> bool {
+        #[cfg(not(feature = "no_function"))]
+        return self.resolve_script_call(global, caches, call).is_some();
+        #[cfg(feature = "no_function")]
+        {
+            let _ = (global, caches, call);
+            false
+        }
+    }
+
+    /// Does an expression contain any part to be run as a frame?
+    #[inline(always)]
+    fn has_pending(&self, global: &GlobalRuntimeState, caches: &mut Caches, expr: &Expr) -> bool {
+        self.next_pending(global, caches, expr).is_some()
+    }
+
+    /// Get the next part of an expression to be run as a frame.
+    fn pending_node<'e>(
+        &self,
+        global: &GlobalRuntimeState,
+        caches: &mut Caches,
+        expr: &'e mut Expr,
+    ) -> Option<&'e mut Expr> {
+        match (self.next_pending(global, caches, expr)?, expr) {
+            (Pending::Node, expr) => Some(expr),
+            (Pending::Arg(i), Expr::FnCall(x, ..)) => {
+                self.pending_node(global, caches, &mut x.args[i])
+            }
+            (Pending::Lhs, Expr::And(x, ..) | Expr::Or(x, ..) | Expr::Coalesce(x, ..)) => {
+                self.pending_node(global, caches, &mut x.lhs)
+            }
+            (Pending::Rhs, Expr::And(x, ..) | Expr::Or(x, ..) | Expr::Coalesce(x, ..)) => {
+                self.pending_node(global, caches, &mut x.rhs)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Get the operand of a simple statement, if it has parts to be run as frames.
+    ///
+    /// Calls to script-defined functions with simple arguments are made directly.
+    fn stmt_operand(
+        &self,
+        global: &GlobalRuntimeState,
+        caches: &mut Caches,
+        mode: ResumeMode,
+        stmt: &Stmt,
+    ) -> Option<Expr> {
+        if mode != ResumeMode::Stackless {
+            return None;
+        }
+
+        let expr = match stmt {
+            Stmt::FnCall(x, pos) => {
+                return x
+                    .args
+                    .iter()
+                    .any(|arg| self.has_pending(global, caches, arg))
+                    .then(|| Expr::FnCall(x.clone(), *pos));
+            }
+            Stmt::Var(x, ..) => &x.1,
+            Stmt::Assignment(x) => &x.1.rhs,
+            Stmt::Return(Some(expr), ..) => expr,
+            Stmt::Expr(expr) => expr,
+            _ => return None,
+        };
+
+        let pending = match expr {
+            Expr::FnCall(x, ..) if async_call_site(stmt).is_some() => x
+                .args
+                .iter()
+                .any(|arg| self.has_pending(global, caches, arg)),
+            _ => self.has_pending(global, caches, expr),
+        };
+
+        pending.then(|| expr.clone())
+    }
+
+    /// Push a new frame for an operand if it has parts to be run as frames.
+    fn push_operand(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &Scope,
+        state: &mut Resumable,
+        mode: ResumeMode,
+        expr: &Expr,
+    ) -> bool {
+        if mode != ResumeMode::Stackless || !self.has_pending(global, caches, expr) {
+            return false;
+        }
+
+        let kind = FrameKind::Operand(Operand::new(expr.clone()).into());
+        self.push_frame(global, scope, state, kind);
+        true
+    }
+
+    /// Evaluate the parts of an expression that come before its next part to be run as a frame,
+    /// replacing them by their values.
+    ///
+    /// Operands of `&&`, `||` and `??` that are short-circuited are removed.
+    fn reduce_operand(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: &mut Option<Dynamic>,
+        expr: &mut Expr,
+    ) -> RhaiResultOf<()> {
+        loop {
+            match self.next_pending(global, caches, expr) {
+                None | Some(Pending::Node) => return Ok(()),
+                Some(Pending::Arg(i)) => {
+                    let Expr::FnCall(x, ..) = expr else {
+                        unreachable!()
+                    };
+
+                    // The first argument of a function call may be passed by reference
+                    let by_ref = x.op_token.is_none();
+
+                    for (j, arg) in x.args[..i].iter_mut().enumerate() {
+                        if arg.is_constant()
+                            || (j == 0
+                                && by_ref
+                                && matches!(arg, Expr::Variable(..) | Expr::ThisPtr(..)))
+                        {
+                            continue;
+                        }
+                        let value =
+                            self.eval_expr(global, caches, scope, this_ptr.as_mut(), arg)?;
+                        *arg = Expr::DynamicConstant(value.into(), arg.position());
+                    }
+
+                    self.reduce_operand(global, caches, scope, this_ptr, &mut x.args[i])?;
+
+                    if self.has_pending(global, caches, &x.args[i]) {
+                        return Ok(());
+                    }
+                }
+                Some(Pending::Lhs) => {
+                    let (Expr::And(x, ..) | Expr::Or(x, ..) | Expr::Coalesce(x, ..)) = expr else {
+                        unreachable!()
+                    };
+
+                    self.reduce_operand(global, caches, scope, this_ptr, &mut x.lhs)?;
+
+                    if self.has_pending(global, caches, &x.lhs) {
+                        return Ok(());
+                    }
+                }
+                Some(Pending::Rhs) => {
+                    let pos = expr.position();
+                    let is_and = matches!(expr, Expr::And(..));
+                    let is_coalesce = matches!(expr, Expr::Coalesce(..));
+
+                    let (Expr::And(x, ..) | Expr::Or(x, ..) | Expr::Coalesce(x, ..)) = expr else {
+                        unreachable!()
+                    };
+
+                    let lhs_pos = x.lhs.position();
+                    let value = self.eval_expr(global, caches, scope, this_ptr.as_mut(), &x.lhs)?;
+
+                    let reduced = if is_coalesce {
+                        if value.is_unit() {
+                            mem::replace(&mut x.rhs, Expr::Unit(pos))
+                        } else {
+                            Expr::DynamicConstant(value.into(), pos)
+                        }
+                    } else {
+                        let value = self.condition_value(value, lhs_pos)?;
+
+                        if value != is_and {
+                            Expr::BoolConstant(value, pos)
+                        } else {
+                            // The right operand decides the result
+                            x.lhs = Expr::BoolConstant(value, lhs_pos);
+                            return self
+                                .reduce_operand(global, caches, scope, this_ptr, &mut x.rhs);
+                        }
+                    };
+
+                    *expr = reduced;
+                }
+            }
+        }
+    }
+
+    /// Reduce the operand run by the innermost frame up to its next part to be run as a frame.
+    fn step_operand(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: &mut Option<Dynamic>,
+        body: &[Stmt],
+        state: &mut Resumable,
+        mode: ResumeMode,
+    ) -> RhaiResultOf<Option<Suspension>> {
+        let unbound = &mut None;
+        let frame_this_ptr = frame_this_ptr(&state.frames, this_ptr, unbound);
+
+        let FrameKind::Operand(ref mut operand) = state.frames.last_mut().unwrap().kind else {
+            unreachable!("operand frame expected")
+        };
+
+        self.reduce_operand(global, caches, scope, frame_this_ptr, &mut operand.expr)?;
+
+        match self.pending_node(global, caches, &mut operand.expr) {
+            // The operand is evaluated normally when no parts remain
+            None => {
+                let value = self.eval_expr(
+                    global,
+                    caches,
+                    scope,
+                    frame_this_ptr.as_mut(),
+                    &operand.expr,
+                )?;
+                self.pop_frame(global, caches, scope, state);
+                self.complete_operand(global, caches, scope, this_ptr, body, state, mode, value)
+            }
+            // Run a statements block
+            Some(Expr::Stmt(block)) => {
+                self.track_operation(global, block.position())?;
+                let block = Stmt::Block(block.clone());
+                operand.block = Some(block.into());
+                self.push_frame(global, scope, state, FrameKind::Block);
+                Ok(None)
+            }
+            // Call a script-defined function
+            #[cfg(not(feature = "no_function"))]
+            Some(expr @ Expr::FnCall(..)) => {
+                let expr = expr.clone();
+                let Expr::FnCall(ref call, pos) = expr else {
+                    unreachable!()
+                };
+                let stmt = &Stmt::Noop(pos);
+                self.call_at_async_site(
+                    global,
+                    caches,
+                    scope,
+                    this_ptr,
+                    state,
+                    mode,
+                    stmt,
+                    Some(&expr),
+                    call,
+                    pos,
+                )
+            }
+            Some(expr) => unreachable!("cannot run as frame: {:?}", expr),
+        }
+    }
+
+    /// Replace the next part of the operand run by the innermost frame by its value.
+    fn resume_operand(
+        &self,
+        global: &GlobalRuntimeState,
+        caches: &mut Caches,
+        state: &mut Resumable,
+        value: Dynamic,
+    ) {
+        let FrameKind::Operand(ref mut operand) = state.frames.last_mut().unwrap().kind else {
+            unreachable!("operand frame expected")
+        };
+
+        operand.block = None;
+
+        let node = self
+            .pending_node(global, caches, &mut operand.expr)
+            .unwrap();
+        *node = Expr::DynamicConstant(value.into(), node.position());
+    }
+
+    /// Complete the current statement of the innermost frame with the value of its operand.
+    fn complete_operand(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: &mut Option<Dynamic>,
+        body: &[Stmt],
+        state: &mut Resumable,
+        mode: ResumeMode,
+        value: Dynamic,
+    ) -> RhaiResultOf<Option<Suspension>> {
+        let root = innermost_root(&state.frames);
+        let (body, start) = match root {
+            Some((i, ref root)) => (root.statements(), i),
+            None => (body, 0),
+        };
+
+        let depth = state.frames.len();
+        let statements = frame_statements(body, &state.frames[start..]);
+        let index = state.frames[depth - 1].index;
+
+        // Condition of a loop at the end of its body
+        if index >= statements.len() {
+            let parent = &state.frames[depth - 2];
+            let stmt = &frame_statements(body, &state.frames[start..depth - 1])[parent.index];
+
+            let (expr, when) = loop_condition(stmt);
+            let repeat = self.condition_value(value, expr.position())? == when;
+            self.repeat_or_pop(global, caches, scope, state, repeat);
+            return Ok(None);
+        }
+
+        let kind = match statements[index] {
+            Stmt::If(ref x, ..) => {
+                if self.condition_value(value, x.expr.position())? {
+                    FrameKind::Then
+                } else {
+                    FrameKind::Else
+                }
+            }
+            Stmt::While(ref x, ..) => {
+                if self.condition_value(value, x.expr.position())? {
+                    FrameKind::Loop
+                } else {
+                    state.value = Dynamic::UNIT;
+                    state.frames.last_mut().unwrap().index += 1;
+                    return Ok(None);
+                }
+            }
+            Stmt::For(ref x, ..) => {
+                self.start_for_loop(global, scope, state, &x.2.expr, value.flatten())?;
+                return Ok(None);
+            }
+            ref stmt => {
+                let stmt = stmt.clone();
+                self.complete_statement(global, caches, scope, this_ptr, state, mode, stmt, value)?;
+                return Ok(None);
+            }
+        };
+
+        self.push_frame(global, scope, state, kind);
+        Ok(None)
+    }
+}
+
+impl Engine {
+    /// Take a snapshot of a suspended evaluation as a [`Continuation`].
+    ///
+    /// All frames except the outermost are popped, so that the scope holds the variables of the
+    /// root statements afterwards.
+    pub(crate) fn snapshot_resumable(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        state: &mut Resumable,
+    ) -> RhaiResultOf<Continuation> {
+        // Imported modules cannot be kept
+        #[cfg(not(feature = "no_module"))]
+        if global.num_imports() > 0 {
+            return Err(ERR::ErrorRuntime(
+                "cannot suspend an evaluation with imported modules".into(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let live_scope = ScopeEntry::from_scope(scope);
+        let mut frames = Vec::with_capacity(state.frames.len());
+
+        loop {
+            let frame = state.frames.last_mut().unwrap();
+
+            let kind = match frame.kind {
+                FrameKind::Body => FrameSnapshotKind::Body,
+                FrameKind::Block => FrameSnapshotKind::Block,
+                FrameKind::Then => FrameSnapshotKind::Then,
+                FrameKind::Else => FrameSnapshotKind::Else,
+                FrameKind::Loop => FrameSnapshotKind::Loop,
+                FrameKind::For(ref source, count) => match **source {
+                    ForSource::Iter(LoopIter::Range(current, end, step)) => {
+                        FrameSnapshotKind::For(current, end, step, count)
+                    }
+                    // Other iterators (including generators) cannot be kept
+                    _ => {
+                        return Err(ERR::ErrorRuntime(
+                            "cannot suspend inside a for loop except over a range".into(),
+                            Position::NONE,
+                        )
+                        .into())
+                    }
+                },
+                FrameKind::Try(..) => FrameSnapshotKind::Try,
+                FrameKind::Catch(ref mut err) => {
+                    FrameSnapshotKind::Catch(self.make_catch_value(global, err))
+                }
+                #[cfg(not(feature = "no_function"))]
+                FrameKind::Call(ref call) => {
+                    // Functions from modules cannot be found again
+                    if call.environ.is_some() {
+                        return Err(ERR::ErrorRuntime(
+                            format!(
+                                "cannot suspend an evaluation inside function '{}' from a module",
+                                call.fn_def.name
+                            )
+                            .into(),
+                            Position::NONE,
+                        )
+                        .into());
+                    }
+
+                    let name = call.fn_def.name.clone();
+                    let num_params = call.fn_def.params.len();
+                    let tail_called_from = call
+                        .stmt
+                        .as_ref()
+                        .and_then(async_call_site)
+                        .map(|(x, ..)| (x.name.clone(), x.args.len()))
+                        .filter(|(fn_name, num_args)| *fn_name != name || *num_args != num_params);
+
+                    FrameSnapshotKind::Call {
+                        name,
+                        num_params,
+                        from_script: call.stmt.is_some(),
+                        tail_called_from,
+                        return_types: call.return_types.iter().cloned().collect(),
+                        caller_scope: call.orig_scope.as_ref().map(ScopeEntry::from_scope),
+                        caller_source: call.orig_source.clone(),
+                    }
+                }
+                #[cfg(feature = "stackless")]
+                FrameKind::Operand(..) => unreachable!("stackless evaluation cannot suspend"),
+            };
+
+            frames.push(FrameSnapshot {
+                kind,
+                index: frame.index,
+                scope_len: frame.scope_len,
+                always_search_scope: frame.always_search_scope,
+            });
+
+            if state.frames.len() == 1 {
+                break;
+            }
+
+            self.pop_frame(global, caches, scope, state);
+        }
+
+        frames.reverse();
+
+        Ok(Continuation {
+            scope: live_scope,
+            frames,
+            value: mem::take(&mut state.value),
+            num_operations: global.num_operations,
+            tag: global.tag.clone(),
+        })
+    }
+
+    /// Restore a suspended evaluation from a [`Continuation`].
+    ///
+    /// The scope is replaced by the variables of the continuation.
+    ///
+    /// Returns an error if the continuation does not match `body`.
+    pub(crate) fn restore_resumable(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        body: &[Stmt],
+        continuation: Continuation,
+    ) -> RhaiResultOf<Resumable> {
+        let mismatch = || -> RhaiError {
+            ERR::ErrorRuntime(
+                "continuation does not match the script".into(),
+                Position::NONE,
+            )
+            .into()
+        };
+
+        let Continuation {
+            scope: live_scope,
+            frames,
+            value,
+            num_operations,
+            tag,
+        } = continuation;
+
+        let mut state = Resumable::default();
+
+        for snapshot in frames {
+            let FrameSnapshot {
+                kind,
+                index,
+                scope_len,
+                always_search_scope,
+            } = snapshot;
+
+            // Statements run by the innermost frame so far
+            #[cfg(any(not(feature = "no_function"), feature = "stackless"))]
+            let root = innermost_root(&state.frames);
+            #[cfg(any(not(feature = "no_function"), feature = "stackless"))]
+            let (body, start) = match root {
+                Some((i, ref root)) => (root.statements(), i),
+                None => (body, 0),
+            };
+            #[cfg(all(feature = "no_function", not(feature = "stackless")))]
+            let start = 0;
+
+            // The statement starting the new frame
+            let stmt = match state.frames.last() {
+                Some(parent) => {
+                    let statements = frame_statements(body, &state.frames[start..]);
+                    Some(statements.get(parent.index).ok_or_else(mismatch)?)
+                }
+                None => None,
+            };
+
+            let kind = match (kind, stmt) {
+                (FrameSnapshotKind::Body, None) => FrameKind::Body,
+                (FrameSnapshotKind::Block, Some(Stmt::Block(..))) => FrameKind::Block,
+                (FrameSnapshotKind::Then, Some(Stmt::If(..))) => FrameKind::Then,
+                (FrameSnapshotKind::Else, Some(Stmt::If(..))) => FrameKind::Else,
+                (FrameSnapshotKind::Loop, Some(Stmt::While(..) | Stmt::Do(..))) => FrameKind::Loop,
+                (FrameSnapshotKind::For(current, end, step, count), Some(Stmt::For(..))) => {
+                    let iter = LoopIter::Range(current, end, step);
+                    FrameKind::For(ForSource::Iter(iter).into(), count)
+                }
+                (FrameSnapshotKind::Try, Some(Stmt::TryCatch(x, ..))) => match x.expr {
+                    Expr::Variable(ref v, .., pos) => FrameKind::Try(Some((v.3.clone(), pos))),
+                    _ => FrameKind::Try(None),
+                },
+                (FrameSnapshotKind::Catch(value), Some(Stmt::TryCatch(..))) => {
+                    FrameKind::Catch(ERR::ErrorRuntime(value, Position::NONE).into())
+                }
+                #[cfg(not(feature = "no_function"))]
+                (
+                    FrameSnapshotKind::Call {
+                        name,
+                        num_params,
+                        from_script,
+                        tail_called_from,
+                        return_types,
+                        caller_scope,
+                        caller_source,
+                    },
+                    Some(stmt),
+                ) => {
+                    let (site_name, site_num_args) = match tail_called_from {
+                        Some((ref fn_name, num_args)) => (fn_name, num_args),
+                        None => (&name, num_params),
+                    };
+
+                    let pos = match async_call_site(stmt) {
+                        Some((call, pos))
+                            if from_script
+                                && call.name == *site_name
+                                && call.args.len() == site_num_args =>
+                        {
+                            pos
+                        }
+                        _ if !from_script && state.frames.len() == 1 => Position::NONE,
+                        _ => return Err(mismatch()),
+                    };
+
+                    let fn_def = global
+                        .lib
+                        .iter()
+                        .rev()
+                        .find_map(|lib| lib.get_script_fn(&name, num_params))
+                        .ok_or_else(mismatch)?
+                        .clone();
+
+                    #[cfg(feature = "debugging")]
+                    let orig_call_stack_len = global
+                        .debugger
+                        .as_ref()
+                        .map_or(0, |dbg| dbg.call_stack().len());
+
+                    #[cfg(feature = "debugging")]
+                    if self.is_debugger_registered() {
+                        let source = global.source.clone();
+
+                        global.debugger_mut().push_call_stack_frame(
+                            name,
+                            std::iter::empty(),
+                            source,
+                            pos,
+                        );
+                    }
+
+                    #[cfg(feature = "profiling")]
+                    let orig_profile_len = global.profile_len();
+                    #[cfg(feature = "profiling")]
+                    if global.profiler.is_some() {
+                        let fn_name = fn_def.name.clone();
+                        let source = global.source.clone();
+                        let def_pos = fn_def.body.position();
+                        global.enter_profile(fn_name, num_params, true, source, def_pos);
+                    }
+
+                    global.level += 1;
+
+                    let call = CallFrame {
+                        fn_def,
+                        environ: None,
+                        stmt: from_script.then(|| stmt.clone()),
+                        pos,
+                        orig_scope: caller_scope.map(ScopeEntry::into_scope),
+                        orig_source: caller_source,
+                        orig_lib_len: global.lib.len(),
+                        #[cfg(not(feature = "no_module"))]
+                        orig_constants: None,
+                        orig_fn_resolution_caches_len: caches.fn_resolution_caches_len(),
+                        #[cfg(feature = "debugging")]
+                        orig_call_stack_len,
+                        #[cfg(feature = "profiling")]
+                        orig_profile_len,
+                        return_types: return_types.into_iter().collect(),
+                    };
+
+                    FrameKind::Call(call.into())
+                }
+                _ => return Err(mismatch()),
+            };
+
+            if kind.is_scoped() {
+                global.scope_level += 1;
+            }
+
+            state.frames.push(Frame {
+                kind,
+                index,
+                scope_len,
+                #[cfg(not(feature = "no_module"))]
+                imports_len: global.num_imports(),
+                always_search_scope,
+            });
+
+            // Check that the next statement exists
+            #[cfg(any(not(feature = "no_function"), feature = "stackless"))]
+            let root = innermost_root(&state.frames);
+            #[cfg(any(not(feature = "no_function"), feature = "stackless"))]
+            let (body, start) = match root {
+                Some((i, ref root)) => (root.statements(), i),
+                None => (body, start),
+            };
+
+            if index != usize::MAX && index > frame_statements(body, &state.frames[start..]).len() {
+                return Err(mismatch());
+            }
+        }
+
+        if state.frames.is_empty() {
+            return Err(mismatch());
+        }
+
+        #[cfg(feature = "no_function")]
+        let _ = caches;
+
+        scope.restore_entries(ScopeEntry::into_scope(live_scope));
+        state.value = value;
+        global.num_operations = num_operations;
+        global.tag = tag;
+
+        Ok(state)
+    }
+}
@@ -2,17 +2,98 @@
 
 use super::{Caches, EvalContext, GlobalRuntimeState, Target};
 use crate::ast::{
-    ASTFlags, BinaryExpr, Expr, FlowControl, OpAssignment, Stmt, SwitchCasesCollection,
+    ASTFlags, BinaryExpr, Expr, FlowControl, Ident, OpAssignment, Pattern, Stmt,
+    SwitchCasesCollection,
 };
+use crate::engine::OP_EQUALS;
 use crate::func::{get_builtin_op_assignment_fn, get_hasher};
 use crate::tokenizer::Token;
 use crate::types::dynamic::{AccessMode, Union};
-use crate::{Dynamic, Engine, RhaiResult, RhaiResultOf, Scope, VarDefInfo, ERR, INT};
+use crate::{
+    calc_fn_hash, Dynamic, Engine, ImmutableString, Position, RhaiError, RhaiResult, RhaiResultOf,
+    Scope, StaticVec, VarDefInfo, ERR, INT,
+};
 use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
+/// Source of values for a `for` loop.
+pub(crate) enum ForSource<I = Box<dyn Iterator<Item = RhaiResultOf<Dynamic>>>> {
+    /// Iterator registered for the type of the value.
+    Iter(I),
+    /// Generator returned by a generator function.
+    #[cfg(not(feature = "no_function"))]
+    Generator(crate::Generator),
+}
+
+/// Get the condition of a `while` or `do` loop, and the value of the condition for which the
+/// loop body runs again.
+#[must_use]
+pub(crate) fn loop_condition(stmt: &Stmt) -> (&Expr, bool) {
+    match stmt {
+        Stmt::While(x, ..) => (&x.expr, true),
+        Stmt::Do(x, options, ..) => (&x.expr, !options.intersects(ASTFlags::NEGATED)),
+        _ => unreachable!("loop statement expected but gets {:?}", stmt),
+    }
+}
+
 impl Engine {
+    /// Get the source of values for a `for` loop over a value.
+    pub(crate) fn make_for_source(
+        &self,
+        _global: &GlobalRuntimeState,
+        iter_obj: Dynamic,
+        pos: Position,
+    ) -> RhaiResultOf<ForSource> {
+        #[cfg(not(feature = "no_function"))]
+        if iter_obj.is::<crate::Generator>() {
+            return Ok(ForSource::Generator(iter_obj.cast::<crate::Generator>()));
+        }
+
+        let iter_type = iter_obj.type_id();
+
+        // lib should only contain scripts, so technically they cannot have iterators
+
+        // Search order:
+        // 1) Global namespace - functions registered via Engine::register_XXX
+        // 2) Global modules - packages
+        // 3) Imported modules - functions marked with global namespace
+        // 4) Global sub-modules - functions marked with global namespace
+        let iter_func = self
+            .global_modules
+            .iter()
+            .find_map(|m| m.get_iter(iter_type));
+
+        #[cfg(not(feature = "no_module"))]
+        let iter_func = iter_func
+            .or_else(|| _global.get_iter(iter_type))
+            .or_else(|| {
+                self.global_sub_modules
+                    .values()
+                    .find_map(|m| m.get_qualified_iter(iter_type))
+            });
+
+        let iter_func = iter_func.ok_or_else(|| ERR::ErrorFor(pos))?;
+
+        Ok(ForSource::Iter(iter_func(iter_obj)))
+    }
+    /// Get the next value for a `for` loop, if any.
+    pub(crate) fn next_for_value<I: Iterator<Item = RhaiResultOf<Dynamic>>>(
+        &self,
+        _global: &mut GlobalRuntimeState,
+        _caches: &mut Caches,
+        source: &mut ForSource<I>,
+        _pos: Position,
+    ) -> Option<RhaiResultOf<Dynamic>> {
+        match source {
+            ForSource::Iter(iter) => iter.next(),
+            #[cfg(not(feature = "no_function"))]
+            ForSource::Generator(generator) => self
+                .resume_generator(_global, _caches, generator, _pos)
+                .transpose(),
+        }
+    }
     /// If the value is a string, intern it.
     #[inline(always)]
     fn intern_string(&self, value: Dynamic) -> Dynamic {
@@ -21,7 +102,406 @@ impl Engine {
             _ => value,
         }
     }
+    /// Does a value match a type annotation?
+    ///
+    /// The type annotation matches either the actual type name of the value or its pretty-printed
+    /// name (e.g. the name used when a custom type is registered).
+    #[inline]
+    #[must_use]
+    pub(crate) fn matches_type_annotation(&self, value: &Dynamic, typ: &str) -> bool {
+        let name = value.type_name();
+        name == typ || self.map_type_name(name) == typ
+    }
+    /// Check that a value matches a type annotation.
+    #[inline]
+    pub(crate) fn check_type_annotation(
+        &self,
+        value: &Dynamic,
+        typ: &str,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        if self.matches_type_annotation(value, typ) {
+            return Ok(());
+        }
+
+        Err(ERR::ErrorMismatchDataType(
+            self.map_type_name(typ).to_string(),
+            self.map_type_name(value.type_name()).to_string(),
+            pos,
+        )
+        .into())
+    }
+
+    /// Get the value of a condition, which must be a boolean value.
+    #[inline]
+    pub(crate) fn condition_value(&self, value: Dynamic, pos: Position) -> RhaiResultOf<bool> {
+        value
+            .as_bool()
+            .map_err(|typ| self.make_type_mismatch_err::<bool>(typ, pos))
+    }
+    /// Evaluate the condition of an `if` statement or a loop, which must be a boolean value.
+    pub(crate) fn eval_condition(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: Option<&mut Dynamic>,
+        expr: &Expr,
+    ) -> RhaiResultOf<bool> {
+        let value = self.eval_expr(global, caches, scope, this_ptr, expr)?;
+        self.condition_value(value, expr.position())
+    }
+    /// Check that there is room in the scope for the variables of a `for` loop.
+    #[inline]
+    pub(crate) fn check_for_loop_vars(
+        &self,
+        scope: &Scope,
+        var: &Ident,
+        counter: Option<&Ident>,
+    ) -> RhaiResultOf<()> {
+        // Guard against too many variables
+        #[cfg(not(feature = "unchecked"))]
+        if scope.len() >= self.max_variables() - usize::from(counter.is_some()) {
+            return Err(ERR::ErrorTooManyVariables(var.pos).into());
+        }
+        #[cfg(feature = "unchecked")]
+        let _ = (scope, var, counter);
+
+        Ok(())
+    }
+    /// Add the variables of a `for` loop to the scope.
+    ///
+    /// Returns the indices of the counter variable (if any) and the loop variable in the scope.
+    pub(crate) fn push_for_loop_vars(
+        &self,
+        scope: &mut Scope,
+        var: &Ident,
+        counter: Option<&Ident>,
+    ) -> (Option<usize>, usize) {
+        let counter_index = counter.map(|counter| {
+            scope.push(counter.name.clone(), 0 as INT);
+            scope.len() - 1
+        });
+
+        scope.push(var.name.clone(), ());
+
+        (counter_index, scope.len() - 1)
+    }
+    /// Set the variables of a `for` loop for an iteration, given their indices in the scope.
+    pub(crate) fn set_for_loop_vars(
+        &self,
+        scope: &mut Scope,
+        counter: Option<(&Ident, usize)>,
+        index: usize,
+        iteration: usize,
+        value: Dynamic,
+    ) -> RhaiResultOf<()> {
+        // Increment counter
+        if let Some((_counter, counter_index)) = counter {
+            // As the variable increments from 0, this should always work
+            // since any overflow will first be caught below.
+            let index_value = iteration as INT;
+
+            #[cfg(not(feature = "unchecked"))]
+            #[allow(clippy::absurd_extreme_comparisons)]
+            if index_value > crate::MAX_USIZE_INT {
+                return Err(ERR::ErrorArithmetic(
+                    format!("for-loop counter overflow: {iteration}"),
+                    _counter.pos,
+                )
+                .into());
+            }
+
+            *scope.get_mut_by_index(counter_index).write_lock().unwrap() =
+                Dynamic::from_int(index_value);
+        }
+
+        // Set loop value
+        *scope.get_mut_by_index(index).write_lock().unwrap() = value;
+
+        Ok(())
+    }
+    /// Get the next value of a `for` loop and set the loop variables for the iteration.
+    ///
+    /// Returns `false` when there are no more values.
+    ///
+    /// If the loop body is empty (its [position][Position] is passed in `empty_body`), the
+    /// iteration counts as an operation and the loop variables are not set.
+    pub(crate) fn next_for_iteration<I: Iterator<Item = RhaiResultOf<Dynamic>>>(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        source: &mut ForSource<I>,
+        counter: Option<(&Ident, usize)>,
+        index: usize,
+        iteration: usize,
+        expr_pos: Position,
+        empty_body: Option<Position>,
+    ) -> RhaiResultOf<bool> {
+        let value = match self.next_for_value(global, caches, source, expr_pos) {
+            Some(value) => value.map_err(|err| err.fill_position(expr_pos))?,
+            None => return Ok(false),
+        };
+
+        match empty_body {
+            Some(pos) => self.track_operation(global, pos)?,
+            None => self.set_for_loop_vars(scope, counter, index, iteration, value.flatten())?,
+        }
+
+        Ok(true)
+    }
+    /// Add the variable of a `catch` block (if any), holding the error caught, to the scope.
+    pub(crate) fn push_catch_var(
+        &self,
+        global: &GlobalRuntimeState,
+        scope: &mut Scope,
+        err: &mut ERR,
+        catch_var: Option<(ImmutableString, Position)>,
+    ) -> RhaiResultOf<()> {
+        let (name, _pos) = match catch_var {
+            Some(var) => var,
+            None => return Ok(()),
+        };
+
+        // Guard against too many variables
+        #[cfg(not(feature = "unchecked"))]
+        if scope.len() >= self.max_variables() {
+            return Err(ERR::ErrorTooManyVariables(_pos).into());
+        }
+
+        scope.push(name, self.make_catch_value(global, err));
+        Ok(())
+    }
+    /// Get the error raised by a `catch` block.
+    ///
+    /// An empty `throw` re-throws the error caught.
+    #[must_use]
+    pub(crate) fn make_catch_block_error(
+        &self,
+        mut caught: RhaiError,
+        err: RhaiError,
+    ) -> RhaiError {
+        match *err {
+            // Re-throw exception
+            ERR::ErrorRuntime(ref v, pos) if v.is_unit() => {
+                caught.set_position(pos);
+                caught
+            }
+            _ => err,
+        }
+    }
+
+    /// Destructure a value according to a pattern, collecting the variables and their values.
+    ///
+    /// Arrays must match the number of items in the pattern, unless there is a rest pattern.
+    /// Missing object map properties are `()`, unless
+    /// [`fail_on_invalid_map_property`][Engine::fail_on_invalid_map_property] is set.
+    fn destructure<'p>(
+        &self,
+        pattern: &'p Pattern,
+        value: Dynamic,
+        vars: &mut StaticVec<(&'p Ident, Dynamic)>,
+    ) -> RhaiResultOf<()> {
+        match pattern {
+            Pattern::Ignore(..) => (),
+            Pattern::Var(var) => vars.push((var, value.flatten())),
+            Pattern::Value(..) | Pattern::Type(..) => {
+                unreachable!("refutable pattern in variable definition")
+            }
+            #[cfg(not(feature = "no_index"))]
+            Pattern::Array(x, pos) => {
+                let (items, rest) = &**x;
+
+                let array = value
+                    .flatten()
+                    .into_array()
+                    .map_err(|typ| self.make_type_mismatch_err::<crate::Array>(typ, *pos))?;
+
+                // Without a rest pattern, the array must have exactly as many items
+                if array.len() < items.len() || (rest.is_none() && array.len() > items.len()) {
+                    let expected = match rest {
+                        Some(..) => format!("array of at least {} item(s)", items.len()),
+                        None => format!("array of {} item(s)", items.len()),
+                    };
+                    let actual = format!("array of {} item(s)", array.len());
+                    return Err(ERR::ErrorMismatchDataType(expected, actual, *pos).into());
+                }
+
+                let mut array = array.into_iter();
+
+                for item in items {
+                    self.destructure(item, array.next().unwrap(), vars)?;
+                }
 
+                match rest {
+                    Some(Pattern::Ignore(..)) | None => (),
+                    Some(rest) => {
+                        self.destructure(rest, array.collect::<crate::Array>().into(), vars)?
+                    }
+                }
+            }
+            #[cfg(not(feature = "no_object"))]
+            Pattern::Map(x, pos) => {
+                let value = value.flatten();
+
+                if !value.is_map() {
+                    return Err(self.make_type_mismatch_err::<crate::Map>(value.type_name(), *pos));
+                }
+
+                let map = value.cast::<crate::Map>();
+
+                for (key, item) in &x.0 {
+                    let value = match map.get(key.as_str()) {
+                        Some(value) => value.clone(),
+                        None if self.fail_on_invalid_map_property() => {
+                            return Err(
+                                ERR::ErrorPropertyNotFound(key.name.to_string(), key.pos).into()
+                            )
+                        }
+                        None => Dynamic::UNIT,
+                    };
+                    self.destructure(item, value, vars)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+    /// Match a value against a `switch` case pattern, collecting the variables bound and their values.
+    ///
+    /// Array patterns without a rest match only arrays of the exact length.
+    /// Object map patterns without a rest match only maps with exactly the listed properties.
+    fn match_pattern<'p>(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        pattern: &'p Pattern,
+        value: &Dynamic,
+        vars: &mut StaticVec<(&'p Ident, Dynamic)>,
+    ) -> RhaiResultOf<bool> {
+        match pattern {
+            Pattern::Ignore(..) => Ok(true),
+            Pattern::Var(var) => {
+                vars.push((var, value.clone().flatten()));
+                Ok(true)
+            }
+            Pattern::Value(x, pos) => {
+                if !value.is_hashable() {
+                    return Ok(false);
+                }
+
+                let hasher1 = &mut get_hasher();
+                let hasher2 = &mut get_hasher();
+                value.hash(hasher1);
+                x.hash(hasher2);
+
+                if hasher1.finish() != hasher2.finish() {
+                    return Ok(false);
+                }
+
+                // Hashes may collide, so also compare the values
+                let args = &mut [&mut value.clone(), &mut (**x).clone()];
+                let hash = calc_fn_hash(None, OP_EQUALS, 2);
+                let op_token = Some(&Token::EqualsTo);
+
+                self.exec_native_fn_call(
+                    global, caches, OP_EQUALS, op_token, hash, None, args, false, false, *pos,
+                )
+                .map(|(v, ..)| v.as_bool().unwrap_or(false))
+            }
+            Pattern::Type(typ, ..) => Ok(self.matches_type_annotation(value, typ)),
+            #[cfg(not(feature = "no_index"))]
+            Pattern::Array(x, ..) => {
+                let (items, rest) = &**x;
+
+                let array = match value.read_lock::<crate::Array>() {
+                    Some(array) => array,
+                    None => return Ok(false),
+                };
+
+                let len_ok = match rest {
+                    Some(..) => array.len() >= items.len(),
+                    None => array.len() == items.len(),
+                };
+
+                if !len_ok {
+                    return Ok(false);
+                }
+
+                for (item, value) in items.iter().zip(array.iter()) {
+                    if !self.match_pattern(global, caches, item, value, vars)? {
+                        return Ok(false);
+                    }
+                }
+
+                match rest {
+                    Some(Pattern::Ignore(..)) | None => Ok(true),
+                    Some(rest) => {
+                        let rest_array: crate::Array = array[items.len()..].to_vec();
+                        self.match_pattern(global, caches, rest, &rest_array.into(), vars)
+                    }
+                }
+            }
+            #[cfg(not(feature = "no_object"))]
+            Pattern::Map(x, ..) => {
+                let (items, has_rest) = &**x;
+
+                let map = match value.read_lock::<crate::Map>() {
+                    Some(map) => map,
+                    None => return Ok(false),
+                };
+
+                if !has_rest && map.len() != items.len() {
+                    return Ok(false);
+                }
+
+                for (key, item) in items {
+                    match map.get(key.as_str()) {
+                        Some(value) if self.match_pattern(global, caches, item, value, vars)? => (),
+                        _ => return Ok(false),
+                    }
+                }
+
+                Ok(true)
+            }
+        }
+    }
+    /// Check whether a variable can be defined, running the variable definition filter if any.
+    fn check_var_definition(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: Option<&mut Dynamic>,
+        var_name: &str,
+        access: AccessMode,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        if !self.allow_shadowing() && scope.contains(var_name) {
+            return Err(ERR::ErrorVariableExists(var_name.to_string(), pos).into());
+        }
+
+        if let Some(ref filter) = self.def_var_filter {
+            let will_shadow = scope.contains(var_name);
+            let is_const = access == AccessMode::ReadOnly;
+            let info = VarDefInfo::new(var_name, is_const, global.scope_level, will_shadow);
+            let orig_scope_len = scope.len();
+            let context = EvalContext::new(self, global, caches, scope, this_ptr);
+            let filter_result = filter(true, info, context);
+
+            if orig_scope_len != scope.len() {
+                // The scope is changed, always search from now on
+                global.always_search_scope = true;
+            }
+
+            if !filter_result? {
+                return Err(ERR::ErrorForbiddenVariable(var_name.to_string(), pos).into());
+            }
+        }
+
+        Ok(())
+    }
     /// Evaluate a statements block.
     pub(crate) fn eval_stmt_block(
         &self,
@@ -77,33 +557,52 @@ impl Engine {
 
             #[cfg(not(feature = "no_module"))]
             if matches!(stmt, Stmt::Import(..)) {
-                // Get the extra modules - see if any functions are marked global.
-                // Without global functions, the extra modules never affect function resolution.
-                if global
-                    .scan_imports_raw()
-                    .skip(orig_imports_len)
-                    .any(|(.., m)| m.contains_indexed_global_functions())
-                {
-                    // Different scenarios where the cache must be cleared - notice that this is
-                    // expensive as all function resolutions must start again
-                    if caches.fn_resolution_caches_len() > orig_fn_resolution_caches_len {
-                        // When new module is imported with global functions and there is already
-                        // a new cache, just clear it
-                        caches.fn_resolution_cache_mut().clear();
-                    } else if restore_orig_state {
-                        // When new module is imported with global functions, push a new cache
-                        caches.push_fn_resolution_cache();
-                    } else {
-                        // When the block is to be evaluated in-place, just clear the current cache
-                        caches.fn_resolution_cache_mut().clear();
-                    }
-                }
+                self.update_caches_on_import(
+                    global,
+                    caches,
+                    orig_imports_len,
+                    orig_fn_resolution_caches_len,
+                    restore_orig_state,
+                );
             }
 
             Ok(result)
         })
     }
 
+    /// Update the function resolution caches after an `import` statement inside a statements block.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) fn update_caches_on_import(
+        &self,
+        global: &GlobalRuntimeState,
+        caches: &mut Caches,
+        orig_imports_len: usize,
+        orig_fn_resolution_caches_len: usize,
+        restore_orig_state: bool,
+    ) {
+        // Get the extra modules - see if any functions are marked global.
+        // Without global functions, the extra modules never affect function resolution.
+        if global
+            .scan_imports_raw()
+            .skip(orig_imports_len)
+            .any(|(.., m)| m.contains_indexed_global_functions())
+        {
+            // Different scenarios where the cache must be cleared - notice that this is
+            // expensive as all function resolutions must start again
+            if caches.fn_resolution_caches_len() > orig_fn_resolution_caches_len {
+                // When new module is imported with global functions and there is already
+                // a new cache, just clear it
+                caches.fn_resolution_cache_mut().clear();
+            } else if restore_orig_state {
+                // When new module is imported with global functions, push a new cache
+                caches.push_fn_resolution_cache();
+            } else {
+                // When the block is to be evaluated in-place, just clear the current cache
+                caches.fn_resolution_cache_mut().clear();
+            }
+        }
+    }
+
     /// Evaluate an op-assignment statement.
     pub(crate) fn eval_op_assignment(
         &self,
@@ -219,7 +718,7 @@ impl Engine {
                 let args = &mut [&mut *lock_guard, &mut new_val];
 
                 match self.exec_native_fn_call(
-                    global, caches, op_x_str, opx, hash_x, args, true, false, pos,
+                    global, caches, op_x_str, opx, hash_x, None, args, true, false, pos,
                 ) {
                     Ok(_) => (),
                     Err(err) if matches!(*err, ERR::ErrorFunctionNotFound(ref f, ..) if f.starts_with(op_x_str)) =>
@@ -229,7 +728,7 @@ impl Engine {
 
                         *args[0] = self
                             .exec_native_fn_call(
-                                global, caches, op_str, op, hash, args, true, false, pos,
+                                global, caches, op_str, op, hash, None, args, true, false, pos,
                             )?
                             .0;
                     }
@@ -253,6 +752,298 @@ impl Engine {
         target.propagate_changed_value(pos)
     }
 
+    /// Get the type annotation of a variable in the [`Scope`], if any.
+    #[must_use]
+    fn search_var_type(
+        &self,
+        global: &GlobalRuntimeState,
+        scope: &Scope,
+        expr: &Expr,
+    ) -> Option<ImmutableString> {
+        let index = match expr {
+            Expr::Variable(v, i, ..) if v.1.is_empty() => match i {
+                _ if global.always_search_scope => 0,
+                Some(i) => i.get() as usize,
+                None => v.0.map_or(0, NonZeroUsize::get),
+            },
+            _ => return None,
+        };
+
+        let index = if index > 0 {
+            scope.len() - index
+        } else {
+            scope.search(expr.get_variable_name(true)?)?
+        };
+
+        scope.get_type_by_index(index).cloned()
+    }
+    /// Assign a (flattened) value to a variable, with or without an operator.
+    ///
+    /// The new value is checked against the type annotation of the variable, if any.
+    pub(crate) fn eval_var_assignment(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: Option<&mut Dynamic>,
+        op_info: &OpAssignment,
+        lhs: &Expr,
+        rhs_val: Dynamic,
+    ) -> RhaiResultOf<()> {
+        self.track_operation(global, lhs.position())?;
+
+        let typ = self.search_var_type(global, scope, lhs);
+
+        let mut target = self.search_namespace(global, caches, scope, this_ptr, lhs)?;
+
+        let is_temp_result = !target.is_ref();
+
+        #[cfg(not(feature = "no_closure"))]
+        // Also handle case where target is a `Dynamic` shared value
+        // (returned by a variable resolver, for example)
+        let is_temp_result = is_temp_result && !target.is_shared();
+
+        // Cannot assign to temp result from expression
+        if is_temp_result {
+            let name = lhs.get_variable_name(false).unwrap_or_default();
+            return Err(ERR::ErrorAssignmentToConstant(name.to_string(), lhs.position()).into());
+        }
+
+        match typ {
+            // Check the new value before a straight assignment
+            Some(typ) if !op_info.is_op_assignment() => {
+                self.check_type_annotation(&rhs_val, &typ, lhs.position())?;
+                self.eval_op_assignment(global, caches, op_info, lhs, &mut target, rhs_val)
+            }
+            // Check the result after an op-assignment
+            Some(typ) => {
+                self.eval_op_assignment(global, caches, op_info, lhs, &mut target, rhs_val)?;
+                let value = target.as_ref().read_lock::<Dynamic>().unwrap();
+                self.check_type_annotation(&value, &typ, lhs.position())
+            }
+            None => self.eval_op_assignment(global, caches, op_info, lhs, &mut target, rhs_val),
+        }
+    }
+
+    /// Check the variables about to be defined by a `let` or `const` statement.
+    pub(crate) fn check_var_stmt(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        mut this_ptr: Option<&mut Dynamic>,
+        stmt: &Stmt,
+    ) -> RhaiResultOf<()> {
+        let (var_name, index, pattern, options, pos) = match stmt {
+            Stmt::Var(x, options, pos) => (&x.0, &x.2, &x.4, options, pos),
+            _ => unreachable!("`let` or `const` statement expected but gets {:?}", stmt),
+        };
+        #[cfg(feature = "unchecked")]
+        let _ = index;
+
+        let access = if options.intersects(ASTFlags::CONSTANT) {
+            AccessMode::ReadOnly
+        } else {
+            AccessMode::ReadWrite
+        };
+
+        // Check variable definitions
+        match pattern {
+            Some(pattern) => {
+                let vars = pattern.variables();
+
+                for var in &vars {
+                    self.check_var_definition(
+                        global,
+                        caches,
+                        scope,
+                        this_ptr.as_deref_mut(),
+                        var.as_str(),
+                        access,
+                        *pos,
+                    )?;
+                }
+
+                // Guard against too many variables
+                #[cfg(not(feature = "unchecked"))]
+                if scope.len() + vars.len() > self.max_variables() {
+                    return Err(ERR::ErrorTooManyVariables(*pos).into());
+                }
+            }
+            None => {
+                self.check_var_definition(
+                    global,
+                    caches,
+                    scope,
+                    this_ptr,
+                    var_name.as_str(),
+                    access,
+                    *pos,
+                )?;
+
+                // Guard against too many variables
+                #[cfg(not(feature = "unchecked"))]
+                if index.is_none() && scope.len() >= self.max_variables() {
+                    return Err(ERR::ErrorTooManyVariables(*pos).into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+    /// Define the variables of a `let` or `const` statement with its (flattened) value.
+    pub(crate) fn define_var_stmt(
+        &self,
+        global: &mut GlobalRuntimeState,
+        scope: &mut Scope,
+        stmt: &Stmt,
+        value: Dynamic,
+        rewind_scope: bool,
+    ) -> RhaiResultOf<()> {
+        let (var_name, index, typ, pattern, options) = match stmt {
+            Stmt::Var(x, options, ..) => (&x.0, &x.2, &x.3, &x.4, options),
+            _ => unreachable!("`let` or `const` statement expected but gets {:?}", stmt),
+        };
+
+        let access = if options.intersects(ASTFlags::CONSTANT) {
+            AccessMode::ReadOnly
+        } else {
+            AccessMode::ReadWrite
+        };
+        let export = options.intersects(ASTFlags::EXPORTED);
+
+        #[cfg(any(feature = "no_function", feature = "no_module"))]
+        let _ = global;
+
+        // Check type annotation
+        if let Some(typ) = typ {
+            self.check_type_annotation(&value, typ, var_name.pos)?;
+        }
+
+        // Destructure the value, if necessary
+        let mut vars = StaticVec::new_const();
+
+        match pattern {
+            Some(pattern) => self.destructure(pattern, value, &mut vars)?,
+            None => vars.push((var_name, value)),
+        }
+
+        for (var_name, value) in vars {
+            let mut value = self.intern_string(value);
+
+            let _alias = if !rewind_scope {
+                // Put global constants into global module
+                #[cfg(not(feature = "no_function"))]
+                #[cfg(not(feature = "no_module"))]
+                if global.scope_level == 0
+                    && access == AccessMode::ReadOnly
+                    && global.lib.iter().any(|m| !m.is_empty())
+                {
+                    crate::func::locked_write(global.constants.get_or_insert_with(|| {
+                        crate::Shared::new(crate::Locked::new(std::collections::BTreeMap::new()))
+                    }))
+                    .insert(var_name.name.clone(), value.clone());
+                }
+
+                export.then_some(var_name)
+            } else if !export {
+                None
+            } else {
+                unreachable!("exported variable not on global level");
+            };
+
+            let index = match index {
+                Some(index) => {
+                    value.set_access_mode(access);
+                    let index = scope.len() - index.get();
+                    *scope.get_mut_by_index(index) = value;
+                    index
+                }
+                _ => {
+                    scope.push_entry(var_name.name.clone(), access, value);
+                    scope.len() - 1
+                }
+            };
+
+            // Keep the type annotation with the variable to check assignments
+            let typ = if pattern.is_none() { typ.clone() } else { None };
+            scope.set_type_by_index(index, typ);
+
+            #[cfg(not(feature = "no_module"))]
+            if let Some(alias) = _alias {
+                scope.add_alias_by_index(scope.len() - 1, alias.as_str().into());
+            }
+        }
+
+        Ok(())
+    }
+    /// Assign the value of the right-hand side of an assignment statement, already evaluated, to
+    /// its left-hand side.
+    pub(crate) fn eval_assignment(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        mut this_ptr: Option<&mut Dynamic>,
+        stmt: &Stmt,
+        rhs_val: Dynamic,
+    ) -> RhaiResultOf<()> {
+        let (op_info, BinaryExpr { lhs, .. }) = match stmt {
+            Stmt::Assignment(x) => &**x,
+            _ => unreachable!("Stmt::Assignment expected but gets {:?}", stmt),
+        };
+
+        #[cfg(feature = "debugging")]
+        let watched = match lhs {
+            Expr::ThisPtr(..) | Expr::Variable(..) => {
+                self.watch_values(global, caches, scope, this_ptr.as_deref_mut(), lhs)
+            }
+            _ => None,
+        };
+
+        // Must be either `this op= val`, `var op= val`, `var[index] op= val` or `var.prop op= val`.
+        // The return value of any op-assignment (should be `()`) is thrown away and not used.
+        match lhs {
+            // this op= rhs
+            #[cfg(not(feature = "no_function"))]
+            Expr::ThisPtr(..) => {
+                self.track_operation(global, lhs.position())?;
+
+                let target = this_ptr
+                    .as_deref_mut()
+                    .ok_or_else(|| ERR::ErrorUnboundThis(lhs.position()))?;
+
+                self.eval_op_assignment(global, caches, op_info, lhs, &mut target.into(), rhs_val)
+            }
+            // name op= rhs
+            Expr::Variable(..) => {
+                let this_ptr = this_ptr.as_deref_mut();
+                self.eval_var_assignment(global, caches, scope, this_ptr, op_info, lhs, rhs_val)
+            }
+            // idx_lhs[idx_expr] op= rhs
+            #[cfg(not(feature = "no_index"))]
+            Expr::Index(..) => {
+                let this_ptr = this_ptr.as_deref_mut();
+                let new_val = Some((self.intern_string(rhs_val), op_info));
+                self.eval_dot_index_chain(global, caches, scope, this_ptr, lhs, new_val)
+                    .map(|_| ())
+            }
+            // dot_lhs.dot_rhs op= rhs
+            #[cfg(not(feature = "no_object"))]
+            Expr::Dot(..) => {
+                let this_ptr = this_ptr.as_deref_mut();
+                let new_val = Some((self.intern_string(rhs_val), op_info));
+                self.eval_dot_index_chain(global, caches, scope, this_ptr, lhs, new_val)
+                    .map(|_| ())
+            }
+            _ => unreachable!("cannot assign to expression: {:?}", lhs),
+        }?;
+
+        #[cfg(feature = "debugging")]
+        self.check_watch_points(global, caches, scope, this_ptr, stmt, watched)?;
+
+        Ok(())
+    }
     /// Evaluate a statement.
     pub(crate) fn eval_stmt(
         &self,
@@ -296,184 +1087,33 @@ impl Engine {
 
             // Assignment
             Stmt::Assignment(x, ..) => {
-                let (op_info, BinaryExpr { lhs, rhs }) = &**x;
+                let BinaryExpr { lhs, rhs } = &x.1;
 
                 if let Expr::ThisPtr(..) = lhs {
                     if this_ptr.is_none() {
                         return Err(ERR::ErrorUnboundThis(lhs.position()).into());
                     }
+                }
 
-                    #[cfg(not(feature = "no_function"))]
-                    {
-                        let rhs_val = self
-                            .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), rhs)?
-                            .flatten();
-
-                        self.track_operation(global, lhs.position())?;
-
-                        let target = &mut this_ptr.unwrap().into();
-
-                        self.eval_op_assignment(global, caches, op_info, lhs, target, rhs_val)?;
-                    }
-                    #[cfg(feature = "no_function")]
-                    unreachable!();
-                } else if let Expr::Variable(x, ..) = lhs {
-                    let rhs_val = self
-                        .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), rhs)?
-                        .flatten();
-
-                    self.track_operation(global, lhs.position())?;
-
-                    let mut target = self.search_namespace(global, caches, scope, this_ptr, lhs)?;
-
-                    let is_temp_result = !target.is_ref();
+                let rhs_val = self
+                    .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), rhs)?
+                    .flatten();
 
-                    #[cfg(not(feature = "no_closure"))]
-                    // Also handle case where target is a `Dynamic` shared value
-                    // (returned by a variable resolver, for example)
-                    let is_temp_result = is_temp_result && !target.is_shared();
-
-                    // Cannot assign to temp result from expression
-                    if is_temp_result {
-                        return Err(ERR::ErrorAssignmentToConstant(
-                            x.3.to_string(),
-                            lhs.position(),
-                        )
-                        .into());
-                    }
-
-                    self.eval_op_assignment(global, caches, op_info, lhs, &mut target, rhs_val)?;
-                } else {
-                    #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
-                    {
-                        let rhs_val = self
-                            .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), rhs)?
-                            .flatten();
-                        let _new_val = Some((self.intern_string(rhs_val), op_info));
-
-                        // Must be either `var[index] op= val` or `var.prop op= val`.
-                        // The return value of any op-assignment (should be `()`) is thrown away and not used.
-                        let _ = match lhs {
-                            // this op= rhs (handled above)
-                            Expr::ThisPtr(..) => {
-                                unreachable!("Expr::ThisPtr case is already handled")
-                            }
-                            // name op= rhs (handled above)
-                            Expr::Variable(..) => {
-                                unreachable!("Expr::Variable case is already handled")
-                            }
-                            // idx_lhs[idx_expr] op= rhs
-                            #[cfg(not(feature = "no_index"))]
-                            Expr::Index(..) => self.eval_dot_index_chain(
-                                global, caches, scope, this_ptr, lhs, _new_val,
-                            ),
-                            // dot_lhs.dot_rhs op= rhs
-                            #[cfg(not(feature = "no_object"))]
-                            Expr::Dot(..) => self.eval_dot_index_chain(
-                                global, caches, scope, this_ptr, lhs, _new_val,
-                            ),
-                            _ => unreachable!("cannot assign to expression: {:?}", lhs),
-                        }?;
-                    }
-                }
+                self.eval_assignment(global, caches, scope, this_ptr, stmt, rhs_val)?;
 
                 Ok(Dynamic::UNIT)
             }
 
             // Variable definition
-            Stmt::Var(x, options, pos) => {
-                if !self.allow_shadowing() && scope.contains(x.0.as_str()) {
-                    return Err(ERR::ErrorVariableExists(x.0.as_str().to_string(), *pos).into());
-                }
-
-                // Let/const statement
-                let (var_name, expr, index) = &**x;
-
-                let access = if options.intersects(ASTFlags::CONSTANT) {
-                    AccessMode::ReadOnly
-                } else {
-                    AccessMode::ReadWrite
-                };
-                let export = options.intersects(ASTFlags::EXPORTED);
-
-                // Check variable definition filter
-                if let Some(ref filter) = self.def_var_filter {
-                    let will_shadow = scope.contains(var_name.as_str());
-                    let is_const = access == AccessMode::ReadOnly;
-                    let info = VarDefInfo::new(
-                        var_name.as_str(),
-                        is_const,
-                        global.scope_level,
-                        will_shadow,
-                    );
-                    let orig_scope_len = scope.len();
-                    let context =
-                        EvalContext::new(self, global, caches, scope, this_ptr.as_deref_mut());
-                    let filter_result = filter(true, info, context);
-
-                    if orig_scope_len != scope.len() {
-                        // The scope is changed, always search from now on
-                        global.always_search_scope = true;
-                    }
-
-                    if !filter_result? {
-                        return Err(ERR::ErrorForbiddenVariable(
-                            var_name.as_str().to_string(),
-                            *pos,
-                        )
-                        .into());
-                    }
-                }
-
-                // Guard against too many variables
-                #[cfg(not(feature = "unchecked"))]
-                if index.is_none() && scope.len() >= self.max_variables() {
-                    return Err(ERR::ErrorTooManyVariables(*pos).into());
-                }
+            Stmt::Var(x, ..) => {
+                self.check_var_stmt(global, caches, scope, this_ptr.as_deref_mut(), stmt)?;
 
                 // Evaluate initial value
                 let value = self
-                    .eval_expr(global, caches, scope, this_ptr, expr)?
+                    .eval_expr(global, caches, scope, this_ptr, &x.1)?
                     .flatten();
-                let mut value = self.intern_string(value);
-
-                let _alias = if !rewind_scope {
-                    // Put global constants into global module
-                    #[cfg(not(feature = "no_function"))]
-                    #[cfg(not(feature = "no_module"))]
-                    if global.scope_level == 0
-                        && access == AccessMode::ReadOnly
-                        && global.lib.iter().any(|m| !m.is_empty())
-                    {
-                        crate::func::locked_write(global.constants.get_or_insert_with(|| {
-                            crate::Shared::new(
-                                crate::Locked::new(std::collections::BTreeMap::new()),
-                            )
-                        }))
-                        .insert(var_name.name.clone(), value.clone());
-                    }
 
-                    export.then_some(var_name)
-                } else if !export {
-                    None
-                } else {
-                    unreachable!("exported variable not on global level");
-                };
-
-                match index {
-                    Some(index) => {
-                        value.set_access_mode(access);
-                        *scope.get_mut_by_index(scope.len() - index.get()) = value;
-                    }
-                    _ => {
-                        scope.push_entry(var_name.name.clone(), access, value);
-                    }
-                }
-
-                #[cfg(not(feature = "no_module"))]
-                if let Some(alias) = _alias {
-                    scope.add_alias_by_index(scope.len() - 1, alias.as_str().into());
-                }
+                self.define_var_stmt(global, scope, stmt, value, rewind_scope)?;
 
                 Ok(Dynamic::UNIT)
             }
@@ -482,10 +1122,8 @@ impl Engine {
             Stmt::If(x, ..) => {
                 let FlowControl { expr, body, branch } = &**x;
 
-                let guard_val = self
-                    .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), expr)?
-                    .as_bool()
-                    .map_err(|typ| self.make_type_mismatch_err::<bool>(typ, expr.position()))?;
+                let guard_val =
+                    self.eval_condition(global, caches, scope, this_ptr.as_deref_mut(), expr)?;
 
                 if guard_val && !body.is_empty() {
                     self.eval_stmt_block(global, caches, scope, this_ptr, body.statements(), true)
@@ -505,13 +1143,15 @@ impl Engine {
                         cases,
                         def_case,
                         ranges,
+                        patterns,
                     },
                 ) = &**x;
 
-                let mut result = None;
-
                 let value = self.eval_expr(global, caches, scope, this_ptr.as_deref_mut(), expr)?;
 
+                // Indices of the literal and range cases matching the value, in order
+                let mut literals = StaticVec::<usize>::new_const();
+
                 if value.is_hashable() {
                     let hasher = &mut get_hasher();
                     value.hash(hasher);
@@ -520,45 +1160,92 @@ impl Engine {
                     // First check hashes
                     if let Some(case_blocks_list) = cases.get(&hash) {
                         debug_assert!(!case_blocks_list.is_empty());
-
-                        for &index in case_blocks_list {
-                            let block = &expressions[index];
-
-                            let cond_result = match block.lhs {
-                                Expr::BoolConstant(b, ..) => b,
-                                ref c => self
-                                    .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), c)?
-                                    .as_bool()
-                                    .map_err(|typ| {
-                                        self.make_type_mismatch_err::<bool>(typ, c.position())
-                                    })?,
-                            };
-
-                            if cond_result {
-                                result = Some(&block.rhs);
-                                break;
-                            }
-                        }
+                        literals.extend(case_blocks_list.iter().copied());
                     } else if !ranges.is_empty() {
                         // Then check integer ranges
-                        for r in ranges.iter().filter(|r| r.contains(&value)) {
-                            let BinaryExpr { lhs, rhs } = &expressions[r.index()];
-
-                            let cond_result = match lhs {
-                                Expr::BoolConstant(b, ..) => *b,
-                                c => self
-                                    .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), c)?
-                                    .as_bool()
-                                    .map_err(|typ| {
-                                        self.make_type_mismatch_err::<bool>(typ, c.position())
-                                    })?,
-                            };
-
-                            if cond_result {
-                                result = Some(rhs);
-                                break;
+                        literals.extend(
+                            ranges
+                                .iter()
+                                .filter(|r| r.contains(&value))
+                                .map(|r| r.index()),
+                        );
+                    }
+                }
+
+                let value = value.flatten();
+                let mut literals = literals.into_iter().peekable();
+                let mut patterns = patterns.iter().peekable();
+                let mut vars = StaticVec::new_const();
+                let mut result = None;
+
+                // Try literal cases and structural patterns in source order
+                loop {
+                    let is_literal = match (literals.peek(), patterns.peek()) {
+                        (Some(&index), Some(&&(_, p_index))) => index < p_index,
+                        (Some(..), None) => true,
+                        (None, Some(..)) => false,
+                        (None, None) => break,
+                    };
+
+                    if is_literal {
+                        let BinaryExpr { lhs, rhs } = &expressions[literals.next().unwrap()];
+
+                        let cond_result = match lhs {
+                            Expr::BoolConstant(b, ..) => *b,
+                            c => {
+                                let this_ptr = this_ptr.as_deref_mut();
+                                self.eval_condition(global, caches, scope, this_ptr, c)?
                             }
+                        };
+
+                        if cond_result {
+                            result = Some(rhs);
+                            break;
                         }
+
+                        continue;
+                    }
+
+                    let (pattern, index) = patterns.next().unwrap();
+
+                    vars.clear();
+
+                    if !self.match_pattern(global, caches, pattern, &value, &mut vars)? {
+                        continue;
+                    }
+
+                    let BinaryExpr { lhs, rhs } = &expressions[*index];
+
+                    // Bound variables are pushed in sorted order, mirroring the parser
+                    vars.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+
+                    let orig_scope_len = scope.len();
+
+                    for (var, value) in vars.drain(..) {
+                        scope.push_entry(var.name.clone(), AccessMode::ReadWrite, value);
+                    }
+
+                    let cond_result = match lhs {
+                        Expr::BoolConstant(b, ..) => Ok(*b),
+                        c => {
+                            let this_ptr = this_ptr.as_deref_mut();
+                            self.eval_condition(global, caches, scope, this_ptr, c)
+                        }
+                    };
+
+                    let result = match cond_result {
+                        Ok(true) => {
+                            let this_ptr = this_ptr.as_deref_mut();
+                            Some(self.eval_expr(global, caches, scope, this_ptr, rhs))
+                        }
+                        Ok(false) => None,
+                        Err(err) => Some(Err(err)),
+                    };
+
+                    scope.rewind(orig_scope_len);
+
+                    if let Some(result) = result {
+                        return result;
                     }
                 }
 
@@ -601,10 +1288,8 @@ impl Engine {
                 let FlowControl { expr, body, .. } = &**x;
 
                 loop {
-                    let condition = self
-                        .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), expr)?
-                        .as_bool()
-                        .map_err(|typ| self.make_type_mismatch_err::<bool>(typ, expr.position()))?;
+                    let condition =
+                        self.eval_condition(global, caches, scope, this_ptr.as_deref_mut(), expr)?;
 
                     if !condition {
                         break Ok(Dynamic::UNIT);
@@ -629,9 +1314,9 @@ impl Engine {
             }
 
             // Do loop
-            Stmt::Do(x, options, ..) => {
-                let FlowControl { expr, body, .. } = &**x;
-                let is_while = !options.intersects(ASTFlags::NEGATED);
+            Stmt::Do(x, ..) => {
+                let body = &x.body;
+                let (expr, is_while) = loop_condition(stmt);
 
                 loop {
                     if !body.is_empty() {
@@ -650,10 +1335,8 @@ impl Engine {
                         }
                     }
 
-                    let condition = self
-                        .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), expr)?
-                        .as_bool()
-                        .map_err(|typ| self.make_type_mismatch_err::<bool>(typ, expr.position()))?;
+                    let condition =
+                        self.eval_condition(global, caches, scope, this_ptr.as_deref_mut(), expr)?;
 
                     if condition ^ is_while {
                         break Ok(Dynamic::UNIT);
@@ -665,105 +1348,57 @@ impl Engine {
             Stmt::For(x, ..) => {
                 let (var_name, counter, FlowControl { expr, body, .. }) = &**x;
 
-                // Guard against too many variables
-                #[cfg(not(feature = "unchecked"))]
-                if scope.len() >= self.max_variables() - usize::from(counter.is_some()) {
-                    return Err(ERR::ErrorTooManyVariables(var_name.pos).into());
-                }
+                self.check_for_loop_vars(scope, var_name, counter.as_ref())?;
 
                 let iter_obj = self
                     .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), expr)?
                     .flatten();
 
-                let iter_type = iter_obj.type_id();
-
-                // lib should only contain scripts, so technically they cannot have iterators
-
-                // Search order:
-                // 1) Global namespace - functions registered via Engine::register_XXX
-                // 2) Global modules - packages
-                // 3) Imported modules - functions marked with global namespace
-                // 4) Global sub-modules - functions marked with global namespace
-                let iter_func = self
-                    .global_modules
-                    .iter()
-                    .find_map(|m| m.get_iter(iter_type));
-
-                #[cfg(not(feature = "no_module"))]
-                let iter_func = iter_func
-                    .or_else(|| global.get_iter(iter_type))
-                    .or_else(|| {
-                        self.global_sub_modules
-                            .values()
-                            .find_map(|m| m.get_qualified_iter(iter_type))
-                    });
-
-                let iter_func = iter_func.ok_or_else(|| ERR::ErrorFor(expr.start_position()))?;
+                let mut source = self.make_for_source(global, iter_obj, expr.start_position())?;
 
                 // Restore scope at end of statement
                 defer! { scope => rewind; let orig_scope_len = scope.len(); }
 
                 // Add the loop variables
-                let counter_index = counter.as_ref().map(|counter| {
-                    scope.push(counter.name.clone(), 0 as INT);
-                    scope.len() - 1
-                });
-
-                scope.push(var_name.name.clone(), ());
-                let index = scope.len() - 1;
+                let (counter_index, index) =
+                    self.push_for_loop_vars(scope, var_name, counter.as_ref());
+                let counter = counter.as_ref().zip(counter_index);
+                let empty_body = Some(body.position()).filter(|_| body.is_empty());
 
                 let mut result = Dynamic::UNIT;
+                let mut i = 0;
 
-                if body.is_empty() {
-                    for _ in iter_func(iter_obj) {
-                        self.track_operation(global, body.position())?;
-                    }
-                } else {
-                    for (i, iter_value) in iter_func(iter_obj).enumerate() {
-                        // Increment counter
-                        if let Some(counter_index) = counter_index {
-                            // As the variable increments from 0, this should always work
-                            // since any overflow will first be caught below.
-                            let index_value = i as INT;
-
-                            #[cfg(not(feature = "unchecked"))]
-                            #[allow(clippy::absurd_extreme_comparisons)]
-                            if index_value > crate::MAX_USIZE_INT {
-                                return Err(ERR::ErrorArithmetic(
-                                    format!("for-loop counter overflow: {i}"),
-                                    counter.as_ref().unwrap().pos,
-                                )
-                                .into());
-                            }
-
-                            *scope.get_mut_by_index(counter_index).write_lock().unwrap() =
-                                Dynamic::from_int(index_value);
-                        }
-
-                        // Set loop value
-                        let value = iter_value
-                            .map_err(|err| err.fill_position(expr.position()))?
-                            .flatten();
+                while self.next_for_iteration(
+                    global,
+                    caches,
+                    scope,
+                    &mut source,
+                    counter,
+                    index,
+                    i,
+                    expr.position(),
+                    empty_body,
+                )? {
+                    i += 1;
 
-                        *scope.get_mut_by_index(index).write_lock().unwrap() = value;
+                    if body.is_empty() {
+                        continue;
+                    }
 
-                        // Run block
-                        let this_ptr = this_ptr.as_deref_mut();
-                        let statements = body.statements();
+                    // Run block
+                    let this_ptr = this_ptr.as_deref_mut();
+                    let statements = body.statements();
 
-                        match self
-                            .eval_stmt_block(global, caches, scope, this_ptr, statements, true)
-                        {
-                            Ok(_) => (),
-                            Err(err) => match *err {
-                                ERR::LoopBreak(false, ..) => (),
-                                ERR::LoopBreak(true, value, ..) => {
-                                    result = value;
-                                    break;
-                                }
-                                _ => return Err(err),
-                            },
-                        }
+                    match self.eval_stmt_block(global, caches, scope, this_ptr, statements, true) {
+                        Ok(_) => (),
+                        Err(err) => match *err {
+                            ERR::LoopBreak(false, ..) => (),
+                            ERR::LoopBreak(true, value, ..) => {
+                                result = value;
+                                break;
+                            }
+                            _ => return Err(err),
+                        },
                     }
                 }
 
@@ -802,69 +1437,21 @@ impl Engine {
                     Err(err) if err.is_pseudo_error() => Err(err),
                     Err(err) if !err.is_catchable() => Err(err),
                     Err(mut err) => {
-                        let err_value = match err.unwrap_inner() {
-                            // No error variable
-                            _ if catch_var.is_unit() => Dynamic::UNIT,
-
-                            ERR::ErrorRuntime(x, ..) => x.clone(),
-
-                            #[cfg(feature = "no_object")]
-                            _ => {
-                                let _ = err.take_position();
-                                err.to_string().into()
-                            }
-                            #[cfg(not(feature = "no_object"))]
-                            _ => {
-                                let mut err_map = crate::Map::new();
-                                let err_pos = err.take_position();
-
-                                err_map.insert("message".into(), err.to_string().into());
-
-                                if let Some(ref source) = global.source {
-                                    err_map.insert("source".into(), source.into());
-                                }
-
-                                if !err_pos.is_none() {
-                                    err_map.insert(
-                                        "line".into(),
-                                        (err_pos.line().unwrap() as INT).into(),
-                                    );
-                                    err_map.insert(
-                                        "position".into(),
-                                        (err_pos.position().unwrap_or(0) as INT).into(),
-                                    );
-                                }
-
-                                err.dump_fields(&mut err_map);
-                                err_map.into()
-                            }
-                        };
-
                         // Restore scope at end of block
                         defer! { scope if !catch_var.is_unit() => rewind; let orig_scope_len = scope.len(); }
 
-                        if let Expr::Variable(x, ..) = catch_var {
-                            // Guard against too many variables
-                            #[cfg(not(feature = "unchecked"))]
-                            if scope.len() >= self.max_variables() {
-                                return Err(ERR::ErrorTooManyVariables(catch_var.position()).into());
-                            }
-                            scope.push(x.3.clone(), err_value);
-                        }
+                        let catch_var = match catch_var {
+                            Expr::Variable(x, .., pos) => Some((x.3.clone(), *pos)),
+                            _ => None,
+                        };
+                        self.push_catch_var(global, scope, &mut err, catch_var)?;
 
                         let this_ptr = this_ptr.as_deref_mut();
                         let statements = branch.statements();
 
                         self.eval_stmt_block(global, caches, scope, this_ptr, statements, true)
                             .map(|_| Dynamic::UNIT)
-                            .map_err(|result_err| match *result_err {
-                                // Re-throw exception
-                                ERR::ErrorRuntime(v, pos) if v.is_unit() => {
-                                    err.set_position(pos);
-                                    err
-                                }
-                                _ => result_err,
-                            })
+                            .map_err(|result_err| self.make_catch_block_error(err, result_err))
                     }
                 }
             }
@@ -887,6 +1474,12 @@ impl Engine {
             // Empty return
             Stmt::Return(None, .., pos) => Err(ERR::Return(Dynamic::UNIT, *pos).into()),
 
+            // Yield statements are only run by generators
+            #[cfg(not(feature = "no_function"))]
+            Stmt::Yield(.., pos) => {
+                Err(ERR::ErrorRuntime("yield outside of a generator".into(), *pos).into())
+            }
+
             // Import statement
             #[cfg(not(feature = "no_module"))]
             Stmt::Import(x, _pos) => {
@@ -994,6 +1587,42 @@ impl Engine {
         }
     }
 
+    /// Convert an error caught by a `try` statement into the value of the `catch` variable.
+    pub(crate) fn make_catch_value(&self, global: &GlobalRuntimeState, err: &mut ERR) -> Dynamic {
+        match err.unwrap_inner() {
+            ERR::ErrorRuntime(x, ..) => x.clone(),
+
+            #[cfg(feature = "no_object")]
+            _ => {
+                let _ = global;
+                let _ = err.take_position();
+                err.to_string().into()
+            }
+            #[cfg(not(feature = "no_object"))]
+            _ => {
+                let mut err_map = crate::Map::new();
+                let err_pos = err.take_position();
+
+                err_map.insert("message".into(), err.to_string().into());
+
+                if let Some(ref source) = global.source {
+                    err_map.insert("source".into(), source.into());
+                }
+
+                if !err_pos.is_none() {
+                    err_map.insert("line".into(), (err_pos.line().unwrap() as INT).into());
+                    err_map.insert(
+                        "position".into(),
+                        (err_pos.position().unwrap_or(0) as INT).into(),
+                    );
+                }
+
+                err.dump_fields(&mut err_map);
+                err_map.into()
+            }
+        }
+    }
+
     /// Evaluate a list of statements with no `this` pointer.
     /// This is commonly used to evaluate a list of statements in an [`AST`][crate::AST] or a script function body.
     #[inline(always)]
@@ -1005,14 +1634,29 @@ impl Engine {
         statements: &[Stmt],
         map_exit_to_return_value: bool,
     ) -> RhaiResult {
-        self.eval_stmt_block(global, caches, scope, None, statements, false)
-            .or_else(|err| match *err {
-                ERR::Return(out, ..) => Ok(out),
-                ERR::Exit(out, ..) if map_exit_to_return_value => Ok(out),
-                ERR::LoopBreak(..) => {
-                    unreachable!("no outer loop scope to break out of")
-                }
-                _ => Err(err),
-            })
+        #[cfg(not(any(feature = "stackless", feature = "bytecode")))]
+        let result = self.eval_stmt_block(global, caches, scope, None, statements, false);
+        #[cfg(feature = "stackless")]
+        let result = self.run_stackless(global, caches, scope, None, statements, false);
+        #[cfg(all(feature = "bytecode", not(feature = "stackless")))]
+        let result = self.eval_bytecode(
+            global,
+            caches,
+            scope,
+            None,
+            statements,
+            #[cfg(not(feature = "no_function"))]
+            None,
+            false,
+        );
+
+        result.or_else(|err| match *err {
+            ERR::Return(out, ..) => Ok(out),
+            ERR::Exit(out, ..) if map_exit_to_return_value => Ok(out),
+            ERR::LoopBreak(..) => {
+                unreachable!("no outer loop scope to break out of")
+            }
+            _ => Err(err),
+        })
     }
 }
@@ -0,0 +1,513 @@
+//! Module implementing the recording of execution traces via the debugging interface.
+#![cfg(feature = "debugging")]
+#![cfg(not(feature = "no_position"))]
+
+use super::{DebuggerCommand, DebuggerEvent, EvalContext};
+use crate::ast::{ASTNode, Expr, Stmt};
+use crate::func::{locked_read, locked_write};
+use crate::{Engine, ImmutableString, Locked, Position, Shared};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// Header line of the text format of a [`Trace`].
+const TRACE_HEADER: &str = "#rhai-trace 1";
+
+/// _(debugging)_ A write to a variable recorded by a [`TraceStep`].
+/// Exported under the `debugging` feature only.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TraceWrite {
+    /// Index of the variable in the [`Scope`][crate::Scope].
+    pub index: usize,
+    /// Name of the variable.
+    pub name: ImmutableString,
+    /// Value of the variable, in debug format.
+    pub value: String,
+}
+
+/// _(debugging)_ A call to a native function recorded by a [`TraceStep`], with its result.
+/// Exported under the `debugging` feature only.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TraceCall {
+    /// Name of the function.
+    pub name: ImmutableString,
+    /// Value returned by the function, in debug format, or the error message.
+    pub result: Result<String, String>,
+}
+
+/// _(debugging)_ A step of a [`Trace`], i.e. a node about to be run.
+/// Exported under the `debugging` feature only.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TraceStep {
+    /// Source (empty if not available).
+    pub source: ImmutableString,
+    /// [Position] of the node.
+    pub pos: Position,
+    /// Nesting level of function calls.
+    pub level: usize,
+    /// Short description of the node, e.g. `let x`, `x =`, `foo()` or `if`, or `end` for the end
+    /// of the script.
+    pub node: ImmutableString,
+    /// Number of variables in the [`Scope`][crate::Scope] of the current function call.
+    pub scope_len: usize,
+    /// Variables of the current function call changed since the last step at the same level.
+    pub writes: Vec<TraceWrite>,
+    /// Native functions called by the node before the next step, with their results.
+    pub calls: Vec<TraceCall>,
+}
+
+/// _(debugging)_ An execution trace recorded by a [`TraceRecorder`].
+/// Exported under the `debugging` feature only.
+///
+/// Not available under `no_position`.
+///
+/// A [`Trace`] is saved and loaded in a compact line-based text format via [`Display`][fmt::Display]
+/// and [`FromStr`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Trace {
+    /// Steps in order of execution.
+    pub steps: Vec<TraceStep>,
+}
+
+impl Trace {
+    /// Create a new empty [`Trace`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { steps: Vec::new() }
+    }
+    /// Number of steps in the [`Trace`].
+    #[inline(always)]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+    /// Is the [`Trace`] empty?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+    /// Get the variables of the current function call at a step, in order of the
+    /// [`Scope`][crate::Scope], with their values in debug format.
+    ///
+    /// The values are replayed from the writes of all previous steps, so nothing is run.
+    ///
+    /// Returns an empty list if the step does not exist.
+    #[must_use]
+    pub fn variables_at(&self, step: usize) -> Vec<(ImmutableString, String)> {
+        if step >= self.steps.len() {
+            return Vec::new();
+        }
+
+        let mut frames = Vec::new();
+
+        for s in &self.steps[..=step] {
+            apply_step(&mut frames, s.level, s.scope_len, &s.writes);
+        }
+
+        frames.pop().unwrap_or_default()
+    }
+}
+
+/// Apply the writes of a step to the variables of each level of function calls.
+fn apply_step(
+    frames: &mut Vec<Vec<(ImmutableString, String)>>,
+    level: usize,
+    scope_len: usize,
+    writes: &[TraceWrite],
+) {
+    frames.resize_with(level + 1, Vec::new);
+
+    let vars = &mut frames[level];
+    vars.truncate(scope_len);
+
+    for write in writes {
+        let var = (write.name.clone(), write.value.clone());
+
+        if write.index < vars.len() {
+            vars[write.index] = var;
+        } else {
+            vars.push(var);
+        }
+    }
+}
+
+impl fmt::Display for Trace {
+    /// Write the [`Trace`] in its text format: a header line followed by one tab-separated record
+    /// per line.
+    ///
+    /// * `f` _source_ = source of the following steps
+    /// * `s` _line_ _position_ _level_ _scope length_ _node_ = step
+    /// * `w` _index_ _name_ _value_ = write to a variable in the step
+    /// * `c` _name_ _value_ = native function call in the step that returns a value
+    /// * `e` _name_ _error_ = native function call in the step that fails
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{TRACE_HEADER}")?;
+
+        let mut source = "";
+
+        for step in &self.steps {
+            if step.source != source {
+                source = &step.source;
+                writeln!(f, "f\t{}", escape(source))?;
+            }
+
+            let line = step.pos.line().unwrap_or(0);
+            let pos = step.pos.position().unwrap_or(0);
+            writeln!(
+                f,
+                "s\t{line}\t{pos}\t{}\t{}\t{}",
+                step.level,
+                step.scope_len,
+                escape(&step.node)
+            )?;
+
+            for write in &step.writes {
+                let name = escape(&write.name);
+                writeln!(f, "w\t{}\t{name}\t{}", write.index, escape(&write.value))?;
+            }
+            for call in &step.calls {
+                match call.result {
+                    Ok(ref value) => writeln!(f, "c\t{}\t{}", escape(&call.name), escape(value))?,
+                    Err(ref err) => writeln!(f, "e\t{}\t{}", escape(&call.name), escape(err))?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Trace {
+    type Err = String;
+
+    /// Parse a [`Trace`] in its text format.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate();
+
+        match lines.next() {
+            Some((.., TRACE_HEADER)) => (),
+            _ => return Err("Not a trace file".into()),
+        }
+
+        let mut trace = Self::new();
+        let mut source = ImmutableString::new();
+
+        for (n, line) in lines {
+            if line.is_empty() {
+                continue;
+            }
+
+            let err = || format!("Invalid trace record at line {}: {line}", n + 1);
+            let fields: Vec<_> = line.split('\t').collect();
+            let num = |i: usize| fields[i].parse::<usize>().map_err(|_| err());
+
+            match (fields[0], fields.len()) {
+                ("f", 2) => source = unescape(fields[1]).into(),
+                ("s", 6) => {
+                    let (line, pos) = (num(1)?, num(2)?);
+                    let pos = match (u16::try_from(line), u16::try_from(pos)) {
+                        (Ok(0), ..) => Position::NONE,
+                        (Ok(line), Ok(pos)) => Position::new(line, pos),
+                        _ => return Err(err()),
+                    };
+
+                    trace.steps.push(TraceStep {
+                        source: source.clone(),
+                        pos,
+                        level: num(3)?,
+                        scope_len: num(4)?,
+                        node: unescape(fields[5]).into(),
+                        writes: Vec::new(),
+                        calls: Vec::new(),
+                    });
+                }
+                ("w", 4) => {
+                    let write = TraceWrite {
+                        index: num(1)?,
+                        name: unescape(fields[2]).into(),
+                        value: unescape(fields[3]),
+                    };
+                    trace.steps.last_mut().ok_or_else(err)?.writes.push(write);
+                }
+                ("c" | "e", 3) => {
+                    let value = unescape(fields[2]);
+                    let call = TraceCall {
+                        name: unescape(fields[1]).into(),
+                        result: if fields[0] == "c" {
+                            Ok(value)
+                        } else {
+                            Err(value)
+                        },
+                    };
+                    trace.steps.last_mut().ok_or_else(err)?.calls.push(call);
+                }
+                _ => return Err(err()),
+            }
+        }
+
+        Ok(trace)
+    }
+}
+
+/// Escape tabs, line breaks and backslashes in a field of the text format.
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '\\' => result.push_str("\\\\"),
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            _ => result.push(ch),
+        }
+    }
+
+    result
+}
+
+/// Reverse [`escape`].
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next() {
+                Some('t') => result.push('\t'),
+                Some('n') => result.push('\n'),
+                Some('r') => result.push('\r'),
+                Some(ch) => result.push(ch),
+                None => result.push('\\'),
+            },
+            _ => result.push(ch),
+        }
+    }
+
+    result
+}
+
+/// Trace data being recorded.
+#[derive(Debug, Clone, Default)]
+struct TraceData {
+    /// Recorded trace.
+    trace: Trace,
+    /// Variables of each level of function calls, as of the last step at that level.
+    frames: Vec<Vec<(ImmutableString, String)>>,
+}
+
+/// _(debugging)_ A recorder of [execution traces][Trace], built on the debugging interface.
+/// Exported under the `debugging` feature only.
+///
+/// Not available under `no_position`.
+///
+/// Each step of the debugger is recorded with the node, its position and source, the writes to
+/// variables since the last step, and the results of native functions called.
+///
+/// Cloning a [`TraceRecorder`] yields a handle to the same recorded trace.
+///
+/// # Example
+///
+/// ```
+/// use rhai::debugger::{Trace, TraceRecorder};
+/// use rhai::Engine;
+///
+/// let mut engine = Engine::new();
+/// let recorder = TraceRecorder::new();
+///
+/// engine.register_trace_recorder(&recorder);
+///
+/// engine.run("let x = 40;\nx += 2;")?;
+///
+/// let trace = recorder.trace();
+///
+/// assert_eq!(trace.steps[0].node, "let x");
+/// assert_eq!(trace.variables_at(trace.len() - 1), [("x".into(), "42".to_string())]);
+///
+/// // Save and load the trace
+/// let text = trace.to_string();
+/// assert_eq!(text.parse::<Trace>()?, trace);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TraceRecorder(Shared<Locked<TraceData>>);
+
+impl TraceRecorder {
+    /// Create a new [`TraceRecorder`].
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Is there no recorded step?
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        locked_read(&self.0).trace.is_empty()
+    }
+    /// Discard the recorded trace.
+    #[inline]
+    pub fn clear(&self) {
+        *locked_write(&self.0) = TraceData::default();
+    }
+    /// Get a copy of the recorded trace.
+    #[inline]
+    #[must_use]
+    pub fn trace(&self) -> Trace {
+        locked_read(&self.0).trace.clone()
+    }
+    /// Record a step, or the end of the script if there is no node.
+    fn step(
+        &self,
+        context: &EvalContext,
+        node: Option<ASTNode>,
+        source: Option<&str>,
+        pos: Position,
+    ) {
+        let data = &mut *locked_write(&self.0);
+        let level = context.call_level();
+
+        let vars: Vec<_> = context
+            .scope()
+            .iter()
+            .map(|(name, _, value)| (name.into(), format!("{value:?}")))
+            .collect();
+
+        data.frames.resize_with(level + 1, Vec::new);
+        let last = &data.frames[level];
+
+        // Steps inside a chain of dots or indexing run without the scope of the function call
+        let scope_len = if vars.is_empty() && matches!(node, Some(ASTNode::Expr(..))) {
+            last.len()
+        } else {
+            vars.len()
+        };
+
+        let writes = vars
+            .iter()
+            .enumerate()
+            .filter(|&(i, var)| last.get(i) != Some(var))
+            .map(|(index, (name, value))| TraceWrite {
+                index,
+                name: name.clone(),
+                value: value.clone(),
+            })
+            .collect();
+
+        data.trace.steps.push(TraceStep {
+            source: source.unwrap_or("").into(),
+            pos,
+            level,
+            node: node.map_or_else(|| "end".into(), node_label).into(),
+            scope_len,
+            writes,
+            calls: Vec::new(),
+        });
+
+        if scope_len == vars.len() {
+            data.frames[level] = vars;
+        }
+    }
+    /// Record the result of a native function call.
+    fn call(&self, context: &EvalContext, result: Result<String, String>) {
+        let call_stack = context.global_runtime_state().debugger().call_stack();
+
+        let name = match call_stack.last() {
+            Some(frame) => {
+                #[cfg(not(feature = "no_function"))]
+                if context
+                    .iter_namespaces()
+                    .any(|m| m.get_script_fn(&frame.fn_name, frame.args.len()).is_some())
+                {
+                    return;
+                }
+                frame.fn_name.clone()
+            }
+            None => return,
+        };
+
+        if let Some(step) = locked_write(&self.0).trace.steps.last_mut() {
+            step.calls.push(TraceCall { name, result });
+        }
+    }
+}
+
+/// Get a short description of an [`AST` Node][ASTNode].
+fn node_label(node: ASTNode) -> String {
+    match node {
+        ASTNode::Stmt(Stmt::Var(x, options, ..)) => {
+            let keyword = if options.contains(crate::ast::ASTFlags::CONSTANT) {
+                "const"
+            } else {
+                "let"
+            };
+            format!("{keyword} {}", x.0.name)
+        }
+        ASTNode::Stmt(Stmt::If(..)) => "if".into(),
+        ASTNode::Stmt(Stmt::Switch(..)) => "switch".into(),
+        ASTNode::Stmt(Stmt::While(..)) => "while".into(),
+        ASTNode::Stmt(Stmt::Do(..)) => "do".into(),
+        ASTNode::Stmt(Stmt::For(..)) => "for".into(),
+        ASTNode::Stmt(Stmt::Assignment(x)) => match x.1.lhs {
+            Expr::Variable(ref v, ..) => format!("{} =", v.3),
+            _ => "assignment".into(),
+        },
+        ASTNode::Stmt(Stmt::FnCall(x, ..)) | ASTNode::Expr(Expr::FnCall(x, ..))
+            if x.op_token.is_some() =>
+        {
+            x.name.to_string()
+        }
+        ASTNode::Stmt(Stmt::FnCall(x, ..)) | ASTNode::Expr(Expr::FnCall(x, ..)) => {
+            format!("{}()", x.name)
+        }
+        ASTNode::Stmt(Stmt::Block(..)) => "block".into(),
+        ASTNode::Stmt(Stmt::Noop(..)) => "noop".into(),
+        ASTNode::Stmt(Stmt::TryCatch(..)) => "try".into(),
+        ASTNode::Stmt(Stmt::BreakLoop(..)) => "break".into(),
+        ASTNode::Stmt(Stmt::Return(..)) => "return".into(),
+        ASTNode::Stmt(..) => "statement".into(),
+        ASTNode::Expr(Expr::Variable(x, ..)) => x.3.to_string(),
+        ASTNode::Expr(Expr::ThisPtr(..)) => "this".into(),
+        ASTNode::Expr(Expr::MethodCall(x, ..)) => format!(".{}()", x.name),
+        ASTNode::Expr(Expr::Dot(..)) => ".".into(),
+        ASTNode::Expr(Expr::Index(..)) => "[]".into(),
+        ASTNode::Expr(Expr::And(..)) => "&&".into(),
+        ASTNode::Expr(Expr::Or(..)) => "||".into(),
+        ASTNode::Expr(Expr::Coalesce(..)) => "??".into(),
+        ASTNode::Expr(expr) if expr.is_constant() => "constant".into(),
+        ASTNode::Expr(..) => "expression".into(),
+    }
+}
+
+impl Engine {
+    /// Register a [`TraceRecorder`] as the debugging interface, replacing any registered debugger.
+    ///
+    /// Not available under `no_position`.
+    #[allow(deprecated)]
+    pub fn register_trace_recorder(&mut self, recorder: &TraceRecorder) -> &mut Self {
+        let recorder = recorder.clone();
+
+        self.register_debugger(
+            |_, dbg| dbg,
+            move |context, event, node, source, pos| {
+                match event {
+                    DebuggerEvent::Start | DebuggerEvent::Step | DebuggerEvent::BreakPoint(..) => {
+                        recorder.step(&context, Some(node), source, pos)
+                    }
+                    DebuggerEvent::End => recorder.step(&context, None, source, pos),
+                    DebuggerEvent::FunctionExitWithValue(value) => {
+                        recorder.call(&context, Ok(format!("{value:?}")))
+                    }
+                    DebuggerEvent::FunctionExitWithError(err) => {
+                        recorder.call(&context, Err(err.to_string()))
+                    }
+                    _ => (),
+                }
+
+                Ok(DebuggerCommand::StepInto)
+            },
+        )
+    }
+}
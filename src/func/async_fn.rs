@@ -0,0 +1,195 @@
+//! Module which defines the asynchronous function registration mechanism.
+
+#![allow(non_snake_case)]
+#![allow(unused_mut)]
+
+use super::call::FnCallArgs;
+use super::function::RhaiFunc;
+use super::native::{SendSync, Shared};
+use super::register::by_value;
+use crate::types::dynamic::Variant;
+use crate::{Dynamic, NativeCallContext, RhaiResult, RhaiResultOf, ERR};
+#[cfg(feature = "metadata")]
+use std::any::type_name;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{
+    any::TypeId,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+};
+
+/// Trait that maps to `Send` only under the `sync` feature.
+#[cfg(feature = "sync")]
+pub trait MaybeSend: Send {}
+/// Trait that maps to `Send` only under the `sync` feature.
+#[cfg(feature = "sync")]
+impl<T: Send> MaybeSend for T {}
+
+/// Trait that maps to `Send` only under the `sync` feature.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSend {}
+/// Trait that maps to `Send` only under the `sync` feature.
+#[cfg(not(feature = "sync"))]
+impl<T> MaybeSend for T {}
+
+/// Boxed future of an asynchronous native Rust function call.
+#[cfg(not(feature = "sync"))]
+pub type BoxFuture = Pin<Box<dyn Future<Output = RhaiResult>>>;
+/// Boxed future of an asynchronous native Rust function call.
+#[cfg(feature = "sync")]
+pub type BoxFuture = Pin<Box<dyn Future<Output = RhaiResult> + Send>>;
+
+/// Lock holding the future of a suspended call.
+///
+/// Under `sync`, a [`Mutex`][std::sync::Mutex] is used because futures are [`Send`] but not
+/// necessarily [`Sync`].
+#[cfg(not(feature = "sync"))]
+type FutureLock = std::cell::RefCell<Option<BoxFuture>>;
+/// Lock holding the future of a suspended call.
+///
+/// Under `sync`, a [`Mutex`][std::sync::Mutex] is used because futures are [`Send`] but not
+/// necessarily [`Sync`].
+#[cfg(feature = "sync")]
+type FutureLock = std::sync::Mutex<Option<BoxFuture>>;
+
+/// A call to an asynchronous native Rust function that is not yet complete.
+///
+/// It is only returned by calls where evaluation can be suspended, and is never exposed to scripts.
+#[derive(Clone)]
+pub struct AsyncCall(Shared<FutureLock>);
+
+impl fmt::Debug for AsyncCall {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AsyncCall")
+    }
+}
+
+impl AsyncCall {
+    /// Take the future of the call, if not already taken.
+    #[inline]
+    #[must_use]
+    pub fn take_future(&self) -> Option<BoxFuture> {
+        #[cfg(not(feature = "sync"))]
+        return self.0.borrow_mut().take();
+        #[cfg(feature = "sync")]
+        return self.0.lock().unwrap().take();
+    }
+}
+
+/// A [`Waker`] that does nothing.
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    #[inline(always)]
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Poll a future once, without any means of being woken up.
+#[inline]
+pub fn poll_once(future: &mut BoxFuture) -> Poll<RhaiResult> {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    future.as_mut().poll(&mut Context::from_waker(&waker))
+}
+
+/// Start a call to an asynchronous native Rust function.
+///
+/// The future is polled once. If it is not ready, the call is suspended as an [`AsyncCall`] when
+/// the current evaluation can be suspended at the call site, otherwise an error is raised.
+pub fn start_async_call(ctx: &NativeCallContext, mut future: BoxFuture) -> RhaiResult {
+    if let Poll::Ready(result) = poll_once(&mut future) {
+        return result;
+    }
+
+    let global = ctx.global_runtime_state();
+
+    if global.async_site == Some((global.level, ctx.position())) {
+        let call = AsyncCall(Shared::new(FutureLock::new(Some(future))));
+        return Ok(Dynamic::from(call));
+    }
+
+    Err(ERR::ErrorRuntime(
+        format!(
+            "Asynchronous function '{}' cannot suspend here: call it by itself in a statement under asynchronous evaluation",
+            ctx.fn_name()
+        )
+        .into(),
+        ctx.position(),
+    )
+    .into())
+}
+
+/// Trait to register asynchronous custom Rust functions.
+///
+/// # Type Parameters
+///
+/// * `A` - a tuple containing parameter types, all passed by value.
+/// * `N` - a constant generic containing the number of parameters, must be consistent with `ARGS`.
+/// * `R` - type of the value produced by the future; the future returns `Result<R, Box<EvalAltResult>>`.
+pub trait RhaiAsyncFunc<A: 'static, const N: usize, R: 'static> {
+    /// Convert this function into a [`RhaiFunc`].
+    #[must_use]
+    fn into_rhai_function(self) -> RhaiFunc;
+    /// Get the type ID's of this function's parameters.
+    #[must_use]
+    fn param_types() -> [TypeId; N];
+    /// _(metadata)_ Get the type names of this function's parameters.
+    /// Exported under the `metadata` feature only.
+    #[cfg(feature = "metadata")]
+    #[must_use]
+    fn param_names() -> [&'static str; N];
+    /// _(metadata)_ Get the type name of this function's return value.
+    /// Exported under the `metadata` feature only.
+    #[cfg(feature = "metadata")]
+    #[inline(always)]
+    #[must_use]
+    fn return_type_name() -> &'static str {
+        type_name::<R>()
+    }
+}
+
+macro_rules! def_register_async {
+    () => {
+        def_register_async!(imp : 0 ;);
+    };
+    (imp : $n:expr ; $($par:ident),*) => {
+    //     ^ number of parameters
+    //                 ^ function parameter generic type name (A, B, C etc.)
+
+        impl<
+            FN: Fn($($par),*) -> FUT + SendSync + 'static,
+            FUT: Future<Output = RhaiResultOf<RET>> + MaybeSend + 'static,
+            $($par: Variant + Clone,)*
+            RET: Variant + Clone,
+        > RhaiAsyncFunc<($($par,)*), $n, RET> for FN {
+            #[inline(always)] fn param_types() -> [TypeId;$n] { [$(TypeId::of::<$par>()),*] }
+            #[cfg(feature = "metadata")] #[inline(always)] fn param_names() -> [&'static str;$n] { [$(type_name::<$par>()),*] }
+            #[inline(always)] fn into_rhai_function(self) -> RhaiFunc {
+                RhaiFunc::Pure { func: Shared::new(move |ctx: Option<NativeCallContext>, args: &mut FnCallArgs| {
+                    let ctx = ctx.unwrap();
+
+                    // The arguments are assumed to be of the correct number and types!
+                    #[allow(unused_variables)]
+                    let mut drain = args.iter_mut();
+                    $(let mut $par = by_value(drain.next().unwrap()); )*
+
+                    // Call the function with each argument value to get the future
+                    let future = self($($par),*);
+
+                    start_async_call(&ctx, Box::pin(async move { future.await.map(Dynamic::from) }))
+                }), has_context: true, is_pure: true, is_volatile: true }
+            }
+        }
+    };
+    ($p0:ident:$n0:expr $(, $p:ident: $n:expr)*) => {
+        def_register_async!(imp : $n0 ; $p0 $(, $p)*);
+        def_register_async!($($p: $n),*);
+    };
+}
+
+def_register_async!(A:20, B:19, C:18, D:17, E:16, F:15, G:14, H:13, J:12, K:11, L:10, M:9, N:8, P:7, Q:6, R:5, S:4, T:3, U:2, V:1);
@@ -2,7 +2,7 @@
 
 use super::{get_builtin_binary_op_fn, get_builtin_op_assignment_fn, RhaiFunc};
 use crate::api::default_limits::MAX_DYNAMIC_PARAMETERS;
-use crate::ast::{Expr, FnCallExpr, FnCallHashes};
+use crate::ast::{Expr, FnCallBinding, FnCallExpr, FnCallHashes};
 use crate::engine::{
     KEYWORD_DEBUG, KEYWORD_EVAL, KEYWORD_FN_PTR, KEYWORD_FN_PTR_CALL, KEYWORD_FN_PTR_CURRY,
     KEYWORD_IS_DEF_VAR, KEYWORD_PRINT, KEYWORD_TYPE_OF,
@@ -163,7 +163,7 @@ impl Engine {
     /// 4) Imported modules - functions marked with global namespace
     /// 5) Static registered modules
     #[must_use]
-    fn resolve_fn<'s>(
+    pub(crate) fn resolve_fn<'s>(
         &self,
         _global: &GlobalRuntimeState,
         caches: &'s mut Caches,
@@ -346,6 +346,7 @@ impl Engine {
         name: &str,
         op_token: Option<&Token>,
         hash: u64,
+        binding: Option<&FnCallBinding>,
         args: &mut FnCallArgs,
         is_ref_mut: bool,
         non_volatile_only: bool,
@@ -353,10 +354,32 @@ impl Engine {
     ) -> RhaiResultOf<(Dynamic, bool)> {
         self.track_operation(global, pos)?;
 
+        // Handle generator.next() - resume the generator with the current global runtime state
+        #[cfg(not(feature = "no_function"))]
+        if name == "next" && op_token.is_none() && args.len() == 1 {
+            let generator = args[0].read_lock::<crate::Generator>().map(|g| g.clone());
+
+            if let Some(generator) = generator {
+                return self
+                    .resume_generator(global, caches, &generator, pos)
+                    .map(|v| (v.unwrap_or_default(), false));
+            }
+        }
+
+        // Use the function bound at compile time if it is still valid
+        let bound = binding
+            .filter(|b| b.version == self.fn_version && b.hash == hash)
+            .and_then(|b| b.get(args));
+
         // Check if function access already in the cache
         let local_entry = &mut None;
-        let a = Some(&mut *args);
-        let func = self.resolve_fn(global, caches, local_entry, op_token, hash, a, true);
+        let func = match bound {
+            Some(entry) => Some(entry),
+            None => {
+                let a = Some(&mut *args);
+                self.resolve_fn(global, caches, local_entry, op_token, hash, a, true)
+            }
+        };
 
         if let Some(FnResolutionCacheEntry { func, source }) = func {
             debug_assert!(func.is_native());
@@ -397,6 +420,15 @@ impl Engine {
                 );
             }
 
+            // Record the call in the profiler
+            #[cfg(feature = "profiling")]
+            let orig_profile_len = global.profile_len();
+            #[cfg(feature = "profiling")]
+            if global.profiler.is_some() {
+                let fn_name = self.get_interned_string(name);
+                global.enter_profile(fn_name, args.len(), false, source.clone(), Position::NONE);
+            }
+
             // Run external function
             let context = func
                 .has_context()
@@ -414,6 +446,9 @@ impl Engine {
             .and_then(|r| self.check_data_size(r, pos))
             .map_err(|err| err.fill_position(pos));
 
+            #[cfg(feature = "profiling")]
+            global.rewind_profile(orig_profile_len);
+
             if swap {
                 backup.restore_first_arg(args);
             }
@@ -566,6 +601,7 @@ impl Engine {
         fn_name: &str,
         op_token: Option<&Token>,
         hashes: FnCallHashes,
+        binding: Option<&FnCallBinding>,
         args: &mut FnCallArgs,
         is_ref_mut: bool,
         _is_method_call: bool,
@@ -630,58 +666,117 @@ impl Engine {
                     unreachable!("Script function expected");
                 };
 
-                let fn_def = &*fn_def;
-                let environ = environ.as_deref();
+                return self
+                    .call_resolved_script_fn(
+                        global,
+                        caches,
+                        _scope,
+                        &fn_def,
+                        environ.as_ref(),
+                        source,
+                        args,
+                        is_ref_mut,
+                        _is_method_call,
+                        pos,
+                    )
+                    .map(|r| (r, false));
+            }
+        }
 
-                if fn_def.body.is_empty() {
-                    return Ok((Dynamic::UNIT, false));
-                }
+        // Native function call
+        let hash = hashes.native();
 
-                let mut empty_scope;
-                let scope = if let Some(scope) = _scope {
-                    scope
-                } else {
-                    empty_scope = Scope::new();
-                    &mut empty_scope
-                };
+        self.exec_native_fn_call(
+            global, caches, fn_name, op_token, hash, binding, args, is_ref_mut, false, pos,
+        )
+    }
 
-                let orig_source = mem::replace(&mut global.source, source);
-                defer! { global => move |g| g.source = orig_source }
+    /// Call a resolved script-defined function, in normal function-call or method-call style.
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) fn call_resolved_script_fn(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: Option<&mut Scope>,
+        fn_def: &Shared<crate::ast::ScriptFuncDef>,
+        environ: Option<&Shared<crate::ast::EncapsulatedEnviron>>,
+        source: Option<ImmutableString>,
+        args: &mut FnCallArgs,
+        is_ref_mut: bool,
+        is_method_call: bool,
+        pos: Position,
+    ) -> RhaiResult {
+        if fn_def.body.is_empty() && fn_def.param_types.is_empty() && fn_def.return_type.is_none() {
+            return Ok(Dynamic::UNIT);
+        }
 
-                return if _is_method_call {
-                    // Method call of script function - map first argument to `this`
-                    let (first_arg, args) = args.split_first_mut().unwrap();
-                    let this_ptr = Some(&mut **first_arg);
-                    self.call_script_fn(
-                        global, caches, scope, this_ptr, environ, fn_def, args, true, pos,
-                    )
-                } else {
-                    // Normal call of script function
-                    let backup = &mut ArgBackup::new();
+        let mut empty_scope;
+        let scope = if let Some(scope) = scope {
+            scope
+        } else {
+            empty_scope = Scope::new();
+            &mut empty_scope
+        };
+
+        let orig_source = mem::replace(&mut global.source, source);
+        defer! { global => move |g| g.source = orig_source }
+
+        if is_method_call {
+            // Method call of script function - map first argument to `this`
+            let (first_arg, args) = args.split_first_mut().unwrap();
+            let this_ptr = Some(&mut **first_arg);
+            self.call_script_fn(
+                global, caches, scope, this_ptr, environ, fn_def, args, true, pos,
+            )
+        } else {
+            // Normal call of script function
+            let backup = &mut ArgBackup::new();
 
-                    // The first argument is a reference?
-                    let swap = is_ref_mut && !args.is_empty();
+            // The first argument is a reference?
+            let swap = is_ref_mut && !args.is_empty();
 
-                    if swap {
-                        backup.change_first_arg_to_copy(args);
-                    }
+            if swap {
+                backup.change_first_arg_to_copy(args);
+            }
 
-                    defer! { args = (args) if swap => move |a| backup.restore_first_arg(a) }
+            defer! { args = (args) if swap => move |a| backup.restore_first_arg(a) }
 
-                    self.call_script_fn(
-                        global, caches, scope, None, environ, fn_def, args, true, pos,
-                    )
-                }
-                .map(|r| (r, false));
-            }
+            self.call_script_fn(
+                global, caches, scope, None, environ, fn_def, args, true, pos,
+            )
         }
+    }
 
-        // Native function call
-        let hash = hashes.native();
+    /// Resolve a call in tail position to a script-defined function that can replace the current
+    /// call frame, if any.
+    ///
+    /// Functions with an encapsulated environment, from a different source, or that are generators
+    /// are not eligible.
+    #[cfg(not(feature = "no_function"))]
+    fn resolve_tail_call(
+        &self,
+        global: &GlobalRuntimeState,
+        caches: &mut Caches,
+        hashes: FnCallHashes,
+    ) -> Option<Shared<crate::ast::ScriptFuncDef>> {
+        if hashes.is_native_only() {
+            return None;
+        }
 
-        self.exec_native_fn_call(
-            global, caches, fn_name, op_token, hash, args, is_ref_mut, false, pos,
-        )
+        let local_entry = &mut None;
+        let hash = hashes.script();
+
+        match self.resolve_fn(global, caches, local_entry, None, hash, None, false)? {
+            FnResolutionCacheEntry {
+                func:
+                    RhaiFunc::Script {
+                        fn_def,
+                        environ: None,
+                    },
+                source,
+            } if !fn_def.is_generator && *source == global.source => Some(fn_def.clone()),
+            _ => None,
+        }
     }
 
     /// Evaluate an argument.
@@ -726,6 +821,7 @@ impl Engine {
         caches: &mut Caches,
         fn_name: &str,
         mut hash: FnCallHashes,
+        binding: Option<&FnCallBinding>,
         target: &mut crate::eval::Target,
         call_args: &mut [Dynamic],
         first_arg_pos: Position,
@@ -745,14 +841,14 @@ impl Engine {
 
                 let _fn_def = ();
                 #[cfg(not(feature = "no_function"))]
-                let _fn_def = fn_ptr.fn_def.as_deref();
+                let _fn_def = fn_ptr.fn_def.as_ref();
 
                 match _fn_def {
                     // Linked to scripted function - short-circuit
                     #[cfg(not(feature = "no_function"))]
                     Some(fn_def) if fn_def.params.len() == args.len() => {
                         let scope = &mut Scope::new();
-                        let environ = fn_ptr.environ.as_ref().map(<_>::as_ref);
+                        let environ = fn_ptr.environ.as_ref();
 
                         self.call_script_fn(
                             global, caches, scope, None, environ, fn_def, args, true, pos,
@@ -775,7 +871,8 @@ impl Engine {
 
                         // Map it to name(args) in function-call style
                         self.exec_fn_call(
-                            global, caches, None, fn_name, None, new_hash, args, false, false, pos,
+                            global, caches, None, fn_name, None, new_hash, None, args, false,
+                            false, pos,
                         )
                     }
                 }
@@ -827,7 +924,7 @@ impl Engine {
 
                         let scope = &mut Scope::new();
                         let this_ptr = Some(target.as_mut());
-                        let environ = environ.as_deref();
+                        let environ = environ.as_ref();
 
                         self.call_script_fn(
                             global, caches, scope, this_ptr, environ, &fn_def, args, true, pos,
@@ -861,8 +958,8 @@ impl Engine {
 
                         // Map it to name(args) in function-call style
                         self.exec_fn_call(
-                            global, caches, None, &name, None, new_hash, args, is_ref_mut, true,
-                            pos,
+                            global, caches, None, &name, None, new_hash, None, args, is_ref_mut,
+                            true, pos,
                         )
                     }
                 }
@@ -920,7 +1017,7 @@ impl Engine {
 
                             let _fn_def = ();
                             #[cfg(not(feature = "no_function"))]
-                            let _fn_def = fn_ptr.fn_def.as_deref();
+                            let _fn_def = fn_ptr.fn_def.as_ref();
 
                             match _fn_def {
                                 // Linked to scripted function
@@ -966,7 +1063,7 @@ impl Engine {
                     Some((fn_def, environ)) => {
                         // Linked to scripted function - short-circuit
                         let scope = &mut Scope::new();
-                        let environ = environ.as_deref();
+                        let environ = environ.as_ref();
                         let this_ptr = Some(target.as_mut());
                         let args = &mut call_args.iter_mut().collect::<FnArgsVec<_>>();
 
@@ -986,7 +1083,8 @@ impl Engine {
                             .collect::<FnArgsVec<_>>();
 
                         self.exec_fn_call(
-                            global, caches, None, fn_name, None, hash, args, is_ref_mut, true, pos,
+                            global, caches, None, fn_name, None, hash, binding, args, is_ref_mut,
+                            true, pos,
                         )
                     }
                 }
@@ -1013,6 +1111,7 @@ impl Engine {
         first_arg: Option<&Expr>,
         args_expr: &[Expr],
         hashes: FnCallHashes,
+        binding: Option<&FnCallBinding>,
         capture_scope: bool,
         pos: Position,
     ) -> RhaiResult {
@@ -1078,7 +1177,7 @@ impl Engine {
                         }
                         let args = &mut arg_values.iter_mut().collect::<FnArgsVec<_>>();
                         let scope = &mut Scope::new();
-                        let environ = environ.as_deref();
+                        let environ = environ.as_ref();
 
                         return self.call_script_fn(
                             global, caches, scope, None, environ, &fn_def, args, true, pos,
@@ -1306,8 +1405,8 @@ impl Engine {
 
             return self
                 .exec_fn_call(
-                    global, caches, scope, fn_name, op_token, hashes, &mut args, is_ref_mut, false,
-                    pos,
+                    global, caches, scope, fn_name, op_token, hashes, None, &mut args, is_ref_mut,
+                    false, pos,
                 )
                 .map(|(v, ..)| v);
         }
@@ -1384,7 +1483,8 @@ impl Engine {
         args.extend(arg_values.iter_mut());
 
         self.exec_fn_call(
-            global, caches, None, fn_name, op_token, hashes, &mut args, is_ref_mut, false, pos,
+            global, caches, None, fn_name, op_token, hashes, binding, &mut args, is_ref_mut, false,
+            pos,
         )
         .map(|(v, ..)| v)
     }
@@ -1540,7 +1640,7 @@ impl Engine {
         match func {
             #[cfg(not(feature = "no_function"))]
             Some(RhaiFunc::Script { fn_def, environ }) => {
-                let environ = environ.as_deref();
+                let environ = environ.as_ref();
                 let scope = &mut Scope::new();
 
                 let orig_source = mem::replace(&mut global.source, module.id_raw().cloned());
@@ -1670,33 +1770,22 @@ impl Engine {
 
         // Short-circuit native unary operator call if under Fast Operators mode
         if self.fast_operators() && args.len() == 1 && op_token == Some(&Token::Bang) {
-            let mut value = self
+            let value = self
                 .get_arg_value(global, caches, scope, this_ptr.as_deref_mut(), &args[0])?
                 .0
                 .flatten();
 
-            return if let Union::Bool(b, ..) = value.0 {
-                Ok((!b).into())
-            } else {
-                let operand = &mut [&mut value];
-                self.exec_fn_call(
-                    global, caches, None, name, op_token, *hashes, operand, false, false, pos,
-                )
-                .map(|(v, ..)| v)
-            };
+            return self.eval_fast_unary_not(global, caches, name, *hashes, value, pos);
         }
 
         // Short-circuit native binary operator call if under Fast Operators mode
         if self.fast_operators() && args.len() == 2 && op_token.is_some() {
-            #[allow(clippy::wildcard_imports)]
-            use Token::*;
-
-            let mut lhs = self
+            let lhs = self
                 .get_arg_value(global, caches, scope, this_ptr.as_deref_mut(), &args[0])?
                 .0
                 .flatten();
 
-            let mut rhs = self
+            let rhs = self
                 .get_arg_value(global, caches, scope, this_ptr.as_deref_mut(), &args[1])?
                 .0
                 .flatten();
@@ -1704,185 +1793,8 @@ impl Engine {
             #[allow(clippy::unnecessary_unwrap)]
             let op_token = op_token.unwrap();
 
-            // For extremely simple primary data operations, do it directly
-            // to avoid the overhead of calling a function.
-            match (&lhs.0, &rhs.0) {
-                (Union::Unit(..), Union::Unit(..)) => match op_token {
-                    EqualsTo => return Ok(Dynamic::TRUE),
-                    NotEqualsTo | GreaterThan | GreaterThanEqualsTo | LessThan
-                    | LessThanEqualsTo => return Ok(Dynamic::FALSE),
-                    _ => (),
-                },
-                (Union::Bool(b1, ..), Union::Bool(b2, ..)) => match op_token {
-                    EqualsTo => return Ok((b1 == b2).into()),
-                    NotEqualsTo => return Ok((b1 != b2).into()),
-                    GreaterThan | GreaterThanEqualsTo | LessThan | LessThanEqualsTo => {
-                        return Ok(Dynamic::FALSE)
-                    }
-                    Pipe => return Ok((*b1 || *b2).into()),
-                    Ampersand => return Ok((*b1 && *b2).into()),
-                    _ => (),
-                },
-                (Union::Int(n1, ..), Union::Int(n2, ..)) => {
-                    #[cfg(not(feature = "unchecked"))]
-                    #[allow(clippy::wildcard_imports)]
-                    use crate::packages::arithmetic::arith_basic::INT::functions::*;
-
-                    #[cfg(not(feature = "unchecked"))]
-                    match op_token {
-                        EqualsTo => return Ok((n1 == n2).into()),
-                        NotEqualsTo => return Ok((n1 != n2).into()),
-                        GreaterThan => return Ok((n1 > n2).into()),
-                        GreaterThanEqualsTo => return Ok((n1 >= n2).into()),
-                        LessThan => return Ok((n1 < n2).into()),
-                        LessThanEqualsTo => return Ok((n1 <= n2).into()),
-                        Plus => return add(*n1, *n2).map(Into::into),
-                        Minus => return subtract(*n1, *n2).map(Into::into),
-                        Multiply => return multiply(*n1, *n2).map(Into::into),
-                        Divide => return divide(*n1, *n2).map(Into::into),
-                        Modulo => return modulo(*n1, *n2).map(Into::into),
-                        _ => (),
-                    }
-                    #[cfg(feature = "unchecked")]
-                    match op_token {
-                        EqualsTo => return Ok((n1 == n2).into()),
-                        NotEqualsTo => return Ok((n1 != n2).into()),
-                        GreaterThan => return Ok((n1 > n2).into()),
-                        GreaterThanEqualsTo => return Ok((n1 >= n2).into()),
-                        LessThan => return Ok((n1 < n2).into()),
-                        LessThanEqualsTo => return Ok((n1 <= n2).into()),
-                        Plus => return Ok((n1 + n2).into()),
-                        Minus => return Ok((n1 - n2).into()),
-                        Multiply => return Ok((n1 * n2).into()),
-                        Divide => return Ok((n1 / n2).into()),
-                        Modulo => return Ok((n1 % n2).into()),
-                        _ => (),
-                    }
-                }
-                #[cfg(not(feature = "no_float"))]
-                (Union::Float(f1, ..), Union::Float(f2, ..)) => match op_token {
-                    #[cfg(feature = "unchecked")]
-                    EqualsTo => return Ok((**f1 == **f2).into()),
-                    #[cfg(not(feature = "unchecked"))]
-                    EqualsTo => return Ok(((**f1 - **f2).abs() <= FLOAT::EPSILON).into()),
-                    #[cfg(feature = "unchecked")]
-                    NotEqualsTo => return Ok((**f1 != **f2).into()),
-                    #[cfg(not(feature = "unchecked"))]
-                    NotEqualsTo => return Ok(((**f1 - **f2).abs() > FLOAT::EPSILON).into()),
-                    GreaterThan => return Ok((**f1 > **f2).into()),
-                    GreaterThanEqualsTo => return Ok((**f1 >= **f2).into()),
-                    LessThan => return Ok((**f1 < **f2).into()),
-                    LessThanEqualsTo => return Ok((**f1 <= **f2).into()),
-                    Plus => return Ok((**f1 + **f2).into()),
-                    Minus => return Ok((**f1 - **f2).into()),
-                    Multiply => return Ok((**f1 * **f2).into()),
-                    Divide => return Ok((**f1 / **f2).into()),
-                    Modulo => return Ok((**f1 % **f2).into()),
-                    _ => (),
-                },
-                #[cfg(not(feature = "no_float"))]
-                (Union::Float(f1, ..), Union::Int(n2, ..)) => match op_token {
-                    #[cfg(feature = "unchecked")]
-                    EqualsTo => return Ok((**f1 == (*n2 as FLOAT)).into()),
-                    #[cfg(not(feature = "unchecked"))]
-                    EqualsTo => return Ok(((**f1 - (*n2 as FLOAT)).abs() <= FLOAT::EPSILON).into()),
-                    #[cfg(feature = "unchecked")]
-                    NotEqualsTo => return Ok((**f1 != (*n2 as FLOAT)).into()),
-                    #[cfg(not(feature = "unchecked"))]
-                    NotEqualsTo => {
-                        return Ok(((**f1 - (*n2 as FLOAT)).abs() > FLOAT::EPSILON).into())
-                    }
-                    GreaterThan => return Ok((**f1 > (*n2 as FLOAT)).into()),
-                    GreaterThanEqualsTo => return Ok((**f1 >= (*n2 as FLOAT)).into()),
-                    LessThan => return Ok((**f1 < (*n2 as FLOAT)).into()),
-                    LessThanEqualsTo => return Ok((**f1 <= (*n2 as FLOAT)).into()),
-                    Plus => return Ok((**f1 + (*n2 as FLOAT)).into()),
-                    Minus => return Ok((**f1 - (*n2 as FLOAT)).into()),
-                    Multiply => return Ok((**f1 * (*n2 as FLOAT)).into()),
-                    Divide => return Ok((**f1 / (*n2 as FLOAT)).into()),
-                    Modulo => return Ok((**f1 % (*n2 as FLOAT)).into()),
-                    _ => (),
-                },
-                #[cfg(not(feature = "no_float"))]
-                (Union::Int(n1, ..), Union::Float(f2, ..)) => match op_token {
-                    #[cfg(feature = "unchecked")]
-                    EqualsTo => return Ok(((*n1 as FLOAT) == **f2).into()),
-                    #[cfg(not(feature = "unchecked"))]
-                    EqualsTo => return Ok((((*n1 as FLOAT) - **f2).abs() <= FLOAT::EPSILON).into()),
-                    #[cfg(feature = "unchecked")]
-                    NotEqualsTo => return Ok(((*n1 as FLOAT) != **f2).into()),
-                    #[cfg(not(feature = "unchecked"))]
-                    NotEqualsTo => {
-                        return Ok((((*n1 as FLOAT) - **f2).abs() > FLOAT::EPSILON).into())
-                    }
-                    GreaterThan => return Ok(((*n1 as FLOAT) > **f2).into()),
-                    GreaterThanEqualsTo => return Ok(((*n1 as FLOAT) >= **f2).into()),
-                    LessThan => return Ok(((*n1 as FLOAT) < **f2).into()),
-                    LessThanEqualsTo => return Ok(((*n1 as FLOAT) <= **f2).into()),
-                    Plus => return Ok(((*n1 as FLOAT) + **f2).into()),
-                    Minus => return Ok(((*n1 as FLOAT) - **f2).into()),
-                    Multiply => return Ok(((*n1 as FLOAT) * **f2).into()),
-                    Divide => return Ok(((*n1 as FLOAT) / **f2).into()),
-                    Modulo => return Ok(((*n1 as FLOAT) % **f2).into()),
-                    _ => (),
-                },
-                (Union::Str(s1, ..), Union::Str(s2, ..)) => match op_token {
-                    EqualsTo => return Ok((s1 == s2).into()),
-                    NotEqualsTo => return Ok((s1 != s2).into()),
-                    GreaterThan => return Ok((s1 > s2).into()),
-                    GreaterThanEqualsTo => return Ok((s1 >= s2).into()),
-                    LessThan => return Ok((s1 < s2).into()),
-                    LessThanEqualsTo => return Ok((s1 <= s2).into()),
-                    Plus => {
-                        #[cfg(not(feature = "unchecked"))]
-                        self.throw_on_size((0, 0, s1.len() + s2.len()))?;
-                        return Ok((s1 + s2).into());
-                    }
-                    Minus => return Ok((s1 - s2).into()),
-                    _ => (),
-                },
-                (Union::Char(c1, ..), Union::Char(c2, ..)) => match op_token {
-                    EqualsTo => return Ok((c1 == c2).into()),
-                    NotEqualsTo => return Ok((c1 != c2).into()),
-                    GreaterThan => return Ok((c1 > c2).into()),
-                    GreaterThanEqualsTo => return Ok((c1 >= c2).into()),
-                    LessThan => return Ok((c1 < c2).into()),
-                    LessThanEqualsTo => return Ok((c1 <= c2).into()),
-                    Plus => {
-                        let mut result = SmartString::new_const();
-                        result.push(*c1);
-                        result.push(*c2);
-
-                        #[cfg(not(feature = "unchecked"))]
-                        self.throw_on_size((0, 0, result.len()))?;
-
-                        return Ok(result.into());
-                    }
-                    _ => (),
-                },
-                (Union::Variant(..), _) | (_, Union::Variant(..)) => (),
-                _ => {
-                    if let Some((func, need_context)) =
-                        get_builtin_binary_op_fn(op_token, &lhs, &rhs)
-                    {
-                        // We may not need to bump the level because built-in's do not need it.
-                        //defer! { let orig_level = global.level; global.level += 1 }
-
-                        let context =
-                            need_context.then(|| (self, name.as_str(), None, &*global, pos).into());
-                        return func(context, &mut [&mut lhs, &mut rhs]);
-                    }
-                }
-            }
-
-            let operands = &mut [&mut lhs, &mut rhs];
-            let op_token = Some(op_token);
-
             return self
-                .exec_fn_call(
-                    global, caches, None, name, op_token, *hashes, operands, false, false, pos,
-                )
-                .map(|(v, ..)| v);
+                .eval_fast_binary_op(global, caches, name, op_token, *hashes, lhs, rhs, pos);
         }
 
         #[cfg(not(feature = "no_module"))]
@@ -1895,15 +1807,257 @@ impl Engine {
             );
         }
 
+        // Tail call of a script-defined function - reuse the current call frame
+        #[cfg(not(feature = "no_function"))]
+        if expr.is_tail_call && global.tail_call_level == Some(global.level) {
+            if let Some(fn_def) = self.resolve_tail_call(global, caches, *hashes) {
+                let mut arg_values = FnArgsVec::with_capacity(args.len());
+
+                for expr in args {
+                    let (value, ..) =
+                        self.get_arg_value(global, caches, scope, this_ptr.as_deref_mut(), expr)?;
+                    arg_values.push(value.flatten());
+                }
+
+                // Check for data race.
+                #[cfg(not(feature = "no_closure"))]
+                ensure_no_data_race(
+                    name,
+                    &arg_values.iter_mut().collect::<FnArgsVec<_>>(),
+                    false,
+                )?;
+
+                let args = arg_values;
+                global.tail_call = Some(crate::func::TailCall { fn_def, args, pos }.into());
+
+                // Unwind the body of the current function
+                return Err(ERR::Return(Dynamic::UNIT, pos).into());
+            }
+        }
+
         // Normal function call
         let (first_arg, rest_args) = args.split_first().map_or_else(
             || (None, args.as_ref()),
             |(first, rest)| (Some(first), rest),
         );
 
+        let binding = expr.binding.as_deref();
+
         self.make_function_call(
             global, caches, scope, this_ptr, name, op_token, first_arg, rest_args, *hashes,
-            *capture, pos,
+            binding, *capture, pos,
         )
     }
+
+    /// Call the native unary `!` operator on a (flattened) operand under Fast Operators mode.
+    pub(crate) fn eval_fast_unary_not(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        name: &str,
+        hashes: FnCallHashes,
+        mut value: Dynamic,
+        pos: Position,
+    ) -> RhaiResult {
+        if let Union::Bool(b, ..) = value.0 {
+            return Ok((!b).into());
+        }
+
+        let operand = &mut [&mut value];
+        let op_token = Some(&Token::Bang);
+
+        self.exec_fn_call(
+            global, caches, None, name, op_token, hashes, None, operand, false, false, pos,
+        )
+        .map(|(v, ..)| v)
+    }
+
+    /// Call a native binary operator on (flattened) operands under Fast Operators mode.
+    pub(crate) fn eval_fast_binary_op(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        name: &str,
+        op_token: &Token,
+        hashes: FnCallHashes,
+        mut lhs: Dynamic,
+        mut rhs: Dynamic,
+        pos: Position,
+    ) -> RhaiResult {
+        #[allow(clippy::wildcard_imports)]
+        use Token::*;
+
+        // For extremely simple primary data operations, do it directly
+        // to avoid the overhead of calling a function.
+        match (&lhs.0, &rhs.0) {
+            (Union::Unit(..), Union::Unit(..)) => match op_token {
+                EqualsTo => return Ok(Dynamic::TRUE),
+                NotEqualsTo | GreaterThan | GreaterThanEqualsTo | LessThan | LessThanEqualsTo => {
+                    return Ok(Dynamic::FALSE)
+                }
+                _ => (),
+            },
+            (Union::Bool(b1, ..), Union::Bool(b2, ..)) => match op_token {
+                EqualsTo => return Ok((b1 == b2).into()),
+                NotEqualsTo => return Ok((b1 != b2).into()),
+                GreaterThan | GreaterThanEqualsTo | LessThan | LessThanEqualsTo => {
+                    return Ok(Dynamic::FALSE)
+                }
+                Pipe => return Ok((*b1 || *b2).into()),
+                Ampersand => return Ok((*b1 && *b2).into()),
+                _ => (),
+            },
+            (Union::Int(n1, ..), Union::Int(n2, ..)) => {
+                #[cfg(not(feature = "unchecked"))]
+                #[allow(clippy::wildcard_imports)]
+                use crate::packages::arithmetic::arith_basic::INT::functions::*;
+
+                #[cfg(not(feature = "unchecked"))]
+                match op_token {
+                    EqualsTo => return Ok((n1 == n2).into()),
+                    NotEqualsTo => return Ok((n1 != n2).into()),
+                    GreaterThan => return Ok((n1 > n2).into()),
+                    GreaterThanEqualsTo => return Ok((n1 >= n2).into()),
+                    LessThan => return Ok((n1 < n2).into()),
+                    LessThanEqualsTo => return Ok((n1 <= n2).into()),
+                    Plus => return add(*n1, *n2).map(Into::into),
+                    Minus => return subtract(*n1, *n2).map(Into::into),
+                    Multiply => return multiply(*n1, *n2).map(Into::into),
+                    Divide => return divide(*n1, *n2).map(Into::into),
+                    Modulo => return modulo(*n1, *n2).map(Into::into),
+                    _ => (),
+                }
+                #[cfg(feature = "unchecked")]
+                match op_token {
+                    EqualsTo => return Ok((n1 == n2).into()),
+                    NotEqualsTo => return Ok((n1 != n2).into()),
+                    GreaterThan => return Ok((n1 > n2).into()),
+                    GreaterThanEqualsTo => return Ok((n1 >= n2).into()),
+                    LessThan => return Ok((n1 < n2).into()),
+                    LessThanEqualsTo => return Ok((n1 <= n2).into()),
+                    Plus => return Ok((n1 + n2).into()),
+                    Minus => return Ok((n1 - n2).into()),
+                    Multiply => return Ok((n1 * n2).into()),
+                    Divide => return Ok((n1 / n2).into()),
+                    Modulo => return Ok((n1 % n2).into()),
+                    _ => (),
+                }
+            }
+            #[cfg(not(feature = "no_float"))]
+            (Union::Float(f1, ..), Union::Float(f2, ..)) => match op_token {
+                #[cfg(feature = "unchecked")]
+                EqualsTo => return Ok((**f1 == **f2).into()),
+                #[cfg(not(feature = "unchecked"))]
+                EqualsTo => return Ok(((**f1 - **f2).abs() <= FLOAT::EPSILON).into()),
+                #[cfg(feature = "unchecked")]
+                NotEqualsTo => return Ok((**f1 != **f2).into()),
+                #[cfg(not(feature = "unchecked"))]
+                NotEqualsTo => return Ok(((**f1 - **f2).abs() > FLOAT::EPSILON).into()),
+                GreaterThan => return Ok((**f1 > **f2).into()),
+                GreaterThanEqualsTo => return Ok((**f1 >= **f2).into()),
+                LessThan => return Ok((**f1 < **f2).into()),
+                LessThanEqualsTo => return Ok((**f1 <= **f2).into()),
+                Plus => return Ok((**f1 + **f2).into()),
+                Minus => return Ok((**f1 - **f2).into()),
+                Multiply => return Ok((**f1 * **f2).into()),
+                Divide => return Ok((**f1 / **f2).into()),
+                Modulo => return Ok((**f1 % **f2).into()),
+                _ => (),
+            },
+            #[cfg(not(feature = "no_float"))]
+            (Union::Float(f1, ..), Union::Int(n2, ..)) => match op_token {
+                #[cfg(feature = "unchecked")]
+                EqualsTo => return Ok((**f1 == (*n2 as FLOAT)).into()),
+                #[cfg(not(feature = "unchecked"))]
+                EqualsTo => return Ok(((**f1 - (*n2 as FLOAT)).abs() <= FLOAT::EPSILON).into()),
+                #[cfg(feature = "unchecked")]
+                NotEqualsTo => return Ok((**f1 != (*n2 as FLOAT)).into()),
+                #[cfg(not(feature = "unchecked"))]
+                NotEqualsTo => return Ok(((**f1 - (*n2 as FLOAT)).abs() > FLOAT::EPSILON).into()),
+                GreaterThan => return Ok((**f1 > (*n2 as FLOAT)).into()),
+                GreaterThanEqualsTo => return Ok((**f1 >= (*n2 as FLOAT)).into()),
+                LessThan => return Ok((**f1 < (*n2 as FLOAT)).into()),
+                LessThanEqualsTo => return Ok((**f1 <= (*n2 as FLOAT)).into()),
+                Plus => return Ok((**f1 + (*n2 as FLOAT)).into()),
+                Minus => return Ok((**f1 - (*n2 as FLOAT)).into()),
+                Multiply => return Ok((**f1 * (*n2 as FLOAT)).into()),
+                Divide => return Ok((**f1 / (*n2 as FLOAT)).into()),
+                Modulo => return Ok((**f1 % (*n2 as FLOAT)).into()),
+                _ => (),
+            },
+            #[cfg(not(feature = "no_float"))]
+            (Union::Int(n1, ..), Union::Float(f2, ..)) => match op_token {
+                #[cfg(feature = "unchecked")]
+                EqualsTo => return Ok(((*n1 as FLOAT) == **f2).into()),
+                #[cfg(not(feature = "unchecked"))]
+                EqualsTo => return Ok((((*n1 as FLOAT) - **f2).abs() <= FLOAT::EPSILON).into()),
+                #[cfg(feature = "unchecked")]
+                NotEqualsTo => return Ok(((*n1 as FLOAT) != **f2).into()),
+                #[cfg(not(feature = "unchecked"))]
+                NotEqualsTo => return Ok((((*n1 as FLOAT) - **f2).abs() > FLOAT::EPSILON).into()),
+                GreaterThan => return Ok(((*n1 as FLOAT) > **f2).into()),
+                GreaterThanEqualsTo => return Ok(((*n1 as FLOAT) >= **f2).into()),
+                LessThan => return Ok(((*n1 as FLOAT) < **f2).into()),
+                LessThanEqualsTo => return Ok(((*n1 as FLOAT) <= **f2).into()),
+                Plus => return Ok(((*n1 as FLOAT) + **f2).into()),
+                Minus => return Ok(((*n1 as FLOAT) - **f2).into()),
+                Multiply => return Ok(((*n1 as FLOAT) * **f2).into()),
+                Divide => return Ok(((*n1 as FLOAT) / **f2).into()),
+                Modulo => return Ok(((*n1 as FLOAT) % **f2).into()),
+                _ => (),
+            },
+            (Union::Str(s1, ..), Union::Str(s2, ..)) => match op_token {
+                EqualsTo => return Ok((s1 == s2).into()),
+                NotEqualsTo => return Ok((s1 != s2).into()),
+                GreaterThan => return Ok((s1 > s2).into()),
+                GreaterThanEqualsTo => return Ok((s1 >= s2).into()),
+                LessThan => return Ok((s1 < s2).into()),
+                LessThanEqualsTo => return Ok((s1 <= s2).into()),
+                Plus => {
+                    #[cfg(not(feature = "unchecked"))]
+                    self.throw_on_size((0, 0, s1.len() + s2.len()))?;
+                    return Ok((s1 + s2).into());
+                }
+                Minus => return Ok((s1 - s2).into()),
+                _ => (),
+            },
+            (Union::Char(c1, ..), Union::Char(c2, ..)) => match op_token {
+                EqualsTo => return Ok((c1 == c2).into()),
+                NotEqualsTo => return Ok((c1 != c2).into()),
+                GreaterThan => return Ok((c1 > c2).into()),
+                GreaterThanEqualsTo => return Ok((c1 >= c2).into()),
+                LessThan => return Ok((c1 < c2).into()),
+                LessThanEqualsTo => return Ok((c1 <= c2).into()),
+                Plus => {
+                    let mut result = SmartString::new_const();
+                    result.push(*c1);
+                    result.push(*c2);
+
+                    #[cfg(not(feature = "unchecked"))]
+                    self.throw_on_size((0, 0, result.len()))?;
+
+                    return Ok(result.into());
+                }
+                _ => (),
+            },
+            (Union::Variant(..), _) | (_, Union::Variant(..)) => (),
+            _ => {
+                if let Some((func, need_context)) = get_builtin_binary_op_fn(op_token, &lhs, &rhs) {
+                    // We may not need to bump the level because built-in's do not need it.
+                    //defer! { let orig_level = global.level; global.level += 1 }
+
+                    let context = need_context.then(|| (self, name, None, &*global, pos).into());
+                    return func(context, &mut [&mut lhs, &mut rhs]);
+                }
+            }
+        }
+
+        let operands = &mut [&mut lhs, &mut rhs];
+        let op_token = Some(op_token);
+
+        self.exec_fn_call(
+            global, caches, None, name, op_token, hashes, None, operands, false, false, pos,
+        )
+        .map(|(v, ..)| v)
+    }
 }
@@ -435,6 +435,7 @@ impl<'a> NativeCallContext<'a> {
                     fn_name,
                     op_token.as_ref(),
                     calc_fn_hash(None, fn_name, args_len),
+                    None,
                     args,
                     is_ref_mut,
                     false,
@@ -464,6 +465,7 @@ impl<'a> NativeCallContext<'a> {
                 fn_name,
                 op_token.as_ref(),
                 hash,
+                None,
                 args,
                 is_ref_mut,
                 is_method_call,
@@ -4,10 +4,57 @@
 use super::call::FnCallArgs;
 use crate::ast::{EncapsulatedEnviron, ScriptFuncDef};
 use crate::eval::{Caches, GlobalRuntimeState};
-use crate::{Dynamic, Engine, Position, RhaiResult, Scope, ERR};
+use crate::{
+    Dynamic, Engine, FnArgsVec, Generator, ImmutableString, Position, RhaiResult, RhaiResultOf,
+    Scope, Shared, StaticVec, ERR,
+};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
+/// A call to a script-defined function in tail position, made in place of the function whose body
+/// contains it.
+#[derive(Debug, Clone)]
+pub(crate) struct TailCall {
+    /// Script-defined function to call.
+    pub fn_def: Shared<ScriptFuncDef>,
+    /// Argument values.
+    pub args: FnArgsVec<Dynamic>,
+    /// [Position] of the call.
+    pub pos: Position,
+}
+
+/// Record the return type of a script-defined function replaced by a tail call to another one,
+/// unless the other function has the same return type.
+pub(crate) fn add_tail_call_return_type(
+    return_types: &mut StaticVec<ImmutableString>,
+    fn_def: &ScriptFuncDef,
+    next_fn_def: &ScriptFuncDef,
+) {
+    if let Some(ref typ) = fn_def.return_type {
+        if next_fn_def.return_type.as_ref() != Some(typ) && !return_types.contains(typ) {
+            return_types.push(typ.clone());
+        }
+    }
+}
+
+/// Put the arguments of a call to a script-defined function into a [`Scope`] as variables,
+/// keeping the type annotations of the parameters.
+pub(crate) fn push_fn_params(
+    scope: &mut Scope,
+    fn_def: &ScriptFuncDef,
+    args: impl IntoIterator<Item = Dynamic>,
+) {
+    let scope_len = scope.len();
+
+    scope.extend(fn_def.params.iter().cloned().zip(args));
+
+    for (i, typ) in fn_def.param_types.iter().enumerate() {
+        if typ.is_some() {
+            scope.set_type_by_index(scope_len + i, typ.clone());
+        }
+    }
+}
+
 impl Engine {
     /// # Main Entry-Point
     ///
@@ -27,28 +74,50 @@ impl Engine {
         caches: &mut Caches,
         scope: &mut Scope,
         mut this_ptr: Option<&mut Dynamic>,
-        _environ: Option<&EncapsulatedEnviron>,
-        fn_def: &ScriptFuncDef,
+        _environ: Option<&Shared<EncapsulatedEnviron>>,
+        fn_def: &Shared<ScriptFuncDef>,
         args: &mut FnCallArgs,
         rewind_scope: bool,
         pos: Position,
     ) -> RhaiResult {
         debug_assert_eq!(fn_def.params.len(), args.len());
 
-        self.track_operation(global, pos)?;
+        let level = global.level;
+        self.check_script_fn_call(global, fn_def, args.iter().map(|v| &**v), level, pos)?;
 
-        // Check for stack overflow
-        #[cfg(not(feature = "unchecked"))]
-        if global.level > self.max_call_levels() {
-            return Err(ERR::ErrorStackOverflow(pos).into());
+        // Record the call in the profiler
+        #[cfg(feature = "profiling")]
+        let orig_profile_len = self.enter_fn_profile(global, fn_def);
+        #[cfg(feature = "profiling")]
+        defer! { global => move |g| g.rewind_profile(orig_profile_len) }
+
+        // Calling a generator function only creates a generator
+        if fn_def.is_generator {
+            self.check_fn_params_len(0, fn_def, pos)?;
+
+            let mut fn_scope = Scope::new();
+            push_fn_params(&mut fn_scope, fn_def, args.iter_mut().map(|v| v.take()));
+
+            let this_ptr = this_ptr.as_deref().cloned();
+            let generator = Generator::new(fn_def.clone(), _environ.cloned(), this_ptr, fn_scope);
+            let value = Dynamic::from(generator);
+
+            // Check return type
+            if let Some(ref typ) = fn_def.return_type {
+                self.check_type_annotation(&value, typ, pos)?;
+            }
+            return Ok(value);
         }
 
         #[cfg(feature = "debugging")]
-        if self.debugger_interface.is_none() && fn_def.body.is_empty() {
+        if self.debugger_interface.is_none()
+            && fn_def.body.is_empty()
+            && fn_def.return_type.is_none()
+        {
             return Ok(Dynamic::UNIT);
         }
         #[cfg(not(feature = "debugging"))]
-        if fn_def.body.is_empty() {
+        if fn_def.body.is_empty() && fn_def.return_type.is_none() {
             return Ok(Dynamic::UNIT);
         }
 
@@ -57,124 +126,137 @@ impl Engine {
         #[cfg(not(feature = "no_module"))]
         let orig_imports_len = global.num_imports();
 
-        #[cfg(feature = "debugging")]
-        let orig_call_stack_len = global
-            .debugger
-            .as_ref()
-            .map_or(0, |dbg| dbg.call_stack().len());
-
-        // Guard against too many variables
-        #[cfg(not(feature = "unchecked"))]
-        if scope.len() + fn_def.params.len() > self.max_variables() {
-            return Err(ERR::ErrorTooManyVariables(pos).into());
-        }
+        self.check_fn_params_len(scope.len(), fn_def, pos)?;
 
         // Put arguments into scope as variables
-        scope.extend(fn_def.params.iter().cloned().zip(args.iter_mut().map(|v| {
-            // Actually consume the arguments instead of cloning them
-            v.take()
-        })));
+        push_fn_params(
+            scope,
+            fn_def,
+            args.iter_mut().map(|v| {
+                // Actually consume the arguments instead of cloning them
+                v.take()
+            }),
+        );
 
         // Push a new call stack frame
         #[cfg(feature = "debugging")]
-        if self.is_debugger_registered() {
-            let fn_name = fn_def.name.clone();
-            let args = scope
-                .iter_inner()
-                .skip(orig_scope_len)
-                .map(|(.., v)| v.flatten_clone());
-            let source = global.source.clone();
-
-            global
-                .debugger_mut()
-                .push_call_stack_frame(fn_name, args, source, pos);
-        }
+        let orig_call_stack_len =
+            self.push_fn_call_stack_frame(global, fn_def, scope, orig_scope_len, pos);
 
         // Merge in encapsulated environment, if any
         let orig_fn_resolution_caches_len = caches.fn_resolution_caches_len();
 
         #[cfg(not(feature = "no_module"))]
-        let orig_constants = _environ.map(|environ| {
-            let EncapsulatedEnviron {
-                lib,
-                imports,
-                constants,
-            } = environ;
+        let orig_constants = self.enter_fn_environ(global, _environ);
 
-            imports
-                .iter()
-                .cloned()
-                .for_each(|(n, m)| global.push_import(n, m));
+        // Tail calls replace the function call within the same environment
+        let body_lib_len = global.lib.len();
+        #[cfg(not(feature = "no_module"))]
+        let body_imports_len = global.num_imports();
+        let body_fn_resolution_caches_len = caches.fn_resolution_caches_len();
 
-            global.lib.push(lib.clone());
+        let orig_tail_call_level = global.tail_call_level;
+        global.tail_call_level =
+            (rewind_scope && self.tail_call_optimization()).then_some(global.level);
 
-            std::mem::replace(&mut global.constants, constants.clone())
-        });
+        let mut fn_def = fn_def;
+        let mut tail_fn_def;
+        // Return types of functions replaced by tail calls
+        let mut return_types = StaticVec::<ImmutableString>::new_const();
 
-        #[cfg(feature = "debugging")]
-        if self.is_debugger_registered() {
-            let node = crate::ast::Stmt::Noop(fn_def.body.position());
-            self.run_debugger(global, caches, scope, this_ptr.as_deref_mut(), &node)?;
-        }
+        let mut _result = loop {
+            #[cfg(feature = "debugging")]
+            if self.is_debugger_registered() {
+                let node = crate::ast::Stmt::Noop(fn_def.body.position());
+                if let Err(err) =
+                    self.run_debugger(global, caches, scope, this_ptr.as_deref_mut(), &node)
+                {
+                    break Err(err);
+                }
+            }
 
-        // Evaluate the function
-        let mut _result: RhaiResult = self
-            .eval_stmt_block(
+            // Evaluate the function
+            let statements = fn_def.body.statements();
+            #[cfg(not(any(feature = "stackless", feature = "bytecode")))]
+            let result = self.eval_stmt_block(
                 global,
                 caches,
                 scope,
                 this_ptr.as_deref_mut(),
-                fn_def.body.statements(),
+                statements,
                 rewind_scope,
-            )
-            .or_else(|err| match *err {
+            );
+            #[cfg(feature = "stackless")]
+            let result = self.run_stackless(
+                global,
+                caches,
+                scope,
+                this_ptr.as_deref_mut(),
+                statements,
+                rewind_scope,
+            );
+            #[cfg(all(feature = "bytecode", not(feature = "stackless")))]
+            let result = self.eval_bytecode(
+                global,
+                caches,
+                scope,
+                this_ptr.as_deref_mut(),
+                statements,
+                Some(fn_def),
+                rewind_scope,
+            );
+
+            let result = result.or_else(|err| match *err {
                 // Convert return statement to return value
                 ERR::Return(x, ..) => Ok(x),
-                // Exit value is passed straight-through
-                mut err @ ERR::Exit(..) => {
-                    err.set_position(pos);
-                    Err(err.into())
-                }
-                // System errors are passed straight-through
-                mut err if err.is_system_exception() => {
-                    err.set_position(pos);
-                    Err(err.into())
-                }
-                // Other errors are wrapped in `ErrorInFunctionCall`
-                _ => Err(ERR::ErrorInFunctionCall(
-                    fn_def.name.to_string(),
-                    #[cfg(not(feature = "no_module"))]
-                    _environ
-                        .and_then(|environ| environ.lib.id())
-                        .unwrap_or_else(|| global.source().unwrap_or(""))
-                        .to_string(),
-                    #[cfg(feature = "no_module")]
-                    global.source().unwrap_or("").to_string(),
-                    err,
-                    pos,
-                )
-                .into()),
+                _ => Err(self.make_fn_call_error(global, &fn_def.name, _environ, err, pos)),
             });
 
-        #[cfg(feature = "debugging")]
-        if self.is_debugger_registered() {
-            let trigger = match global.debugger_mut().status {
-                crate::eval::DebuggerStatus::FunctionExit(n) => n >= global.level,
-                crate::eval::DebuggerStatus::Next(.., true) => true,
-                _ => false,
+            let tail_call = match (global.tail_call.take(), result) {
+                (Some(tail_call), Ok(..)) => tail_call,
+                (.., result) => break result,
             };
 
-            if trigger {
-                let node = crate::ast::Stmt::Noop(fn_def.body.end_position().or_else(pos));
-                let node = (&node).into();
-                let event = match _result {
-                    Ok(ref r) => crate::eval::DebuggerEvent::FunctionExitWithValue(r),
-                    Err(ref err) => crate::eval::DebuggerEvent::FunctionExitWithError(err),
-                };
-                match self.run_debugger_raw(global, caches, scope, this_ptr, node, event) {
-                    Ok(_) => (),
-                    Err(err) => _result = Err(err),
-                }
+            // Replace the function call by the tail call
+            let TailCall {
+                fn_def: next_fn_def,
+                args: tail_args,
+                pos: tail_pos,
+            } = *tail_call;
+
+            add_tail_call_return_type(&mut return_types, fn_def, &next_fn_def);
+
+            scope.rewind(orig_scope_len);
+            global.lib.truncate(body_lib_len);
+            #[cfg(not(feature = "no_module"))]
+            global.truncate_imports(body_imports_len);
+            caches.rewind_fn_resolution_caches(body_fn_resolution_caches_len);
+
+            this_ptr = None;
+            tail_fn_def = next_fn_def;
+            fn_def = &tail_fn_def;
+
+            if let Err(err) = self.start_tail_call(global, scope, fn_def, tail_args, tail_pos) {
+                break Err(err);
+            }
+        };
+
+        global.tail_call_level = orig_tail_call_level;
+
+        // Check return type
+        if let Ok(ref value) = _result {
+            if let Err(err) = self.check_return_types(fn_def, &return_types, value, pos) {
+                _result = Err(err);
+            }
+        }
+
+        #[cfg(feature = "debugging")]
+        if self.is_debugger_registered() {
+            let result = _result.as_ref();
+            if let Err(err) =
+                self.run_debugger_on_fn_exit(global, caches, scope, this_ptr, fn_def, result, pos)
+            {
+                _result = Err(err);
             }
 
             // Pop the call stack
@@ -208,6 +290,269 @@ impl Engine {
         _result
     }
 
+    /// Check a call to a script-defined function, running at a certain call level, before its
+    /// arguments are put into the scope.
+    pub(crate) fn check_script_fn_call<'a>(
+        &self,
+        global: &mut GlobalRuntimeState,
+        fn_def: &ScriptFuncDef,
+        args: impl Iterator<Item = &'a Dynamic>,
+        level: usize,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        self.track_operation(global, pos)?;
+
+        // Check for stack overflow
+        #[cfg(not(feature = "unchecked"))]
+        if level > self.max_call_levels() {
+            return Err(ERR::ErrorStackOverflow(pos).into());
+        }
+        #[cfg(feature = "unchecked")]
+        let _ = level;
+
+        // Check parameter types
+        self.check_param_types(fn_def, args, pos)
+    }
+    /// Check that there is room in a scope, with a certain number of variables, for the parameters
+    /// of a script-defined function.
+    #[inline]
+    pub(crate) fn check_fn_params_len(
+        &self,
+        scope_len: usize,
+        fn_def: &ScriptFuncDef,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        // Guard against too many variables
+        #[cfg(not(feature = "unchecked"))]
+        if scope_len + fn_def.params.len() > self.max_variables() {
+            return Err(ERR::ErrorTooManyVariables(pos).into());
+        }
+        #[cfg(feature = "unchecked")]
+        let _ = (scope_len, fn_def, pos);
+
+        Ok(())
+    }
+    /// Check the types of arguments to a script-defined function against its parameter types.
+    pub(crate) fn check_param_types<'a>(
+        &self,
+        fn_def: &ScriptFuncDef,
+        args: impl Iterator<Item = &'a Dynamic>,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        fn_def
+            .param_types
+            .iter()
+            .zip(args)
+            .filter_map(|(typ, arg)| typ.as_ref().map(|typ| (typ, arg)))
+            .try_for_each(|(typ, arg)| self.check_type_annotation(arg, typ, pos))
+    }
+    /// Check the return value of a script-defined function against its return type, plus the
+    /// return types of the functions it replaced by tail calls.
+    pub(crate) fn check_return_types(
+        &self,
+        fn_def: &ScriptFuncDef,
+        return_types: &[ImmutableString],
+        value: &Dynamic,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        fn_def
+            .return_type
+            .iter()
+            .chain(return_types)
+            .try_for_each(|typ| self.check_type_annotation(value, typ, pos))
+    }
+    /// Record the call to a script-defined function in the profiler, returning the number of calls
+    /// in progress before it.
+    #[cfg(feature = "profiling")]
+    pub(crate) fn enter_fn_profile(
+        &self,
+        global: &mut GlobalRuntimeState,
+        fn_def: &ScriptFuncDef,
+    ) -> usize {
+        let orig_profile_len = global.profile_len();
+
+        if global.profiler.is_some() {
+            let fn_name = fn_def.name.clone();
+            let source = global.source.clone();
+            let def_pos = fn_def.body.position();
+            global.enter_profile(fn_name, fn_def.params.len(), true, source, def_pos);
+        }
+
+        orig_profile_len
+    }
+    /// Push a new call stack frame for a script-defined function, whose arguments are the
+    /// variables after `scope_len` in the scope, returning the length of the call stack before it.
+    #[cfg(feature = "debugging")]
+    pub(crate) fn push_fn_call_stack_frame(
+        &self,
+        global: &mut GlobalRuntimeState,
+        fn_def: &ScriptFuncDef,
+        scope: &Scope,
+        scope_len: usize,
+        pos: Position,
+    ) -> usize {
+        let orig_call_stack_len = global
+            .debugger
+            .as_ref()
+            .map_or(0, |dbg| dbg.call_stack().len());
+
+        if self.is_debugger_registered() {
+            let fn_name = fn_def.name.clone();
+            let args = scope
+                .iter_inner()
+                .skip(scope_len)
+                .map(|(.., v)| v.flatten_clone());
+            let source = global.source.clone();
+
+            global
+                .debugger_mut()
+                .push_call_stack_frame(fn_name, args, source, pos);
+        }
+
+        orig_call_stack_len
+    }
+    /// Run the debugger when a script-defined function exits, if it is stepping out of the
+    /// function.
+    #[cfg(feature = "debugging")]
+    pub(crate) fn run_debugger_on_fn_exit(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: Option<&mut Dynamic>,
+        fn_def: &ScriptFuncDef,
+        result: Result<&Dynamic, &crate::RhaiError>,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        if !self.is_debugger_registered() {
+            return Ok(());
+        }
+
+        let trigger = match global.debugger_mut().status {
+            crate::eval::DebuggerStatus::FunctionExit(n) => n >= global.level,
+            crate::eval::DebuggerStatus::Next(.., true) => true,
+            _ => false,
+        };
+
+        if trigger {
+            let node = crate::ast::Stmt::Noop(fn_def.body.end_position().or_else(pos));
+            let event = match result {
+                Ok(r) => crate::eval::DebuggerEvent::FunctionExitWithValue(r),
+                Err(err) => crate::eval::DebuggerEvent::FunctionExitWithError(err),
+            };
+            self.run_debugger_raw(global, caches, scope, this_ptr, (&node).into(), event)?;
+        }
+
+        Ok(())
+    }
+    /// Merge in the encapsulated environment of a script-defined function, if any, returning the
+    /// constants it replaces.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) fn enter_fn_environ(
+        &self,
+        global: &mut GlobalRuntimeState,
+        environ: Option<&Shared<EncapsulatedEnviron>>,
+    ) -> Option<Option<crate::eval::SharedGlobalConstants>> {
+        environ.map(|environ| {
+            let EncapsulatedEnviron {
+                lib,
+                imports,
+                constants,
+            } = &**environ;
+
+            imports
+                .iter()
+                .cloned()
+                .for_each(|(n, m)| global.push_import(n, m));
+
+            global.lib.push(lib.clone());
+
+            std::mem::replace(&mut global.constants, constants.clone())
+        })
+    }
+    /// Wrap an error raised inside a script-defined function.
+    pub(crate) fn make_fn_call_error(
+        &self,
+        global: &GlobalRuntimeState,
+        fn_name: &str,
+        _environ: Option<&Shared<EncapsulatedEnviron>>,
+        err: crate::RhaiError,
+        pos: Position,
+    ) -> crate::RhaiError {
+        match *err {
+            // Exit value is passed straight-through
+            mut err @ ERR::Exit(..) => {
+                err.set_position(pos);
+                err.into()
+            }
+            // System errors are passed straight-through
+            mut err if err.is_system_exception() => {
+                err.set_position(pos);
+                err.into()
+            }
+            // Other errors are wrapped in `ErrorInFunctionCall`
+            _ => ERR::ErrorInFunctionCall(
+                fn_name.to_string(),
+                #[cfg(not(feature = "no_module"))]
+                _environ
+                    .and_then(|environ| environ.lib.id())
+                    .unwrap_or_else(|| global.source().unwrap_or(""))
+                    .to_string(),
+                #[cfg(feature = "no_module")]
+                global.source().unwrap_or("").to_string(),
+                err,
+                pos,
+            )
+            .into(),
+        }
+    }
+    /// Set up a tail call to a script-defined function in the current call frame.
+    ///
+    /// The scope must already be rewound to the length before the replaced call.
+    fn start_tail_call(
+        &self,
+        global: &mut GlobalRuntimeState,
+        scope: &mut Scope,
+        fn_def: &ScriptFuncDef,
+        args: FnArgsVec<Dynamic>,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        // The call level does not change
+        let level = global.level;
+        self.check_script_fn_call(global, fn_def, args.iter(), level, pos)?;
+        self.check_fn_params_len(scope.len(), fn_def, pos)?;
+
+        // Replace the call stack frame
+        #[cfg(feature = "debugging")]
+        if self.is_debugger_registered() {
+            let fn_name = fn_def.name.clone();
+            let call_args = args.iter().map(Dynamic::flatten_clone);
+            let source = global.source.clone();
+            let keep_frames = self.keep_tail_call_frames();
+            let dbg = global.debugger_mut();
+
+            if !keep_frames {
+                dbg.rewind_call_stack(dbg.call_stack().len().saturating_sub(1));
+            }
+            dbg.push_call_stack_frame(fn_name, call_args, source, pos);
+        }
+
+        // Replace the call in the profiler
+        #[cfg(feature = "profiling")]
+        if global.profiler.is_some() {
+            let fn_name = fn_def.name.clone();
+            let source = global.source.clone();
+            let def_pos = fn_def.body.position();
+            global.rewind_profile(global.profile_len().saturating_sub(1));
+            global.enter_profile(fn_name, fn_def.params.len(), true, source, def_pos);
+        }
+
+        // Put arguments into scope as variables
+        push_fn_params(scope, fn_def, args);
+
+        Ok(())
+    }
+
     // Does a script-defined function exist?
     ///
     /// # Note
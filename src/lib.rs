@@ -232,24 +232,31 @@ pub use api::custom_syntax::Expression;
 #[cfg(not(feature = "no_std"))]
 #[cfg(any(not(target_family = "wasm"), not(target_os = "unknown")))]
 pub use api::files::{eval_file, run_file};
+#[cfg(not(feature = "no_position"))]
+pub use api::highlight::{HighlightKind, HighlightSpan};
+pub use api::lint::{LintCode, LintWarning};
 pub use api::{eval::eval, run::run};
 pub use ast::{FnAccess, AST};
 use defer::Deferred;
 pub use engine::{Engine, OP_CONTAINS, OP_EQUALS};
 pub use eval::EvalContext;
+#[cfg(feature = "profiling")]
+pub use eval::{ProfileEntry, Profiler};
 #[cfg(not(feature = "no_function"))]
 #[cfg(not(feature = "no_object"))]
 use func::calc_typed_method_hash;
 use func::{calc_fn_hash, calc_fn_hash_full, calc_var_hash};
-pub use func::{plugin, FuncArgs, NativeCallContext, RhaiNativeFunc};
+pub use func::{plugin, FuncArgs, NativeCallContext, RhaiAsyncFunc, RhaiNativeFunc};
 pub use module::{FnNamespace, FuncRegistration, Module};
 pub use packages::string_basic::{FUNC_TO_DEBUG, FUNC_TO_STRING};
 pub use rhai_codegen::*;
+#[cfg(not(feature = "no_function"))]
+pub use types::Generator;
 #[cfg(not(feature = "no_time"))]
 pub use types::Instant;
 pub use types::{
-    Dynamic, EvalAltResult, FnPtr, ImmutableString, LexError, ParseError, ParseErrorType, Position,
-    Scope, VarDefInfo,
+    Continuation, Dynamic, EvalAltResult, EvalOutcome, FnPtr, ImmutableString, LexError,
+    ParseError, ParseErrorType, Position, Scope, Suspender, VarDefInfo,
 };
 
 /// _(debugging)_ Module containing types for debugging.
@@ -258,7 +265,26 @@ pub use types::{
 pub mod debugger {
     #[cfg(not(feature = "no_function"))]
     pub use super::eval::CallStackFrame;
-    pub use super::eval::{BreakPoint, Debugger, DebuggerCommand, DebuggerEvent};
+    pub use super::eval::{
+        BreakPoint, BreakPointOptions, Debugger, DebuggerCommand, DebuggerEvent, HitCondition,
+        WatchPoint,
+    };
+    #[cfg(not(feature = "no_position"))]
+    pub use super::eval::{BranchCoverage, BranchKind, Coverage, FileCoverage};
+    #[cfg(not(feature = "no_position"))]
+    pub use super::eval::{Trace, TraceCall, TraceRecorder, TraceStep, TraceWrite};
+}
+
+/// Module containing types for running tests written in Rhai scripts.
+///
+/// Not available under `no_function` or `no_std`.
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_std"))]
+#[cfg(any(not(target_family = "wasm"), not(target_os = "unknown")))]
+pub mod testing {
+    pub use super::api::testing::{
+        OnCreateTestEngine, TestCase, TestKind, TestOutcome, TestReport, TestResult, TestRunner,
+    };
 }
 
 /// _(internals)_ An identifier in Rhai.
@@ -342,8 +368,8 @@ pub use types::{BloomFilterU64, CustomTypeInfo, Span, StringsInterner};
 #[cfg(feature = "internals")]
 pub use tokenizer::{
     get_next_token, is_valid_function_name, is_valid_identifier, parse_string_literal, InputStream,
-    MultiInputsStream, Token, TokenIterator, TokenizeState, TokenizerControl,
-    TokenizerControlBlock,
+    MultiInputsStream, RecordedToken, Token, TokenIterator, TokenRecorder, TokenizeState,
+    TokenizerControl, TokenizerControlBlock,
 };
 
 #[cfg(feature = "internals")]
@@ -354,9 +380,9 @@ pub use api::default_limits;
 
 #[cfg(feature = "internals")]
 pub use ast::{
-    ASTFlags, ASTNode, BinaryExpr, EncapsulatedEnviron, Expr, FlowControl, FnCallExpr,
-    FnCallHashes, Ident, OpAssignment, RangeCase, ScriptFuncDef, Stmt, StmtBlock,
-    SwitchCasesCollection,
+    ASTFlags, ASTNode, BinaryExpr, EncapsulatedEnviron, Expr, FlowControl, FnCallBinding,
+    FnCallExpr, FnCallHashes, Ident, OpAssignment, Pattern, RangeCase, ScriptFuncDef, Stmt,
+    StmtBlock, SwitchCasesCollection,
 };
 
 #[cfg(feature = "internals")]
@@ -378,6 +404,14 @@ pub use func::{locked_read, locked_write, NativeCallContextStore, RhaiFunc};
 #[cfg(feature = "metadata")]
 pub use api::definitions::Definitions;
 
+#[cfg(feature = "internals")]
+#[cfg(feature = "metadata")]
+pub use api::docs::Documentation;
+
+#[cfg(feature = "metadata")]
+#[cfg(not(feature = "no_position"))]
+pub use api::completion::{Completion, CompletionKind};
+
 /// Number of items to keep inline for [`StaticVec`].
 const STATIC_VEC_INLINE_SIZE: usize = 3;
 
@@ -470,6 +504,10 @@ compile_error!("`f32_float` cannot be used with `no_float`");
 #[cfg(feature = "only_i64")]
 compile_error!("`only_i32` and `only_i64` cannot be used together");
 
+#[cfg(feature = "no_std")]
+#[cfg(feature = "profiling")]
+compile_error!("`profiling` cannot be used with `no-std`");
+
 #[cfg(feature = "no_std")]
 #[cfg(feature = "wasm-bindgen")]
 compile_error!("`wasm-bindgen` cannot be used with `no-std`");
@@ -144,6 +144,7 @@ impl<'a> OptimizerState<'a> {
                 fn_name,
                 op_token,
                 calc_fn_hash(None, fn_name, arg_values.len()),
+                None,
                 &mut arg_values.iter_mut().collect::<FnArgsVec<_>>(),
                 false,
                 true,
@@ -222,6 +223,14 @@ fn optimize_stmt_block(
                 Stmt::Var(x, options, ..) => {
                     optimize_expr(&mut x.1, state, false);
 
+                    // Destructured variables are never constant-propagated
+                    if let Some(ref pattern) = x.4 {
+                        for var in pattern.variables() {
+                            state.push_var(var.name.clone(), None);
+                        }
+                        return;
+                    }
+
                     let value = if options.intersects(ASTFlags::CONSTANT) && x.1.is_constant() {
                         // constant literal
                         Some(Cow::Owned(x.1.get_literal_value().unwrap()))
@@ -514,7 +523,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
         }
 
         // switch const { ... }
-        Stmt::Switch(x, pos) if is_hashable_constant(&x.0) => {
+        Stmt::Switch(x, pos) if is_hashable_constant(&x.0) && x.1.patterns.is_empty() => {
             let (
                 match_expr,
                 SwitchCasesCollection {
@@ -522,6 +531,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                     cases,
                     ranges,
                     def_case,
+                    ..
                 },
             ) = &mut **x;
 
@@ -665,18 +675,29 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                     expressions,
                     cases,
                     ranges,
+                    patterns,
                     def_case,
-                    ..
                 },
             ) = &mut **x;
 
             optimize_expr(match_expr, state, false);
 
             // Optimize blocks
-            for b in &mut *expressions {
+            for (index, b) in expressions.iter_mut().enumerate() {
+                let orig_constants_len = state.variables.len();
+
+                // Variables bound by a pattern shadow any constants
+                if let Some((pattern, ..)) = patterns.iter().find(|(_, n)| *n == index) {
+                    for var in pattern.variables() {
+                        state.push_var(var.name.clone(), None);
+                    }
+                }
+
                 optimize_expr(&mut b.lhs, state, false);
                 optimize_expr(&mut b.rhs, state, false);
 
+                state.rewind_var(orig_constants_len);
+
                 if matches!(b.lhs, Expr::BoolConstant(false, ..)) && !b.rhs.is_unit() {
                     b.rhs = Expr::Unit(b.rhs.position());
                     state.set_dirty();
@@ -722,6 +743,16 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                 }
             });
 
+            // Remove false patterns
+            patterns.retain(|(_, index)| {
+                if matches!(expressions[*index].lhs, Expr::BoolConstant(false, ..)) {
+                    state.set_dirty();
+                    false
+                } else {
+                    true
+                }
+            });
+
             if let Some(index) = def_case {
                 optimize_expr(&mut expressions[*index].rhs, state, false);
             }
@@ -731,6 +762,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                 if *def_case != Some(index)
                     && cases.values().flat_map(|c| c.iter()).all(|&n| n != index)
                     && ranges.iter().all(|r| r.index() != index)
+                    && patterns.iter().all(|(_, n)| *n != index)
                     && !b.rhs.is_unit()
                 {
                     b.rhs = Expr::Unit(b.rhs.position());
@@ -854,6 +886,10 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
         // return expr;
         Stmt::Return(Some(ref mut expr), ..) => optimize_expr(expr, state, false),
 
+        // yield expr;
+        #[cfg(not(feature = "no_function"))]
+        Stmt::Yield(Some(ref mut expr), ..) => optimize_expr(expr, state, false),
+
         // Share nothing
         #[cfg(not(feature = "no_closure"))]
         Stmt::Share(x) if x.is_empty() => {
@@ -1396,6 +1432,10 @@ impl Engine {
                 let statements = fn_def.body.take_statements();
                 *fn_def.body.statements_mut() =
                     self.optimize_top_level(statements, scope, lib2, optimization_level);
+                // Optimization may leave calls in new tail positions
+                if !fn_def.is_generator {
+                    crate::parser::mark_tail_calls(fn_def.body.statements_mut(), true);
+                }
                 fn_def.into()
             }))
             .into()
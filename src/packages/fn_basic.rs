@@ -9,6 +9,9 @@ def_package! {
         lib.set_standard_lib(true);
 
         combine_with_exported_module!(lib, "FnPtr", fn_ptr_functions);
+
+        #[cfg(not(feature = "no_function"))]
+        combine_with_exported_module!(lib, "Generator", generator_functions);
     }
 }
 
@@ -45,3 +48,56 @@ mod fn_ptr_functions {
         fn_ptr.is_anonymous()
     }
 }
+
+#[cfg(not(feature = "no_function"))]
+#[export_module]
+mod generator_functions {
+    use crate::{Dynamic, Generator, RhaiResultOf};
+
+    /// Run the generator up to its next `yield` statement and return the yielded value.
+    ///
+    /// Return `()` if the generator has finished.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// fn count(n) {
+    ///     for i in 0..n { yield i; }
+    /// }
+    ///
+    /// let g = count(2);
+    ///
+    /// print(g.next());        // prints 0
+    /// print(g.next());        // prints 1
+    /// print(g.next());        // prints ""
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn next(ctx: NativeCallContext, generator: &mut Generator) -> RhaiResultOf<Dynamic> {
+        generator.resume(&ctx).map(Option::unwrap_or_default)
+    }
+    /// Return `true` if the generator has finished.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// fn one() { yield 1; }
+    ///
+    /// let g = one();
+    ///
+    /// print(g.is_done);       // prints false
+    ///
+    /// g.next();
+    /// g.next();
+    ///
+    /// print(g.is_done);       // prints true
+    /// ```
+    #[rhai_fn(name = "is_done", get = "is_done", pure)]
+    pub fn is_done(generator: &mut Generator) -> bool {
+        generator.is_done()
+    }
+    /// Return the name of the generator function.
+    #[rhai_fn(name = "name", get = "name", pure)]
+    pub fn name(generator: &mut Generator) -> ImmutableString {
+        generator.name.clone()
+    }
+}
@@ -2,12 +2,12 @@
 
 use crate::api::options::LangOptions;
 use crate::ast::{
-    ASTFlags, BinaryExpr, CaseBlocksList, Expr, FlowControl, FnCallExpr, FnCallHashes, Ident,
-    Namespace, OpAssignment, RangeCase, ScriptFuncDef, Stmt, StmtBlock, StmtBlockContainer,
-    SwitchCasesCollection,
+    ASTFlags, ASTNode, BinaryExpr, CaseBlocksList, Expr, FlowControl, FnCallBinding, FnCallExpr,
+    FnCallHashes, Ident, Namespace, OpAssignment, Pattern, RangeCase, ScriptFuncDef, Stmt,
+    StmtBlock, StmtBlockContainer, SwitchCasesCollection,
 };
 use crate::engine::{Precedence, OP_CONTAINS, OP_NOT};
-use crate::eval::{Caches, GlobalRuntimeState};
+use crate::eval::{Caches, FnResolutionCacheEntry, GlobalRuntimeState};
 use crate::func::{hashing::get_hasher, StraightHashMap};
 use crate::tokenizer::{
     is_reserved_keyword_or_symbol, is_valid_function_name, is_valid_identifier, Token, TokenStream,
@@ -26,6 +26,7 @@ use bitflags::bitflags;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{
+    collections::{BTreeMap, BTreeSet},
     convert::TryFrom,
     fmt,
     hash::{Hash, Hasher},
@@ -40,6 +41,9 @@ type FnLib = StraightHashMap<Shared<ScriptFuncDef>>;
 /// Invalid variable name that acts as a search barrier in a [`Scope`].
 const SCOPE_SEARCH_BARRIER_MARKER: &str = "$ BARRIER $";
 
+/// Precedence of the pipeline operator `|>`, lower than all standard binary operators.
+const PIPE_PRECEDENCE: Option<Precedence> = Precedence::new(20);
+
 impl PERR {
     /// Make a [`ParseError`] using the current type and position.
     #[cold]
@@ -74,6 +78,9 @@ pub struct ParseState<'a, 't, 's, 'f> {
     /// Tracks a list of external variables (variables that are not explicitly declared in the scope).
     #[cfg(not(feature = "no_closure"))]
     pub external_vars: ThinVec<Ident>,
+    /// Type annotations of variables in enclosing scopes that can be captured by closures.
+    #[cfg(not(feature = "no_closure"))]
+    pub external_var_types: BTreeMap<ImmutableString, ImmutableString>,
     /// An indicator that, when set to `false`, disables variable capturing into externals one
     /// single time up until the nearest consumed Identifier token.
     ///
@@ -88,6 +95,8 @@ pub struct ParseState<'a, 't, 's, 'f> {
     /// List of globally-imported [module][crate::Module] names.
     #[cfg(not(feature = "no_module"))]
     pub global_imports: ThinVec<ImmutableString>,
+    /// Native Rust functions in the global namespace, keyed by hash, for binding function calls.
+    pub fn_bindings: Option<BTreeMap<u64, Shared<FnCallBinding>>>,
     /// Unused dummy field.
     #[cfg(feature = "no_function")]
     pub dummy: &'f (),
@@ -108,6 +117,7 @@ impl fmt::Debug for ParseState<'_, '_, '_, '_> {
 
         #[cfg(not(feature = "no_closure"))]
         f.field("external_vars", &self.external_vars)
+            .field("external_var_types", &self.external_var_types)
             .field("allow_capture", &self.allow_capture);
 
         #[cfg(not(feature = "no_module"))]
@@ -140,6 +150,8 @@ impl<'a, 't, 's, 'f> ParseState<'a, 't, 's, 'f> {
             expr_filter: |_| true,
             #[cfg(not(feature = "no_closure"))]
             external_vars: ThinVec::new(),
+            #[cfg(not(feature = "no_closure"))]
+            external_var_types: BTreeMap::new(),
             allow_capture: true,
             interned_strings,
             external_constants,
@@ -150,6 +162,7 @@ impl<'a, 't, 's, 'f> ParseState<'a, 't, 's, 'f> {
             imports: ThinVec::new(),
             #[cfg(not(feature = "no_module"))]
             global_imports: ThinVec::new(),
+            fn_bindings: None,
         }
     }
 
@@ -220,6 +233,28 @@ impl<'a, 't, 's, 'f> ParseState<'a, 't, 's, 'f> {
         (index, is_func_name)
     }
 
+    /// Get the type annotations of all variables visible to a closure defined at this point,
+    /// keyed by variable name.
+    #[cfg(not(feature = "no_closure"))]
+    #[must_use]
+    pub fn visible_var_types(&self) -> BTreeMap<ImmutableString, ImmutableString> {
+        let mut types = self.external_var_types.clone();
+
+        for (index, (name, ..)) in self.stack.iter_inner().enumerate() {
+            match self.stack.get_type_by_index(index) {
+                Some(typ) => {
+                    types.insert(name.clone(), typ.clone());
+                }
+                None if types.is_empty() => (),
+                None => {
+                    types.remove(name);
+                }
+            }
+        }
+
+        types
+    }
+
     /// Find a module by name in the [`ParseState`], searching in reverse.
     ///
     /// Returns the offset to be deducted from `Stack::len`,
@@ -552,6 +587,305 @@ fn parse_var_name(input: &mut TokenStream) -> ParseResult<(SmartString, Position
     }
 }
 
+/// Get the interned name of a type, mapping `int` and `float` to the actual types.
+fn get_interned_type_name(state: &mut ParseState, name: &str) -> ImmutableString {
+    match name {
+        "int" => state.get_interned_string(std::any::type_name::<crate::INT>()),
+        #[cfg(not(feature = "no_float"))]
+        "float" => state.get_interned_string(std::any::type_name::<crate::FLOAT>()),
+        _ => state.get_interned_string(name),
+    }
+}
+
+/// Is the name the short name of a standard type (e.g. `int`)?
+///
+/// Such names are type patterns when nested inside `switch` case patterns.
+fn is_standard_type_name(name: &str) -> bool {
+    match name {
+        "int" | "bool" | "char" | "string" | "Fn" | "range" => true,
+        #[cfg(not(feature = "no_float"))]
+        "float" => true,
+        #[cfg(feature = "decimal")]
+        "decimal" => true,
+        #[cfg(not(feature = "no_index"))]
+        "array" | "blob" => true,
+        #[cfg(not(feature = "no_object"))]
+        "map" => true,
+        #[cfg(not(feature = "no_time"))]
+        "timestamp" => true,
+        _ => false,
+    }
+}
+
+/// Is the token the `->` symbol?
+///
+/// `->` is a reserved symbol, so it is returned either as an error or as a custom symbol.
+#[cfg(not(feature = "no_function"))]
+fn is_arrow_symbol(token: &Token) -> bool {
+    match token {
+        Token::LexError(err) => matches!(**err, LexError::ImproperSymbol(ref s, ..) if s == "->"),
+        #[cfg(not(feature = "no_custom_syntax"))]
+        Token::Custom(s) => s.as_str() == "->",
+        _ => false,
+    }
+}
+
+/// Is the token the pipeline operator `|>`?
+///
+/// `|>` is a reserved symbol, so it is returned as such unless it is a custom operator or disabled.
+fn is_pipe_symbol(token: &Token) -> bool {
+    matches!(token, Token::Reserved(s) if s.as_str() == "|>")
+}
+
+/// Is the token the start of a destructuring pattern?
+fn is_pattern_start(token: &Token) -> bool {
+    match token {
+        #[cfg(not(feature = "no_index"))]
+        Token::LeftBracket => true,
+        #[cfg(not(feature = "no_object"))]
+        Token::MapStart => true,
+        _ => false,
+    }
+}
+
+/// Make sure that all variables in a pattern are unique.
+fn ensure_unique_variables(pattern: &Pattern) -> ParseResult<()> {
+    let vars = pattern.variables();
+
+    for (i, var) in vars.iter().enumerate() {
+        if vars[..i].iter().any(|v| v.name == var.name) {
+            return Err(PERR::DuplicatedVariable(var.name.to_string()).into_err(var.pos));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that a node does not contain any `yield` statement.
+#[cfg(not(feature = "no_function"))]
+fn ensure_no_yield(node: ASTNode) -> ParseResult<()> {
+    let mut yield_pos = None;
+
+    let on_node = &mut |path: &[ASTNode]| match path.last() {
+        Some(ASTNode::Stmt(Stmt::Yield(.., pos))) => {
+            yield_pos = Some(*pos);
+            false
+        }
+        _ => true,
+    };
+
+    match node {
+        ASTNode::Stmt(stmt) => stmt.walk(&mut Vec::new(), on_node),
+        ASTNode::Expr(expr) => expr.walk(&mut Vec::new(), on_node),
+    };
+
+    yield_pos.map_or(Ok(()), |pos| Err(PERR::WrongYield.into_err(pos)))
+}
+
+/// Check that all `yield` statements in a function body can be suspended, i.e. they are not
+/// inside expressions or `switch` cases.
+///
+/// Returns `true` if there is any `yield` statement (i.e. the function is a generator).
+#[cfg(not(feature = "no_function"))]
+fn check_yield_statements(statements: &[Stmt]) -> ParseResult<bool> {
+    let mut has_yield = false;
+
+    for stmt in statements {
+        has_yield |= match stmt {
+            Stmt::Yield(expr, ..) => {
+                if let Some(expr) = expr {
+                    ensure_no_yield(ASTNode::Expr(expr))?;
+                }
+                true
+            }
+            Stmt::Block(block) => check_yield_statements(block.statements())?,
+            Stmt::If(x, ..) => {
+                ensure_no_yield(ASTNode::Expr(&x.expr))?;
+                let body = check_yield_statements(x.body.statements())?;
+                check_yield_statements(x.branch.statements())? || body
+            }
+            Stmt::While(x, ..) | Stmt::Do(x, ..) => {
+                ensure_no_yield(ASTNode::Expr(&x.expr))?;
+                check_yield_statements(x.body.statements())?
+            }
+            Stmt::For(x, ..) => {
+                ensure_no_yield(ASTNode::Expr(&x.2.expr))?;
+                check_yield_statements(x.2.body.statements())?
+            }
+            Stmt::TryCatch(x, ..) => {
+                let body = check_yield_statements(x.body.statements())?;
+                check_yield_statements(x.branch.statements())? || body
+            }
+            stmt => {
+                ensure_no_yield(ASTNode::Stmt(stmt))?;
+                false
+            }
+        };
+    }
+
+    Ok(has_yield)
+}
+
+/// Mark function calls in tail position within a block of [statements][Stmt] in the body of a
+/// script-defined function.
+///
+/// Calls in `return` statements are in tail position anywhere except within `try` blocks.
+/// If `is_tail` is `true`, the value of the block is the return value of the function, so calls
+/// producing that value are in tail position as well.
+#[cfg(not(feature = "no_function"))]
+pub(crate) fn mark_tail_calls(statements: &mut [Stmt], is_tail: bool) {
+    let last = statements.len().wrapping_sub(1);
+
+    for (i, stmt) in statements.iter_mut().enumerate() {
+        let is_tail = is_tail && i == last;
+
+        match stmt {
+            Stmt::Return(Some(expr), flags, ..) if !flags.contains(ASTFlags::BREAK) => {
+                mark_tail_call(expr, true);
+            }
+            Stmt::FnCall(x, ..) if is_tail => mark_tail_fn_call(x),
+            Stmt::Expr(expr) => mark_tail_call(expr, is_tail),
+            Stmt::Block(block) => mark_tail_calls(block.statements_mut(), is_tail),
+            Stmt::If(x, ..) => {
+                mark_tail_calls(x.body.statements_mut(), is_tail);
+                mark_tail_calls(x.branch.statements_mut(), is_tail);
+            }
+            Stmt::Switch(x, ..) => {
+                x.1.expressions
+                    .iter_mut()
+                    .for_each(|case| mark_tail_call(&mut case.rhs, is_tail))
+            }
+            Stmt::While(x, ..) | Stmt::Do(x, ..) => mark_tail_calls(x.body.statements_mut(), false),
+            Stmt::For(x, ..) => mark_tail_calls(x.2.body.statements_mut(), false),
+            Stmt::TryCatch(x, ..) => mark_tail_calls(x.branch.statements_mut(), false),
+            _ => (),
+        }
+    }
+}
+
+/// Mark function calls in tail position within an expression.
+#[cfg(not(feature = "no_function"))]
+fn mark_tail_call(expr: &mut Expr, is_tail: bool) {
+    match expr {
+        Expr::FnCall(x, ..) if is_tail => mark_tail_fn_call(x),
+        Expr::Stmt(block) => mark_tail_calls(block.statements_mut(), is_tail),
+        _ => (),
+    }
+}
+
+/// Mark a function call in tail position, if it may call a script-defined function.
+#[cfg(not(feature = "no_function"))]
+fn mark_tail_fn_call(x: &mut crate::ast::FnCallExpr) {
+    x.is_tail_call = !x.is_qualified()
+        && x.op_token.is_none()
+        && !x.capture_parent_scope
+        && !x.hashes.is_native_only();
+}
+
+/// Check that a node does not contain any call to an asynchronous function, given the hashes of
+/// their names and numbers of parameters.
+fn ensure_no_async_call(node: ASTNode, async_fns: &BTreeSet<u64>) -> ParseResult<()> {
+    let mut async_call = None;
+
+    let on_node = &mut |path: &[ASTNode]| match path.last() {
+        Some(
+            ASTNode::Stmt(Stmt::FnCall(x, pos))
+            | ASTNode::Expr(Expr::FnCall(x, pos) | Expr::MethodCall(x, pos)),
+        ) if !x.is_qualified()
+            && x.op_token.is_none()
+            && async_fns.contains(&x.hashes.native()) =>
+        {
+            async_call = Some((x.name.clone(), *pos));
+            false
+        }
+        _ => true,
+    };
+
+    match node {
+        ASTNode::Stmt(stmt) => stmt.walk(&mut Vec::new(), on_node),
+        ASTNode::Expr(expr) => expr.walk(&mut Vec::new(), on_node),
+    };
+
+    async_call.map_or(Ok(()), |(name, pos)| {
+        Err(PERR::WrongAsyncCall(name.to_string()).into_err(pos))
+    })
+}
+
+/// Check that all calls to asynchronous functions in statements can suspend asynchronous
+/// evaluation, i.e. they are called by themselves in statements such as `foo(x);`,
+/// `let y = foo(x);`, `y = foo(x);` or `return foo(x);`.
+fn check_async_call_sites(statements: &[Stmt], async_fns: &BTreeSet<u64>) -> ParseResult<()> {
+    for stmt in statements {
+        match stmt {
+            Stmt::Block(block) => check_async_call_sites(block.statements(), async_fns)?,
+            Stmt::If(x, ..) => {
+                ensure_no_async_call(ASTNode::Expr(&x.expr), async_fns)?;
+                check_async_call_sites(x.body.statements(), async_fns)?;
+                check_async_call_sites(x.branch.statements(), async_fns)?;
+            }
+            Stmt::While(x, ..) | Stmt::Do(x, ..) => {
+                ensure_no_async_call(ASTNode::Expr(&x.expr), async_fns)?;
+                check_async_call_sites(x.body.statements(), async_fns)?;
+            }
+            Stmt::For(x, ..) => {
+                ensure_no_async_call(ASTNode::Expr(&x.2.expr), async_fns)?;
+                check_async_call_sites(x.2.body.statements(), async_fns)?;
+            }
+            Stmt::TryCatch(x, ..) => {
+                check_async_call_sites(x.body.statements(), async_fns)?;
+                check_async_call_sites(x.branch.statements(), async_fns)?;
+            }
+            stmt => match crate::eval::async_call_site(stmt) {
+                Some((call, ..)) => call
+                    .args
+                    .iter()
+                    .try_for_each(|arg| ensure_no_async_call(ASTNode::Expr(arg), async_fns))?,
+                None => ensure_no_async_call(ASTNode::Stmt(stmt), async_fns)?,
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the value matched by a pattern that contains only literal values, if any.
+fn pattern_literal_value(pattern: &Pattern) -> Option<Dynamic> {
+    match pattern {
+        Pattern::Value(value, ..) => Some((**value).clone()),
+        #[cfg(not(feature = "no_index"))]
+        Pattern::Array(x, ..) if x.1.is_none() => {
+            x.0.iter()
+                .map(pattern_literal_value)
+                .collect::<Option<crate::Array>>()
+                .map(Into::into)
+        }
+        #[cfg(not(feature = "no_object"))]
+        Pattern::Map(x, ..) if !x.1 => x
+            .0
+            .iter()
+            .map(|(key, item)| pattern_literal_value(item).map(|v| (key.name.as_str().into(), v)))
+            .collect::<Option<crate::Map>>()
+            .map(Into::into),
+        _ => None,
+    }
+}
+
+/// Create a statement that destructures a hidden variable (e.g. a loop variable or a function
+/// parameter named after the text of the pattern).
+fn make_destructuring_stmt(var: Ident, pattern: Pattern) -> Stmt {
+    let pos = var.pos;
+    let expr = Expr::Variable(
+        (None, Namespace::NONE, 0, var.name.clone()).into(),
+        None,
+        pos,
+    );
+    Stmt::Var(
+        (var, expr, None, None, Some(pattern.into())).into(),
+        ASTFlags::empty(),
+        pos,
+    )
+}
+
 /// Optimize the structure of a chained expression where the root expression is another chained expression.
 ///
 /// # Panics
@@ -630,6 +964,48 @@ fn optimize_combo_chain(expr: &mut Expr) {
 }
 
 impl Engine {
+    /// Bind a function call to the native Rust functions it may resolve to, if enabled.
+    fn bind_fn_call(&self, state: &mut ParseState, f: &mut FnCallExpr) {
+        f.binding = None;
+
+        if !self.bind_native_calls()
+            || f.op_token.is_some()
+            || f.capture_parent_scope
+            || f.is_qualified()
+        {
+            return;
+        }
+
+        // Index all native Rust functions in the global namespace, in resolution order
+        let bindings = state.fn_bindings.get_or_insert_with(|| {
+            let mut bindings = BTreeMap::new();
+
+            for m in &self.global_modules {
+                for (func, meta) in m.iter_fn().filter(|(func, ..)| func.is_native()) {
+                    let hash = calc_fn_hash(None, &meta.name, meta.param_types.len());
+                    let entry = FnResolutionCacheEntry {
+                        func: func.clone(),
+                        source: m.id_raw().cloned(),
+                    };
+
+                    bindings
+                        .entry(hash)
+                        .or_insert_with(|| FnCallBinding {
+                            version: self.fn_version,
+                            hash,
+                            funcs: StaticVec::new_const(),
+                        })
+                        .funcs
+                        .push((meta.param_types.clone(), entry));
+                }
+            }
+
+            bindings.into_iter().map(|(k, v)| (k, v.into())).collect()
+        });
+
+        f.binding = bindings.get(&f.hashes.native()).cloned();
+    }
+
     /// Parse a function call.
     fn parse_fn_call(
         &self,
@@ -704,15 +1080,19 @@ impl Engine {
 
                 args.shrink_to_fit();
 
-                return Ok(FnCallExpr {
+                let mut f = FnCallExpr {
                     name: state.get_interned_string(id),
                     capture_parent_scope,
+                    is_tail_call: false,
+                    binding: None,
                     op_token: None,
                     namespace: _namespace,
                     hashes,
                     args,
-                }
-                .into_fn_call_expr(settings.pos));
+                };
+                self.bind_fn_call(state, &mut f);
+
+                return Ok(f.into_fn_call_expr(settings.pos));
             }
             // id...
             _ => (),
@@ -771,15 +1151,19 @@ impl Engine {
 
                     args.shrink_to_fit();
 
-                    return Ok(FnCallExpr {
+                    let mut f = FnCallExpr {
                         name: state.get_interned_string(id),
                         capture_parent_scope,
+                        is_tail_call: false,
+                        binding: None,
                         op_token: None,
                         namespace: _namespace,
                         hashes,
                         args,
-                    }
-                    .into_fn_call_expr(settings.pos));
+                    };
+                    self.bind_fn_call(state, &mut f);
+
+                    return Ok(f.into_fn_call_expr(settings.pos));
                 }
                 // id(...args,
                 (Token::Comma, ..) => {
@@ -1162,13 +1546,16 @@ impl Engine {
         let mut expressions = FnArgsVec::<BinaryExpr>::new();
         let mut cases = StraightHashMap::<CaseBlocksList>::default();
         let mut ranges = StaticVec::<RangeCase>::new();
+        let mut patterns = StaticVec::<(Pattern, usize)>::new();
         let mut def_case = None;
         let mut def_case_pos = Position::NONE;
 
         loop {
             const MISSING_RBRACE: &str = "to end this switch block";
 
-            let (case_expr_list, condition) = match state.input.peek().unwrap() {
+            let prev_stack_len = state.stack.len();
+
+            let (case_expr_list, case_patterns, condition) = match state.input.peek().unwrap() {
                 (Token::RightBrace, ..) => {
                     eat_token(state.input, &Token::RightBrace);
                     break;
@@ -1190,6 +1577,7 @@ impl Engine {
                     }
 
                     (
+                        StaticVec::new_const(),
                         StaticVec::new_const(),
                         Expr::BoolConstant(true, Position::NONE),
                     )
@@ -1200,25 +1588,86 @@ impl Engine {
 
                 _ => {
                     let mut case_expr_list = StaticVec::new_const();
+                    let mut case_patterns = StaticVec::<Pattern>::new_const();
 
                     loop {
                         let filter = state.expr_filter;
                         state.expr_filter = |t| t != &Token::Pipe;
-                        let expr = self.parse_expr(state, settings);
-                        state.expr_filter = filter;
 
-                        match expr {
-                            Ok(expr) => case_expr_list.push(expr),
-                            Err(err) => {
-                                return Err(PERR::ExprExpected("literal".into()).into_err(err.1))
+                        let result = match state.input.peek().unwrap() {
+                            // Structural pattern
+                            (token, ..) if is_pattern_start(token) => {
+                                self.parse_pattern(state, settings, true).map(|pattern| {
+                                    match pattern_literal_value(&pattern) {
+                                        // Literal-only patterns are matched by value
+                                        Some(value) => case_expr_list
+                                            .push(Expr::from_dynamic(value, pattern.position())),
+                                        None => case_patterns.push(pattern),
+                                    }
+                                })
                             }
-                        }
+                            // Type pattern
+                            (Token::Identifier(..), ..) => {
+                                parse_var_name(state.input).map(|(name, pos)| {
+                                    let typ = get_interned_type_name(state, &name);
+                                    case_patterns.push(Pattern::Type(typ, pos));
+                                })
+                            }
+                            // Literal value
+                            _ => match self.parse_expr(state, settings) {
+                                Ok(expr) => {
+                                    case_expr_list.push(expr);
+                                    Ok(())
+                                }
+                                Err(err) => {
+                                    Err(PERR::ExprExpected("literal".into()).into_err(err.1))
+                                }
+                            },
+                        };
+
+                        state.expr_filter = filter;
+                        result?;
 
                         if !match_token(state.input, &Token::Pipe).0 {
                             break;
                         }
                     }
 
+                    // All alternatives must bind the same variables
+                    let mut vars = StaticVec::<ImmutableString>::new_const();
+
+                    for (i, pattern) in case_patterns.iter().enumerate() {
+                        ensure_unique_variables(pattern)?;
+
+                        let mut names: StaticVec<_> = pattern
+                            .variables()
+                            .into_iter()
+                            .map(|v| v.name.clone())
+                            .collect();
+                        names.sort();
+
+                        if i == 0 && case_expr_list.is_empty() {
+                            vars = names;
+                        } else if names != vars {
+                            let name = names
+                                .iter()
+                                .find(|n| !vars.contains(n))
+                                .or_else(|| vars.iter().find(|n| !names.contains(n)))
+                                .unwrap();
+
+                            return Err(PERR::MissingSymbol(format!(
+                                "Variable {name} must be bound in all alternatives of this switch case"
+                            ))
+                            .into_err(pattern.position()));
+                        }
+                    }
+
+                    // Bound variables are visible in the condition and the action
+                    // (in sorted order)
+                    for name in vars {
+                        state.stack.push(name, ());
+                    }
+
                     let condition = if match_token(state.input, &Token::If).0 {
                         ensure_not_statement_expr(state.input, "a boolean")?;
                         let guard = self.parse_expr(state, settings)?.ensure_bool_expr()?;
@@ -1227,7 +1676,7 @@ impl Engine {
                     } else {
                         Expr::BoolConstant(true, Position::NONE)
                     };
-                    (case_expr_list, condition)
+                    (case_expr_list, case_patterns, condition)
                 }
             };
 
@@ -1254,6 +1703,8 @@ impl Engine {
                     (Expr::Stmt(stmt_block.into()), need_comma)
                 };
 
+            state.stack.rewind(prev_stack_len);
+
             expressions.push(BinaryExpr {
                 lhs: condition,
                 rhs: action_expr,
@@ -1261,8 +1712,12 @@ impl Engine {
 
             let index = expressions.len() - 1;
 
+            patterns.extend(case_patterns.into_iter().map(|pattern| (pattern, index)));
+
             if case_expr_list.is_empty() {
-                def_case = Some(index);
+                if patterns.last().map_or(true, |(_, n)| *n != index) {
+                    def_case = Some(index);
+                }
             } else {
                 for expr in case_expr_list {
                     let value = expr.get_literal_value().ok_or_else(|| {
@@ -1337,11 +1792,13 @@ impl Engine {
         expressions.shrink_to_fit();
         cases.shrink_to_fit();
         ranges.shrink_to_fit();
+        patterns.shrink_to_fit();
 
         let cases = SwitchCasesCollection {
             expressions,
             cases,
             ranges,
+            patterns,
             def_case,
         };
 
@@ -1464,6 +1921,9 @@ impl Engine {
             }
             #[cfg(not(feature = "no_function"))]
             Token::Pipe | Token::Or if settings.has_option(LangOptions::ANON_FN) => {
+                #[cfg(not(feature = "no_closure"))]
+                let var_types = state.visible_var_types();
+
                 // Build new parse state
                 let new_interner = &mut StringsInterner::new();
                 let new_state = &mut ParseState::new(
@@ -1488,6 +1948,12 @@ impl Engine {
                     new_state.global_imports.extend(state.imports.clone());
                 }
 
+                // Captured variables keep their type annotations
+                #[cfg(not(feature = "no_closure"))]
+                {
+                    new_state.external_var_types = var_types;
+                }
+
                 // Brand new options
                 #[cfg(not(feature = "no_closure"))]
                 let options = self.options & !LangOptions::STRICT_VAR; // a capturing closure can access variables not defined locally, so turn off Strict Variables mode
@@ -1866,7 +2332,7 @@ impl Engine {
                     let options = ChainingFlags::PROPERTY | ChainingFlags::DISALLOW_NAMESPACES;
                     let rhs = self.parse_primary(state, settings.level_up()?, options)?;
 
-                    Self::make_dot_expr(state, expr, rhs, _parent_options, op_flags, tail_pos)?
+                    self.make_dot_expr(state, expr, rhs, _parent_options, op_flags, tail_pos)?
                 }
                 // Unknown postfix operator
                 (expr, token) => {
@@ -1976,6 +2442,8 @@ impl Engine {
                         args: IntoIterator::into_iter([expr]).collect(),
                         op_token: Some(token),
                         capture_parent_scope: false,
+                        is_tail_call: false,
+                        binding: None,
                     }
                     .into_fn_call_expr(pos)),
                 }
@@ -1998,6 +2466,8 @@ impl Engine {
                         args: IntoIterator::into_iter([expr]).collect(),
                         op_token: Some(token),
                         capture_parent_scope: false,
+                        is_tail_call: false,
+                        binding: None,
                     }
                     .into_fn_call_expr(pos)),
                 }
@@ -2017,6 +2487,8 @@ impl Engine {
                     },
                     op_token: Some(token),
                     capture_parent_scope: false,
+                    is_tail_call: false,
+                    binding: None,
                 }
                 .into_fn_call_expr(pos))
             }
@@ -2140,6 +2612,7 @@ impl Engine {
     /// Make a dot expression.
     #[cfg(not(feature = "no_object"))]
     fn make_dot_expr(
+        &self,
         state: &mut ParseState,
         lhs: Expr,
         rhs: Expr,
@@ -2153,7 +2626,7 @@ impl Engine {
                 if !parent_options.intersects(ASTFlags::BREAK) =>
             {
                 let options = options | parent_options;
-                x.rhs = Self::make_dot_expr(state, x.rhs, rhs, options, op_flags, op_pos)?;
+                x.rhs = self.make_dot_expr(state, x.rhs, rhs, options, op_flags, op_pos)?;
                 Ok(Expr::Index(x, ASTFlags::empty(), pos))
             }
             // lhs.module::id - syntax error
@@ -2213,6 +2686,7 @@ impl Engine {
                 } else {
                     FnCallHashes::from_native_only(calc_fn_hash(None, &f.name, args_len))
                 };
+                self.bind_fn_call(state, &mut f);
 
                 let rhs = Expr::MethodCall(f, func_pos);
                 Ok(Expr::Dot(BinaryExpr { lhs, rhs }.into(), op_flags, op_pos))
@@ -2270,6 +2744,7 @@ impl Engine {
                         } else {
                             FnCallHashes::from_native_only(calc_fn_hash(None, &f.name, args_len))
                         };
+                        self.bind_fn_call(state, &mut f);
 
                         let new_lhs = BinaryExpr {
                             lhs: Expr::MethodCall(f, func_pos),
@@ -2318,6 +2793,7 @@ impl Engine {
                     .get(&**c)
                     .copied()
                     .ok_or_else(|| PERR::Reserved(c.to_string()).into_err(*current_pos))?,
+                token if is_pipe_symbol(token) => PIPE_PRECEDENCE,
                 Token::Reserved(c) if !is_valid_identifier(c) => {
                     return Err(PERR::UnknownOperator(c.to_string()).into_err(*current_pos))
                 }
@@ -2333,6 +2809,14 @@ impl Engine {
 
             let (op_token, pos) = state.input.next().unwrap();
 
+            // The target of a pipeline never extends to the right
+            if is_pipe_symbol(&op_token) {
+                settings = settings.level_up()?;
+                settings.pos = pos;
+                root = self.parse_pipe_target(state, settings, root)?;
+                continue;
+            }
+
             let rhs = self.parse_unary(state, settings)?;
 
             let (next_op, next_pos) = state.input.peek().unwrap();
@@ -2343,6 +2827,7 @@ impl Engine {
                     .get(&**c)
                     .copied()
                     .ok_or_else(|| PERR::Reserved(c.to_string()).into_err(*next_pos))?,
+                token if is_pipe_symbol(token) => PIPE_PRECEDENCE,
                 Token::Reserved(c) if !is_valid_identifier(c) => {
                     return Err(PERR::UnknownOperator(c.to_string()).into_err(*next_pos))
                 }
@@ -2373,6 +2858,8 @@ impl Engine {
                 args: IntoIterator::into_iter([root, rhs]).collect(),
                 op_token: native_only.then(|| op_token.clone()),
                 capture_parent_scope: false,
+                is_tail_call: false,
+                binding: None,
             };
 
             root = match op_token {
@@ -2426,6 +2913,8 @@ impl Engine {
                             args: IntoIterator::into_iter([fn_call]).collect(),
                             op_token: Some(Token::Bang),
                             capture_parent_scope: false,
+                            is_tail_call: false,
+                            binding: None,
                         };
                         not_base.into_fn_call_expr(pos)
                     }
@@ -2449,6 +2938,75 @@ impl Engine {
         }
     }
 
+    /// Parse the target of a pipeline operator `|>`, feeding `lhs` into it.
+    ///
+    /// * `lhs |> f(a, b)` becomes `f(lhs, a, b)` (also for namespace-qualified functions).
+    /// * `lhs |> .method(a, b)` becomes `lhs.method(a, b)`.
+    /// * Any other target expression must evaluate to a function pointer, so `lhs |> fp` becomes
+    ///   `call(fp, lhs)`.
+    fn parse_pipe_target(
+        &self,
+        state: &mut ParseState,
+        settings: ParseSettings,
+        lhs: Expr,
+    ) -> ParseResult<Expr> {
+        // lhs |> .method(...)
+        #[cfg(not(feature = "no_object"))]
+        if state.input.peek().unwrap().0 == Token::Period {
+            return self.parse_postfix(state, settings, lhs, ChainingFlags::empty());
+        }
+
+        let (target, target_pos) = match self.parse_unary(state, settings)? {
+            // lhs |> f(...)
+            Expr::FnCall(mut x, pos)
+                if x.op_token.is_none() && x.name != crate::engine::KEYWORD_FN_PTR =>
+            {
+                x.args.insert(0, lhs);
+
+                #[cfg(not(feature = "no_module"))]
+                let hash = calc_fn_hash(
+                    x.namespace.path.iter().map(Ident::as_str),
+                    &x.name,
+                    x.args.len(),
+                );
+                #[cfg(feature = "no_module")]
+                let hash = calc_fn_hash(None, &x.name, x.args.len());
+
+                x.hashes = if is_valid_function_name(&x.name) {
+                    FnCallHashes::from_hash(hash)
+                } else {
+                    FnCallHashes::from_native_only(hash)
+                };
+                self.bind_fn_call(state, &mut x);
+
+                return Ok(Expr::FnCall(x, pos));
+            }
+            // lhs |> fp
+            expr => {
+                let pos = expr.start_position();
+                (expr, pos)
+            }
+        };
+
+        let hash = calc_fn_hash(None, crate::engine::KEYWORD_FN_PTR_CALL, 2);
+
+        Ok(FnCallExpr {
+            namespace: Namespace::NONE,
+            name: state.get_interned_string(crate::engine::KEYWORD_FN_PTR_CALL),
+            hashes: if is_valid_function_name(crate::engine::KEYWORD_FN_PTR_CALL) {
+                FnCallHashes::from_hash(hash)
+            } else {
+                FnCallHashes::from_native_only(hash)
+            },
+            args: IntoIterator::into_iter([target, lhs]).collect(),
+            op_token: None,
+            capture_parent_scope: false,
+            is_tail_call: false,
+            binding: None,
+        }
+        .into_fn_call_expr(target_pos))
+    }
+
     /// Parse a custom syntax.
     #[cfg(not(feature = "no_custom_syntax"))]
     fn parse_custom_syntax(
@@ -2762,10 +3320,10 @@ impl Engine {
         let mut settings = settings.level_up_with_position(eat_token(state.input, &Token::For))?;
 
         // for name ...
-        let (name, name_pos, counter_name, counter_pos) =
+        let (name, name_pos, pattern, counter_name, counter_pos) =
             if match_token(state.input, &Token::LeftParen).0 {
                 // ( name, counter )
-                let (name, name_pos) = parse_var_name(state.input)?;
+                let (name, name_pos, pattern) = self.parse_var_name_or_pattern(state, settings)?;
                 let (has_comma, pos) = match_token(state.input, &Token::Comma);
                 if !has_comma {
                     return Err(PERR::MissingToken(
@@ -2776,7 +3334,11 @@ impl Engine {
                 }
                 let (counter_name, counter_pos) = parse_var_name(state.input)?;
 
-                if counter_name == name {
+                if counter_name == name
+                    || pattern.as_ref().map_or(false, |p| {
+                        p.variables().iter().any(|v| v.name == counter_name)
+                    })
+                {
                     return Err(PERR::DuplicatedVariable(counter_name.into()).into_err(counter_pos));
                 }
 
@@ -2788,11 +3350,11 @@ impl Engine {
                     )
                     .into_err(pos));
                 }
-                (name, name_pos, Some(counter_name), counter_pos)
+                (name, name_pos, pattern, Some(counter_name), counter_pos)
             } else {
                 // name
-                let (name, name_pos) = parse_var_name(state.input)?;
-                (name, name_pos, None, Position::NONE)
+                let (name, name_pos, pattern) = self.parse_var_name_or_pattern(state, settings)?;
+                (name, name_pos, pattern, None, Position::NONE)
             };
 
         // for name in ...
@@ -2830,11 +3392,23 @@ impl Engine {
             }
             state.stack.push(&loop_var.name, ());
 
+            // Variables destructured from the loop variable at the start of the body
+            if let Some(ref pattern) = pattern {
+                for var in pattern.variables() {
+                    state.stack.push(var.name.clone(), ());
+                }
+            }
+
             prev_stack_len
         };
 
         settings.flags |= ParseSettingFlags::BREAKABLE;
-        let body = self.parse_block(state, settings)?.into();
+        let mut body: StmtBlock = self.parse_block(state, settings)?.into();
+
+        if let Some(pattern) = pattern {
+            let stmt = make_destructuring_stmt(loop_var.clone(), pattern);
+            body.statements_mut().insert(0, stmt);
+        }
 
         state.stack.rewind(prev_stack_len);
 
@@ -2846,26 +3420,275 @@ impl Engine {
         ))
     }
 
-    /// Parse a variable definition statement.
-    fn parse_let(
+    /// Parse a destructuring pattern.
+    ///
+    /// Literal values can only be matched by refutable patterns (i.e. in `switch` cases).
+    fn parse_pattern(
         &self,
         state: &mut ParseState,
-        mut settings: ParseSettings,
-        access: AccessMode,
-        is_export: bool,
-    ) -> ParseResult<Stmt> {
-        // let/const... (specified in `var_type`)
-        settings.pos = state.input.next().unwrap().1;
+        settings: ParseSettings,
+        refutable: bool,
+    ) -> ParseResult<Pattern> {
+        let settings = settings.level_up()?;
+        let pos = state.input.peek().unwrap().1;
 
-        // let name ...
-        let (name, pos) = parse_var_name(state.input)?;
+        match state.input.peek().unwrap().0 {
+            Token::Underscore => {
+                eat_token(state.input, &Token::Underscore);
+                Ok(Pattern::Ignore(pos))
+            }
+            Token::Identifier(..) => {
+                let (name, pos) = parse_var_name(state.input)?;
+
+                if refutable && is_standard_type_name(&name) {
+                    let typ = get_interned_type_name(state, &name);
+                    return Ok(Pattern::Type(typ, pos));
+                }
 
-        if !self.allow_shadowing() && state.stack.get(&name).is_some() {
+                let name = state.get_interned_string(name);
+                Ok(Pattern::Var(Ident { name, pos }))
+            }
+
+            // [ pattern, ... ..rest ]
+            #[cfg(not(feature = "no_index"))]
+            Token::LeftBracket => {
+                eat_token(state.input, &Token::LeftBracket);
+
+                let mut items = StaticVec::new_const();
+                let mut rest = None;
+
+                loop {
+                    match state.input.peek().unwrap().0 {
+                        Token::RightBracket => {
+                            eat_token(state.input, &Token::RightBracket);
+                            break;
+                        }
+                        Token::ExclusiveRange => {
+                            let pos = eat_token(state.input, &Token::ExclusiveRange);
+
+                            rest = Some(match state.input.peek().unwrap().0 {
+                                Token::Identifier(..) => {
+                                    self.parse_pattern(state, settings, refutable)?
+                                }
+                                _ => Pattern::Ignore(pos),
+                            });
+
+                            match state.input.next().unwrap() {
+                                (Token::RightBracket, ..) => break,
+                                (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                                (.., pos) => {
+                                    return Err(PERR::MissingToken(
+                                        Token::RightBracket.into(),
+                                        "after the rest of the array pattern".into(),
+                                    )
+                                    .into_err(pos))
+                                }
+                            }
+                        }
+                        _ => items.push(self.parse_pattern(state, settings, refutable)?),
+                    }
+
+                    match state.input.next().unwrap() {
+                        (Token::RightBracket, ..) => break,
+                        (Token::Comma, ..) => (),
+                        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                        (.., pos) => {
+                            return Err(PERR::MissingToken(
+                                Token::Comma.into(),
+                                "to separate the items of the array pattern".into(),
+                            )
+                            .into_err(pos))
+                        }
+                    }
+                }
+
+                items.shrink_to_fit();
+                Ok(Pattern::Array((items, rest).into(), pos))
+            }
+
+            // #{ key: pattern, ... .. }
+            #[cfg(not(feature = "no_object"))]
+            Token::MapStart => {
+                eat_token(state.input, &Token::MapStart);
+
+                let mut items = StaticVec::new_const();
+                let mut has_rest = false;
+
+                loop {
+                    let (key, is_identifier) = match state.input.next().unwrap() {
+                        (Token::RightBrace, ..) => break,
+                        (Token::ExclusiveRange, ..) => {
+                            has_rest = true;
+
+                            match state.input.next().unwrap() {
+                                (Token::RightBrace, ..) => break,
+                                (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                                (.., pos) => {
+                                    return Err(PERR::MissingToken(
+                                        Token::RightBrace.into(),
+                                        "after the rest of the object map pattern".into(),
+                                    )
+                                    .into_err(pos))
+                                }
+                            }
+                        }
+                        (Token::Identifier(s), pos) => (
+                            Ident {
+                                name: state.get_interned_string(*s),
+                                pos,
+                            },
+                            true,
+                        ),
+                        (Token::StringConstant(s), pos) => (
+                            Ident {
+                                name: state.get_interned_string(*s),
+                                pos,
+                            },
+                            false,
+                        ),
+                        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                        (.., pos) => return Err(PERR::PropertyExpected.into_err(pos)),
+                    };
+
+                    let pattern = if match_token(state.input, &Token::Colon).0 {
+                        self.parse_pattern(state, settings, refutable)?
+                    } else if is_identifier {
+                        Pattern::Var(key.clone())
+                    } else {
+                        return Err(PERR::MissingToken(
+                            Token::Colon.into(),
+                            format!(
+                                "after the property '{}' of the object map pattern",
+                                key.name
+                            ),
+                        )
+                        .into_err(state.input.peek().unwrap().1));
+                    };
+
+                    items.push((key, pattern));
+
+                    match state.input.next().unwrap() {
+                        (Token::RightBrace, ..) => break,
+                        (Token::Comma, ..) => (),
+                        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                        (.., pos) => {
+                            return Err(PERR::MissingToken(
+                                Token::Comma.into(),
+                                "to separate the properties of the object map pattern".into(),
+                            )
+                            .into_err(pos))
+                        }
+                    }
+                }
+
+                items.shrink_to_fit();
+                Ok(Pattern::Map((items, has_rest).into(), pos))
+            }
+
+            // Literal value
+            _ if refutable => {
+                let expr = self.parse_expr(state, settings)?;
+                let value = expr.get_literal_value().ok_or_else(|| {
+                    PERR::ExprExpected("a literal".into()).into_err(expr.start_position())
+                })?;
+                Ok(Pattern::Value(value.into(), pos))
+            }
+
+            _ => match state.input.next().unwrap() {
+                (Token::Reserved(s), pos) if is_valid_identifier(&s) => {
+                    Err(PERR::Reserved(s.to_string()).into_err(pos))
+                }
+                (Token::LexError(err), pos) => Err(err.into_err(pos)),
+                (.., pos) => Err(PERR::VariableExpected.into_err(pos)),
+            },
+        }
+    }
+
+    /// Parse a variable name or a destructuring pattern.
+    ///
+    /// For a pattern, the name returned is the text of the pattern, which is not a valid variable
+    /// name.
+    fn parse_var_name_or_pattern(
+        &self,
+        state: &mut ParseState,
+        settings: ParseSettings,
+    ) -> ParseResult<(SmartString, Position, Option<Pattern>)> {
+        if !is_pattern_start(&state.input.peek().unwrap().0) {
+            let (name, pos) = parse_var_name(state.input)?;
+            return Ok((name, pos, None));
+        }
+
+        let pattern = self.parse_pattern(state, settings, false)?;
+        ensure_unique_variables(&pattern)?;
+
+        Ok((
+            pattern.to_string().into(),
+            pattern.position(),
+            Some(pattern),
+        ))
+    }
+
+    /// Is the name a known type, i.e. a standard type or a registered custom type?
+    fn is_known_type_name(&self, name: &str) -> bool {
+        if is_standard_type_name(name) || name == std::any::type_name::<crate::INT>() {
+            return true;
+        }
+        #[cfg(not(feature = "no_float"))]
+        if name == std::any::type_name::<crate::FLOAT>() {
+            return true;
+        }
+        #[cfg(not(feature = "no_function"))]
+        if name == "generator" {
+            return true;
+        }
+
+        let is_custom_type =
+            |m: &crate::Module| m.iter_custom_types().any(|(_, t)| t.display_name == name);
+
+        #[cfg(not(feature = "no_module"))]
+        if self.global_sub_modules.values().any(|m| is_custom_type(m)) {
+            return true;
+        }
+
+        self.global_modules.iter().any(|m| is_custom_type(m))
+    }
+
+    /// Parse a type annotation (after `:` or `->`).
+    ///
+    /// Type names in string literals are kept as-is and not checked.
+    fn parse_type_annotation(&self, state: &mut ParseState) -> ParseResult<ImmutableString> {
+        match state.input.next().unwrap() {
+            (Token::Identifier(s), pos) if !self.is_known_type_name(&s) => {
+                Err(PERR::TypeUndefined(s.to_string()).into_err(pos))
+            }
+            (Token::Identifier(s) | Token::StringConstant(s), ..) => {
+                Ok(get_interned_type_name(state, &s))
+            }
+            // `Fn` is a reserved keyword
+            (Token::Reserved(s), ..) if s.as_str() == crate::engine::KEYWORD_FN_PTR => {
+                Ok(get_interned_type_name(state, &s))
+            }
+            (Token::Unit, ..) => Ok(state.get_interned_string("()")),
+            (Token::LexError(err), pos) => Err(err.into_err(pos)),
+            (.., pos) => Err(PERR::MissingSymbol("Expecting a type name".into()).into_err(pos)),
+        }
+    }
+
+    /// Check whether a variable can be defined, running the variable definition filter if any.
+    fn check_var_def(
+        &self,
+        state: &mut ParseState,
+        settings: ParseSettings,
+        name: &str,
+        pos: Position,
+        access: AccessMode,
+    ) -> ParseResult<()> {
+        if !self.allow_shadowing() && state.stack.get(name).is_some() {
             return Err(PERR::VariableExists(name.into()).into_err(pos));
         }
 
         if let Some(ref filter) = self.def_var_filter {
-            let will_shadow = state.stack.get(&name).is_some();
+            let will_shadow = state.stack.get(name).is_some();
 
             let global = state
                 .global
@@ -2873,7 +3696,7 @@ impl Engine {
 
             global.level = settings.level;
             let is_const = access == AccessMode::ReadOnly;
-            let info = VarDefInfo::new(&name, is_const, settings.level, will_shadow);
+            let info = VarDefInfo::new(name, is_const, settings.level, will_shadow);
             let caches = &mut Caches::new();
             let context = EvalContext::new(self, global, caches, &mut state.stack, None);
 
@@ -2889,12 +3712,57 @@ impl Engine {
             }
         }
 
+        Ok(())
+    }
+
+    /// Parse a variable definition statement.
+    fn parse_let(
+        &self,
+        state: &mut ParseState,
+        mut settings: ParseSettings,
+        access: AccessMode,
+        is_export: bool,
+    ) -> ParseResult<Stmt> {
+        // let/const... (specified in `var_type`)
+        settings.pos = state.input.next().unwrap().1;
+
+        // let name ... | let pattern ...
+        let (name, pos, pattern) = self.parse_var_name_or_pattern(state, settings)?;
+
+        match pattern {
+            Some(ref pattern) => {
+                for var in pattern.variables() {
+                    self.check_var_def(state, settings, &var.name, var.pos, access)?;
+                }
+            }
+            None => self.check_var_def(state, settings, &name, pos, access)?,
+        }
+
         let name = state.get_interned_string(name);
 
+        // let name: type ...
+        let typ = if match_token(state.input, &Token::Colon).0 {
+            Some(self.parse_type_annotation(state)?)
+        } else {
+            None
+        };
+
         // let name = ...
         let expr = if match_token(state.input, &Token::Equals).0 {
             // let name = expr
             self.parse_expr(state, settings.level_up()?)?
+        } else if pattern.is_some() {
+            return Err(PERR::MissingToken(
+                Token::Equals.into(),
+                "after the destructuring pattern".into(),
+            )
+            .into_err(state.input.peek().unwrap().1));
+        } else if typ.is_some() {
+            return Err(PERR::MissingToken(
+                Token::Equals.into(),
+                "to initialize the variable with a type annotation".into(),
+            )
+            .into_err(state.input.peek().unwrap().1));
         } else {
             Expr::Unit(Position::NONE)
         };
@@ -2905,6 +3773,31 @@ impl Engine {
             ASTFlags::empty()
         };
 
+        // Destructuring always defines new variables
+        if let Some(pattern) = pattern {
+            for var in pattern.variables() {
+                state
+                    .stack
+                    .push_entry(var.name.clone(), access, Dynamic::UNIT);
+
+                #[cfg(not(feature = "no_module"))]
+                if is_export {
+                    state
+                        .stack
+                        .add_alias_by_index(state.stack.len() - 1, var.name.clone());
+                }
+            }
+
+            let var_def = (Ident { name, pos }, expr, None, typ, Some(pattern.into())).into();
+
+            return Ok(match access {
+                AccessMode::ReadWrite => Stmt::Var(var_def, export, settings.pos),
+                AccessMode::ReadOnly => {
+                    Stmt::Var(var_def, ASTFlags::CONSTANT | export, settings.pos)
+                }
+            });
+        }
+
         let (existing, hit_barrier) = state.find_var(&name);
 
         let existing = if !hit_barrier && existing > 0 {
@@ -2928,6 +3821,10 @@ impl Engine {
             None
         };
 
+        // Keep the type annotation with the variable for closures capturing it
+        let index = existing.unwrap_or(state.stack.len() - 1);
+        state.stack.set_type_by_index(index, typ.clone());
+
         #[cfg(not(feature = "no_module"))]
         if is_export {
             state
@@ -2935,7 +3832,7 @@ impl Engine {
                 .add_alias_by_index(state.stack.len() - 1, name.clone());
         }
 
-        let var_def = (Ident { name, pos }, expr, idx).into();
+        let var_def = (Ident { name, pos }, expr, idx, typ, None).into();
 
         Ok(match access {
             // let name = expr
@@ -3413,6 +4310,26 @@ impl Engine {
                 }
             }
 
+            // `yield` is a reserved keyword
+            #[cfg(not(feature = "no_function"))]
+            Token::Reserved(s)
+                if s.as_str() == "yield" && settings.has_flag(ParseSettingFlags::FN_SCOPE) =>
+            {
+                let pos = state.input.next().unwrap().1;
+
+                match state.input.peek().unwrap() {
+                    // `yield` at <EOF>, at end of block or `yield;`
+                    (Token::EOF | Token::RightBrace | Token::SemiColon, ..) => {
+                        Ok(Stmt::Yield(None, pos))
+                    }
+                    // `yield` with expression
+                    _ => {
+                        let expr = self.parse_expr(state, settings.level_up()?)?;
+                        Ok(Stmt::Yield(Some(expr.into()), pos))
+                    }
+                }
+            }
+
             Token::Try => self.parse_try_catch(state, settings.level_up()?),
 
             Token::Let => self.parse_let(state, settings.level_up()?, ReadWrite, false),
@@ -3521,12 +4438,7 @@ impl Engine {
             match token {
                 Token::StringConstant(s) if next_token == &Token::Period => {
                     eat_token(state.input, &Token::Period);
-                    let s = match s.as_str() {
-                        "int" => state.get_interned_string(std::any::type_name::<crate::INT>()),
-                        #[cfg(not(feature = "no_float"))]
-                        "float" => state.get_interned_string(std::any::type_name::<crate::FLOAT>()),
-                        _ => state.get_interned_string(*s),
-                    };
+                    let s = get_interned_type_name(state, &s);
                     (state.input.next().unwrap(), Some(s))
                 }
                 Token::StringConstant(..) => {
@@ -3538,12 +4450,7 @@ impl Engine {
                 }
                 Token::Identifier(s) if next_token == &Token::Period => {
                     eat_token(state.input, &Token::Period);
-                    let s = match s.as_str() {
-                        "int" => state.get_interned_string(std::any::type_name::<crate::INT>()),
-                        #[cfg(not(feature = "no_float"))]
-                        "float" => state.get_interned_string(std::any::type_name::<crate::FLOAT>()),
-                        _ => state.get_interned_string(*s),
-                    };
+                    let s = get_interned_type_name(state, &s);
                     (state.input.next().unwrap(), Some(s))
                 }
                 _ => ((token, pos), None),
@@ -3570,35 +4477,81 @@ impl Engine {
             (.., pos) => return Err(PERR::FnMissingParams(name.into()).into_err(*pos)),
         };
 
-        let mut params = StaticVec::<(ImmutableString, _)>::new_const();
+        let mut params = StaticVec::<(ImmutableString, _, _)>::new_const();
+        let mut patterns = StaticVec::<(Ident, Pattern)>::new_const();
 
         if !no_params {
             let sep_err = format!("to separate the parameters of function '{name}'");
 
             loop {
-                match state.input.next().unwrap() {
-                    (Token::RightParen, ..) => break,
-                    (Token::Identifier(s), pos) => {
-                        if params.iter().any(|(p, _)| p == &*s) {
-                            return Err(
-                                PERR::FnDuplicatedParam(name.into(), s.to_string()).into_err(pos)
-                            );
-                        }
-
-                        let s = state.get_interned_string(*s);
-                        state.stack.push(s.clone(), ());
-                        params.push((s, pos));
+                let (s, pos, pattern) = match state.input.peek().unwrap() {
+                    (Token::RightParen, ..) => {
+                        eat_token(state.input, &Token::RightParen);
+                        break;
                     }
-                    (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                    (Token::Identifier(..), ..) => {
+                        self.parse_var_name_or_pattern(state, settings)?
+                    }
+                    (token, ..) if is_pattern_start(token) => {
+                        self.parse_var_name_or_pattern(state, settings)?
+                    }
+                    (Token::LexError(..), ..) => match state.input.next().unwrap() {
+                        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                        token => unreachable!("Token::LexError expected but gets {:?}", token),
+                    },
                     (.., pos) => {
                         return Err(PERR::MissingToken(
                             Token::RightParen.into(),
                             format!("to close the parameters list of function '{name}'"),
                         )
-                        .into_err(pos))
+                        .into_err(*pos))
+                    }
+                };
+
+                let is_defined = |n: &str| {
+                    params.iter().any(|(p, ..)| p == n)
+                        || patterns
+                            .iter()
+                            .any(|(_, p)| p.variables().iter().any(|v| v.name == n))
+                };
+
+                match pattern {
+                    Some(ref pattern) => {
+                        if let Some(v) = pattern.variables().iter().find(|v| is_defined(&v.name)) {
+                            return Err(PERR::FnDuplicatedParam(name.into(), v.name.to_string())
+                                .into_err(v.pos));
+                        }
+                    }
+                    None if is_defined(&s) => {
+                        return Err(
+                            PERR::FnDuplicatedParam(name.into(), s.to_string()).into_err(pos)
+                        );
                     }
+                    None => (),
                 }
 
+                // Parse type annotation, if any
+                let typ = if match_token(state.input, &Token::Colon).0 {
+                    Some(self.parse_type_annotation(state)?)
+                } else {
+                    None
+                };
+
+                let s = state.get_interned_string(s);
+                state.stack.push(s.clone(), ());
+                let index = state.stack.len() - 1;
+                state.stack.set_type_by_index(index, typ.clone());
+
+                if let Some(pattern) = pattern {
+                    let param = Ident {
+                        name: s.clone(),
+                        pos,
+                    };
+                    patterns.push((param, pattern));
+                }
+
+                params.push((s, typ, pos));
+
                 match state.input.next().unwrap() {
                     (Token::RightParen, ..) => break,
                     (Token::Comma, ..) => (),
@@ -3610,13 +4563,47 @@ impl Engine {
             }
         }
 
+        // Parse return type annotation, if any
+        let return_type = if is_arrow_symbol(&state.input.peek().unwrap().0) {
+            state.input.next().unwrap();
+            Some(self.parse_type_annotation(state)?)
+        } else {
+            None
+        };
+
+        // Variables destructured from the parameters at the start of the body
+        for (_, pattern) in &patterns {
+            for var in pattern.variables() {
+                state.stack.push(var.name.clone(), ());
+            }
+        }
+
         // Parse function body
-        let body = match state.input.peek().unwrap() {
+        let mut body: StmtBlock = match state.input.peek().unwrap() {
             (Token::LeftBrace, ..) => self.parse_block(state, settings)?,
             (.., pos) => return Err(PERR::FnMissingBody(name.into()).into_err(*pos)),
         }
         .into();
 
+        for (i, (param, pattern)) in patterns.into_iter().enumerate() {
+            let stmt = make_destructuring_stmt(param, pattern);
+            body.statements_mut().insert(i, stmt);
+        }
+
+        let is_generator = check_yield_statements(body.statements())?;
+
+        // Mark calls that may be replaced by tail calls at runtime
+        if !is_generator {
+            mark_tail_calls(body.statements_mut(), true);
+        }
+
+        let mut param_types: FnArgsVec<_> = if params.iter().any(|(_, typ, _)| typ.is_some()) {
+            params.iter().map(|(_, typ, _)| typ.clone()).collect()
+        } else {
+            FnArgsVec::new_const()
+        };
+        param_types.shrink_to_fit();
+
         let mut params: FnArgsVec<_> = params.into_iter().map(|(p, ..)| p).collect();
         params.shrink_to_fit();
 
@@ -3626,6 +4613,9 @@ impl Engine {
             #[cfg(not(feature = "no_object"))]
             this_type,
             params,
+            param_types,
+            return_type,
+            is_generator,
             body,
             #[cfg(feature = "metadata")]
             comments: comments.into_iter().collect(),
@@ -3677,6 +4667,8 @@ impl Engine {
             args,
             op_token: None,
             capture_parent_scope: false,
+            is_tail_call: false,
+            binding: None,
         }
         .into_fn_call_expr(pos);
 
@@ -3706,32 +4698,69 @@ impl Engine {
     ) -> ParseResult<(Expr, Shared<ScriptFuncDef>, ThinVec<Ident>)> {
         let settings = settings.level_up()?;
         let mut params_list = StaticVec::<ImmutableString>::new_const();
+        let mut patterns = StaticVec::<(Ident, Pattern)>::new_const();
 
         if state.input.next().unwrap().0 != Token::Or && !match_token(state.input, &Token::Pipe).0 {
             loop {
-                match state.input.next().unwrap() {
-                    (Token::Pipe, ..) => break,
-                    (Token::Identifier(s), pos) => {
-                        if params_list.iter().any(|p| p == &*s) {
-                            return Err(
-                                PERR::FnDuplicatedParam(String::new(), s.to_string()).into_err(pos)
-                            );
-                        }
-
-                        let s = state.get_interned_string(*s);
-                        state.stack.push(s.clone(), ());
-                        params_list.push(s);
+                let (s, pos, pattern) = match state.input.peek().unwrap() {
+                    (Token::Pipe, ..) => {
+                        eat_token(state.input, &Token::Pipe);
+                        break;
                     }
-                    (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                    (Token::Identifier(..), ..) => {
+                        self.parse_var_name_or_pattern(state, settings)?
+                    }
+                    (token, ..) if is_pattern_start(token) => {
+                        self.parse_var_name_or_pattern(state, settings)?
+                    }
+                    (Token::LexError(..), ..) => match state.input.next().unwrap() {
+                        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                        token => unreachable!("Token::LexError expected but gets {:?}", token),
+                    },
                     (.., pos) => {
                         return Err(PERR::MissingToken(
                             Token::Pipe.into(),
                             "to close the parameters list of anonymous function or closure".into(),
                         )
-                        .into_err(pos))
+                        .into_err(*pos))
+                    }
+                };
+
+                let is_defined = |n: &str| {
+                    params_list.iter().any(|p| p == n)
+                        || patterns
+                            .iter()
+                            .any(|(_, p)| p.variables().iter().any(|v| v.name == n))
+                };
+
+                match pattern {
+                    Some(ref pattern) => {
+                        if let Some(v) = pattern.variables().iter().find(|v| is_defined(&v.name)) {
+                            return Err(PERR::FnDuplicatedParam(String::new(), v.name.to_string())
+                                .into_err(v.pos));
+                        }
                     }
+                    None if is_defined(&s) => {
+                        return Err(
+                            PERR::FnDuplicatedParam(String::new(), s.to_string()).into_err(pos)
+                        );
+                    }
+                    None => (),
+                }
+
+                let s = state.get_interned_string(s);
+                state.stack.push(s.clone(), ());
+
+                if let Some(pattern) = pattern {
+                    let param = Ident {
+                        name: s.clone(),
+                        pos,
+                    };
+                    patterns.push((param, pattern));
                 }
 
+                params_list.push(s);
+
                 match state.input.next().unwrap() {
                     (Token::Pipe, ..) => break,
                     (Token::Comma, ..) => (),
@@ -3747,8 +4776,27 @@ impl Engine {
             }
         }
 
+        // Variables destructured from the parameters at the start of the body
+        for (_, pattern) in &patterns {
+            for var in pattern.variables() {
+                state.stack.push(var.name.clone(), ());
+            }
+        }
+
         // Parse function body
-        let body = self.parse_stmt(state, settings)?;
+        let mut body: StmtBlock = self.parse_stmt(state, settings)?.into();
+
+        for (i, (param, pattern)) in patterns.into_iter().enumerate() {
+            let stmt = make_destructuring_stmt(param, pattern);
+            body.statements_mut().insert(i, stmt);
+        }
+
+        let is_generator = check_yield_statements(body.statements())?;
+
+        // Mark calls that may be replaced by tail calls at runtime
+        if !is_generator {
+            mark_tail_calls(body.statements_mut(), true);
+        }
 
         // External variables may need to be processed in a consistent order,
         // so extract them into a list.
@@ -3764,11 +4812,29 @@ impl Engine {
         #[cfg(feature = "no_closure")]
         let (mut params, externals) = (FnArgsVec::with_capacity(params_list.len()), ThinVec::new());
 
+        // Captured variables keep their type annotations
+        #[cfg(not(feature = "no_closure"))]
+        let param_types: FnArgsVec<_> = if externals
+            .iter()
+            .any(|v| state.external_var_types.contains_key(&v.name))
+        {
+            externals
+                .iter()
+                .map(|v| state.external_var_types.get(&v.name).cloned())
+                .chain(params_list.iter().map(|_| None))
+                .collect()
+        } else {
+            FnArgsVec::new_const()
+        };
+        #[cfg(feature = "no_closure")]
+        let param_types: FnArgsVec<Option<ImmutableString>> = FnArgsVec::new_const();
+
         params.append(&mut params_list);
 
         // Create unique function name by hashing the script body plus the parameters.
         let hasher = &mut get_hasher();
         params.iter().for_each(|p| p.hash(hasher));
+        param_types.iter().for_each(|t| t.hash(hasher));
         body.hash(hasher);
         let hash = hasher.finish();
         let fn_name = state.get_interned_string(make_anonymous_fn(hash));
@@ -3780,7 +4846,10 @@ impl Engine {
             #[cfg(not(feature = "no_object"))]
             this_type: None,
             params,
-            body: body.into(),
+            param_types,
+            return_type: None,
+            is_generator,
+            body,
             #[cfg(not(feature = "no_function"))]
             #[cfg(feature = "metadata")]
             comments: <_>::default(),
@@ -3829,6 +4898,8 @@ impl Engine {
         let mut statements = StmtBlockContainer::new_const();
         statements.push(Stmt::Expr(expr.into()));
 
+        self.check_async_calls(state, &statements)?;
+
         #[cfg(not(feature = "no_optimize"))]
         return Ok(self.optimize_into_ast(
             state.external_constants,
@@ -3847,7 +4918,7 @@ impl Engine {
     }
 
     /// Parse the global level statements.
-    fn parse_global_level(
+    pub(crate) fn parse_global_level(
         &self,
         state: &mut ParseState,
         process_settings: impl FnOnce(&mut ParseSettings),
@@ -3900,6 +4971,8 @@ impl Engine {
             }
         }
 
+        self.check_async_calls(state, &statements)?;
+
         #[cfg(not(feature = "no_function"))]
         let lib = state.lib.values().cloned().collect();
         #[cfg(feature = "no_function")]
@@ -3908,6 +4981,46 @@ impl Engine {
         Ok((statements, lib))
     }
 
+    /// Check that all calls to asynchronous functions registered with the [`Engine`], in the
+    /// global statements and in script-defined functions, can suspend asynchronous evaluation.
+    fn check_async_calls(&self, state: &ParseState, statements: &[Stmt]) -> ParseResult<()> {
+        if self.async_fns.is_empty() {
+            return Ok(());
+        }
+
+        // Script-defined functions take precedence over asynchronous functions
+        #[cfg(not(feature = "no_function"))]
+        let async_fns = &self
+            .async_fns
+            .iter()
+            .copied()
+            .filter(|hash| !state.lib.contains_key(hash))
+            .collect();
+        #[cfg(feature = "no_function")]
+        let async_fns = {
+            let _ = state;
+            &self.async_fns
+        };
+
+        check_async_call_sites(statements, async_fns)?;
+
+        // Closures and generator functions are never run where evaluation can suspend
+        #[cfg(not(feature = "no_function"))]
+        for fn_def in state.lib.values() {
+            if fn_def.is_generator || is_anonymous_fn(&fn_def.name) {
+                fn_def
+                    .body
+                    .statements()
+                    .iter()
+                    .try_for_each(|stmt| ensure_no_async_call(ASTNode::Stmt(stmt), async_fns))?;
+            } else {
+                check_async_call_sites(fn_def.body.statements(), async_fns)?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Run the parser on an input stream, returning an AST.
     #[inline]
     pub(crate) fn parse(
@@ -39,7 +39,7 @@ fn check_struct_sizes() {
         return;
     }
 
-    assert_eq!(size_of::<Scope>(), 24);
+    assert_eq!(size_of::<Scope>(), 32);
     assert_eq!(
         size_of::<FnPtr>(),
         32 - if cfg!(feature = "no_function") {
@@ -29,6 +29,10 @@ pub struct TokenizerControlBlock {
     ///
     /// Set to `Some` in order to collect a compressed script.
     pub compressed: Option<String>,
+    /// Has the script ended within a multi-line string or a block comment?
+    ///
+    /// Such a script may be completed by more input (e.g. when reading it line by line).
+    pub is_incomplete: bool,
 }
 
 impl TokenizerControlBlock {
@@ -41,6 +45,7 @@ impl TokenizerControlBlock {
             #[cfg(feature = "metadata")]
             global_comments: String::new(),
             compressed: None,
+            is_incomplete: false,
         }
     }
 }
@@ -48,6 +53,12 @@ impl TokenizerControlBlock {
 /// _(internals)_ A shared object that allows control of the tokenizer from outside.
 pub type TokenizerControl = Rc<RefCell<TokenizerControlBlock>>;
 
+/// _(internals)_ A token recorded together with its original text and position.
+pub type RecordedToken = (Token, SmartString, Position);
+
+/// _(internals)_ A shared buffer of recorded tokens.
+pub type TokenRecorder = Rc<RefCell<Vec<RecordedToken>>>;
+
 type LERR = LexError;
 
 /// Separator character for numbers.
@@ -1932,7 +1943,7 @@ fn get_next_token_inner(
 
                         // Long streams of `///...` are not doc-comments
                         match stream.peek_next() {
-                            Some('/') => None,
+                            Some('/') => state.include_comments.then(|| "///".into()),
                             _ => Some("///".into()),
                         }
                     }
@@ -1972,6 +1983,10 @@ fn get_next_token_inner(
                             *g += "\n";
                         }
                         *g += &comment;
+
+                        if state.include_comments {
+                            return (Token::Comment(comment.into()), start_pos);
+                        }
                     }
                     Some(comment) => return (Token::Comment(comment.into()), start_pos),
                     None => (),
@@ -1989,7 +2004,7 @@ fn get_next_token_inner(
 
                         // Long streams of `/****...` are not doc-comments
                         match stream.peek_next() {
-                            Some('*') => None,
+                            Some('*') => state.include_comments.then(|| "/**".into()),
                             _ => Some("/**".into()),
                         }
                     }
@@ -2474,13 +2489,18 @@ pub struct TokenIterator<'a> {
     pub stream: MultiInputsStream<'a>,
     /// A processor function that maps a token to another.
     pub token_mapper: Option<&'a OnParseTokenCallback>,
+    /// Buffer to record tokens (including comments) together with their original text and position.
+    ///
+    /// Set to `Some` in order to record all tokens.
+    /// Comments are recorded and then skipped, so they are never returned to the parser.
+    pub recorded_tokens: Option<TokenRecorder>,
 }
 
 impl<'a> Iterator for TokenIterator<'a> {
     type Item = (Token, Position);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (within_interpolated, compress_script) = {
+        let (within_interpolated, compress_script, record_tokens) = {
             let control = &mut *self.state.tokenizer_control.borrow_mut();
 
             if control.is_within_text {
@@ -2493,10 +2513,33 @@ impl<'a> Iterator for TokenIterator<'a> {
             (
                 self.state.is_within_text_terminated_by.is_some(),
                 control.compressed.is_some(),
+                self.recorded_tokens.is_some(),
             )
         };
 
-        let (token, pos) = match get_next_token(&mut self.stream, &mut self.state, &mut self.pos) {
+        let mut next = get_next_token(&mut self.stream, &mut self.state, &mut self.pos);
+
+        // Record and skip comments, if needed
+        if record_tokens {
+            while let (Token::Comment(comment), pos) = next {
+                let text = comment.as_str().into();
+
+                if let Some(ref tokens) = self.recorded_tokens {
+                    tokens
+                        .borrow_mut()
+                        .push((Token::Comment(comment), text, pos));
+                }
+
+                next = get_next_token(&mut self.stream, &mut self.state, &mut self.pos);
+            }
+        }
+
+        let (token, pos) = match next {
+            // {EOF} within a block comment
+            r @ (Token::EOF, _) if self.state.comment_level > 0 => {
+                self.state.tokenizer_control.borrow_mut().is_incomplete = true;
+                return Some(r);
+            }
             // {EOF}
             r @ (Token::EOF, _) => return Some(r),
             // {EOF} after unterminated string.
@@ -2506,6 +2549,7 @@ impl<'a> Iterator for TokenIterator<'a> {
             // script it is a syntax error.
             (Token::StringConstant(..), pos) if self.state.is_within_text_terminated_by.is_some() => {
                 self.state.is_within_text_terminated_by = None;
+                self.state.tokenizer_control.borrow_mut().is_incomplete = true;
                 return Some((Token::LexError(LERR::UnterminatedString.into()), pos));
             }
             // Reserved keyword/symbol
@@ -2624,6 +2668,38 @@ impl<'a> Iterator for TokenIterator<'a> {
             }
         }
 
+        // Record the token together with its original text, if needed
+        if record_tokens && token != Token::EOF {
+            // Keep improper symbols as written
+            let improper_symbol = match token {
+                Token::LexError(ref err) => match **err {
+                    LERR::ImproperSymbol(ref s, ..) => Some(s.as_str()),
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            let text = match (improper_symbol, &self.state.last_token) {
+                (Some(s), ..) => s.into(),
+                (None, Some(last_token)) if last_token.is_empty() => token.to_string().into(),
+                (None, Some(last_token))
+                    if within_interpolated
+                        && matches!(
+                            token,
+                            Token::StringConstant(..) | Token::InterpolatedString(..)
+                        ) =>
+                {
+                    last_token[1..].into()
+                }
+                (None, Some(last_token)) => last_token.clone(),
+                (None, None) => token.to_string().into(),
+            };
+
+            if let Some(ref tokens) = self.recorded_tokens {
+                tokens.borrow_mut().push((token.clone(), text, pos));
+            }
+        }
+
         Some((token, pos))
     }
 }
@@ -2692,6 +2768,7 @@ impl Engine {
                     index: 0,
                 },
                 token_mapper,
+                recorded_tokens: None,
             },
             buffer2,
         )
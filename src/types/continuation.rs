@@ -0,0 +1,226 @@
+//! Types for suspending and resuming the evaluation of a script.
+
+use crate::{Dynamic, ImmutableString, Scope, Shared, INT};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A handle to request the suspension of an evaluation started by
+/// [`Engine::eval_ast_suspendable`][crate::Engine::eval_ast_suspendable] or
+/// [`Engine::resume_ast`][crate::Engine::resume_ast].
+///
+/// Clones of a [`Suspender`] refer to the same request, so a clone can be captured by host
+/// functions or by the [progress callback][crate::Engine::on_progress].
+///
+/// The evaluation is suspended before the next statement is run, after the statement running when
+/// the suspension is requested completes.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use rhai::{Engine, EvalOutcome, Scope, Suspender};
+///
+/// let mut engine = Engine::new();
+/// let suspender = Suspender::new();
+///
+/// let s = suspender.clone();
+/// engine.register_fn("checkpoint", move || s.suspend());
+///
+/// let ast = engine.compile("let x = 40; checkpoint(); x + 2")?;
+/// let mut scope = Scope::new();
+///
+/// let EvalOutcome::Suspended(continuation) =
+///     engine.eval_ast_suspendable(&mut scope, &ast, &suspender)?
+/// else {
+///     unreachable!()
+/// };
+///
+/// // The continuation can be kept (or serialized) and resumed later
+/// let mut scope = Scope::new();
+///
+/// let EvalOutcome::Done(result) =
+///     engine.resume_ast(&mut scope, &ast, continuation, &suspender)?
+/// else {
+///     unreachable!()
+/// };
+///
+/// assert_eq!(result.as_int().unwrap(), 42);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Suspender(Shared<AtomicBool>);
+
+impl Suspender {
+    /// Create a new [`Suspender`].
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Request the suspension of the evaluation.
+    #[inline(always)]
+    pub fn suspend(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+    /// Is the suspension of the evaluation requested?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_requested(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+    /// Clear the request, returning whether there was one.
+    #[inline(always)]
+    pub(crate) fn take_request(&self) -> bool {
+        self.0.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// Result of an evaluation that can be suspended.
+#[derive(Debug, Clone)]
+pub enum EvalOutcome {
+    /// The evaluation finished, with its result value.
+    Done(Dynamic),
+    /// The evaluation was suspended.
+    Suspended(Continuation),
+}
+
+/// A suspended evaluation of a script, which can be resumed by
+/// [`Engine::resume_ast`][crate::Engine::resume_ast] with the same [`AST`][crate::AST].
+///
+/// It holds all variables, the call stack of script-defined functions and the position of the
+/// next statement to run.
+///
+/// Under the `serde` feature, a [`Continuation`] can be serialized, for example to resume the
+/// evaluation in another process. This fails if any variable holds a value that cannot be
+/// serialized, such as a function pointer or a custom type.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct Continuation {
+    /// Variables visible to the next statement.
+    pub(crate) scope: Vec<ScopeEntry>,
+    /// Stack of statements blocks being run, outermost first.
+    pub(crate) frames: Vec<FrameSnapshot>,
+    /// Value of the last statement run.
+    pub(crate) value: Dynamic,
+    /// Number of operations performed so far.
+    pub(crate) num_operations: u64,
+    /// Custom state of the evaluation.
+    pub(crate) tag: Dynamic,
+}
+
+/// A variable kept in a [`Continuation`].
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub(crate) struct ScopeEntry {
+    /// Name of the variable.
+    pub name: ImmutableString,
+    /// Value of the variable.
+    pub value: Dynamic,
+    /// Is the variable a constant?
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub is_constant: bool,
+}
+
+impl ScopeEntry {
+    /// Copy all the variables in a [`Scope`].
+    #[must_use]
+    pub fn from_scope(scope: &Scope) -> Vec<Self> {
+        scope
+            .iter_inner()
+            .map(|(name, is_constant, value)| Self {
+                name: name.clone(),
+                value: value.clone(),
+                is_constant,
+            })
+            .collect()
+    }
+    /// Create a [`Scope`] containing variables.
+    #[must_use]
+    pub fn into_scope(entries: Vec<Self>) -> Scope<'static> {
+        let mut scope = Scope::with_capacity(entries.len());
+
+        for entry in entries {
+            if entry.is_constant {
+                scope.push_constant_dynamic(entry.name, entry.value);
+            } else {
+                scope.push_dynamic(entry.name, entry.value);
+            }
+        }
+
+        scope
+    }
+}
+
+/// A statements block kept in a [`Continuation`].
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub(crate) struct FrameSnapshot {
+    /// Kind of the statements block.
+    pub kind: FrameSnapshotKind,
+    /// Index of the next statement.
+    pub index: usize,
+    /// Length of the scope when the block started.
+    pub scope_len: usize,
+    /// Value of `always_search_scope` when the block started.
+    pub always_search_scope: bool,
+}
+
+/// Kind of a statements block kept in a [`Continuation`].
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub(crate) enum FrameSnapshotKind {
+    /// The root statements.
+    Body,
+    /// A statements block.
+    Block,
+    /// The `then` branch of an `if` statement.
+    Then,
+    /// The `else` branch of an `if` statement.
+    Else,
+    /// The body of a `while`, `loop` or `do` loop.
+    Loop,
+    /// The body of a `for` loop over a range, with the current value, the end (exclusive) and the
+    /// step of the range, and the number of iterations so far.
+    For(INT, INT, INT, usize),
+    /// The body of a `try` statement.
+    Try,
+    /// The `catch` block of a `try` statement, with the value of the error caught.
+    Catch(Dynamic),
+    /// The body of a script-defined function.
+    #[cfg(not(feature = "no_function"))]
+    Call {
+        /// Name of the function.
+        name: ImmutableString,
+        /// Number of parameters of the function.
+        num_params: usize,
+        /// Is the function called by the statement of the parent block?
+        from_script: bool,
+        /// Name and number of arguments of the function called by the statement of the parent
+        /// block, if it is replaced by a tail call to this function.
+        tail_called_from: Option<(ImmutableString, usize)>,
+        /// Return types of the functions replaced by tail calls.
+        return_types: Vec<ImmutableString>,
+        /// Variables of the caller, if hidden from the function.
+        caller_scope: Option<Vec<ScopeEntry>>,
+        /// Source of the caller.
+        caller_source: Option<ImmutableString>,
+    },
+}
@@ -254,7 +254,7 @@ impl FnPtr {
                     caches,
                     &mut crate::Scope::new(),
                     this_ptr,
-                    self.environ.as_deref(),
+                    self.environ.as_ref(),
                     fn_def,
                     args,
                     true,
@@ -0,0 +1,133 @@
+//! The `Generator` type.
+#![cfg(not(feature = "no_function"))]
+
+use crate::ast::{EncapsulatedEnviron, ScriptFuncDef};
+use crate::eval::Resumable;
+use crate::func::{locked_read, Locked};
+use crate::{Dynamic, ImmutableString, NativeCallContext, RhaiResultOf, Scope, Shared};
+use std::fmt;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Suspended state of a generator.
+pub(crate) struct GeneratorState {
+    /// The generator function.
+    pub fn_def: Shared<ScriptFuncDef>,
+    /// Encapsulated environment of the generator function, if any.
+    #[cfg_attr(feature = "no_module", allow(dead_code))]
+    pub environ: Option<Shared<EncapsulatedEnviron>>,
+    /// Copy of the object the generator function was called on, if any.
+    pub this_ptr: Option<Dynamic>,
+    /// Local variables of the generator function.
+    pub scope: Scope<'static>,
+    /// State of the function body being run.
+    pub frames: Resumable,
+    /// Has the generator finished?
+    pub done: bool,
+}
+
+impl GeneratorState {
+    /// Mark the generator as finished, releasing all its local variables.
+    #[inline]
+    pub fn finish(&mut self) {
+        self.done = true;
+        self.this_ptr = None;
+        self.scope.clear();
+        self.frames = Resumable::default();
+    }
+}
+
+/// A suspended run of a script-defined generator function.
+///
+/// Calling a function that contains `yield` statements returns a [`Generator`] without running the
+/// function body. The body runs up to the next `yield` each time the generator is resumed, either
+/// by iterating over it with `for` or by calling `next` on it.
+///
+/// `yield` statements can be placed inside blocks, `if` statements, loops and `try`/`catch` blocks,
+/// but not inside expressions (e.g. statement blocks used as values) or `switch` cases, where they
+/// are rejected when the script is compiled.
+///
+/// Cloning a [`Generator`] produces another reference to the same suspended run.
+///
+/// If the generator function is called as a method, `this` refers to a _copy_ of the object.
+///
+/// Not available under `no_function`.
+#[derive(Clone)]
+pub struct Generator {
+    pub(crate) name: ImmutableString,
+    pub(crate) state: Shared<Locked<GeneratorState>>,
+}
+
+impl fmt::Debug for Generator {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Generator").field(&self.name).finish()
+    }
+}
+
+impl fmt::Display for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Generator({})", self.name)
+    }
+}
+
+impl Generator {
+    /// Create a new [`Generator`] that has not yet started running.
+    #[must_use]
+    pub(crate) fn new(
+        fn_def: Shared<ScriptFuncDef>,
+        environ: Option<Shared<EncapsulatedEnviron>>,
+        this_ptr: Option<Dynamic>,
+        scope: Scope<'static>,
+    ) -> Self {
+        Self {
+            name: fn_def.name.clone(),
+            state: Shared::new(Locked::new(GeneratorState {
+                fn_def,
+                environ,
+                this_ptr,
+                scope,
+                frames: Resumable::default(),
+                done: false,
+            })),
+        }
+    }
+    /// Get the name of the generator function.
+    #[inline(always)]
+    #[must_use]
+    pub fn fn_name(&self) -> &str {
+        &self.name
+    }
+    /// Has the generator finished?
+    ///
+    /// A generator finishes when its function body ends, when it runs a `return` statement or when
+    /// it fails with an error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the generator is currently running.
+    #[inline]
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        locked_read(&self.state).done
+    }
+    /// Run the generator up to its next `yield` statement.
+    ///
+    /// Returns `None` when the generator has finished.
+    ///
+    /// The generator runs with a copy of the global runtime state of the calling function, so
+    /// operations counted while it runs are not added to the calling script.
+    /// Calling `next` on a generator within a script resumes it with the script's own state.
+    #[inline]
+    pub fn resume(&self, context: &NativeCallContext) -> RhaiResultOf<Option<Dynamic>> {
+        let global = &mut context.global_runtime_state().clone();
+        global.level += 1;
+
+        let caches = &mut crate::eval::Caches::new();
+
+        context
+            .engine()
+            .resume_generator(global, caches, self, context.position())
+    }
+}
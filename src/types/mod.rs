@@ -1,11 +1,13 @@
 //! Module defining Rhai data types.
 
 pub mod bloom_filter;
+pub mod continuation;
 pub mod custom_types;
 pub mod dynamic;
 pub mod error;
 pub mod float;
 pub mod fn_ptr;
+pub mod generator;
 pub mod immutable_string;
 pub mod interner;
 pub mod parse_error;
@@ -16,6 +18,7 @@ pub mod var_def;
 pub mod variant;
 
 pub use bloom_filter::BloomFilterU64;
+pub use continuation::{Continuation, EvalOutcome, Suspender};
 pub use custom_types::{CustomTypeInfo, CustomTypesCollection};
 pub use dynamic::Dynamic;
 #[cfg(not(feature = "no_time"))]
@@ -24,6 +27,8 @@ pub use error::EvalAltResult;
 #[cfg(not(feature = "no_float"))]
 pub use float::FloatWrapper;
 pub use fn_ptr::FnPtr;
+#[cfg(not(feature = "no_function"))]
+pub use generator::Generator;
 pub use immutable_string::ImmutableString;
 pub use interner::StringsInterner;
 pub use parse_error::{LexError, ParseError, ParseErrorType};
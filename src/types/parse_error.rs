@@ -177,6 +177,10 @@ pub enum ParseErrorType {
     ///
     /// Only appears when strict variables mode is enabled.
     ModuleUndefined(String),
+    /// A type annotation names a type that is not known.
+    ///
+    /// Type names given as string literals are not checked.
+    TypeUndefined(String),
     /// Expression exceeding the maximum levels of complexity.
     ExprTooDeep,
     /// Number of scripted functions over maximum limit.
@@ -185,6 +189,11 @@ pub enum ParseErrorType {
     LiteralTooLarge(String, usize),
     /// Break statement not inside a loop.
     LoopBreak,
+    /// Yield statement inside an expression or a `switch` case.
+    WrongYield,
+    /// Call to an asynchronous function where asynchronous evaluation cannot be suspended.
+    /// Wrapped value is the function name.
+    WrongAsyncCall(String),
 }
 
 impl fmt::Display for ParseErrorType {
@@ -221,6 +230,7 @@ impl fmt::Display for ParseErrorType {
             Self::VariableExists(s) => write!(f, "Variable already defined: {s}"),
             Self::VariableUndefined(s) => write!(f, "Undefined variable: {s}"),
             Self::ModuleUndefined(s) => write!(f, "Undefined module: {s}"),
+            Self::TypeUndefined(s) => write!(f, "Undefined type: {s}"),
 
             Self::MismatchedType(r, a) => write!(f, "Expecting {r}, not {a}"),
             Self::ExprExpected(s) => write!(f, "Expecting {s} expression"),
@@ -252,6 +262,8 @@ impl fmt::Display for ParseErrorType {
             Self::ExprTooDeep => f.write_str("Expression exceeds maximum complexity"),
             Self::TooManyFunctions => f.write_str("Number of functions defined exceeds maximum limit"),
             Self::LoopBreak => f.write_str("Break statement should only be used inside a loop"),
+            Self::WrongYield => f.write_str("Yield statement cannot be used inside an expression or a switch case"),
+            Self::WrongAsyncCall(s) => write!(f, "Asynchronous function '{s}' can only be called by itself in a statement, e.g. '{s}(..);' or 'let x = {s}(..);'"),
 
             #[allow(deprecated)]
             Self::DuplicatedSwitchCase => f.write_str("Duplicated switch case"),
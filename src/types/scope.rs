@@ -8,6 +8,7 @@ use std::{
     fmt, iter,
     iter::{Extend, FromIterator},
     marker::PhantomData,
+    mem,
 };
 
 /// Minimum number of entries in the [`Scope`] to avoid reallocations.
@@ -53,7 +54,7 @@ pub const MIN_SCOPE_ENTRIES: usize = 8;
 //
 // # Implementation Notes
 //
-// [`Scope`] is implemented as three arrays.  Two (`values` and `names`) are of exactly the same
+// [`Scope`] is implemented as four arrays.  Two (`values` and `names`) are of exactly the same
 // length. That's because in the vast majority of cases the name is NOT used to look up a variable.
 // Variable lookup is usually via direct indexing, by-passing the name altogether.
 //
@@ -69,6 +70,10 @@ pub struct Scope<'a> {
     /// This `Vec` is not filled until needed because aliases are used rarely
     /// (only for `export` statements).
     aliases: ThinVec<StaticVec<ImmutableString>>,
+    /// Type annotation of the entry, if any.
+    ///
+    /// This `Vec` is not filled until needed because type annotations are used rarely.
+    types: ThinVec<Option<ImmutableString>>,
     /// Phantom to keep the lifetime parameter in order not to break existing code.
     dummy: PhantomData<&'a ()>,
 }
@@ -106,6 +111,7 @@ impl Clone for Scope<'_> {
                 .collect(),
             names: self.names.clone(),
             aliases: self.aliases.clone(),
+            types: self.types.clone(),
             dummy: self.dummy,
         }
     }
@@ -175,6 +181,7 @@ impl Scope<'_> {
             values: ThinVec::new(),
             names: ThinVec::new(),
             aliases: ThinVec::new(),
+            types: ThinVec::new(),
             dummy: PhantomData,
         }
     }
@@ -197,6 +204,7 @@ impl Scope<'_> {
             values: ThinVec::with_capacity(capacity),
             names: ThinVec::with_capacity(capacity),
             aliases: ThinVec::new(),
+            types: ThinVec::new(),
             dummy: PhantomData,
         }
     }
@@ -224,6 +232,7 @@ impl Scope<'_> {
         self.names.clear();
         self.values.clear();
         self.aliases.clear();
+        self.types.clear();
         self
     }
     /// Get the number of entries inside the [`Scope`].
@@ -402,6 +411,7 @@ impl Scope<'_> {
             .unwrap_or_else(|| panic!("`Scope` is empty"));
         self.values.truncate(self.names.len());
         self.aliases.truncate(self.names.len());
+        self.types.truncate(self.names.len());
         self
     }
     /// Remove the last entry from the [`Scope`] and return it.
@@ -409,6 +419,7 @@ impl Scope<'_> {
     #[allow(dead_code)]
     pub(crate) fn pop_entry(&mut self) -> Option<(ImmutableString, Dynamic, Vec<ImmutableString>)> {
         self.values.pop().map(|value| {
+            self.types.truncate(self.values.len());
             (
                 self.names.pop().unwrap(),
                 value,
@@ -420,6 +431,29 @@ impl Scope<'_> {
             )
         })
     }
+    /// Move all entries out of the [`Scope`], leaving it empty.
+    #[inline]
+    #[must_use]
+    #[allow(dead_code)]
+    pub(crate) fn take_entries(&mut self) -> Scope<'static> {
+        Scope {
+            values: mem::take(&mut self.values),
+            names: mem::take(&mut self.names),
+            aliases: mem::take(&mut self.aliases),
+            types: mem::take(&mut self.types),
+            dummy: PhantomData,
+        }
+    }
+    /// Put back entries moved out by [`take_entries`][Scope::take_entries], replacing all
+    /// current entries.
+    #[inline]
+    #[allow(dead_code)]
+    pub(crate) fn restore_entries(&mut self, entries: Scope<'static>) {
+        self.values = entries.values;
+        self.names = entries.names;
+        self.aliases = entries.aliases;
+        self.types = entries.types;
+    }
     /// Truncate (rewind) the [`Scope`] to a previous size.
     ///
     /// # Example
@@ -451,6 +485,7 @@ impl Scope<'_> {
         self.names.truncate(size);
         self.values.truncate(size);
         self.aliases.truncate(size);
+        self.types.truncate(size);
         self
     }
     /// Does the [`Scope`] contain the entry?
@@ -762,6 +797,9 @@ impl Scope<'_> {
             if self.aliases.len() > index {
                 self.aliases.remove(index);
             }
+            if self.types.len() > index {
+                self.types.remove(index);
+            }
             self.values.remove(index).try_cast()
         })
     }
@@ -806,6 +844,34 @@ impl Scope<'_> {
     pub(crate) fn get_mut_by_index(&mut self, index: usize) -> &mut Dynamic {
         &mut self.values[index]
     }
+    /// Get the type annotation of an entry in the [`Scope`] based on the index, if any.
+    #[inline]
+    #[must_use]
+    pub(crate) fn get_type_by_index(&self, index: usize) -> Option<&ImmutableString> {
+        self.types.get(index).and_then(Option::as_ref)
+    }
+    /// Set the type annotation of an entry in the [`Scope`] based on the index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[inline]
+    pub(crate) fn set_type_by_index(
+        &mut self,
+        index: usize,
+        typ: Option<ImmutableString>,
+    ) -> &mut Self {
+        assert!(index < self.len(), "index out of bounds");
+
+        if self.types.len() <= index {
+            if typ.is_none() {
+                return self;
+            }
+            self.types.resize(index + 1, None);
+        }
+        self.types[index] = typ;
+        self
+    }
     /// Add an alias to an entry in the [`Scope`].
     ///
     /// # Panics
@@ -871,6 +937,9 @@ impl Scope<'_> {
                 scope.aliases.resize(scope.len() - 1, <_>::default());
                 scope.aliases.push(self.aliases[index].clone());
             }
+
+            let typ = self.get_type_by_index(index).cloned();
+            scope.set_type_by_index(scope.len() - 1, typ);
         });
 
         scope
@@ -951,6 +1020,14 @@ impl Scope<'_> {
                 self.aliases.drain(start..start + len).for_each(|_| {});
             }
         }
+
+        if self.types.len() > start {
+            if self.types.len() <= start + len {
+                self.types.truncate(start);
+            } else {
+                self.types.drain(start..start + len).for_each(|_| {});
+            }
+        }
     }
 }
 
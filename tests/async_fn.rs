@@ -0,0 +1,284 @@
+use rhai::{Engine, EvalAltResult, ParseErrorType, Scope, INT};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Run a future to completion, returning its output and the number of times it was pending.
+fn block_on<F: Future>(future: F) -> (F::Output, usize) {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut future = Box::pin(future);
+    let mut pending = 0;
+
+    loop {
+        match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+            Poll::Ready(value) => return (value, pending),
+            Poll::Pending => pending += 1,
+        }
+    }
+}
+
+/// A future that is pending twice before producing its value.
+///
+/// The first poll happens when the asynchronous function is called, so the evaluation is suspended
+/// once for each call.
+struct Delay<T>(Option<T>, usize);
+
+impl<T: Unpin> Future for Delay<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        if self.1 >= 2 {
+            Poll::Ready(self.0.take().unwrap())
+        } else {
+            self.1 += 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+fn delay<T>(value: T) -> Delay<T> {
+    Delay(Some(value), 0)
+}
+
+fn make_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine
+        .register_async_fn("delay", |x: INT| delay(Ok(x)))
+        .register_async_fn("now", |x: INT| std::future::ready(Ok(x)))
+        .register_async_fn("fail", |x: INT| delay(Err::<INT, _>(EvalAltResult::ErrorRuntime(x.into(), rhai::Position::NONE).into())));
+
+    engine
+}
+
+#[test]
+fn test_async_statements() {
+    let engine = make_engine();
+
+    let (result, pending) = block_on(engine.eval_async::<INT>(
+        "
+            let x = delay(1);
+            x = delay(x + 1);
+            delay(0);
+            x + 40
+        ",
+    ));
+    assert_eq!(result.unwrap(), 42);
+    assert_eq!(pending, 3);
+
+    let (result, pending) = block_on(engine.eval_async::<INT>("return delay(42); 0"));
+    assert_eq!(result.unwrap(), 42);
+    assert_eq!(pending, 1);
+
+    let (result, pending) = block_on(engine.eval_async::<INT>("let x = now(41); x + 1"));
+    assert_eq!(result.unwrap(), 42);
+    assert_eq!(pending, 0);
+
+    let mut scope = Scope::new();
+    scope.push("x", 40 as INT);
+
+    let (result, _) = block_on(engine.eval_with_scope_async::<()>(&mut scope, "let y = delay(2); x += y;"));
+    result.unwrap();
+    assert_eq!(scope.get_value::<INT>("x").unwrap(), 42);
+    assert_eq!(scope.get_value::<INT>("y").unwrap(), 2);
+}
+
+#[test]
+fn test_async_blocks() {
+    let engine = make_engine();
+
+    let (result, pending) = block_on(engine.eval_async::<INT>(
+        "
+            let sum = 0;
+
+            for i in 0..3 {
+                let v = delay(i + 10);
+                sum += v;
+            }
+
+            let n = 0;
+            while n < 2 {
+                n += 1;
+                sum = delay(sum + 1);
+                if n > 5 { break; }
+            }
+
+            try {
+                fail(7);
+                sum = 0;
+            } catch (e) {
+                sum += e;
+            }
+
+            sum
+        ",
+    ));
+    assert_eq!(result.unwrap(), 42);
+    assert_eq!(pending, 6);
+
+    let (result, _) = block_on(engine.eval_async::<INT>("fail(42); 0"));
+    assert!(matches!(*result.unwrap_err(), EvalAltResult::ErrorRuntime(v, ..) if v.as_int().unwrap() == 42));
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_async_functions() {
+    let engine = make_engine();
+
+    let (result, pending) = block_on(engine.eval_async::<INT>(
+        "
+            fn add(a, b) {
+                let c = delay(b);
+                if c > 100 { return c; }
+                a + c
+            }
+            fn twice(x) {
+                let y = add(x, x);
+                return add(y, 0);
+            }
+
+            let sum = add(1, 1);
+            sum = twice(sum);
+            sum + 38
+        ",
+    ));
+    assert_eq!(result.unwrap(), 42);
+    assert_eq!(pending, 3);
+
+    let (result, _) = block_on(engine.eval_async::<INT>(
+        "
+            let secret = 42;
+            fn peek() { delay(0); secret }
+            peek()
+        ",
+    ));
+    assert!(matches!(*result.unwrap_err(), EvalAltResult::ErrorInFunctionCall(..)));
+
+    let (result, _) = block_on(engine.eval_async::<INT>(
+        "
+            fn oops() { fail(42); }
+            let r = 0;
+            try { oops(); } catch (e) { r = e; }
+            r
+        ",
+    ));
+    assert_eq!(result.unwrap(), 42);
+
+    let ast = engine
+        .compile(
+            "
+                let z = delay(1);
+                fn add(x, y) { let z = delay(y); x + z }
+            ",
+        )
+        .unwrap();
+
+    let mut scope = Scope::new();
+    let (result, pending) = block_on(engine.call_fn_async::<INT>(&mut scope, &ast, "add", (2 as INT, 40 as INT)));
+    assert_eq!(result.unwrap(), 42);
+    assert_eq!(pending, 2);
+    assert!(scope.is_empty());
+
+    let (result, _) = block_on(engine.call_fn_async::<INT>(&mut scope, &ast, "sub", (2 as INT, 40 as INT)));
+    assert!(matches!(*result.unwrap_err(), EvalAltResult::ErrorFunctionNotFound(..)));
+}
+
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_optimize"))]
+#[test]
+fn test_async_tail_calls() {
+    let mut engine = make_engine();
+    engine.set_tail_call_optimization(true);
+
+    let (result, pending) = block_on(engine.eval_async::<INT>(
+        "
+            fn loop1(n, acc) { if n == 0 { return delay(acc); } loop1(n - 1, acc + 1) }
+            loop1(100000, 0)
+        ",
+    ));
+    assert_eq!(result.unwrap(), 100000);
+    assert_eq!(pending, 1);
+
+    let (result, _) = block_on(engine.eval_async::<bool>(
+        "
+            fn even(n) { if n == 0 { true } else { odd(n - 1) } }
+            fn odd(n) { if n == 0 { false } else { even(n - 1) } }
+            even(10001)
+        ",
+    ));
+    assert!(!result.unwrap());
+}
+
+#[test]
+fn test_async_cannot_suspend() {
+    let engine = make_engine();
+
+    assert_eq!(engine.eval::<INT>("let x = now(40); x + 2").unwrap(), 42);
+    assert!(matches!(*engine.eval::<INT>("delay(42)").unwrap_err(), EvalAltResult::ErrorRuntime(..)));
+
+    // Calls that are not whole statements are rejected when compiling
+    let mut scripts = vec!["let x = 1 + delay(41); x", "if delay(1) > 0 { 42 }", "while now(0) > 0 { }", "foo(delay(1))", "switch 1 { 1 => delay(1), _ => 0 }"];
+
+    #[cfg(not(feature = "no_index"))]
+    scripts.push("let x = [delay(41)]; x[0]");
+    #[cfg(not(feature = "no_object"))]
+    scripts.push("let x = 41; x.delay()");
+    #[cfg(not(feature = "no_function"))]
+    scripts.extend(["fn foo() { let x = delay(1) * 2; }", "let f = || delay(1); call(f)", "fn gen() { let x = delay(1); yield x; }"]);
+
+    for script in scripts {
+        let err = engine.compile(script).unwrap_err();
+        assert_eq!(*err.err_type(), ParseErrorType::WrongAsyncCall(if script.contains("now") { "now" } else { "delay" }.into()), "{script}");
+
+        let (result, _) = block_on(engine.eval_async::<INT>(script));
+        assert!(matches!(*result.unwrap_err(), EvalAltResult::ErrorParsing(ParseErrorType::WrongAsyncCall(..), ..)), "{}", script);
+    }
+
+    // Script-defined functions take precedence
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(engine.eval::<INT>("fn delay(x) { x } 1 + delay(41)").unwrap(), 42);
+
+    // Script-defined functions called where evaluation cannot be suspended
+    #[cfg(not(feature = "no_function"))]
+    {
+        let (result, _) = block_on(engine.eval_async::<INT>("fn foo() { delay(41) } 1 + foo()"));
+        assert!(matches!(*result.unwrap_err(), EvalAltResult::ErrorInFunctionCall(.., err, _) if matches!(*err, EvalAltResult::ErrorRuntime(..))));
+    }
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_async_limits() {
+    let mut engine = make_engine();
+
+    engine.set_max_operations(500);
+
+    let (result, _) = block_on(engine.eval_async::<()>("loop { delay(0); }"));
+    assert!(matches!(*result.unwrap_err(), EvalAltResult::ErrorTooManyOperations(..)));
+
+    engine.set_max_operations(0);
+
+    engine.on_progress(|count| if count > 500 { Some(rhai::Dynamic::UNIT) } else { None });
+
+    let (result, _) = block_on(engine.eval_async::<()>("while true { let x = delay(0); }"));
+    assert!(matches!(*result.unwrap_err(), EvalAltResult::ErrorTerminated(..)));
+}
+
+#[cfg(feature = "sync")]
+#[test]
+fn test_async_send() {
+    fn assert_send<T: Send>(_: &T) {}
+
+    let engine = make_engine();
+    let future = engine.eval_async::<INT>("delay(42)");
+    assert_send(&future);
+    assert_eq!(block_on(future).0.unwrap(), 42);
+}
@@ -0,0 +1,110 @@
+use rhai::{Dynamic, Engine, ImmutableString, Scope, INT};
+
+#[test]
+fn test_binding() {
+    let mut engine = Engine::new();
+    engine.set_bind_native_calls(true);
+
+    engine.register_fn("add", |x: INT, y: INT| x + y);
+    engine.register_fn("add", |s: &str, y: INT| format!("{s}{y}"));
+    engine.register_fn("describe", |x: Dynamic| format!("<{}>", x.type_name()));
+
+    let ast = engine
+        .compile(
+            r#"
+                let total = 0;
+                for i in 0..10 { total = add(total, i); }
+                let text = add("x", total);
+                text + describe(total) + describe("a") + describe(add(1, 2) > 2)
+            "#,
+        )
+        .unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(engine.eval_ast::<String>(&ast).unwrap(), "x45<i64><string><bool>");
+    }
+
+    // Arguments of other types are resolved normally
+    assert!(engine.eval::<INT>("add(1.5, 2)").is_err());
+    assert_eq!(engine.eval::<String>(r#"add("a" + "b", 1)"#).unwrap(), "ab1");
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+fn test_binding_method_calls() {
+    let mut engine = Engine::new();
+    engine.set_bind_native_calls(true);
+
+    engine.register_fn("double", |x: &mut INT| *x *= 2);
+    engine.register_fn("tag", |s: &mut ImmutableString, t: &str| format!("{s}:{t}"));
+
+    assert_eq!(engine.eval::<INT>("let x = 21; x.double(); x").unwrap(), 42);
+    assert_eq!(engine.eval::<String>(r#"let s = "a"; s.tag("b")"#).unwrap(), "a:b");
+    assert_eq!(engine.eval::<String>(r#"let s = "a"; s.tag("b").tag("c")"#).unwrap(), "a:b:c");
+    assert_eq!(engine.eval::<INT>("[1, 2, 3].len()").unwrap(), 3);
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_binding_script_functions() {
+    let mut engine = Engine::new();
+    engine.set_bind_native_calls(true);
+
+    engine.register_fn("add", |x: INT, y: INT| x + y);
+
+    // Script-defined functions take precedence
+    assert_eq!(engine.eval::<INT>("fn add(x, y) { x * y } add(6, 7)").unwrap(), 42);
+
+    let ast = engine.compile("add(6, 7)").unwrap();
+    let lib = engine.compile("fn add(x, y) { x - y }").unwrap();
+
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 13);
+    assert_eq!(engine.eval_ast::<INT>(&lib.merge(&ast)).unwrap(), -1);
+}
+
+#[test]
+fn test_binding_registration() {
+    let mut engine = Engine::new();
+    engine.set_bind_native_calls(true);
+
+    engine.register_fn("calc", |x: INT| x + 1);
+
+    let ast = engine.compile("calc(41)").unwrap();
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+
+    // Functions registered later are used instead of the bound ones
+    engine.register_fn("calc", |x: INT| x * 2);
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 82);
+
+    // Bindings do not leak into other engines
+    let mut engine2 = Engine::new();
+    engine2.register_fn("calc", |x: INT| x - 1);
+    assert_eq!(engine2.eval_ast::<INT>(&ast).unwrap(), 40);
+
+    let mut scope = Scope::new();
+    scope.push("x", 1 as INT);
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "calc(x)").unwrap(), 2);
+}
+
+#[test]
+#[cfg(feature = "internals")]
+fn test_binding_ast() {
+    use rhai::{Expr, Stmt};
+
+    let mut engine = Engine::new();
+    engine.register_fn("calc", |x: INT| x + 1);
+
+    let is_bound = |engine: &Engine| {
+        let ast = engine.compile("calc(41)").unwrap();
+
+        match &ast.statements()[0] {
+            Stmt::FnCall(x, ..) => x.binding.is_some(),
+            Stmt::Expr(expr) => matches!(**expr, Expr::FnCall(ref x, ..) if x.binding.is_some()),
+            stmt => unreachable!("{:?}", stmt),
+        }
+    };
+
+    assert!(!is_bound(&engine));
+    engine.set_bind_native_calls(true);
+    assert!(is_bound(&engine));
+}
@@ -0,0 +1,209 @@
+#![cfg(feature = "bytecode")]
+use rhai::{Engine, EvalAltResult, Position, Scope, INT};
+
+#[test]
+fn test_bytecode_loops() {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    let sum = 0;
+                    for (x, i) in [10, 20, 30, 40] {
+                        if i == 1 { continue; }
+                        if x > 30 { break; }
+                        sum += x * i;
+                    }
+                    sum
+                "
+            )
+            .unwrap(),
+        60
+    );
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    let x = 0;
+                    let y = loop { x += 1; if x > 5 { break x * 2; } };
+                    y
+                "
+            )
+            .unwrap(),
+        12
+    );
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    let x = 0;
+                    let n = 0;
+                    do {
+                        x += 1;
+                        if x % 2 == 0 { continue; }
+                        n += x;
+                    } until x >= 10;
+                    n
+                "
+            )
+            .unwrap(),
+        36
+    );
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    let total = 0;
+                    for i in 0..5 {
+                        let x = i;
+                        let j = 0;
+                        while true {
+                            let x = x * 10;
+                            j += 1;
+                            if j > i { break; }
+                            total += x;
+                        }
+                        total += x;
+                    }
+                    total
+                "
+            )
+            .unwrap(),
+        310
+    );
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    let n = 0;
+                    for i in 0..10 {
+                        try {
+                            if i == 3 { throw i; }
+                            if i == 6 { break; }
+                            n += 1;
+                        } catch (err) {
+                            n += err * 100;
+                        }
+                    }
+                    n
+                "
+            )
+            .unwrap(),
+        305
+    );
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_bytecode_functions() {
+    let engine = Engine::new();
+
+    let ast = engine
+        .compile(
+            "
+                fn sum_to(n) {
+                    let total = 0;
+                    for i in 1..=n { total += i; }
+                    total
+                }
+                fn find(list, target) {
+                    let i = 0;
+                    while i < list.len() {
+                        if list[i] == target { return i; }
+                        i += 1;
+                    }
+                    -1
+                }
+                fn fill(n) {
+                    this = [];
+                    for i in 0..n { this.push(i * i); }
+                }
+                fn sum_to_all(n) {
+                    let total = 0;
+                    for i in 0..n { total += sum_to(i); }
+                    total
+                }
+            ",
+        )
+        .unwrap();
+
+    assert_eq!(engine.eval_ast::<INT>(&ast.merge(&engine.compile("sum_to(100)").unwrap())).unwrap(), 5050);
+    assert_eq!(engine.eval_ast::<INT>(&ast.merge(&engine.compile("find([1, 2, 3, 4], 3)").unwrap())).unwrap(), 2);
+    assert_eq!(engine.eval_ast::<INT>(&ast.merge(&engine.compile("find([1, 2, 3, 4], 5)").unwrap())).unwrap(), -1);
+    assert_eq!(engine.eval_ast::<INT>(&ast.merge(&engine.compile("sum_to_all(5)").unwrap())).unwrap(), 20);
+    assert_eq!(engine.call_fn::<INT>(&mut Scope::new(), &ast, "sum_to", (10 as INT,)).unwrap(), 55);
+
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(engine.eval_ast::<INT>(&ast.merge(&engine.compile("let x = 0; x.fill(4); x[3]").unwrap())).unwrap(), 9);
+
+    #[cfg(not(feature = "no_closure"))]
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    let fns = [];
+                    for i in 0..3 { fns.push(|| i * 10); }
+                    let total = 0;
+                    for f in fns { total += f.call(); }
+                    total
+                "
+            )
+            .unwrap(),
+        60
+    );
+}
+
+#[test]
+#[cfg(not(feature = "unchecked"))]
+fn test_bytecode_operations() {
+    let mut engine = Engine::new();
+
+    // Operations are counted exactly as by the tree-walking evaluator
+    for (script, ops) in [
+        ("let x = 0; while x < 20 { x += 1; }", 126),
+        ("let s = 0; for i in 0..10 { if i % 2 == 0 { s += i; } } s", 81),
+        ("let x = 0; do { x += 1; if x == 3 { continue; } } while x < 5; x", 53),
+        ("let a = 1; let b = loop { a *= 2; if a > 100 && a % 3 != 0 { break a; } }; b ?? 0", 71),
+    ] {
+        engine.set_max_operations(ops);
+        engine.run(script).unwrap();
+
+        engine.set_max_operations(ops - 1);
+        assert!(matches!(*engine.run(script).unwrap_err(), EvalAltResult::ErrorTooManyOperations(..)), "{}", script);
+    }
+
+    engine.set_max_operations(500);
+    assert!(matches!(*engine.run("loop {}").unwrap_err(), EvalAltResult::ErrorTooManyOperations(..)));
+    assert!(matches!(*engine.run("let s = 0; for x in 0..1000 { s += x; }").unwrap_err(), EvalAltResult::ErrorTooManyOperations(..)));
+
+    engine.set_max_operations(0);
+    engine.set_max_variables(3);
+    engine.run("let a = 1; for i in 0..10 { let b = i; } let c = a;").unwrap();
+    assert!(matches!(*engine.run("let a = 1; for i in 0..10 { let b = i; } let c = a; let d = c; let e = d;").unwrap_err(), EvalAltResult::ErrorTooManyVariables(..)));
+}
+
+#[test]
+fn test_bytecode_errors() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.run("let x = 0;\nwhile 42 { x += 1; }").unwrap_err().position(), Position::new(2, 7));
+
+    let err = engine.run("let x = 0;\nfor i in 0..5 {\n    if i == 3 { throw i; }\n}").unwrap_err();
+    assert!(matches!(*err, EvalAltResult::ErrorRuntime(ref v, ..) if v.as_int().unwrap() == 3));
+    assert_eq!(err.position(), Position::new(3, 17));
+
+    let err = engine.run("let x = 0;\nfor i in 0..5 {\n    x += \"a\" - i;\n}").unwrap_err();
+    assert!(matches!(*err, EvalAltResult::ErrorFunctionNotFound(..)));
+    assert_eq!(err.position(), Position::new(3, 10));
+
+    assert!(matches!(
+        *engine.run("let x = 0;\nfor i in 0..5 { y += i; }").unwrap_err(),
+        EvalAltResult::ErrorVariableNotFound(ref name, pos) if name == "y" && pos == Position::new(2, 17)
+    ));
+}
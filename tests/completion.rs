@@ -0,0 +1,116 @@
+#![cfg(feature = "metadata")]
+#![cfg(not(feature = "no_position"))]
+use rhai::{CompletionKind, Engine, Module, Scope, AST, INT};
+
+fn texts(completions: &[rhai::Completion]) -> Vec<&str> {
+    completions.iter().map(|c| c.text.as_str()).collect()
+}
+
+#[test]
+fn test_completion_global() {
+    let mut engine = Engine::new_raw();
+    engine.register_fn("compute", |x: INT| x * 2);
+
+    let mut scope = Scope::new();
+    scope.push("counter", 0 as INT);
+    scope.push_constant("COLOR", "red");
+
+    let (start, completions) = engine.complete(&scope, &AST::empty(), "let y = co", 10);
+    assert_eq!(start, 8);
+    assert_eq!(texts(&completions), ["compute", "const", "continue", "counter"]);
+    assert_eq!(completions[0].kind, CompletionKind::Function);
+    assert_eq!(completions[0].detail.as_deref(), Some("compute(_: int) -> int"));
+    assert_eq!(completions[3].kind, CompletionKind::Variable);
+    assert_eq!(completions[3].detail.as_deref(), Some("i64"));
+
+    let (.., completions) = engine.complete(&scope, &AST::empty(), "C", 1);
+    assert_eq!(completions[0].text, "COLOR");
+    assert_eq!(completions[0].kind, CompletionKind::Constant);
+
+    // No completions within strings and comments
+    assert!(engine.complete(&scope, &AST::empty(), "\"co", 3).1.is_empty());
+    assert!(engine.complete(&scope, &AST::empty(), "x // co", 7).1.is_empty());
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        let ast = engine.compile("fn helper(x) { x } private fn hidden() {}").unwrap();
+        let (.., completions) = engine.complete(&scope, &ast, "he", 2);
+        assert_eq!(texts(&completions), ["helper"]);
+        assert_eq!(completions[0].detail.as_deref(), Some("helper(x)"));
+    }
+}
+
+#[test]
+fn test_completion_methods() {
+    let engine = Engine::new();
+
+    let mut scope = Scope::new();
+    scope.push("text", "hello");
+    scope.push("n", 42 as INT);
+
+    let (start, completions) = engine.complete(&scope, &AST::empty(), "text.to_", 8);
+    assert_eq!(start, 5);
+    assert!(texts(&completions).contains(&"to_upper"));
+    assert!(completions.iter().all(|c| c.kind == CompletionKind::Method));
+
+    // Methods of other types are excluded
+    let (.., completions) = engine.complete(&scope, &AST::empty(), "n.to_", 5);
+    assert!(!texts(&completions).contains(&"to_upper"));
+    assert!(texts(&completions).contains(&"to_string"));
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        let (.., completions) = engine.complete(&scope, &AST::empty(), "text.len", 8);
+        assert_eq!(texts(&completions), ["len"]);
+
+        let mut map = rhai::Map::new();
+        map.insert("alpha".into(), (1 as INT).into());
+        scope.push("obj", map);
+
+        let (.., completions) = engine.complete(&scope, &AST::empty(), "obj.al", 6);
+        assert_eq!(completions[0].text, "alpha");
+        assert_eq!(completions[0].kind, CompletionKind::Property);
+    }
+}
+
+#[cfg(not(feature = "no_module"))]
+#[test]
+fn test_completion_modules() {
+    let mut engine = Engine::new_raw();
+
+    let mut module = Module::new();
+    module.set_var("ANSWER", 42 as INT);
+    module.set_native_fn("calc", |x: INT| Ok(x + 1));
+    let mut sub_module = Module::new();
+    sub_module.set_native_fn("deep", || Ok(0 as INT));
+    module.set_sub_module("inner", sub_module);
+    engine.register_static_module("math", module.into());
+
+    let scope = Scope::new();
+
+    let (.., completions) = engine.complete(&scope, &AST::empty(), "ma", 2);
+    assert_eq!(texts(&completions), ["math"]);
+    assert_eq!(completions[0].kind, CompletionKind::Module);
+
+    let (start, completions) = engine.complete(&scope, &AST::empty(), "math::", 6);
+    assert_eq!(start, 6);
+    assert_eq!(texts(&completions), ["ANSWER", "calc", "inner"]);
+
+    let (.., completions) = engine.complete(&scope, &AST::empty(), "math::inner::d", 14);
+    assert_eq!(texts(&completions), ["deep"]);
+}
+
+#[test]
+fn test_completion_signature_help() {
+    let mut engine = Engine::new();
+    engine.register_fn("area", |w: INT, h: INT| w * h);
+    engine.register_fn("area", |w: INT| w * w);
+
+    let ast = AST::empty();
+
+    assert_eq!(engine.signature_help(&ast, "area(", 5).len(), 2);
+    assert_eq!(engine.signature_help(&ast, "area(1, ", 8), ["area(_: int, _: int) -> int"]);
+    assert_eq!(engine.signature_help(&ast, "area(1, [2, 3", 13), ["area(_: int, _: int) -> int"]);
+    assert!(engine.signature_help(&ast, "area(1) + ", 10).is_empty());
+    assert!(engine.signature_help(&ast, "area(1, 2, ", 11).is_empty());
+}
@@ -0,0 +1,195 @@
+#![cfg(feature = "debugging")]
+#![cfg(not(feature = "no_position"))]
+use rhai::debugger::{BranchKind, Coverage};
+use rhai::{Engine, Scope, INT};
+
+#[test]
+fn test_coverage_lines() {
+    let mut engine = Engine::new();
+    let coverage = Coverage::new();
+    engine.register_coverage(&coverage);
+
+    let mut ast = engine
+        .compile(
+            "let x = 0;
+for i in 0..10 {
+    x += i;
+}
+if x > 100 {
+    print(x);
+}
+x",
+        )
+        .unwrap();
+    ast.set_source("lines");
+
+    coverage.add_ast(&ast);
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 45);
+
+    let files = coverage.files();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].source, "lines");
+    assert_eq!(files[0].lines, [(1, 1), (2, 1), (3, 10), (5, 1), (6, 0), (8, 1)]);
+}
+
+#[test]
+fn test_coverage_branches() {
+    let mut engine = Engine::new();
+    let coverage = Coverage::new();
+    engine.register_coverage(&coverage);
+
+    let ast = engine
+        .compile(
+            "
+                let a = 0; let b = 0; let c = 0; let n = ();
+                for i in 0..10 {
+                    if i < 3 { a += 1; } else if i < 5 { b += 1; }
+                    switch i { 0 => c += 1, 1 | 2 => c += 2, _ => () }
+                    let t = i > 7 && i < 9;
+                    let u = i > 1 || i == 0;
+                    let v = n ?? i;
+                    let w = i ?? 0;
+                    c += v + w;
+                }
+            ",
+        )
+        .unwrap();
+
+    coverage.add_ast(&ast);
+    engine.run_ast(&ast).unwrap();
+
+    let files = coverage.files();
+    let taken = |kind: BranchKind| files[0].branches.iter().filter(|b| b.kind == kind).map(|b| (b.hits, b.taken.clone())).collect::<Vec<_>>();
+
+    assert_eq!(taken(BranchKind::If), [(10, vec![3, 7]), (7, vec![2, 5])]);
+    assert_eq!(taken(BranchKind::Switch), [(10, vec![1, 2, 7])]);
+    assert_eq!(taken(BranchKind::And), [(10, vec![2, 8])]);
+    assert_eq!(taken(BranchKind::Or), [(10, vec![2, 8])]);
+    assert_eq!(taken(BranchKind::Coalesce), [(10, vec![10, 0]), (10, vec![0, 10])]);
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_coverage_functions() {
+    let mut engine = Engine::new();
+    let coverage = Coverage::new();
+    engine.register_coverage(&coverage);
+
+    let ast = engine
+        .compile(
+            "fn sign(x) {
+    if x < 0 {
+        -1
+    } else if x > 0 {
+        1
+    } else {
+        0
+    }
+}
+sign(-5) + sign(5) + sign(7)",
+        )
+        .unwrap();
+
+    coverage.add_ast(&ast);
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 1);
+
+    let file = &coverage.files()[0];
+    assert_eq!(file.lines.iter().find(|(line, ..)| *line == 3), Some(&(3, 1)));
+    assert_eq!(file.lines.iter().find(|(line, ..)| *line == 5), Some(&(5, 2)));
+    assert_eq!(file.lines.iter().find(|(line, ..)| *line == 7), Some(&(7, 0)));
+    assert_eq!(file.branches.iter().map(|b| b.taken.clone()).collect::<Vec<_>>(), [vec![1, 2], vec![2, 0]]);
+}
+
+#[test]
+fn test_coverage_merge() {
+    let script = "let x = 0;\nif x == 0 {\n    x = 1;\n} else {\n    x = 2;\n}";
+
+    let coverage = Coverage::new();
+
+    // Runs with multiple engines are merged
+    for value in [0 as INT, 1] {
+        let mut engine = Engine::new();
+        engine.register_coverage(&coverage);
+
+        let mut ast = engine.compile(script).unwrap();
+        ast.set_source("merge");
+        coverage.add_ast(&ast);
+
+        let mut scope = Scope::new();
+        scope.push("y", value);
+        engine.run_ast_with_scope(&mut scope, &ast).unwrap();
+    }
+
+    let other = Coverage::new();
+    let mut engine = Engine::new();
+    engine.register_coverage(&other);
+    engine.run("let y = 1;").unwrap();
+
+    coverage.merge(&other);
+    coverage.merge(&coverage.clone());
+
+    let files = coverage.files();
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].source, "");
+    assert_eq!(files[1].source, "merge");
+    assert_eq!(files[1].lines, [(1, 2), (2, 2), (3, 2), (5, 0)]);
+
+    coverage.clear();
+    assert!(coverage.is_empty());
+}
+
+#[test]
+fn test_coverage_reports() {
+    let mut engine = Engine::new();
+    let coverage = Coverage::new();
+    engine.register_coverage(&coverage);
+
+    let mut ast = engine.compile("let x = 1;\nif x > 0 {\n    x = 2;\n}\nlet y = x > 5 && x < 10;").unwrap();
+    ast.set_source("report.rhai");
+
+    coverage.add_ast(&ast);
+    engine.run_ast(&ast).unwrap();
+
+    let lcov = coverage.to_lcov();
+    let records: Vec<_> = lcov.lines().collect();
+
+    assert_eq!(
+        records,
+        [
+            "TN:",
+            "SF:report.rhai",
+            "BRDA:2,0,0,1",
+            "BRDA:2,0,1,0",
+            "BRDA:5,1,0,0",
+            "BRDA:5,1,1,1",
+            "BRF:4",
+            "BRH:2",
+            "DA:1,1",
+            "DA:2,1",
+            "DA:3,1",
+            "DA:5,1",
+            "LF:4",
+            "LH:4",
+            "end_of_record"
+        ]
+    );
+
+    #[cfg(not(feature = "no_object"))]
+    #[cfg(not(feature = "no_index"))]
+    {
+        let json = engine.parse_json(coverage.to_json(), true).unwrap();
+        let files = json["files"].clone().into_array().unwrap();
+        let file = files[0].clone().cast::<rhai::Map>();
+
+        assert_eq!(file["source"].clone().into_string().unwrap(), "report.rhai");
+        assert_eq!(file["lines"].clone().into_array().unwrap().len(), 4);
+
+        let branches = file["branches"].clone().into_array().unwrap();
+        let branch = branches[1].clone().cast::<rhai::Map>();
+
+        assert_eq!(branch["kind"].clone().into_string().unwrap(), "&&");
+        assert_eq!(branch["line"].as_int().unwrap(), 5);
+        assert_eq!(branch["hits"].as_int().unwrap(), 1);
+        assert_eq!(branch["taken"].clone().into_array().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,325 @@
+#![cfg(feature = "debugging")]
+#![cfg(not(feature = "no_object"))]
+#![cfg(not(feature = "no_index"))]
+#![cfg(not(feature = "no_position"))]
+use rhai::{Array, Engine, Map, INT};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// A scripted DAP client driving `rhai-dap`.
+struct Client {
+    child: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    json: Engine,
+    seq: INT,
+    events: VecDeque<Map>,
+}
+
+impl Client {
+    fn new() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_rhai-dap")).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+        let input = child.stdin.take().unwrap();
+        let output = BufReader::new(child.stdout.take().unwrap());
+
+        Self {
+            child,
+            input,
+            output,
+            json: Engine::new_raw(),
+            seq: 0,
+            events: VecDeque::new(),
+        }
+    }
+
+    fn read(&mut self) -> Map {
+        let mut len = 0;
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            assert!(self.output.read_line(&mut line).unwrap() > 0, "unexpected end of output");
+            match line.trim().split_once(':') {
+                Some((.., value)) => len = value.trim().parse().unwrap(),
+                None if len > 0 => break,
+                None => (),
+            }
+        }
+
+        let mut content = vec![0; len];
+        self.output.read_exact(&mut content).unwrap();
+        self.json.parse_json(String::from_utf8(content).unwrap(), true).unwrap()
+    }
+
+    /// Send a request and return the response, keeping events received in the meantime.
+    fn request(&mut self, command: &str, args: &str) -> Map {
+        self.seq += 1;
+        let message = format!(r#"{{"seq":{},"type":"request","command":"{command}","arguments":{args}}}"#, self.seq);
+        write!(self.input, "Content-Length: {}\r\n\r\n{message}", message.len()).unwrap();
+        self.input.flush().unwrap();
+
+        loop {
+            let message = self.read();
+            match message["type"].clone().into_string().unwrap().as_str() {
+                "response" if message["request_seq"].as_int().unwrap() == self.seq => {
+                    assert_eq!(message["command"].clone().into_string().unwrap(), command);
+                    return message;
+                }
+                "event" => self.events.push_back(message),
+                _ => (),
+            }
+        }
+    }
+
+    /// Send a request and return the body of its successful response.
+    fn call(&mut self, command: &str, args: &str) -> Map {
+        let response = self.request(command, args);
+        assert!(response["success"].as_bool().unwrap(), "{} failed: {:?}", command, response);
+        response.get("body").map_or_else(Map::new, |body| body.clone().cast::<Map>())
+    }
+
+    /// Wait for an event and return its body, skipping other events.
+    fn event(&mut self, name: &str) -> Map {
+        loop {
+            let message = match self.events.pop_front() {
+                Some(message) => message,
+                None => self.read(),
+            };
+            if message.get("event").map_or(false, |e| e.clone().into_string().unwrap() == name) {
+                return message.get("body").map_or_else(Map::new, |body| body.clone().cast::<Map>());
+            }
+        }
+    }
+
+    /// Get the name and line of each stack frame.
+    fn stack(&mut self) -> Vec<(String, INT)> {
+        let body = self.call("stackTrace", r#"{"threadId":1}"#);
+        body["stackFrames"]
+            .clone()
+            .cast::<Array>()
+            .into_iter()
+            .map(|frame| {
+                let frame = frame.cast::<Map>();
+                (frame["name"].clone().into_string().unwrap(), frame["line"].as_int().unwrap())
+            })
+            .collect()
+    }
+
+    /// Get the values of the variables in the scope of a stack frame.
+    fn variables(&mut self, frame: INT) -> Vec<(String, String)> {
+        let scopes = self.call("scopes", &format!(r#"{{"frameId":{frame}}}"#));
+        let scope = scopes["scopes"].clone().cast::<Array>()[0].clone().cast::<Map>();
+        let reference = scope["variablesReference"].as_int().unwrap();
+
+        let body = self.call("variables", &format!(r#"{{"variablesReference":{reference}}}"#));
+        body["variables"]
+            .clone()
+            .cast::<Array>()
+            .into_iter()
+            .map(|v| {
+                let v = v.cast::<Map>();
+                (v["name"].clone().into_string().unwrap(), v["value"].clone().into_string().unwrap())
+            })
+            .collect()
+    }
+
+    fn evaluate(&mut self, expr: &str) -> String {
+        let body = self.call("evaluate", &format!(r#"{{"expression":{expr:?},"frameId":0}}"#));
+        body["result"].clone().into_string().unwrap()
+    }
+
+    /// Start debugging a script.
+    fn launch(&mut self, path: &str, stop_on_entry: bool) {
+        self.call("initialize", r#"{"adapterID":"rhai"}"#);
+        self.event("initialized");
+        self.call("launch", &format!(r#"{{"program":{path:?},"stopOnEntry":{stop_on_entry}}}"#));
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn write_script(name: &str, script: &str) -> String {
+    let path = std::env::temp_dir().join(format!("rhai-dap-{}-{name}.rhai", std::process::id()));
+    std::fs::write(&path, script).unwrap();
+    path.canonicalize().unwrap().to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_dap_breakpoints() {
+    let path = write_script(
+        "breakpoints",
+        "
+let total = 0;
+for i in 0..3 {
+    total += i; total += 1;
+}
+print(total);
+",
+    );
+
+    let mut client = Client::new();
+    client.launch(&path, false);
+
+    let body = client.call("setBreakpoints", &format!(r#"{{"source":{{"path":{path:?}}},"breakpoints":[{{"line":4}}]}}"#));
+    let bp = body["breakpoints"].clone().cast::<Array>()[0].clone().cast::<Map>();
+    assert!(bp["verified"].as_bool().unwrap());
+    let id = bp["id"].as_int().unwrap();
+
+    client.call("configurationDone", "{}");
+
+    let stopped = client.event("stopped");
+    assert_eq!(stopped["reason"].clone().into_string().unwrap(), "breakpoint");
+    assert_eq!(stopped["hitBreakpointIds"].clone().cast::<Array>()[0].as_int().unwrap(), id);
+    assert_eq!(client.stack(), [("<main>".to_string(), 4)]);
+    assert_eq!(client.variables(0), [("total".to_string(), "0".to_string()), ("i".to_string(), "0".to_string())]);
+
+    // Each iteration stops once at the line
+    client.call("continue", r#"{"threadId":1}"#);
+    client.event("stopped");
+    assert_eq!(client.evaluate("i"), "1");
+    assert_eq!(client.evaluate("total"), "1");
+
+    // Remove break-points
+    client.call("setBreakpoints", &format!(r#"{{"source":{{"path":{path:?}}},"breakpoints":[]}}"#));
+    client.call("continue", r#"{"threadId":1}"#);
+
+    let output = client.event("output");
+    assert_eq!(output["category"].clone().into_string().unwrap(), "stdout");
+    assert_eq!(output["output"].clone().into_string().unwrap(), "6\n");
+    assert_eq!(client.event("exited")["exitCode"].as_int().unwrap(), 0);
+    client.event("terminated");
+
+    client.call("disconnect", "{}");
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_dap_stepping() {
+    let path = write_script(
+        "stepping",
+        "
+fn double(x) {
+    let y = x * 2;
+    y
+}
+let a = 21;
+let b = double(a);
+print(b);
+",
+    );
+
+    let mut client = Client::new();
+    client.launch(&path, true);
+    client.call("configurationDone", "{}");
+
+    assert_eq!(client.event("stopped")["reason"].clone().into_string().unwrap(), "entry");
+    assert_eq!(client.stack(), [("<main>".to_string(), 6)]);
+
+    client.call("next", r#"{"threadId":1}"#);
+    assert_eq!(client.event("stopped")["reason"].clone().into_string().unwrap(), "step");
+    assert_eq!(client.stack(), [("<main>".to_string(), 7)]);
+
+    client.call("stepIn", r#"{"threadId":1}"#);
+    client.event("stopped");
+    assert_eq!(client.stack(), [("double".to_string(), 3), ("<main>".to_string(), 7)]);
+    assert_eq!(client.variables(0), [("x".to_string(), "21".to_string())]);
+
+    assert_eq!(client.evaluate("x + 1"), "22");
+    assert_eq!(client.evaluate("double(x)"), "42");
+
+    client.call("next", r#"{"threadId":1}"#);
+    client.event("stopped");
+    assert_eq!(client.stack(), [("double".to_string(), 4), ("<main>".to_string(), 7)]);
+    assert_eq!(client.evaluate("y"), "42");
+
+    client.call("stepOut", r#"{"threadId":1}"#);
+    assert_eq!(client.event("stopped")["reason"].clone().into_string().unwrap(), "step");
+
+    client.call("continue", r#"{"threadId":1}"#);
+    assert_eq!(client.event("output")["output"].clone().into_string().unwrap(), "42\n");
+    assert_eq!(client.event("exited")["exitCode"].as_int().unwrap(), 0);
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_dap_function_breakpoints() {
+    let path = write_script("functions", "fn fail(x) { throw x; }\nlet s = \"hello\";\nfail(s);\n");
+
+    let mut client = Client::new();
+    client.launch(&path, false);
+    client.call("setFunctionBreakpoints", r#"{"breakpoints":[{"name":"fail"}]}"#);
+    client.call("configurationDone", "{}");
+
+    assert_eq!(client.event("stopped")["reason"].clone().into_string().unwrap(), "function breakpoint");
+    assert_eq!(client.stack(), [("<main>".to_string(), 3)]);
+    assert_eq!(client.evaluate("s.len()"), "5");
+    assert_eq!(client.evaluate("s"), r#""hello""#);
+
+    // Errors are reported as responses
+    assert!(!client.request("evaluate", r#"{"expression":"undefined_var"}"#)["success"].as_bool().unwrap());
+
+    client.call("continue", r#"{"threadId":1}"#);
+    let output = client.event("output");
+    assert_eq!(output["category"].clone().into_string().unwrap(), "stderr");
+    assert!(output["output"].clone().into_string().unwrap().contains("hello"));
+    assert_eq!(client.event("exited")["exitCode"].as_int().unwrap(), 1);
+}
+
+#[test]
+fn test_dap_launch_error() {
+    let mut client = Client::new();
+    client.call("initialize", "{}");
+
+    let response = client.request("launch", r#"{"program":"this-script-does-not-exist.rhai"}"#);
+    assert!(!response["success"].as_bool().unwrap());
+    assert!(response["message"].clone().into_string().unwrap().contains("this-script-does-not-exist.rhai"));
+
+    let path = write_script("syntax", "let x = ;");
+    let response = client.request("launch", &format!(r#"{{"program":{path:?}}}"#));
+    assert!(!response["success"].as_bool().unwrap());
+
+    let threads = client.call("threads", "{}");
+    assert_eq!(threads["threads"].clone().cast::<Array>().len(), 1);
+
+    client.call("disconnect", "{}");
+}
+
+#[test]
+fn test_dap_break_point_options() {
+    let path = write_script("options", "let total = 0;\nfor i in 0..10 {\n    total += i;\n}\nprint(total);\n");
+
+    let mut client = Client::new();
+    client.call("initialize", r#"{"adapterID":"rhai"}"#);
+    client.event("initialized");
+    client.call("launch", &format!(r#"{{"program":{path:?}}}"#));
+
+    let body = client.call(
+        "setBreakpoints",
+        &format!(r#"{{"source":{{"path":{path:?}}},"breakpoints":[{{"line":3,"condition":"i >= 5","hitCondition":"%2"}},{{"line":5,"logMessage":"total = {{total}}"}},{{"line":4,"hitCondition":"oops"}}]}}"#),
+    );
+    let bps = body["breakpoints"].clone().cast::<Array>();
+    assert!(bps[0].clone().cast::<Map>()["verified"].as_bool().unwrap());
+    assert!(!bps[2].clone().cast::<Map>()["verified"].as_bool().unwrap());
+
+    client.call("configurationDone", "{}");
+
+    // Every second hit where the condition holds
+    assert_eq!(client.event("stopped")["reason"].clone().into_string().unwrap(), "breakpoint");
+    assert_eq!(client.evaluate("i"), "6");
+
+    client.call("continue", r#"{"threadId":1}"#);
+    client.event("stopped");
+    assert_eq!(client.evaluate("i"), "8");
+
+    // Log points print without stopping
+    client.call("continue", r#"{"threadId":1}"#);
+    assert_eq!(client.event("output")["output"].clone().into_string().unwrap(), "total = 45\n");
+    assert_eq!(client.event("output")["output"].clone().into_string().unwrap(), "45\n");
+    assert_eq!(client.event("exited")["exitCode"].as_int().unwrap(), 0);
+}
@@ -71,3 +71,299 @@ fn test_debugger_state() {
 
     engine.run("let x = 42;").unwrap();
 }
+
+#[test]
+fn test_debugger_break_point_options() {
+    use rhai::debugger::{BreakPoint, BreakPointOptions, DebuggerCommand, DebuggerEvent, HitCondition};
+    use std::sync::{Arc, Mutex};
+
+    fn run(options: BreakPointOptions) -> (Vec<INT>, Vec<String>) {
+        let mut engine = Engine::new();
+        let stops = Arc::new(Mutex::new(Vec::new()));
+        let output = Arc::new(Mutex::new(Vec::new()));
+
+        engine.register_fn("touch", |_: INT| ());
+
+        let log = output.clone();
+        engine.on_print(move |s| log.lock().unwrap().push(s.to_string()));
+
+        let s = stops.clone();
+        #[allow(deprecated)]
+        engine.register_debugger(
+            move |_, mut debugger| {
+                let bp = BreakPoint::AtFunctionName { name: "touch".into(), enabled: true };
+                debugger.add_break_point_with_options(bp, options.clone());
+                debugger
+            },
+            move |context, event, _, _, _| {
+                if let DebuggerEvent::BreakPoint(..) = event {
+                    s.lock().unwrap().push(context.scope().get_value::<INT>("i").unwrap());
+                }
+                Ok(DebuggerCommand::Continue)
+            },
+        );
+
+        engine.run("for i in 0..10 { touch(i); }").unwrap();
+
+        let stops = stops.lock().unwrap().clone();
+        let output = output.lock().unwrap().clone();
+        (stops, output)
+    }
+
+    assert_eq!(run(BreakPointOptions::default()).0, (0..10).collect::<Vec<_>>());
+
+    let condition = BreakPointOptions {
+        condition: Some("i % 3 == 0".into()),
+        ..Default::default()
+    };
+    assert_eq!(run(condition.clone()).0, [0, 3, 6, 9]);
+
+    let every = BreakPointOptions {
+        hit_condition: Some(HitCondition::Every(2)),
+        ..condition
+    };
+    assert_eq!(run(every).0, [3, 9]);
+
+    let equals = BreakPointOptions {
+        hit_condition: Some("== 5".parse().unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(run(equals).0, [4]);
+
+    let at_least = BreakPointOptions {
+        hit_condition: Some(">=9".parse().unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(run(at_least).0, [8, 9]);
+
+    // Failed conditions trigger the break-point
+    let error = BreakPointOptions {
+        condition: Some("no_such_variable".into()),
+        ..Default::default()
+    };
+    assert_eq!(run(error).0.len(), 10);
+
+    // Log messages are printed instead of stopping
+    let log = BreakPointOptions {
+        condition: Some("i > 6".into()),
+        log_message: Some("i = {i}, i * 2 = {i * 2} {{done}}".into()),
+        ..Default::default()
+    };
+    let (stops, output) = run(log);
+    assert!(stops.is_empty());
+    assert_eq!(output, ["i = 7, i * 2 = 14 {done}", "i = 8, i * 2 = 16 {done}", "i = 9, i * 2 = 18 {done}"]);
+
+    assert!("0".parse::<HitCondition>().is_err());
+    assert!("> 1".parse::<HitCondition>().is_err());
+    assert_eq!("% 4".parse::<HitCondition>().unwrap(), HitCondition::Every(4));
+    assert_eq!(HitCondition::AtLeast(3).to_string(), ">= 3");
+}
+
+#[test]
+fn test_debugger_break_point_options_remove() {
+    use rhai::debugger::{BreakPoint, BreakPointOptions, DebuggerCommand, DebuggerEvent, HitCondition};
+    use std::sync::{Arc, Mutex};
+
+    let mut engine = Engine::new();
+    let stops = Arc::new(Mutex::new(0));
+
+    engine.register_fn("a", || ());
+    engine.register_fn("b", || ());
+
+    let s = stops.clone();
+    #[allow(deprecated)]
+    engine.register_debugger(
+        |_, mut debugger| {
+            let a = BreakPoint::AtFunctionName { name: "a".into(), enabled: true };
+            let b = BreakPoint::AtFunctionName { name: "b".into(), enabled: true };
+            let never = BreakPointOptions {
+                condition: Some("false".into()),
+                ..Default::default()
+            };
+            let second = BreakPointOptions {
+                hit_condition: Some(HitCondition::Equals(2)),
+                ..Default::default()
+            };
+
+            assert_eq!(debugger.add_break_point_with_options(a, never), 0);
+            assert_eq!(debugger.add_break_point_with_options(b, second.clone()), 1);
+
+            // Options move together with the following break-points
+            debugger.remove_break_point(0);
+            assert_eq!(debugger.break_points().len(), 1);
+            assert_eq!(debugger.break_point_options(0), Some(&second));
+            assert_eq!(debugger.break_point_options(1), None);
+
+            debugger
+        },
+        move |_, event, _, _, _| {
+            if let DebuggerEvent::BreakPoint(0) = event {
+                *s.lock().unwrap() += 1;
+            }
+            Ok(DebuggerCommand::Continue)
+        },
+    );
+
+    engine.run("a(); b(); a(); b(); b();").unwrap();
+
+    assert_eq!(*stops.lock().unwrap(), 1);
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+fn test_debugger_watch_points() {
+    use rhai::debugger::{DebuggerCommand, DebuggerEvent, WatchPoint};
+    use std::sync::{Arc, Mutex};
+
+    fn run(paths: &'static [&'static str], script: &str) -> Vec<String> {
+        let mut engine = Engine::new();
+        let changes = Arc::new(Mutex::new(Vec::new()));
+
+        let c = changes.clone();
+        #[allow(deprecated)]
+        engine.register_debugger(
+            move |_, mut debugger| {
+                debugger.watch_points_mut().extend(paths.iter().map(|&path| WatchPoint::new(path)));
+                debugger
+            },
+            move |context, event, _, _, _| {
+                if let DebuggerEvent::WatchPoint(n, old_value, new_value) = event {
+                    let path = &context.global_runtime_state().debugger().watch_points()[n].path;
+                    c.lock().unwrap().push(format!("{path}: {old_value} => {new_value}"));
+                }
+                Ok(DebuggerCommand::Continue)
+            },
+        );
+
+        engine.run(script).unwrap();
+
+        let changes = changes.lock().unwrap().clone();
+        changes
+    }
+
+    assert_eq!(run(&["x"], "let x = 0; for i in 0..3 { x += i; } x = 3;"), ["x: 0 => 1", "x: 1 => 3"]);
+
+    assert_eq!(run(&["a[1]", "a.len()"], "let a = [1, 2]; a.push(3); a[0] = 42; a[1] = 0; let b = a; b[1] = 1;"), ["a.len(): 2 => 3", "a[1]: 2 => 0"]);
+
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        run(
+            &["this.count", "x.count"],
+            "
+                fn inc() { this.count += 1; this.other = true; }
+                let x = #{ count: 0 };
+                x.inc();
+                x.count *= 10;
+            "
+        ),
+        ["this.count: 0 => 1", "x.count: 0 => 1", "x.count: 1 => 10"]
+    );
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_index"))]
+fn test_debugger_eval() {
+    use rhai::debugger::{BreakPoint, DebuggerCommand, DebuggerEvent};
+    use std::sync::{Arc, Mutex};
+
+    let mut engine = Engine::new();
+    let results = Arc::new(Mutex::new(Vec::new()));
+
+    let r = results.clone();
+    #[allow(deprecated)]
+    engine.register_debugger(
+        |_, mut debugger| {
+            debugger.break_points_mut().push(BreakPoint::AtFunctionName { name: "check".into(), enabled: true });
+            debugger
+        },
+        move |mut context, event, _, _, _| {
+            if let DebuggerEvent::BreakPoint(..) = event {
+                let mut results = r.lock().unwrap();
+
+                // Calls within the evaluation do not trigger break-points
+                results.push(context.eval_script("a[1] * 2 + double(n) + check(0)").unwrap().to_string());
+
+                // Modify variables
+                assert!(context.eval_script("a[1] = 42; n += 1").unwrap().is_unit());
+                assert!(context.eval_script("let temp = 1; temp").is_ok());
+                assert!(!context.scope().contains("temp"));
+
+                // Evaluate in the calling function
+                results.push(context.eval_script_in_frame(1, "x * 2").unwrap().to_string());
+                assert!(context.eval_script_in_frame(2, "x").is_err());
+
+                #[cfg(not(feature = "no_object"))]
+                if context.this_ptr().is_some() {
+                    assert!(context.eval_script("this.value = 123").unwrap().is_unit());
+                }
+            }
+            Ok(DebuggerCommand::Continue)
+        },
+    );
+
+    let script = "
+        fn check(v) { v }
+        fn double(x) { x * 2 }
+        fn inner(y) { let a = [1, 2]; let n = 1; check(y); a[1] + n }
+        fn outer(x) { inner(x + 1) }
+        outer(10)
+    ";
+    assert_eq!(engine.eval::<INT>(script).unwrap(), 44);
+    assert_eq!(*results.lock().unwrap(), ["6", "20"]);
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn check(v) { v }
+                    fn double(x) { x * 2 }
+                    fn get() { let a = [0, 0]; let n = 0; check(0); this.value }
+                    fn wrap(x) { let obj = #{ value: 1 }; obj.get() }
+                    wrap(0)
+                "
+            )
+            .unwrap(),
+        123
+    );
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_debugger_function_break_points() {
+    use rhai::debugger::{BreakPoint, DebuggerCommand, DebuggerEvent};
+    use std::sync::{Arc, Mutex};
+
+    let mut engine = Engine::new();
+    let stops = Arc::new(Mutex::new(Vec::new()));
+
+    // Calls are not turned into function call statements
+    #[cfg(not(feature = "no_optimize"))]
+    engine.set_optimization_level(rhai::OptimizationLevel::None);
+
+    let s = stops.clone();
+    #[allow(deprecated)]
+    engine.register_debugger(
+        |_, mut debugger| {
+            debugger.break_points_mut().push(BreakPoint::AtFunctionName { name: "check".into(), enabled: true });
+            debugger
+        },
+        move |_, event, _, _, pos| {
+            if let DebuggerEvent::BreakPoint(..) = event {
+                s.lock().unwrap().push(pos.line().unwrap());
+            }
+            Ok(DebuggerCommand::Continue)
+        },
+    );
+
+    let script = "
+        fn check(x) { x }
+        let x = check(1);
+        check(x);
+        x + check(2)
+    ";
+    assert_eq!(engine.eval::<INT>(script).unwrap(), 3);
+    assert_eq!(*stops.lock().unwrap(), [3, 4, 5]);
+}
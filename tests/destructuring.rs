@@ -0,0 +1,82 @@
+use rhai::{Engine, EvalAltResult, ParseErrorType, Scope, INT};
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_destructuring_arrays() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("let [a, b, c] = [1, 2, 3]; a * 100 + b * 10 + c").unwrap(), 123);
+    assert_eq!(engine.eval::<INT>("let [a, _, c] = [1, 2, 3]; a + c").unwrap(), 4);
+    assert_eq!(engine.eval::<INT>("let [a, b, ..rest] = [1, 2, 3, 4, 5]; a + b + rest.len()").unwrap(), 6);
+    assert_eq!(engine.eval::<INT>("let [..rest] = [1, 2]; rest.len()").unwrap(), 2);
+    assert_eq!(engine.eval::<INT>("let [a, ..] = [1, 2, 3]; a").unwrap(), 1);
+    assert_eq!(engine.eval::<INT>("let [a, [b, c]] = [1, [2, 3]]; a + b + c").unwrap(), 6);
+    assert_eq!(engine.eval::<INT>("let [a, b, ..rest] = [1, 2]; rest.len()").unwrap(), 0);
+    assert_eq!(engine.eval::<INT>("let x = 1; let [x, y] = [x + 1, x + 2]; x * y").unwrap(), 6);
+    assert_eq!(engine.eval::<INT>("const [a, b] = [1, 2]; a + b").unwrap(), 3);
+
+    assert!(matches!(engine.compile("const [a, b] = [1, 2]; a = 3;").unwrap_err().err_type(), ParseErrorType::AssignmentToConstant(..)));
+    assert!(matches!(*engine.run("let [a, b] = 42;").unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+    assert!(matches!(*engine.run("let [a, b] = [1];").unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+    assert!(matches!(*engine.run("let [a, b] = [1, 2, 3];").unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+    assert!(matches!(*engine.run("let [a, b, ..rest] = [1];").unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+    assert!(matches!(*engine.run("for [a, b] in [[1, 2], [3]] {}").unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+    assert!(matches!(engine.compile("let [a, a] = [1, 2];").unwrap_err().err_type(), ParseErrorType::DuplicatedVariable(..)));
+    assert!(matches!(engine.compile("let [a, b];").unwrap_err().err_type(), ParseErrorType::MissingToken(..)));
+    assert!(engine.compile("let [a, ..rest, b] = [1, 2, 3];").is_err());
+
+    let mut scope = Scope::new();
+    engine.run_with_scope(&mut scope, "let [a, [b, ..c]] = [1, [2, 3, 4]];").unwrap();
+    assert_eq!(scope.get_value::<INT>("a").unwrap(), 1);
+    assert_eq!(scope.get_value::<INT>("b").unwrap(), 2);
+    assert_eq!(scope.get_value::<rhai::Array>("c").unwrap().len(), 2);
+    assert_eq!(scope.len(), 3);
+}
+
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_destructuring_maps() {
+    let mut engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("let ev = #{from: 1, to: 2, value: 3}; let #{from, to, value: amount} = ev; from * 100 + to * 10 + amount").unwrap(), 123);
+    assert_eq!(engine.eval::<INT>(r#"let #{"hello world": x, y: #{z}} = #{"hello world": 1, y: #{z: 2}}; x + z"#).unwrap(), 3);
+    assert_eq!(engine.eval::<()>("let #{a, b} = #{a: 1}; b").unwrap(), ());
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(engine.eval::<INT>("let #{a: [x, y], b} = #{a: [1, 2], b: 3}; x + y + b").unwrap(), 6);
+
+    assert!(matches!(*engine.run("let #{a} = [1];").unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+    assert!(matches!(engine.compile(r#"let #{"a"} = #{a: 1};"#).unwrap_err().err_type(), ParseErrorType::MissingToken(..)));
+
+    engine.set_fail_on_invalid_map_property(true);
+
+    assert!(matches!(*engine.run("let #{a, b} = #{a: 1};").unwrap_err(), EvalAltResult::ErrorPropertyNotFound(..)));
+}
+
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_destructuring_loops_and_functions() {
+    let mut engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("let sum = 0; for [a, b] in [[1, 2], [3, 4]] { sum += a * b; } sum").unwrap(), 14);
+    assert_eq!(engine.eval::<INT>("let sum = 0; for (#{x}, i) in [#{x: 1}, #{x: 2}] { sum += x * i; } sum").unwrap(), 2);
+    assert!(matches!(engine.compile("for ([a, i], i) in [] {}").unwrap_err().err_type(), ParseErrorType::DuplicatedVariable(..)));
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        assert_eq!(engine.eval::<INT>("fn foo([a, b], #{c}) { a + b + c } foo([1, 2], #{c: 3})").unwrap(), 6);
+        assert_eq!(engine.eval::<INT>("let f = |[a, b], c| a * b + c; f.call([2, 3], 4)").unwrap(), 10);
+        assert_eq!(engine.eval::<INT>("[[1, 2], [3, 4]].map(|[a, b]| a * b).reduce(|sum, x| sum + x, 0)").unwrap(), 14);
+        assert!(matches!(engine.compile("fn foo(a, [a, b]) {}").unwrap_err().err_type(), ParseErrorType::FnDuplicatedParam(..)));
+    }
+
+    engine.set_strict_variables(true);
+
+    engine.compile("let [a, #{b}] = [1, #{b: 2}]; a + b").unwrap();
+    engine.compile("for [a, b] in [] { print(a + b); }").unwrap();
+    assert!(engine.compile("let [a, b] = [a, 1];").is_err());
+
+    #[cfg(not(feature = "no_function"))]
+    engine.compile("fn foo([a, b]) { a + b }").unwrap();
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "metadata")]
+#![cfg(not(feature = "no_object"))]
+use rhai::{CustomType, Engine, FuncRegistration, Module, TypeBuilder, INT};
+
+#[derive(Debug, Clone)]
+struct Point {
+    x: INT,
+}
+
+impl CustomType for Point {
+    fn build(mut builder: TypeBuilder<Self>) {
+        builder
+            .with_name("Point")
+            .with_fn("point", |x: INT| Point { x })
+            .with_fn("+", |a: &mut Point, b: Point| Point { x: a.x + b.x })
+            .with_get_set("x", |p: &mut Point| p.x, |p: &mut Point, x: INT| p.x = x);
+
+        #[cfg(not(feature = "no_index"))]
+        builder.with_indexer_get(|p: &mut Point, _: INT| p.x);
+    }
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.build_type::<Point>();
+
+    FuncRegistration::new("scale")
+        .with_params_info(["p: &mut Point", "factor: INT", "Point"])
+        .with_comments(["/// Scale a point.\n///\n/// # Example\n///\n/// ```rhai\n/// scale(point(1), 2)\n/// ```"])
+        .register_into_engine(&mut engine, |p: &mut Point, factor: INT| Point { x: p.x * factor });
+
+    #[cfg(not(feature = "no_module"))]
+    {
+        let mut module = Module::new();
+        module.set_doc("//! Geometry helpers.");
+        module.set_var("ORIGIN", 0 as INT);
+        FuncRegistration::new("norm")
+            .with_params_info(["p: &mut Point", "INT"])
+            .with_comments(["/// Length of a <point> & more."])
+            .set_into_module(&mut module, |p: &mut Point| p.x.abs());
+
+        let mut sub_module = Module::new();
+        FuncRegistration::new("zero").set_into_module(&mut sub_module, || 0 as INT);
+        module.set_sub_module("extra", sub_module);
+
+        engine.register_static_module("geo", module.into());
+    }
+
+    engine
+}
+
+#[test]
+fn test_docs_markdown() {
+    let engine = engine();
+    let ast = engine
+        .compile(
+            "
+                //! Script helpers.
+
+                /// Add one.
+                fn inc(x) { x + 1 }
+
+                private fn hidden() { }
+            ",
+        )
+        .unwrap();
+
+    let files = engine
+        .documentation()
+        .with_title("Test API")
+        .include_standard_packages(false)
+        .with_script_module("helpers", &ast)
+        .markdown_files();
+
+    let names: Vec<_> = files.iter().map(|(name, ..)| name.as_str()).collect();
+    assert_eq!(names[0], "index.md");
+    assert!(names.contains(&"global.md"));
+    assert!(names.contains(&"script-helpers.md"));
+    assert!(names.contains(&"type-Point.md"));
+    assert!(!names.contains(&"std.md"));
+
+    let file = |name: &str| files.iter().find(|(n, ..)| n == name).unwrap().1.as_str();
+
+    let index = file("index.md");
+    assert!(index.starts_with("# Test API\n"));
+    assert!(index.contains("* [Global namespace](global.md)"));
+    assert!(index.contains("* [Type `Point`](type-Point.md)"));
+
+    let global = file("global.md");
+    assert!(global.contains("## Operators\n\n### `+`\n\n```rhai\nop +(_: Point, _: Point) -> Point\n```"));
+    assert!(global.contains("## Properties\n\n### `x`\n\n```rhai\nget x(_: Point) -> int\nset x(_: Point, _: int)\n```"));
+    assert!(global.contains("```rhai\nfn scale(p: Point, factor: int) -> Point\n```\n\nScale a point.\n\n#### Example\n\n```rhai\nscale(point(1), 2)\n```"));
+    #[cfg(not(feature = "no_index"))]
+    assert!(global.contains("## Indexers\n\n### `[]`\n\n```rhai\nget [](_: Point, _: int) -> int\n```"));
+
+    // Functions taking the custom type as the first parameter
+    let point = file("type-Point.md");
+    assert!(point.contains("op +(_: Point, _: Point) -> Point"));
+    assert!(point.contains("fn scale(p: Point, factor: int) -> Point"));
+    assert!(!point.contains("fn point("));
+
+    let helpers = file("script-helpers.md");
+    assert!(helpers.contains("# Module `helpers`\n\nScript helpers."));
+    assert!(helpers.contains("```rhai\nfn inc(x)\n```\n\nAdd one."));
+    assert!(!helpers.contains("hidden"));
+
+    #[cfg(not(feature = "no_module"))]
+    {
+        let geo = file("module-geo.md");
+        assert!(geo.contains("# Module `geo`\n\nGeometry helpers."));
+        assert!(geo.contains("* [`geo::extra`](module-geo.extra.md)"));
+        assert!(geo.contains("```rhai\nconst ORIGIN: int;\n```"));
+        assert!(file("module-geo.extra.md").contains("fn zero()"));
+        assert!(file("type-Point.md").contains("fn norm(p: Point) -> int"));
+    }
+}
+
+#[test]
+fn test_docs_html() {
+    let engine = engine();
+    let files = engine.documentation().html_files();
+
+    assert_eq!(files[0].0, "index.html");
+    assert!(files.iter().any(|(name, ..)| name == "std.html"));
+
+    for (name, html) in &files {
+        assert!(html.starts_with("<!DOCTYPE html>"), "{}", name);
+        assert!(html.contains("<a href=\"type-Point.html\">Point</a>"), "{}", name);
+        assert!(!html.contains("http://") && !html.contains("https://"), "{}", name);
+    }
+
+    let global = &files.iter().find(|(name, ..)| name == "global.html").unwrap().1;
+    assert!(global.contains("<h3 id=\"op.-2b\"><code>+</code></h3>"));
+    assert!(global.contains("<li><a href=\"#fn.scale\"><code>scale</code></a></li>"));
+    assert!(global.contains("<h4>Example</h4>"));
+    assert!(global.contains("<pre><code class=\"language-rhai\">scale(point(1), 2)\n</code></pre>"));
+
+    #[cfg(not(feature = "no_module"))]
+    {
+        let geo = &files.iter().find(|(name, ..)| name == "module-geo.html").unwrap().1;
+        assert!(geo.contains("<p>Length of a &lt;point&gt; &amp; more.</p>"));
+    }
+}
@@ -0,0 +1,115 @@
+use rhai::Engine;
+
+#[test]
+fn test_format_script() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.format_script("let x=40+2;let y=x*-3;").unwrap(), "let x = 40 + 2;\nlet y = x * -3;\n");
+    assert_eq!(engine.format_script("if x>0{print(x)}else{print(-x)}").unwrap(), "if x > 0 { print(x) } else { print(-x) }\n");
+    assert_eq!(engine.format_script("while x<10{\nx+=1;\n   print(x);}").unwrap(), "while x < 10 {\n    x += 1;\n    print(x);\n}\n");
+    assert_eq!(engine.format_script("let f=|a,b|a+b;let g=||42;").unwrap(), "let f = |a, b| a + b;\nlet g = || 42;\n");
+    assert_eq!(engine.format_script("let a=[1,2,3];a[0]=a[1]??0;").unwrap(), "let a = [1, 2, 3];\na[0] = a[1] ?? 0;\n");
+    assert_eq!(engine.format_script("for (x,i) in 0..10 {}").unwrap(), "for (x, i) in 0..10 {}\n");
+    assert_eq!(engine.format_script("if x>0{x+=1;print(x);}").unwrap(), "if x > 0 {\n    x += 1;\n    print(x);\n}\n");
+    assert_eq!(engine.format_script("{if x{a}else{b} c}").unwrap(), "{\n    if x { a } else { b }\n    c\n}\n");
+    assert_eq!(engine.format_script("loop{do{x+=1}while x<10;}").unwrap(), "loop { do { x += 1 } while x < 10; }\n");
+    assert_eq!(engine.format_script("if x{`a${b}c`}").unwrap(), "if x { `a${b}c` }\n");
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(engine.format_script("let m=#{a:1,b:#{}};m.a=m?.b;").unwrap(), "let m = #{ a: 1, b: #{} };\nm.a = m?.b;\n");
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(engine.format_script("fn foo(x,y){\nx+y\n}\n\n\n\nfoo(1,2)").unwrap(), "fn foo(x, y) {\n    x + y\n}\n\nfoo(1, 2)\n");
+
+    assert_eq!(
+        engine.format_script("switch x {\n1=>print(1),2|3=>{print(2)}\n4 if x>0=>()\n}").unwrap(),
+        "switch x {\n    1 => print(1),\n    2 | 3 => { print(2) }\n    4 if x > 0 => ()\n}\n"
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(engine.format_script("switch x {[first,..rest]=>first,[_,..]|int=>0}").unwrap(), "switch x { [first, ..rest] => first, [_, ..] | int => 0 }\n");
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(engine.format_script("switch x {#{a:1,..}=>1,#{..}=>2}").unwrap(), "switch x { #{ a: 1, .. } => 1, #{ .. } => 2 }\n");
+
+    assert!(engine.format_script("let x = ;").is_err());
+}
+
+#[test]
+fn test_format_script_comments() {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.format_script("// header\nlet x=1;   // trailing\n\n/* block */ let y=2;\n{\n// inside\n}").unwrap(),
+        "// header\nlet x = 1; // trailing\n\n/* block */ let y = 2;\n{\n    // inside\n}\n"
+    );
+
+    assert_eq!(engine.format_script("let x = 1 + // comment\n2;").unwrap(), "let x = 1 + // comment\n    2;\n");
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.format_script("/// doc-comment\n/** block doc-comment */\nfn foo() {}\n//// not doc-comment").unwrap(),
+        "/// doc-comment\n/** block doc-comment */\nfn foo() {}\n//// not doc-comment\n"
+    );
+}
+
+#[cfg(feature = "metadata")]
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_format_script_doc_comments() {
+    let engine = Engine::new();
+
+    let script = "//! Module doc\n//////////\n/// Doc\nfn foo() {}\n/*****/\n/**** not doc */\nlet x = 1;\n";
+
+    assert_eq!(engine.format_script(script).unwrap(), script);
+
+    let ast = engine.compile(script).unwrap();
+    assert_eq!(ast.doc(), "//! Module doc");
+    assert_eq!(ast.iter_functions().next().unwrap().comments, vec!["/// Doc"]);
+}
+
+#[test]
+fn test_format_script_strings() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.format_script(r#"let s="a\tb\"c";let c='x';"#).unwrap(), "let s = \"a\\tb\\\"c\";\nlet c = 'x';\n");
+    assert_eq!(engine.format_script("let s=`x = ${ x+1 }, y = ${y}`;").unwrap(), "let s = `x = ${x + 1}, y = ${y}`;\n");
+    assert_eq!(engine.format_script("let s=`multi\n   line ${x}`;").unwrap(), "let s = `multi\n   line ${x}`;\n");
+}
+
+#[test]
+fn test_format_script_round_trip() {
+    let engine = Engine::new();
+
+    let script = r#"
+        // Compute some values
+        let total=0;let items=[1,2,  3,-4];
+        for item in items{ if item<0 {continue;}total+=item }
+        let msg=`total = ${total}, first = ${items[0]}`;
+        let r = if total>5{ "big" }else{ "small" };
+        do { total -= 1; } until total<=0;
+        loop{ break }
+        try { throw "oops" } catch (err) { print(err) }
+        let x = switch total { 0 => "zero", 1..=5 => "few", _ => "many" };
+        let y = !(x == "zero") && total != 1 || items.len() >= 3;
+    "#;
+
+    let formatted = engine.format_script(script).unwrap();
+
+    assert_eq!(engine.compact_script(script).unwrap(), engine.compact_script(&formatted).unwrap());
+    assert_eq!(engine.format_script(&formatted).unwrap(), formatted);
+}
+
+#[cfg(not(feature = "no_custom_syntax"))]
+#[test]
+fn test_format_script_custom_syntax() {
+    let mut engine = Engine::new();
+
+    engine.register_custom_syntax(["exec", "[", "$ident$", "]", "->", "$block$"], true, |_, _| Ok(().into())).unwrap();
+
+    let script = "exec[x]->{print(x);}\nexec [ y ] -> {\nlet z=y;\n}";
+    let formatted = engine.format_script(script).unwrap();
+
+    assert_eq!(formatted, "exec [x] -> { print(x); }\nexec [y] -> {\n    let z = y;\n}\n");
+    assert_eq!(engine.compact_script(script).unwrap(), engine.compact_script(&formatted).unwrap());
+}
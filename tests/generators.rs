@@ -0,0 +1,239 @@
+#![cfg(not(feature = "no_function"))]
+use rhai::{Engine, EvalAltResult, Generator, ParseErrorType, INT};
+
+#[test]
+fn test_generators_for() {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn evens(n) {
+                        let i = 0;
+                        while i < n {
+                            yield i;
+                            i += 2;
+                        }
+                    }
+
+                    let sum = 0;
+                    for x in evens(10) { sum += x; }
+                    sum
+                "
+            )
+            .unwrap(),
+        20
+    );
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn squares(n) {
+                        for (x, i) in 1..=n {
+                            if i % 2 == 0 { yield x * x; } else { let y = x; yield -y; }
+                        }
+                        yield 1000;
+                    }
+
+                    let sum = 0;
+                    for (x, i) in squares(4) { sum += x * (i + 1); }
+                    sum
+                "
+            )
+            .unwrap(),
+        1 - 2 * 2 + 9 * 3 - 4 * 4 + 1000 * 5
+    );
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn nested() {
+                        for x in 0..3 {
+                            let y = 0;
+                            do {
+                                { yield x * 10 + y; }
+                                y += 1;
+                            } until y >= x;
+                        }
+                    }
+
+                    let sum = 0;
+                    for x in nested() { sum += x; }
+                    sum
+                "
+            )
+            .unwrap(),
+        0 + 10 + 20 + 21
+    );
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+fn test_generators_next() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("fn gen(x) { yield x; yield x + 1; } let g = gen(40); g.next() + g.next() + if g.is_done { 100 } else { 0 }").unwrap(), 81);
+    assert!(engine.eval::<bool>("fn gen() { yield 1; } let g = gen(); g.next(); g.next(); g.is_done").unwrap());
+    assert_eq!(engine.eval::<()>("fn gen() { yield 1; return; yield 2; } let g = gen(); g.next(); g.next()").unwrap(), ());
+    assert_eq!(engine.eval::<INT>("fn gen(n) { for i in 0..n { yield i; } } let g = gen(2); g.next(); g.next(); g.next(); if g.is_done { 42 } else { 0 }").unwrap(), 42);
+    assert_eq!(engine.eval::<String>("fn gen() { yield 1; } type_of(gen())").unwrap(), "generator");
+    assert_eq!(engine.eval::<String>("fn gen() { yield 1; } gen().name").unwrap(), "gen");
+
+    let g = engine.eval::<Generator>("fn gen() { yield 1; } gen()").unwrap();
+    assert_eq!(g.fn_name(), "gen");
+    assert!(!g.is_done());
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn naturals() {
+                        let n = 0;
+                        loop {
+                            n += 1;
+                            yield n;
+                        }
+                    }
+
+                    let sum = 0;
+                    for n in naturals() {
+                        if n > 100 { break; }
+                        if n % 2 == 0 { continue; }
+                        sum += n;
+                    }
+                    sum
+                "
+            )
+            .unwrap(),
+        2500
+    );
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn inner(n) { for i in 0..n { yield i; } }
+                    fn outer() { for i in inner(3) { yield i; yield i * 100; } }
+
+                    let a = outer();
+                    let b = outer();
+                    a.next();
+                    a.next();
+                    b.next() + a.next() + a.next()
+                "
+            )
+            .unwrap(),
+        101
+    );
+}
+
+#[test]
+#[cfg(not(feature = "no_closure"))]
+#[cfg(not(feature = "no_object"))]
+fn test_generators_closures() {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    let total = 0;
+                    let gen = || { for i in 0..5 { total += i; yield total; } };
+
+                    let g = gen.call();
+                    g.next();
+                    g.next();
+                    total
+                "
+            )
+            .unwrap(),
+        1
+    );
+}
+
+#[test]
+fn test_generators_try() {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn gen() {
+                        for i in 0..3 {
+                            try {
+                                yield i;
+                                if i == 1 { throw 10; }
+                                yield i * 100;
+                            } catch (e) {
+                                yield e;
+                            }
+                        }
+                    }
+
+                    let sum = 0;
+                    for x in gen() { sum += x; }
+                    sum
+                "
+            )
+            .unwrap(),
+        0 + 0 + 1 + 10 + 2 + 200
+    );
+
+    assert!(matches!(
+        *engine.run("fn gen() { try { yield 1; } catch { } throw 42; } for x in gen() { }").unwrap_err(),
+        EvalAltResult::ErrorInFunctionCall(name, .., err, _) if name == "gen" && matches!(*err, EvalAltResult::ErrorRuntime(..))
+    ));
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+fn test_generators_errors() {
+    let engine = Engine::new();
+
+    assert!(matches!(
+        engine.compile("fn gen(x) { switch x { 1 => { yield 1; } } }").unwrap_err().err_type(),
+        ParseErrorType::WrongYield
+    ));
+    assert!(matches!(
+        engine.compile("fn gen() { let x = { yield 1; 2 } + 1; }").unwrap_err().err_type(),
+        ParseErrorType::WrongYield
+    ));
+    assert!(matches!(
+        engine.compile("yield 1;").unwrap_err().err_type(),
+        ParseErrorType::Reserved(s) if s == "yield"
+    ));
+
+    assert!(matches!(
+        *engine.run("fn gen() { yield 1; throw 42; } for x in gen() { }").unwrap_err(),
+        EvalAltResult::ErrorInFunctionCall(name, .., err, _) if name == "gen" && matches!(*err, EvalAltResult::ErrorRuntime(..))
+    ));
+    assert_eq!(engine.eval::<()>("fn gen() { throw 42; yield 1; } let g = gen(); try { g.next() } catch { } g.next()").unwrap(), ());
+}
+
+#[test]
+#[cfg(not(feature = "unchecked"))]
+fn test_generators_max_operations() {
+    let mut engine = Engine::new();
+    engine.set_max_operations(500);
+
+    assert!(matches!(
+        *engine.run("fn naturals() { let n = 0; loop { n += 1; yield n; } } for n in naturals() { }").unwrap_err(),
+        EvalAltResult::ErrorTooManyOperations(..)
+    ));
+
+    // Operations within the generator count towards the limit when resumed via `next`
+    engine.set_max_operations(2000);
+
+    let script = "fn gen() { loop { let x = 0; while x < 50 { x += 1; } yield 1; } } let g = gen(); for i in 0..100 { next(g); }";
+    assert!(matches!(*engine.run(script).unwrap_err(), EvalAltResult::ErrorTooManyOperations(..)));
+
+    #[cfg(not(feature = "no_object"))]
+    assert!(matches!(
+        *engine.run("fn gen() { loop { let x = 0; while x < 50 { x += 1; } yield 1; } } let g = gen(); for i in 0..100 { g.next(); }").unwrap_err(),
+        EvalAltResult::ErrorTooManyOperations(..)
+    ));
+}
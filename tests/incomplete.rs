@@ -0,0 +1,49 @@
+use rhai::{Engine, ParseErrorType, Scope};
+
+#[cfg(not(feature = "no_position"))]
+#[test]
+fn test_incomplete_input() {
+    let engine = Engine::new();
+    let scope = Scope::new();
+
+    for script in [
+        "if x > 0 {",
+        "let x = 1 +",
+        "let x =",
+        "foo(1, ",
+        "while x",
+        "x +\n\n",
+        "let s = `hello",
+        "let s = \"hello \\",
+        "let s = `a ${x} b",
+        "x /* comment",
+        "do { x } while",
+        #[cfg(not(feature = "no_index"))]
+        "[1, 2",
+        #[cfg(not(feature = "no_object"))]
+        "#{ a: 1",
+        #[cfg(not(feature = "no_object"))]
+        "x.",
+        #[cfg(not(feature = "no_function"))]
+        "fn foo(x) {\n    x + 1",
+    ] {
+        assert!(engine.compile_with_scope_if_complete(&scope, script).unwrap().is_none(), "{}", script);
+    }
+
+    assert!(engine.compile_with_scope_if_complete(&scope, "let x = 1; x + 1").unwrap().is_some());
+    assert!(engine.compile_with_scope_if_complete(&scope, "let x = 1 // comment").unwrap().is_some());
+}
+
+#[test]
+fn test_incomplete_syntax_errors() {
+    let engine = Engine::new();
+    let scope = Scope::new();
+
+    assert!(matches!(engine.compile_with_scope_if_complete(&scope, "let x = ;").unwrap_err().err_type(), ParseErrorType::BadInput(..)));
+    assert!(matches!(engine.compile_with_scope_if_complete(&scope, "if x > 0 } {").unwrap_err().err_type(), ParseErrorType::MissingToken(..)));
+    assert!(matches!(engine.compile_with_scope_if_complete(&scope, "let s = \"abc").unwrap_err().err_type(), ParseErrorType::BadInput(..)));
+    assert!(engine.compile_with_scope_if_complete(&scope, "}").is_err());
+
+    // Errors are reported as usual by other compile functions
+    assert!(matches!(engine.compile("if x > 0 {").unwrap_err().err_type(), ParseErrorType::MissingToken(..)));
+}
@@ -0,0 +1,95 @@
+use rhai::{Engine, LintCode, Position};
+
+fn lint(engine: &Engine, script: &str) -> Vec<(LintCode, Option<usize>)> {
+    engine.lint(script).unwrap().into_iter().map(|w| (w.code, w.position.line())).collect()
+}
+
+#[test]
+fn test_lint_unused_variables() {
+    let engine = Engine::new();
+
+    assert_eq!(lint(&engine, "let x = 1;\nlet y = 2;\nprint(y);"), [(LintCode::UnusedVariable, Some(1))]);
+    assert_eq!(lint(&engine, "let _x = 1;\nlet y = 2;\nlet f = |a| a + y;\nf.call(1);"), []);
+    assert_eq!(lint(&engine, "let x = 1;\nx = 2;"), []);
+    assert_eq!(lint(&engine, "let x = 1;\neval(\"x\");"), []);
+    assert_eq!(lint(&engine, "for i in 0..10 {\n    print(42);\n}\nfor (x, i) in [1, 2] {\n    print(x);\n}"), [(LintCode::UnusedVariable, Some(1)), (LintCode::UnusedVariable, Some(4))]);
+    assert_eq!(lint(&engine, "let x = [];\nx.push(if true { let y = 1; 42 } else { 0 });"), [(LintCode::UnusedVariable, Some(2))]);
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(lint(&engine, "fn foo(x) {\n    let y = x;\n    42\n}\nfoo(1);"), [(LintCode::UnusedVariable, Some(2))]);
+
+    #[cfg(not(feature = "no_module"))]
+    assert_eq!(lint(&engine, "export let x = 1;\nlet y = 2;\nexport y as z;"), []);
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(lint(&engine, "let [a, b] = [1, 2];\nprint(a);\nfor [x, y] in [] {\n    print(y);\n}"), [(LintCode::UnusedVariable, Some(1)), (LintCode::UnusedVariable, Some(3))]);
+}
+
+#[test]
+fn test_lint_shadowed_variables() {
+    let engine = Engine::new();
+
+    let warnings = engine.lint("let x = 1;\nlet x = 2;\nprint(x);").unwrap();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].code, LintCode::UnusedVariable);
+    assert_eq!(warnings[1].code, LintCode::ShadowedVariable);
+    assert_eq!(warnings[1].message, "Variable x shadows a previous definition at line 1, position 5");
+
+    assert_eq!(lint(&engine, "let x = 1;\nlet x = x + 1;\nprint(x);"), []);
+    assert_eq!(lint(&engine, "let x = 1;\nif x > 0 {\n    let x = 2;\n    print(x);\n}"), [(LintCode::ShadowedVariable, Some(3))]);
+    assert_eq!(lint(&engine, "for x in 0..10 {\n    let x = 2;\n    print(x);\n}"), [(LintCode::UnusedVariable, Some(1)), (LintCode::ShadowedVariable, Some(2))]);
+    assert_eq!(lint(&engine, "{\n    let x = 1;\n    print(x);\n}\nlet x = 2;\nprint(x);"), []);
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(lint(&engine, "fn foo(x) {\n    let x = 42;\n    x\n}\nfoo(1);"), [(LintCode::ShadowedVariable, Some(2))]);
+}
+
+#[test]
+fn test_lint_unreachable_code() {
+    let engine = Engine::new();
+
+    assert_eq!(lint(&engine, "loop {\n    break;\n    print(1);\n}"), [(LintCode::UnreachableCode, Some(3))]);
+    assert_eq!(lint(&engine, "throw 42;\nprint(1);\nprint(2);"), [(LintCode::UnreachableCode, Some(2))]);
+    assert_eq!(lint(&engine, "if true {\n    return;\n}\nprint(1);"), []);
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(lint(&engine, "fn foo() {\n    return 1;\n    2\n}\nfoo();"), [(LintCode::UnreachableCode, Some(3))]);
+}
+
+#[test]
+fn test_lint_unknown_functions() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("bar", |x: i64| x + 1);
+
+    let warnings = engine.lint("bar(1);\nlet x = bar(1, 2);\nfoo(x);\nlet s = [1].len() + \"x\".len;\nprint(s);").unwrap();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].code, LintCode::UnknownFunction);
+    assert_eq!(warnings[0].message, "Function not found: bar with 2 parameters");
+    assert_eq!(warnings[0].position, Position::new(2, 9));
+    assert_eq!(warnings[1].to_string(), "Function not found: foo [unknown_function] (line 3, position 1)");
+
+    assert_eq!(lint(&engine, "let f = Fn(\"bar\");\nprint(type_of(f));\ndebug(f.call(1));\nlet x = [1, 2].map(|v| foo(v));"), [(LintCode::UnusedVariable, Some(4)), (LintCode::UnknownFunction, Some(4))]);
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(lint(&engine, "fn foo(x) { x }\nfoo(1);\nfoo();"), [(LintCode::UnknownFunction, Some(3))]);
+}
+
+#[test]
+fn test_lint_suppression() {
+    let engine = Engine::new();
+
+    assert_eq!(lint(&engine, "let x = 1; // rhai-lint: allow(unused_variable)\nfoo(); /* rhai-lint: allow */"), []);
+    assert_eq!(lint(&engine, "// rhai-lint: allow(unused_variable, shadowed_variable)\nlet x = 1;\nlet y = 2;"), [(LintCode::UnusedVariable, Some(3))]);
+    assert_eq!(lint(&engine, "let x = 1; // rhai-lint: allow(unknown_function)"), [(LintCode::UnusedVariable, Some(1))]);
+
+    assert!(engine.lint("let x = ;").is_err());
+}
+
+#[test]
+fn test_lint_codes() {
+    for code in LintCode::ALL {
+        assert_eq!(LintCode::lookup(code.as_str()), Some(code));
+    }
+    assert_eq!(LintCode::lookup("foo"), None);
+}
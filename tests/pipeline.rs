@@ -0,0 +1,71 @@
+use rhai::{Engine, INT};
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_pipeline() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("fn add(x, y) { x + y } 40 |> add(2)").unwrap(), 42);
+    assert_eq!(engine.eval::<INT>("fn add(x, y) { x + y } 1 + 2 |> add(3) |> add(4) * 2").unwrap(), 20);
+    assert_eq!(engine.eval::<INT>("fn add(x, y) { x + y } fn double(x) { x * 2 } 20 |> double() |> add(2)").unwrap(), 42);
+    assert!(engine.eval::<bool>(r#""hello" |> len() == 5"#).unwrap());
+    assert_eq!(engine.eval::<INT>("let f = |x| x * 2; 21 |> f").unwrap(), 42);
+    assert_eq!(engine.eval::<INT>("fn add(x, y) { x + y } 2 |> Fn(\"add\").curry(40)").unwrap(), 42);
+    #[cfg(not(feature = "no_closure"))]
+    assert_eq!(engine.eval::<INT>("let x = 1; 41 |> (|v| v + x)").unwrap(), 42);
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(engine.eval::<INT>("[1, 2, 3] |> map(|x| x * 2) |> reduce(|s, x| s + x, 0)").unwrap(), 12);
+
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(engine.eval::<INT>("[1, 2, 3] |> .map(|x| x * 2) |> .len()").unwrap(), 3);
+
+    #[cfg(not(feature = "no_module"))]
+    {
+        let mut engine = Engine::new();
+        let mut module = rhai::Module::new();
+        module.set_native_fn("calc", |x: INT, y: INT| Ok(x * y));
+        engine.register_static_module("math", module.into());
+
+        assert_eq!(engine.eval::<INT>("6 |> math::calc(7)").unwrap(), 42);
+    }
+
+    assert!(engine.eval::<INT>("1 |> 2").is_err());
+}
+
+#[test]
+#[cfg(not(feature = "no_optimize"))]
+fn test_pipeline_optimize() {
+    let mut engine = Engine::new();
+    engine.set_optimization_level(rhai::OptimizationLevel::Full);
+
+    let ast = engine.compile(r#""hello" |> len()"#).unwrap();
+    #[cfg(feature = "internals")]
+    assert_eq!(ast.statements().len(), 1);
+    assert_eq!(ast.iter_literal_variables(true, true).count(), 0);
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 5);
+
+    let ast = engine.compile(r#"const X = "hello" |> len(); X"#).unwrap();
+    assert_eq!(ast.iter_literal_variables(true, false).map(|(.., v)| v.as_int().unwrap()).collect::<Vec<_>>(), [5]);
+}
+
+#[test]
+#[cfg(feature = "internals")]
+#[cfg(not(feature = "no_function"))]
+fn test_pipeline_walk() {
+    let engine = Engine::new();
+
+    let ast = engine.compile("fn add(x, y) { x + y } let x = 40 |> add(2);").unwrap();
+
+    let mut calls = Vec::new();
+
+    ast.walk(&mut |path| {
+        if let Some(rhai::ASTNode::Expr(rhai::Expr::FnCall(x, ..))) = path.last() {
+            calls.push((x.name.to_string(), x.args.len()));
+        }
+        true
+    });
+
+    assert!(calls.contains(&("add".to_string(), 2)));
+}
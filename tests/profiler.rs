@@ -0,0 +1,153 @@
+#![cfg(feature = "profiling")]
+use rhai::{Engine, ProfileEntry, Profiler, INT};
+
+fn find(profiler: &Profiler, name: &str) -> ProfileEntry {
+    profiler.entries().into_iter().find(|e| e.fn_name == name).unwrap()
+}
+
+#[test]
+fn test_profiler_native() {
+    let mut engine = Engine::new();
+    let profiler = Profiler::new();
+    engine.set_profiler(Some(profiler.clone()));
+
+    engine.register_fn("add", |x: INT, y: INT| x + y);
+
+    assert!(profiler.is_empty());
+    assert_eq!(engine.eval::<INT>("let x = 0; for i in 0..5 { x = add(x, i); } x").unwrap(), 10);
+
+    let add = find(&profiler, "add");
+    assert!(!add.is_script);
+    assert_eq!(add.num_params, 2);
+    assert_eq!(add.calls, 5);
+    assert!(add.pos.is_none());
+    assert_eq!(add.inclusive_time, add.exclusive_time);
+
+    profiler.clear();
+    assert!(profiler.is_empty());
+
+    engine.set_profiler(None);
+    assert!(engine.profiler().is_none());
+    engine.run("add(1, 2)").unwrap();
+    assert!(profiler.is_empty());
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_profiler_script() {
+    let mut engine = Engine::new();
+    let profiler = Profiler::new();
+    engine.set_profiler(Some(profiler.clone()));
+
+    let mut ast = engine
+        .compile(
+            "
+                fn inner(x) { let s = 0; for i in 0..x { s += i; } s }
+                fn outer(x) { inner(x) + inner(x * 2) }
+                outer(10)
+            ",
+        )
+        .unwrap();
+    ast.set_source("test");
+
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 235);
+
+    let inner = find(&profiler, "inner");
+    let outer = find(&profiler, "outer");
+
+    assert!(inner.is_script);
+    assert_eq!(inner.num_params, 1);
+    assert_eq!(inner.source.as_deref(), Some("test"));
+    #[cfg(not(feature = "no_position"))]
+    assert_eq!(inner.pos.line(), Some(2));
+
+    assert_eq!(inner.calls, 2);
+    assert_eq!(outer.calls, 1);
+
+    assert!(inner.inclusive_time >= inner.exclusive_time);
+    assert!(outer.inclusive_time >= inner.inclusive_time + outer.exclusive_time);
+    assert_eq!(inner.inclusive_operations, inner.exclusive_operations);
+    assert_eq!(outer.inclusive_operations, outer.exclusive_operations + inner.inclusive_operations);
+    #[cfg(not(feature = "unchecked"))]
+    assert!(inner.exclusive_operations > outer.exclusive_operations);
+
+    // Entries are kept across evaluations
+    engine.eval_ast::<INT>(&ast).unwrap();
+    assert_eq!(find(&profiler, "inner").calls, 4);
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_profiler_recursion() {
+    let mut engine = Engine::new();
+    let profiler = Profiler::new();
+    engine.set_profiler(Some(profiler.clone()));
+
+    assert_eq!(engine.eval::<INT>("fn fib(n) { if n < 2 { n } else { fib(n - 1) + fib(n - 2) } } fib(6)").unwrap(), 8);
+
+    let fib = find(&profiler, "fib");
+    assert_eq!(fib.calls, 25);
+    assert_eq!(fib.inclusive_time, fib.exclusive_time);
+    assert_eq!(fib.inclusive_operations, fib.exclusive_operations);
+
+    // Tail calls replace the current call
+    engine.set_tail_call_optimization(true);
+    profiler.clear();
+    engine.run("fn count(n) { if n == 0 { return 0; } count(n - 1) } count(5)").unwrap();
+
+    assert_eq!(find(&profiler, "count").calls, 6);
+    #[cfg(not(feature = "no_optimize"))]
+    assert!(profiler.to_folded().lines().all(|line| !line.contains(';')));
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_profiler_folded() {
+    let mut engine = Engine::new();
+    let profiler = Profiler::new();
+    engine.set_profiler(Some(profiler.clone()));
+
+    engine.register_fn("work", |x: INT| x + 1);
+
+    engine.run("fn b(x) { work(x) } fn a(x) { b(x) + work(x) } a(1); b(2);").unwrap();
+
+    let folded = profiler.to_folded();
+    let mut stacks = folded
+        .lines()
+        .map(|line| {
+            let (stack, time) = line.rsplit_once(' ').unwrap();
+            time.parse::<u128>().unwrap();
+            stack.split(';').map(|f| f.split(' ').next().unwrap()).collect::<Vec<_>>().join(";")
+        })
+        .collect::<Vec<_>>();
+    stacks.sort();
+
+    assert_eq!(stacks, ["a", "a;b", "a;b;work", "a;work", "b", "b;work"]);
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_index"))]
+fn test_profiler_json() {
+    let mut engine = Engine::new();
+    let profiler = Profiler::new();
+    engine.set_profiler(Some(profiler.clone()));
+
+    engine.register_fn("work", |x: INT| x + 1);
+    engine.run("work(1); work(2);").unwrap();
+
+    let json = engine.parse_json(profiler.to_json(), true).unwrap();
+    let functions = json["functions"].clone().into_array().unwrap();
+
+    assert_eq!(functions.len(), 1);
+
+    let work = functions[0].clone().cast::<rhai::Map>();
+    assert_eq!(work["name"].clone().into_string().unwrap(), "work");
+    assert_eq!(work["calls"].as_int().unwrap(), 2);
+    assert_eq!(work["params"].as_int().unwrap(), 1);
+    assert!(!work["script"].as_bool().unwrap());
+    assert!(work["source"].is_unit());
+    assert!(work["line"].is_unit());
+    assert!(work.contains_key("inclusive_time"));
+    assert!(work.contains_key("exclusive_operations"));
+}
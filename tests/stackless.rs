@@ -0,0 +1,189 @@
+#![cfg(feature = "stackless")]
+#![cfg(not(feature = "no_function"))]
+use rhai::{Engine, EvalAltResult, INT};
+
+#[test]
+#[cfg(not(feature = "unchecked"))]
+fn test_stackless_deep_recursion() {
+    // Deep recursion does not depend on the size of the native stack
+    let result = std::thread::Builder::new()
+        .stack_size(256 * 1024)
+        .spawn(|| {
+            let mut engine = Engine::new();
+            engine.set_max_call_levels(100_000);
+
+            engine
+                .eval::<INT>(
+                    "
+                        fn sum(n) { if n == 0 { 0 } else { n + sum(n - 1) } }
+                        sum(50000)
+                    ",
+                )
+                .unwrap()
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert_eq!(result, 1_250_025_000);
+
+    let mut engine = Engine::new();
+    engine.set_max_call_levels(1000);
+
+    assert!(matches!(
+        *engine
+            .run(
+                "
+                    fn sum(n) { if n == 0 { 0 } else { n + sum(n - 1) } }
+                    sum(2000)
+                "
+            )
+            .unwrap_err(),
+        EvalAltResult::ErrorInFunctionCall(..) | EvalAltResult::ErrorStackOverflow(..)
+    ));
+}
+
+#[test]
+fn test_stackless_operands() {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn fib(n) { if n < 2 { n } else { fib(n - 1) + fib(n - 2) } }
+                    fib(7)
+                "
+            )
+            .unwrap(),
+        13
+    );
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn is_even(n) { n == 0 || is_odd(n - 1) }
+                    fn is_odd(n) { n != 0 && is_even(n - 1) }
+                    let count = 0;
+                    let n = 0;
+                    while is_even(2) && n < 6 {
+                        if is_odd(n) { count += 1; }
+                        n += 1;
+                    }
+                    count
+                "
+            )
+            .unwrap(),
+        3
+    );
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn nothing() { }
+                    fn double(x) { x * 2 }
+                    let x = nothing() ?? double({ let y = 20; y + 1 });
+                    x
+                "
+            )
+            .unwrap(),
+        42
+    );
+
+    assert!(matches!(
+        *engine
+            .run(
+                "
+                    fn fail(n) { if n == 0 { throw 42; } fail(n - 1) + 1 }
+                    fail(3)
+                "
+            )
+            .unwrap_err(),
+        EvalAltResult::ErrorInFunctionCall(..)
+    ));
+}
+
+#[test]
+fn test_stackless_scope() {
+    let engine = Engine::new();
+    let mut scope = rhai::Scope::new();
+
+    engine
+        .run_with_scope(
+            &mut scope,
+            "
+                fn add(x, y) { x + y }
+                let x = add(1, 2);
+                let y = if x > 0 { add(x, 10) } else { 0 };
+            ",
+        )
+        .unwrap();
+
+    assert_eq!(scope.get_value::<INT>("x").unwrap(), 3);
+    assert_eq!(scope.get_value::<INT>("y").unwrap(), 13);
+
+    let result = engine
+        .eval::<INT>(
+            "
+                fn add(x, y) { x + y }
+                let total = 0;
+                for i in 0..10 { total = add(total, i); }
+                total
+            ",
+        )
+        .unwrap();
+
+    assert_eq!(result, 45);
+}
+
+#[test]
+#[cfg(not(feature = "unchecked"))]
+fn test_stackless_native_recursion() {
+    // These forms still recurse on the native stack, so they remain bounded by max_call_levels
+    let mut engine = Engine::new();
+
+    #[cfg(not(feature = "no_custom_syntax"))]
+    engine
+        .register_custom_syntax(["twice", "$expr$"], false, |context, inputs| {
+            let value = context.eval_expression_tree(&inputs[0])?.as_int().unwrap();
+            Ok((value * 2).into())
+        })
+        .unwrap();
+
+    let mut scripts = vec![
+        // Function pointers
+        r#"fn f(n) { if n == 0 { 0 } else { 1 + Fn("f").call(n - 1) } }"#,
+        // Closures
+        "fn f(n) { let g = |n| if n == 0 { 0 } else { 1 + f(n - 1) }; g.call(n) }",
+        // Switch cases
+        "fn f(n) { switch n { 0 => 0, _ => 1 + f(n - 1) } }",
+    ];
+
+    // Method calls
+    #[cfg(not(feature = "no_object"))]
+    scripts.push(
+        "
+            fn g() { if this == 0 { 0 } else { let x = this - 1; 1 + x.g() } }
+            fn f(n) { n.g() }
+        ",
+    );
+
+    // Nested expressions other than function call arguments and `&&`, `||` or `??`
+    #[cfg(not(feature = "no_index"))]
+    scripts.push("fn f(n) { if n == 0 { 0 } else { [f(n - 1)][0] + 1 } }");
+
+    // Custom syntax
+    #[cfg(not(feature = "no_custom_syntax"))]
+    scripts.push("fn f(n) { if n == 0 { 0 } else { 1 + (twice f(n - 1)) / 2 } }");
+
+    for script in scripts {
+        engine.set_max_call_levels(100);
+        assert_eq!(engine.eval::<INT>(&format!("{script} f(20)")).unwrap(), 20, "{script}");
+
+        engine.set_max_call_levels(16);
+        let err = engine.run(&format!("{script} f(100)")).unwrap_err();
+        assert!(matches!(err.unwrap_inner(), EvalAltResult::ErrorStackOverflow(..)), "{}: {}", script, err);
+    }
+}
@@ -0,0 +1,308 @@
+use rhai::{Continuation, Engine, EvalAltResult, EvalOutcome, Scope, Suspender, AST, INT};
+
+fn make_engine(suspender: &Suspender) -> Engine {
+    let mut engine = Engine::new();
+
+    let s = suspender.clone();
+    engine.register_fn("checkpoint", move || s.suspend());
+
+    engine
+}
+
+/// Run an [`AST`] to completion, resuming it in a new scope after each suspension.
+fn run_to_end(engine: &Engine, ast: &AST, suspender: &Suspender) -> (INT, usize) {
+    let mut outcome = engine.eval_ast_suspendable(&mut Scope::new(), ast, suspender).unwrap();
+    let mut suspensions = 0;
+
+    loop {
+        match outcome {
+            EvalOutcome::Done(value) => return (value.as_int().unwrap(), suspensions),
+            EvalOutcome::Suspended(continuation) => {
+                suspensions += 1;
+                outcome = engine.resume_ast(&mut Scope::new(), ast, continuation, suspender).unwrap();
+            }
+        }
+    }
+}
+
+fn suspend_once(engine: &Engine, ast: &AST, suspender: &Suspender) -> Continuation {
+    match engine.eval_ast_suspendable(&mut Scope::new(), ast, suspender).unwrap() {
+        EvalOutcome::Suspended(continuation) => continuation,
+        EvalOutcome::Done(..) => panic!("evaluation should be suspended"),
+    }
+}
+
+#[test]
+fn test_suspend_statements() {
+    let suspender = Suspender::new();
+    let engine = make_engine(&suspender);
+
+    let ast = engine.compile("let x = 40; checkpoint(); x += 1; checkpoint(); x + 1").unwrap();
+    assert_eq!(run_to_end(&engine, &ast, &suspender), (42, 2));
+
+    let ast = engine.compile("42").unwrap();
+    assert_eq!(run_to_end(&engine, &ast, &suspender), (42, 0));
+
+    // A request made at the end of the evaluation is discarded
+    let ast = engine.compile("checkpoint(); 42").unwrap();
+    let mut scope = Scope::new();
+    let continuation = suspend_once(&engine, &ast, &suspender);
+    engine.resume_ast(&mut scope, &ast, continuation, &suspender).unwrap();
+    assert!(!suspender.is_requested());
+
+    // Variables are visible in the scope when suspended
+    let ast = engine.compile("let x = 42; checkpoint(); const y = x;").unwrap();
+    let mut scope = Scope::new();
+    let continuation = match engine.eval_ast_suspendable(&mut scope, &ast, &suspender).unwrap() {
+        EvalOutcome::Suspended(continuation) => continuation,
+        EvalOutcome::Done(..) => unreachable!(),
+    };
+    assert_eq!(scope.get_value::<INT>("x").unwrap(), 42);
+
+    let mut scope = Scope::new();
+    engine.resume_ast(&mut scope, &ast, continuation, &suspender).unwrap();
+    assert_eq!(scope.get_value::<INT>("y").unwrap(), 42);
+    assert!(scope.is_constant("y").unwrap());
+}
+
+#[test]
+fn test_suspend_blocks() {
+    let suspender = Suspender::new();
+    let engine = make_engine(&suspender);
+
+    let ast = engine
+        .compile(
+            "
+                let sum = 0;
+
+                for x in 1..=10 {
+                    let y = x * 2;
+                    if x % 3 == 0 { checkpoint(); } else { sum += 1; }
+                    sum += y;
+                }
+
+                let n = 0;
+                while n < 3 {
+                    n += 1;
+                    { checkpoint(); }
+                }
+
+                try {
+                    checkpoint();
+                    throw 7;
+                } catch (e) {
+                    checkpoint();
+                    sum += e;
+                }
+
+                sum + n
+            ",
+        )
+        .unwrap();
+
+    assert_eq!(run_to_end(&engine, &ast, &suspender), (127, 8));
+}
+
+#[test]
+fn test_suspend_ranges() {
+    let suspender = Suspender::new();
+    let engine = make_engine(&suspender);
+
+    // Ranges are kept without collecting their values
+    let ast = engine
+        .compile(
+            "
+                let sum = 0;
+                for i in 0..1000000000 {
+                    sum += i;
+                    if i == 2 { checkpoint(); }
+                    if i == 4 { break; }
+                }
+                sum
+            ",
+        )
+        .unwrap();
+    assert_eq!(run_to_end(&engine, &ast, &suspender), (10, 1));
+
+    let ast = engine.compile("let sum = 0; for i in range(10, 0, -3) { sum += i; checkpoint(); } sum").unwrap();
+    assert_eq!(run_to_end(&engine, &ast, &suspender), (22, 4));
+
+    let ast = engine.compile("let sum = 0; for (i, n) in 1..=3 { sum += i * n; checkpoint(); } sum").unwrap();
+    assert_eq!(run_to_end(&engine, &ast, &suspender), (8, 3));
+
+    // Other iterators cannot be suspended
+    #[cfg(not(feature = "no_index"))]
+    {
+        let ast = engine.compile("for x in [1, 2, 3] { checkpoint(); }").unwrap();
+        let err = engine.eval_ast_suspendable(&mut Scope::new(), &ast, &suspender).unwrap_err();
+        assert!(matches!(*err, EvalAltResult::ErrorRuntime(..)));
+    }
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_suspend_functions() {
+    let suspender = Suspender::new();
+    let engine = make_engine(&suspender);
+
+    let ast = engine
+        .compile(
+            "
+                fn add(a, b) {
+                    let c = b;
+                    checkpoint();
+                    if c > 100 { return c; }
+                    a + c
+                }
+                fn twice(x) {
+                    let y = add(x, x);
+                    return add(y, 0);
+                }
+
+                let sum = add(1, 1);
+                sum = twice(sum);
+                sum + 38
+            ",
+        )
+        .unwrap();
+
+    assert_eq!(run_to_end(&engine, &ast, &suspender), (42, 3));
+
+    // The caller's variables stay hidden from the function after resuming
+    let ast = engine
+        .compile(
+            "
+                fn peek() { checkpoint(); let r = 0; try { r = secret; } catch { r = -1; } r }
+                let secret = 42;
+                let x = peek();
+                x + secret
+            ",
+        )
+        .unwrap();
+
+    assert_eq!(run_to_end(&engine, &ast, &suspender), (41, 1));
+}
+
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_optimize"))]
+#[test]
+fn test_suspend_tail_calls() {
+    let suspender = Suspender::new();
+    let mut engine = make_engine(&suspender);
+    engine.set_tail_call_optimization(true);
+
+    let ast = engine
+        .compile(
+            "
+                fn loop1(n, acc) {
+                    if n % 25000 == 0 { checkpoint(); }
+                    if n == 0 { return acc; }
+                    loop1(n - 1, acc + 1)
+                }
+                loop1(100000, 0)
+            ",
+        )
+        .unwrap();
+    assert_eq!(run_to_end(&engine, &ast, &suspender), (100000, 5));
+
+    // Suspended inside a function replacing the one called by the script
+    let ast = engine
+        .compile(
+            "
+                fn even(n) { if n == 0 { true } else { odd(n - 1) } }
+                fn odd(n) -> bool {
+                    if n % 5000 == 0 { checkpoint(); }
+                    if n == 0 { false } else { even(n - 1) }
+                }
+                let r = even(10001);
+                if r { 1 } else { 0 }
+            ",
+        )
+        .unwrap();
+    assert_eq!(run_to_end(&engine, &ast, &suspender), (0, 3));
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_suspend_on_progress() {
+    let suspender = Suspender::new();
+    let mut engine = Engine::new();
+
+    let s = suspender.clone();
+    engine.on_progress(move |count| {
+        if count % 100 == 0 {
+            s.suspend();
+        }
+        None
+    });
+
+    let ast = engine.compile("let sum = 0; for i in 0..100 { sum += i; } sum").unwrap();
+    let (sum, suspensions) = run_to_end(&engine, &ast, &suspender);
+
+    assert_eq!(sum, 4950);
+    assert!(suspensions > 0);
+}
+
+#[test]
+fn test_suspend_errors() {
+    let suspender = Suspender::new();
+    let engine = make_engine(&suspender);
+
+    let ast = engine.compile("let x = 1; { let y = 2; checkpoint(); } x").unwrap();
+    let continuation = suspend_once(&engine, &ast, &suspender);
+
+    let other = engine.compile("let x = 1; x").unwrap();
+    let err = engine.resume_ast(&mut Scope::new(), &other, continuation, &suspender).unwrap_err();
+    assert!(matches!(*err, EvalAltResult::ErrorRuntime(..)));
+
+    #[cfg(not(feature = "no_module"))]
+    {
+        let ast = engine.compile(r#"import "hello" as h; checkpoint(); 42"#);
+
+        if let Ok(ast) = ast {
+            let mut engine = make_engine(&suspender);
+            let mut resolver = rhai::module_resolvers::StaticModuleResolver::new();
+            resolver.insert("hello", rhai::Module::new());
+            engine.set_module_resolver(resolver);
+
+            let err = engine.eval_ast_suspendable(&mut Scope::new(), &ast, &suspender).unwrap_err();
+            assert!(matches!(*err, EvalAltResult::ErrorRuntime(..)));
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_suspend_serde() {
+    let suspender = Suspender::new();
+    let engine = make_engine(&suspender);
+
+    let ast = engine
+        .compile(
+            "
+                let sum = 0;
+                for x in 1..=4 {
+                    sum += x;
+                    checkpoint();
+                }
+                sum
+            ",
+        )
+        .unwrap();
+
+    let mut outcome = engine.eval_ast_suspendable(&mut Scope::new(), &ast, &suspender).unwrap();
+    let mut suspensions = 0;
+
+    while let EvalOutcome::Suspended(continuation) = outcome {
+        suspensions += 1;
+        let json = serde_json::to_string(&continuation).unwrap();
+        let continuation: Continuation = serde_json::from_str(&json).unwrap();
+        outcome = engine.resume_ast(&mut Scope::new(), &ast, continuation, &suspender).unwrap();
+    }
+
+    match outcome {
+        EvalOutcome::Done(sum) => assert_eq!(sum.as_int().unwrap(), 10),
+        EvalOutcome::Suspended(..) => unreachable!(),
+    }
+    assert_eq!(suspensions, 4);
+}
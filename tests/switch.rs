@@ -305,3 +305,61 @@ fn test_switch_ranges() {
         'z'
     );
 }
+
+#[test]
+fn test_switch_patterns() {
+    let mut engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("switch 42 { string => 1, int => 2, _ => 3 }").unwrap(), 2);
+    assert_eq!(engine.eval::<INT>(r#"switch "hello" { int | bool => 1, string => 2, _ => 3 }"#).unwrap(), 2);
+    assert_eq!(engine.eval::<INT>("switch true { 1 | bool => 1, _ => 2 }").unwrap(), 1);
+    assert_eq!(engine.eval::<INT>("let x = 42; switch x { 1 => 1, int if x > 40 => 2, int => 3 }").unwrap(), 2);
+    assert_eq!(engine.eval::<String>(r#"switch 5 { int => "type", 5 => "literal", _ => "default" }"#).unwrap(), "type");
+    assert_eq!(engine.eval::<String>(r#"switch 5 { 5 => "literal", int => "type", _ => "default" }"#).unwrap(), "literal");
+    assert_eq!(engine.eval::<INT>("switch 5 { int if false => 0, 1..10 => 1, int => 2 }").unwrap(), 1);
+    assert_eq!(engine.eval::<INT>("switch 5 { bool => 0, 1..3 => 1, int => 2, _ => 3 }").unwrap(), 2);
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert_eq!(engine.eval::<INT>("switch [1, 2, 3] { [] => 0, [first, ..] => first }").unwrap(), 1);
+        assert_eq!(engine.eval::<INT>("switch [1, 2, 3] { [a, b] => a + b, [a, b, c] => a + b + c }").unwrap(), 6);
+        assert_eq!(engine.eval::<INT>("switch [1, 2, 3] { [2, ..] => 0, [1, ..rest] => rest.len() }").unwrap(), 2);
+        assert_eq!(engine.eval::<INT>("switch [1, 2] { [1, 2] => 1, _ => 2 }").unwrap(), 1);
+        assert_eq!(engine.eval::<INT>(r#"switch [1, "x"] { [int, int] => 1, [x, string] => x, _ => 3 }"#).unwrap(), 1);
+        assert_eq!(engine.eval::<INT>("switch [1, 2] { [x, 1] | [1, x] => x, _ => 0 }").unwrap(), 2);
+        assert_eq!(engine.eval::<INT>("switch 42 { [x, ..] => x, _ => 0 }").unwrap(), 0);
+        assert_eq!(engine.eval::<INT>("let n = 0; switch [1] { [a] => n += a, _ => () } n").unwrap(), 1);
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        let script = r#"
+            let total = 0;
+            for ev in [#{kind: "transfer", amount: 10}, #{kind: "transfer", amount: -5}, #{kind: "mint", amount: 1, to: "x"}, 42] {
+                total += switch ev {
+                    #{kind: "transfer", amount} if amount > 0 => amount,
+                    #{kind: "mint", to: string, ..} => 100,
+                    #{kind, ..} => 1000,
+                    _ => 10000
+                };
+            }
+            total
+        "#;
+        assert_eq!(engine.eval::<INT>(script).unwrap(), 11110);
+
+        assert_eq!(engine.eval::<INT>("switch #{a: 1, b: 2} { #{a: 1} => 1, #{a: 1, ..} => 2, _ => 3 }").unwrap(), 2);
+        assert_eq!(engine.eval::<INT>("switch #{a: 1} { #{a: 1} => 1, _ => 2 }").unwrap(), 1);
+        assert_eq!(engine.eval::<INT>("switch #{a: 1, b: 2} { #{a} => a, #{a, b} => a + b, _ => 0 }").unwrap(), 3);
+        assert_eq!(engine.eval::<INT>("switch #{a: 1} { #{..} => 1, _ => 2 }").unwrap(), 1);
+        assert_eq!(engine.eval::<INT>("let #{a, ..} = #{a: 1, b: 2}; a").unwrap(), 1);
+    }
+
+    assert!(matches!(engine.compile("switch x { [a, ..] | [b] => 1 }").unwrap_err().err_type(), ParseErrorType::MissingSymbol(..)));
+    assert!(matches!(engine.compile("switch x { 1 | [a] => 1 }").unwrap_err().err_type(), ParseErrorType::MissingSymbol(..)));
+    assert!(matches!(engine.compile("switch x { [a, a] => 1 }").unwrap_err().err_type(), ParseErrorType::DuplicatedVariable(..)));
+
+    engine.set_strict_variables(true);
+
+    engine.compile("switch 42 { [a, ..b] if a > 0 => a + b.len(), [x] | [_, x] => x, _ => 0 }").unwrap();
+    assert!(engine.compile("switch 42 { [a] => 1, _ => a }").is_err());
+}
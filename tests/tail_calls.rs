@@ -0,0 +1,213 @@
+#![cfg(not(feature = "no_function"))]
+use rhai::{Engine, EvalAltResult, INT};
+
+#[test]
+fn test_tail_calls() {
+    let mut engine = Engine::new();
+    engine.set_tail_call_optimization(true);
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn count(n, acc) {
+                        if n == 0 { acc } else { count(n - 1, acc + 1) }
+                    }
+                    count(10000, 0)
+                "
+            )
+            .unwrap(),
+        10000
+    );
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn sum(n, acc) {
+                        let next = acc + n;
+                        if n <= 0 { return acc; }
+                        return sum(n - 1, next);
+                    }
+                    sum(1000, 0)
+                "
+            )
+            .unwrap(),
+        500500
+    );
+
+    assert!(engine
+        .eval::<bool>(
+            "
+                fn is_even(n) { if n == 0 { true } else { is_odd(n - 1) } }
+                fn is_odd(n) { if n == 0 { false } else { is_even(n - 1) } }
+                is_even(5000)
+            "
+        )
+        .unwrap());
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn loop_down(n) {
+                        switch n {
+                            0 => 42,
+                            _ => loop_down(n - 1)
+                        }
+                    }
+                    loop_down(1000)
+                "
+            )
+            .unwrap(),
+        42
+    );
+}
+
+#[test]
+fn test_tail_calls_without_optimization() {
+    let mut engine = Engine::new();
+    engine.set_tail_call_optimization(true);
+    #[cfg(not(feature = "no_optimize"))]
+    engine.set_optimization_level(rhai::OptimizationLevel::None);
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn count(n, acc) {
+                        if n == 0 { return acc; }
+                        count(n - 1, acc + 1)
+                    }
+                    count(1000, 0)
+                "
+            )
+            .unwrap(),
+        1000
+    );
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_tail_calls_limits() {
+    let mut engine = Engine::new();
+    engine.set_tail_call_optimization(true);
+
+    // Not in tail position
+    assert!(matches!(
+        *engine.eval::<INT>("fn sum(n) { if n == 0 { 0 } else { n + sum(n - 1) } } sum(1000)").unwrap_err(),
+        EvalAltResult::ErrorInFunctionCall(..) | EvalAltResult::ErrorStackOverflow(..)
+    ));
+
+    // Within a `try` block
+    assert!(engine
+        .eval::<INT>("fn count(n) { try { return if n == 0 { 0 } else { count(n - 1) }; } catch { -1 } } count(1000)")
+        .is_err());
+
+    engine.set_tail_call_optimization(false);
+
+    assert!(engine.eval::<INT>("fn count(n) { if n == 0 { 0 } else { count(n - 1) } } count(1000)").is_err());
+
+    engine.set_tail_call_optimization(true);
+
+    assert_eq!(engine.eval::<INT>("fn count(n) { if n == 0 { 0 } else { count(n - 1) } } count(1000)").unwrap(), 0);
+
+    engine.set_max_operations(500);
+
+    assert!(matches!(*engine.eval::<INT>("fn forever(n) { forever(n + 1) } forever(0)").unwrap_err(), EvalAltResult::ErrorTooManyOperations(..)));
+}
+
+#[test]
+fn test_tail_calls_semantics() {
+    let mut engine = Engine::new();
+
+    // Tail calls are not optimized by default, keeping the full error chain
+    fn chain_depth(mut err: &EvalAltResult) -> usize {
+        let mut depth = 0;
+        while let EvalAltResult::ErrorInFunctionCall(.., inner, _) = err {
+            depth += 1;
+            err = inner;
+        }
+        depth
+    }
+
+    let script = r#"fn f(x) { if x == 0 { throw "boom"; } f(x - 1) } f(3)"#;
+
+    assert!(!engine.tail_call_optimization());
+    assert_eq!(chain_depth(&engine.run(script).unwrap_err()), 4);
+
+    engine.set_tail_call_optimization(true);
+    assert_eq!(chain_depth(&engine.run(script).unwrap_err()), 1);
+
+    // Errors in tail calls can still be caught by the caller
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn fail(n) { throw n; }
+                    fn check(n) { if n > 0 { fail(n) } else { 0 } }
+                    fn guarded(n) { try { return check(n); } catch (e) { return e + 1; } }
+                    guarded(41)
+                "
+            )
+            .unwrap(),
+        42
+    );
+
+    // Return types of replaced functions are still checked
+    assert!(engine
+        .eval::<INT>(
+            r#"
+                fn text(x) { `${x}` }
+                fn num(x) -> int { text(x) }
+                num(42)
+            "#
+        )
+        .is_err());
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn inner(x) -> int { x + 1 }
+                    fn outer(x) -> int { inner(x) }
+                    outer(41)
+                "
+            )
+            .unwrap(),
+        42
+    );
+
+    // Variables of the replaced function are not visible
+    assert!(engine
+        .eval::<INT>(
+            "
+                fn peek() { secret }
+                fn hide() { let secret = 42; peek() }
+                hide()
+            "
+        )
+        .is_err());
+}
+
+#[cfg(feature = "debugging")]
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_tail_calls_debugging() {
+    let mut engine = Engine::new();
+    engine.set_tail_call_optimization(true);
+
+    #[allow(deprecated)]
+    engine.register_debugger(|_, dbg| dbg, |_, _, _, _, _| Ok(rhai::debugger::DebuggerCommand::Continue));
+
+    let script = "
+        fn foo(x) { if x >= 5 { back_trace() } else { foo(x + 1) } }
+        foo(0)
+    ";
+
+    assert_eq!(engine.eval::<rhai::Array>(script).unwrap().len(), 6);
+
+    engine.set_keep_tail_call_frames(false);
+
+    assert_eq!(engine.eval::<rhai::Array>(script).unwrap().len(), 1);
+}
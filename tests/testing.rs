@@ -0,0 +1,290 @@
+#![cfg(not(feature = "no_function"))]
+#![cfg(not(feature = "no_std"))]
+use rhai::testing::{TestKind, TestOutcome, TestRunner};
+use rhai::{Engine, INT};
+
+#[test]
+fn test_testing_assertions() {
+    let mut engine = Engine::new();
+    engine.register_assertions();
+
+    engine
+        .run(
+            r#"
+                assert(true);
+                assert(1 < 2, "math works");
+                assert_eq(1 + 1, 2);
+                assert_eq("x", "x", "strings");
+                assert_ne(1, 2);
+                assert_ne(1, "1");
+                assert_throws(|| throw "oops");
+                assert_throws(|| throw "oops", "oops");
+            "#,
+        )
+        .unwrap();
+
+    let err = engine.run(r#"assert(false, "not true")"#).unwrap_err();
+    assert!(err.to_string().contains("assertion failed: not true"));
+
+    let err = engine.run("assert_eq(1, 2)").unwrap_err().to_string();
+    assert!(err.contains("left: 1"));
+    assert!(err.contains("right: 2"));
+    assert!(!err.contains("diff:"));
+
+    let err = engine.run("assert_ne(42, 42)").unwrap_err();
+    assert!(err.to_string().contains("both: 42"));
+
+    let err = engine.run("assert_throws(|| 42)").unwrap_err();
+    assert!(err.to_string().contains("expected an error, but the call returns 42"));
+
+    let err = engine.run(r#"assert_throws(|| throw "oops", "other")"#).unwrap_err();
+    assert!(err.to_string().contains(r#"expected an error containing "other""#));
+}
+
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_testing_assertions_diff() {
+    let mut engine = Engine::new();
+    engine.register_assertions();
+
+    let err = engine.run(r#"assert_eq(#{ a: 1, b: [1, 2, 3], c: "x" }, #{ a: 1, b: [1, 5], d: true })"#).unwrap_err().to_string();
+
+    let diff: Vec<_> = err.lines().skip_while(|line| !line.contains("diff:")).skip(1).map(|line| line.trim()).collect();
+
+    assert_eq!(diff[0], ".b[1]: 2 != 5");
+    assert_eq!(diff[1], ".b[2]: 3 (left only)");
+    assert_eq!(diff[2], r#".c: "x" (left only)"#);
+    assert!(diff[3].starts_with(".d: true (right only)"));
+}
+
+#[test]
+fn test_testing_runner() {
+    let mut runner = TestRunner::new();
+
+    runner
+        .add_script(
+            "first",
+            "
+                const LIMIT = 10;
+                fn double(x) { x * 2 }
+                fn test_double() { assert_eq(double(21), 42); }
+                fn test_limit() { assert(double(global::LIMIT) < global::LIMIT, \"too large\"); }
+                fn test_with_param(x) { assert(false); }
+                fn helper() { assert(false); }
+            ",
+        )
+        .add_script("second", "fn test_second() { let x = 1; x += 1; assert_eq(x, 2); }")
+        .add_script("broken", "fn test_broken( {");
+
+    let (tests, errors) = runner.discover();
+    let names: Vec<_> = tests.iter().map(|t| t.to_string()).collect();
+    assert_eq!(names, ["first::test_double", "first::test_limit", "second::test_second"]);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, "broken");
+
+    let report = runner.run();
+
+    assert_eq!(report.passed(), 2);
+    assert_eq!(report.failed(), 1);
+    assert_eq!(report.ignored(), 0);
+    assert!(!report.is_success());
+
+    match report.results[1].outcome {
+        TestOutcome::Failed(ref message) => assert!(message.contains("too large"), "{}", message),
+        ref outcome => panic!("{:?}", outcome),
+    }
+
+    // Filters match the full names of tests
+    runner.add_filter("second::").add_filter("double");
+    let report = runner.run();
+
+    let names: Vec<_> = report.results.iter().map(|r| r.case.name.as_str()).collect();
+    assert_eq!(names, ["test_double", "test_second"]);
+}
+
+#[test]
+fn test_testing_runner_isolation() {
+    let mut runner = TestRunner::new();
+    runner.set_engine_factory(|| {
+        let mut engine = Engine::new();
+        engine.register_fn("answer", || 42 as INT);
+        engine
+    });
+
+    // Tests run on multiple threads, each with its own engine from the factory
+    let script = (0..20)
+        .map(|i| format!("fn test_{i}() {{ assert_eq(answer(), 42); assert_eq(global::COUNT, 0); }}"))
+        .chain(std::iter::once("const COUNT = 0;".to_string()))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    runner.add_script("isolation", script).set_threads(4);
+
+    let report = runner.run();
+
+    assert!(report.is_success(), "{:?}", report);
+    assert_eq!(report.passed(), 20);
+
+    // Results are in order of position regardless of threads
+    let names: Vec<_> = report.results.iter().map(|r| r.case.name.clone()).collect();
+    let expected: Vec<_> = (0..20).map(|i| format!("test_{i}")).collect();
+    assert_eq!(names, expected);
+}
+
+#[cfg(feature = "metadata")]
+#[test]
+fn test_testing_attributes() {
+    let mut runner = TestRunner::new();
+
+    runner.add_script(
+        "attributes",
+        "
+            /// #[test]
+            fn checks_something() { assert(true); }
+
+            /// Not run.
+            /// #[ignore]
+            fn test_ignored() { assert(false); }
+
+            /// Not a test.
+            fn something_else() { assert(false); }
+        ",
+    );
+
+    let report = runner.run();
+
+    assert!(report.is_success());
+    assert_eq!(report.passed(), 1);
+    assert_eq!(report.ignored(), 1);
+    assert_eq!(report.results[0].case.name, "checks_something");
+    assert!(report.results[1].case.ignored);
+    assert_eq!(report.results[1].outcome, TestOutcome::Ignored);
+}
+
+#[test]
+fn test_testing_junit() {
+    let mut runner = TestRunner::new();
+
+    runner
+        .add_script(
+            "a<b>",
+            r#"
+                fn test_pass() { }
+                fn test_fail() { assert(false, "x & y"); }
+            "#,
+        )
+        .add_script("bad", "fn test_bad( {");
+
+    let xml = runner.run().to_junit_xml();
+
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites tests=\"2\" failures=\"1\" errors=\"1\" skipped=\"0\""));
+    assert!(xml.contains("<testsuite name=\"a&lt;b&gt;\" tests=\"2\" failures=\"1\" errors=\"0\""));
+    assert!(xml.contains("<testcase name=\"test_pass\" classname=\"a&lt;b&gt;\""));
+    assert!(xml.contains("<failure message=\"Runtime error: assertion failed: x &amp; y"));
+    assert!(xml.contains("<testcase name=\"(compile)\" classname=\"bad\""));
+    assert!(xml.trim_end().ends_with("</testsuites>"));
+}
+
+#[cfg(feature = "metadata")]
+#[test]
+fn test_testing_doc_tests() {
+    let mut runner = TestRunner::new();
+
+    runner
+        .add_script(
+            "doc",
+            r#"
+                /// Double a number.
+                ///
+                /// ```rhai
+                /// assert_eq(double(21), 42);
+                /// ```
+                ///
+                /// ```rhai
+                /// let x = 1;
+                /// assert_eq(double(x), 3);
+                /// ```
+                ///
+                /// ```rhai,ignore
+                /// this is not run
+                /// ```
+                ///
+                /// ```text
+                /// not code
+                /// ```
+                fn double(x) { x * 2 }
+
+                /**
+                 * Get the answer.
+                 *
+                 * ```rhai,no_run
+                 * throw "not run";
+                 * ```
+                 *
+                 * ```rhai
+                 * if answer() != 42 { throw "wrong answer"; }
+                 * ```
+                 */
+                fn answer() { 42 }
+            "#,
+        )
+        .set_doc_tests(true);
+
+    let (tests, ..) = runner.discover();
+    let names: Vec<_> = tests.iter().map(|t| t.to_string()).collect();
+    assert_eq!(names, ["doc::double (line 5)", "doc::double (line 9)", "doc::double (line 14)", "doc::answer (line 26)", "doc::answer (line 30)"]);
+
+    let report = runner.run();
+
+    assert_eq!(report.passed(), 3);
+    assert_eq!(report.failed(), 1);
+    assert_eq!(report.ignored(), 1);
+
+    // Failures are reported at the line in the script
+    match report.results[1].outcome {
+        TestOutcome::Failed(ref message) => assert!(message.contains("(line 10, position 1)"), "{}", message),
+        ref outcome => panic!("{:?}", outcome),
+    }
+}
+
+#[cfg(feature = "metadata")]
+#[test]
+fn test_testing_native_doc_tests() {
+    use rhai::{FuncRegistration, Module};
+
+    let mut runner = TestRunner::new();
+
+    runner.set_engine_factory(|| {
+        let mut engine = Engine::new();
+
+        FuncRegistration::new("triple")
+            .with_comments(["/// Triple a number.\n///\n/// ```rhai\n/// assert_eq(triple(2), 6);\n/// ```"])
+            .register_into_engine(&mut engine, |x: INT| x * 3);
+
+        let mut module = Module::new();
+        FuncRegistration::new("half")
+            .with_comments(["/// Halve a number.\n///\n/// ```rhai\n/// assert_eq(math::half(4), 3);\n/// ```"])
+            .set_into_module(&mut module, |x: INT| x / 2);
+        engine.register_static_module("math", module.into());
+
+        engine
+    });
+
+    runner.set_native_doc_tests(true);
+
+    let report = runner.run();
+    let names: Vec<_> = report.results.iter().map(|r| r.case.to_string()).collect();
+
+    assert_eq!(names, ["(native)::math::half (example 1)", "(native)::triple (example 1)"]);
+    assert_eq!(report.passed(), 1);
+    assert!(matches!(report.results[0].outcome, TestOutcome::Failed(..)));
+    assert_eq!(
+        report.results[0].case.kind,
+        TestKind::DocTest {
+            code: "assert_eq(math::half(4), 3);\n".to_string(),
+            index: 1,
+            no_run: false
+        }
+    );
+}
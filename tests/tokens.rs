@@ -94,3 +94,37 @@ fn test_tokens_unicode_xid_ident() {
     );
     assert!(result.is_err());
 }
+
+#[cfg(not(feature = "no_position"))]
+#[test]
+fn test_tokens_highlight() {
+    use rhai::HighlightKind;
+
+    let mut engine = Engine::new();
+    engine.register_custom_operator("foo", 160).unwrap();
+
+    let script = r#"let x = `a${"b"}c` foo calc(1); /* end */ "oops"#;
+    let spans: Vec<_> = engine.highlight_script(script).into_iter().map(|span| (span.kind, &script[span.range])).collect();
+
+    assert_eq!(
+        spans,
+        [
+            (HighlightKind::Keyword, "let"),
+            (HighlightKind::Identifier, "x"),
+            (HighlightKind::Symbol, "="),
+            (HighlightKind::String, "`a$"),
+            (HighlightKind::Symbol, "{"),
+            (HighlightKind::String, "\"b\""),
+            (HighlightKind::Symbol, "}"),
+            (HighlightKind::String, "c`"),
+            (HighlightKind::Custom, "foo"),
+            (HighlightKind::Function, "calc"),
+            (HighlightKind::Symbol, "("),
+            (HighlightKind::Number, "1"),
+            (HighlightKind::Symbol, ")"),
+            (HighlightKind::Symbol, ";"),
+            (HighlightKind::Comment, "/* end */"),
+            (HighlightKind::Error, "\"oops"),
+        ]
+    );
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "debugging")]
+#![cfg(not(feature = "no_position"))]
+use rhai::debugger::{Trace, TraceRecorder};
+use rhai::{Engine, INT};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+#[test]
+fn test_trace_record() {
+    let mut engine = Engine::new();
+    let recorder = TraceRecorder::new();
+    engine.register_trace_recorder(&recorder);
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let c = counter.clone();
+    engine.register_fn("next_id", move || c.fetch_add(1, Ordering::SeqCst) as INT + 100);
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    fn add(x) { let y = x + next_id(); y }
+                    let a = 1;
+                    let b = add(a);
+                    a = b * 2;
+                    a
+                "
+            )
+            .unwrap(),
+        202
+    );
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+    assert!(!recorder.is_empty());
+
+    let trace = recorder.trace();
+
+    // Native function calls are recorded with their results, script-defined functions are not
+    let calls: Vec<_> = trace.steps.iter().flat_map(|s| s.calls.iter()).filter(|c| c.name == "next_id").collect();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].result, Ok("100".to_string()));
+    assert!(trace.steps.iter().all(|s| s.calls.iter().all(|c| c.name != "add")));
+
+    // Variables are replayed at each step, including inside function calls
+    let step = trace.steps.iter().position(|s| s.node == "let y").unwrap();
+    assert_eq!(trace.steps[step].level, 1);
+    assert_eq!(trace.variables_at(step), [("x".into(), "1".to_string())]);
+
+    let step = trace.steps.iter().position(|s| s.node == "a =").unwrap();
+    assert_eq!(trace.steps[step].level, 0);
+    assert_eq!(trace.variables_at(step), [("a".into(), "1".to_string()), ("b".into(), "101".to_string())]);
+    assert_eq!(trace.variables_at(trace.len() - 1), [("a".into(), "202".to_string()), ("b".into(), "101".to_string())]);
+    assert!(trace.variables_at(trace.len()).is_empty());
+    assert_eq!(trace.steps[trace.len() - 1].node, "end");
+
+    recorder.clear();
+    assert!(recorder.is_empty());
+}
+
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_trace_method_call() {
+    let mut engine = Engine::new();
+    let recorder = TraceRecorder::new();
+    engine.register_trace_recorder(&recorder);
+
+    engine.run("let a = [1]; let n = 2; a.push(n); a.push(n + 1); a.len()").unwrap();
+
+    let trace = recorder.trace();
+
+    // Steps inside method calls keep the variables of the function call
+    let steps: Vec<_> = trace.steps.iter().enumerate().filter(|(_, s)| s.node == ".push()").collect();
+    assert_eq!(steps.len(), 2);
+
+    for (step, _) in steps {
+        let vars = trace.variables_at(step);
+        assert_eq!(vars.len(), 2);
+        assert_eq!(vars[1], ("n".into(), "2".to_string()));
+    }
+
+    assert_eq!(trace.variables_at(trace.len() - 1)[0], ("a".into(), "[1, 2, 3]".to_string()));
+}
+
+#[test]
+fn test_trace_text() {
+    let mut engine = Engine::new();
+    let recorder = TraceRecorder::new();
+    engine.register_trace_recorder(&recorder);
+
+    let mut ast = engine.compile("let s = \"a\\tb\\nc\\\\\";\nlet n = parse_int(s);").unwrap();
+    ast.set_source("text");
+
+    assert!(engine.run_ast(&ast).is_err());
+
+    let trace = recorder.trace();
+    assert!(trace.steps.iter().all(|s| s.source == "text"));
+
+    let error = trace.steps.iter().flat_map(|s| s.calls.iter()).last().unwrap();
+    assert_eq!(error.name, "parse_int");
+    assert!(error.result.is_err());
+
+    let text = trace.to_string();
+    assert!(text.starts_with("#rhai-trace 1\nf\ttext\n"));
+    assert_eq!(text.lines().filter(|line| line.starts_with('f')).count(), 1);
+    assert_eq!(text.parse::<Trace>().unwrap(), trace);
+
+    assert!("".parse::<Trace>().is_err());
+    assert!("#rhai-trace 1\nw\t0\tx\t1".parse::<Trace>().is_err());
+    assert!("#rhai-trace 1\ns\t1\t1\tx\t0\tlet x".parse::<Trace>().is_err());
+    assert_eq!("#rhai-trace 1\n".parse::<Trace>().unwrap(), Trace::new());
+}
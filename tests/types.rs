@@ -1,4 +1,4 @@
-use rhai::{Engine, INT};
+use rhai::{Engine, EvalAltResult, ParseErrorType, INT};
 
 #[test]
 fn test_type_of() {
@@ -47,3 +47,82 @@ fn test_type_of() {
     #[cfg(feature = "only_i32")]
     assert_eq!(engine.eval::<String>("let x = 123; type_of(x)").unwrap(), "i32");
 }
+
+#[test]
+fn test_type_annotations() {
+    let mut engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("let x: int = 42; x").unwrap(), 42);
+    assert_eq!(engine.eval::<String>(r#"const S: string = "hello"; S"#).unwrap(), "hello");
+    assert!(matches!(*engine.run(r#"let x: int = "hello";"#).unwrap_err(), EvalAltResult::ErrorMismatchDataType(ref e, ref a, ..) if e == std::any::type_name::<INT>() && a == "string"));
+    assert!(matches!(*engine.run(r#"let x: int = 1; x = "hello";"#).unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+    #[cfg(not(feature = "no_float"))]
+    assert!(matches!(*engine.run("let x: int = 1; x += 0.5;").unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+    assert_eq!(engine.eval::<INT>(r#"let x: int = 1; x = 2; x += 40; x"#).unwrap(), 42);
+    assert_eq!(engine.eval::<String>(r#"let x: int = 1; let x = "hello"; x = "world"; x"#).unwrap(), "world");
+    assert_eq!(engine.eval::<INT>(r#"let x: int = 1; try { x = "hello"; } catch {} x"#).unwrap(), 1);
+    assert!(matches!(*engine.run(r#"let x: int = 1; eval("x = \"hello\"");"#).unwrap_err(), EvalAltResult::ErrorInFunctionCall(ref f, .., ref err, _) if f == "eval" && matches!(**err, EvalAltResult::ErrorMismatchDataType(..))));
+    assert!(matches!(*engine.run(r#"let x: int = 1; eval(""); x = "hello";"#).unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+    assert!(matches!(engine.compile("let x: int;").unwrap_err().err_type(), ParseErrorType::MissingToken(..)));
+    assert!(matches!(engine.compile("let x: Foo = 1;").unwrap_err().err_type(), ParseErrorType::TypeUndefined(ref t) if t == "Foo"));
+    engine.compile(r#"let x: "my_crate::Foo" = 1;"#).unwrap();
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(engine.eval::<INT>("let a: array = [1, 2, 3]; a.len()").unwrap(), 3);
+    #[cfg(not(feature = "no_object"))]
+    assert!(matches!(*engine.run("let m: map = [1, 2, 3];").unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        assert_eq!(engine.eval::<INT>("fn add(x: int, y) -> int { x + y } add(40, 2)").unwrap(), 42);
+        assert!(matches!(*engine.run(r#"fn add(x: int, y) { x + y } add("40", 2);"#).unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+        assert!(matches!(*engine.run(r#"fn foo(x) -> string { x } foo(42);"#).unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+        engine.run("fn foo() -> () {} foo();").unwrap();
+        assert!(matches!(*engine.run(r#"fn foo() -> bool {} foo();"#).unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+        assert!(matches!(*engine.run(r#"fn foo(x: int) { x = "hello"; } foo(42);"#).unwrap_err(), EvalAltResult::ErrorInFunctionCall(.., ref err, _) if matches!(**err, EvalAltResult::ErrorMismatchDataType(..))));
+        assert!(engine.compile("fn foo(x: 42) {}").is_err());
+        assert_eq!(
+            engine
+                .eval::<INT>(r#"fn apply(f: Fn, x: int) -> int { call(f, x) } fn inc(x) { x + 1 } let f: Fn = Fn("inc"); apply(f, 41)"#)
+                .unwrap(),
+            42
+        );
+        assert!(matches!(*engine.run("fn apply(f: Fn) {} apply(42);").unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+
+        let ast = engine.compile("fn transfer(from: string, to, amount: int) -> bool { true }").unwrap();
+        let f = ast.iter_functions().next().unwrap();
+        assert_eq!(f.param_types, [Some("string"), None, Some(std::any::type_name::<INT>())]);
+        assert_eq!(f.return_type, Some("bool"));
+        assert_eq!(f.to_string(), format!("transfer(from: string, to, amount: {}) -> bool", std::any::type_name::<INT>()));
+    }
+
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_closure"))]
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert!(matches!(*engine.run(r#"let x: int = 1; let f = || { x = "hello"; }; f.call();"#).unwrap_err(), EvalAltResult::ErrorInFunctionCall(.., ref err, _) if matches!(**err, EvalAltResult::ErrorMismatchDataType(..))));
+        assert_eq!(engine.eval::<INT>("let x: int = 1; let f = || { x += 41; }; f.call(); x").unwrap(), 42);
+    }
+
+    #[cfg(feature = "metadata")]
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_module"))]
+    {
+        let ast = engine.compile("fn add(x: int, y) -> int { x + y }").unwrap();
+        let module = rhai::Module::eval_ast_as_new(rhai::Scope::new(), &ast, &engine).unwrap();
+        engine.register_static_module("calc", module.into());
+
+        let (_, defs) = engine.definitions().modules().find(|(name, _)| name == "calc").unwrap();
+        assert!(defs.contains("fn add(x: int, y: ?) -> int;"), "{}", defs);
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        #[derive(Clone)]
+        struct TestStruct;
+
+        engine.register_type_with_name::<TestStruct>("Hello").register_fn("new_ts", || TestStruct);
+        engine.run("let x: Hello = new_ts();").unwrap();
+        assert!(matches!(*engine.run("let x: Hello = 42;").unwrap_err(), EvalAltResult::ErrorMismatchDataType(ref e, ..) if e == "Hello"));
+    }
+}